@@ -3,6 +3,8 @@
 //! 测试 Line Counter 工具在不同文件大小和内容类型下的性能。
 
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use line_counter::mmap::count_file_mmap;
+use line_counter::{CountOptions, count_lines};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use tempfile::NamedTempFile;
@@ -215,6 +217,56 @@ fn bench_buffer_sizes(c: &mut Criterion) {
     });
 }
 
+/// 基准测试：内存映射 + memchr 与逐行读取的对比
+fn bench_mmap(c: &mut Criterion) {
+    let content = (0..100000)
+        .map(|i| {
+            if i % 20 == 0 {
+                String::new()
+            } else {
+                format!("Line {} with some content and unicode: 📊🎉", i)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let temp_file = create_test_file(&content);
+    let file_path = temp_file.path();
+
+    c.bench_function("mmap_large_file_100k_lines", |b| {
+        b.iter(|| {
+            let file = File::open(black_box(file_path)).unwrap();
+            count_file_mmap(&file, &CountOptions::default()).unwrap()
+        })
+    });
+}
+
+/// 基准测试：字节级统计核心与逐行分配 `String` 的对比
+fn bench_bytewise_core(c: &mut Criterion) {
+    let content = (0..100000)
+        .map(|i| {
+            if i % 20 == 0 {
+                String::new()
+            } else {
+                format!("Line {} with some content and unicode: 📊🎉", i)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let temp_file = create_test_file(&content);
+    let file_path = temp_file.path();
+    let options = CountOptions::default();
+
+    c.bench_function("bytewise_large_file_100k_lines", |b| {
+        b.iter(|| {
+            let file = File::open(black_box(file_path)).unwrap();
+            let reader = BufReader::new(file);
+            count_lines(reader, &options).unwrap()
+        })
+    });
+}
+
 criterion_group!(
     benches,
     bench_small_file,
@@ -223,7 +275,9 @@ criterion_group!(
     bench_long_lines_file,
     bench_empty_lines_file,
     bench_unicode_file,
-    bench_buffer_sizes
+    bench_buffer_sizes,
+    bench_mmap,
+    bench_bytewise_core
 );
 
 criterion_main!(benches);
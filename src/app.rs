@@ -0,0 +1,2081 @@
+//! 命令行程序流程
+//!
+//! 负责验证输入文件、调度统计任务，并交由 [`crate::output`] 输出结果。
+//! 多个文件通过 `rayon` 在线程池中并行统计，结果按输入顺序汇总。
+
+use crate::baseline;
+use crate::bench;
+use crate::binary::SNIFF_SIZE;
+use crate::buffer;
+use crate::cache::{self, Changed, ResultCache, Since};
+use crate::cli::{self, Cli, Command, CompareArgs, CountArgs, DiffArgs, GitDiffArgs, OutputFormat};
+use crate::compare;
+use crate::config::{Config, NeverRead};
+use crate::counter::{CountOptions, count_lines_in_ranges_parallel, count_lines_streaming};
+use crate::database;
+use crate::decompress::{self, Compression, DecompressionLimits};
+use crate::diff::{self, StatsDiff};
+use crate::encoding::{self, Detection};
+use crate::engine::{self, Engine};
+use crate::error::LineCounterError;
+use crate::follow;
+use crate::git;
+use crate::headers::HeaderOptions;
+use crate::input::{self, CountingReader, STDIN_DISPLAY_NAME, is_special_file, is_stdin, is_url};
+use crate::language::{self, Language, Languages};
+use crate::locale::Lang;
+use crate::logging;
+use crate::meta::{self, RunMetadata};
+use crate::mmap::{count_file_mmap, count_file_parallel};
+use crate::output::{self, Style};
+use crate::pattern::CaseMode;
+use crate::progress;
+use crate::records::RecordMode;
+use crate::report::{self, FileError, FileReport, Summary};
+use crate::resources::Resources;
+use crate::serve;
+use crate::sink::{
+    self, BadgeSink, HumanSink, JsonSink, MarkdownSink, PlainKvSink, ReportSink, Sections,
+};
+use crate::stats::LineStats;
+use crate::tags;
+use crate::threshold::{self, Thresholds};
+use crate::tui;
+use crate::walk::{self, collect_files};
+use crate::warning;
+use crate::watch::{self, WatchState};
+use crate::words::StopWords;
+use crate::workspace::{self, WorkspaceSummary};
+use anyhow::{Context, Result, bail};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use encoding_rs::{Encoding, UTF_8};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+use tracing::{debug, error, info, trace, warn};
+
+/// 默认的文件大小限制（字节）
+///
+/// 设置为 100MB 以防止处理过大文件导致内存问题，可通过 `--max-size` 修改
+pub const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024; // 100MB
+
+/// 默认的单个 URL 下载大小上限（字节），可通过 `--max-download` 修改
+pub const MAX_DOWNLOAD_SIZE: u64 = 1024 * 1024 * 1024; // 1GB
+
+/// 流式模式下输出中间结果的间隔
+pub const STREAM_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 文件读取选项
+///
+/// 与统计内容无关、只影响如何打开和读取输入的选项。
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    /// 文件较大时是否显示读取进度
+    pub progress: bool,
+    /// 流式模式中间结果的输出风格
+    pub style: Style,
+    /// 标准输入内容使用的伪文件名，用于报告中的路径和按文件名的分类
+    pub stdin_name: Option<PathBuf>,
+    /// 读取缓冲区大小，`None` 表示根据输入自动选择
+    pub buffer_size: Option<usize>,
+    /// 单个文件的大小上限（字节），0 表示不限制
+    pub max_size: u64,
+    /// 单个 URL 下载的大小上限（字节），0 表示不限制
+    pub max_download: u64,
+    /// 流式模式：不限制文件大小，总是缓冲读取，并定期输出中间结果
+    pub stream: bool,
+    /// 输入内容的编码，`None` 表示自动识别
+    pub encoding: Option<&'static Encoding>,
+    /// 不存在的路径报告为跳过，而不是返回错误
+    pub ignore_missing: bool,
+    /// 统计多个文件时，单个文件统计失败不中断其他文件，而是报告为统计失败（没有指定 `--strict` 时）
+    pub keep_going: bool,
+    /// 低内存环境：不使用分块并行引擎，自动选择的缓冲区不超过 [`buffer::MIN_BUFFER_SIZE`]
+    pub low_memory: bool,
+    /// 透明解压 gzip、zstd、bzip2 和 xz 压缩的输入
+    pub decompress: bool,
+    /// 解压缩压缩内容时的安全限制
+    pub decompression: DecompressionLimits,
+    /// 截止时间：之后不再开始统计新的文件，尚未统计的文件报告为超时
+    pub deadline: Option<Instant>,
+    /// 识别语言使用的语言表（包含 `--languages-file` 中的自定义语言）
+    pub languages: Languages,
+    /// 按每个文件语言的注释语法统计注释行和代码行
+    pub comments: bool,
+    /// 按每个文件语言的续行规则统计逻辑行数
+    pub logical: bool,
+    /// 统计模式：如何为每个文件选择记录格式
+    pub mode: RecordMode,
+    /// 为散文类语言的文件统计单词数（`--reading-time`）
+    pub words: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            progress: false,
+            style: Style::default(),
+            stdin_name: None,
+            buffer_size: None,
+            max_size: MAX_FILE_SIZE,
+            max_download: MAX_DOWNLOAD_SIZE,
+            stream: false,
+            encoding: None,
+            ignore_missing: false,
+            keep_going: false,
+            low_memory: false,
+            decompress: true,
+            decompression: DecompressionLimits::default(),
+            deadline: None,
+            languages: Languages::default(),
+            comments: false,
+            logical: false,
+            mode: RecordMode::Auto,
+            words: false,
+        }
+    }
+}
+
+/// 程序入口
+///
+/// 解析命令行参数，读取配置文件，并分派到直接统计或子命令。
+/// 配置中的 `never_read` 规则对所有模式生效。
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功执行
+/// * `Err(anyhow::Error)` - 执行过程中发生错误
+pub fn run() -> Result<()> {
+    let (cli, matches) = parse_args();
+    execute(cli, &matches)
+}
+
+/// 解析命令行参数，参数无效时输出用法并退出
+///
+/// 统计参数不能与子命令同时使用；`--lang`、`--config`、`--verbose` 和 `--quiet` 等全局选项
+/// 可以写在子命令之前或之后。
+///
+/// # 返回值
+///
+/// 解析后的参数和 `clap` 的解析结果（用于判断选项是否在命令行中给出）
+pub fn parse_args() -> (Cli, ArgMatches) {
+    let matches = Cli::command().get_matches();
+    if let Some((name, _)) = matches.subcommand()
+        && let Some(arg) = Cli::command().get_arguments().find(|arg| {
+            !arg.is_global_set()
+                && matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        })
+    {
+        let arg = arg
+            .get_long()
+            .map_or_else(|| arg.get_id().to_string(), |long| format!("--{}", long));
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!("'{}' 不能与子命令 '{}' 同时使用", arg, name),
+            )
+            .exit();
+    }
+    let cli = Cli::from_arg_matches(&matches)
+        .map_err(|e| e.format(&mut Cli::command()))
+        .unwrap_or_else(|e| e.exit());
+    (cli, matches)
+}
+
+/// 按解析后的参数执行
+///
+/// # 参数
+///
+/// * `cli` - 解析后的参数
+/// * `matches` - `clap` 的解析结果
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功执行
+/// * `Err(anyhow::Error)` - 执行过程中发生错误
+pub fn execute(mut cli: Cli, matches: &ArgMatches) -> Result<()> {
+    logging::init(cli.log_level());
+    let lang = cli.lang();
+    let config = Config::load(cli.config.as_deref())?;
+    let never_read = config.never_read.clone();
+
+    match &mut cli.command {
+        None => {
+            cli.count.lang = lang;
+            prepare_count_args(&mut cli.count, &config, matches)?;
+            apply_exit_policy(&cli.count, count_and_report(&cli.count))
+        }
+        Some(Command::Watch(args)) => {
+            let matches = matches
+                .subcommand_matches("watch")
+                .expect("已解析 watch 子命令");
+            args.count.lang = lang;
+            prepare_count_args(&mut args.count, &config, matches)?;
+            watch::run(args)
+        }
+        Some(Command::Diff(args)) => run_diff(args, &never_read, lang),
+        Some(Command::Compare(args)) => run_compare(args, &never_read, lang),
+        Some(Command::GitDiff(args)) => run_git_diff(args, &never_read, lang),
+        Some(Command::Bench(args)) => {
+            never_read.check(&args.file)?;
+            bench::run(args)
+        }
+        Some(Command::Tui(args)) => tui::run(args, &never_read, lang),
+        Some(Command::Serve(args)) => serve::run(args, never_read, lang),
+    }
+}
+
+/// 填入统计参数中来自文件的部分：配置文件中的设置、`--languages-file` 中的自定义语言、
+/// `--stop-words` 中的停用词和 `--files-from` 中的路径
+///
+/// 配置文件中的默认值只用于命令行没有显式给出的选项；`exclude` 与 `--exclude`、`[tags]` 与 `--tag` 合并生效。
+///
+/// # 参数
+///
+/// * `args` - 统计参数
+/// * `config` - 合并后的配置
+/// * `matches` - 统计参数所在命令的解析结果，用于判断选项是否在命令行中给出
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功填入
+/// * `Err(anyhow::Error)` - 自定义语言文件、停用词文件或文件列表无法读取，或自定义语言文件无效
+fn prepare_count_args(args: &mut CountArgs, config: &Config, matches: &ArgMatches) -> Result<()> {
+    let unset = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+    args.never_read = config.never_read.clone();
+    args.exclude.extend(config.exclude.iter().cloned());
+    args.tags.extend(config.tags.iter().cloned());
+    if let Some(case) = config.case
+        && unset("ignore_case")
+        && unset("smart_case")
+        && unset("case_sensitive")
+    {
+        args.ignore_case = case == CaseMode::Insensitive;
+        args.smart_case = case == CaseMode::Smart;
+    }
+    if let Some(format) = config.format
+        && unset("format")
+    {
+        args.format = format;
+    }
+    if let Some(max_size) = config.max_size
+        && unset("max_size")
+    {
+        args.max_size = max_size;
+    }
+    if let Some(max_size) = config.max_decompressed_size
+        && unset("max_decompressed_size")
+    {
+        args.max_decompressed_size = max_size;
+    }
+    let thresholds = &config.thresholds;
+    if unset("max_total_lines") {
+        args.max_total_lines = args.max_total_lines.or(thresholds.max_total_lines);
+    }
+    if unset("max_file_lines") {
+        args.max_file_lines = args.max_file_lines.or(thresholds.max_file_lines);
+    }
+    if unset("max_empty_pct") {
+        args.max_empty_pct = args.max_empty_pct.or(thresholds.max_empty_pct);
+    }
+
+    if let Some(path) = &args.languages_file {
+        args.custom_languages = Languages::load(path)?;
+    }
+    if let Some(path) = &args.stop_words {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("无法读取停用词文件 '{}'", path.display()))?;
+        args.stop_word_list = StopWords::parse(&text);
+    }
+
+    if let Some(list) = &args.files_from {
+        let paths = if is_stdin(list) {
+            if args.paths.iter().any(|path| is_stdin(path)) {
+                bail!("标准输入已用于读取文件列表，不能同时统计标准输入的内容");
+            }
+            input::read_file_list(io::stdin().lock(), args.null)
+        } else {
+            File::open(list).and_then(|file| input::read_file_list(BufReader::new(file), args.null))
+        }
+        .with_context(|| format!("无法读取文件列表 '{}'", list.display()))?;
+        args.paths.extend(paths);
+    }
+    Ok(())
+}
+
+/// 按退出码选项处理统计结果
+///
+/// 指定 `--no-fail` 时，错误（包括检查失败）按与正常退出时相同的格式输出到标准错误，
+/// 然后以零状态退出。
+///
+/// # 参数
+///
+/// * `args` - 统计参数
+/// * `result` - 统计和检查的结果
+fn apply_exit_policy(args: &CountArgs, result: Result<()>) -> Result<()> {
+    match result {
+        Err(e) if args.no_fail => {
+            eprintln!("Error: {}", render_error(&e, args.lang));
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// 按输出语言渲染错误
+///
+/// 中文与 `anyhow` 的 `{:?}` 格式相同；其他语言逐个翻译错误及其原因，
+/// [`LineCounterError`] 使用 [`LineCounterError::localized`]，其余按消息目录翻译。
+///
+/// # 参数
+///
+/// * `error` - 错误
+/// * `lang` - 输出语言
+///
+/// # 返回值
+///
+/// 第一行为错误信息，有原因时随后是 `Caused by:` 和编号的原因列表
+pub fn render_error(error: &anyhow::Error, lang: Lang) -> String {
+    if lang == Lang::Zh {
+        return format!("{:?}", error);
+    }
+    let messages = error_chain(error, lang);
+    let mut out = messages[0].clone();
+    if messages.len() > 1 {
+        out.push_str("\n\nCaused by:");
+        for (index, message) in messages[1..].iter().enumerate() {
+            out.push_str(&format!("\n    {}: {}", index, message));
+        }
+    }
+    out
+}
+
+/// 按输出语言翻译错误及其每一层原因
+///
+/// # 返回值
+///
+/// 从外到内排列的错误信息，至少有一项
+pub fn error_chain(error: &anyhow::Error, lang: Lang) -> Vec<String> {
+    error
+        .chain()
+        .map(|cause| match cause.downcast_ref::<LineCounterError>() {
+            Some(error) => error.localized(lang),
+            None => lang.translate(&cause.to_string()).into_owned(),
+        })
+        .collect()
+}
+
+/// 执行 `diff` 子命令
+///
+/// # 参数
+///
+/// * `args` - `diff` 子命令的参数
+/// * `never_read` - 配置中永远不读取的路径
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功执行
+/// * `Err(anyhow::Error)` - 任意一个输入被配置拒绝读取、无法统计或解析
+fn run_diff(args: &DiffArgs, never_read: &NeverRead, lang: Lang) -> Result<()> {
+    never_read.check(&args.old)?;
+    never_read.check(&args.new)?;
+    let (old, new) = if args.report {
+        (
+            diff::stats_from_report(&args.old)?,
+            diff::stats_from_report(&args.new)?,
+        )
+    } else {
+        let options = CountOptions {
+            headers: HeaderOptions {
+                exclude: args.exclude_headers,
+                ..HeaderOptions::default()
+            },
+            ..CountOptions::default()
+        };
+        (
+            diff::stats_from_file(&args.old, &options)?,
+            diff::stats_from_file(&args.new, &options)?,
+        )
+    };
+
+    let mut stats_diff = StatsDiff::new(old, new);
+    if args.ignore_blank_changes {
+        stats_diff = stats_diff.ignore_blank_changes();
+    }
+    match args.format {
+        OutputFormat::Human => {
+            let style = Style::from_options(args.accessible, args.no_emoji, lang);
+            output::print_diff(&args.old, &args.new, &stats_diff, &style)
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats_diff)?),
+        OutputFormat::Markdown => print!(
+            "{}",
+            output::render_diff_markdown(&args.old, &args.new, &stats_diff)
+        ),
+        OutputFormat::Badge => bail!("--format badge 只适用于统计结果，不适用于 diff"),
+        OutputFormat::PlainKv => bail!("--format plain-kv 只适用于统计结果，不适用于 diff"),
+    }
+
+    Ok(())
+}
+
+/// 执行 `compare` 子命令
+///
+/// # 参数
+///
+/// * `args` - `compare` 子命令的参数
+/// * `never_read` - 配置中永远不读取的路径，目录中匹配的文件不参与比较
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功执行
+/// * `Err(anyhow::Error)` - 任意一个输入不是目录、被配置拒绝读取，或其中的文件无法统计
+fn run_compare(args: &CompareArgs, never_read: &NeverRead, lang: Lang) -> Result<()> {
+    for dir in [&args.old, &args.new] {
+        if !dir.is_dir() {
+            bail!("'{}' 不是目录", dir.display());
+        }
+        never_read.check(dir)?;
+    }
+    let options = CountOptions {
+        headers: HeaderOptions {
+            exclude: args.exclude_headers,
+            ..HeaderOptions::default()
+        },
+        ..CountOptions::default()
+    };
+    let old = compare::count_dir(&args.old, &options, args.jobs, never_read)?;
+    let new = compare::count_dir(&args.new, &options, args.jobs, never_read)?;
+
+    let mut dir_diff =
+        compare::compare(&args.old, &old, &args.new, &new, args.ignore_blank_changes);
+    if !args.detail {
+        dir_diff.files.clear();
+    }
+    match args.format {
+        OutputFormat::Human => {
+            let style = Style::from_options(args.accessible, args.no_emoji, lang);
+            output::print_dir_diff(&args.old, &args.new, &dir_diff, &style)
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&dir_diff)?),
+        OutputFormat::Markdown => print!(
+            "{}",
+            output::render_dir_diff_markdown(&args.old, &args.new, &dir_diff)
+        ),
+        OutputFormat::Badge => bail!("--format badge 只适用于统计结果，不适用于 compare"),
+        OutputFormat::PlainKv => bail!("--format plain-kv 只适用于统计结果，不适用于 compare"),
+    }
+
+    Ok(())
+}
+
+/// 执行 `git-diff` 子命令：统计两个 Git 版本之间的行变化并输出结果
+///
+/// # 参数
+///
+/// * `args` - `git-diff` 子命令的参数
+/// * `never_read` - 配置中永远不读取的路径，作为排除的 pathspec 传给 `git diff`
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功执行
+/// * `Err(anyhow::Error)` - 版本不存在、当前目录不在仓库中或输出失败
+fn run_git_diff(args: &GitDiffArgs, never_read: &NeverRead, lang: Lang) -> Result<()> {
+    let mut pathspecs = args.paths.clone();
+    pathspecs.extend(
+        never_read
+            .patterns()
+            .map(|pattern| PathBuf::from(format!(":(exclude,glob){}", pattern))),
+    );
+    let mut revision_diff = git::diff_revisions(&args.repo, &args.range, &pathspecs)?;
+    if args.summary_only {
+        revision_diff.files.clear();
+    }
+    match args.format {
+        OutputFormat::Human => {
+            let style = Style::from_options(args.accessible, args.no_emoji, lang);
+            output::print_revision_diff(&revision_diff, &style)
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&revision_diff)?),
+        OutputFormat::Markdown => {
+            print!("{}", output::render_revision_diff_markdown(&revision_diff))
+        }
+        OutputFormat::Badge => bail!("--format badge 只适用于统计结果，不适用于 git-diff"),
+        OutputFormat::PlainKv => bail!("--format plain-kv 只适用于统计结果，不适用于 git-diff"),
+    }
+
+    Ok(())
+}
+
+/// 去掉匹配配置中 `never_read` 规则的文件
+///
+/// 命令行直接给出的路径匹配时拒绝统计；遍历目录得到的文件匹配时跳过，并在标准错误中说明。
+///
+/// # 参数
+///
+/// * `files` - 待统计的文件
+/// * `args` - 统计参数
+///
+/// # 返回值
+///
+/// * `Ok(Vec<PathBuf>)` - 可以读取的文件
+/// * `Err(anyhow::Error)` - 命令行直接给出的路径匹配某个规则
+fn exclude_never_read(files: Vec<PathBuf>, args: &CountArgs) -> Result<Vec<PathBuf>> {
+    if args.never_read.is_empty() {
+        return Ok(files);
+    }
+    for path in args.paths.iter().filter(|path| !is_stdin(path)) {
+        args.never_read.check(path)?;
+    }
+    let style = args.stderr_style();
+    Ok(files
+        .into_iter()
+        .filter(|path| match args.never_read.matching(path) {
+            Some(pattern) => {
+                info!(
+                    "{}{}",
+                    style.icon("🚫 "),
+                    style.format(
+                        "跳过 {}：匹配 never_read 规则 '{}'",
+                        &[&path.display(), &pattern]
+                    )
+                );
+                false
+            }
+            None => true,
+        })
+        .collect())
+}
+
+/// 去掉展开目录得到的、匹配 `--exclude` 或配置中 `exclude` 规则的文件
+///
+/// 命令行直接给出的文件总是保留。
+///
+/// # 参数
+///
+/// * `files` - 展开后的文件列表
+/// * `args` - 统计参数
+///
+/// # 返回值
+///
+/// 剩余的文件
+fn exclude_globs(files: Vec<PathBuf>, args: &CountArgs) -> Vec<PathBuf> {
+    if args.exclude.is_empty() {
+        return files;
+    }
+    files
+        .into_iter()
+        .filter(|path| {
+            if args.paths.contains(path) {
+                return true;
+            }
+            let Some(glob) = args
+                .exclude
+                .iter()
+                .find(|glob| glob.matches(path, args.case_mode()))
+            else {
+                return true;
+            };
+            debug!(
+                path = %path.display(),
+                glob = glob.as_str(),
+                "{}",
+                args.lang.tr("跳过匹配 exclude 规则的文件")
+            );
+            false
+        })
+        .collect()
+}
+
+/// 列出将要统计的文件，不读取文件内容
+///
+/// 路径按统计的顺序输出到标准输出，每行一个，可以直接作为 `--files-from` 的输入。
+/// 不存在的文件和超过 `--max-size` 的文件（统计时会报错）在标准错误中标出，
+/// 最后在标准错误中输出文件数和总大小。
+///
+/// # 参数
+///
+/// * `files` - 应用过滤规则后的文件列表
+/// * `args` - 统计参数
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功列出
+/// * `Err(anyhow::Error)` - 写入标准输出失败
+fn list_files(files: &[PathBuf], args: &CountArgs) -> Result<()> {
+    use std::io::Write;
+
+    let style = args.stderr_style();
+    let mut out = io::stdout().lock();
+    let mut total_size = 0;
+    for path in files {
+        writeln!(out, "{}", path.display())?;
+        if is_stdin(path) || is_url(path) {
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(path) else {
+            if !args.ignore_missing {
+                warn!(
+                    "{}{}: {}",
+                    style.icon("⚠️  "),
+                    path.display(),
+                    style.tr("文件不存在")
+                );
+            }
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        total_size += metadata.len();
+        if args.max_size > 0 && metadata.len() > args.max_size && !args.stream {
+            warn!(
+                "{}{}: {}",
+                style.icon("⚠️  "),
+                path.display(),
+                style.format(
+                    "大小 {} bytes 超过 --max-size，统计时会报错",
+                    &[&metadata.len()]
+                )
+            );
+        }
+    }
+    out.flush()?;
+    info!(
+        "{}{}",
+        style.icon("📋 "),
+        style.format(
+            "共 {} 个文件，总大小 {} bytes",
+            &[&files.len(), &total_size]
+        )
+    );
+    Ok(())
+}
+
+/// 统计给定的路径并输出结果
+///
+/// 处理统计参数，验证输入文件，并执行行数统计。
+///
+/// # 参数
+///
+/// * `args` - 统计参数
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功执行
+/// * `Err(anyhow::Error)` - 执行过程中发生错误
+///
+/// # 错误处理
+///
+/// 函数会检查以下错误情况：
+/// - 缺少命令行参数
+/// - 文件不存在
+/// - 文件是目录（未指定 `-r`）
+/// - 文件过大
+/// - 权限不足
+/// - 文件读取错误
+pub fn count_and_report(args: &CountArgs) -> Result<()> {
+    count_and_report_with(args, None)
+}
+
+/// 统计给定的路径并输出结果，可选复用监视模式的状态
+///
+/// 监视模式下，没有发生变化的文件直接使用缓存的统计结果；指定 `--only-changes` 时只输出
+/// 统计结果与上一次不同的文件。后者只影响输出的内容，写入工作区摘要、基线比较和行尾空白检查
+/// 仍基于全部文件。
+///
+/// # 参数
+///
+/// * `args` - 统计参数
+/// * `state` - 监视模式在多次统计之间保留的状态；有变化跟踪器时第一次之后不再输出“正在处理”的提示
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功执行
+/// * `Err(anyhow::Error)` - 执行过程中发生错误
+pub(crate) fn count_and_report_with(
+    args: &CountArgs,
+    mut state: Option<&mut WatchState>,
+) -> Result<()> {
+    let started = SystemTime::now();
+    let deadline = args.deadline.map(|deadline| Instant::now() + deadline);
+    let style = args.style();
+
+    // 验证命令行参数；文件列表为空时统计零个文件
+    if args.paths.is_empty() && args.files_from.is_none() {
+        let program_name = std::env::args().next().unwrap_or_default();
+        cli::print_usage_help(&program_name, &style);
+        return Err(LineCounterError::MissingArgument.into());
+    }
+
+    let files = if args.git {
+        git::tracked_files(&args.paths)?
+    } else {
+        collect_files(&args.paths, args.recursive, args.symlinks())?
+    };
+    let files = exclude_never_read(files, args)?;
+    let files = exclude_globs(files, args);
+    if args.list {
+        return list_files(&files, args);
+    }
+    let options = args.count_options();
+    let resources = Resources::detect();
+    let read_options = ReadOptions {
+        // 进度条在屏幕阅读器中只是不断刷新的噪音
+        progress: !args.no_progress && !args.accessible,
+        style: style.clone(),
+        stdin_name: args.stdin_name.clone(),
+        buffer_size: args.buffer_size.fixed(),
+        max_size: args.max_size,
+        max_download: args.max_download,
+        stream: args.stream,
+        encoding: args.encoding,
+        ignore_missing: args.ignore_missing,
+        keep_going: !args.strict && files.len() > 1,
+        low_memory: resources.is_low_memory(),
+        decompress: !args.no_decompress,
+        decompression: args.decompression_limits(),
+        deadline,
+        languages: args.custom_languages,
+        comments: args.comments,
+        logical: args.logical,
+        mode: args.mode,
+        words: args.reading_time,
+    };
+    let jobs = if args.jobs == 0 {
+        resources.default_jobs()
+    } else {
+        args.jobs
+    };
+
+    let quiet = state
+        .as_ref()
+        .and_then(|state| state.changes.as_ref())
+        .is_some_and(|tracker| tracker.has_previous());
+    if resources.is_low_memory() && !quiet {
+        warn!(
+            "{}{}",
+            style.icon("⚠️  "),
+            style.format(
+                "可用内存较少（{} MB），改为顺序统计并使用较小的读取缓冲区",
+                &[&(resources.available_memory.unwrap_or_default() / (1024 * 1024))]
+            )
+        );
+    }
+    if args.format == OutputFormat::Human && !quiet {
+        let style = args.stderr_style();
+        let message = match files.as_slice() {
+            [file_path] => format!(
+                "{}{}: {}",
+                style.icon("📊 "),
+                style.tr("正在处理文件"),
+                file_path.display()
+            ),
+            _ => format!(
+                "{}{}",
+                style.icon("📊 "),
+                style.format("正在处理 {} 个文件", &[&files.len()])
+            ),
+        };
+        info!("{}", message);
+    }
+
+    // 监视模式使用自己的内存缓存
+    let mut cache = cache::cache_dir()
+        .filter(|_| state.is_none() && !args.no_cache && !args.follow)
+        .map(|dir| ResultCache::open(&dir, &cache_key(args)));
+    let mut reports = match (state.as_deref_mut(), cache.as_mut()) {
+        _ if args.follow => vec![follow::count(&files, &options, &read_options)?],
+        (Some(state), _) => state.cache.count(&files, jobs, &options, &read_options)?,
+        (None, Some(cache)) => cache.count(
+            &files,
+            &change_detection(args)?,
+            jobs,
+            &options,
+            &read_options,
+        )?,
+        (None, None) => count_files(&files, jobs, &options, &read_options)?,
+    };
+    // 缓存只影响速度，无法写入时只给出警告
+    if let Some(Err(e)) = cache.as_mut().map(ResultCache::save) {
+        let style = args.stderr_style();
+        warn!(
+            "{}{}",
+            style.icon("⚠️  "),
+            error_chain(&e, style.lang).join(": ")
+        );
+    }
+    for report in &mut reports {
+        report.symlink_target = walk::symlink_target(&report.path);
+    }
+    if args.warnings {
+        for report in &mut reports {
+            report.warnings = warning::check(&report.stats);
+        }
+    }
+    if !args.tags.is_empty() {
+        for report in &mut reports {
+            report.tags = tags::tags_for(&args.tags, &report.path, args.case_mode());
+        }
+    }
+    if args.reading_time {
+        for report in &mut reports {
+            report.reading_minutes = report
+                .stats
+                .words
+                .map(|words| report::reading_minutes(words, args.wpm));
+        }
+    }
+    match args.sort {
+        Some(key) => report::sort_reports(&mut reports, key, args.reverse),
+        None if args.reverse => reports.reverse(),
+        None => {}
+    }
+    let finished = SystemTime::now();
+    let elapsed = finished.duration_since(started).unwrap_or_default();
+    let meta = || RunMetadata::new(std::env::args().collect(), args, started, finished);
+
+    let tracker = state.and_then(|state| state.changes.as_mut());
+    let only_changes = tracker.is_some();
+    let changed: Cow<[FileReport]> = match tracker {
+        Some(tracker) => Cow::Owned(tracker.update(&reports)),
+        None => Cow::Borrowed(&reports),
+    };
+    if !changed.is_empty() || !only_changes {
+        let sections = Sections {
+            languages: args.languages,
+            grouping: args.grouping(),
+            authors: if args.by_author {
+                Some(blame_authors(&changed)?)
+            } else {
+                None
+            },
+        };
+        // 写入文件的报告先在内存中渲染，完成后再一次写入
+        let mut buffer = Vec::new();
+        let mut stdout = io::stdout();
+        let writer: &mut dyn io::Write = match args.output {
+            Some(_) => &mut buffer,
+            None => &mut stdout,
+        };
+        let style = args.report_style();
+        let mut sink: Box<dyn ReportSink> = match args.format {
+            OutputFormat::Human => {
+                Box::new(HumanSink::new(writer, style, args.top_lines, sections))
+            }
+            OutputFormat::Json => Box::new(JsonSink::new(writer, meta(), sections)),
+            OutputFormat::Markdown => Box::new(MarkdownSink::new(
+                writer,
+                sections,
+                args.precision.unwrap_or(output::DEFAULT_PRECISION),
+            )),
+            OutputFormat::Badge => Box::new(BadgeSink::new(writer)),
+            OutputFormat::PlainKv => Box::new(PlainKvSink::new(
+                writer,
+                args.precision.unwrap_or(output::PLAIN_KV_PRECISION),
+            )),
+        };
+        sink::write_reports(sink.as_mut(), &changed, elapsed)?;
+        drop(sink);
+        if let Some(path) = &args.output {
+            write_output(path, &buffer)
+                .with_context(|| format!("无法写入输出文件 '{}'", path.display()))?;
+        }
+
+        output::print_warnings(&changed, &args.stderr_style());
+    }
+    // 输出到终端的可读报告已逐个列出统计失败的文件及原因，标准错误中不再重复
+    let listed: &[FileReport] = if args.format == OutputFormat::Human && args.output.is_none() {
+        &changed
+    } else {
+        &[]
+    };
+    let failed = print_failures(&reports, listed, &args.stderr_style());
+
+    if args.emit_summary {
+        emit_summary(args, &meta(), &reports)?;
+    }
+
+    if let Some(db_path) = &args.output_db {
+        write_database(db_path, args, &meta(), &reports)?;
+    }
+
+    if let Some(baseline_path) = &args.baseline {
+        check_baseline(baseline_path, args, &reports)?;
+    }
+
+    let thresholds = args.thresholds();
+    if !thresholds.is_empty() {
+        check_thresholds(&thresholds, args, &reports)?;
+    }
+
+    if args.fail_on_trailing_whitespace {
+        let lines = Summary::from_reports(&reports)
+            .stats
+            .trailing_whitespace_lines;
+        if lines > 0 {
+            return Err(LineCounterError::TrailingWhitespace { lines }.into());
+        }
+    }
+
+    if args.fail_on_warning {
+        let count = Summary::from_reports(&reports).warnings;
+        if count > 0 {
+            return Err(LineCounterError::WarningsFound { count }.into());
+        }
+    }
+
+    if failed > 0 {
+        return Err(LineCounterError::FilesFailed { count: failed }.into());
+    }
+
+    Ok(())
+}
+
+/// 在标准错误中汇总统计失败的文件及原因
+///
+/// # 参数
+///
+/// * `reports` - 本次统计的报告
+/// * `listed` - 已在报告中列出失败原因的文件，不再重复输出
+/// * `style` - 输出风格
+///
+/// # 返回值
+///
+/// 统计失败的文件数
+fn print_failures(reports: &[FileReport], listed: &[FileReport], style: &Style) -> usize {
+    let mut failed = 0;
+    for report in reports {
+        let Some(error) = &report.error else {
+            continue;
+        };
+        failed += 1;
+        if !listed.iter().any(|other| other.path == report.path) {
+            error!("{}{}: {}", style.icon("❌ "), report.path.display(), error);
+        }
+    }
+    failed
+}
+
+/// 按 `--changed-only` 和 `--since` 确定判断文件是否发生变化的依据
+///
+/// # 参数
+///
+/// * `args` - 统计参数
+///
+/// # 返回值
+///
+/// * `Ok(Changed)` - 判断依据；参照点为 Git 版本时，包含每个输入路径所在仓库中发生变化的文件
+/// * `Err(anyhow::Error)` - 输入路径不在 Git 仓库中或版本不存在
+fn change_detection(args: &CountArgs) -> Result<Changed> {
+    if !args.changed_only {
+        return Ok(Changed::Stamp);
+    }
+    let rev = match &args.since {
+        None => return Ok(Changed::SinceCounted),
+        Some(Since::Time(time)) => return Ok(Changed::ModifiedAfter(*time)),
+        Some(Since::Rev(rev)) => rev,
+    };
+    let mut changed = HashSet::new();
+    for path in args
+        .paths
+        .iter()
+        .filter(|path| !is_stdin(path) && path.exists())
+    {
+        let dir = if path.is_dir() {
+            path.as_path()
+        } else {
+            match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            }
+        };
+        changed.extend(
+            git::changed_files(dir, rev)?
+                .into_iter()
+                .filter_map(|file| file.canonicalize().ok()),
+        );
+    }
+    Ok(Changed::Paths(changed))
+}
+
+/// 统计结果缓存的键：报告中的统计参数，加上不出现在报告中但影响统计结果的读取选项，
+/// 以及 `--languages-file` 和 `--stop-words` 的内容的哈希（修改这两个文件后不会读到旧的结果）
+fn cache_key(args: &CountArgs) -> impl Serialize + '_ {
+    let contents = |path: &Option<PathBuf>| {
+        path.as_ref()
+            .map(|path| meta::config_hash(&fs::read_to_string(path).ok()))
+    };
+    (
+        args,
+        args.encoding.map(|encoding| encoding.name()),
+        args.stream,
+        args.max_size,
+        args.max_decompressed_size,
+        args.max_compression_ratio,
+        contents(&args.languages_file),
+        contents(&args.stop_words),
+    )
+}
+
+/// 按最后修改的作者统计已统计文件中的行数
+///
+/// 被跳过的文件、标准输入和压缩文件不参与统计。
+///
+/// # 参数
+///
+/// * `reports` - 本次统计的报告
+///
+/// # 返回值
+///
+/// * `Ok(Vec<AuthorShare>)` - 按行数从多到少排列的作者
+/// * `Err(anyhow::Error)` - 任意一个文件无法执行 `git blame`
+fn blame_authors(reports: &[FileReport]) -> Result<Vec<git::AuthorShare>> {
+    let paths: Vec<&Path> = reports
+        .iter()
+        .filter(|report| {
+            !report.is_skipped() && !is_stdin(&report.path) && report.compression.is_none()
+        })
+        .map(|report| report.path.as_path())
+        .collect();
+    Ok(git::authors(&paths)?)
+}
+
+/// 在工作区根目录写入摘要文件
+///
+/// # 参数
+///
+/// * `args` - 统计参数（第一个输入路径决定工作区根目录）
+/// * `meta` - 运行元数据
+/// * `reports` - 本次统计的报告
+///
+/// # 返回值
+///
+/// * `Ok(())` - 写入成功
+/// * `Err(anyhow::Error)` - 无法确定当前目录或写入失败
+fn emit_summary(args: &CountArgs, meta: &RunMetadata, reports: &[FileReport]) -> Result<()> {
+    let start = match args.paths.iter().find(|path| !is_stdin(path)) {
+        Some(path) => path.clone(),
+        None => std::env::current_dir().context("无法获取当前目录")?,
+    };
+    let root = workspace::find_root(&start);
+    let summary = WorkspaceSummary::new(meta, &Summary::from_reports(reports));
+    let path = workspace::write_summary(&root, &summary)
+        .with_context(|| format!("无法写入工作区摘要到 '{}'", root.display()))?;
+
+    let style = args.stderr_style();
+    let message = format!(
+        "{}{}: {}",
+        style.icon("💾 "),
+        style.tr("已写入工作区摘要"),
+        path.display()
+    );
+    info!("{}", message);
+    Ok(())
+}
+
+/// 把报告写入 `--output` 指定的文件
+///
+/// 先写入同一目录下的临时文件再重命名，读取该文件的其他程序只会看到完整的报告；
+/// 写入失败时删除临时文件，原有的报告保持不变。
+///
+/// # 参数
+///
+/// * `path` - 输出文件路径
+/// * `contents` - 渲染好的报告
+///
+/// # 返回值
+///
+/// * `Ok(())` - 写入成功
+/// * `Err(io::Error)` - 无法写入临时文件或重命名失败
+fn write_output(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    let result = fs::write(&temp_path, contents).and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// 把本次运行的结果追加写入 SQLite 数据库
+///
+/// # 参数
+///
+/// * `db_path` - 数据库文件路径
+/// * `args` - 统计参数（提供输出格式）
+/// * `meta` - 运行元数据
+/// * `reports` - 本次统计的报告
+///
+/// # 返回值
+///
+/// * `Ok(())` - 写入成功
+/// * `Err(anyhow::Error)` - 无法打开或写入数据库
+fn write_database(
+    db_path: &Path,
+    args: &CountArgs,
+    meta: &RunMetadata,
+    reports: &[FileReport],
+) -> Result<()> {
+    let mut connection = database::open(db_path)
+        .with_context(|| format!("无法打开数据库 '{}'", db_path.display()))?;
+    let run_id = database::write_run(&mut connection, meta, reports)
+        .with_context(|| format!("无法写入数据库 '{}'", db_path.display()))?;
+
+    let style = args.stderr_style();
+    let message = format!(
+        "{}{}",
+        style.icon("🗄️  "),
+        style.format(
+            "已写入数据库: {}（运行编号 {}，{} 个文件）",
+            &[&db_path.display(), &run_id, &reports.len()]
+        )
+    );
+    info!("{}", message);
+    Ok(())
+}
+
+/// 与基线报告比较，增长超过容差时返回错误
+///
+/// # 参数
+///
+/// * `baseline_path` - 基线报告路径
+/// * `args` - 统计参数（提供容差和输出格式）
+/// * `reports` - 本次统计的报告
+///
+/// # 返回值
+///
+/// * `Ok(())` - 所有指标都在容差内
+/// * `Err(anyhow::Error)` - 基线无法读取，或有指标超过容差
+fn check_baseline(baseline_path: &Path, args: &CountArgs, reports: &[FileReport]) -> Result<()> {
+    let baseline_summary = diff::summary_from_report(baseline_path)?;
+    let current = Summary::from_reports(reports);
+    let mut checks = baseline::compare(&baseline_summary.stats, &current.stats, args.tolerance);
+    checks.extend(baseline::compare_tags(
+        &baseline_summary.tags,
+        &current.tags,
+        args.tolerance,
+    ));
+
+    for line in output::format_baseline(baseline_path, &checks, args.tolerance, &args.style()) {
+        match args.format {
+            OutputFormat::Human if args.output.is_none() => println!("{}", line),
+            OutputFormat::Human
+            | OutputFormat::Json
+            | OutputFormat::Markdown
+            | OutputFormat::Badge
+            | OutputFormat::PlainKv => {
+                eprintln!("{}", line)
+            }
+        }
+    }
+
+    let exceeded = checks.iter().filter(|check| check.exceeded).count();
+    if exceeded > 0 {
+        return Err(LineCounterError::BaselineExceeded { count: exceeded }.into());
+    }
+    Ok(())
+}
+
+/// 检查统计结果是否超过阈值，超过时返回错误
+///
+/// # 参数
+///
+/// * `thresholds` - 阈值设置
+/// * `args` - 统计参数（提供输出格式和风格）
+/// * `reports` - 本次统计的报告
+///
+/// # 返回值
+///
+/// * `Ok(())` - 没有超过任何阈值
+/// * `Err(anyhow::Error)` - 有项目超过阈值
+fn check_thresholds(
+    thresholds: &Thresholds,
+    args: &CountArgs,
+    reports: &[FileReport],
+) -> Result<()> {
+    let violations = threshold::evaluate(thresholds, reports);
+    let style = match args.format {
+        OutputFormat::Human if args.output.is_none() => args.style(),
+        OutputFormat::Human
+        | OutputFormat::Json
+        | OutputFormat::Markdown
+        | OutputFormat::Badge
+        | OutputFormat::PlainKv => args.stderr_style(),
+    };
+    for line in output::format_violations(&violations, &style) {
+        match args.format {
+            OutputFormat::Human if args.output.is_none() => println!("{}", line),
+            OutputFormat::Human
+            | OutputFormat::Json
+            | OutputFormat::Markdown
+            | OutputFormat::Badge
+            | OutputFormat::PlainKv => {
+                eprintln!("{}", line)
+            }
+        }
+    }
+
+    if !violations.is_empty() {
+        return Err(LineCounterError::ThresholdExceeded {
+            count: violations.len(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// 并行统计多个文件
+///
+/// 使用 `rayon` 线程池在多个 CPU 核心上统计文件，
+/// 返回的报告顺序与输入文件顺序一致，保证输出确定。
+///
+/// # 参数
+///
+/// * `files` - 待统计的文件列表
+/// * `jobs` - 线程数，0 表示使用 `rayon` 默认值（CPU 核心数）
+/// * `options` - 统计选项
+/// * `read_options` - 文件读取选项；单个大文件按字节显示进度，多个文件按文件数显示进度
+///
+/// # 返回值
+///
+/// * `Ok(Vec<FileReport>)` - 按输入顺序排列的统计报告
+/// * `Err(anyhow::Error)` - 任意文件统计失败；[`ReadOptions::keep_going`] 时失败的文件改为记录在报告中
+pub fn count_files(
+    files: &[PathBuf],
+    jobs: usize,
+    options: &CountOptions,
+    read_options: &ReadOptions,
+) -> Result<Vec<FileReport>> {
+    if let [file_path] = files {
+        // 单个文件的分块并行引擎同样遵守线程数（例如容器的 CPU 配额）
+        if jobs != 0 && jobs != rayon::current_num_threads() {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()
+                .context("无法创建线程池")?;
+            return Ok(vec![pool.install(|| {
+                count_file_or_failure(file_path, options, read_options)
+            })?]);
+        }
+        return Ok(vec![count_file_or_failure(
+            file_path,
+            options,
+            read_options,
+        )?]);
+    }
+
+    // 流式模式按顺序统计，使中间结果不交错
+    if read_options.stream {
+        return files
+            .iter()
+            .map(|path| count_file_or_failure(path, options, read_options))
+            .collect();
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .context("无法创建线程池")?;
+
+    let bar = progress::file_progress(files.len(), read_options.progress, read_options.style.lang);
+    let per_file_options = ReadOptions {
+        progress: false,
+        ..read_options.clone()
+    };
+    let reports = pool.install(|| {
+        files
+            .par_iter()
+            .map(|path| {
+                let report = count_file_or_failure(path, options, &per_file_options);
+                bar.inc(1);
+                report
+            })
+            .collect()
+    });
+    bar.finish_and_clear();
+
+    reports
+}
+
+/// 统计单个文件，允许继续时（[`ReadOptions::keep_going`]）把失败记录为统计失败的报告
+///
+/// # 参数
+///
+/// * `file_path` - 文件路径
+/// * `options` - 统计选项
+/// * `read_options` - 文件读取选项
+///
+/// # 返回值
+///
+/// * `Ok(FileReport)` - 文件统计报告，或允许继续时统计失败的报告
+/// * `Err(anyhow::Error)` - 不允许继续时文件验证或读取失败
+fn count_file_or_failure(
+    file_path: &Path,
+    options: &CountOptions,
+    read_options: &ReadOptions,
+) -> Result<FileReport> {
+    match count_file(file_path, options, read_options) {
+        Err(e) if read_options.keep_going => {
+            let error = FileError::new(e, read_options.style.lang);
+            Ok(FileReport::failed(file_path.to_path_buf(), error))
+        }
+        result => result,
+    }
+}
+
+/// 验证并统计单个文件
+///
+/// # 参数
+///
+/// * `file_path` - 文件路径
+/// * `options` - 统计选项
+/// * `read_options` - 文件读取选项；只有缓冲读取引擎显示进度
+///
+/// # 返回值
+///
+/// * `Ok(FileReport)` - 文件统计报告
+/// * `Err(anyhow::Error)` - 文件验证或读取失败
+pub fn count_file(
+    file_path: &Path,
+    options: &CountOptions,
+    read_options: &ReadOptions,
+) -> Result<FileReport> {
+    let started = Instant::now();
+    let style = &read_options.style;
+    // 到达截止时间后不再开始统计新的文件；正在统计的文件会完成
+    if read_options
+        .deadline
+        .is_some_and(|deadline| started >= deadline)
+    {
+        debug!(path = %file_path.display(), "{}", style.tr("已到达截止时间，跳过文件"));
+        return Ok(FileReport::timed_out(file_path.to_path_buf()));
+    }
+
+    if is_stdin(file_path) {
+        return count_stdin(options, read_options);
+    }
+
+    if is_url(file_path) {
+        #[cfg(feature = "http")]
+        return crate::download::count(file_path, options, read_options);
+        #[cfg(not(feature = "http"))]
+        bail!("统计 URL 需要以 http 特性构建: {}", file_path.display());
+    }
+
+    let file_path_str = file_path.display().to_string();
+
+    // 验证文件存在性
+    if read_options.ignore_missing && !file_path.exists() {
+        debug!(path = %file_path.display(), "{}", style.tr("跳过不存在的文件"));
+        return Ok(FileReport::missing(file_path.to_path_buf()));
+    }
+    validate_file_exists(file_path, &file_path_str)?;
+
+    // 验证不是目录
+    validate_not_directory(file_path, &file_path_str)?;
+
+    // 命名管道、字符设备等没有可用的大小，也不能回退或内存映射，与标准输入一样流式统计
+    if is_special_file(file_path) {
+        let file = open_file_with_error_handling(file_path, &file_path_str)?;
+        return count_reader(file, file_path.to_path_buf(), options, read_options)
+            .with_context(|| format!("统计文件 '{}' 时发生错误", file_path.display()));
+    }
+
+    // 检查文件大小
+    let max_size = if read_options.stream {
+        0
+    } else {
+        read_options.max_size
+    };
+    let metadata = validate_file_size(file_path, &file_path_str, max_size)?;
+
+    let mut file = open_file_with_error_handling(file_path, &file_path_str)?;
+
+    let sample = read_sample(&mut file)?;
+    if read_options.decompress
+        && let Some(compression) = Compression::detect(&sample)
+    {
+        let report = count_compressed_file(
+            file,
+            file_path,
+            &metadata,
+            compression,
+            options,
+            read_options,
+        )?;
+        debug!(
+            path = %file_path.display(),
+            compression = ?compression,
+            lines = report.stats.total_lines,
+            elapsed = ?started.elapsed(),
+            "{}",
+            style.tr("统计完成")
+        );
+        return Ok(FileReport {
+            unstable: modified_during_count(file_path, &metadata, style),
+            ..report
+        });
+    }
+
+    // 识别编码，跳过二进制文件
+    let Some(encoding) = resolve_encoding(&sample, options, read_options) else {
+        debug!(path = %file_path.display(), "{}", style.tr("跳过二进制文件"));
+        return Ok(FileReport {
+            path: file_path.to_path_buf(),
+            size: metadata.len(),
+            stats: LineStats::default(),
+            binary: true,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: None,
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: None,
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        });
+    };
+
+    let bom = encoding::has_bom(&sample);
+    let language = read_options
+        .languages
+        .detect_with_shebang(file_path, language::from_shebang(&sample));
+    let options = &file_options(options, language, read_options);
+
+    // 统计行数：按文件大小等条件选择引擎；非 UTF-8 内容需要转码，按原始字节统计逐行读取，都只能缓冲读取
+    let cores = if read_options.low_memory {
+        1
+    } else {
+        rayon::current_num_threads()
+    };
+    let requested = if read_options.stream || encoding != UTF_8 || options.bytes {
+        Engine::Buffered
+    } else {
+        options.engine
+    };
+    let engine = engine::select(requested, metadata.len(), cores, options, || {
+        engine::filesystem_kind(file_path)
+    });
+    trace!(
+        path = %file_path.display(),
+        encoding = encoding.name(),
+        language = language.map(|language| language.name),
+        engine = ?engine,
+        buffer_size = buffer_size(Some(&metadata), read_options),
+        "{}",
+        style.tr("开始统计")
+    );
+    let stats = match engine {
+        Engine::Mmap => count_file_mmap(&file, options),
+        Engine::Parallel => count_file_parallel(&file, options, cores),
+        Engine::ParallelRead => count_lines_in_ranges_parallel(
+            &file,
+            metadata.len(),
+            options,
+            cores,
+            buffer_size(Some(&metadata), read_options),
+        ),
+        Engine::Auto | Engine::Buffered => {
+            let bar = progress::byte_progress(
+                metadata.len(),
+                read_options.progress && !read_options.stream,
+                read_options.style.lang,
+            );
+            let buffer_size = buffer_size(Some(&metadata), read_options);
+            let reader: Box<dyn Read> = if encoding == UTF_8 {
+                Box::new(bar.wrap_read(file))
+            } else {
+                Box::new(encoding::transcode(bar.wrap_read(file), encoding))
+            };
+            let mut reporter = StreamReporter::new(file_path, read_options);
+            let stats = count_lines_streaming(
+                BufReader::with_capacity(buffer_size, reader),
+                options,
+                |stats, bytes| reporter.report(stats, bytes),
+            );
+            bar.finish_and_clear();
+            stats
+        }
+    }
+    .with_context(|| format!("统计文件 '{}' 时发生错误", file_path.display()))?;
+    debug!(
+        path = %file_path.display(),
+        engine = ?engine,
+        lines = stats.total_lines,
+        elapsed = ?started.elapsed(),
+        "{}",
+        style.tr("统计完成")
+    );
+
+    Ok(FileReport {
+        path: file_path.to_path_buf(),
+        size: metadata.len(),
+        stats,
+        binary: false,
+        missing: false,
+        limit_exceeded: None,
+        timed_out: false,
+        encoding: (!options.bytes).then(|| encoding.name()),
+        bom,
+        compression: None,
+        symlink_target: None,
+        language: language.map(|language| language.name),
+        warnings: Vec::new(),
+        tags: Vec::new(),
+        reading_minutes: None,
+        unstable: modified_during_count(file_path, &metadata, style),
+        error: None,
+    })
+}
+
+/// 检查文件在统计过程中是否被修改
+///
+/// 重新读取元数据，与开始统计前的大小和修改时间比较；被修改时（如仍在写入的日志）向标准错误输出警告。
+/// 统计过程中被删除的文件仍通过已打开的句柄完整读取，不算作被修改。
+///
+/// # 参数
+///
+/// * `file_path` - 文件路径
+/// * `before` - 开始统计前的元数据
+/// * `style` - 警告的输出风格
+///
+/// # 返回值
+///
+/// 文件是否在统计过程中被修改
+fn modified_during_count(file_path: &Path, before: &fs::Metadata, style: &Style) -> bool {
+    let Ok(after) = fs::metadata(file_path) else {
+        return false;
+    };
+    if after.len() == before.len() && after.modified().ok() == before.modified().ok() {
+        return false;
+    }
+    warn!(
+        "{}{}: {}",
+        style.icon("⚠️  "),
+        file_path.display(),
+        style.format(
+            "文件在统计过程中被修改（{} → {} bytes），结果可能不准确",
+            &[&before.len(), &after.len()]
+        )
+    );
+    true
+}
+
+/// 统计压缩文件解压后的内容
+///
+/// 压缩内容总是缓冲读取，进度按已读取的压缩字节数显示。
+///
+/// # 参数
+///
+/// * `file` - 已打开、位于开头的压缩文件
+/// * `file_path` - 文件路径
+/// * `metadata` - 文件元数据
+/// * `compression` - 压缩格式
+/// * `options` - 统计选项
+/// * `read_options` - 文件读取选项（提供解压缩限制）
+///
+/// # 返回值
+///
+/// * `Ok(FileReport)` - 文件统计报告；超过解压缩限制时报告为跳过
+/// * `Err(anyhow::Error)` - 压缩数据损坏或读取失败
+fn count_compressed_file(
+    file: File,
+    file_path: &Path,
+    metadata: &fs::Metadata,
+    compression: Compression,
+    options: &CountOptions,
+    read_options: &ReadOptions,
+) -> Result<FileReport> {
+    let bar = progress::byte_progress(
+        metadata.len(),
+        read_options.progress && !read_options.stream,
+        read_options.style.lang,
+    );
+    let decoder = compression
+        .decoder(bar.wrap_read(file), read_options.decompression)
+        .with_context(|| format!("无法解压文件 '{}'", file_path.display()))?;
+    let buffer_size = buffer_size(Some(metadata), read_options);
+    let reader = BufReader::with_capacity(buffer_size, decoder);
+    let inner_path = decompress::inner_path(file_path, compression);
+    let result = count_text(
+        reader,
+        buffer_size,
+        file_path,
+        &inner_path,
+        options,
+        read_options,
+    )
+    .with_context(|| format!("统计文件 '{}' 时发生错误", file_path.display()));
+    bar.finish_and_clear();
+
+    compressed_report(file_path.to_path_buf(), metadata.len(), compression, result)
+}
+
+/// 统计标准输入的内容
+///
+/// 标准输入没有预先可知的大小，因此不做大小检查；
+/// 报告中的大小为实际读取的字节数。压缩的标准输入（如 `cat app.log.gz | line-counter -`）同样会被解压。
+fn count_stdin(options: &CountOptions, read_options: &ReadOptions) -> Result<FileReport> {
+    let path = read_options
+        .stdin_name
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(STDIN_DISPLAY_NAME));
+    count_reader(std::io::stdin().lock(), path, options, read_options)
+        .context("统计标准输入时发生错误")
+}
+
+/// 统计无法回退的输入（标准输入、命名管道等特殊文件、HTTP 请求的内容）
+///
+/// 报告中的大小为实际读取的字节数；压缩的内容同样会被解压。
+///
+/// # 参数
+///
+/// * `reader` - 输入
+/// * `path` - 报告中的路径，同时用于识别语言
+/// * `options` - 统计选项
+/// * `read_options` - 读取选项
+///
+/// # 返回值
+///
+/// * `Ok(FileReport)` - 统计报告
+/// * `Err(anyhow::Error)` - 读取失败
+pub(crate) fn count_reader(
+    reader: impl Read,
+    path: PathBuf,
+    options: &CountOptions,
+    read_options: &ReadOptions,
+) -> Result<FileReport> {
+    let mut counting = CountingReader::new(reader);
+    let buffer_size = buffer_size(None, read_options);
+    let mut reader = BufReader::with_capacity(buffer_size, &mut counting);
+
+    // 输入无法回退，用第一个读取缓冲区识别压缩格式
+    let sample = reader.fill_buf()?;
+    let compression = Compression::detect(sample).filter(|_| read_options.decompress);
+    let result = match compression {
+        Some(compression) => compression
+            .decoder(&mut reader, read_options.decompression)
+            .map_err(anyhow::Error::from)
+            .and_then(|decoder| {
+                let inner_path = decompress::inner_path(&path, compression);
+                count_text(
+                    BufReader::with_capacity(buffer_size, decoder),
+                    buffer_size,
+                    &path,
+                    &inner_path,
+                    options,
+                    read_options,
+                )
+            }),
+        None => count_text(
+            &mut reader,
+            buffer_size,
+            &path,
+            &path,
+            options,
+            read_options,
+        ),
+    };
+    let size = counting.bytes_read();
+
+    match compression {
+        Some(compression) => compressed_report(path, size, compression, result),
+        None => Ok(text_report(path, size, None, result?)),
+    }
+}
+
+/// 文本内容的统计结果
+struct TextCount {
+    stats: LineStats,
+    /// 内容的编码，按原始字节统计时为 `None`
+    encoding: Option<&'static Encoding>,
+    bom: bool,
+    /// 识别出的语言
+    language: Option<&'static Language>,
+}
+
+/// 识别内容的编码并统计行数（标准输入和压缩文件共用）
+///
+/// 输入无法回退，用第一个读取缓冲区识别编码和二进制内容。
+///
+/// # 参数
+///
+/// * `reader` - 内容读取器
+/// * `buffer_size` - 读取缓冲区大小，转码后的内容使用同样大小的缓冲区
+/// * `path` - 报告中的路径（用于流式模式的中间结果）
+/// * `language_path` - 识别语言使用的文件名（压缩输入为去掉压缩扩展名后的文件名）
+/// * `options` - 统计选项
+/// * `read_options` - 读取选项
+///
+/// # 返回值
+///
+/// * `Ok(Some(TextCount))` - 统计结果
+/// * `Ok(None)` - 二进制内容，应当跳过
+/// * `Err(anyhow::Error)` - 读取失败
+fn count_text(
+    mut reader: impl BufRead,
+    buffer_size: usize,
+    path: &Path,
+    language_path: &Path,
+    options: &CountOptions,
+    read_options: &ReadOptions,
+) -> Result<Option<TextCount>> {
+    let sample = reader.fill_buf()?;
+    let sample = &sample[..sample.len().min(SNIFF_SIZE)];
+    let Some(encoding) = resolve_encoding(sample, options, read_options) else {
+        return Ok(None);
+    };
+
+    let bom = encoding::has_bom(sample);
+    let language = read_options
+        .languages
+        .detect_with_shebang(language_path, language::from_shebang(sample));
+    let options = &file_options(options, language, read_options);
+    let reader: Box<dyn BufRead> = if encoding == UTF_8 {
+        Box::new(reader)
+    } else {
+        Box::new(BufReader::with_capacity(
+            buffer_size,
+            encoding::transcode(reader, encoding),
+        ))
+    };
+    let mut reporter = StreamReporter::new(path, read_options);
+    let stats = count_lines_streaming(reader, options, |stats, bytes| {
+        reporter.report(stats, bytes)
+    })?;
+
+    Ok(Some(TextCount {
+        stats,
+        encoding: (!options.bytes).then_some(encoding),
+        bom,
+        language,
+    }))
+}
+
+/// 按文件的语言补充注释语法、续行规则和单词数统计
+///
+/// # 参数
+///
+/// * `options` - 所有文件共用的统计选项
+/// * `language` - 文件的语言
+/// * `read_options` - 读取选项
+///
+/// # 返回值
+///
+/// 指定 `--comments` 且语言有注释语法、指定 `--logical` 且语言有续行规则、
+/// 指定 `--reading-time` 且语言为散文类、或 `--mode` 为文件选择了记录格式时为补充后的副本，
+/// 否则为原来的选项
+fn file_options<'a>(
+    options: &'a CountOptions,
+    language: Option<&Language>,
+    read_options: &ReadOptions,
+) -> Cow<'a, CountOptions> {
+    let comments = language
+        .map(|language| language.comments)
+        .filter(|syntax| read_options.comments && !syntax.is_empty());
+    let logical = language
+        .map(|language| language.comments)
+        .filter(|syntax| read_options.logical && !syntax.continuation.is_empty());
+    let words = read_options.words && language.is_some_and(|language| language.prose);
+    let records = read_options
+        .mode
+        .resolve(language.and_then(|language| language.records));
+    if comments.is_none() && logical.is_none() && !words && records.is_none() {
+        return Cow::Borrowed(options);
+    }
+    Cow::Owned(CountOptions {
+        comments,
+        logical,
+        words,
+        records,
+        ..options.clone()
+    })
+}
+
+/// 根据文本内容的统计结果构造报告
+///
+/// # 参数
+///
+/// * `path` - 报告中的路径
+/// * `size` - 输入的大小（压缩输入为压缩数据的大小）
+/// * `compression` - 透明解压的压缩格式
+/// * `counted` - 统计结果，`None` 表示二进制内容
+fn text_report(
+    path: PathBuf,
+    size: u64,
+    compression: Option<Compression>,
+    counted: Option<TextCount>,
+) -> FileReport {
+    let binary = counted.is_none();
+    let (stats, encoding, bom, language) = match counted {
+        Some(counted) => (
+            counted.stats,
+            counted.encoding.map(Encoding::name),
+            counted.bom,
+            counted.language,
+        ),
+        None => (LineStats::default(), None, false, None),
+    };
+    FileReport {
+        path,
+        size,
+        stats,
+        binary,
+        missing: false,
+        limit_exceeded: None,
+        timed_out: false,
+        encoding,
+        bom,
+        compression,
+        symlink_target: None,
+        language: language.map(|language| language.name),
+        warnings: Vec::new(),
+        tags: Vec::new(),
+        reading_minutes: None,
+        unstable: false,
+        error: None,
+    }
+}
+
+/// 根据压缩输入的统计结果构造报告，超过解压缩限制的输入报告为跳过，不使整个命令失败
+///
+/// # 参数
+///
+/// * `path` - 报告中的路径
+/// * `size` - 压缩数据的大小
+/// * `compression` - 压缩格式
+/// * `result` - 统计结果
+fn compressed_report(
+    path: PathBuf,
+    size: u64,
+    compression: Compression,
+    result: Result<Option<TextCount>>,
+) -> Result<FileReport> {
+    match result {
+        Ok(counted) => Ok(text_report(path, size, Some(compression), counted)),
+        Err(e) => match decompress::limit_exceeded(&e) {
+            Some(exceeded) => Ok(FileReport::limit_exceeded(
+                path,
+                size,
+                compression,
+                exceeded,
+            )),
+            None => Err(e),
+        },
+    }
+}
+
+/// 自动选择或命令行指定的读取缓冲区大小；低内存环境下自动选择的大小不超过 [`buffer::MIN_BUFFER_SIZE`]
+///
+/// 没有文件元数据（标准输入、命名管道、下载内容）时自动选择 [`buffer::STREAM_BUFFER_SIZE`]。
+fn buffer_size(metadata: Option<&fs::Metadata>, read_options: &ReadOptions) -> usize {
+    read_options.buffer_size.unwrap_or_else(|| {
+        let size = metadata.map_or(buffer::STREAM_BUFFER_SIZE, buffer::buffer_size_for);
+        if read_options.low_memory {
+            size.min(buffer::MIN_BUFFER_SIZE)
+        } else {
+            size
+        }
+    })
+}
+
+/// 确定输入使用的编码
+///
+/// # 参数
+///
+/// * `sample` - 输入开头的数据
+/// * `options` - 统计选项；统计二进制内容时二进制数据按 UTF-8 处理，指定 `lossy` 时没有 BOM 的文本按 UTF-8 处理，
+///   按原始字节统计时不识别编码
+/// * `read_options` - 读取选项；指定了编码时不再识别
+///
+/// # 返回值
+///
+/// * `Some(&Encoding)` - 使用的编码
+/// * `None` - 二进制内容，应当跳过
+fn resolve_encoding(
+    sample: &[u8],
+    options: &CountOptions,
+    read_options: &ReadOptions,
+) -> Option<&'static Encoding> {
+    if let Some(encoding) = read_options.encoding {
+        return Some(encoding);
+    }
+    // 按原始字节统计时不转码，也不跳过二进制内容
+    if options.bytes {
+        return Some(UTF_8);
+    }
+    match encoding::detect(sample) {
+        // 替换无效序列时按 UTF-8 解码，不猜测为传统编码；带 BOM 的内容仍按 BOM 解码
+        Detection::Text(_) if options.lossy && !encoding::has_bom(sample) => Some(UTF_8),
+        Detection::Text(encoding) => Some(encoding),
+        Detection::Binary if options.binary => Some(UTF_8),
+        Detection::Binary => None,
+    }
+}
+
+/// 读取文件开头用于识别编码，之后将读取位置移回文件开头
+///
+/// # 参数
+///
+/// * `file` - 已打开的文件
+///
+/// # 返回值
+///
+/// * `Ok(Vec<u8>)` - 文件开头最多 [`SNIFF_SIZE`] 字节
+/// * `Err(anyhow::Error)` - 读取或移动读取位置失败
+fn read_sample(file: &mut File) -> Result<Vec<u8>> {
+    let mut sample = Vec::with_capacity(SNIFF_SIZE);
+    file.by_ref()
+        .take(SNIFF_SIZE as u64)
+        .read_to_end(&mut sample)
+        .context("读取文件开头时发生错误")?;
+    file.seek(SeekFrom::Start(0)).context("无法移回文件开头")?;
+    Ok(sample)
+}
+
+/// 流式模式下的中间结果输出
+///
+/// 每隔 [`STREAM_FLUSH_INTERVAL`] 向标准错误输出一次目前的统计结果，
+/// 标准输出只保留最终结果，便于管道处理。
+struct StreamReporter<'a> {
+    path: &'a Path,
+    enabled: bool,
+    style: &'a Style,
+    last_flush: Instant,
+}
+
+impl<'a> StreamReporter<'a> {
+    fn new(path: &'a Path, read_options: &'a ReadOptions) -> Self {
+        Self {
+            path,
+            enabled: read_options.stream,
+            style: &read_options.style,
+            last_flush: Instant::now(),
+        }
+    }
+
+    fn report(&mut self, stats: &LineStats, bytes: u64) {
+        if self.enabled && self.last_flush.elapsed() >= STREAM_FLUSH_INTERVAL {
+            info!(
+                "{}",
+                output::format_stream_progress(self.path, stats, bytes, self.style)
+            );
+            self.last_flush = Instant::now();
+        }
+    }
+}
+
+/// 验证文件是否存在
+///
+/// # 参数
+///
+/// * `file_path` - 文件路径
+/// * `file_path_str` - 文件路径字符串（用于错误消息）
+///
+/// # 返回值
+///
+/// * `Ok(())` - 文件存在
+/// * `Err(LineCounterError)` - 文件不存在
+fn validate_file_exists(file_path: &Path, file_path_str: &str) -> Result<()> {
+    if !file_path.exists() {
+        return Err(LineCounterError::FileNotFound {
+            path: file_path_str.to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// 验证路径不是目录
+///
+/// # 参数
+///
+/// * `file_path` - 文件路径
+/// * `file_path_str` - 文件路径字符串（用于错误消息）
+///
+/// # 返回值
+///
+/// * `Ok(())` - 路径是文件
+/// * `Err(LineCounterError)` - 路径是目录
+pub(crate) fn validate_not_directory(file_path: &Path, file_path_str: &str) -> Result<()> {
+    if file_path.is_dir() {
+        return Err(LineCounterError::IsDirectory {
+            path: file_path_str.to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// 验证文件大小并获取元数据
+///
+/// # 参数
+///
+/// * `file_path` - 文件路径
+/// * `file_path_str` - 文件路径字符串（用于错误消息）
+/// * `max_size` - 文件大小上限（字节），0 表示不限制
+///
+/// # 返回值
+///
+/// * `Ok(std::fs::Metadata)` - 文件元数据
+/// * `Err(anyhow::Error)` - 无法获取元数据或文件过大
+fn validate_file_size(
+    file_path: &Path,
+    file_path_str: &str,
+    max_size: u64,
+) -> Result<std::fs::Metadata> {
+    let metadata = std::fs::metadata(file_path)
+        .with_context(|| format!("无法获取文件 '{}' 的元数据", file_path.display()))?;
+
+    if max_size > 0 && metadata.len() > max_size {
+        return Err(LineCounterError::FileTooLarge {
+            path: file_path_str.to_string(),
+            size: metadata.len(),
+        }
+        .into());
+    }
+
+    Ok(metadata)
+}
+
+/// 打开文件并处理各种错误情况
+///
+/// # 参数
+///
+/// * `file_path` - 文件路径
+/// * `file_path_str` - 文件路径字符串（用于错误消息）
+///
+/// # 返回值
+///
+/// * `Ok(File)` - 成功打开的文件
+/// * `Err(anyhow::Error)` - 文件打开失败
+pub(crate) fn open_file_with_error_handling(file_path: &Path, file_path_str: &str) -> Result<File> {
+    File::open(file_path)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => LineCounterError::FileNotFound {
+                path: file_path_str.to_string(),
+            },
+            std::io::ErrorKind::PermissionDenied => LineCounterError::PermissionDenied {
+                path: file_path_str.to_string(),
+            },
+            _ => LineCounterError::FileReadError {
+                path: file_path_str.to_string(),
+            },
+        })
+        .with_context(|| format!("尝试打开文件 '{}'", file_path.display()))
+}
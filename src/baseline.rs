@@ -0,0 +1,163 @@
+//! 基线比较
+//!
+//! `--baseline baseline.json` 将本次统计的汇总结果与之前用 `--format json` 保存的报告比较，
+//! 总行数或非空行数的增长超过 `--tolerance`（百分比）时以非零状态退出，
+//! 用作 CI 中防止文件膨胀的检查。行数减少总是允许的。
+//!
+//! 基线和本次统计中都有的标签（见 [`crate::tags`]）也会分别比较。
+
+use crate::stats::LineStats;
+use crate::tags::TagRollup;
+use serde::Serialize;
+
+/// 单项指标与基线的比较结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BaselineCheck {
+    /// 指标名称
+    pub metric: &'static str,
+    /// 比较的标签，`None` 表示整体汇总
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// 基线中的值
+    pub baseline: usize,
+    /// 本次统计的值
+    pub current: usize,
+    /// 相对基线的增长百分比（基线为 0 且有增长时为无穷大）
+    pub growth_percent: f64,
+    /// 是否超过容差
+    pub exceeded: bool,
+}
+
+/// 将本次统计结果与基线比较
+///
+/// # 参数
+///
+/// * `baseline` - 基线报告中的汇总结果
+/// * `current` - 本次统计的汇总结果
+/// * `tolerance` - 允许的增长百分比（如 `5.0` 表示 5%）
+///
+/// # 返回值
+///
+/// * `Vec<BaselineCheck>` - 总行数和非空行数的比较结果
+pub fn compare(baseline: &LineStats, current: &LineStats, tolerance: f64) -> Vec<BaselineCheck> {
+    [
+        ("总行数", baseline.total_lines, current.total_lines),
+        (
+            "非空行数",
+            baseline.non_empty_lines,
+            current.non_empty_lines,
+        ),
+    ]
+    .into_iter()
+    .map(|(metric, baseline, current)| {
+        let growth_percent = growth_percent(baseline, current);
+        BaselineCheck {
+            metric,
+            tag: None,
+            baseline,
+            current,
+            growth_percent,
+            exceeded: current > baseline && growth_percent > tolerance,
+        }
+    })
+    .collect()
+}
+
+/// 逐个标签与基线比较
+///
+/// # 参数
+///
+/// * `baseline` - 基线报告中的标签汇总
+/// * `current` - 本次统计的标签汇总
+/// * `tolerance` - 允许的增长百分比
+///
+/// # 返回值
+///
+/// * `Vec<BaselineCheck>` - 两边都有的标签的总行数和非空行数比较结果，按本次统计的标签顺序排列
+pub fn compare_tags(
+    baseline: &[TagRollup],
+    current: &[TagRollup],
+    tolerance: f64,
+) -> Vec<BaselineCheck> {
+    current
+        .iter()
+        .filter_map(|rollup| {
+            let base = baseline.iter().find(|base| base.name == rollup.name)?;
+            Some(
+                compare(&base.stats, &rollup.stats, tolerance)
+                    .into_iter()
+                    .map(|check| BaselineCheck {
+                        tag: Some(rollup.name.clone()),
+                        ..check
+                    }),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+/// 计算相对基线的增长百分比
+fn growth_percent(baseline: usize, current: usize) -> f64 {
+    if baseline == 0 {
+        if current == 0 { 0.0 } else { f64::INFINITY }
+    } else {
+        (current as f64 - baseline as f64) / baseline as f64 * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试容差内外的增长判断
+    #[test]
+    fn test_compare_with_tolerance() {
+        let baseline = LineStats::new(100, 80, 20);
+
+        let checks = compare(&baseline, &LineStats::new(104, 84, 20), 5.0);
+        assert!(checks.iter().all(|check| !check.exceeded));
+
+        let checks = compare(&baseline, &LineStats::new(110, 80, 30), 5.0);
+        assert_eq!(checks[0].growth_percent, 10.0);
+        assert!(checks[0].exceeded);
+        assert!(!checks[1].exceeded);
+    }
+
+    /// 测试按标签比较
+    #[test]
+    fn test_compare_tags() {
+        let rollup = |name: &str, total| TagRollup {
+            name: name.to_string(),
+            files: 1,
+            bytes: 0,
+            stats: LineStats::new(total, total, 0),
+        };
+        let baseline = vec![rollup("legacy", 100), rollup("removed", 10)];
+        let current = vec![rollup("core", 50), rollup("legacy", 120)];
+
+        let checks = compare_tags(&baseline, &current, 5.0);
+        assert_eq!(checks.len(), 2);
+        assert!(
+            checks
+                .iter()
+                .all(|check| check.tag.as_deref() == Some("legacy"))
+        );
+        assert!(checks[0].exceeded);
+        assert_eq!(checks[0].growth_percent, 20.0);
+    }
+
+    /// 测试行数减少和空基线
+    #[test]
+    fn test_compare_shrink_and_empty_baseline() {
+        let checks = compare(
+            &LineStats::new(100, 80, 20),
+            &LineStats::new(50, 40, 10),
+            0.0,
+        );
+        assert!(checks.iter().all(|check| !check.exceeded));
+
+        let checks = compare(&LineStats::default(), &LineStats::new(1, 1, 0), 1000.0);
+        assert!(checks[0].growth_percent.is_infinite());
+        assert!(checks[0].exceeded);
+    }
+}
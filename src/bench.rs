@@ -0,0 +1,180 @@
+//! 吞吐量基准测试
+//!
+//! `line-counter bench <文件>` 对同一文件分别使用各个统计引擎计时，
+//! 加上 `--compare-wc` 时同时计时系统的 `wc -l`，并输出相对性能，
+//! 方便用户在报告性能回退时给出可比较的数据。
+//!
+//! 每个引擎运行多次并取最快的一次，以减少冷缓存和调度抖动的影响。
+
+use crate::buffer::buffer_size_for;
+use crate::cli::BenchArgs;
+use crate::counter::{CountOptions, count_lines, count_lines_in_ranges_parallel};
+use crate::mmap::{count_file_mmap, count_file_parallel};
+use anyhow::{Context, Result, bail};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// 单个引擎的计时结果
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    /// 引擎名称
+    pub name: &'static str,
+    /// 最快一次的耗时
+    pub best: Duration,
+}
+
+/// 运行基准测试并输出结果
+///
+/// # 参数
+///
+/// * `args` - `bench` 子命令的参数
+///
+/// # 返回值
+///
+/// * `Ok(())` - 成功执行
+/// * `Err(anyhow::Error)` - 文件无法读取或 `wc` 执行失败
+pub fn run(args: &BenchArgs) -> Result<()> {
+    let path = args.file.as_path();
+    let metadata = std::fs::metadata(path)
+        .with_context(|| format!("无法获取文件 '{}' 的元数据", path.display()))?;
+    let size = metadata.len();
+    let buffer_size = buffer_size_for(&metadata);
+    let iterations = args.iterations.max(1);
+    let options = CountOptions::default();
+    let cores = rayon::current_num_threads();
+
+    let mut results = vec![
+        BenchResult {
+            name: "buffered",
+            best: time_best(iterations, || {
+                count_lines(
+                    BufReader::with_capacity(buffer_size, File::open(path)?),
+                    &options,
+                )?;
+                Ok(())
+            })?,
+        },
+        BenchResult {
+            name: "mmap",
+            best: time_best(iterations, || {
+                count_file_mmap(&File::open(path)?, &options)?;
+                Ok(())
+            })?,
+        },
+        BenchResult {
+            name: "parallel",
+            best: time_best(iterations, || {
+                count_file_parallel(&File::open(path)?, &options, cores)?;
+                Ok(())
+            })?,
+        },
+        BenchResult {
+            name: "parallel-read",
+            best: time_best(iterations, || {
+                count_lines_in_ranges_parallel(
+                    &File::open(path)?,
+                    size,
+                    &options,
+                    cores,
+                    buffer_size,
+                )?;
+                Ok(())
+            })?,
+        },
+    ];
+
+    let wc = if args.compare_wc {
+        Some(BenchResult {
+            name: "wc -l",
+            best: time_best(iterations, || run_wc(path))?,
+        })
+    } else {
+        None
+    };
+    results.extend(wc.clone());
+
+    println!(
+        "⏱️  基准测试: {} ({} bytes, {} 次取最快)",
+        path.display(),
+        size,
+        iterations
+    );
+    for result in &results {
+        let relative = match &wc {
+            Some(wc) => format!(", {:.2}x wc", relative_speed(result.best, wc.best)),
+            None => String::new(),
+        };
+        println!(
+            "🔧 {:<13} {:>10.3} ms  {:>10.1} MB/s{}",
+            result.name,
+            result.best.as_secs_f64() * 1000.0,
+            throughput_mb_per_sec(size, result.best),
+            relative
+        );
+    }
+
+    Ok(())
+}
+
+/// 运行多次并返回最快一次的耗时
+fn time_best<F: FnMut() -> Result<()>>(iterations: usize, mut f: F) -> Result<Duration> {
+    let mut best = Duration::MAX;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f()?;
+        best = best.min(start.elapsed());
+    }
+    Ok(best)
+}
+
+/// 执行一次 `wc -l`，丢弃其输出
+fn run_wc(path: &Path) -> Result<()> {
+    let status = Command::new("wc")
+        .arg("-l")
+        .arg(path)
+        .stdout(Stdio::null())
+        .status()
+        .context("无法执行 wc，请确认系统中已安装")?;
+    if !status.success() {
+        bail!("wc 执行失败: {}", status);
+    }
+    Ok(())
+}
+
+/// 计算吞吐量（MB/s）
+fn throughput_mb_per_sec(bytes: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs == 0.0 {
+        0.0
+    } else {
+        bytes as f64 / (1024.0 * 1024.0) / secs
+    }
+}
+
+/// 计算相对于基准的速度倍数（大于 1 表示比基准快）
+fn relative_speed(elapsed: Duration, baseline: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs == 0.0 {
+        0.0
+    } else {
+        baseline.as_secs_f64() / secs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试吞吐量与相对速度的计算
+    #[test]
+    fn test_throughput_and_relative_speed() {
+        let second = Duration::from_secs(1);
+        assert_eq!(throughput_mb_per_sec(2 * 1024 * 1024, second), 2.0);
+        assert_eq!(throughput_mb_per_sec(100, Duration::ZERO), 0.0);
+        assert_eq!(relative_speed(Duration::from_millis(500), second), 2.0);
+        assert_eq!(relative_speed(Duration::ZERO, second), 0.0);
+    }
+}
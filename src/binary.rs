@@ -0,0 +1,76 @@
+//! 二进制文件识别
+//!
+//! 统计前检查文件开头的一块数据：包含 NUL 字节，或无效 UTF-8 字节的比例过高时视为二进制文件并跳过，
+//! 而不是读到一半才因为无效 UTF-8 而失败。`--count-binary` 可以强制统计二进制文件中的换行符。
+//!
+//! 该判断作为 [`crate::encoding::detect`] 的最后一步使用：UTF-16 和传统多字节编码的文本会先被识别出来。
+
+/// 用于识别的开头数据大小
+pub const SNIFF_SIZE: usize = 8 * 1024; // 8KB
+
+/// 无效 UTF-8 字节占比超过该值时视为二进制文件
+const INVALID_UTF8_RATIO: f64 = 0.3;
+
+/// 判断一块开头数据是否来自二进制文件
+///
+/// # 参数
+///
+/// * `sample` - 文件开头的数据，末尾可能截断在多字节字符中间
+///
+/// # 返回值
+///
+/// * `bool` - 包含 NUL 字节或无效 UTF-8 字节占比超过 30% 时为 `true`
+pub fn looks_binary(sample: &[u8]) -> bool {
+    if sample.is_empty() {
+        return false;
+    }
+    if memchr::memchr(0, sample).is_some() {
+        return true;
+    }
+
+    let invalid = invalid_utf8_bytes(sample);
+    invalid as f64 / sample.len() as f64 > INVALID_UTF8_RATIO
+}
+
+/// 统计无效 UTF-8 字节数（末尾截断的字符不计入）
+fn invalid_utf8_bytes(mut data: &[u8]) -> usize {
+    let mut invalid = 0;
+    loop {
+        match std::str::from_utf8(data) {
+            Ok(_) => return invalid,
+            Err(e) => match e.error_len() {
+                Some(len) => {
+                    invalid += len;
+                    data = &data[e.valid_up_to() + len..];
+                }
+                // 末尾不完整的字符
+                None => return invalid,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试文本数据不被识别为二进制
+    #[test]
+    fn test_text_is_not_binary() {
+        assert!(!looks_binary(b""));
+        assert!(!looks_binary("fn main() {}\n// 中文注释\n".as_bytes()));
+        // 截断在多字节字符中间
+        assert!(!looks_binary(&"中文".as_bytes()[..4]));
+        // 偶尔出现的无效字节
+        let mut data = "正常的日志内容\n".repeat(10).into_bytes();
+        data.push(0xff);
+        assert!(!looks_binary(&data));
+    }
+
+    /// 测试二进制数据的识别
+    #[test]
+    fn test_binary_detection() {
+        assert!(looks_binary(b"\x7fELF\x02\x01\x01\x00"));
+        assert!(looks_binary(&[0xff, 0xfe, 0x80, 0x81, b'a']));
+    }
+}
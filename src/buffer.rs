@@ -0,0 +1,159 @@
+//! 读取缓冲区大小
+//!
+//! `BufReader` 默认的 8KB 缓冲区对中大型文件偏小：基准测试中 64KB 缓冲区在中等文件上明显更快。
+//! 未通过 `--buffer-size` 指定时，根据文件大小和文件系统块大小选择缓冲区大小：
+//!
+//! - 小文件：不超过文件本身大小（按块大小向上取整），避免为小文件分配大缓冲区
+//! - 中等文件：[`MEDIUM_BUFFER_SIZE`]
+//! - 超过 [`LARGE_FILE_THRESHOLD`] 的大文件：[`LARGE_BUFFER_SIZE`]
+//!
+//! 结果总是块大小的整数倍，使每次读取对齐文件系统块。
+//! 标准输入、命名管道和下载内容等没有预先可知大小的输入使用 [`STREAM_BUFFER_SIZE`]。
+
+use crate::units;
+use std::fs::Metadata;
+
+/// 最小缓冲区大小，与 `BufReader` 的默认值相同
+pub const MIN_BUFFER_SIZE: usize = 8 * 1024; // 8KB
+
+/// 中等文件使用的缓冲区大小
+pub const MEDIUM_BUFFER_SIZE: usize = 64 * 1024; // 64KB
+
+/// 大文件使用的缓冲区大小
+pub const LARGE_BUFFER_SIZE: usize = 256 * 1024; // 256KB
+
+/// 没有预先可知大小的输入使用的缓冲区大小，与 Linux 管道的默认容量相同
+pub const STREAM_BUFFER_SIZE: usize = 64 * 1024; // 64KB
+
+/// 使用大缓冲区的文件大小阈值
+pub const LARGE_FILE_THRESHOLD: u64 = 16 * 1024 * 1024; // 16MB
+
+/// 无法获取文件系统块大小时使用的默认值
+const DEFAULT_BLOCK_SIZE: u64 = 4096;
+
+/// 读取缓冲区大小的设置（`--buffer-size`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BufferSize {
+    /// 根据输入自动选择
+    #[default]
+    Auto,
+    /// 固定大小（字节）
+    Fixed(usize),
+}
+
+impl BufferSize {
+    /// 解析 `auto` 或带单位的大小（如 `64K`，见 [`units::parse_size`]）
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(BufferSize)` - 缓冲区大小的设置
+    /// * `Err(String)` - 格式无效或大小为 0，供 `clap` 显示
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if value.trim().eq_ignore_ascii_case("auto") {
+            return Ok(BufferSize::Auto);
+        }
+        match units::parse_size(value)? {
+            0 => Err("缓冲区大小必须大于 0".to_string()),
+            size => usize::try_from(size)
+                .map(BufferSize::Fixed)
+                .map_err(|_| format!("大小超出范围: '{}'", value)),
+        }
+    }
+
+    /// 固定的大小，自动选择时为 `None`
+    pub fn fixed(self) -> Option<usize> {
+        match self {
+            BufferSize::Auto => None,
+            BufferSize::Fixed(size) => Some(size),
+        }
+    }
+}
+
+/// 根据文件大小和块大小选择缓冲区大小
+///
+/// # 参数
+///
+/// * `file_size` - 文件大小（字节）
+/// * `block_size` - 文件系统块大小（字节），0 表示未知
+///
+/// # 返回值
+///
+/// * `usize` - 缓冲区大小（字节）
+pub fn adaptive_buffer_size(file_size: u64, block_size: u64) -> usize {
+    let block_size = if block_size == 0 {
+        DEFAULT_BLOCK_SIZE
+    } else {
+        block_size
+    };
+
+    let target = if file_size >= LARGE_FILE_THRESHOLD {
+        LARGE_BUFFER_SIZE as u64
+    } else {
+        file_size.clamp(MIN_BUFFER_SIZE as u64, MEDIUM_BUFFER_SIZE as u64)
+    };
+
+    target.div_ceil(block_size).saturating_mul(block_size) as usize
+}
+
+/// 根据文件元数据选择缓冲区大小
+///
+/// # 参数
+///
+/// * `metadata` - 文件元数据
+pub fn buffer_size_for(metadata: &Metadata) -> usize {
+    adaptive_buffer_size(metadata.len(), block_size(metadata))
+}
+
+/// 获取文件所在文件系统的块大小
+#[cfg(unix)]
+fn block_size(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blksize()
+}
+
+/// 获取文件所在文件系统的块大小（非 Unix 平台未知）
+#[cfg(not(unix))]
+fn block_size(_metadata: &Metadata) -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试不同文件大小选择的缓冲区大小
+    #[test]
+    fn test_adaptive_buffer_size() {
+        assert_eq!(adaptive_buffer_size(0, 4096), MIN_BUFFER_SIZE);
+        assert_eq!(adaptive_buffer_size(20 * 1024, 4096), 20 * 1024);
+        assert_eq!(
+            adaptive_buffer_size(10 * 1024 * 1024, 4096),
+            MEDIUM_BUFFER_SIZE
+        );
+        assert_eq!(
+            adaptive_buffer_size(LARGE_FILE_THRESHOLD, 4096),
+            LARGE_BUFFER_SIZE
+        );
+    }
+
+    /// 测试缓冲区大小按块大小对齐
+    #[test]
+    fn test_adaptive_buffer_size_block_aligned() {
+        assert_eq!(adaptive_buffer_size(10_000, 4096), 12 * 1024);
+        assert_eq!(adaptive_buffer_size(10_000, 0), 12 * 1024);
+        assert_eq!(adaptive_buffer_size(1024 * 1024, 128 * 1024), 128 * 1024);
+    }
+
+    /// 测试解析缓冲区大小的设置
+    #[test]
+    fn test_buffer_size_parse() {
+        assert_eq!(BufferSize::parse("auto"), Ok(BufferSize::Auto));
+        assert_eq!(BufferSize::parse("AUTO"), Ok(BufferSize::Auto));
+        assert_eq!(BufferSize::parse("7"), Ok(BufferSize::Fixed(7)));
+        assert_eq!(BufferSize::parse("64K"), Ok(BufferSize::Fixed(64 * 1024)));
+        assert_eq!(BufferSize::parse("1M").unwrap().fixed(), Some(1024 * 1024));
+        assert_eq!(BufferSize::Auto.fixed(), None);
+        assert!(BufferSize::parse("0").is_err());
+        assert!(BufferSize::parse("big").is_err());
+    }
+}
@@ -0,0 +1,540 @@
+//! 持久化的统计结果缓存
+//!
+//! 统计结果按文件的绝对路径缓存在 `$XDG_CACHE_HOME/line-counter`（未设置时为 `~/.cache/line-counter`）中，
+//! 再次统计时大小和修改时间都没有变化的文件直接使用缓存的结果，不再读取内容。
+//! 缓存按配置哈希（统计参数、`--languages-file` 和 `--stop-words` 的内容加上工具版本）分为多个文件，
+//! 改变统计选项、修改自定义语言或停用词、升级工具后自然使用新的缓存，不会读到按其他选项统计的结果。
+//!
+//! 为避免在修改时间精度较低的文件系统上漏掉同一时刻内的修改，最近 [`RACY_WINDOW`] 内修改过的文件不写入缓存。
+//! 标准输入、不存在的路径、超过解压缩限制和到达截止时间的文件不缓存。
+//! 缓存文件损坏或无法读取时视为空缓存；`--no-cache` 既不读取也不写入缓存。
+//!
+//! `--changed-only` 改为按 [`Changed`] 判断哪些文件发生了变化，只重新统计这些文件，
+//! 其余文件直接使用缓存的结果（不再比较大小和修改时间），合并后输出完整的报告：
+//!
+//! - 默认为缓存的结果统计之后修改过的文件（只比较修改时间，不比较大小）
+//! - `--since <时间>`（RFC 3339，如 `2026-10-01T12:00:00Z`）为修改时间晚于该时间的文件
+//! - `--since <版本>` 为工作区中相对该 Git 版本发生变化的文件和未跟踪的文件，见 [`crate::git::changed_files`]
+//!
+//! 增量统计相信缓存反映了参照点时的内容，缓存中没有的文件总是重新统计。
+
+use crate::app::{ReadOptions, count_files};
+use crate::counter::CountOptions;
+use crate::decompress::Compression;
+use crate::input::is_stdin;
+use crate::meta::config_hash;
+use crate::report::FileReport;
+use crate::stats::LineStats;
+use anyhow::{Context, Result};
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::debug;
+
+/// 修改时间距今不超过该时长的文件不写入缓存
+pub const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// 一个文件的缓存结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    /// 统计时的文件大小（字节）
+    size: u64,
+    /// 统计时的修改时间（距 Unix 纪元）
+    modified: Duration,
+    /// 统计该结果的运行开始的时间（距 Unix 纪元）
+    counted: Duration,
+    stats: LineStats,
+    binary: bool,
+    encoding: Option<String>,
+    bom: bool,
+    compression: Option<Compression>,
+    language: Option<String>,
+}
+
+/// 判断文件是否发生变化、需要重新统计的依据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changed {
+    /// 大小或修改时间与缓存的结果不同（默认）
+    Stamp,
+    /// 修改时间晚于缓存中的结果的统计时间（`--changed-only`）
+    SinceCounted,
+    /// 修改时间晚于给定的时间（`--changed-only --since <时间>`）
+    ModifiedAfter(SystemTime),
+    /// 属于给定的文件，路径为解析了符号链接的绝对路径（`--changed-only --since <版本>`）
+    Paths(HashSet<PathBuf>),
+}
+
+/// `--since` 指定的增量统计参照点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Since {
+    /// 时间
+    Time(SystemTime),
+    /// Git 版本
+    Rev(String),
+}
+
+impl Since {
+    /// 解析 `--since` 的值：能按 RFC 3339 解析的为时间，其余为 Git 版本
+    ///
+    /// # 参数
+    ///
+    /// * `value` - 命令行中的值，如 `2026-10-01T12:00:00Z`、`2026-10-01 12:00:00`、`HEAD~3`、`main`
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(Since)` - 参照点
+    /// * `Err(String)` - 值为空或以 `-` 开头
+    pub fn parse(value: &str) -> std::result::Result<Self, String> {
+        if let Ok(time) = humantime::parse_rfc3339_weak(value) {
+            return Ok(Since::Time(time));
+        }
+        if value.is_empty() || value.starts_with('-') {
+            return Err(format!("无效的版本: '{}'", value));
+        }
+        Ok(Since::Rev(value.to_string()))
+    }
+}
+
+/// 文件当前的大小和修改时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    size: u64,
+    modified: Duration,
+}
+
+impl Stamp {
+    /// 读取文件的大小和修改时间，无法获取或不是普通文件（内容每次读取都不同）时为 `None`
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path)
+            .ok()
+            .filter(|metadata| metadata.is_file())?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?;
+        Some(Self {
+            size: metadata.len(),
+            modified,
+        })
+    }
+}
+
+/// 保存在缓存目录中的统计结果
+#[derive(Debug)]
+pub struct ResultCache {
+    /// 缓存文件路径
+    path: PathBuf,
+    /// 以绝对路径为键的缓存结果
+    entries: HashMap<String, Entry>,
+    /// 本次运行是否修改了缓存
+    dirty: bool,
+}
+
+/// 默认的缓存目录，无法确定用户目录时为 `None`
+pub fn cache_dir() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(dir.join("line-counter"))
+}
+
+impl ResultCache {
+    /// 打开配置对应的缓存
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 缓存目录
+    /// * `config` - 影响统计结果的参数
+    ///
+    /// # 返回值
+    ///
+    /// 缓存；缓存文件不存在、无法读取或已损坏时为空缓存
+    pub fn open<C: Serialize>(dir: &Path, config: &C) -> Self {
+        let hash = config_hash(&(env!("CARGO_PKG_VERSION"), config));
+        let name = hash.rsplit(':').next().unwrap_or(&hash);
+        let path = dir.join(format!("{}.json", name));
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// 缓存的文件数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 统计文件，没有变化的文件直接使用缓存的结果
+    ///
+    /// # 参数
+    ///
+    /// * `files` - 待统计的文件列表
+    /// * `changed` - 判断文件是否发生变化的依据
+    /// * `jobs` - 线程数
+    /// * `options` - 统计选项
+    /// * `read_options` - 文件读取选项（提供识别语言使用的语言表）
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(Vec<FileReport>)` - 按输入顺序排列的统计报告
+    /// * `Err(anyhow::Error)` - 任意需要重新统计的文件统计失败
+    pub fn count(
+        &mut self,
+        files: &[PathBuf],
+        changed: &Changed,
+        jobs: usize,
+        options: &CountOptions,
+        read_options: &ReadOptions,
+    ) -> Result<Vec<FileReport>> {
+        let started = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let keys: Vec<Option<(String, Stamp)>> = files
+            .iter()
+            .map(|path| {
+                if is_stdin(path) {
+                    return None;
+                }
+                let key = std::path::absolute(path).ok()?.to_str()?.to_string();
+                Some((key, Stamp::of(path)?))
+            })
+            .collect();
+
+        let mut reports: Vec<Option<FileReport>> = files
+            .iter()
+            .zip(&keys)
+            .map(|(path, key)| {
+                let (key, stamp) = key.as_ref()?;
+                let entry = self.entries.get(key)?;
+                let fresh = match changed {
+                    Changed::Stamp => entry.size == stamp.size && entry.modified == stamp.modified,
+                    Changed::SinceCounted => stamp.modified + RACY_WINDOW <= entry.counted,
+                    Changed::ModifiedAfter(time) => {
+                        SystemTime::UNIX_EPOCH + stamp.modified <= *time
+                    }
+                    Changed::Paths(paths) => {
+                        path.canonicalize().is_ok_and(|path| !paths.contains(&path))
+                    }
+                };
+                let report = fresh.then(|| restore(entry, path, read_options)).flatten();
+                if report.is_some() {
+                    debug!(
+                        path = %path.display(),
+                        "{}",
+                        read_options.style.tr("使用缓存的统计结果")
+                    );
+                }
+                report
+            })
+            .collect();
+
+        let stale: Vec<PathBuf> = files
+            .iter()
+            .zip(&reports)
+            .filter(|(_, report)| report.is_none())
+            .map(|(path, _)| path.clone())
+            .collect();
+        debug!(
+            "{}",
+            read_options.style.format(
+                "缓存命中 {} 个文件，重新统计 {} 个文件",
+                &[&(files.len() - stale.len()), &stale.len()]
+            )
+        );
+        let mut fresh = count_files(&stale, jobs, options, read_options)?.into_iter();
+
+        for (report, key) in reports.iter_mut().zip(keys) {
+            if report.is_some() {
+                continue;
+            }
+            let counted = fresh.next().expect("每个未缓存的文件都有一份报告");
+            if let Some((key, stamp)) = key
+                && stamp.modified + RACY_WINDOW <= started
+                && let Some(entry) = store(&counted, stamp, started)
+            {
+                self.entries.insert(key, entry);
+                self.dirty = true;
+            }
+            *report = Some(counted);
+        }
+        Ok(reports.into_iter().flatten().collect())
+    }
+
+    /// 把缓存写回缓存目录，同时移除已经不存在的文件的结果；缓存没有变化时不写入
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(())` - 写入成功或不需要写入
+    /// * `Err(anyhow::Error)` - 无法创建缓存目录或写入缓存文件
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.entries.retain(|path, _| Path::new(path).exists());
+
+        let error = || format!("无法写入缓存 '{}'", self.path.display());
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).with_context(error)?;
+        }
+        // 先写入临时文件再重命名，同时运行的其他进程不会读到写了一半的缓存
+        let temp_path = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        let json = serde_json::to_vec(&self.entries).with_context(error)?;
+        fs::write(&temp_path, json).with_context(error)?;
+        fs::rename(&temp_path, &self.path).with_context(error)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// 需要缓存的报告对应的缓存结果，不缓存的报告为 `None`
+fn store(report: &FileReport, stamp: Stamp, counted: Duration) -> Option<Entry> {
+    if !report.is_cacheable() {
+        return None;
+    }
+    Some(Entry {
+        size: stamp.size,
+        modified: stamp.modified,
+        counted,
+        stats: report.stats.clone(),
+        binary: report.binary,
+        encoding: report.encoding.map(str::to_string),
+        bom: report.bom,
+        compression: report.compression,
+        language: report.language.map(str::to_string),
+    })
+}
+
+/// 由缓存结果还原报告；编码或语言已经无法识别（如自定义语言被删除）时为 `None`，按未缓存处理
+fn restore(entry: &Entry, path: &Path, read_options: &ReadOptions) -> Option<FileReport> {
+    let encoding = match &entry.encoding {
+        Some(name) => Some(Encoding::for_label(name.as_bytes())?.name()),
+        None => None,
+    };
+    let language = match &entry.language {
+        Some(name) => Some(read_options.languages.by_name(name)?.name),
+        None => None,
+    };
+    Some(FileReport {
+        path: path.to_path_buf(),
+        size: entry.size,
+        stats: entry.stats.clone(),
+        binary: entry.binary,
+        missing: false,
+        limit_exceeded: None,
+        timed_out: false,
+        encoding,
+        bom: entry.bom,
+        compression: entry.compression,
+        symlink_target: None,
+        language,
+        warnings: Vec::new(),
+        tags: Vec::new(),
+        reading_minutes: None,
+        unstable: false,
+        error: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::count_file;
+    use std::fs::File;
+
+    /// 创建修改时间足够早、可以写入缓存的文件
+    fn create_old_file(path: &Path, content: &str) {
+        fs::write(path, content).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(60);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    /// 测试缓存的命中、失效与持久化
+    #[test]
+    fn test_result_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let first = temp_dir.path().join("a.rs");
+        let second = temp_dir.path().join("b.txt");
+        create_old_file(&first, "fn main() {\n}\n");
+        create_old_file(&second, "1\n");
+        let files = vec![first.clone(), second.clone()];
+        let count = |cache: &mut ResultCache| {
+            cache
+                .count(
+                    &files,
+                    &Changed::Stamp,
+                    1,
+                    &CountOptions::default(),
+                    &ReadOptions::default(),
+                )
+                .unwrap()
+                .iter()
+                .map(|report| report.stats.total_lines)
+                .collect::<Vec<_>>()
+        };
+
+        let mut cache = ResultCache::open(&cache_dir, &"config");
+        assert_eq!(count(&mut cache), vec![2, 1]);
+        assert_eq!(cache.len(), 2);
+        cache.save().unwrap();
+
+        // 同样大小和修改时间的文件直接使用缓存，即使内容已经改变
+        let modified = fs::metadata(&first).unwrap().modified().unwrap();
+        fs::write(&first, "a\nb\nc\nd\ne\nf\ng\n").unwrap();
+        File::options()
+            .write(true)
+            .open(&first)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let mut cache = ResultCache::open(&cache_dir, &"config");
+        let reports = cache
+            .count(
+                &files,
+                &Changed::Stamp,
+                1,
+                &CountOptions::default(),
+                &ReadOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(reports[0].stats.total_lines, 2);
+        assert_eq!(reports[0].language, Some("Rust"));
+        assert_eq!(reports[0].encoding, Some("UTF-8"));
+        assert_eq!(reports[0].path, first);
+
+        // 大小或修改时间变化后重新统计
+        create_old_file(&second, "1\n2\n3\n");
+        assert_eq!(count(&mut cache), vec![2, 3]);
+
+        // 其他配置使用独立的缓存
+        let mut other = ResultCache::open(&cache_dir, &"other");
+        assert!(other.is_empty());
+
+        // 刚刚修改过的文件不写入缓存
+        fs::write(&second, "1\n").unwrap();
+        assert_eq!(count(&mut other), vec![7, 1]);
+        assert_eq!(other.len(), 1);
+
+        // 已经删除的文件在写入时被移除
+        cache.save().unwrap();
+        fs::remove_file(&second).unwrap();
+        cache.dirty = true;
+        cache.save().unwrap();
+        assert_eq!(ResultCache::open(&cache_dir, &"config").len(), 1);
+    }
+
+    /// 测试 `--since` 的解析
+    #[test]
+    fn test_since() {
+        assert_eq!(
+            Since::parse("2026-10-01T12:00:00Z"),
+            Ok(Since::Time(
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1_790_856_000)
+            ))
+        );
+        assert!(matches!(
+            Since::parse("2026-10-01 12:00:00"),
+            Ok(Since::Time(_))
+        ));
+        assert_eq!(Since::parse("HEAD~3"), Ok(Since::Rev("HEAD~3".to_string())));
+        assert!(Since::parse("--output=x").is_err());
+        assert!(Since::parse("").is_err());
+    }
+
+    /// 测试增量统计按不同的依据判断文件是否发生变化
+    #[test]
+    fn test_changed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let first = temp_dir.path().join("a.txt");
+        let second = temp_dir.path().join("b.txt");
+        create_old_file(&first, "1\n");
+        create_old_file(&second, "1\n");
+        let files = vec![first.clone(), second.clone()];
+        let mut cache = ResultCache::open(&temp_dir.path().join("cache"), &"config");
+        let mut count = |changed: Changed| {
+            cache
+                .count(
+                    &files,
+                    &changed,
+                    1,
+                    &CountOptions::default(),
+                    &ReadOptions::default(),
+                )
+                .unwrap()
+                .iter()
+                .map(|report| report.stats.total_lines)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(count(Changed::Stamp), vec![1, 1]);
+
+        // 修改时间早于统计时间的文件直接使用缓存，不比较大小
+        let modified = fs::metadata(&first).unwrap().modified().unwrap();
+        for (path, content) in [(&first, "1\n2\n"), (&second, "1\n2\n3\n")] {
+            fs::write(path, content).unwrap();
+            File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        assert_eq!(count(Changed::SinceCounted), vec![1, 1]);
+
+        let paths = HashSet::from([second.canonicalize().unwrap()]);
+        assert_eq!(count(Changed::Paths(paths)), vec![1, 3]);
+
+        let since = SystemTime::now() - Duration::from_secs(90);
+        assert_eq!(count(Changed::ModifiedAfter(since)), vec![2, 3]);
+
+        fs::write(&first, "x\n").unwrap();
+        assert_eq!(count(Changed::SinceCounted), vec![1, 3]);
+    }
+
+    /// 测试损坏的缓存文件被视为空缓存
+    #[test]
+    fn test_corrupt_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache = ResultCache::open(temp_dir.path(), &"config");
+        fs::write(&cache.path, "{not json").unwrap();
+        assert!(ResultCache::open(temp_dir.path(), &"config").is_empty());
+    }
+
+    /// 测试统计过程中被修改的文件不写入缓存
+    #[test]
+    fn test_store_unstable() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.log");
+        create_old_file(&path, "1\n2\n");
+        let report = count_file(&path, &CountOptions::default(), &ReadOptions::default()).unwrap();
+        let stamp = Stamp::of(&path).unwrap();
+        assert!(store(&report, stamp, Duration::ZERO).is_some());
+
+        let report = FileReport {
+            unstable: true,
+            ..report
+        };
+        assert!(store(&report, stamp, Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,975 @@
+//! 命令行参数定义
+//!
+//! 使用 `clap` 派生宏解析命令行参数。
+//! 不带子命令时直接统计给定的路径；`watch` 子命令复用同一组统计参数 [`CountArgs`]，
+//! `diff`、`compare` 和 `bench` 子命令使用各自独立的参数。
+//!
+//! [`CountArgs`] 同时实现了 `Serialize`，用于计算结构化报告中的配置哈希；
+//! 不影响统计结果的参数（输入路径、线程数、输出格式等）不参与序列化。
+
+use crate::buffer::BufferSize;
+use crate::cache::Since;
+use crate::color::{self, Theme};
+use crate::config::{NeverRead, PathGlob};
+use crate::counter::{self, CountOptions, LineMatch, LineRange, SectionFilter};
+use crate::decompress::{DEFAULT_MAX_RATIO, DecompressionLimits};
+use crate::duplicates::DuplicateOptions;
+use crate::encoding;
+use crate::engine::Engine;
+use crate::group::{GroupBy, Grouping};
+use crate::headers::HeaderOptions;
+use crate::hyperlink::{self, HyperlinkFormat, Hyperlinks, Stream};
+use crate::language::Languages;
+use crate::locale::Lang;
+use crate::logging;
+use crate::normalize::{self, Normalization};
+use crate::output::{DEFAULT_PRECISION, Style};
+use crate::pattern::CaseMode;
+use crate::records::RecordMode;
+use crate::report::{self, SortKey};
+use crate::tags::{self, TagRule};
+use crate::threshold::{self, Thresholds};
+use crate::todos::{self, TodoOptions};
+use crate::tokens::Tokenizer;
+use crate::units;
+use crate::walk::SymlinkPolicy;
+use crate::words::{StopWords, WordFreqOptions};
+use clap::builder::RangedU64ValueParser;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use encoding_rs::Encoding;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+
+/// 输出格式
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// 带表情符号的可读文本
+    Human,
+    /// 带运行元数据的 JSON 报告
+    Json,
+    /// GitHub 风格的 Markdown 表格，可直接粘贴到 PR 描述或 wiki 页面
+    Markdown,
+    /// shields.io 端点徽章的 JSON（总行数），只适用于统计结果
+    Badge,
+    /// 每行一个 `key=value` 指标，键名稳定，供不想依赖 JSON 解析器的脚本使用，只适用于统计结果
+    PlainKv,
+}
+
+/// 命令行参数
+#[derive(Parser, Debug, Clone)]
+#[command(
+    name = "line-counter",
+    version,
+    about = "统计文件的行数并提供详细的分析信息"
+)]
+pub struct Cli {
+    /// 子命令
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 项目配置文件（默认为当前目录中的 .line-counter.toml）
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// 输出和错误信息的语言（默认按 LC_ALL、LC_MESSAGES、LANG 选择）
+    #[arg(long, value_enum, value_name = "LANG", global = true)]
+    pub lang: Option<Lang>,
+
+    /// 输出调试信息（每个文件的耗时、跳过原因、缓存命中等），重复指定输出更详细的跟踪信息；
+    /// `-v` 表示 --invert-match，因此只有长选项
+    #[arg(long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// 只输出结果和错误，不输出状态提示和警告
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// 统计参数
+    #[command(flatten)]
+    pub count: CountArgs,
+}
+
+impl Cli {
+    /// 输出语言：`--lang` 指定的语言，未指定时按区域设置选择
+    pub fn lang(&self) -> Lang {
+        self.lang.unwrap_or_else(Lang::from_env)
+    }
+
+    /// 日志级别：由 `--verbose` 和 `--quiet` 决定（见 [`crate::logging`]）
+    pub fn log_level(&self) -> LevelFilter {
+        logging::level(self.verbose, self.quiet)
+    }
+}
+
+/// 子命令
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// 监视文件或目录，内容变化时重新统计
+    Watch(Box<WatchArgs>),
+    /// 比较两个文件（或两份 JSON 报告）的行数变化
+    Diff(DiffArgs),
+    /// 按相对路径比较两个目录中文件的行数变化
+    Compare(CompareArgs),
+    /// 统计两个 Git 版本之间每个文件新增、删除和修改的行数
+    GitDiff(GitDiffArgs),
+    /// 对文件计时各个统计引擎，可与系统的 `wc -l` 对比
+    Bench(BenchArgs),
+    /// 在终端中以可展开的目录树交互浏览目录的行数统计
+    Tui(TuiArgs),
+    /// 以 HTTP 接口提供统计功能
+    Serve(ServeArgs),
+}
+
+/// `watch` 子命令的参数
+#[derive(Args, Debug, Clone)]
+pub struct WatchArgs {
+    /// 合并连续变化事件的等待时间（毫秒）
+    #[arg(long, value_name = "MS", default_value_t = 300)]
+    pub debounce: u64,
+
+    /// 重新统计后只输出统计结果与上一次不同的文件
+    #[arg(long)]
+    pub only_changes: bool,
+
+    /// 统计参数
+    #[command(flatten)]
+    pub count: CountArgs,
+}
+
+/// `diff` 子命令的参数
+#[derive(Args, Debug, Clone)]
+pub struct DiffArgs {
+    /// 旧的文件（或报告）
+    pub old: PathBuf,
+
+    /// 新的文件（或报告）
+    pub new: PathBuf,
+
+    /// 输入是 `--format json` 生成的报告，比较其中的汇总结果
+    #[arg(long)]
+    pub report: bool,
+
+    /// 从行数统计中排除 shebang 和许可证头（仅比较文件时有效）
+    #[arg(long, conflicts_with = "report")]
+    pub exclude_headers: bool,
+
+    /// 忽略只涉及空行的变化：总行数的变化按非空行数计算，增删空行不算作增长
+    #[arg(long)]
+    pub ignore_blank_changes: bool,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+
+    /// 面向屏幕阅读器的文本输出：不使用表情符号和符号，用完整的词语描述变化
+    #[arg(long)]
+    pub accessible: bool,
+
+    /// 不输出表情符号（区域设置不是 UTF-8 时自动生效）
+    #[arg(long)]
+    pub no_emoji: bool,
+}
+
+/// `compare` 子命令的参数
+#[derive(Args, Debug, Clone)]
+pub struct CompareArgs {
+    /// 旧的目录
+    pub old: PathBuf,
+
+    /// 新的目录
+    pub new: PathBuf,
+
+    /// 逐个列出新增、删除和修改的文件及其行数变化
+    #[arg(long)]
+    pub detail: bool,
+
+    /// 从行数统计中排除 shebang 和许可证头
+    #[arg(long)]
+    pub exclude_headers: bool,
+
+    /// 忽略只涉及空行的变化：只增删空行的文件视为未变化，总行数的变化按非空行数计算
+    #[arg(long)]
+    pub ignore_blank_changes: bool,
+
+    /// 并行统计使用的线程数（0 表示使用全部可用核心）
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+
+    /// 面向屏幕阅读器的文本输出：不使用表情符号和符号，用完整的词语描述变化
+    #[arg(long)]
+    pub accessible: bool,
+
+    /// 不输出表情符号（区域设置不是 UTF-8 时自动生效）
+    #[arg(long)]
+    pub no_emoji: bool,
+}
+
+/// `git-diff` 子命令的参数
+#[derive(Args, Debug, Clone)]
+pub struct GitDiffArgs {
+    /// 版本范围，如 `v1.0..v1.1` 或 `HEAD~3..HEAD`
+    #[arg(value_name = "REV1..REV2")]
+    pub range: String,
+
+    /// 只统计这些路径下的文件（相对于仓库根目录）
+    pub paths: Vec<PathBuf>,
+
+    /// 仓库中的目录
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub repo: PathBuf,
+
+    /// 只输出汇总，不逐个列出文件
+    #[arg(long)]
+    pub summary_only: bool,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+
+    /// 面向屏幕阅读器的文本输出：不使用表情符号和符号，用完整的词语描述变化
+    #[arg(long)]
+    pub accessible: bool,
+
+    /// 不输出表情符号（区域设置不是 UTF-8 时自动生效）
+    #[arg(long)]
+    pub no_emoji: bool,
+}
+
+/// `bench` 子命令的参数
+#[derive(Args, Debug, Clone)]
+pub struct BenchArgs {
+    /// 用于计时的文件
+    pub file: PathBuf,
+
+    /// 同时计时系统的 `wc -l` 并输出相对性能
+    #[arg(long)]
+    pub compare_wc: bool,
+
+    /// 每个引擎的运行次数（取最快的一次）
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub iterations: usize,
+}
+
+/// `tui` 子命令的参数
+#[derive(Args, Debug, Clone)]
+pub struct TuiArgs {
+    /// 要浏览的目录
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// 从行数统计中排除 shebang 和许可证头
+    #[arg(long)]
+    pub exclude_headers: bool,
+
+    /// 并行统计使用的线程数（0 表示使用全部可用核心）
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+}
+
+/// `serve` 子命令的参数
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct ServeArgs {
+    /// 监听的地址，默认只接受本机的连接
+    #[arg(long, default_value = "127.0.0.1")]
+    #[serde(skip)]
+    pub host: String,
+
+    /// 监听的端口，0 表示由系统分配
+    #[arg(long, default_value_t = 8080)]
+    #[serde(skip)]
+    pub port: u16,
+
+    /// `GET /count` 可以读取的目录，请求中的路径相对于该目录，不能指向该目录之外
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    #[serde(skip)]
+    pub root: PathBuf,
+
+    /// 从行数统计中排除 shebang 和许可证头
+    #[arg(long)]
+    pub exclude_headers: bool,
+
+    /// 按每个文件语言的注释语法统计注释行数和代码行数
+    #[arg(long)]
+    pub comments: bool,
+
+    /// 单个文件和请求内容的大小上限（支持 K、M、G 等单位），0 表示不限制
+    #[arg(long, value_name = "SIZE", default_value = "100M", value_parser = units::parse_size)]
+    #[serde(skip)]
+    pub max_size: u64,
+
+    /// 统计目录时并行使用的线程数（0 表示使用全部可用核心）
+    #[arg(short, long, default_value_t = 0)]
+    #[serde(skip)]
+    pub jobs: usize,
+}
+
+/// 统计参数
+///
+/// 直接统计和各个子命令共用的参数。
+#[derive(Args, Serialize, Debug, Clone)]
+pub struct CountArgs {
+    /// 要统计的文件或目录，`-` 表示标准输入
+    #[serde(skip)]
+    pub paths: Vec<PathBuf>,
+
+    /// 从文件中读取要统计的路径（每行一个），`-` 表示从标准输入读取；与命令行中的路径合并
+    #[arg(long, value_name = "FILE")]
+    #[serde(skip)]
+    pub files_from: Option<PathBuf>,
+
+    /// `--files-from` 中的路径以 NUL 分隔（对应 `find -print0`），路径可以包含换行符
+    #[arg(short = '0', long, requires = "files_from")]
+    #[serde(skip)]
+    pub null: bool,
+
+    /// 递归统计目录中的所有文件
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// 展开目录时跳过匹配 glob 模式的路径（如 `target/**`、`**/*.min.js`），可重复指定，
+    /// 与配置文件中的 `exclude` 合并生效；命令行直接给出的文件不受影响
+    #[arg(long, value_name = "GLOB", value_parser = PathGlob::parse)]
+    pub exclude: Vec<PathGlob>,
+
+    /// 只统计 Git 仓库中已跟踪的文件（目录通过 `git ls-files` 展开，不需要 -r）
+    #[arg(long)]
+    pub git: bool,
+
+    /// 通过 `git blame` 把每一行归属于最后修改它的作者，输出按作者统计的行数（需要 --git）
+    #[arg(long, requires = "git")]
+    #[serde(skip)]
+    pub by_author: bool,
+
+    /// 遍历目录时跟随符号链接（默认；与 --no-follow 互相覆盖，以最后出现的为准），指向上层目录的循环链接会被跳过
+    #[arg(long, overrides_with = "no_follow")]
+    #[serde(skip)]
+    pub follow_symlinks: bool,
+
+    /// 遍历目录时跳过符号链接（命令行直接给出的符号链接仍然统计其目标）
+    #[arg(long, overrides_with = "follow_symlinks")]
+    pub no_follow: bool,
+
+    /// 只列出应用 --exclude、--git 和 never_read 等规则后将要统计的文件（每行一个），不读取文件内容
+    #[arg(long, conflicts_with = "follow")]
+    #[serde(skip)]
+    pub list: bool,
+
+    /// 并行统计使用的线程数（0 表示使用全部可用核心，遵守 cgroup CPU 配额；可用内存不足 512MB 时为 1）
+    #[arg(short, long, default_value_t = 0)]
+    #[serde(skip)]
+    pub jobs: usize,
+
+    /// 统计引擎，默认根据文件大小、文件系统类型和可用核心数自动选择
+    #[arg(long, value_enum, default_value_t = Engine::Auto)]
+    #[serde(skip)]
+    pub engine: Engine,
+
+    /// 使用内存映射读取文件，等同于 `--engine mmap`
+    #[arg(long, conflicts_with = "engine")]
+    pub mmap: bool,
+
+    /// 区段开始标记（正则表达式），与 --section-end 一起使用时只统计区段内的行
+    #[arg(long, value_name = "REGEX", requires = "section_end")]
+    #[serde(serialize_with = "regex_serde::option")]
+    pub section_start: Option<Regex>,
+
+    /// 区段结束标记（正则表达式）
+    #[arg(long, value_name = "REGEX", requires = "section_start")]
+    #[serde(serialize_with = "regex_serde::option")]
+    pub section_end: Option<Regex>,
+
+    /// 改为统计区段之外的行
+    #[arg(long, requires = "section_start")]
+    pub outside_sections: bool,
+
+    /// 只统计该行号范围内的行（如 `100..500`，包含两端，可省略任意一端）
+    #[arg(long, value_name = "START..END", value_parser = counter::parse_line_range)]
+    pub lines: Option<LineRange>,
+
+    /// 同时统计匹配正则表达式的行数（类似 `grep -c`）
+    #[arg(long = "match", value_name = "REGEX")]
+    #[serde(serialize_with = "regex_serde::option")]
+    pub match_pattern: Option<Regex>,
+
+    /// 改为统计不匹配 --match 的行数（类似 `grep -vc`）
+    #[arg(short = 'v', long, requires = "match_pattern")]
+    pub invert_match: bool,
+
+    /// 正则表达式不区分大小写（与 -s、-S 互相覆盖，以最后出现的为准）
+    #[arg(short = 'i', long, overrides_with_all = ["smart_case", "case_sensitive"])]
+    pub ignore_case: bool,
+
+    /// 智能大小写：模式中没有大写字母时不区分大小写
+    #[arg(short = 'S', long, overrides_with_all = ["ignore_case", "case_sensitive"])]
+    pub smart_case: bool,
+
+    /// 正则表达式区分大小写（默认）
+    #[arg(short = 's', long, overrides_with_all = ["ignore_case", "smart_case"])]
+    #[serde(skip)]
+    pub case_sensitive: bool,
+
+    /// 从行数统计中排除 shebang 和许可证头
+    #[arg(long)]
+    pub exclude_headers: bool,
+
+    /// 输入内容的编码（如 gbk、shift_jis、utf-16le、latin1），默认自动识别
+    #[arg(long, value_name = "ENCODING", value_parser = encoding::parse_label)]
+    #[serde(skip)]
+    pub encoding: Option<&'static Encoding>,
+
+    /// 强制统计二进制文件中的换行符（默认跳过二进制文件）
+    #[arg(long)]
+    pub count_binary: bool,
+
+    /// 将无效的 UTF-8 序列替换为 U+FFFD 后继续统计（默认报错），并报告含有无效序列的行数；
+    /// 没有 BOM 的文本总是按 UTF-8 解码，不再识别为传统编码
+    #[arg(long)]
+    pub lossy: bool,
+
+    /// 按原始字节逐行统计，不识别编码也不校验 UTF-8，只报告行数和最长行的字节数（最快）
+    #[arg(long, conflicts_with_all = [
+        "encoding", "lossy", "engine", "mmap", "section_start", "lines", "match_pattern",
+        "exclude_headers", "line_lengths", "top_lines", "duplicates", "todos", "word_freq",
+        "line_endings", "indentation", "warnings", "comments", "logical", "reading_time", "tokens",
+    ])]
+    pub bytes_mode: bool,
+
+    /// 不透明解压 gzip、zstd、bzip2 和 xz 压缩的文件，按原始字节统计
+    #[arg(long)]
+    pub no_decompress: bool,
+
+    /// 统计最长行在字节、字符和终端显示列宽三种单位下的长度
+    #[arg(long)]
+    pub line_lengths: bool,
+
+    /// 在汇总之后列出按字节数最长的 N 行及其行号和长度
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub top_lines: usize,
+
+    /// 统计与前面某一行内容相同的非空行（重复行）的数量和占比
+    #[arg(long)]
+    pub duplicates: bool,
+
+    /// 配合 `--duplicates` 使用，列出出现次数最多的 N 个重复行
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "duplicates")]
+    pub top_duplicates: usize,
+
+    /// 检测重复行前对行做的规范化：逗号分隔的 nfc、trim、casefold，或 all
+    #[arg(long, value_name = "LIST", value_parser = normalize::parse_list, requires = "duplicates")]
+    pub normalize: Option<Normalization>,
+
+    /// 统计 TODO、FIXME、HACK、XXX 等待办标记在每个文件中出现的次数
+    #[arg(long)]
+    pub todos: bool,
+
+    /// 配合 `--todos` 使用，替换默认的标记（逗号分隔，区分大小写）
+    #[arg(
+        long,
+        value_name = "MARKERS",
+        value_delimiter = ',',
+        default_values_t = todos::DEFAULT_MARKERS.iter().map(|marker| marker.to_string()),
+        requires = "todos"
+    )]
+    pub todo_markers: Vec<String>,
+
+    /// 配合 `--todos` 使用，列出每个标记所在的行号和内容
+    #[arg(long, requires = "todos")]
+    pub list_todos: bool,
+
+    /// 列出出现次数最多的 N 个单词（按 Unicode 单词边界切分，不区分大小写）
+    #[arg(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub word_freq: Option<usize>,
+
+    /// 配合 `--word-freq` 使用，停用词文件（以空白分隔，`#` 之后为注释），其中的单词不计入
+    #[arg(long, value_name = "FILE", requires = "word_freq")]
+    pub stop_words: Option<PathBuf>,
+
+    /// 统计 LF、CRLF 和单独的 CR 三种换行符的数量，并报告主要风格和是否混用
+    #[arg(long)]
+    pub line_endings: bool,
+
+    /// 统计制表符缩进和空格缩进的行数，估计缩进宽度并报告混合缩进的文件
+    #[arg(long)]
+    pub indentation: bool,
+
+    /// 检查文本规范：文件混用换行符、最后一行缺少换行符或混用缩进时输出警告
+    #[arg(long)]
+    pub warnings: bool,
+
+    /// 所有文件的总行数超过该值时以退出码 3 退出
+    #[arg(long, value_name = "N")]
+    #[serde(skip)]
+    pub max_total_lines: Option<usize>,
+
+    /// 任意一个文件的总行数超过该值时以退出码 3 退出
+    #[arg(long, value_name = "N")]
+    #[serde(skip)]
+    pub max_file_lines: Option<usize>,
+
+    /// 空行占比（按行数加权）超过该百分比时以退出码 3 退出
+    #[arg(long, value_name = "PERCENT", value_parser = threshold::parse_percent)]
+    #[serde(skip)]
+    pub max_empty_pct: Option<f64>,
+
+    /// 存在任何文本规范警告时以非零状态退出（需要同时指定 `--warnings`）
+    #[arg(long, requires = "warnings")]
+    #[serde(skip)]
+    pub fail_on_warning: bool,
+
+    /// 存在行尾有空格或制表符的行时以非零状态退出
+    #[arg(long)]
+    #[serde(skip)]
+    pub fail_on_trailing_whitespace: bool,
+
+    /// 总是以零状态退出：错误和检查失败只输出到标准错误（适用于只做报告的定时任务）
+    #[arg(long)]
+    #[serde(skip)]
+    pub no_fail: bool,
+
+    /// 给路径匹配 glob 模式的文件打上标签并按标签分别汇总，格式为 `名称=模式[,模式...]`，可重复指定
+    #[arg(long = "tag", value_name = "NAME=GLOB", value_parser = tags::parse_rule)]
+    pub tags: Vec<TagRule>,
+
+    /// 多文件模式下额外输出按分组合并的汇总：按扩展名（ext）或按目录（dir）
+    #[arg(long, value_enum, value_name = "KEY")]
+    #[serde(skip)]
+    pub group_by: Option<GroupBy>,
+
+    /// 按目录分组时保留的目录层数（1 表示顶层目录）
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "group_by", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    #[serde(skip)]
+    pub depth: usize,
+
+    /// 输出按字节数和行数计算的语言分布（与 GitHub linguist 的语言条一致）
+    #[arg(long)]
+    #[serde(skip)]
+    pub languages: bool,
+
+    /// 自定义语言文件（TOML），声明内置语言表之外的语言及其扩展名和注释语法
+    #[arg(long, value_name = "FILE")]
+    pub languages_file: Option<PathBuf>,
+
+    /// 按文件语言的注释语法统计注释行和代码行
+    #[arg(long)]
+    pub comments: bool,
+
+    /// 按文件语言的续行规则（行尾的 `\`、没有闭合的括号）合并续行，同时统计物理行数和逻辑行数
+    #[arg(long)]
+    pub logical: bool,
+
+    /// 统计模式：auto 为 .csv/.tsv 文件统计记录数、列数和空字段，csv/tsv 对所有文件按该格式统计，
+    /// jsonl 统计 JSON Lines 的记录、无效行和空行，json 检查文件是否为单个合法的 JSON 文档，lines 只统计行数
+    #[arg(long, value_enum, default_value_t = RecordMode::Auto)]
+    pub mode: RecordMode,
+
+    /// 为散文类文件（Markdown、纯文本）统计单词数并估计阅读时间
+    #[arg(long)]
+    pub reading_time: bool,
+
+    /// 估计阅读时间使用的阅读速度（每分钟单词数）
+    #[arg(long, value_name = "N", default_value_t = report::DEFAULT_WPM, requires = "reading_time", value_parser = RangedU64ValueParser::<u64>::new().range(1..))]
+    pub wpm: u64,
+
+    /// 统计每个文件的大语言模型 token 数
+    #[arg(long)]
+    pub tokens: bool,
+
+    /// 统计 token 数使用的分词方式：approx 按字符类别估计，cl100k、o200k 使用 BPE 词表（需要 tiktoken 特性）
+    #[arg(long, value_enum, default_value_t = Tokenizer::Approx, requires = "tokens")]
+    pub tokenizer: Tokenizer,
+
+    /// 许可证头识别模式（正则表达式，可重复指定，覆盖内置模式）
+    #[arg(long = "license-pattern", value_name = "REGEX")]
+    #[serde(serialize_with = "regex_serde::vec")]
+    pub license_patterns: Vec<Regex>,
+
+    /// 标准输入内容使用的伪文件名（如 foo.rs），用于报告路径和按文件名分类
+    #[arg(long, value_name = "NAME")]
+    #[serde(skip)]
+    pub stdin_name: Option<PathBuf>,
+
+    /// 流式模式：不限制文件大小，以恒定内存读取任意大的文件或无界的标准输入，
+    /// 并每秒向标准错误输出一次中间结果
+    #[arg(long)]
+    #[serde(skip)]
+    pub stream: bool,
+
+    /// 跟踪模式：像 tail -f 一样读到文件末尾后继续统计新追加的行，每秒向标准错误输出总行数和增长速度；
+    /// 只能用于单个文件，到达 --deadline、文件被删除或截断时停止并输出统计结果
+    #[arg(long, conflicts_with_all = ["recursive", "git", "files_from"])]
+    #[serde(skip)]
+    pub follow: bool,
+
+    /// 单个文件的大小上限（支持 K、M、G 等单位），0 表示不限制
+    #[arg(long, value_name = "SIZE", default_value = "100M", value_parser = units::parse_size)]
+    #[serde(skip)]
+    pub max_size: u64,
+
+    /// 单个 URL 下载的大小上限（支持 K、M、G 等单位），0 表示不限制；超过时停止下载并报错（需要 `http` 特性）
+    #[arg(long, value_name = "SIZE", default_value = "1G", value_parser = units::parse_size)]
+    #[serde(skip)]
+    pub max_download: u64,
+
+    /// 解压缩内容时单个文件解压后的大小上限（支持 K、M、G 等单位），0 表示不限制；超过时跳过该文件
+    #[arg(long, value_name = "SIZE", default_value = "1G", value_parser = units::parse_size)]
+    #[serde(skip)]
+    pub max_decompressed_size: u64,
+
+    /// 解压缩内容时单个文件的压缩比上限，0 表示不限制；超过时跳过该文件
+    #[arg(long, value_name = "RATIO", default_value_t = DEFAULT_MAX_RATIO)]
+    #[serde(skip)]
+    pub max_compression_ratio: f64,
+
+    /// 读取缓冲区大小（支持 K、M 等单位，如 64K）；auto（默认）根据文件大小和文件系统块大小自动选择，
+    /// 标准输入和命名管道等没有预先可知大小的输入使用 64K
+    #[arg(long, value_name = "SIZE", default_value = "auto", value_parser = BufferSize::parse)]
+    #[serde(skip)]
+    pub buffer_size: BufferSize,
+
+    /// 统计的时间预算（如 `30s`、`5m`）：到达截止时间后不再开始统计新的文件，
+    /// 输出已完成的结果并标记为不完整
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    #[serde(skip)]
+    pub deadline: Option<Duration>,
+
+    /// 将不存在的输入路径报告为跳过，而不是使整个命令失败
+    #[arg(long)]
+    #[serde(skip)]
+    pub ignore_missing: bool,
+
+    /// 任意文件统计失败时立即停止；默认统计多个文件时记录失败的文件并继续统计其余文件，最后汇总错误
+    #[arg(long)]
+    #[serde(skip)]
+    pub strict: bool,
+
+    /// 不显示进度条
+    #[arg(long)]
+    #[serde(skip)]
+    pub no_progress: bool,
+
+    /// 不读取也不写入统计结果缓存
+    #[arg(long)]
+    #[serde(skip)]
+    pub no_cache: bool,
+
+    /// 增量统计：只重新统计发生变化的文件，其余文件直接使用缓存的结果
+    #[arg(long, conflicts_with = "no_cache")]
+    #[serde(skip)]
+    pub changed_only: bool,
+
+    /// 增量统计的参照点：时间（RFC 3339）或 Git 版本，默认为缓存的结果统计的时间
+    #[arg(long, value_name = "TIME|REV", requires = "changed_only", value_parser = Since::parse)]
+    #[serde(skip)]
+    pub since: Option<Since>,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    #[serde(skip)]
+    pub format: OutputFormat,
+
+    /// 面向屏幕阅读器的文本输出：不使用表情符号、进度条和对齐用的空白，指标使用完整的词语
+    #[arg(long)]
+    #[serde(skip)]
+    pub accessible: bool,
+
+    /// 不输出表情符号，检查结果的标记换成文字（区域设置不是 UTF-8 时自动生效）
+    #[arg(long)]
+    #[serde(skip)]
+    pub no_emoji: bool,
+
+    /// 可读文本中的大小按 1024 进制换算（如 `1.4 MiB`），较大的数量带千位分隔符或简写（如 `12.3k`）；
+    /// 其他输出格式仍为原始整数
+    #[arg(long)]
+    #[serde(skip)]
+    pub human: bool,
+
+    /// 百分比保留的小数位数，适用于可读文本、Markdown 和 `key=value` 输出
+    /// （默认分别为 1、1 和 2 位；JSON 中为原始的浮点数）
+    #[arg(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(0..=10))]
+    #[serde(skip)]
+    pub precision: Option<usize>,
+
+    /// 输出语言，由 `--lang` 或区域设置决定
+    #[arg(skip)]
+    #[serde(skip)]
+    pub lang: Lang,
+
+    /// 何时输出颜色：auto 只在输出流是支持颜色的终端时（重定向到文件或管道时不输出），
+    /// 颜色可以用环境变量 LINE_COUNTER_COLORS 定制
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = color::When::Auto)]
+    #[serde(skip)]
+    pub color: color::When,
+
+    /// 何时把文件路径渲染为终端超链接（OSC 8）：auto 只在终端支持时
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = hyperlink::When::Auto)]
+    #[serde(skip)]
+    pub hyperlinks: hyperlink::When,
+
+    /// 超链接地址模板，可用变量 {path}、{line}、{column}、{host}，
+    /// 或内置别名 default、file、vscode、vscode-insiders、vscodium、cursor、kitty、textmate、none
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "default",
+        value_parser = hyperlink::parse_format
+    )]
+    #[serde(skip)]
+    pub hyperlink_format: HyperlinkFormat,
+
+    /// 多文件结果的排序方式（默认按输入顺序）：行数、大小、空行占比从大到小，路径按字典序
+    #[arg(long, value_enum, value_name = "KEY")]
+    #[serde(skip)]
+    pub sort: Option<SortKey>,
+
+    /// 反转排序方向（不指定 `--sort` 时反转输入顺序）
+    #[arg(long)]
+    #[serde(skip)]
+    pub reverse: bool,
+
+    /// 在工作区根目录写入紧凑的摘要文件 `.lc-summary.json`，供编辑器插件读取
+    #[arg(long)]
+    #[serde(skip)]
+    pub emit_summary: bool,
+
+    /// 把报告写入文件而不是标准输出（先写入临时文件再重命名），提示信息改为输出到标准错误
+    #[arg(long, value_name = "FILE")]
+    #[serde(skip)]
+    pub output: Option<PathBuf>,
+
+    /// 把结果追加写入 SQLite 数据库（每次运行的每个文件一行，带有运行时间），不存在时自动创建
+    #[arg(long, value_name = "FILE")]
+    #[serde(skip)]
+    pub output_db: Option<PathBuf>,
+
+    /// 基线报告（`--format json` 的输出），行数增长超过容差时以非零状态退出
+    #[arg(long, value_name = "FILE")]
+    #[serde(skip)]
+    pub baseline: Option<PathBuf>,
+
+    /// 相对基线允许的增长百分比
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        default_value_t = 0.0,
+        requires = "baseline"
+    )]
+    #[serde(skip)]
+    pub tolerance: f64,
+
+    /// 配置文件中永远不读取的路径，由 [`crate::app::run`] 在解析参数后填入
+    #[arg(skip)]
+    #[serde(skip)]
+    pub never_read: NeverRead,
+
+    /// `--languages-file` 中的自定义语言，由 [`crate::app::run`] 在解析参数后填入
+    #[arg(skip)]
+    #[serde(skip)]
+    pub custom_languages: Languages,
+
+    /// `--stop-words` 中的停用词，由 [`crate::app::run`] 在解析参数后填入
+    #[arg(skip)]
+    #[serde(skip)]
+    pub stop_word_list: StopWords,
+}
+
+impl CountArgs {
+    /// 命令行指定的大小写匹配方式
+    pub fn case_mode(&self) -> CaseMode {
+        if self.ignore_case {
+            CaseMode::Insensitive
+        } else if self.smart_case {
+            CaseMode::Smart
+        } else {
+            CaseMode::Sensitive
+        }
+    }
+
+    /// 命令行指定的符号链接处理方式
+    pub fn symlinks(&self) -> SymlinkPolicy {
+        if self.no_follow {
+            SymlinkPolicy::NoFollow
+        } else {
+            SymlinkPolicy::Follow
+        }
+    }
+
+    /// 根据命令行参数构造分组设置，未指定 `--group-by` 时为 `None`
+    pub fn grouping(&self) -> Option<Grouping> {
+        self.group_by.map(|by| Grouping {
+            by,
+            depth: self.depth,
+            roots: self.paths.clone(),
+        })
+    }
+
+    /// 命令行指定的标准输出的可读文本风格
+    pub fn style(&self) -> Style {
+        self.style_for(Stream::Stdout)
+    }
+
+    /// 命令行指定的标准错误（警告）的可读文本风格
+    pub fn stderr_style(&self) -> Style {
+        self.style_for(Stream::Stderr)
+    }
+
+    /// 统计报告的可读文本风格：指定 `--output` 时文件不是终端，只在指定 `always` 时输出超链接和颜色
+    pub fn report_style(&self) -> Style {
+        if self.output.is_none() {
+            return self.style();
+        }
+        let style = Style::from_options(self.accessible, self.no_emoji, self.lang);
+        Style {
+            hyperlinks: match self.hyperlinks {
+                hyperlink::When::Auto => None,
+                when => Hyperlinks::enabled(when, &self.hyperlink_format, Stream::Stdout),
+            },
+            colors: match self.color {
+                color::When::Auto => None,
+                when => Theme::enabled(when, Stream::Stdout),
+            },
+            human: self.human,
+            precision: self.precision.unwrap_or(DEFAULT_PRECISION),
+            ..style
+        }
+    }
+
+    /// 命令行指定的可读文本风格，是否输出超链接和颜色取决于输出流是否支持
+    fn style_for(&self, stream: Stream) -> Style {
+        let style = Style::from_options(self.accessible, self.no_emoji, self.lang);
+        Style {
+            hyperlinks: Hyperlinks::enabled(self.hyperlinks, &self.hyperlink_format, stream),
+            colors: Theme::enabled(self.color, stream),
+            human: self.human,
+            precision: self.precision.unwrap_or(DEFAULT_PRECISION),
+            ..style
+        }
+    }
+
+    /// 根据命令行参数构造阈值设置
+    pub fn thresholds(&self) -> Thresholds {
+        Thresholds {
+            max_total_lines: self.max_total_lines,
+            max_file_lines: self.max_file_lines,
+            max_empty_percent: self.max_empty_pct,
+        }
+    }
+
+    /// 根据命令行参数构造解压缩限制
+    pub fn decompression_limits(&self) -> DecompressionLimits {
+        DecompressionLimits {
+            max_size: self.max_decompressed_size,
+            max_ratio: self.max_compression_ratio,
+        }
+    }
+
+    /// 根据命令行参数构造统计选项
+    pub fn count_options(&self) -> CountOptions {
+        let case_mode = self.case_mode();
+        let section = match (&self.section_start, &self.section_end) {
+            (Some(start), Some(end)) => Some(SectionFilter {
+                start: case_mode.apply(start),
+                end: case_mode.apply(end),
+                outside: self.outside_sections,
+            }),
+            _ => None,
+        };
+
+        let mut headers = HeaderOptions {
+            exclude: self.exclude_headers,
+            ..HeaderOptions::default()
+        };
+        if !self.license_patterns.is_empty() {
+            headers.license_patterns = self
+                .license_patterns
+                .iter()
+                .map(|pattern| case_mode.apply(pattern))
+                .collect();
+        }
+
+        CountOptions {
+            engine: if self.mmap { Engine::Mmap } else { self.engine },
+            section,
+            lines: self.lines,
+            line_match: self.match_pattern.as_ref().map(|pattern| LineMatch {
+                pattern: case_mode.apply(pattern),
+                invert: self.invert_match,
+            }),
+            headers,
+            binary: self.count_binary,
+            lossy: self.lossy,
+            bytes: self.bytes_mode,
+            line_lengths: self.line_lengths,
+            line_endings: self.line_endings || self.warnings,
+            indentation: self.indentation || self.warnings,
+            top_lines: self.top_lines,
+            duplicates: self.duplicates.then(|| DuplicateOptions {
+                top: self.top_duplicates,
+                normalization: self.normalize.unwrap_or_default(),
+            }),
+            todos: self
+                .todos
+                .then(|| TodoOptions::new(&self.todo_markers, self.list_todos)),
+            // 注释语法、续行规则、记录格式和是否统计单词数取决于每个文件的语言，由 [`crate::app::count_file`] 逐个文件填入
+            comments: None,
+            logical: None,
+            words: false,
+            records: None,
+            word_freq: self.word_freq.map(|top| WordFreqOptions {
+                top,
+                stop_words: self.stop_word_list.clone(),
+            }),
+            tokens: self.tokens.then_some(self.tokenizer),
+        }
+    }
+}
+
+/// 正则表达式字段的序列化辅助函数（序列化为模式字符串）
+mod regex_serde {
+    use regex::Regex;
+    use serde::{Serialize, Serializer};
+
+    pub fn option<S: Serializer>(value: &Option<Regex>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.as_ref().map(Regex::as_str).serialize(serializer)
+    }
+
+    pub fn vec<S: Serializer>(value: &[Regex], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(Regex::as_str))
+    }
+}
+
+/// 打印使用帮助信息
+///
+/// # 参数
+///
+/// * `program_name` - 程序名称
+/// * `style` - 输出风格
+pub fn print_usage_help(program_name: &str, style: &Style) {
+    eprintln!(
+        "{}{}",
+        style.icon("❌ "),
+        style.tr("错误: 缺少文件路径参数")
+    );
+    eprintln!(
+        "{}{}",
+        style.icon("📖 "),
+        style.format("用法: {} <文件路径>...", &[&program_name])
+    );
+    eprintln!(
+        "{}{}",
+        style.icon("💡 "),
+        style.format("示例: {} example.txt", &[&program_name])
+    );
+    eprintln!(
+        "{}{}",
+        style.icon("💡 "),
+        style.format("示例: {} -r src/", &[&program_name])
+    );
+}
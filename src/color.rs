@@ -0,0 +1,180 @@
+//! 终端颜色
+//!
+//! 可读文本输出中的标题、警告和超过阈值的数值使用 ANSI 颜色突出显示。
+//! `--color` 控制何时输出颜色：`auto`（默认）只在输出流是支持颜色的终端时输出
+//! （遵循 `NO_COLOR`、`FORCE_COLOR` 和 `TERM=dumb` 等约定），`always` 总是输出，`never` 从不输出。
+//! 输出被重定向到文件或管道时，`auto` 不输出颜色，保证结果可以直接被其他程序处理。
+//!
+//! 颜色可以用环境变量 `LINE_COUNTER_COLORS` 定制，格式与 `GREP_COLORS` 类似：
+//! 用 `:` 分隔的 `名称=SGR 参数` 列表，如 `heading=1;34:error=1;35`。
+//! 名称为 `heading`（标题）、`warning`（警告和跳过的文件）、`error`（超限的项目）
+//! 和 `success`（通过的检查），值为空时不给该类文本着色；无法识别的项被忽略。
+
+use crate::hyperlink::Stream;
+use clap::ValueEnum;
+use std::fmt::Display;
+
+/// 定制颜色的环境变量
+pub const COLORS_ENV: &str = "LINE_COUNTER_COLORS";
+
+/// 何时输出颜色
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum When {
+    /// 输出流是支持颜色的终端时
+    #[default]
+    Auto,
+    /// 总是输出
+    Always,
+    /// 从不输出
+    Never,
+}
+
+/// 文本的用途，决定使用的颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// 标题（如“汇总完成!”、“语言分布:”）
+    Heading,
+    /// 警告和跳过的文件
+    Warning,
+    /// 超过阈值或基线的项目
+    Error,
+    /// 通过的检查
+    Success,
+}
+
+/// 每种用途的 SGR 参数（如 `1;31`），空字符串表示不着色
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    heading: String,
+    warning: String,
+    error: String,
+    success: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            heading: "1;36".to_string(),
+            warning: "33".to_string(),
+            error: "1;31".to_string(),
+            success: "32".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    /// 在默认颜色的基础上应用定制
+    ///
+    /// # 参数
+    ///
+    /// * `spec` - `LINE_COUNTER_COLORS` 格式的定制，如 `heading=1;34:error=1;35`
+    ///
+    /// # 返回值
+    ///
+    /// 应用了定制的颜色；名称未知或参数不是 `;` 分隔的数字的项被忽略
+    pub fn parse(spec: &str) -> Self {
+        let mut theme = Self::default();
+        for entry in spec.split(':') {
+            let Some((name, sgr)) = entry.split_once('=') else {
+                continue;
+            };
+            let valid = sgr
+                .split(';')
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+            if !sgr.is_empty() && !valid {
+                continue;
+            }
+            let slot = match name.trim() {
+                "heading" => &mut theme.heading,
+                "warning" => &mut theme.warning,
+                "error" => &mut theme.error,
+                "success" => &mut theme.success,
+                _ => continue,
+            };
+            *slot = sgr.to_string();
+        }
+        theme
+    }
+
+    /// 读取 `LINE_COUNTER_COLORS`，未设置时为默认颜色
+    pub fn from_env() -> Self {
+        std::env::var(COLORS_ENV)
+            .map(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+
+    /// 根据选项和输出流确定是否输出颜色
+    ///
+    /// # 参数
+    ///
+    /// * `when` - `--color` 选项
+    /// * `stream` - 文本所在的输出流
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(Theme)` - 需要输出颜色
+    /// * `None` - 不输出颜色
+    pub fn enabled(when: When, stream: Stream) -> Option<Self> {
+        let enabled = match when {
+            When::Always => true,
+            When::Never => false,
+            When::Auto => supports_color::on_cached(match stream {
+                Stream::Stdout => supports_color::Stream::Stdout,
+                Stream::Stderr => supports_color::Stream::Stderr,
+            })
+            .is_some_and(|level| level.has_basic),
+        };
+        enabled.then(Self::from_env)
+    }
+
+    /// 给文本着色
+    ///
+    /// # 参数
+    ///
+    /// * `role` - 文本的用途
+    /// * `text` - 文本
+    ///
+    /// # 返回值
+    ///
+    /// 包含 SGR 转义序列的文本；该用途不着色时为原文本
+    pub fn paint(&self, role: Role, text: impl Display) -> String {
+        let sgr = match role {
+            Role::Heading => &self.heading,
+            Role::Warning => &self.warning,
+            Role::Error => &self.error,
+            Role::Success => &self.success,
+        };
+        if sgr.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", sgr, text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试颜色定制的解析
+    #[test]
+    fn test_parse() {
+        assert_eq!(Theme::parse(""), Theme::default());
+
+        let theme = Theme::parse("heading=1;34:error=:bogus=1:warning=red:success=38;5;10");
+        assert_eq!(theme.heading, "1;34");
+        assert_eq!(theme.error, "");
+        assert_eq!(theme.warning, Theme::default().warning);
+        assert_eq!(theme.success, "38;5;10");
+    }
+
+    /// 测试着色与关闭颜色
+    #[test]
+    fn test_paint() {
+        let theme = Theme::parse("error=");
+        assert_eq!(theme.paint(Role::Heading, "汇总"), "\x1b[1;36m汇总\x1b[0m");
+        assert_eq!(theme.paint(Role::Error, 42), "42");
+        assert_eq!(Theme::enabled(When::Never, Stream::Stdout), None);
+        assert!(Theme::enabled(When::Always, Stream::Stderr).is_some());
+    }
+}
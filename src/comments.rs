@@ -0,0 +1,370 @@
+//! 注释行与代码行的分类
+//!
+//! 按语言的注释语法（[`CommentSyntax`]）逐行判断一行是空行、注释行还是代码行：
+//!
+//! - 只包含注释（行注释、块注释或块注释的一部分）的行为注释行
+//! - 包含任何注释之外内容的行为代码行，如 `let x = 1; // 说明`
+//! - 只有空白的行为空行，块注释和字符串中的空白行同样是空行
+//!
+//! 字符串中的注释标记不开始注释（`"http://example.com"`），字符串和块注释可以跨越多行，
+//! 字符串中的 `\` 转义下一个字符。块注释不支持嵌套。
+//!
+//! 分类的同时按语言的续行规则（[`Continuation`]）判断一行是否延续到下一行，
+//! `--logical` 把延续的行合并为一个逻辑行：
+//!
+//! - 代码以续行符结尾的行（Shell 和 C 的 `\`），续行符之后可以有行注释
+//! - 括号（`(`、`[`、`{`）没有闭合的行（Python、TOML），字符串和注释中的括号不计入
+//! - 有续行规则的语言中，没有结束的字符串（如 Python 的 `"""`）同样延续到下一行
+
+/// 语言的注释语法
+///
+/// 块注释的开始标记先于行注释匹配（Lua 的 `--[[` 与 `--`），
+/// 同类标记按声明的顺序匹配，较长的标记（如 `"""`）应当放在较短的标记（`"`）之前。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommentSyntax {
+    /// 行注释的开始标记（如 `//`、`#`）
+    pub line: &'static [&'static str],
+    /// 块注释的开始和结束标记（如 `("/*", "*/")`）
+    pub block: &'static [(&'static str, &'static str)],
+    /// 字符串的定界符（开始和结束相同，如 `"`、`'`、`"""`）
+    pub strings: &'static [&'static str],
+    /// 续行规则
+    pub continuation: Continuation,
+}
+
+impl CommentSyntax {
+    /// 没有任何注释语法
+    pub const NONE: CommentSyntax = CommentSyntax {
+        line: &[],
+        block: &[],
+        strings: &[],
+        continuation: Continuation::NONE,
+    };
+
+    /// 是否没有任何注释标记（此时无法区分注释行和代码行）
+    pub fn is_empty(&self) -> bool {
+        self.line.is_empty() && self.block.is_empty()
+    }
+}
+
+/// 语言的续行规则
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Continuation {
+    /// 行尾的续行符（如 `\`、PowerShell 的 `` ` ``），`None` 表示没有
+    pub marker: Option<&'static str>,
+    /// 没有闭合的括号是否延续到下一行
+    pub brackets: bool,
+}
+
+impl Continuation {
+    /// 没有续行规则
+    pub const NONE: Continuation = Continuation {
+        marker: None,
+        brackets: false,
+    };
+
+    /// 以 `\` 续行（Shell、C 预处理指令、Makefile 等）
+    pub const BACKSLASH: Continuation = Continuation {
+        marker: Some("\\"),
+        brackets: false,
+    };
+
+    /// 是否没有任何续行规则（此时每个物理行都是一个逻辑行）
+    pub fn is_empty(&self) -> bool {
+        self.marker.is_none() && !self.brackets
+    }
+}
+
+/// 一行的分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// 只有空白的行
+    Blank,
+    /// 只包含注释的行
+    Comment,
+    /// 包含代码的行
+    Code,
+}
+
+/// 跨行的分类状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// 代码中
+    Code,
+    /// 块注释中，值为块注释结束标记
+    Block(&'static str),
+    /// 字符串中，值为字符串定界符
+    String(&'static str),
+}
+
+/// 逐行分类器
+///
+/// 按顺序对文件的每一行调用 [`Classifier::classify`]，块注释、字符串和括号的状态在行之间保留。
+#[derive(Debug, Clone)]
+pub struct Classifier {
+    syntax: CommentSyntax,
+    state: State,
+    /// 没有闭合的括号数（仅在续行规则包含括号时统计）
+    depth: usize,
+    /// 上一次分类的行是否延续到下一行
+    continues: bool,
+}
+
+impl Classifier {
+    /// 创建分类器
+    ///
+    /// # 参数
+    ///
+    /// * `syntax` - 语言的注释语法
+    pub fn new(syntax: CommentSyntax) -> Self {
+        Self {
+            syntax,
+            state: State::Code,
+            depth: 0,
+            continues: false,
+        }
+    }
+
+    /// 上一次分类的行是否按续行规则延续到下一行
+    pub fn continues(&self) -> bool {
+        self.continues
+    }
+
+    /// 对下一行分类
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 一行内容（不含换行符）
+    ///
+    /// # 返回值
+    ///
+    /// 这一行的分类
+    pub fn classify(&mut self, line: &str) -> LineKind {
+        if line.trim().is_empty() {
+            self.continues = self.is_open();
+            return LineKind::Blank;
+        }
+
+        let mut code = false;
+        let mut comment = false;
+        // 最后一段代码的结束位置，用于判断代码是否以续行符结尾
+        let mut code_end = 0;
+        let mut rest = line;
+        while !rest.is_empty() {
+            match self.state {
+                State::Block(end) => {
+                    comment = true;
+                    match rest.find(end) {
+                        Some(index) => {
+                            rest = &rest[index + end.len()..];
+                            self.state = State::Code;
+                        }
+                        None => break,
+                    }
+                }
+                State::String(delimiter) => {
+                    code = true;
+                    match find_string_end(rest, delimiter) {
+                        Some(index) => {
+                            rest = &rest[index + delimiter.len()..];
+                            self.state = State::Code;
+                            code_end = line.len() - rest.len();
+                        }
+                        None => break,
+                    }
+                }
+                State::Code => {
+                    rest = rest.trim_start();
+                    if rest.is_empty() {
+                        break;
+                    }
+                    if let Some((start, end)) = self
+                        .syntax
+                        .block
+                        .iter()
+                        .find(|(start, _)| rest.starts_with(start))
+                    {
+                        rest = &rest[start.len()..];
+                        self.state = State::Block(end);
+                        continue;
+                    }
+                    if self.syntax.line.iter().any(|start| rest.starts_with(start)) {
+                        comment = true;
+                        break;
+                    }
+                    code = true;
+                    if let Some(delimiter) = self
+                        .syntax
+                        .strings
+                        .iter()
+                        .find(|delimiter| rest.starts_with(*delimiter))
+                    {
+                        rest = &rest[delimiter.len()..];
+                        self.state = State::String(delimiter);
+                        continue;
+                    }
+                    let next = rest.chars().next().unwrap_or_default();
+                    if self.syntax.continuation.brackets {
+                        match next {
+                            '(' | '[' | '{' => self.depth += 1,
+                            ')' | ']' | '}' => self.depth = self.depth.saturating_sub(1),
+                            _ => {}
+                        }
+                    }
+                    rest = &rest[next.len_utf8()..];
+                    code_end = line.len() - rest.len();
+                }
+            }
+        }
+
+        let marker = self
+            .syntax
+            .continuation
+            .marker
+            .filter(|_| self.state == State::Code)
+            .is_some_and(|marker| line[..code_end].ends_with(marker));
+        self.continues = marker || self.is_open();
+
+        if code {
+            LineKind::Code
+        } else if comment {
+            LineKind::Comment
+        } else {
+            LineKind::Blank
+        }
+    }
+
+    /// 是否有没有闭合的括号或没有结束的字符串（仅在语言有续行规则时）
+    fn is_open(&self) -> bool {
+        !self.syntax.continuation.is_empty()
+            && (self.depth > 0 || matches!(self.state, State::String(_)))
+    }
+}
+
+/// 查找字符串的结束定界符，跳过 `\` 转义的字符
+fn find_string_end(text: &str, delimiter: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if text[index..].starts_with(delimiter) {
+            return Some(index);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const C: CommentSyntax = CommentSyntax {
+        line: &["//"],
+        block: &[("/*", "*/")],
+        strings: &["\""],
+        continuation: Continuation::NONE,
+    };
+
+    /// 对每一行分类
+    fn classify(syntax: CommentSyntax, text: &str) -> Vec<LineKind> {
+        let mut classifier = Classifier::new(syntax);
+        text.lines().map(|line| classifier.classify(line)).collect()
+    }
+
+    /// 测试行注释、块注释与代码行的判断
+    #[test]
+    fn test_classify() {
+        use LineKind::*;
+        let text = "// 说明\nint x = 1; // 尾注释\n\n/* 块注释\n\n   继续 */\n/* a */ int y; /* b */\n  /* c */ /* d */\n";
+        assert_eq!(
+            classify(C, text),
+            vec![Comment, Code, Blank, Comment, Blank, Comment, Code, Comment]
+        );
+    }
+
+    /// 测试字符串中的注释标记和转义
+    #[test]
+    fn test_strings() {
+        use LineKind::*;
+        let text = "url = \"http://example.com\";\ns = \"a \\\" // b\";\nt = \"多行\n// 仍在字符串中\n\";\n// 注释\n";
+        assert_eq!(
+            classify(C, text),
+            vec![Code, Code, Code, Code, Code, Comment]
+        );
+
+        let python = CommentSyntax {
+            line: &["#"],
+            block: &[],
+            strings: &["\"\"\"", "\"", "'"],
+            continuation: Continuation::NONE,
+        };
+        let text = "\"\"\"文档\n\n# 不是注释\n\"\"\"\nx = '#' # 注释\n# 注释\n";
+        assert_eq!(
+            classify(python, text),
+            vec![Code, Blank, Code, Code, Code, Comment]
+        );
+    }
+
+    /// 测试按续行符、没有闭合的括号和没有结束的字符串判断续行
+    #[test]
+    fn test_continuation() {
+        let continues = |syntax: CommentSyntax, text: &str| -> Vec<bool> {
+            let mut classifier = Classifier::new(syntax);
+            text.lines()
+                .map(|line| {
+                    classifier.classify(line);
+                    classifier.continues()
+                })
+                .collect()
+        };
+
+        let shell = CommentSyntax {
+            line: &["#"],
+            block: &[],
+            strings: &["\"", "'"],
+            continuation: Continuation::BACKSLASH,
+        };
+        let text = "cp a \\\n  b \\ # 注释\n  c\n# 不是续行 \\\necho \"a\\\\\"\n";
+        assert_eq!(
+            continues(shell, text),
+            vec![true, true, false, false, false]
+        );
+
+        let python = CommentSyntax {
+            line: &["#"],
+            block: &[],
+            strings: &["\"\"\"", "\"", "'"],
+            continuation: Continuation {
+                marker: Some("\\"),
+                brackets: true,
+            },
+        };
+        let text = "x = f(1,\n\n      ')' # (\n      )\ns = \"\"\"文档\n\"\"\"\n";
+        assert_eq!(
+            continues(python, text),
+            vec![true, true, true, false, true, false]
+        );
+
+        // 没有续行规则时，多行字符串也不延续
+        assert_eq!(
+            continues(C, "a = (1,\ns = \"多行\n\";\n"),
+            vec![false, false, false]
+        );
+        assert!(Continuation::NONE.is_empty());
+        assert!(!Continuation::BACKSLASH.is_empty());
+    }
+
+    /// 测试没有注释语法时只区分空行和代码行
+    #[test]
+    fn test_no_syntax() {
+        use LineKind::*;
+        assert!(CommentSyntax::NONE.is_empty());
+        assert!(!C.is_empty());
+        assert_eq!(
+            classify(CommentSyntax::NONE, "// x\n\n/* y */\n"),
+            vec![Code, Blank, Code]
+        );
+    }
+}
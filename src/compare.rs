@@ -0,0 +1,276 @@
+//! 目录比较
+//!
+//! `line-counter compare <旧目录> <新目录>` 递归统计两个目录，按相对路径匹配文件，
+//! 输出新增、删除和修改的文件数以及整体的行数变化；`--detail` 还会逐个列出有变化的文件。
+//! 行数统计和文件大小都相同的文件视为未变化，不单独列出；
+//! 使用 `--ignore-blank-changes` 时只比较非空行数，只增删了空行的文件同样视为未变化。
+
+use crate::app::{ReadOptions, count_files};
+use crate::config::NeverRead;
+use crate::counter::CountOptions;
+use crate::diff::StatsDiff;
+use crate::report::{FileReport, Summary};
+use crate::stats::LineStats;
+use crate::walk::{SymlinkPolicy, collect_files};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 文件在两个目录之间的变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileStatus {
+    /// 只存在于新目录
+    Added,
+    /// 只存在于旧目录
+    Removed,
+    /// 两边都存在，行数或大小不同
+    Changed,
+}
+
+impl fmt::Display for FileStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FileStatus::Added => "新增",
+            FileStatus::Removed => "删除",
+            FileStatus::Changed => "修改",
+        })
+    }
+}
+
+/// 单个文件的比较结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileDiff {
+    /// 相对于目录根的路径
+    pub path: PathBuf,
+    /// 变化类型
+    pub status: FileStatus,
+    /// 行数变化；新增文件的旧统计和删除文件的新统计为空
+    pub stats: StatsDiff,
+    /// 文件大小的变化（字节）
+    pub bytes: i64,
+}
+
+/// 两个目录的比较结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DirDiff {
+    /// 新增的文件数
+    pub added: usize,
+    /// 删除的文件数
+    pub removed: usize,
+    /// 修改的文件数
+    pub changed: usize,
+    /// 未变化的文件数
+    pub unchanged: usize,
+    /// 整体的行数变化
+    pub total: StatsDiff,
+    /// 有变化的文件，按相对路径排列；不需要逐个列出时为空
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileDiff>,
+}
+
+/// 递归统计目录中的所有文件
+///
+/// # 参数
+///
+/// * `dir` - 目录
+/// * `options` - 统计选项
+/// * `jobs` - 并行统计使用的线程数，0 表示使用全部可用核心
+/// * `never_read` - 配置中永远不读取的路径，匹配的文件被跳过
+///
+/// # 返回值
+///
+/// * `Ok(Vec<FileReport>)` - 按路径排列的统计报告
+/// * `Err(anyhow::Error)` - 目录无法遍历或文件无法统计
+pub fn count_dir(
+    dir: &Path,
+    options: &CountOptions,
+    jobs: usize,
+    never_read: &NeverRead,
+) -> Result<Vec<FileReport>> {
+    let mut files = collect_files(&[dir.to_path_buf()], true, SymlinkPolicy::Follow)
+        .with_context(|| format!("无法遍历目录 '{}'", dir.display()))?;
+    files.retain(|path| never_read.matching(path).is_none());
+    count_files(&files, jobs, options, &ReadOptions::default())
+}
+
+/// 按相对路径比较两个目录的统计报告
+///
+/// # 参数
+///
+/// * `old_root` - 旧目录
+/// * `old` - 旧目录中文件的统计报告
+/// * `new_root` - 新目录
+/// * `new` - 新目录中文件的统计报告
+/// * `ignore_blank` - 忽略只涉及空行的变化（见 [`StatsDiff::ignore_blank_changes`]）
+///
+/// # 返回值
+///
+/// 比较结果，`files` 中包含所有有变化的文件
+pub fn compare(
+    old_root: &Path,
+    old: &[FileReport],
+    new_root: &Path,
+    new: &[FileReport],
+    ignore_blank: bool,
+) -> DirDiff {
+    let relative = |root: &Path, reports: &[FileReport]| -> BTreeMap<PathBuf, (u64, LineStats)> {
+        reports
+            .iter()
+            .map(|report| {
+                let path = report.path.strip_prefix(root).unwrap_or(&report.path);
+                (path.to_path_buf(), (report.size, report.stats.clone()))
+            })
+            .collect()
+    };
+    let unchanged_file = |old_size, old_stats: &LineStats, new_size, new_stats: &LineStats| {
+        if ignore_blank {
+            old_stats.non_empty_lines == new_stats.non_empty_lines
+        } else {
+            old_size == new_size && old_stats == new_stats
+        }
+    };
+    let diff = |old_stats, new_stats| {
+        let diff = StatsDiff::new(old_stats, new_stats);
+        if ignore_blank {
+            diff.ignore_blank_changes()
+        } else {
+            diff
+        }
+    };
+    let mut old_files = relative(old_root, old);
+    let new_files = relative(new_root, new);
+
+    let mut files = Vec::new();
+    let mut unchanged = 0;
+    for (path, (new_size, new_stats)) in new_files {
+        let (status, old_size, old_stats) = match old_files.remove(&path) {
+            Some((old_size, old_stats))
+                if unchanged_file(old_size, &old_stats, new_size, &new_stats) =>
+            {
+                unchanged += 1;
+                continue;
+            }
+            Some((old_size, old_stats)) => (FileStatus::Changed, old_size, old_stats),
+            None => (FileStatus::Added, 0, LineStats::default()),
+        };
+        files.push(FileDiff {
+            path,
+            status,
+            stats: diff(old_stats, new_stats),
+            bytes: new_size as i64 - old_size as i64,
+        });
+    }
+    for (path, (old_size, old_stats)) in old_files {
+        files.push(FileDiff {
+            path,
+            status: FileStatus::Removed,
+            stats: diff(old_stats, LineStats::default()),
+            bytes: -(old_size as i64),
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let count = |status| files.iter().filter(|file| file.status == status).count();
+    DirDiff {
+        added: count(FileStatus::Added),
+        removed: count(FileStatus::Removed),
+        changed: count(FileStatus::Changed),
+        unchanged,
+        total: diff(
+            Summary::from_reports(old).stats,
+            Summary::from_reports(new).stats,
+        ),
+        files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试按相对路径匹配文件并分类
+    #[test]
+    fn test_compare() {
+        let report = |path: &str, size, lines| FileReport {
+            path: PathBuf::from(path),
+            size,
+            stats: LineStats::new(lines, lines, 0),
+            binary: false,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: None,
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+        let old = vec![
+            report("old/a.rs", 10, 2),
+            report("old/b.rs", 10, 2),
+            report("old/gone.rs", 30, 5),
+            report("old/sub/c.rs", 10, 2),
+        ];
+        let new = vec![
+            report("new/a.rs", 10, 2),
+            report("new/b.rs", 40, 8),
+            report("new/sub/c.rs", 12, 2),
+            report("new/sub/d.rs", 5, 1),
+        ];
+
+        let diff = compare(Path::new("old"), &old, Path::new("new"), &new, false);
+        assert_eq!(
+            (diff.added, diff.removed, diff.changed, diff.unchanged),
+            (1, 1, 2, 1)
+        );
+        assert_eq!(diff.total.delta.total_lines, 2);
+
+        let summary: Vec<_> = diff
+            .files
+            .iter()
+            .map(|file| (file.path.to_str().unwrap(), file.status, file.bytes))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("b.rs", FileStatus::Changed, 30),
+                ("gone.rs", FileStatus::Removed, -30),
+                ("sub/c.rs", FileStatus::Changed, 2),
+                ("sub/d.rs", FileStatus::Added, 5),
+            ]
+        );
+        assert_eq!(diff.files[0].stats.delta.total_lines, 6);
+        assert_eq!(diff.files[1].stats.new, LineStats::default());
+    }
+
+    /// 测试忽略空行变化时只增删空行的文件视为未变化
+    #[test]
+    fn test_compare_ignore_blank() {
+        let report = |path: &str, size, non_empty, empty| FileReport {
+            stats: LineStats::new(non_empty + empty, non_empty, empty),
+            size,
+            missing: false,
+            ..FileReport::missing(PathBuf::from(path))
+        };
+        let old = vec![report("old/a.rs", 10, 3, 0), report("old/b.rs", 10, 3, 0)];
+        let new = vec![report("new/a.rs", 14, 3, 4), report("new/b.rs", 14, 4, 1)];
+
+        let diff = compare(Path::new("old"), &old, Path::new("new"), &new, false);
+        assert_eq!((diff.changed, diff.unchanged), (2, 0));
+
+        let diff = compare(Path::new("old"), &old, Path::new("new"), &new, true);
+        assert_eq!((diff.changed, diff.unchanged), (1, 1));
+        assert_eq!(diff.files[0].path, PathBuf::from("b.rs"));
+        assert_eq!(diff.files[0].stats.delta.total_lines, 1);
+        assert_eq!(diff.total.delta.total_lines, 1);
+        assert_eq!(diff.total.delta.empty_lines, 0);
+    }
+}
@@ -0,0 +1,600 @@
+//! 配置文件
+//!
+//! 依次读取用户级配置 `$XDG_CONFIG_HOME/line-counter/config.toml`（未设置时为 `~/.config/line-counter/config.toml`）
+//! 和当前目录中的项目配置 [`CONFIG_FILE_NAME`]（或 `--config` 指定的文件），不存在的文件被忽略。
+//!
+//! 支持的设置：
+//!
+//! - `never_read`：永远不读取的路径模式（glob，如 `["**/*.pem", "secrets/**"]`），相对模式相对于配置文件所在目录。
+//!   多个配置文件中的规则合并生效，项目配置无法取消用户级配置中的规则，
+//!   适合在共享 CI 中保证敏感文件不会被任何模式读取，见 [`NeverRead`]。
+//! - `exclude`：展开目录时跳过的路径模式，与 `--exclude` 合并生效，见 [`PathGlob`]
+//! - `[tags]`：标签规则，键为标签名称，值为一个或一组路径模式，与 `--tag` 合并生效
+//! - `case`：`sensitive`、`insensitive` 或 `smart`，命令行没有指定 `-s`、`-i`、`-S` 时模式的大小写匹配方式
+//! - `format`、`max_size`、`max_decompressed_size`：对应同名命令行选项的默认值
+//! - `[thresholds]`：`max_total_lines`、`max_file_lines` 和 `max_empty_pct` 的默认值
+//!
+//! 同一项设置在项目配置中的值覆盖用户级配置中的值，命令行中显式给出的选项又覆盖配置文件。
+
+use crate::cli::OutputFormat;
+use crate::error::{LineCounterError, Result};
+use crate::pattern::CaseMode;
+use crate::tags::TagRule;
+use crate::threshold;
+use crate::units;
+use glob::Pattern;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 项目配置文件的名称
+pub const CONFIG_FILE_NAME: &str = ".line-counter.toml";
+
+/// 配置文件的内容
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// 永远不读取的路径
+    #[serde(default)]
+    pub never_read: NeverRead,
+    /// 展开目录时跳过的路径
+    #[serde(default)]
+    pub exclude: Vec<PathGlob>,
+    /// 标签规则
+    #[serde(default, deserialize_with = "tag_rules")]
+    pub tags: Vec<TagRule>,
+    /// 默认的大小写匹配方式
+    pub case: Option<CaseMode>,
+    /// 默认的输出格式
+    pub format: Option<OutputFormat>,
+    /// 默认的单个文件大小上限（字节）
+    #[serde(default, deserialize_with = "size")]
+    pub max_size: Option<u64>,
+    /// 默认的解压后大小上限（字节）
+    #[serde(default, deserialize_with = "size")]
+    pub max_decompressed_size: Option<u64>,
+    /// 默认的阈值
+    #[serde(default)]
+    pub thresholds: ThresholdDefaults,
+}
+
+/// 配置文件中的阈值（`[thresholds]` 表）
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdDefaults {
+    /// 所有文件的总行数上限
+    pub max_total_lines: Option<usize>,
+    /// 单个文件的总行数上限
+    pub max_file_lines: Option<usize>,
+    /// 空行占比上限（百分比）
+    #[serde(default, deserialize_with = "percent")]
+    pub max_empty_pct: Option<f64>,
+}
+
+impl Config {
+    /// 读取用户级配置和项目配置并合并
+    ///
+    /// # 参数
+    ///
+    /// * `explicit` - `--config` 指定的项目配置文件，`None` 时使用当前目录中的 [`CONFIG_FILE_NAME`]
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(Config)` - 合并后的配置，没有任何配置文件时为默认配置
+    /// * `Err(LineCounterError)` - 配置文件无法读取、格式错误，或 `--config` 指定的文件不存在
+    pub fn load(explicit: Option<&Path>) -> Result<Self> {
+        let project = match explicit {
+            Some(path) => Some(path.to_path_buf()),
+            None => Some(PathBuf::from(CONFIG_FILE_NAME)).filter(|path| path.is_file()),
+        };
+        let global = user_config_path().filter(|path| path.is_file());
+
+        let mut config = Self::default();
+        for path in global.into_iter().chain(project) {
+            config.merge(Self::read(&path)?);
+        }
+        Ok(config)
+    }
+
+    /// 读取单个配置文件
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 配置文件路径
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(Config)` - 配置内容
+    /// * `Err(LineCounterError)` - 文件无法读取、不是有效的 TOML、包含未知设置或模式无效
+    pub fn read(path: &Path) -> Result<Self> {
+        let error = |message: String| LineCounterError::Config {
+            path: path.display().to_string(),
+            message,
+        };
+        let text = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let mut config: Self = toml::from_str(&text).map_err(|e| error(e.message().to_string()))?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        if let Ok(dir) = dir.canonicalize() {
+            config.never_read.set_base(&dir);
+        }
+        Ok(config)
+    }
+
+    /// 合并另一个配置文件中的设置：路径规则合并，其余设置以后读取的文件为准
+    fn merge(&mut self, other: Self) {
+        self.never_read.rules.extend(other.never_read.rules);
+        self.exclude.extend(other.exclude);
+        self.tags.extend(other.tags);
+        self.case = other.case.or(self.case);
+        self.format = other.format.or(self.format);
+        self.max_size = other.max_size.or(self.max_size);
+        self.max_decompressed_size = other.max_decompressed_size.or(self.max_decompressed_size);
+        let thresholds = &mut self.thresholds;
+        thresholds.max_total_lines = other
+            .thresholds
+            .max_total_lines
+            .or(thresholds.max_total_lines);
+        thresholds.max_file_lines = other
+            .thresholds
+            .max_file_lines
+            .or(thresholds.max_file_lines);
+        thresholds.max_empty_pct = other.thresholds.max_empty_pct.or(thresholds.max_empty_pct);
+    }
+}
+
+/// 大小设置：整数字节数或带单位的字符串（如 `"10M"`）
+fn size<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+    match Size::deserialize(deserializer)? {
+        Size::Bytes(bytes) => Ok(Some(bytes)),
+        Size::Text(text) => units::parse_size(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// 标签规则（`[tags]` 表）：键为标签名称，值为一个路径模式或一组路径模式
+fn tag_rules<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<TagRule>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Patterns {
+        One(String),
+        Many(Vec<String>),
+    }
+    BTreeMap::<String, Patterns>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, patterns)| {
+            let patterns = match patterns {
+                Patterns::One(pattern) => vec![pattern],
+                Patterns::Many(patterns) => patterns,
+            };
+            TagRule::new(&name, patterns.iter().map(String::as_str))
+        })
+        .collect::<std::result::Result<_, _>>()
+        .map_err(serde::de::Error::custom)
+}
+
+/// 百分比设置：0 到 100 之间的数字
+fn percent<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<f64>, D::Error> {
+    let value = f64::deserialize(deserializer)?;
+    threshold::parse_percent(&value.to_string())
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// 用户级配置文件的路径
+fn user_config_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(dir.join("line-counter").join("config.toml"))
+}
+
+/// 路径模式（glob）
+///
+/// 按输入路径（去掉开头的 `./`）匹配，`*` 不跨越目录分隔符，`**` 匹配任意层目录，
+/// 大小写按 [`CaseMode`] 匹配。
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct PathGlob(Pattern);
+
+impl Serialize for PathGlob {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for PathGlob {
+    type Error = String;
+
+    fn try_from(pattern: String) -> std::result::Result<Self, String> {
+        Self::parse(&pattern)
+    }
+}
+
+impl PathGlob {
+    /// 解析路径模式，供 `clap` 和配置文件使用
+    ///
+    /// # 参数
+    ///
+    /// * `pattern` - glob 模式，开头的 `./` 被忽略
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(PathGlob)` - 路径模式
+    /// * `Err(String)` - 模式无效
+    pub fn parse(pattern: &str) -> std::result::Result<Self, String> {
+        let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+        Pattern::new(pattern)
+            .map(Self)
+            .map_err(|e| format!("无效的路径模式 '{}': {}", pattern, e))
+    }
+
+    /// 原始模式
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// 判断路径是否匹配
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 输入路径
+    /// * `case` - 大小写匹配方式
+    pub fn matches(&self, path: &Path, case: CaseMode) -> bool {
+        let path = path.strip_prefix(".").unwrap_or(path);
+        self.0
+            .matches_path_with(path, case.glob_options(self.as_str()))
+    }
+}
+
+/// 永远不读取的路径模式
+///
+/// 模式按三种路径匹配，任意一种匹配即拒绝读取：
+///
+/// - 输入路径（去掉开头的 `./`）
+/// - 解析符号链接后的绝对路径，`**/` 或 `/` 开头的模式由此无法通过 `../`、绝对路径或符号链接绕过
+/// - 解析符号链接后相对于声明规则的配置文件所在目录的路径，因此 `secrets/**`、`.env` 这样的相对模式
+///   同样无法通过 `../`、绝对路径或在子目录中运行绕过
+///
+/// 规则总是区分大小写，不受 `-i` 等选项影响。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct NeverRead {
+    rules: Vec<NeverReadRule>,
+}
+
+/// 单条永远不读取的规则
+#[derive(Debug, Clone)]
+struct NeverReadRule {
+    /// 路径模式
+    pattern: Pattern,
+    /// 声明规则的配置文件所在目录（已解析符号链接），相对模式同时按相对于该目录的路径匹配
+    base: Option<PathBuf>,
+}
+
+impl TryFrom<Vec<String>> for NeverRead {
+    type Error = String;
+
+    fn try_from(patterns: Vec<String>) -> std::result::Result<Self, String> {
+        let rules = patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+                Pattern::new(pattern)
+                    .map(|pattern| NeverReadRule {
+                        pattern,
+                        base: None,
+                    })
+                    .map_err(|e| format!("无效的路径模式 '{}': {}", pattern, e))
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+}
+
+impl NeverRead {
+    /// 是否没有任何规则
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 规则的原始模式
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|rule| rule.pattern.as_str())
+    }
+
+    /// 设置声明这些规则的配置文件所在目录
+    ///
+    /// # 参数
+    ///
+    /// * `base` - 已解析符号链接的目录
+    fn set_base(&mut self, base: &Path) {
+        for rule in &mut self.rules {
+            rule.base = Some(base.to_path_buf());
+        }
+    }
+
+    /// 查找路径匹配的规则
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 输入路径
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(&str)` - 第一个匹配的模式
+    /// * `None` - 路径可以读取
+    pub fn matching(&self, path: &Path) -> Option<&str> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let relative = path.strip_prefix(".").unwrap_or(path);
+        let absolute = path.canonicalize().ok();
+        self.rules
+            .iter()
+            .find(|rule| {
+                let pattern = &rule.pattern;
+                let options = CaseMode::Sensitive.glob_options(pattern.as_str());
+                let from_base = absolute
+                    .as_deref()
+                    .zip(rule.base.as_deref())
+                    .and_then(|(absolute, base)| absolute.strip_prefix(base).ok());
+                pattern.matches_path_with(relative, options)
+                    || absolute
+                        .as_deref()
+                        .is_some_and(|absolute| pattern.matches_path_with(absolute, options))
+                    || from_base.is_some_and(|path| pattern.matches_path_with(path, options))
+            })
+            .map(|rule| rule.pattern.as_str())
+    }
+
+    /// 查找路径匹配的规则，模式还按解析符号链接后相对于 `root` 的路径匹配
+    ///
+    /// 用于 `serve` 等以某个目录为根提供文件的场景，`config/secrets/**` 这样相对于根目录的模式同样生效。
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 输入路径
+    /// * `root` - 已解析符号链接的根目录
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(&str)` - 第一个匹配的模式
+    /// * `None` - 路径可以读取
+    pub fn matching_under(&self, path: &Path, root: &Path) -> Option<&str> {
+        self.matching(path).or_else(|| {
+            let absolute = path.canonicalize().ok()?;
+            let relative = absolute.strip_prefix(root).ok()?;
+            self.rules
+                .iter()
+                .find(|rule| {
+                    let options = CaseMode::Sensitive.glob_options(rule.pattern.as_str());
+                    rule.pattern.matches_path_with(relative, options)
+                })
+                .map(|rule| rule.pattern.as_str())
+        })
+    }
+
+    /// 检查路径是否可以读取
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 输入路径
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(())` - 路径不匹配任何规则
+    /// * `Err(LineCounterError)` - 路径匹配某个规则
+    pub fn check(&self, path: &Path) -> Result<()> {
+        rejected(path, self.matching(path))
+    }
+
+    /// 检查路径是否可以读取，模式还按相对于 `root` 的路径匹配（见 [`NeverRead::matching_under`]）
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 输入路径
+    /// * `root` - 已解析符号链接的根目录
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(())` - 路径不匹配任何规则
+    /// * `Err(LineCounterError)` - 路径匹配某个规则
+    pub fn check_under(&self, path: &Path, root: &Path) -> Result<()> {
+        rejected(path, self.matching_under(path, root))
+    }
+}
+
+/// 匹配了规则的路径对应的错误
+fn rejected(path: &Path, pattern: Option<&str>) -> Result<()> {
+    match pattern {
+        Some(pattern) => Err(LineCounterError::NeverRead {
+            path: path.display().to_string(),
+            pattern: pattern.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试配置文件的解析与合并
+    #[test]
+    fn test_read_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let global = temp_dir.path().join("global.toml");
+        let project = temp_dir.path().join("project.toml");
+        fs::write(&global, "never_read = [\"**/*.pem\"]\n").unwrap();
+        fs::write(&project, "never_read = [\"./secrets/**\"]\n").unwrap();
+
+        let mut config = Config::read(&global).unwrap();
+        config.merge(Config::read(&project).unwrap());
+        assert_eq!(
+            config.never_read.patterns().collect::<Vec<_>>(),
+            vec!["**/*.pem", "secrets/**"]
+        );
+
+        fs::write(
+            &global,
+            "format = \"markdown\"\nmax_size = \"10M\"\nexclude = [\"target/**\"]\n\n[thresholds]\nmax_file_lines = 500\nmax_empty_pct = 30\n",
+        )
+        .unwrap();
+        fs::write(
+            &project,
+            "format = \"json\"\nmax_decompressed_size = 1024\nexclude = [\"./vendor/**\"]\n\n[thresholds]\nmax_total_lines = 10000\n",
+        )
+        .unwrap();
+        let mut config = Config::read(&global).unwrap();
+        config.merge(Config::read(&project).unwrap());
+        assert_eq!(config.format, Some(OutputFormat::Json));
+        assert_eq!(config.max_size, Some(10 * 1024 * 1024));
+        assert_eq!(config.max_decompressed_size, Some(1024));
+        assert_eq!(config.thresholds.max_total_lines, Some(10000));
+        assert_eq!(config.thresholds.max_file_lines, Some(500));
+        assert_eq!(config.thresholds.max_empty_pct, Some(30.0));
+        assert_eq!(
+            config
+                .exclude
+                .iter()
+                .map(PathGlob::as_str)
+                .collect::<Vec<_>>(),
+            vec!["target/**", "vendor/**"]
+        );
+
+        fs::write(
+            &global,
+            "case = \"smart\"\n\n[tags]\nlegacy = \"src/old/**\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &project,
+            "case = \"insensitive\"\n\n[tags]\ngenerated = [\"gen/**\", \"./proto/*.rs\"]\n",
+        )
+        .unwrap();
+        let mut config = Config::read(&global).unwrap();
+        config.merge(Config::read(&project).unwrap());
+        assert_eq!(config.case, Some(CaseMode::Insensitive));
+        assert_eq!(
+            serde_json::to_value(&config.tags).unwrap(),
+            serde_json::json!(["legacy=src/old/**", "generated=gen/**,proto/*.rs"])
+        );
+
+        for invalid in [
+            "format = \"yaml\"\n",
+            "case = \"upper\"\n",
+            "[tags]\nlegacy = []\n",
+            "[tags]\nlegacy = \"src/[\"\n",
+            "max_size = \"lots\"\n",
+            "[thresholds]\nmax_empty_pct = 120\n",
+            "[thresholds]\nmax_lines = 1\n",
+        ] {
+            fs::write(&project, invalid).unwrap();
+            assert!(Config::read(&project).is_err(), "{}", invalid);
+        }
+
+        fs::write(&project, "never_reed = []\n").unwrap();
+        let err = Config::read(&project).unwrap_err();
+        assert!(matches!(err, LineCounterError::Config { .. }));
+        fs::write(&project, "never_read = [\"a[\"]\n").unwrap();
+        assert!(Config::read(&project).is_err());
+        assert!(Config::read(&temp_dir.path().join("missing.toml")).is_err());
+    }
+
+    /// 测试路径模式的匹配
+    #[test]
+    fn test_path_glob() {
+        let glob = PathGlob::parse("./target/**").unwrap();
+        assert_eq!(glob.as_str(), "target/**");
+        let case = CaseMode::Sensitive;
+        assert!(glob.matches(Path::new("./target/debug/build.log"), case));
+        assert!(!glob.matches(Path::new("src/target.rs"), case));
+        assert!(
+            PathGlob::parse("**/*.min.js")
+                .unwrap()
+                .matches(Path::new("web/app.min.js"), case)
+        );
+        assert!(
+            !PathGlob::parse("*.js")
+                .unwrap()
+                .matches(Path::new("web/app.js"), case)
+        );
+        assert!(!glob.matches(Path::new("Target/debug/build.log"), case));
+        assert!(glob.matches(Path::new("Target/debug/build.log"), CaseMode::Insensitive));
+        assert!(glob.matches(Path::new("TARGET/a"), CaseMode::Smart));
+        assert!(PathGlob::parse("a[").is_err());
+    }
+
+    /// 测试路径与规则的匹配
+    #[test]
+    fn test_never_read() {
+        let never_read =
+            NeverRead::try_from(vec!["**/*.pem".to_string(), "**/secrets/**".to_string()]).unwrap();
+        assert_eq!(never_read.matching(Path::new("key.pem")), Some("**/*.pem"));
+        assert_eq!(
+            never_read.matching(Path::new("./certs/server.pem")),
+            Some("**/*.pem")
+        );
+        assert_eq!(
+            never_read.matching(Path::new("/etc/app/secrets/token")),
+            Some("**/secrets/**")
+        );
+        assert_eq!(never_read.matching(Path::new("src/main.rs")), None);
+        assert!(never_read.check(Path::new("src/main.rs")).is_ok());
+        assert!(matches!(
+            never_read.check(Path::new("secrets/a.txt")),
+            Err(LineCounterError::NeverRead { .. })
+        ));
+
+        // 通过符号链接也无法绕过
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let target = temp_dir.path().join("server.pem");
+        fs::write(&target, "key").unwrap();
+        #[cfg(unix)]
+        {
+            let link = temp_dir.path().join("innocent.txt");
+            std::os::unix::fs::symlink(&target, &link).unwrap();
+            assert_eq!(never_read.matching(&link), Some("**/*.pem"));
+        }
+
+        // 相对模式按相对于配置文件所在目录的路径匹配，无法通过 `../` 或绝对路径绕过
+        let project = temp_dir.path().join("proj");
+        fs::create_dir_all(project.join("secrets")).unwrap();
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("secrets/x"), "token").unwrap();
+        fs::write(project.join(".env"), "KEY=1").unwrap();
+        fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+        let config_path = project.join(CONFIG_FILE_NAME);
+        fs::write(&config_path, "never_read = [\"secrets/**\", \".env\"]\n").unwrap();
+        let never_read = Config::read(&config_path).unwrap().never_read;
+
+        let via_parent = project.join("src/../../proj/secrets/x");
+        assert_eq!(never_read.matching(&via_parent), Some("secrets/**"));
+        assert_eq!(
+            never_read.matching(&project.join("secrets/x")),
+            Some("secrets/**")
+        );
+        assert_eq!(never_read.matching(&project.join(".env")), Some(".env"));
+        assert_eq!(never_read.matching(&project.join("src/main.rs")), None);
+
+        // 没有所在目录的规则只按输入路径和绝对路径匹配
+        let unanchored = NeverRead::try_from(vec!["secrets/**".to_string()]).unwrap();
+        assert_eq!(unanchored.matching(&via_parent), None);
+    }
+}
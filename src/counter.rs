@@ -0,0 +1,2190 @@
+//! 核心统计逻辑
+//!
+//! 从任意 [`BufRead`] 或内存中的字节切片读取内容并生成 [`LineStats`]。
+
+use crate::comments::{Classifier, CommentSyntax, LineKind};
+use crate::duplicates::{DuplicateOptions, DuplicateTracker};
+use crate::engine::Engine;
+use crate::error::{LineCounterError, Result};
+use crate::headers::{HeaderOptions, MAX_HEADER_LINES, is_comment_line, is_shebang};
+use crate::records::{RecordFormat, RecordTracker};
+use crate::stats::{
+    CommentStats, Indentation, LengthDistribution, LineEndings, LineLength, LineStats,
+    LogicalLines, LongLine, MAX_INDENT_WIDTH,
+};
+use crate::todos::TodoOptions;
+use crate::tokens::Tokenizer;
+use crate::words::{WordFreqOptions, WordFreqTracker};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Read};
+use std::ops::Range;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// UTF-8 字节顺序标记
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 统计选项
+///
+/// 控制单个文件使用的统计方式。
+#[derive(Debug, Clone, Default)]
+pub struct CountOptions {
+    /// 统计引擎
+    pub engine: Engine,
+    /// 只统计标记区段内（或区段外）的行
+    pub section: Option<SectionFilter>,
+    /// 只统计该行号范围内的行
+    pub lines: Option<LineRange>,
+    /// 统计匹配正则表达式的行数，`None` 表示不统计
+    pub line_match: Option<LineMatch>,
+    /// shebang 与许可证头识别选项
+    pub headers: HeaderOptions,
+    /// 统计二进制内容：无效 UTF-8 的行按替换字符处理并计为非空行，而不是返回错误
+    pub binary: bool,
+    /// 将无效的 UTF-8 序列替换为 U+FFFD 后继续统计，并统计含有无效序列的行数，而不是返回错误
+    pub lossy: bool,
+    /// 按原始字节逐行统计（见 [`count_byte_lines`]），不识别编码也不校验 UTF-8，其他统计选项不起作用
+    pub bytes: bool,
+    /// 统计最长行在字节、字符和显示列宽三种单位下的长度
+    pub line_lengths: bool,
+    /// 统计 LF、CRLF 和单独的 CR 三种换行符的数量
+    pub line_endings: bool,
+    /// 统计制表符缩进和空格缩进的行数，并估计缩进宽度
+    pub indentation: bool,
+    /// 记录按字节数最长的若干行及其行号，0 表示不记录
+    pub top_lines: usize,
+    /// 检测重复的非空行，`None` 表示不检测
+    pub duplicates: Option<DuplicateOptions>,
+    /// 统计待办标记，`None` 表示不统计
+    pub todos: Option<TodoOptions>,
+    /// 按注释语法统计注释行和代码行，`None` 表示不统计
+    pub comments: Option<CommentSyntax>,
+    /// 按语法中的续行规则合并续行，统计逻辑行数，`None` 表示不统计
+    pub logical: Option<CommentSyntax>,
+    /// 按 Unicode 单词边界（UAX #29）统计单词数，每个汉字计为一个单词
+    pub words: bool,
+    /// 统计出现次数最多的单词，`None` 表示不统计
+    pub word_freq: Option<WordFreqOptions>,
+    /// 按分词方式统计大语言模型的 token 数，`None` 表示不统计
+    pub tokens: Option<Tokenizer>,
+    /// 按结构化记录格式统计记录数，`None` 表示只统计行数
+    pub records: Option<RecordFormat>,
+}
+
+/// 标记区段过滤器
+///
+/// 以匹配 `start` 的行开始、匹配 `end` 的行结束的区域视为一个区段，
+/// 标记行本身不计入统计。区段不支持嵌套：区段内再次出现的开始标记按普通行处理。
+#[derive(Debug, Clone)]
+pub struct SectionFilter {
+    /// 区段开始标记
+    pub start: Regex,
+    /// 区段结束标记
+    pub end: Regex,
+    /// 为 `true` 时改为统计区段之外的行
+    pub outside: bool,
+}
+
+/// 行匹配条件
+///
+/// 与 `grep -c` 一致，统计匹配（或不匹配）正则表达式的行数，空行同样参与匹配。
+/// 区段过滤、行号范围和 `--exclude-headers` 排除的行不参与匹配。
+#[derive(Debug, Clone)]
+pub struct LineMatch {
+    /// 正则表达式
+    pub pattern: Regex,
+    /// 为 `true` 时改为统计不匹配的行
+    pub invert: bool,
+}
+
+impl LineMatch {
+    /// 判断一行是否计入匹配行数
+    pub fn is_match(&self, line: &str) -> bool {
+        self.pattern.is_match(line) != self.invert
+    }
+}
+
+/// 行号范围（从 1 开始，包含两端）
+///
+/// 范围之外的行完全不参与统计和分类（换行符、缩进、重复行等），读过范围的末尾后不再读取。
+/// 范围不从第 1 行开始时不识别 shebang 和许可证头。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LineRange {
+    /// 第一行
+    pub start: usize,
+    /// 最后一行，`None` 表示到文件末尾
+    pub end: Option<usize>,
+}
+
+impl LineRange {
+    /// 判断行号是否在范围内
+    pub fn contains(&self, line: usize) -> bool {
+        line >= self.start && self.end.is_none_or(|end| line <= end)
+    }
+
+    /// 判断行号是否已经超过范围的末尾
+    fn is_past(&self, line: usize) -> bool {
+        self.end.is_some_and(|end| line > end)
+    }
+}
+
+/// 解析行号范围
+///
+/// # 参数
+///
+/// * `value` - `开始..结束`（包含两端，如 `100..500`），可以省略任意一端（`100..`、`..500`），
+///   也可以是单个行号（`42`）
+///
+/// # 返回值
+///
+/// * `Ok(LineRange)` - 行号范围
+/// * `Err(String)` - 格式错误、行号为 0 或开始大于结束，供 `clap` 显示
+pub fn parse_line_range(value: &str) -> std::result::Result<LineRange, String> {
+    let parse = |number: &str| {
+        number
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&number| number > 0)
+            .ok_or_else(|| format!("无效的行号 '{}'：行号从 1 开始", number.trim()))
+    };
+
+    let (start, end) = match value.split_once("..") {
+        Some((start, end)) => (
+            if start.trim().is_empty() {
+                1
+            } else {
+                parse(start)?
+            },
+            if end.trim().is_empty() {
+                None
+            } else {
+                Some(parse(end)?)
+            },
+        ),
+        None => {
+            let line = parse(value)?;
+            (line, Some(line))
+        }
+    };
+    if end.is_some_and(|end| end < start) {
+        return Err(format!("行号范围的开始大于结束: '{}'", value));
+    }
+    Ok(LineRange { start, end })
+}
+
+/// 文件头识别状态
+enum HeaderState {
+    /// 尚未遇到非空行
+    Start,
+    /// 正在缓存开头的注释块
+    InBlock {
+        /// 缓存的注释行
+        pending: Vec<String>,
+        /// 第一行注释的行号
+        first_line: usize,
+        /// 注释块中是否出现许可证模式
+        is_license: bool,
+    },
+    /// 文件头已结束
+    Done,
+}
+
+/// 逐行累加统计结果
+///
+/// [`count_lines`] 和 [`count_lines_in_slice`] 共用的每行处理逻辑，
+/// 负责文件头识别、区段过滤和空行判断。
+struct LineAccumulator<'a> {
+    options: &'a CountOptions,
+    stats: LineStats,
+    /// 当前行号（从 1 开始，包含被过滤的行）
+    line_number: usize,
+    in_section: bool,
+    header: HeaderState,
+    /// 下一行是否为内容的第一行（需要去掉开头的 BOM）
+    at_start: bool,
+    /// 上一个非空行的纯空格缩进宽度（用于估计缩进宽度），缩进含制表符时为 `None`
+    previous_indent: Option<usize>,
+    /// 重复行跟踪器（仅在启用重复行检测时）
+    duplicates: Option<DuplicateTracker>,
+    /// 单词频率跟踪器（仅在启用单词频率统计时）
+    word_freq: Option<WordFreqTracker>,
+    /// 结构化记录统计器（仅在按记录格式统计时）
+    records: Option<RecordTracker>,
+    /// 注释行分类器（仅在启用注释行或逻辑行统计时）
+    classifier: Option<Classifier>,
+    /// 上一行是否延续到当前行
+    continued: bool,
+}
+
+impl<'a> LineAccumulator<'a> {
+    fn new(options: &'a CountOptions) -> Self {
+        Self {
+            options,
+            stats: LineStats {
+                line_endings: options.line_endings.then(LineEndings::default),
+                indentation: options.indentation.then(Indentation::default),
+                matched_lines: options.line_match.as_ref().map(|_| 0),
+                invalid_utf8_lines: options.lossy.then_some(0),
+                todos: options.todos.as_ref().map(TodoOptions::empty_stats),
+                comments: options.comments.map(|_| CommentStats::default()),
+                logical: options.logical.map(|_| LogicalLines::default()),
+                words: options.words.then_some(0),
+                tokens: options.tokens.map(Tokenizer::empty_count),
+                ..LineStats::default()
+            },
+            line_number: 0,
+            in_section: false,
+            // 范围不从第一行开始时，范围内的第一行不是文件开头
+            header: if options.lines.is_some_and(|lines| lines.start > 1) {
+                HeaderState::Done
+            } else {
+                HeaderState::Start
+            },
+            at_start: true,
+            previous_indent: None,
+            duplicates: options.duplicates.map(DuplicateTracker::new),
+            word_freq: options.word_freq.clone().map(WordFreqTracker::new),
+            records: options.records.map(RecordTracker::new),
+            classifier: options.comments.or(options.logical).map(Classifier::new),
+            continued: false,
+        }
+    }
+
+    /// 创建统计文件开头之后的分块使用的累加器：不再识别文件头和 BOM
+    fn after_header(options: &'a CountOptions) -> Self {
+        Self {
+            header: HeaderState::Done,
+            at_start: false,
+            ..Self::new(options)
+        }
+    }
+
+    /// 处理一行原始字节（不含 `\n`）
+    ///
+    /// 去掉第一行开头的 UTF-8 BOM 和行尾的 `\r`，并校验 UTF-8，校验不分配内存。
+    /// `terminated` 表示这一行是否以 `\n` 结尾（最后一行可能没有），用于统计换行符。
+    ///
+    /// 文件头识别结束且没有区段过滤、行匹配、待办标记和注释行统计时，直接在字节上判断空行（见 [`is_blank_line`]），
+    /// 纯 ASCII 的行不再需要单独的 UTF-8 校验。
+    fn push_bytes(&mut self, bytes: &[u8], terminated: bool) -> Result<()> {
+        let bytes = if std::mem::take(&mut self.at_start) {
+            bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes)
+        } else {
+            bytes
+        };
+        if let Some(lines) = self.options.lines
+            && !lines.contains(self.line_number + 1)
+        {
+            self.line_number += 1;
+            return Ok(());
+        }
+        if let Some(endings) = &mut self.stats.line_endings {
+            record_line_ending(endings, bytes, terminated);
+        }
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        if self.options.top_lines > 0 {
+            self.record_long_line(bytes);
+        }
+
+        if self.options.section.is_none()
+            && self.options.line_match.is_none()
+            && self.options.todos.is_none()
+            && self.options.comments.is_none()
+            && self.options.logical.is_none()
+            && !self.options.line_lengths
+            && !self.options.words
+            && self.options.word_freq.is_none()
+            && self.options.tokens.is_none()
+            && self.options.records.is_none()
+            && matches!(self.header, HeaderState::Done)
+        {
+            let blank = match is_blank_line(bytes) {
+                Ok(blank) => blank,
+                Err(_) if self.replace_invalid() => false,
+                Err(e) => return Err(self.invalid_utf8(e)),
+            };
+            self.line_number += 1;
+            self.measure_indentation(bytes, blank);
+            self.track_duplicate(bytes, blank);
+            self.tally(blank, matches!(bytes.last(), Some(b' ' | b'\t')));
+            return Ok(());
+        }
+
+        match std::str::from_utf8(bytes) {
+            Ok(line) => self.push(line),
+            Err(_) if self.replace_invalid() => self.push(&String::from_utf8_lossy(bytes)),
+            Err(e) => return Err(self.invalid_utf8(e)),
+        }
+        Ok(())
+    }
+
+    /// 记录较长的行（下一行，行号为 `line_number + 1`）
+    ///
+    /// 保持最多 `top_lines` 行按字节数从大到小排列，字节数相同时保留较早的行。
+    /// 只有能进入列表的行才计算字符数和显示列宽。
+    fn record_long_line(&mut self, bytes: &[u8]) {
+        let longest = &mut self.stats.longest_lines;
+        if longest.len() == self.options.top_lines
+            && longest
+                .last()
+                .is_some_and(|last| last.length.bytes >= bytes.len())
+        {
+            return;
+        }
+
+        let long_line = LongLine {
+            line: self.line_number + 1,
+            length: LineLength::of(&String::from_utf8_lossy(bytes)),
+        };
+        let index = longest.partition_point(|other| other.length.bytes >= bytes.len());
+        longest.insert(index, long_line);
+        longest.truncate(self.options.top_lines);
+    }
+
+    /// 下一行不是有效 UTF-8 时是否按替换字符处理，而不是返回错误
+    ///
+    /// 指定 `--lossy` 时同时计入含有无效序列的行数。
+    fn replace_invalid(&mut self) -> bool {
+        match &mut self.stats.invalid_utf8_lines {
+            Some(lines) => {
+                *lines += 1;
+                true
+            }
+            None => self.options.binary,
+        }
+    }
+
+    /// 下一行不是有效 UTF-8 时的错误
+    fn invalid_utf8(&self, error: std::str::Utf8Error) -> LineCounterError {
+        LineCounterError::LineReadError {
+            line: self.line_number + 1,
+            source: std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+        }
+    }
+
+    /// 处理一行内容（不含换行符）
+    ///
+    /// 文件开头的注释块会先被缓存，直到注释块结束才能确定它是否为许可证头。
+    fn push(&mut self, line: &str) {
+        self.line_number += 1;
+
+        match &mut self.header {
+            HeaderState::Start => {
+                if self.line_number == 1 && is_shebang(line) {
+                    self.stats.shebang_lines += 1;
+                    if !self.options.headers.exclude {
+                        self.count(line);
+                    }
+                } else if line.trim().is_empty() {
+                    self.count(line);
+                } else if is_comment_line(line) {
+                    self.header = HeaderState::InBlock {
+                        pending: vec![line.to_string()],
+                        first_line: self.line_number,
+                        is_license: self.options.headers.is_license_line(line),
+                    };
+                } else {
+                    self.header = HeaderState::Done;
+                    self.count(line);
+                }
+            }
+            HeaderState::InBlock {
+                pending,
+                is_license,
+                ..
+            } if is_comment_line(line) && pending.len() < MAX_HEADER_LINES => {
+                *is_license |= self.options.headers.is_license_line(line);
+                pending.push(line.to_string());
+            }
+            HeaderState::InBlock { .. } => {
+                self.flush_header();
+                self.count(line);
+            }
+            HeaderState::Done => self.count(line),
+        }
+    }
+
+    /// 结束文件头识别，将缓存的注释块计入统计
+    fn flush_header(&mut self) {
+        if let HeaderState::InBlock {
+            pending,
+            first_line,
+            is_license,
+        } = std::mem::replace(&mut self.header, HeaderState::Done)
+        {
+            if is_license {
+                self.stats.license_lines += pending.len();
+                if self.options.headers.exclude {
+                    return;
+                }
+            }
+            for (offset, line) in pending.iter().enumerate() {
+                self.count_at(line, first_line + offset);
+            }
+        }
+    }
+
+    /// 对当前行执行区段过滤、行匹配和空行判断
+    fn count(&mut self, line: &str) {
+        self.count_at(line, self.line_number);
+    }
+
+    /// 对给定行号的一行执行区段过滤、行匹配、待办标记统计和空行判断
+    ///
+    /// 开头的注释块在缓存结束后才计入统计，此时的行号不是当前行号。
+    fn count_at(&mut self, line: &str, line_number: usize) {
+        if let Some(section) = &self.options.section {
+            if !self.in_section && section.start.is_match(line) {
+                self.in_section = true;
+                return;
+            }
+            if self.in_section && section.end.is_match(line) {
+                self.in_section = false;
+                return;
+            }
+            if self.in_section == section.outside {
+                return;
+            }
+        }
+
+        if let Some(line_match) = &self.options.line_match
+            && line_match.is_match(line)
+            && let Some(matched) = &mut self.stats.matched_lines
+        {
+            *matched += 1;
+        }
+        if let Some(todo_options) = &self.options.todos
+            && let Some(todos) = &mut self.stats.todos
+        {
+            todo_options.scan(line, line_number, todos);
+        }
+
+        if self.options.line_lengths {
+            let length = LineLength::of(line);
+            self.stats.max_line_length = Some(match self.stats.max_line_length {
+                Some(max) => max.max(length),
+                None => length,
+            });
+            self.stats
+                .line_length_distribution
+                .get_or_insert_with(LengthDistribution::default)
+                .add(length.cells);
+        }
+        if let Some(words) = &mut self.stats.words {
+            *words += line.unicode_words().count();
+        }
+        if let (Some(tokens), Some(tokenizer)) = (&mut self.stats.tokens, self.options.tokens) {
+            tokens.tokens += tokenizer.count_line(line);
+        }
+        if let Some(tracker) = &mut self.word_freq {
+            tracker.push(line);
+        }
+        if let Some(tracker) = &mut self.records {
+            tracker.push(line, line_number);
+        }
+
+        if let Some(classifier) = &mut self.classifier {
+            let kind = classifier.classify(line);
+            if let Some(comments) = &mut self.stats.comments {
+                match kind {
+                    LineKind::Comment => comments.comment_lines += 1,
+                    LineKind::Code => comments.code_lines += 1,
+                    LineKind::Blank => {}
+                }
+            }
+            if let Some(logical) = &mut self.stats.logical {
+                logical.physical_lines += 1;
+                if !std::mem::replace(&mut self.continued, classifier.continues()) {
+                    logical.logical_lines += 1;
+                }
+            }
+        }
+
+        let blank = line.trim().is_empty();
+        self.measure_indentation(line.as_bytes(), blank);
+        self.track_duplicate(line.as_bytes(), blank);
+        self.tally(blank, line.ends_with([' ', '\t']));
+    }
+
+    /// 记录一行的缩进（仅在启用缩进统计时）
+    ///
+    /// 空行不参与统计，也不打断缩进宽度的估计。
+    fn measure_indentation(&mut self, bytes: &[u8], blank: bool) {
+        let Some(indentation) = &mut self.stats.indentation else {
+            return;
+        };
+        if blank {
+            return;
+        }
+
+        let width = bytes
+            .iter()
+            .take_while(|&&b| b == b' ' || b == b'\t')
+            .count();
+        let indent = &bytes[..width];
+        match indent.first() {
+            Some(b'\t') => indentation.tabs += 1,
+            Some(_) => indentation.spaces += 1,
+            None => {}
+        }
+
+        if indent.contains(&b'\t') {
+            self.previous_indent = None;
+            return;
+        }
+        if let Some(previous) = self.previous_indent
+            && (1..=MAX_INDENT_WIDTH).contains(&width.saturating_sub(previous))
+        {
+            indentation.width_votes[width - previous - 1] += 1;
+        }
+        self.previous_indent = Some(width);
+    }
+
+    /// 将非空行交给重复行跟踪器（仅在启用重复行检测时）
+    fn track_duplicate(&mut self, bytes: &[u8], blank: bool) {
+        if let Some(tracker) = &mut self.duplicates
+            && !blank
+        {
+            tracker.push(bytes);
+        }
+    }
+
+    /// 将一行计入总行数、空行或非空行，以及行尾空白行数
+    ///
+    /// 只有空白的行已经计为空行，不再计入行尾空白行数。
+    fn tally(&mut self, blank: bool, trailing_whitespace: bool) {
+        self.stats.total_lines += 1;
+        if trailing_whitespace && !blank {
+            self.stats.trailing_whitespace_lines += 1;
+        }
+
+        if blank {
+            self.stats.empty_lines += 1;
+        } else {
+            self.stats.non_empty_lines += 1;
+        }
+    }
+
+    /// 是否已经读过行号范围的末尾，之后的内容不再需要读取
+    fn is_past_range(&self) -> bool {
+        self.options
+            .lines
+            .is_some_and(|lines| lines.is_past(self.line_number + 1))
+    }
+
+    /// 读取并处理读取器中的全部内容，最后一行可以没有换行符
+    ///
+    /// 每处理完一个读取缓冲区调用一次 `on_progress`，参数为目前的统计结果和已读取的字节数。
+    fn push_reader<R, F>(&mut self, mut reader: R, mut on_progress: F) -> Result<()>
+    where
+        R: BufRead,
+        F: FnMut(&LineStats, u64),
+    {
+        let mut bytes_read = 0u64;
+        // 跨越缓冲区边界的不完整行
+        let mut partial = Vec::new();
+
+        loop {
+            let buffer = match reader.fill_buf() {
+                Ok(buffer) => buffer,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(source) => {
+                    return Err(LineCounterError::LineReadError {
+                        line: self.line_number + 1,
+                        source,
+                    });
+                }
+            };
+            if buffer.is_empty() {
+                break;
+            }
+
+            self.push_buffer(buffer, &mut partial)?;
+            let consumed = buffer.len();
+            reader.consume(consumed);
+            bytes_read += consumed as u64;
+            on_progress(&self.stats, bytes_read);
+            if self.is_past_range() {
+                return Ok(());
+            }
+        }
+
+        self.push_partial(&partial)
+    }
+
+    /// 处理一个读取缓冲区中的完整行
+    ///
+    /// `partial` 是跨越缓冲区边界的不完整行：处理前与缓冲区中的第一行拼接，处理后保存缓冲区末尾的不完整行。
+    fn push_buffer(&mut self, buffer: &[u8], partial: &mut Vec<u8>) -> Result<()> {
+        let mut start = 0;
+        for end in memchr::memchr_iter(b'\n', buffer) {
+            if partial.is_empty() {
+                self.push_bytes(&buffer[start..end], true)?;
+            } else {
+                partial.extend_from_slice(&buffer[start..end]);
+                self.push_bytes(partial, true)?;
+                partial.clear();
+            }
+            start = end + 1;
+        }
+        partial.extend_from_slice(&buffer[start..]);
+        Ok(())
+    }
+
+    /// 处理输入结束时剩余的不完整行（最后一行没有换行符结尾）
+    fn push_partial(&mut self, partial: &[u8]) -> Result<()> {
+        if partial.is_empty() {
+            return Ok(());
+        }
+        self.push_bytes(partial, false)
+    }
+
+    /// 处理一段完整的内容，最后一行可以没有换行符
+    fn push_slice(&mut self, data: &[u8]) -> Result<()> {
+        let mut start = 0;
+
+        for end in memchr::memchr_iter(b'\n', data) {
+            self.push_bytes(&data[start..end], true)?;
+            start = end + 1;
+            if self.is_past_range() {
+                return Ok(());
+            }
+        }
+
+        // 最后一行没有换行符结尾
+        if start < data.len() {
+            self.push_bytes(&data[start..], false)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> LineStats {
+        self.flush_header();
+        self.stats.duplicates = self.duplicates.map(DuplicateTracker::finish);
+        self.stats.word_freq = self.word_freq.map(WordFreqTracker::finish);
+        if let Some(tracker) = self.records {
+            tracker.finish(&mut self.stats);
+        }
+        self.stats
+    }
+}
+
+/// 记录一行的换行符
+///
+/// # 参数
+///
+/// * `endings` - 换行符计数
+/// * `bytes` - 一行原始字节（不含 `\n`）
+/// * `terminated` - 这一行是否以 `\n` 结尾
+fn record_line_ending(endings: &mut LineEndings, bytes: &[u8], terminated: bool) {
+    let body = match bytes.strip_suffix(b"\r") {
+        Some(body) if terminated => {
+            endings.crlf += 1;
+            body
+        }
+        _ => {
+            if terminated {
+                endings.lf += 1;
+            } else if !bytes.ends_with(b"\r") {
+                endings.missing_final_newline += 1;
+            }
+            bytes
+        }
+    };
+    endings.cr += memchr::memchr_iter(b'\r', body).count();
+}
+
+/// 判断一行原始字节是否只包含空白字符
+///
+/// 纯 ASCII 的行直接在字节上判断，不分配也不做 UTF-8 解码；
+/// 只有包含非 ASCII 字节时才校验 UTF-8 并按 Unicode 空白字符判断，与 `str::trim` 的结果一致。
+///
+/// # 参数
+///
+/// * `bytes` - 一行内容（不含换行符）
+///
+/// # 返回值
+///
+/// * `Ok(bool)` - 是否为空行
+/// * `Err(Utf8Error)` - 包含非 ASCII 字节且不是有效的 UTF-8
+fn is_blank_line(bytes: &[u8]) -> std::result::Result<bool, std::str::Utf8Error> {
+    if bytes.is_ascii() {
+        // 与 char::is_whitespace 在 ASCII 范围内一致（包含 \x0B，u8::is_ascii_whitespace 不包含）
+        Ok(bytes
+            .iter()
+            .all(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\x0B' | b'\x0C' | b'\r')))
+    } else {
+        Ok(std::str::from_utf8(bytes)?.trim().is_empty())
+    }
+}
+
+/// 统计文件行数
+///
+/// 读取文件内容并统计总行数、非空行数和空行数。
+///
+/// # 参数
+///
+/// * `reader` - 缓冲读取器
+/// * `options` - 统计选项
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 读取过程中发生错误
+///
+/// # 实现细节
+///
+/// - 直接在 `BufRead` 的内部缓冲区上用 `memchr` 查找换行符，不为每一行分配 `String`
+/// - 只有跨越缓冲区边界的行才会被复制到一个复用的临时缓冲区
+/// - 纯 ASCII 的行直接在字节上判断是否为空行；含非 ASCII 字节的行先做 UTF-8 校验（不分配），再用 `trim()` 判断
+/// - 与 `BufRead::lines()` 一致，行尾的 `\r\n` 视为一个换行符
+/// - 内容开头的 UTF-8 BOM 不计入第一行
+/// - 启用 [`CountOptions::line_endings`] 时统计 LF、CRLF 和单独的 CR 的数量
+/// - 读取失败时返回 [`LineCounterError::LineReadError`]，包含出错的行号
+pub fn count_lines<R: BufRead>(reader: R, options: &CountOptions) -> Result<LineStats> {
+    count_lines_streaming(reader, options, |_, _| {})
+}
+
+/// 流式统计行数，并在处理完每个读取缓冲区后回调中间结果
+///
+/// 内存占用与输入大小无关：只保留一个读取缓冲区、跨缓冲区的当前行，
+/// 以及最多 [`MAX_HEADER_LINES`] 行的开头注释块，适合任意大的文件和无界的标准输入。
+///
+/// # 参数
+///
+/// * `reader` - 缓冲读取器
+/// * `options` - 统计选项
+/// * `on_progress` - 回调，参数为目前的统计结果和已读取的字节数；
+///   尚未确定是否为许可证头的开头注释块不计入中间结果
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 最终的行数统计结果
+/// * `Err(LineCounterError)` - 读取过程中发生错误
+pub fn count_lines_streaming<R, F>(
+    reader: R,
+    options: &CountOptions,
+    on_progress: F,
+) -> Result<LineStats>
+where
+    R: BufRead,
+    F: FnMut(&LineStats, u64),
+{
+    if options.bytes {
+        return count_byte_lines(reader, on_progress);
+    }
+    let mut accumulator = LineAccumulator::new(options);
+    accumulator.push_reader(reader, on_progress)?;
+    Ok(accumulator.finish())
+}
+
+/// 按原始字节统计时回调中间结果的间隔行数
+const BYTE_LINES_PROGRESS_INTERVAL: usize = 16 * 1024;
+
+/// 按原始字节逐行统计行数，不识别编码也不校验 UTF-8
+///
+/// 用 `read_until(b'\n')` 将每一行读入一个复用的缓冲区，适用于任意字节内容
+/// （夹杂二进制数据的日志、未知编码的文件），跳过 UTF-8 校验也使它成为最快的统计方式。
+/// 只统计总行数、空行数、非空行数、行尾有空白的行数和最长行的字节数。
+///
+/// # 参数
+///
+/// * `reader` - 缓冲读取器
+/// * `on_progress` - 回调，参数为目前的统计结果和已读取的字节数
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 读取过程中发生错误
+///
+/// # 实现细节
+///
+/// - 与 [`count_lines`] 一致，行尾的 `\r\n` 视为一个换行符，最后一行可以没有换行符
+/// - 只包含 ASCII 空白字符的行为空行，含有非 ASCII 字节的行总是计为非空行
+/// - 内容开头的 BOM 作为普通字节计入第一行
+pub fn count_byte_lines<R, F>(mut reader: R, mut on_progress: F) -> Result<LineStats>
+where
+    R: BufRead,
+    F: FnMut(&LineStats, u64),
+{
+    let mut stats = byte_line_stats();
+    let mut line = Vec::new();
+    let mut bytes_read = 0u64;
+
+    loop {
+        line.clear();
+        let read = match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(source) => {
+                return Err(LineCounterError::LineReadError {
+                    line: stats.total_lines + 1,
+                    source,
+                });
+            }
+        };
+        bytes_read += read as u64;
+        push_byte_line(&mut stats, &line);
+
+        if stats
+            .total_lines
+            .is_multiple_of(BYTE_LINES_PROGRESS_INTERVAL)
+        {
+            on_progress(&stats, bytes_read);
+        }
+    }
+
+    Ok(stats)
+}
+
+/// 按原始字节统计的初始结果，最长行的字节数从 0 开始
+fn byte_line_stats() -> LineStats {
+    LineStats {
+        max_line_bytes: Some(0),
+        ..LineStats::default()
+    }
+}
+
+/// 把按原始字节读取的一行（可以包含行尾的换行符）计入统计结果（见 [`count_byte_lines`]）
+fn push_byte_line(stats: &mut LineStats, line: &[u8]) {
+    let bytes = line.strip_suffix(b"\n").unwrap_or(line);
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    let blank = bytes
+        .iter()
+        .all(|b| matches!(b, b' ' | b'\t' | b'\x0B' | b'\x0C' | b'\r'));
+    stats.total_lines += 1;
+    if blank {
+        stats.empty_lines += 1;
+    } else {
+        stats.non_empty_lines += 1;
+        if matches!(bytes.last(), Some(b' ' | b'\t')) {
+            stats.trailing_whitespace_lines += 1;
+        }
+    }
+    stats.max_line_bytes = stats.max_line_bytes.max(Some(bytes.len()));
+}
+
+/// 从异步读取器中按原始字节逐行统计行数（需要 `async` 特性），结果与 [`count_byte_lines`] 一致
+///
+/// # 参数
+///
+/// * `reader` - 异步缓冲读取器
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 读取过程中发生错误
+#[cfg(feature = "async")]
+async fn count_byte_lines_async<R>(mut reader: R) -> Result<LineStats>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut stats = byte_line_stats();
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) => break,
+            Ok(_) => push_byte_line(&mut stats, &line),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(source) => {
+                return Err(LineCounterError::LineReadError {
+                    line: stats.total_lines + 1,
+                    source,
+                });
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// 从异步读取器中统计行数（需要 `async` 特性）
+///
+/// 与 [`count_lines`] 的结果一致。等待输入时让出运行时线程，每次只处理一个读取缓冲区，
+/// 适合在异步服务中统计上传的内容而不阻塞运行时。
+///
+/// # 参数
+///
+/// * `reader` - 异步缓冲读取器，如 `tokio::io::BufReader`
+/// * `options` - 统计选项
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 读取过程中发生错误
+#[cfg(feature = "async")]
+pub async fn count_lines_async<R>(mut reader: R, options: &CountOptions) -> Result<LineStats>
+where
+    R: AsyncBufRead + Unpin,
+{
+    if options.bytes {
+        return count_byte_lines_async(reader).await;
+    }
+    let mut accumulator = LineAccumulator::new(options);
+    // 跨越缓冲区边界的不完整行
+    let mut partial = Vec::new();
+
+    loop {
+        let buffer = match reader.fill_buf().await {
+            Ok(buffer) => buffer,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(source) => {
+                return Err(LineCounterError::LineReadError {
+                    line: accumulator.line_number + 1,
+                    source,
+                });
+            }
+        };
+        if buffer.is_empty() {
+            break;
+        }
+
+        accumulator.push_buffer(buffer, &mut partial)?;
+        let consumed = buffer.len();
+        reader.consume(consumed);
+        if accumulator.is_past_range() {
+            return Ok(accumulator.finish());
+        }
+    }
+
+    accumulator.push_partial(&partial)?;
+    Ok(accumulator.finish())
+}
+
+/// 统计内存中字节切片的行数
+///
+/// 与 [`count_lines`] 的结果一致，适用于内存映射的文件内容。
+///
+/// # 参数
+///
+/// * `data` - 文件的完整内容
+/// * `options` - 统计选项
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 某一行不是有效的 UTF-8
+pub fn count_lines_in_slice(data: &[u8], options: &CountOptions) -> Result<LineStats> {
+    let mut accumulator = LineAccumulator::new(options);
+    accumulator.push_slice(data)?;
+    Ok(accumulator.finish())
+}
+
+/// 将字节切片分块并行统计行数
+///
+/// 与 [`count_lines_in_slice`] 的结果一致，适用于内存映射的大文件：
+///
+/// 1. 先顺序处理文件开头，直到文件头识别结束（文件头识别依赖前面的行）
+/// 2. 将剩余内容在换行符处切分为 `chunks` 块，用 `rayon` 并行统计后合并
+///    （未启用 `parallel` 特性时按顺序统计各块，结果相同）
+///
+/// 区段过滤、行号范围、缩进宽度估计、最长行和待办标记的行号、注释行分类、逻辑行合并、记录解析以及重复行检测依赖前面的行，无法分块；单词频率只保留前 N 个单词，分块后无法准确合并；
+/// 启用它们时退化为顺序统计。
+///
+/// # 参数
+///
+/// * `data` - 文件的完整内容
+/// * `options` - 统计选项
+/// * `chunks` - 分块数，通常为可用的 CPU 核心数
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 某一行不是有效的 UTF-8
+pub fn count_lines_in_slice_parallel(
+    data: &[u8],
+    options: &CountOptions,
+    chunks: usize,
+) -> Result<LineStats> {
+    if !can_split(options) || chunks <= 1 {
+        return count_lines_in_slice(data, options);
+    }
+
+    // 顺序处理文件头
+    let mut head = LineAccumulator::new(options);
+    let mut start = 0;
+    while !matches!(head.header, HeaderState::Done) {
+        match memchr::memchr(b'\n', &data[start..]) {
+            Some(offset) => {
+                head.push_bytes(&data[start..start + offset], true)?;
+                start += offset + 1;
+            }
+            None => {
+                head.push_slice(&data[start..])?;
+                return Ok(head.finish());
+            }
+        }
+    }
+
+    let rest = &data[start..];
+    let results = count_chunks(chunk_ranges(rest, chunks), |range| {
+        let mut accumulator = LineAccumulator::after_header(options);
+        accumulator.push_slice(&rest[range])?;
+        Ok(accumulator.finish())
+    });
+    match merge_chunks(head, results) {
+        Some(stats) => Ok(stats),
+        // 分块中的行号是相对的，重新顺序统计以报告准确的出错行号
+        None => count_lines_in_slice(data, options),
+    }
+}
+
+/// 按位置读取输入，分段并行统计行数
+///
+/// 与 [`count_lines_in_slice_parallel`] 的结果一致，但不需要把整个文件映射到内存，
+/// 适用于网络文件系统等不适合内存映射的大文件：
+///
+/// 1. 先顺序读取文件开头，直到文件头识别结束
+/// 2. 按位置读取查找换行符，将剩余内容切分为 `chunks` 个以换行符结尾的字节范围
+/// 3. 每个范围由一个线程用自己的读取缓冲区流式统计，最后合并各范围的结果
+///
+/// 无法分块的统计选项与 [`count_lines_in_slice_parallel`] 相同，启用它们时退化为顺序读取。
+///
+/// # 参数
+///
+/// * `source` - 可以按位置读取的输入，如 [`std::fs::File`]
+/// * `len` - 输入的字节数
+/// * `options` - 统计选项
+/// * `chunks` - 分块数，通常为可用的 CPU 核心数
+/// * `buffer_size` - 每个分块的读取缓冲区大小
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 读取失败或某一行不是有效的 UTF-8
+pub fn count_lines_in_ranges_parallel<S: ReadAt + ?Sized>(
+    source: &S,
+    len: u64,
+    options: &CountOptions,
+    chunks: usize,
+    buffer_size: usize,
+) -> Result<LineStats> {
+    let reader = |range: Range<u64>| {
+        BufReader::with_capacity(
+            buffer_size,
+            RangeReader {
+                source,
+                position: range.start,
+                end: range.end,
+            },
+        )
+    };
+    if !can_split(options) || chunks <= 1 {
+        return count_lines(reader(0..len), options);
+    }
+
+    // 顺序读取文件头
+    let mut head = LineAccumulator::new(options);
+    let mut head_reader = reader(0..len);
+    let mut start = 0u64;
+    let mut line = Vec::new();
+    while !matches!(head.header, HeaderState::Done) {
+        line.clear();
+        let read = head_reader.read_until(b'\n', &mut line).map_err(|source| {
+            LineCounterError::LineReadError {
+                line: head.line_number + 1,
+                source,
+            }
+        })?;
+        start += read as u64;
+        match line.strip_suffix(b"\n") {
+            Some(bytes) => head.push_bytes(bytes, true)?,
+            None => {
+                head.push_slice(&line)?;
+                return Ok(head.finish());
+            }
+        }
+    }
+
+    // 查找换行符失败时退化为顺序读取，由它报告错误
+    let Ok(ranges) = chunk_ranges_at(source, start..len, chunks) else {
+        return count_lines(reader(0..len), options);
+    };
+    let results = count_chunks(ranges, |range| {
+        let mut accumulator = LineAccumulator::after_header(options);
+        accumulator.push_reader(reader(range), |_, _| {})?;
+        Ok(accumulator.finish())
+    });
+    match merge_chunks(head, results) {
+        Some(stats) => Ok(stats),
+        // 分块中的行号是相对的，重新顺序统计以报告准确的出错行号
+        None => count_lines(reader(0..len), options),
+    }
+}
+
+/// 统计选项是否允许分块统计（见 [`count_lines_in_slice_parallel`]）
+fn can_split(options: &CountOptions) -> bool {
+    options.section.is_none()
+        && options.lines.is_none()
+        && !options.indentation
+        && options.top_lines == 0
+        && options.duplicates.is_none()
+        && options.word_freq.is_none()
+        && !options.todos.as_ref().is_some_and(|todos| todos.list)
+        && options.comments.is_none()
+        && options.logical.is_none()
+        && options.records.is_none()
+}
+
+/// 统计各个分块；启用 `parallel` 特性时用 `rayon` 并行统计，否则按顺序统计
+fn count_chunks<T, F>(chunks: Vec<T>, count_chunk: F) -> Vec<Result<LineStats>>
+where
+    T: Send,
+    F: Fn(T) -> Result<LineStats> + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    let results = chunks.into_par_iter().map(count_chunk).collect();
+    #[cfg(not(feature = "parallel"))]
+    let results = chunks.into_iter().map(count_chunk).collect();
+    results
+}
+
+/// 把各分块的结果合并到文件头的统计结果中；任意分块失败时返回 `None`
+fn merge_chunks(head: LineAccumulator<'_>, results: Vec<Result<LineStats>>) -> Option<LineStats> {
+    let mut stats = head.finish();
+    for result in results {
+        stats += &result.ok()?;
+    }
+    Some(stats)
+}
+
+/// 可以按位置读取的输入
+///
+/// 多个线程可以同时从不同位置读取，分段并行统计时不需要共享读取位置。
+pub trait ReadAt: Sync {
+    /// 从 `offset` 处读取内容到 `buf`
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(usize)` - 读取的字节数，0 表示已到达末尾
+    /// * `Err(io::Error)` - 读取失败
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+}
+
+impl ReadAt for [u8] {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let start = usize::try_from(offset).map_or(self.len(), |offset| offset.min(self.len()));
+        let len = buf.len().min(self.len() - start);
+        buf[..len].copy_from_slice(&self[start..start + len]);
+        Ok(len)
+    }
+}
+
+#[cfg(unix)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+}
+
+#[cfg(windows)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+}
+
+/// 读取 [`ReadAt`] 输入中 `position..end` 范围的读取器
+struct RangeReader<'a, S: ?Sized> {
+    source: &'a S,
+    position: u64,
+    end: u64,
+}
+
+impl<S: ReadAt + ?Sized> Read for RangeReader<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.end.saturating_sub(self.position);
+        let len = buf
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        if len == 0 {
+            return Ok(0);
+        }
+        let read = self.source.read_at(&mut buf[..len], self.position)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+/// 按位置读取查找换行符，将 `range` 切分为最多 `chunks` 个范围，每个范围（除最后一个外）以 `\n` 结尾
+fn chunk_ranges_at<S: ReadAt + ?Sized>(
+    source: &S,
+    range: Range<u64>,
+    chunks: usize,
+) -> io::Result<Vec<Range<u64>>> {
+    let chunk_size = (range.end - range.start)
+        .div_ceil(chunks.max(1) as u64)
+        .max(1);
+    let mut ranges = Vec::with_capacity(chunks);
+    let mut buffer = vec![0; 64 * 1024];
+    let mut start = range.start;
+
+    while start < range.end {
+        let target = (start + chunk_size).min(range.end);
+        let mut reader = RangeReader {
+            source,
+            position: target - 1,
+            end: range.end,
+        };
+        let end = loop {
+            let position = reader.position;
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break range.end;
+            }
+            if let Some(offset) = memchr::memchr(b'\n', &buffer[..read]) {
+                break position + offset as u64 + 1;
+            }
+        };
+        ranges.push(start..end);
+        start = end;
+    }
+
+    Ok(ranges)
+}
+
+/// 将数据在换行符处切分为最多 `chunks` 个范围，每个范围（除最后一个外）以 `\n` 结尾
+fn chunk_ranges(data: &[u8], chunks: usize) -> Vec<Range<usize>> {
+    let chunk_size = data.len().div_ceil(chunks.max(1)).max(1);
+    let mut ranges = Vec::with_capacity(chunks);
+    let mut start = 0;
+
+    while start < data.len() {
+        let target = (start + chunk_size).min(data.len());
+        let end = match memchr::memchr(b'\n', &data[target - 1..]) {
+            Some(offset) => target + offset,
+            None => data.len(),
+        };
+        ranges.push(start..end);
+        start = end;
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comments::Continuation;
+    use std::io::Cursor;
+
+    /// 测试基本的行数统计
+    #[test]
+    fn test_count_lines_basic() {
+        let stats = count_lines(Cursor::new("a\n\n  \nb"), &CountOptions::default()).unwrap();
+        assert_eq!(stats, LineStats::new(4, 2, 2));
+    }
+
+    /// 测试无效 UTF-8 时报告出错行号
+    #[test]
+    fn test_count_lines_invalid_utf8() {
+        let err = count_lines(
+            Cursor::new(b"ok\n\xff\xfe\n".to_vec()),
+            &CountOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            LineCounterError::LineReadError { line: 2, .. }
+        ));
+    }
+
+    /// 测试字节切片统计与逐行读取的结果一致
+    #[test]
+    fn test_count_lines_in_slice_matches_reader() {
+        for content in ["", "a", "a\n", "a\n\n  \nb", "\n\n\n", "x\r\n\r\ny\r\n"] {
+            let expected = count_lines(Cursor::new(content), &CountOptions::default()).unwrap();
+            let actual =
+                count_lines_in_slice(content.as_bytes(), &CountOptions::default()).unwrap();
+            assert_eq!(actual, expected, "content: {:?}", content);
+        }
+    }
+
+    /// 测试开头的 UTF-8 BOM 不计入第一行的内容
+    #[test]
+    fn test_count_lines_strips_bom() {
+        let options = CountOptions::default();
+        let content = "\u{FEFF}\n#!/bin/sh\n\u{FEFF}\n";
+        let expected = LineStats::new(3, 2, 1);
+
+        let reader = std::io::BufReader::with_capacity(2, Cursor::new(content));
+        assert_eq!(count_lines(reader, &options).unwrap(), expected);
+        assert_eq!(
+            count_lines_in_slice(content.as_bytes(), &options).unwrap(),
+            expected
+        );
+        for chunks in 1..=4 {
+            assert_eq!(
+                count_lines_in_slice_parallel(content.as_bytes(), &options, chunks).unwrap(),
+                expected
+            );
+        }
+
+        let stats = count_lines(Cursor::new("\u{FEFF}#!/bin/sh\necho\n"), &options).unwrap();
+        assert_eq!(stats.shebang_lines, 1);
+    }
+
+    /// 测试换行符统计，各个统计方式结果一致
+    #[test]
+    fn test_count_line_endings() {
+        let options = CountOptions {
+            line_endings: true,
+            ..CountOptions::default()
+        };
+        let content = "a\nb\r\nc\rd\r\n\r\ne\r";
+        let expected = LineEndings {
+            lf: 1,
+            crlf: 3,
+            cr: 2,
+            missing_final_newline: 0,
+        };
+
+        let reader = std::io::BufReader::with_capacity(3, Cursor::new(content));
+        let stats = count_lines(reader, &options).unwrap();
+        assert_eq!(stats.total_lines, 5);
+        assert_eq!(stats.line_endings, Some(expected));
+        assert_eq!(
+            count_lines_in_slice(content.as_bytes(), &options)
+                .unwrap()
+                .line_endings,
+            Some(expected)
+        );
+        for chunks in 1..=6 {
+            assert_eq!(
+                count_lines_in_slice_parallel(content.as_bytes(), &options, chunks)
+                    .unwrap()
+                    .line_endings,
+                Some(expected)
+            );
+        }
+
+        let stats = count_lines(Cursor::new("a\nb"), &options).unwrap();
+        assert_eq!(stats.line_endings.unwrap().missing_final_newline, 1);
+        let stats = count_lines(Cursor::new(""), &options).unwrap();
+        assert_eq!(stats.line_endings, Some(LineEndings::default()));
+
+        let stats = count_lines(Cursor::new("a\nb"), &CountOptions::default()).unwrap();
+        assert_eq!(stats.line_endings, None);
+    }
+
+    /// 测试行尾空白的统计，各个统计方式结果一致
+    #[test]
+    fn test_count_trailing_whitespace() {
+        let options = CountOptions::default();
+        let content = "// 注释 \nfn main() {\t\r\n    \n}\n中文 \nend\u{3000}\n";
+        let reader = std::io::BufReader::with_capacity(4, Cursor::new(content));
+        let stats = count_lines(reader, &options).unwrap();
+        assert_eq!(stats.trailing_whitespace_lines, 3);
+
+        assert_eq!(
+            count_lines_in_slice(content.as_bytes(), &options).unwrap(),
+            stats
+        );
+        for chunks in 1..=4 {
+            assert_eq!(
+                count_lines_in_slice_parallel(content.as_bytes(), &options, chunks).unwrap(),
+                stats
+            );
+        }
+    }
+
+    /// 测试重复行检测只检查计入统计的非空行
+    #[test]
+    fn test_count_duplicates() {
+        let options = CountOptions {
+            duplicates: Some(DuplicateOptions {
+                top: 1,
+                ..DuplicateOptions::default()
+            }),
+            ..CountOptions::default()
+        };
+        let content = "// comment\n// comment\nok\n\n\nok\nfail\nok";
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let duplicates = stats.duplicates.as_ref().unwrap();
+        assert_eq!(duplicates.lines, 6);
+        assert_eq!(duplicates.duplicates, 3);
+        assert_eq!(duplicates.top[0].text, "ok");
+        assert_eq!(duplicates.top[0].count, 3);
+
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 4).unwrap(),
+            stats
+        );
+        assert_eq!(
+            count_lines(Cursor::new(content), &CountOptions::default())
+                .unwrap()
+                .duplicates,
+            None
+        );
+    }
+
+    /// 测试记录最长的若干行
+    #[test]
+    fn test_count_top_lines() {
+        let options = CountOptions {
+            top_lines: 2,
+            ..CountOptions::default()
+        };
+        let content = "#!/bin/sh\nab\nabcd\n中文\nabcdef\n";
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let lines: Vec<_> = stats
+            .longest_lines
+            .iter()
+            .map(|long_line| (long_line.line, long_line.length.bytes))
+            .collect();
+        // "#!/bin/sh" 最长；"中文" 与 "abcdef" 同为 6 字节，保留较早的行
+        assert_eq!(lines, vec![(1, 9), (4, 6)]);
+        assert_eq!(stats.longest_lines[1].length.cells, 4);
+
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 4).unwrap(),
+            stats
+        );
+        assert!(
+            count_lines(Cursor::new(content), &CountOptions::default())
+                .unwrap()
+                .longest_lines
+                .is_empty()
+        );
+    }
+
+    /// 测试缩进统计
+    #[test]
+    fn test_count_indentation() {
+        let options = CountOptions {
+            indentation: true,
+            ..CountOptions::default()
+        };
+        let content = "fn main() {\n    if x {\n        y();\n\n    }\n\tz();\n}\n";
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let indentation = stats.indentation.unwrap();
+        assert_eq!(indentation.spaces, 3);
+        assert_eq!(indentation.tabs, 1);
+        assert_eq!(indentation.width(), Some(4));
+        assert!(indentation.is_mixed());
+
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 4).unwrap(),
+            stats
+        );
+        assert_eq!(
+            count_lines(Cursor::new(content), &CountOptions::default())
+                .unwrap()
+                .indentation,
+            None
+        );
+    }
+
+    /// 测试跨越缓冲区边界的行
+    #[test]
+    fn test_count_lines_small_buffer() {
+        let content = "第一行比较长\n\n  \r\n第四行也比较长\r\nlast";
+        let reader = std::io::BufReader::with_capacity(4, Cursor::new(content));
+        let stats = count_lines(reader, &CountOptions::default()).unwrap();
+        assert_eq!(stats, LineStats::new(5, 3, 2));
+    }
+
+    /// 测试字节切片中的无效 UTF-8 报告出错行号
+    #[test]
+    fn test_count_lines_in_slice_invalid_utf8() {
+        let err = count_lines_in_slice(b"ok\n\xff\xfe\n", &CountOptions::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            LineCounterError::LineReadError { line: 2, .. }
+        ));
+    }
+
+    /// 构造区段过滤选项
+    fn section_options(outside: bool) -> CountOptions {
+        CountOptions {
+            section: Some(SectionFilter {
+                start: Regex::new("^# BEGIN GENERATED").unwrap(),
+                end: Regex::new("^# END GENERATED").unwrap(),
+                outside,
+            }),
+            ..CountOptions::default()
+        }
+    }
+
+    /// 测试只统计区段内的行
+    #[test]
+    fn test_count_lines_inside_section() {
+        let content = "a\n# BEGIN GENERATED\nx\n\ny\n# END GENERATED\nb\n# BEGIN GENERATED\nz\n";
+        let options = section_options(false);
+
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        assert_eq!(stats, LineStats::new(4, 3, 1));
+        assert_eq!(
+            count_lines_in_slice(content.as_bytes(), &options).unwrap(),
+            stats
+        );
+    }
+
+    /// 测试只统计区段外的行
+    #[test]
+    fn test_count_lines_outside_section() {
+        let content = "a\n# BEGIN GENERATED\nx\n# END GENERATED\n\nb\n";
+        let stats = count_lines(Cursor::new(content), &section_options(true)).unwrap();
+        assert_eq!(stats, LineStats::new(3, 2, 1));
+    }
+
+    /// 测试统计匹配（和不匹配）正则表达式的行数
+    #[test]
+    fn test_count_lines_matching() {
+        let content = "#!/bin/sh\nTODO: a\n\nb\n# todo later\nTODO: c\n";
+        let options = |invert| CountOptions {
+            line_match: Some(LineMatch {
+                pattern: Regex::new("TODO").unwrap(),
+                invert,
+            }),
+            ..CountOptions::default()
+        };
+
+        let stats = count_lines(Cursor::new(content), &options(false)).unwrap();
+        assert_eq!(stats.matched_lines, Some(2));
+        assert_eq!(stats.total_lines, 6);
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options(false), 3).unwrap(),
+            stats
+        );
+
+        // 空行和 shebang 同样参与匹配
+        let stats = count_lines(Cursor::new(content), &options(true)).unwrap();
+        assert_eq!(stats.matched_lines, Some(4));
+
+        let stats = count_lines(Cursor::new(content), &CountOptions::default()).unwrap();
+        assert_eq!(stats.matched_lines, None);
+    }
+
+    /// 测试待办标记的统计和行号（包括开头注释块中的标记）
+    #[test]
+    fn test_count_todos() {
+        let content = "// TODO: header note\n// more\nfn main() {\n    // FIXME later\n}\n";
+        let options = |list| CountOptions {
+            todos: Some(TodoOptions::new(crate::todos::DEFAULT_MARKERS, list)),
+            ..CountOptions::default()
+        };
+
+        let stats = count_lines(Cursor::new(content), &options(true)).unwrap();
+        let todos = stats.todos.as_ref().unwrap();
+        assert_eq!(todos.total(), 2);
+        let lines: Vec<_> = todos.items.iter().map(|item| item.line).collect();
+        assert_eq!(lines, vec![1, 4]);
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options(true), 3).unwrap(),
+            stats
+        );
+
+        let counted =
+            count_lines_in_slice_parallel(content.as_bytes(), &options(false), 3).unwrap();
+        assert_eq!(counted.todos.unwrap().counts["FIXME"], 1);
+    }
+
+    /// 测试按注释语法统计注释行和代码行
+    #[test]
+    fn test_count_comments() {
+        let content = "#!/bin/sh\n// Copyright\n\n/* a\n * b */\nfn main() {} // c\n";
+        let options = CountOptions {
+            comments: Some(CommentSyntax {
+                line: &["//"],
+                block: &[("/*", "*/")],
+                strings: &["\""],
+                continuation: Continuation::NONE,
+            }),
+            ..CountOptions::default()
+        };
+
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let comments = stats.comments.unwrap();
+        assert_eq!(comments.comment_lines, 3);
+        assert_eq!(comments.code_lines, 2);
+        assert_eq!(
+            comments.comment_lines + comments.code_lines,
+            stats.non_empty_lines
+        );
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 3).unwrap(),
+            stats
+        );
+        assert!(
+            count_lines(Cursor::new(content), &CountOptions::default())
+                .unwrap()
+                .comments
+                .is_none()
+        );
+    }
+
+    /// 测试合并续行后的逻辑行数
+    #[test]
+    fn test_count_logical() {
+        let content = "#!/bin/sh\n./configure \\\n  --prefix=/usr \\\n  --quiet\n\nmake\n";
+        let options = CountOptions {
+            logical: Some(CommentSyntax {
+                line: &["#"],
+                block: &[],
+                strings: &["\""],
+                continuation: Continuation::BACKSLASH,
+            }),
+            ..CountOptions::default()
+        };
+
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let logical = stats.logical.unwrap();
+        assert_eq!(logical.physical_lines, 6);
+        assert_eq!(logical.logical_lines, 4);
+        assert_eq!(logical.continued_lines(), 2);
+        // 只统计逻辑行数时不统计注释行
+        assert_eq!(stats.comments, None);
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 3).unwrap(),
+            stats
+        );
+    }
+
+    /// 测试按 CSV 统计记录，引号字段中的换行符不结束记录
+    #[test]
+    fn test_count_records() {
+        let content = "id,note\r\n1,\"第一行\r\n第二行\"\r\n2,\r\n3,x,y\r\n";
+        let options = CountOptions {
+            records: Some(RecordFormat::Csv),
+            ..CountOptions::default()
+        };
+
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        assert_eq!(stats.total_lines, 5);
+        let csv = stats.csv.as_ref().unwrap();
+        assert_eq!(csv.records, 4);
+        assert_eq!(csv.columns, 2);
+        assert_eq!(csv.inconsistent_records, 1);
+        assert_eq!(csv.inconsistent_lines, vec![5]);
+        assert_eq!(csv.empty_fields, 1);
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 3).unwrap(),
+            stats
+        );
+    }
+
+    /// 测试按 JSON Lines 统计记录和检查单个 JSON 文档
+    #[test]
+    fn test_count_json_records() {
+        let content = "{\"id\": 1}\r\n\r\n{\"id\": 2}\r\n{oops}\r\n";
+        let options = CountOptions {
+            records: Some(RecordFormat::JsonLines),
+            ..CountOptions::default()
+        };
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let jsonl = stats.jsonl.as_ref().unwrap();
+        assert_eq!(jsonl.records, 2);
+        assert_eq!(jsonl.invalid_lines, 1);
+        assert_eq!(jsonl.empty_lines, 1);
+        assert_eq!(stats.json, None);
+
+        let options = CountOptions {
+            records: Some(RecordFormat::Json),
+            ..CountOptions::default()
+        };
+        let content = "\u{feff}[\r\n  1,\r\n  \"a\"\r\n]";
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let json = stats.json.as_ref().unwrap();
+        assert!(json.is_valid());
+        assert_eq!(json.array_length, Some(2));
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 3).unwrap(),
+            stats
+        );
+    }
+
+    /// 测试行号范围的解析
+    #[test]
+    fn test_parse_line_range() {
+        let range = |start, end| LineRange { start, end };
+        assert_eq!(parse_line_range("100..500"), Ok(range(100, Some(500))));
+        assert_eq!(parse_line_range("100.."), Ok(range(100, None)));
+        assert_eq!(parse_line_range("..5"), Ok(range(1, Some(5))));
+        assert_eq!(parse_line_range("42"), Ok(range(42, Some(42))));
+
+        assert!(parse_line_range("0..5").is_err());
+        assert!(parse_line_range("5..3").is_err());
+        assert!(parse_line_range("a..b").is_err());
+        assert!(parse_line_range("").is_err());
+    }
+
+    /// 测试只统计行号范围内的行
+    #[test]
+    fn test_count_lines_in_range() {
+        let content = "// Copyright\n\nfn a() {\n    x\n}\r\n\nfn b() {}\n";
+        let options = |start, end| CountOptions {
+            lines: Some(LineRange { start, end }),
+            line_endings: true,
+            top_lines: 1,
+            ..CountOptions::default()
+        };
+
+        let stats = count_lines(Cursor::new(content), &options(3, Some(6))).unwrap();
+        assert_eq!((stats.total_lines, stats.non_empty_lines), (4, 3));
+        let endings = stats.line_endings.unwrap();
+        assert_eq!((endings.lf, endings.crlf), (3, 1));
+        // 行号仍是文件中的行号
+        assert_eq!(stats.longest_lines[0].line, 3);
+        assert_eq!(
+            count_lines_in_slice(content.as_bytes(), &options(3, Some(6))).unwrap(),
+            stats
+        );
+
+        // 范围不从第一行开始时不识别文件头
+        let header = "\n// Copyright 2024\nfn a() {}\n";
+        let stats = count_lines(Cursor::new(header), &options(1, None)).unwrap();
+        assert_eq!(stats.license_lines, 1);
+        let stats = count_lines(Cursor::new(header), &options(2, None)).unwrap();
+        assert_eq!(stats.license_lines, 0);
+        assert_eq!(stats.total_lines, 2);
+
+        // 超出文件末尾的范围
+        let stats = count_lines(Cursor::new(content), &options(7, None)).unwrap();
+        assert_eq!(stats.total_lines, 1);
+        let stats = count_lines(Cursor::new(content), &options(100, None)).unwrap();
+        assert_eq!(stats.total_lines, 0);
+    }
+
+    /// 测试 shebang 和许可证头的识别
+    #[test]
+    fn test_count_lines_headers() {
+        let content = "#!/usr/bin/env python3\n# Copyright 2024 Example\n# Licensed under MIT\n\nprint('hi')\n";
+        let stats = count_lines(Cursor::new(content), &CountOptions::default()).unwrap();
+        assert_eq!(stats.shebang_lines, 1);
+        assert_eq!(stats.license_lines, 2);
+        assert_eq!(stats.total_lines, 5);
+        assert_eq!(stats.non_empty_lines, 4);
+    }
+
+    /// 测试排除文件头
+    #[test]
+    fn test_count_lines_exclude_headers() {
+        let content = "// SPDX-License-Identifier: MIT\n// Copyright Example\nfn main() {}\n";
+        let mut options = CountOptions::default();
+        options.headers.exclude = true;
+
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        assert_eq!(stats.license_lines, 2);
+        assert_eq!(
+            stats,
+            count_lines_in_slice(content.as_bytes(), &options).unwrap()
+        );
+        assert_eq!(stats.total_lines, 1);
+        assert_eq!(stats.non_empty_lines, 1);
+    }
+
+    /// 测试普通的开头注释不视为许可证头
+    #[test]
+    fn test_count_lines_plain_comment_not_license() {
+        let content = "// 工具函数\nfn main() {}\n";
+        let mut options = CountOptions::default();
+        options.headers.exclude = true;
+
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        assert_eq!(stats.license_lines, 0);
+        assert_eq!(stats.total_lines, 2);
+    }
+
+    /// 测试字节级空行判断与 `str::trim` 一致
+    #[test]
+    fn test_is_blank_line() {
+        for line in [
+            "",
+            " ",
+            "\t \x0B\x0C\r",
+            "a",
+            "  x  ",
+            "\u{3000}",
+            "\u{85}",
+            "\u{a0}",
+            "中文",
+            " \u{2003}b",
+        ] {
+            assert_eq!(
+                is_blank_line(line.as_bytes()),
+                Ok(line.trim().is_empty()),
+                "line: {:?}",
+                line
+            );
+        }
+        assert!(is_blank_line(b"\xff").is_err());
+    }
+
+    /// 测试文件头之后的无效 UTF-8 仍然报告准确的行号
+    #[test]
+    fn test_count_lines_invalid_utf8_after_header() {
+        let data = b"fn main() {}\n\n\xff\xfe\n";
+        let err = count_lines_in_slice(data, &CountOptions::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            LineCounterError::LineReadError { line: 3, .. }
+        ));
+    }
+
+    /// 测试统计二进制内容时无效 UTF-8 的行计为非空行
+    #[test]
+    fn test_count_lines_binary() {
+        let data = b"\xff\xfe\n\n\x00\x01\n  \n\xc3";
+        let options = CountOptions {
+            binary: true,
+            ..CountOptions::default()
+        };
+        let stats = count_lines_in_slice(data, &options).unwrap();
+        assert_eq!(stats, LineStats::new(5, 3, 2));
+        assert_eq!(count_lines(Cursor::new(data), &options).unwrap(), stats);
+    }
+
+    /// 测试 `lossy` 时无效 UTF-8 的行按替换字符处理并计数
+    #[test]
+    fn test_count_lines_lossy() {
+        let data = b"GET /\n\xff\xfe\n\nok \xc3(\n\xc3";
+        let options = CountOptions {
+            lossy: true,
+            ..CountOptions::default()
+        };
+        let stats = count_lines_in_slice(data, &options).unwrap();
+        assert_eq!(stats.total_lines, 5);
+        assert_eq!(stats.non_empty_lines, 4);
+        assert_eq!(stats.invalid_utf8_lines, Some(3));
+        assert_eq!(count_lines(Cursor::new(data), &options).unwrap(), stats);
+
+        let options = CountOptions {
+            lossy: true,
+            words: true,
+            ..CountOptions::default()
+        };
+        let stats = count_lines(Cursor::new(data), &options).unwrap();
+        assert_eq!(stats.invalid_utf8_lines, Some(3));
+        assert_eq!(stats.non_empty_lines, 4);
+    }
+
+    /// 测试按原始字节统计：不校验 UTF-8，只按 ASCII 空白字符判断空行
+    #[test]
+    fn test_count_byte_lines() {
+        let data = b"\xef\xbb\xbfGET /\r\n\xff\xfe\x00\n \t\r\n\nend \n\xc3";
+        let options = CountOptions {
+            bytes: true,
+            ..CountOptions::default()
+        };
+        let stats = count_lines(Cursor::new(data), &options).unwrap();
+        assert_eq!(stats.total_lines, 6);
+        assert_eq!(stats.non_empty_lines, 4);
+        assert_eq!(stats.empty_lines, 2);
+        assert_eq!(stats.trailing_whitespace_lines, 1);
+        assert_eq!(stats.max_line_bytes, Some(8));
+
+        let stats = count_byte_lines(Cursor::new(""), |_, _| {}).unwrap();
+        assert_eq!(stats.total_lines, 0);
+        assert_eq!(stats.max_line_bytes, Some(0));
+    }
+
+    /// 测试最长行长度的统计
+    #[test]
+    fn test_count_lines_line_lengths() {
+        let content = "short\n中文中文中文\r\n\nabcdefghij\n";
+        let options = CountOptions {
+            line_lengths: true,
+            ..CountOptions::default()
+        };
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        assert_eq!(
+            stats.max_line_length,
+            Some(LineLength {
+                bytes: 18,
+                chars: 10,
+                cells: 12
+            })
+        );
+        // 各行的列宽为 5、12、0、10
+        let distribution = stats.line_length_distribution.as_ref().unwrap();
+        assert_eq!(distribution.lines(), 4);
+        assert_eq!(distribution.median(), Some(5));
+        assert_eq!(distribution.percentile(90.0), Some(12));
+        assert_eq!(
+            count_lines_in_slice(content.as_bytes(), &options).unwrap(),
+            stats
+        );
+
+        let stats = count_lines(Cursor::new(content), &CountOptions::default()).unwrap();
+        assert_eq!(stats.max_line_length, None);
+        assert_eq!(stats.line_length_distribution, None);
+    }
+
+    /// 测试按 Unicode 单词边界统计单词数，每个汉字计为一个单词
+    #[test]
+    fn test_count_lines_words() {
+        let content = "# Hello, world!\n\nIt's 3.5 km — 你好世界\n";
+        let options = CountOptions {
+            words: true,
+            ..CountOptions::default()
+        };
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        // Hello、world、It's、3.5、km 和 4 个汉字
+        assert_eq!(stats.words, Some(9));
+        assert_eq!(
+            count_lines_in_slice(content.as_bytes(), &options).unwrap(),
+            stats
+        );
+
+        let stats = count_lines(Cursor::new(content), &CountOptions::default()).unwrap();
+        assert_eq!(stats.words, None);
+    }
+
+    /// 测试统计 token 数，包括文件头和空行，分块并行统计的结果相同
+    #[test]
+    fn test_count_lines_tokens() {
+        let content = "#!/bin/sh\n# Copyright 2026\n\necho 你好 \"world\"\n";
+        let options = CountOptions {
+            tokens: Some(Tokenizer::Approx),
+            ..CountOptions::default()
+        };
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let expected: usize = content
+            .lines()
+            .map(|line| Tokenizer::Approx.count_line(line))
+            .sum();
+        let tokens = stats.tokens.unwrap();
+        assert_eq!(tokens.tokens, expected);
+        assert!(tokens.estimated);
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 3).unwrap(),
+            stats
+        );
+
+        let stats = count_lines(Cursor::new(content), &CountOptions::default()).unwrap();
+        assert_eq!(stats.tokens, None);
+    }
+
+    /// 测试统计出现次数最多的单词
+    #[test]
+    fn test_count_lines_word_freq() {
+        let content = "Apples and pears\n\napples, APPLES\n";
+        let options = CountOptions {
+            word_freq: Some(WordFreqOptions {
+                top: 1,
+                ..WordFreqOptions::default()
+            }),
+            ..CountOptions::default()
+        };
+        let stats = count_lines(Cursor::new(content), &options).unwrap();
+        let word_freq = stats.word_freq.as_ref().unwrap();
+        assert_eq!(word_freq.words, 5);
+        assert_eq!(word_freq.top[0].word, "apples");
+        assert_eq!(word_freq.top[0].count, 3);
+        assert_eq!(
+            count_lines_in_slice_parallel(content.as_bytes(), &options, 4).unwrap(),
+            stats
+        );
+    }
+
+    /// 测试流式统计的中间结果单调增加，最终结果与普通统计一致
+    #[test]
+    fn test_count_lines_streaming() {
+        let content = "line\n\n".repeat(100);
+        let mut snapshots = Vec::new();
+        let reader = std::io::BufReader::with_capacity(16, Cursor::new(&content));
+        let stats = count_lines_streaming(reader, &CountOptions::default(), |stats, bytes| {
+            snapshots.push((stats.total_lines, bytes));
+        })
+        .unwrap();
+
+        assert_eq!(stats, LineStats::new(200, 100, 100));
+        assert!(
+            snapshots
+                .windows(2)
+                .all(|w| w[0].0 <= w[1].0 && w[0].1 < w[1].1)
+        );
+        assert_eq!(snapshots.last().unwrap().1, content.len() as u64);
+    }
+
+    /// 测试分块并行统计与顺序统计结果一致
+    #[test]
+    fn test_count_lines_in_slice_parallel_matches_sequential() {
+        let body = "fn main() {}\n\n    let x = 1;\r\n".repeat(50);
+        let contents = [
+            String::new(),
+            "a".to_string(),
+            "\n\n\n".to_string(),
+            body.clone(),
+            format!(
+                "#!/bin/sh\n\n// Copyright 2024\n// SPDX-License-Identifier: MIT\n{}",
+                body
+            ),
+            format!("{}tail without newline", body),
+        ];
+
+        let mut excluded = CountOptions::default();
+        excluded.headers.exclude = true;
+
+        for options in [CountOptions::default(), excluded] {
+            for content in &contents {
+                let expected = count_lines_in_slice(content.as_bytes(), &options).unwrap();
+                for chunks in [1, 2, 3, 7, 64] {
+                    let actual =
+                        count_lines_in_slice_parallel(content.as_bytes(), &options, chunks)
+                            .unwrap();
+                    assert_eq!(
+                        actual, expected,
+                        "chunks: {}, content: {:?}",
+                        chunks, content
+                    );
+                }
+            }
+        }
+    }
+
+    /// 测试分块并行统计报告准确的出错行号
+    #[test]
+    fn test_count_lines_in_slice_parallel_invalid_utf8() {
+        let mut data = "line\n".repeat(100).into_bytes();
+        data.extend_from_slice(b"\xff\n");
+        let err = count_lines_in_slice_parallel(&data, &CountOptions::default(), 4).unwrap_err();
+        assert!(matches!(
+            err,
+            LineCounterError::LineReadError { line: 101, .. }
+        ));
+    }
+
+    /// 测试分块范围覆盖全部数据且在换行符处切分
+    #[test]
+    fn test_chunk_ranges() {
+        let data = b"aa\nbb\ncc\ndd";
+        let ranges = chunk_ranges(data, 3);
+        assert_eq!(ranges.first().unwrap().start, 0);
+        assert_eq!(ranges.last().unwrap().end, data.len());
+        for pair in ranges.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+            assert_eq!(data[pair[0].end - 1], b'\n');
+        }
+    }
+
+    /// 测试按位置读取的分段并行统计与顺序统计结果一致
+    #[test]
+    fn test_count_lines_in_ranges_parallel_matches_sequential() {
+        let body = "fn main() {}\n\n    let x = 1;\r\n".repeat(50);
+        let contents = [
+            String::new(),
+            "a".to_string(),
+            "\n\n\n".to_string(),
+            format!("\u{feff}{}", body),
+            format!(
+                "#!/bin/sh\n\n// Copyright 2024\n// SPDX-License-Identifier: MIT\n{}",
+                body
+            ),
+            format!("{}tail without newline", body),
+        ];
+
+        let mut excluded = CountOptions::default();
+        excluded.headers.exclude = true;
+
+        for options in [CountOptions::default(), excluded] {
+            for content in &contents {
+                let data = content.as_bytes();
+                let expected = count_lines_in_slice(data, &options).unwrap();
+                for (chunks, buffer_size) in [(1, 8192), (2, 7), (3, 64), (7, 1), (64, 16)] {
+                    let actual = count_lines_in_ranges_parallel(
+                        data,
+                        data.len() as u64,
+                        &options,
+                        chunks,
+                        buffer_size,
+                    )
+                    .unwrap();
+                    assert_eq!(
+                        actual, expected,
+                        "chunks: {}, content: {:?}",
+                        chunks, content
+                    );
+                }
+            }
+        }
+
+        let mut data = "line\n".repeat(100).into_bytes();
+        data.extend_from_slice(b"\xff\nend\n");
+        let err = count_lines_in_ranges_parallel(
+            &data[..],
+            data.len() as u64,
+            &CountOptions::default(),
+            4,
+            16,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            LineCounterError::LineReadError { line: 101, .. }
+        ));
+    }
+
+    /// 测试按位置读取切分的范围与切片切分的范围相同
+    #[test]
+    fn test_chunk_ranges_at() {
+        let data = b"aa\nbb\ncc\ndd\n\n\nlonger line\nx";
+        for chunks in [1, 2, 3, 5, 100] {
+            let expected: Vec<Range<u64>> = chunk_ranges(data, chunks)
+                .into_iter()
+                .map(|range| range.start as u64..range.end as u64)
+                .collect();
+            let actual = chunk_ranges_at(&data[..], 0..data.len() as u64, chunks).unwrap();
+            assert_eq!(actual, expected, "chunks: {}", chunks);
+        }
+    }
+
+    /// 测试异步统计与同步统计结果一致，且返回的 future 可以在多线程运行时中使用
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_count_lines_async() {
+        fn assert_send<T: Send>(value: T) -> T {
+            value
+        }
+
+        let content = "#!/bin/sh\n// Copyright 2024\n\necho 1\r\n\ntail".repeat(20);
+        let mut excluded = CountOptions::default();
+        excluded.headers.exclude = true;
+        for options in [CountOptions::default(), excluded] {
+            let expected = count_lines(Cursor::new(&content), &options).unwrap();
+            for capacity in [1, 7, 8192] {
+                let reader = tokio::io::BufReader::with_capacity(capacity, content.as_bytes());
+                let actual = assert_send(count_lines_async(reader, &options))
+                    .await
+                    .unwrap();
+                assert_eq!(actual, expected, "capacity: {}", capacity);
+            }
+        }
+
+        let err = count_lines_async(&b"ok\n\xff\n"[..], &CountOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            LineCounterError::LineReadError { line: 2, .. }
+        ));
+
+        // 按原始字节统计时不校验 UTF-8，结果与同步版本一致
+        let bytes = CountOptions {
+            bytes: true,
+            ..CountOptions::default()
+        };
+        let content = b"ok\n\xff\xfe \r\n\n  \ntail".repeat(20);
+        let expected = count_byte_lines(Cursor::new(&content), |_, _| {}).unwrap();
+        assert_eq!(expected.total_lines, 81);
+        for capacity in [1, 7, 8192] {
+            let reader = tokio::io::BufReader::with_capacity(capacity, &content[..]);
+            let actual = assert_send(count_lines_async(reader, &bytes))
+                .await
+                .unwrap();
+            assert_eq!(actual, expected, "capacity: {}", capacity);
+        }
+    }
+}
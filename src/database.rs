@@ -0,0 +1,233 @@
+//! SQLite 报告存储
+//!
+//! `--output-db stats.db` 把每次运行的结果追加写入 SQLite 数据库，便于用 SQL 查询和跟踪行数趋势：
+//!
+//! - `runs`：每次运行一行，包括起止时间、工具版本、主机名和配置哈希
+//! - `files`：每次运行的每个文件一行，通过 `run_id` 关联到 `runs`
+//! - `file_history`：连接两张表的视图，每行带有所属运行的时间
+//!
+//! 数据库不存在时自动创建；表结构版本记录在 `PRAGMA user_version` 中。
+//!
+//! ```sql
+//! SELECT started_at, SUM(total_lines) FROM file_history GROUP BY run_id ORDER BY started_at;
+//! ```
+
+use crate::error::{LineCounterError, Result};
+use crate::meta::RunMetadata;
+use crate::report::FileReport;
+use rusqlite::{Connection, params};
+use std::path::Path;
+
+/// 表结构版本，表结构发生不兼容变化时递增
+pub const SCHEMA_VERSION: i32 = 1;
+
+/// 建表语句
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    tool_version TEXT NOT NULL,
+    host TEXT NOT NULL,
+    config_hash TEXT NOT NULL,
+    command_line TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS files (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    path TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    total_lines INTEGER NOT NULL,
+    non_empty_lines INTEGER NOT NULL,
+    empty_lines INTEGER NOT NULL,
+    language TEXT,
+    encoding TEXT,
+    skipped TEXT
+);
+CREATE INDEX IF NOT EXISTS files_run_id ON files(run_id);
+CREATE INDEX IF NOT EXISTS files_path ON files(path);
+CREATE VIEW IF NOT EXISTS file_history AS
+    SELECT runs.started_at, files.* FROM files JOIN runs ON runs.id = files.run_id;
+";
+
+/// 跳过的原因，写入 `files.skipped` 列；未跳过的文件为 `None`
+fn skip_reason(report: &FileReport) -> Option<&'static str> {
+    if report.binary {
+        Some("binary")
+    } else if report.missing {
+        Some("missing")
+    } else if report.limit_exceeded.is_some() {
+        Some("limit-exceeded")
+    } else if report.timed_out {
+        Some("timed-out")
+    } else if report.error.is_some() {
+        Some("error")
+    } else {
+        None
+    }
+}
+
+/// 打开数据库，必要时创建表结构
+///
+/// # 参数
+///
+/// * `path` - 数据库文件路径
+///
+/// # 返回值
+///
+/// * `Ok(Connection)` - 数据库连接
+/// * `Err(LineCounterError)` - 无法打开，或表结构版本比本工具支持的更新
+pub fn open(path: &Path) -> Result<Connection> {
+    let connection = Connection::open(path)?;
+    let version: i32 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        return Err(LineCounterError::UnsupportedDatabase {
+            version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    connection.execute_batch(SCHEMA)?;
+    connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    Ok(connection)
+}
+
+/// 写入一次运行的结果
+///
+/// 运行和所有文件在同一个事务中写入，中途失败时数据库保持不变。
+///
+/// # 参数
+///
+/// * `connection` - 数据库连接
+/// * `meta` - 运行元数据
+/// * `reports` - 单个文件的统计报告
+///
+/// # 返回值
+///
+/// * `Ok(i64)` - 新运行的编号（`runs.id`）
+/// * `Err(LineCounterError)` - 写入失败
+pub fn write_run(
+    connection: &mut Connection,
+    meta: &RunMetadata,
+    reports: &[FileReport],
+) -> Result<i64> {
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "INSERT INTO runs (started_at, finished_at, duration_ms, tool_version, host, config_hash, command_line)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            meta.started_at,
+            meta.finished_at,
+            meta.duration_ms as i64,
+            meta.version,
+            meta.host,
+            meta.config_hash,
+            meta.command_line.join(" "),
+        ],
+    )?;
+    let run_id = transaction.last_insert_rowid();
+
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO files (run_id, path, size, total_lines, non_empty_lines, empty_lines, language, encoding, skipped)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for report in reports {
+            insert.execute(params![
+                run_id,
+                report.path.to_string_lossy(),
+                report.size as i64,
+                report.stats.total_lines as i64,
+                report.stats.non_empty_lines as i64,
+                report.stats.empty_lines as i64,
+                report.language,
+                report.encoding,
+                skip_reason(report),
+            ])?;
+        }
+    }
+
+    transaction.commit()?;
+    Ok(run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::LineStats;
+    use std::path::PathBuf;
+    use std::time::SystemTime;
+
+    /// 测试多次运行追加写入同一个数据库
+    #[test]
+    fn test_write_runs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("stats.db");
+        let meta = RunMetadata::new(
+            vec!["line-counter".to_string()],
+            &(),
+            SystemTime::now(),
+            SystemTime::now(),
+        );
+        let report = |lines| FileReport {
+            path: PathBuf::from("src/a.rs"),
+            size: 10,
+            stats: LineStats::new(lines, lines - 1, 1),
+            binary: false,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: Some("Rust"),
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+
+        let mut connection = open(&path).unwrap();
+        let first = write_run(&mut connection, &meta, &[report(5)]).unwrap();
+        drop(connection);
+
+        let mut connection = open(&path).unwrap();
+        let reports = [report(8), FileReport::missing(PathBuf::from("gone.rs"))];
+        let second = write_run(&mut connection, &meta, &reports).unwrap();
+        assert_ne!(first, second);
+
+        let lines: Vec<(i64, i64)> = connection
+            .prepare("SELECT run_id, total_lines FROM file_history WHERE path = 'src/a.rs' ORDER BY run_id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(lines, vec![(first, 5), (second, 8)]);
+
+        let skipped: String = connection
+            .query_row(
+                "SELECT skipped FROM files WHERE path = 'gone.rs'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(skipped, "missing");
+    }
+
+    /// 测试拒绝更新版本的表结构
+    #[test]
+    fn test_newer_schema() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("stats.db");
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(matches!(
+            open(&path),
+            Err(LineCounterError::UnsupportedDatabase { version: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,487 @@
+//! 透明解压缩及其安全限制
+//!
+//! 以 gzip、zstd、bzip2 或 xz 压缩的输入（如轮转后的 `app.log.gz`）按开头的魔数识别（[`Compression::detect`]），
+//! 统计解压后的内容；报告中的大小仍是压缩文件的大小，语言按去掉压缩扩展名后的文件名识别。
+//! 多个压缩流首尾相接的文件（如 `cat a.gz b.gz`）会全部解压。使用 `--no-decompress` 时按原始字节统计。
+//!
+//! 统计不可信来源的压缩内容时，少量压缩数据可能解压出极大的内容（zip 炸弹）。
+//! 所有解压缩路径都通过 [`guarded`] 包装解码器，逐块检查两项限制：
+//!
+//! - 解压后的总字节数不超过 [`DecompressionLimits::max_size`]（`--max-decompressed-size`，默认 1G）
+//! - 解压后字节数与已读取的压缩字节数之比不超过 [`DecompressionLimits::max_ratio`]
+//!   （`--max-compression-ratio`，默认 100）；解压出的内容不足 [`RATIO_GRACE_BYTES`] 时不检查比例，
+//!   避免很小但高度重复的文件被误判
+//!
+//! 超过限制时读取返回携带 [`LimitExceeded`] 的 IO 错误，调用方用 [`limit_exceeded`] 识别后
+//! 把该输入报告为跳过，而不是使整个命令失败。
+
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 默认的解压后大小上限
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: u64 = 1024 * 1024 * 1024; // 1GB
+
+/// 默认的压缩比上限
+pub const DEFAULT_MAX_RATIO: f64 = 100.0;
+
+/// 解压出的内容达到该大小后才检查压缩比
+pub const RATIO_GRACE_BYTES: u64 = 1024 * 1024; // 1MB
+
+/// 解压缩限制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecompressionLimits {
+    /// 解压后的大小上限（字节），0 表示不限制
+    pub max_size: u64,
+    /// 压缩比上限，0 表示不限制
+    pub max_ratio: f64,
+}
+
+impl Default for DecompressionLimits {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+            max_ratio: DEFAULT_MAX_RATIO,
+        }
+    }
+}
+
+/// 超过的解压缩限制
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "limit", rename_all = "kebab-case")]
+pub enum LimitExceeded {
+    /// 解压后的大小超过上限
+    Size {
+        /// 大小上限（字节）
+        max_size: u64,
+    },
+    /// 压缩比超过上限
+    Ratio {
+        /// 压缩比上限
+        max_ratio: f64,
+    },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Size { max_size } => {
+                write!(f, "解压后的大小超过上限 {} bytes", max_size)
+            }
+            LimitExceeded::Ratio { max_ratio } => {
+                write!(f, "压缩比超过上限 {}", max_ratio)
+            }
+        }
+    }
+}
+
+impl Error for LimitExceeded {}
+
+/// 从错误链中找出解压缩限制错误
+///
+/// # 参数
+///
+/// * `error` - 统计时返回的错误
+///
+/// # 返回值
+///
+/// * `Some(LimitExceeded)` - 因超过解压缩限制而失败
+/// * `None` - 其他错误
+pub fn limit_exceeded(error: &anyhow::Error) -> Option<LimitExceeded> {
+    error.chain().find_map(|cause| {
+        cause
+            .downcast_ref::<LimitExceeded>()
+            .or_else(|| {
+                cause
+                    .downcast_ref::<io::Error>()
+                    .and_then(|error| error.get_ref())
+                    .and_then(|inner| inner.downcast_ref::<LimitExceeded>())
+            })
+            .copied()
+    })
+}
+
+/// 压缩格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// gzip（`.gz`）
+    Gzip,
+    /// Zstandard（`.zst`）
+    Zstd,
+    /// bzip2（`.bz2`）
+    Bzip2,
+    /// xz（`.xz`）
+    Xz,
+}
+
+impl Compression {
+    /// 根据内容开头的魔数识别压缩格式
+    ///
+    /// # 参数
+    ///
+    /// * `sample` - 输入开头的数据
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(Compression)` - 识别出的压缩格式
+    /// * `None` - 不是支持的压缩格式
+    pub fn detect(sample: &[u8]) -> Option<Self> {
+        const MAGICS: &[(&[u8], Compression)] = &[
+            (&[0x1f, 0x8b], Compression::Gzip),
+            (&[0x28, 0xb5, 0x2f, 0xfd], Compression::Zstd),
+            (b"BZh", Compression::Bzip2),
+            (&[0xfd, b'7', b'z', b'X', b'Z', 0x00], Compression::Xz),
+        ];
+        MAGICS
+            .iter()
+            .find(|(magic, _)| sample.starts_with(magic))
+            .map(|&(_, compression)| compression)
+    }
+
+    /// 压缩格式对应的扩展名（不含 `.`）
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+            Compression::Bzip2 => "bz2",
+            Compression::Xz => "xz",
+        }
+    }
+
+    /// 打开受解压缩限制保护的解码器
+    ///
+    /// # 参数
+    ///
+    /// * `source` - 压缩数据
+    /// * `limits` - 解压缩限制
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(Box<dyn Read>)` - 读取解压后内容的读取器
+    /// * `Err(io::Error)` - 无法创建解码器
+    pub fn decoder<'a, R: Read + 'a>(
+        self,
+        source: R,
+        limits: DecompressionLimits,
+    ) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Compression::Gzip => {
+                Box::new(guarded(source, limits, flate2::read::MultiGzDecoder::new))
+            }
+            Compression::Zstd => Box::new(try_guarded(source, limits, zstd::Decoder::new)?),
+            Compression::Bzip2 => {
+                Box::new(guarded(source, limits, bzip2::read::MultiBzDecoder::new))
+            }
+            Compression::Xz => Box::new(guarded(
+                source,
+                limits,
+                xz2::read::XzDecoder::new_multi_decoder,
+            )),
+        })
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+        })
+    }
+}
+
+/// 去掉压缩扩展名，得到压缩前的文件名（用于识别语言）
+///
+/// # 参数
+///
+/// * `path` - 压缩文件的路径
+/// * `compression` - 压缩格式
+///
+/// # 返回值
+///
+/// 扩展名与压缩格式一致时为去掉扩展名的路径（如 `main.rs.gz` → `main.rs`），否则为原路径
+pub fn inner_path(path: &Path, compression: Compression) -> PathBuf {
+    let matches = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(compression.extension()));
+    if matches {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// 统计压缩数据已读取字节数的读取器，交给解码器读取
+pub struct CompressedSource<R> {
+    inner: R,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CompressedSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+/// 受解压缩限制保护的解码器
+pub struct Guarded<D> {
+    decoder: D,
+    compressed: Arc<AtomicU64>,
+    decompressed: u64,
+    limits: DecompressionLimits,
+}
+
+impl<D> Guarded<D> {
+    /// 检查已解压的字节数是否超过限制
+    fn check(&self) -> Result<(), LimitExceeded> {
+        let limits = self.limits;
+        if limits.max_size > 0 && self.decompressed > limits.max_size {
+            return Err(LimitExceeded::Size {
+                max_size: limits.max_size,
+            });
+        }
+        let compressed = self.compressed.load(Ordering::Relaxed).max(1);
+        if limits.max_ratio > 0.0
+            && self.decompressed > RATIO_GRACE_BYTES
+            && self.decompressed as f64 > compressed as f64 * limits.max_ratio
+        {
+            return Err(LimitExceeded::Ratio {
+                max_ratio: limits.max_ratio,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<D: Read> Read for Guarded<D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.decoder.read(buf)?;
+        self.decompressed += read as u64;
+        self.check()
+            .map_err(|exceeded| io::Error::new(io::ErrorKind::InvalidData, exceeded))?;
+        Ok(read)
+    }
+}
+
+/// 用解压缩限制包装解码器
+///
+/// # 参数
+///
+/// * `source` - 压缩数据
+/// * `limits` - 解压缩限制
+/// * `decoder` - 根据压缩数据构造解码器，如 `|source| GzDecoder::new(source)`
+///
+/// # 返回值
+///
+/// 读取解压后内容的读取器，超过限制时返回携带 [`LimitExceeded`] 的 IO 错误
+pub fn guarded<R, D>(
+    source: R,
+    limits: DecompressionLimits,
+    decoder: impl FnOnce(CompressedSource<R>) -> D,
+) -> Guarded<D>
+where
+    R: Read,
+    D: Read,
+{
+    match try_guarded(source, limits, |source| {
+        Ok::<_, Infallible>(decoder(source))
+    }) {
+        Ok(guarded) => guarded,
+        Err(never) => match never {},
+    }
+}
+
+/// 用解压缩限制包装构造时可能失败的解码器
+///
+/// # 参数
+///
+/// * `source` - 压缩数据
+/// * `limits` - 解压缩限制
+/// * `decoder` - 根据压缩数据构造解码器，如 `zstd::Decoder::new`
+///
+/// # 返回值
+///
+/// * `Ok(Guarded<D>)` - 读取解压后内容的读取器
+/// * `Err(E)` - 构造解码器失败
+pub fn try_guarded<R, D, E>(
+    source: R,
+    limits: DecompressionLimits,
+    decoder: impl FnOnce(CompressedSource<R>) -> Result<D, E>,
+) -> Result<Guarded<D>, E>
+where
+    R: Read,
+    D: Read,
+{
+    let compressed = Arc::new(AtomicU64::new(0));
+    let source = CompressedSource {
+        inner: source,
+        bytes_read: Arc::clone(&compressed),
+    };
+    Ok(Guarded {
+        decoder: decoder(source)?,
+        compressed,
+        decompressed: 0,
+        limits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    /// 把每个字节重复多次的“解码器”，模拟高压缩比的数据
+    struct Repeat<R> {
+        inner: R,
+        factor: usize,
+    }
+
+    impl<R: Read> Read for Repeat<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut byte = [0u8];
+            if self.inner.read(&mut byte)? == 0 {
+                return Ok(0);
+            }
+            let len = self.factor.min(buf.len());
+            buf[..len].fill(byte[0]);
+            Ok(len)
+        }
+    }
+
+    fn read_all(reader: &mut impl Read) -> io::Result<u64> {
+        io::copy(reader, &mut io::sink())
+    }
+
+    /// 测试解压后大小的限制
+    #[test]
+    fn test_size_limit() {
+        let limits = DecompressionLimits {
+            max_size: 100,
+            max_ratio: 0.0,
+        };
+        let mut reader = guarded(Cursor::new(vec![b'a'; 100]), limits, |source| source);
+        assert_eq!(read_all(&mut reader).unwrap(), 100);
+
+        let mut reader = guarded(Cursor::new(vec![b'a'; 101]), limits, |source| source);
+        let error = read_all(&mut reader).unwrap_err();
+        let error = anyhow::Error::new(error).context("统计文件时发生错误");
+        assert_eq!(
+            limit_exceeded(&error),
+            Some(LimitExceeded::Size { max_size: 100 })
+        );
+    }
+
+    /// 测试压缩比的限制
+    #[test]
+    fn test_ratio_limit() {
+        let limits = DecompressionLimits {
+            max_size: 0,
+            max_ratio: 100.0,
+        };
+        // 压缩比 1000，但解压出的内容不足宽限大小
+        let mut reader = guarded(Cursor::new(vec![b'a'; 1000]), limits, |source| Repeat {
+            inner: source,
+            factor: 1000,
+        });
+        assert_eq!(read_all(&mut reader).unwrap(), 1_000_000);
+
+        // 压缩比 1000，超过宽限大小后失败
+        let mut reader = guarded(Cursor::new(vec![b'a'; 2000]), limits, |source| Repeat {
+            inner: source,
+            factor: 1000,
+        });
+        let error = anyhow::Error::new(read_all(&mut reader).unwrap_err());
+        assert_eq!(
+            limit_exceeded(&error),
+            Some(LimitExceeded::Ratio { max_ratio: 100.0 })
+        );
+
+        // 压缩比 50，不超过上限
+        let mut reader = guarded(Cursor::new(vec![b'a'; 100_000]), limits, |source| Repeat {
+            inner: source,
+            factor: 50,
+        });
+        assert_eq!(read_all(&mut reader).unwrap(), 5_000_000);
+    }
+
+    /// 测试按魔数识别压缩格式和去掉压缩扩展名
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            Compression::detect(&[0x1f, 0x8b, 8]),
+            Some(Compression::Gzip)
+        );
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0]),
+            Some(Compression::Zstd)
+        );
+        assert_eq!(Compression::detect(b"BZh91AY"), Some(Compression::Bzip2));
+        assert_eq!(
+            Compression::detect(b"\xfd7zXZ\x00\x00"),
+            Some(Compression::Xz)
+        );
+        assert_eq!(Compression::detect(b"fn main() {}"), None);
+        assert_eq!(Compression::detect(&[0x1f]), None);
+
+        let inner = |path: &str, compression| inner_path(Path::new(path), compression);
+        assert_eq!(
+            inner("src/main.rs.gz", Compression::Gzip),
+            Path::new("src/main.rs")
+        );
+        assert_eq!(inner("app.log.XZ", Compression::Xz), Path::new("app.log"));
+        // 扩展名与实际格式不一致时保留原路径
+        assert_eq!(inner("data.gz", Compression::Zstd), Path::new("data.gz"));
+    }
+
+    /// 测试各种压缩格式的解压，包括首尾相接的多个压缩流
+    #[test]
+    fn test_decoders() {
+        let text = b"line 1\nline 2\n";
+        let mut gzip = Vec::new();
+        for _ in 0..2 {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(text).unwrap();
+            gzip.extend(encoder.finish().unwrap());
+        }
+        let zstd = zstd::encode_all(&text[..], 0).unwrap();
+        let mut bzip2 = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip2.write_all(text).unwrap();
+        let mut xz = xz2::write::XzEncoder::new(Vec::new(), 6);
+        xz.write_all(text).unwrap();
+
+        let cases = [
+            (gzip, 2),
+            (zstd, 1),
+            (bzip2.finish().unwrap(), 1),
+            (xz.finish().unwrap(), 1),
+        ];
+        for (data, copies) in cases {
+            let compression = Compression::detect(&data).unwrap();
+            let mut decoder = compression
+                .decoder(Cursor::new(data), DecompressionLimits::default())
+                .unwrap();
+            let mut decoded = Vec::new();
+            decoder.read_to_end(&mut decoded).unwrap();
+            assert_eq!(decoded, text.repeat(copies), "{}", compression);
+        }
+    }
+
+    /// 测试其他错误不被识别为限制错误
+    #[test]
+    fn test_other_errors() {
+        let error = anyhow::Error::new(io::Error::other("磁盘错误"));
+        assert_eq!(limit_exceeded(&error), None);
+    }
+}
@@ -0,0 +1,177 @@
+//! 差异模式
+//!
+//! `line-counter diff <旧> <新>` 比较两个输入的行数统计并输出变化量。
+//! 输入可以是两个文件，也可以是两份由 `--format json` 生成的报告（`--report`），
+//! 后者比较报告中的汇总结果。
+//!
+//! `--ignore-blank-changes` 忽略只涉及空行的变化（见 [`StatsDiff::ignore_blank_changes`]），
+//! 增删或移动空行不会被算作增长，比较只关注非空行数的变化。
+
+use crate::app::{ReadOptions, count_file};
+use crate::counter::CountOptions;
+use crate::report::Summary;
+use crate::stats::LineStats;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 两次统计之间的行数变化量（新 - 旧）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LineDelta {
+    /// 总行数变化
+    pub total_lines: i64,
+    /// 非空行数变化
+    pub non_empty_lines: i64,
+    /// 空行数变化
+    pub empty_lines: i64,
+}
+
+/// 两次统计的比较结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatsDiff {
+    /// 旧的统计结果
+    pub old: LineStats,
+    /// 新的统计结果
+    pub new: LineStats,
+    /// 变化量
+    pub delta: LineDelta,
+    /// 是否忽略了只涉及空行的变化
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub blank_ignored: bool,
+}
+
+impl StatsDiff {
+    /// 比较两次统计结果
+    ///
+    /// # 参数
+    ///
+    /// * `old` - 旧的统计结果
+    /// * `new` - 新的统计结果
+    pub fn new(old: LineStats, new: LineStats) -> Self {
+        let change = |old: usize, new: usize| new as i64 - old as i64;
+        let delta = LineDelta {
+            total_lines: change(old.total_lines, new.total_lines),
+            non_empty_lines: change(old.non_empty_lines, new.non_empty_lines),
+            empty_lines: change(old.empty_lines, new.empty_lines),
+        };
+        Self {
+            old,
+            new,
+            delta,
+            blank_ignored: false,
+        }
+    }
+
+    /// 忽略只涉及空行的变化
+    ///
+    /// 总行数的变化量改为非空行数的变化量，空行数的变化量记为 0；旧的和新的统计结果保持不变。
+    pub fn ignore_blank_changes(self) -> Self {
+        Self {
+            delta: LineDelta {
+                total_lines: self.delta.non_empty_lines,
+                empty_lines: 0,
+                ..self.delta
+            },
+            blank_ignored: true,
+            ..self
+        }
+    }
+}
+
+/// JSON 报告中差异模式需要的部分
+#[derive(Deserialize)]
+struct SavedReport {
+    summary: Summary,
+}
+
+/// 统计一个文件，用于差异比较
+///
+/// # 参数
+///
+/// * `path` - 文件路径
+/// * `options` - 统计选项
+pub fn stats_from_file(path: &Path, options: &CountOptions) -> Result<LineStats> {
+    let read_options = ReadOptions::default();
+    Ok(count_file(path, options, &read_options)?.stats)
+}
+
+/// 从 JSON 报告中读取汇总结果，用于差异比较
+///
+/// # 参数
+///
+/// * `path` - 由 `--format json` 生成的报告文件
+pub fn stats_from_report(path: &Path) -> Result<LineStats> {
+    Ok(summary_from_report(path)?.stats)
+}
+
+/// 从 JSON 报告中读取完整的汇总结果（包括标签汇总），用于基线比较
+///
+/// # 参数
+///
+/// * `path` - 由 `--format json` 生成的报告文件
+pub fn summary_from_report(path: &Path) -> Result<Summary> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("无法读取报告 '{}'", path.display()))?;
+    parse_report(&content).with_context(|| format!("无法解析报告 '{}'", path.display()))
+}
+
+/// 解析 JSON 报告并返回汇总结果
+fn parse_report(content: &str) -> serde_json::Result<Summary> {
+    let report: SavedReport = serde_json::from_str(content)?;
+    Ok(report.summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试变化量的计算（包括减少的情况）
+    #[test]
+    fn test_stats_diff() {
+        let diff = StatsDiff::new(LineStats::new(10, 8, 2), LineStats::new(15, 7, 8));
+        assert_eq!(
+            diff.delta,
+            LineDelta {
+                total_lines: 5,
+                non_empty_lines: -1,
+                empty_lines: 6,
+            }
+        );
+    }
+
+    /// 测试忽略空行变化
+    #[test]
+    fn test_ignore_blank_changes() {
+        let diff = StatsDiff::new(LineStats::new(10, 8, 2), LineStats::new(15, 9, 6))
+            .ignore_blank_changes();
+        assert_eq!(
+            diff.delta,
+            LineDelta {
+                total_lines: 1,
+                non_empty_lines: 1,
+                empty_lines: 0,
+            }
+        );
+        assert_eq!(diff.new.total_lines, 15);
+        assert!(diff.blank_ignored);
+    }
+
+    /// 测试从 JSON 报告中读取汇总结果
+    #[test]
+    fn test_parse_report() {
+        let content = r#"{
+            "meta": {"tool": "line-counter"},
+            "files": [],
+            "summary": {
+                "files": 1,
+                "bytes": 20,
+                "stats": {"total_lines": 4, "non_empty_lines": 3, "empty_lines": 1}
+            }
+        }"#;
+        assert_eq!(
+            parse_report(content).unwrap().stats,
+            LineStats::new(4, 3, 1)
+        );
+        assert!(parse_report("{}").is_err());
+    }
+}
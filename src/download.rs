@@ -0,0 +1,147 @@
+//! 统计 URL 的内容
+//!
+//! 以 `http` 特性构建时，命令行中以 `http://` 或 `https://` 开头的路径会被下载，
+//! 边下载边统计，内容不写入磁盘。下载的内容与标准输入一样按流式统计，压缩的内容同样会被解压，
+//! 报告中的大小为实际下载的字节数。
+//!
+//! `--max-download` 限制单个 URL 下载的字节数：响应声明的 `Content-Length` 超过上限时不开始读取，
+//! 没有声明长度的响应在下载的字节数超过上限时停止下载并报错。
+
+use crate::app::{ReadOptions, count_reader};
+use crate::counter::CountOptions;
+use crate::report::FileReport;
+use anyhow::{Context, Result, bail};
+use std::io::{self, Read};
+use std::path::Path;
+
+/// 下载并统计 URL 的内容
+///
+/// # 参数
+///
+/// * `url` - 要下载的 URL，同时作为报告中的路径和识别语言使用的文件名
+/// * `options` - 统计选项
+/// * `read_options` - 读取选项（提供下载大小上限）
+///
+/// # 返回值
+///
+/// * `Ok(FileReport)` - 统计报告
+/// * `Err(anyhow::Error)` - 请求失败、服务器返回错误状态码或下载的内容超过上限
+pub fn count(url: &Path, options: &CountOptions, read_options: &ReadOptions) -> Result<FileReport> {
+    let url_str = url.display().to_string();
+    let response = ureq::get(&url_str)
+        .call()
+        .with_context(|| format!("无法下载 '{}'", url_str))?;
+    let body = response.into_body();
+    let max_size = read_options.max_download;
+    if max_size > 0
+        && let Some(length) = body.content_length()
+        && length > max_size
+    {
+        bail!(
+            "'{}' 的大小 {} bytes 超过下载上限 {} bytes",
+            url_str,
+            length,
+            max_size
+        );
+    }
+
+    let reader = LimitedReader {
+        inner: body.into_reader(),
+        remaining: max_size,
+        max_size,
+    };
+    count_reader(reader, url.to_path_buf(), options, read_options)
+        .with_context(|| format!("统计文件 '{}' 时发生错误", url_str))
+}
+
+/// 下载的字节数超过上限时返回错误的读取器
+///
+/// 与 [`Read::take`] 不同，超过上限时不会静默地截断内容。
+struct LimitedReader<R> {
+    inner: R,
+    /// 还可以读取的字节数
+    remaining: u64,
+    /// 大小上限，0 表示不限制
+    max_size: u64,
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if self.max_size == 0 {
+            return Ok(read);
+        }
+        match self.remaining.checked_sub(read as u64) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                Ok(read)
+            }
+            None => Err(io::Error::other(format!(
+                "下载的内容超过上限 {} bytes",
+                self.max_size
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use tiny_http::{Response, Server};
+
+    /// 在本机启动只响应一次请求的 HTTP 服务，返回请求的 URL
+    fn serve_once(body: &'static str) -> String {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/app.log", server.server_addr());
+        thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request.respond(Response::from_string(body)).unwrap();
+        });
+        url
+    }
+
+    /// 测试下载并统计 URL 的内容
+    #[test]
+    fn test_count_url() {
+        let url = serve_once("GET /\n\nPOST /count\n");
+        let report = count(
+            Path::new(&url),
+            &CountOptions::default(),
+            &ReadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report.size, 19);
+        assert_eq!(report.stats.total_lines, 3);
+        assert_eq!(report.stats.empty_lines, 1);
+
+        let url = serve_once("too large\n");
+        let read_options = ReadOptions {
+            max_download: 4,
+            ..ReadOptions::default()
+        };
+        let error = count(Path::new(&url), &CountOptions::default(), &read_options).unwrap_err();
+        assert!(error.to_string().contains("超过下载上限 4 bytes"));
+    }
+
+    /// 测试下载的字节数超过上限时报错，不截断内容
+    #[test]
+    fn test_limited_reader() {
+        let reader = |max_size| LimitedReader {
+            inner: &b"line 1\nline 2\n"[..],
+            remaining: max_size,
+            max_size,
+        };
+
+        let mut content = String::new();
+        reader(14).read_to_string(&mut content).unwrap();
+        assert_eq!(content, "line 1\nline 2\n");
+
+        let mut content = String::new();
+        reader(0).read_to_string(&mut content).unwrap();
+        assert_eq!(content.len(), 14);
+
+        let error = reader(10).read_to_string(&mut String::new()).unwrap_err();
+        assert!(error.to_string().contains("下载的内容超过上限 10 bytes"));
+    }
+}
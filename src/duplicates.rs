@@ -0,0 +1,220 @@
+//! 重复行检测
+//!
+//! `--duplicates` 统计与前面某一行内容完全相同的非空行数，`--top-duplicates <N>` 同时列出最常重复的行。
+//! 比较前可以用 `--normalize` 对行做规范化（见 [`crate::normalize`]）。
+//!
+//! 为了让内存占用有上限，跟踪器只保存每种内容的 64 位哈希和出现次数，
+//! 行内容只在第二次出现时（且需要列出最常重复的行时）截断保存。
+//! 不同内容的行数超过 [`MAX_TRACKED_LINES`] 后，新出现的内容不再跟踪，
+//! 结果标记为近似值（重复行数为下限）。
+
+use crate::normalize::Normalization;
+use crate::stats::{DuplicateStats, RepeatedLine};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// 最多跟踪的不同内容的行数
+///
+/// 每种内容约占 40 字节（不含保存的行内容），上限对应的内存约为 40MB。
+pub const MAX_TRACKED_LINES: usize = 1 << 20;
+
+/// 最常重复的行保存的最大字符数
+pub const MAX_PREVIEW_CHARS: usize = 200;
+
+/// 重复行检测选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DuplicateOptions {
+    /// 列出的最常重复的行数，0 表示不列出
+    pub top: usize,
+    /// 比较前对行做的规范化
+    pub normalization: Normalization,
+}
+
+/// 某种内容的出现记录
+#[derive(Debug)]
+struct Entry {
+    /// 出现次数
+    count: usize,
+    /// 截断后的行内容（第二次出现且需要列出时才保存）
+    preview: Option<Box<str>>,
+}
+
+/// 逐行跟踪重复的行
+#[derive(Debug)]
+pub struct DuplicateTracker {
+    options: DuplicateOptions,
+    entries: HashMap<u64, Entry>,
+    lines: usize,
+    duplicates: usize,
+    approximate: bool,
+}
+
+impl DuplicateTracker {
+    /// 创建跟踪器
+    ///
+    /// # 参数
+    ///
+    /// * `options` - 重复行检测选项
+    pub fn new(options: DuplicateOptions) -> Self {
+        Self {
+            options,
+            entries: HashMap::new(),
+            lines: 0,
+            duplicates: 0,
+            approximate: false,
+        }
+    }
+
+    /// 记录一个非空行
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 行内容（不含换行符）；无效的 UTF-8 按替换字符处理
+    pub fn push(&mut self, line: &[u8]) {
+        self.lines += 1;
+
+        let text = if self.options.normalization.is_identity() {
+            None
+        } else {
+            let line = String::from_utf8_lossy(line);
+            Some(self.options.normalization.apply(&line).into_owned())
+        };
+        let content = text.as_ref().map_or(line, String::as_bytes);
+
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let tracked = self.entries.len();
+        match self.entries.get_mut(&hash) {
+            Some(entry) => {
+                entry.count += 1;
+                self.duplicates += 1;
+                if entry.preview.is_none() && self.options.top > 0 {
+                    entry.preview = Some(preview(content));
+                }
+            }
+            None if tracked < MAX_TRACKED_LINES => {
+                self.entries.insert(
+                    hash,
+                    Entry {
+                        count: 1,
+                        preview: None,
+                    },
+                );
+            }
+            None => self.approximate = true,
+        }
+    }
+
+    /// 结束跟踪，返回统计结果
+    pub fn finish(self) -> DuplicateStats {
+        let mut top: Vec<RepeatedLine> = self
+            .entries
+            .into_values()
+            .filter_map(|entry| {
+                Some(RepeatedLine {
+                    text: entry.preview?.into_string(),
+                    count: entry.count,
+                })
+            })
+            .collect();
+        top.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.text.cmp(&b.text)));
+        top.truncate(self.options.top);
+
+        DuplicateStats {
+            lines: self.lines,
+            distinct: self.lines - self.duplicates,
+            duplicates: self.duplicates,
+            approximate: self.approximate,
+            top,
+        }
+    }
+}
+
+/// 截断行内容用于显示
+fn preview(content: &[u8]) -> Box<str> {
+    String::from_utf8_lossy(content)
+        .chars()
+        .take(MAX_PREVIEW_CHARS)
+        .collect::<String>()
+        .into_boxed_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normalize;
+
+    fn track(lines: &[&str], options: DuplicateOptions) -> DuplicateStats {
+        let mut tracker = DuplicateTracker::new(options);
+        for line in lines {
+            tracker.push(line.as_bytes());
+        }
+        tracker.finish()
+    }
+
+    /// 测试重复行计数和最常重复的行
+    #[test]
+    fn test_duplicates() {
+        let lines = [
+            "GET /",
+            "POST /login",
+            "GET /",
+            "GET /",
+            "POST /login",
+            "PUT /",
+        ];
+        let stats = track(
+            &lines,
+            DuplicateOptions {
+                top: 1,
+                ..DuplicateOptions::default()
+            },
+        );
+        assert_eq!(stats.lines, 6);
+        assert_eq!(stats.distinct, 3);
+        assert_eq!(stats.duplicates, 3);
+        assert!(!stats.approximate);
+        assert_eq!(
+            stats.top,
+            vec![RepeatedLine {
+                text: "GET /".to_string(),
+                count: 3,
+            }]
+        );
+
+        // 不需要列出时不保存行内容
+        assert!(track(&lines, DuplicateOptions::default()).top.is_empty());
+    }
+
+    /// 测试规范化后再比较
+    #[test]
+    fn test_duplicates_normalized() {
+        let lines = ["Hello ", "hello", "  HELLO"];
+        assert_eq!(track(&lines, DuplicateOptions::default()).duplicates, 0);
+
+        let options = DuplicateOptions {
+            top: 5,
+            normalization: normalize::parse_list("trim,casefold").unwrap(),
+        };
+        let stats = track(&lines, options);
+        assert_eq!(stats.duplicates, 2);
+        assert_eq!(stats.top[0].text, "hello");
+        assert_eq!(stats.top[0].count, 3);
+    }
+
+    /// 测试过长的行内容被截断
+    #[test]
+    fn test_preview_truncated() {
+        let long = "中".repeat(MAX_PREVIEW_CHARS + 10);
+        let stats = track(
+            &[&long, &long],
+            DuplicateOptions {
+                top: 1,
+                ..DuplicateOptions::default()
+            },
+        );
+        assert_eq!(stats.top[0].text.chars().count(), MAX_PREVIEW_CHARS);
+    }
+}
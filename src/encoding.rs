@@ -0,0 +1,361 @@
+//! 文本编码识别与转码
+//!
+//! 统计前根据文件开头的一块数据识别编码：
+//!
+//! 1. 字节顺序标记（BOM）：UTF-8、UTF-16LE、UTF-16BE
+//! 2. 没有 BOM 的 UTF-16：ASCII 字符的高字节为 NUL，NUL 集中在奇数或偶数位置
+//! 3. 包含 NUL 字节的其他数据视为二进制
+//! 4. 有效的 UTF-8（允许少量无效字节，交由统计时报告）
+//! 5. 依次尝试 GBK、Big5、Shift_JIS、EUC-KR，选择能无错误解码且解码结果符合该语言常用字符的编码
+//! 6. 都不符合时，无效字节比例过高视为二进制，否则按 Latin-1（windows-1252）处理
+//!
+//! 非 UTF-8 的内容通过 `encoding_rs_io` 透明地转码为 UTF-8 后再统计。
+//! `--encoding` 可以跳过识别直接指定编码。
+
+use crate::binary;
+use encoding_rs::{
+    BIG5, DecoderResult, EUC_KR, Encoding, GBK, SHIFT_JIS, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252,
+};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use std::io::Read;
+
+/// 识别结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detection {
+    /// 文本，附带识别出的编码
+    Text(&'static Encoding),
+    /// 二进制数据
+    Binary,
+}
+
+/// 依次尝试的传统多字节编码
+const LEGACY_ENCODINGS: &[&Encoding] = &[GBK, BIG5, SHIFT_JIS, EUC_KR];
+
+/// 解码结果中常用字符占非 ASCII 字符的最低比例
+const MIN_COMMON_RATIO: f64 = 0.9;
+
+/// 判断数据是否以字节顺序标记（UTF-8、UTF-16LE 或 UTF-16BE 的 BOM）开头
+///
+/// # 参数
+///
+/// * `sample` - 文件开头的数据
+pub fn has_bom(sample: &[u8]) -> bool {
+    Encoding::for_bom(sample).is_some()
+}
+
+/// 识别一块开头数据的编码
+///
+/// # 参数
+///
+/// * `sample` - 文件开头的数据，末尾可能截断在多字节字符中间
+///
+/// # 返回值
+///
+/// * `Detection` - 识别出的编码，或判断为二进制数据
+pub fn detect(sample: &[u8]) -> Detection {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return Detection::Text(encoding);
+    }
+    if let Some(encoding) = detect_utf16(sample) {
+        return Detection::Text(encoding);
+    }
+    if memchr::memchr(0, sample).is_some() {
+        return Detection::Binary;
+    }
+
+    let (multibyte, invalid) = utf8_profile(sample);
+    if invalid == 0 || multibyte > invalid {
+        return Detection::Text(UTF_8);
+    }
+
+    if let Some(encoding) = detect_legacy(sample) {
+        return Detection::Text(encoding);
+    }
+
+    if binary::looks_binary(sample) {
+        Detection::Binary
+    } else {
+        Detection::Text(WINDOWS_1252)
+    }
+}
+
+/// 根据名称查找编码（如 `gbk`、`shift_jis`、`utf-16le`、`latin1`）
+///
+/// 供 `clap` 解析 `--encoding` 使用。
+pub fn parse_label(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("无法识别的编码: '{}'", label))
+}
+
+/// 将读取器包装为输出 UTF-8 的转码读取器
+///
+/// 开头的 BOM 会被去掉，无法解码的字节替换为 U+FFFD。
+///
+/// # 参数
+///
+/// * `reader` - 原始字节读取器
+/// * `encoding` - 原始内容的编码
+pub fn transcode<R: Read>(reader: R, encoding: &'static Encoding) -> impl Read {
+    DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding))
+        .strip_bom(true)
+        .build(reader)
+}
+
+/// 识别没有 BOM 的 UTF-16
+///
+/// ASCII 字符在 UTF-16LE 中高字节（奇数位置）为 NUL，在 UTF-16BE 中低字节（偶数位置）为 NUL。
+fn detect_utf16(sample: &[u8]) -> Option<&'static Encoding> {
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+
+    let (mut even_nul, mut odd_nul) = (0usize, 0usize);
+    for pair in sample.chunks_exact(2) {
+        even_nul += (pair[0] == 0) as usize;
+        odd_nul += (pair[1] == 0) as usize;
+    }
+
+    let mostly = |count: usize| count as f64 / pairs as f64 > 0.4;
+    let rarely = |count: usize| (count as f64 / pairs as f64) < 0.05;
+    let encoding = if mostly(odd_nul) && rarely(even_nul) {
+        UTF_16LE
+    } else if mostly(even_nul) && rarely(odd_nul) {
+        UTF_16BE
+    } else {
+        return None;
+    };
+
+    // 解码结果中几乎没有控制字符时才认为是文本
+    let decoded = decode_strict(encoding, sample)?;
+    let total = decoded.chars().count();
+    let control = decoded
+        .chars()
+        .filter(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+        .count();
+    (control as f64 / total as f64 <= 0.1).then_some(encoding)
+}
+
+/// 统计有效的多字节 UTF-8 字符数和无效字节数（末尾截断的字符不计入）
+fn utf8_profile(mut data: &[u8]) -> (usize, usize) {
+    let (mut multibyte, mut invalid) = (0, 0);
+    loop {
+        let (valid, rest) = match std::str::from_utf8(data) {
+            Ok(valid) => (valid, None),
+            Err(e) => {
+                let valid = std::str::from_utf8(&data[..e.valid_up_to()]).unwrap_or_default();
+                (valid, Some(e))
+            }
+        };
+        multibyte += valid.chars().filter(|c| !c.is_ascii()).count();
+
+        match rest {
+            Some(e) => match e.error_len() {
+                Some(len) => {
+                    invalid += len;
+                    data = &data[e.valid_up_to() + len..];
+                }
+                None => return (multibyte, invalid),
+            },
+            None => return (multibyte, invalid),
+        }
+    }
+}
+
+/// 尝试传统多字节编码
+///
+/// 能无错误解码且常用字符比例足够高的编码中，优先选择解码结果含有该语言特有字符
+/// （日文假名、韩文谚文）的编码，否则按 [`LEGACY_ENCODINGS`] 的顺序选择。
+fn detect_legacy(sample: &[u8]) -> Option<&'static Encoding> {
+    let mut fallback = None;
+
+    for &encoding in LEGACY_ENCODINGS {
+        let Some(decoded) = decode_strict(encoding, sample) else {
+            continue;
+        };
+
+        let (mut non_ascii, mut common, mut distinctive) = (0usize, 0usize, 0usize);
+        for c in decoded.chars().filter(|c| !c.is_ascii()) {
+            non_ascii += 1;
+            let (is_common, is_distinctive) = classify(encoding, c);
+            common += is_common as usize;
+            distinctive += is_distinctive as usize;
+        }
+
+        if non_ascii == 0 || (common as f64 / non_ascii as f64) < MIN_COMMON_RATIO {
+            continue;
+        }
+        // Big5 文本也能按 GBK 无错误解码，但常用汉字多落在 GBK 扩展区；
+        // 真正的 GBK 文本绝大多数字符位于 GB2312 区
+        if encoding == GBK && gb2312_ratio(sample) < MIN_COMMON_RATIO {
+            continue;
+        }
+        if distinctive > 0 {
+            return Some(encoding);
+        }
+        fallback = fallback.or(Some(encoding));
+    }
+
+    fallback
+}
+
+/// 判断解码出的字符在该编码的语言中是否常用，以及是否为该语言特有
+fn classify(encoding: &'static Encoding, c: char) -> (bool, bool) {
+    let c = c as u32;
+    let han = (0x4E00..=0x9FFF).contains(&c);
+    let punctuation = (0x3000..=0x303F).contains(&c) || (0xFF01..=0xFF5E).contains(&c);
+    let kana = (0x3040..=0x30FF).contains(&c);
+    let hangul = (0xAC00..=0xD7A3).contains(&c);
+
+    if encoding == SHIFT_JIS {
+        (han || punctuation || kana, kana)
+    } else if encoding == EUC_KR {
+        // 现代韩文几乎不使用汉字；中文按 EUC-KR 解码会混入大量汉字
+        (punctuation || hangul, hangul)
+    } else {
+        (han || punctuation, false)
+    }
+}
+
+/// GBK 双字节字符中位于 GB2312 区（首字节 0xA1–0xF7，尾字节 0xA1–0xFE）的比例
+fn gb2312_ratio(sample: &[u8]) -> f64 {
+    let (mut total, mut gb2312) = (0usize, 0usize);
+    let mut i = 0;
+    while i < sample.len() {
+        if sample[i] < 0x80 {
+            i += 1;
+            continue;
+        }
+        if let Some(&trail) = sample.get(i + 1) {
+            total += 1;
+            gb2312 +=
+                ((0xA1..=0xF7).contains(&sample[i]) && (0xA1..=0xFE).contains(&trail)) as usize;
+        }
+        i += 2;
+    }
+
+    if total == 0 {
+        0.0
+    } else {
+        gb2312 as f64 / total as f64
+    }
+}
+
+/// 不做替换地解码，遇到无效字节返回 `None`（末尾截断的字符不视为错误）
+fn decode_strict(encoding: &'static Encoding, sample: &[u8]) -> Option<String> {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let capacity = decoder.max_utf8_buffer_length_without_replacement(sample.len())?;
+    let mut decoded = String::with_capacity(capacity);
+    let (result, _) = decoder.decode_to_string_without_replacement(sample, &mut decoded, false);
+    match result {
+        DecoderResult::InputEmpty => Some(decoded),
+        DecoderResult::Malformed(..) | DecoderResult::OutputFull => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(encoding: &'static Encoding, text: &str) -> Vec<u8> {
+        encoding.encode(text).0.into_owned()
+    }
+
+    /// 测试 BOM 和 UTF-8 的识别
+    #[test]
+    fn test_detect_utf8_and_bom() {
+        assert_eq!(detect(b""), Detection::Text(UTF_8));
+        assert_eq!(
+            detect("普通的 UTF-8 文本\n".as_bytes()),
+            Detection::Text(UTF_8)
+        );
+        assert_eq!(detect(b"\xEF\xBB\xBFabc"), Detection::Text(UTF_8));
+        assert_eq!(detect(b"\xFF\xFEa\x00b\x00"), Detection::Text(UTF_16LE));
+        assert_eq!(detect(b"\xFE\xFF\x00a\x00b"), Detection::Text(UTF_16BE));
+    }
+
+    /// 测试 BOM 的识别
+    #[test]
+    fn test_has_bom() {
+        assert!(has_bom(b"\xEF\xBB\xBFabc"));
+        assert!(has_bom(b"\xFF\xFEa\x00"));
+        assert!(has_bom(b"\xFE\xFF\x00a"));
+        assert!(!has_bom(b"abc"));
+        assert!(!has_bom(b"\xEF\xBB"));
+    }
+
+    /// 测试没有 BOM 的 UTF-16
+    #[test]
+    fn test_detect_utf16_without_bom() {
+        let text = "hello world\nline two\n";
+        let le: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(detect(&le), Detection::Text(UTF_16LE));
+        assert_eq!(detect(&be), Detection::Text(UTF_16BE));
+    }
+
+    /// 测试传统多字节编码的识别
+    #[test]
+    fn test_detect_legacy_encodings() {
+        let chinese = "// 计算两个数的和\nfn add(a: i32, b: i32) -> i32 { a + b }\n// 这是中文注释，用于测试编码识别\n";
+        assert_eq!(detect(&encode(GBK, chinese)), Detection::Text(GBK));
+
+        let japanese = "これは日本語のテキストです。\nファイルの行数を数えます。\n";
+        assert_eq!(
+            detect(&encode(SHIFT_JIS, japanese)),
+            Detection::Text(SHIFT_JIS)
+        );
+
+        let korean = "이것은 한국어 텍스트입니다.\n줄 수를 셉니다.\n";
+        assert_eq!(detect(&encode(EUC_KR, korean)), Detection::Text(EUC_KR));
+
+        let traditional =
+            "這是繁體中文的測試文字，用來檢查編碼識別是否正確。\n程式會計算檔案的行數。\n";
+        assert_eq!(detect(&encode(BIG5, traditional)), Detection::Text(BIG5));
+
+        let latin1 = "Café crème brûlée\nna\u{ef}ve façade\n";
+        assert_eq!(
+            detect(&encode(WINDOWS_1252, latin1)),
+            Detection::Text(WINDOWS_1252)
+        );
+    }
+
+    /// 测试二进制数据的识别
+    #[test]
+    fn test_detect_binary() {
+        assert_eq!(
+            detect(b"\x7fELF\x02\x01\x01\x00\x00\x00"),
+            Detection::Binary
+        );
+        assert_eq!(
+            detect(&[0x80, 0x81, 0xc0, 0xc1, 0xff, b'a']),
+            Detection::Binary
+        );
+    }
+
+    /// 测试转码读取器
+    #[test]
+    fn test_transcode() {
+        let mut decoded = String::new();
+        transcode(&encode(GBK, "中文\n")[..], GBK)
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "中文\n");
+
+        let mut decoded = String::new();
+        transcode(&b"\xFF\xFEa\x00\n\x00"[..], UTF_16LE)
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "a\n");
+    }
+
+    /// 测试编码名称解析
+    #[test]
+    fn test_parse_label() {
+        assert_eq!(parse_label("gbk"), Ok(GBK));
+        assert_eq!(parse_label("Shift_JIS"), Ok(SHIFT_JIS));
+        assert_eq!(parse_label("latin1"), Ok(WINDOWS_1252));
+        assert!(parse_label("no-such-encoding").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! 统计引擎选择
+//!
+//! 单个文件可以使用四种引擎统计：
+//!
+//! - [`Engine::Buffered`]：缓冲读取，适合小文件、标准输入和网络文件系统
+//! - [`Engine::Mmap`]：内存映射后在字节上统计，适合中等以上的本地文件
+//! - [`Engine::Parallel`]：内存映射后分块并行统计，适合多核机器上的大文件
+//! - [`Engine::ParallelRead`]：在换行符处切分为字节范围，各线程按位置读取并统计，
+//!   适合网络文件系统上的大文件（不使用内存映射）
+//!
+//! 默认的 [`Engine::Auto`] 根据文件大小、文件系统类型和可用核心数选择，
+//! 用户无需调整参数即可获得较好的性能。
+
+use crate::counter::CountOptions;
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::path::Path;
+
+/// 使用内存映射的最小文件大小
+///
+/// 更小的文件映射开销大于收益，缓冲读取更快。
+pub const MMAP_THRESHOLD: u64 = 1024 * 1024; // 1MB
+
+/// 使用分块并行统计的最小文件大小
+pub const PARALLEL_THRESHOLD: u64 = 32 * 1024 * 1024; // 32MB
+
+/// 网络和用户态文件系统类型
+///
+/// 在这些文件系统上内存映射可能很慢，或在文件被远端修改时导致 `SIGBUS`。
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "ncpfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "fuse",
+    "fuseblk",
+];
+
+/// 统计引擎
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
+pub enum Engine {
+    /// 根据文件大小、文件系统类型和可用核心数自动选择
+    #[default]
+    Auto,
+    /// 缓冲读取
+    Buffered,
+    /// 内存映射
+    Mmap,
+    /// 内存映射后分块并行统计
+    Parallel,
+    /// 切分为字节范围后各线程按位置读取并统计，不使用内存映射
+    ParallelRead,
+}
+
+/// 文件系统类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsKind {
+    /// 本地文件系统
+    Local,
+    /// 网络或用户态文件系统
+    Network,
+    /// 无法判断
+    Unknown,
+}
+
+/// 为单个文件确定实际使用的引擎
+///
+/// # 参数
+///
+/// * `requested` - 用户指定的引擎，不是 [`Engine::Auto`] 时原样返回
+/// * `file_size` - 文件大小（字节）
+/// * `cores` - 可用的 CPU 核心数
+/// * `options` - 统计选项；区段过滤和行号范围无法分块，不会选择并行引擎
+/// * `fs_kind` - 获取文件系统类别，只有文件足够大时才会调用
+///
+/// # 返回值
+///
+/// * `Engine` - 实际使用的引擎，不会是 [`Engine::Auto`]
+pub fn select(
+    requested: Engine,
+    file_size: u64,
+    cores: usize,
+    options: &CountOptions,
+    fs_kind: impl FnOnce() -> FsKind,
+) -> Engine {
+    if requested != Engine::Auto {
+        return requested;
+    }
+    if file_size < MMAP_THRESHOLD {
+        return Engine::Buffered;
+    }
+    let parallel = file_size >= PARALLEL_THRESHOLD
+        && cores > 1
+        && options.section.is_none()
+        && options.lines.is_none();
+    // 网络文件系统上不使用内存映射
+    match (fs_kind() == FsKind::Network, parallel) {
+        (true, true) => Engine::ParallelRead,
+        (true, false) => Engine::Buffered,
+        (false, true) => Engine::Parallel,
+        (false, false) => Engine::Mmap,
+    }
+}
+
+/// 判断文件所在的文件系统类别
+///
+/// Linux 上读取 `/proc/self/mountinfo`，取包含该文件的最长挂载点；其他平台返回 [`FsKind::Unknown`]。
+///
+/// # 参数
+///
+/// * `path` - 文件路径
+pub fn filesystem_kind(path: &Path) -> FsKind {
+    if !cfg!(target_os = "linux") {
+        return FsKind::Unknown;
+    }
+    let Ok(path) = path.canonicalize() else {
+        return FsKind::Unknown;
+    };
+    match std::fs::read_to_string("/proc/self/mountinfo") {
+        Ok(mountinfo) => fs_kind_from_mountinfo(&mountinfo, &path),
+        Err(_) => FsKind::Unknown,
+    }
+}
+
+/// 根据 `mountinfo` 内容判断文件系统类别
+fn fs_kind_from_mountinfo(mountinfo: &str, path: &Path) -> FsKind {
+    let mut best: Option<(usize, &str)> = None;
+
+    for line in mountinfo.lines() {
+        // 格式: <id> <parent> <major:minor> <root> <挂载点> <选项> ... - <类型> <来源> <超级块选项>
+        let Some((left, right)) = line.split_once(" - ") else {
+            continue;
+        };
+        let (Some(mount_point), Some(fs_type)) = (left.split(' ').nth(4), right.split(' ').next())
+        else {
+            continue;
+        };
+        let mount_point = mount_point.replace("\\040", " ");
+        if path.starts_with(&mount_point)
+            && best.is_none_or(|(length, _)| mount_point.len() >= length)
+        {
+            best = Some((mount_point.len(), fs_type));
+        }
+    }
+
+    match best {
+        Some((_, fs_type)) => {
+            let base = fs_type.split('.').next().unwrap_or(fs_type);
+            if NETWORK_FILESYSTEMS.contains(&base) {
+                FsKind::Network
+            } else {
+                FsKind::Local
+            }
+        }
+        None => FsKind::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::SectionFilter;
+    use regex::Regex;
+
+    /// 测试自动选择引擎
+    #[test]
+    fn test_select_auto() {
+        let options = CountOptions::default();
+        let local = || FsKind::Local;
+
+        assert_eq!(
+            select(Engine::Auto, 1024, 8, &options, local),
+            Engine::Buffered
+        );
+        assert_eq!(
+            select(Engine::Auto, MMAP_THRESHOLD, 8, &options, local),
+            Engine::Mmap
+        );
+        assert_eq!(
+            select(Engine::Auto, PARALLEL_THRESHOLD, 8, &options, local),
+            Engine::Parallel
+        );
+        assert_eq!(
+            select(Engine::Auto, PARALLEL_THRESHOLD, 1, &options, local),
+            Engine::Mmap
+        );
+        assert_eq!(
+            select(Engine::Auto, MMAP_THRESHOLD, 8, &options, || {
+                FsKind::Network
+            }),
+            Engine::Buffered
+        );
+        assert_eq!(
+            select(Engine::Auto, PARALLEL_THRESHOLD, 8, &options, || {
+                FsKind::Network
+            }),
+            Engine::ParallelRead
+        );
+        assert_eq!(
+            select(Engine::Auto, PARALLEL_THRESHOLD, 1, &options, || {
+                FsKind::Network
+            }),
+            Engine::Buffered
+        );
+        assert_eq!(select(Engine::Mmap, 10, 8, &options, local), Engine::Mmap);
+    }
+
+    /// 测试区段过滤时不选择并行引擎
+    #[test]
+    fn test_select_auto_with_section() {
+        let options = CountOptions {
+            section: Some(SectionFilter {
+                start: Regex::new("start").unwrap(),
+                end: Regex::new("end").unwrap(),
+                outside: false,
+            }),
+            ..CountOptions::default()
+        };
+        assert_eq!(
+            select(Engine::Auto, PARALLEL_THRESHOLD, 8, &options, || {
+                FsKind::Local
+            }),
+            Engine::Mmap
+        );
+    }
+
+    /// 测试从 mountinfo 判断文件系统类别
+    #[test]
+    fn test_fs_kind_from_mountinfo() {
+        let mountinfo = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw
+35 22 0:40 / /mnt/share rw,relatime shared:2 - nfs4 server:/export rw
+36 22 0:41 / /home/user/remote\\040dir rw - fuse.sshfs host: rw";
+
+        let kind = |path: &str| fs_kind_from_mountinfo(mountinfo, Path::new(path));
+        assert_eq!(kind("/home/user/a.txt"), FsKind::Local);
+        assert_eq!(kind("/mnt/share/log.txt"), FsKind::Network);
+        assert_eq!(kind("/home/user/remote dir/a.txt"), FsKind::Network);
+        assert_eq!(fs_kind_from_mountinfo("", Path::new("/a")), FsKind::Unknown);
+    }
+}
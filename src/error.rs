@@ -0,0 +1,379 @@
+//! 错误类型定义
+//!
+//! 本模块定义了 Line Counter 的结构化错误类型 [`LineCounterError`]。
+//! 核心统计逻辑直接返回该类型，命令行层再通过 `anyhow` 附加上下文。
+
+use crate::locale::Lang;
+use thiserror::Error;
+
+/// 超过阈值（`--max-total-lines` 等）时的退出码，与一般错误的退出码 1 区分
+pub const THRESHOLD_EXIT_CODE: u8 = 3;
+
+/// Line Counter 工具的自定义错误类型
+///
+/// 使用 `thiserror` 派生宏自动实现 `Error` trait，
+/// 提供结构化的错误信息和上下文。
+#[derive(Error, Debug)]
+pub enum LineCounterError {
+    /// 文件路径格式无效
+    #[error("文件路径无效: {path}")]
+    InvalidPath {
+        /// 无效的文件路径
+        path: String,
+    },
+
+    /// 指定的文件不存在
+    #[error("文件不存在: {path}")]
+    FileNotFound {
+        /// 不存在的文件路径
+        path: String,
+    },
+
+    /// 文件读取失败
+    #[error("无法读取文件: {path}")]
+    FileReadError {
+        /// 读取失败的文件路径
+        path: String,
+    },
+
+    /// 指定路径是目录而非文件
+    #[error("文件是一个目录，不是文件: {path}")]
+    IsDirectory {
+        /// 目录路径
+        path: String,
+    },
+
+    /// 文件访问权限不足
+    #[error("权限不足，无法访问文件: {path}")]
+    PermissionDenied {
+        /// 权限不足的文件路径
+        path: String,
+    },
+
+    /// 文件过大，超过处理限制
+    #[error("文件过大，无法处理: {path}, 大小: {size} bytes")]
+    FileTooLarge {
+        /// 过大文件的路径
+        path: String,
+        /// 文件大小（字节）
+        size: u64,
+    },
+
+    /// 缺少必需的命令行参数
+    #[error("缺少必需的文件路径参数")]
+    MissingArgument,
+
+    /// 行数增长超过基线容差
+    #[error("行数超过基线容差: {count} 项指标超限")]
+    BaselineExceeded {
+        /// 超限的指标数
+        count: usize,
+    },
+
+    /// 统计结果超过阈值（使用 `--max-total-lines`、`--max-file-lines` 或 `--max-empty-pct` 时）
+    #[error("统计结果超过阈值: {count} 项超限")]
+    ThresholdExceeded {
+        /// 超过阈值的项数
+        count: usize,
+    },
+
+    /// 存在行尾空白（使用 `--fail-on-trailing-whitespace` 时）
+    #[error("存在行尾空白: {lines} 行")]
+    TrailingWhitespace {
+        /// 行尾有空白的行数
+        lines: usize,
+    },
+
+    /// 存在文本规范警告（使用 `--fail-on-warning` 时）
+    #[error("存在文本规范警告: {count} 条")]
+    WarningsFound {
+        /// 警告总数
+        count: usize,
+    },
+
+    /// 多文件统计时有文件统计失败（没有使用 `--strict` 时其余文件仍然统计完成）
+    #[error("{count} 个文件统计失败")]
+    FilesFailed {
+        /// 统计失败的文件数
+        count: usize,
+    },
+
+    /// Git 命令失败（使用 `--git` 等 Git 相关功能时）
+    #[error("Git 命令失败 ({path}): {message}")]
+    Git {
+        /// 执行命令的目录
+        path: String,
+        /// `git` 的错误输出
+        message: String,
+    },
+
+    /// 配置文件无法读取或格式错误
+    #[error("配置文件无效 ({path}): {message}")]
+    Config {
+        /// 配置文件路径
+        path: String,
+        /// 具体的错误信息
+        message: String,
+    },
+
+    /// 路径匹配配置中的 `never_read` 规则，拒绝读取
+    #[error("拒绝读取 '{path}'：匹配配置中的 never_read 规则 '{pattern}'")]
+    NeverRead {
+        /// 被拒绝的路径
+        path: String,
+        /// 匹配的规则
+        pattern: String,
+    },
+
+    /// 读取某一行时发生错误
+    #[error("读取第 {line} 行时发生错误")]
+    LineReadError {
+        /// 出错的行号（从 1 开始）
+        line: usize,
+        /// 底层 IO 错误
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// 数据库的表结构版本比本工具支持的更新（`--output-db`）
+    #[error("数据库表结构版本 {version} 比本工具支持的版本 {supported} 更新")]
+    UnsupportedDatabase {
+        /// 数据库中记录的表结构版本
+        version: i32,
+        /// 本工具支持的表结构版本
+        supported: i32,
+    },
+
+    /// SQLite 错误的包装
+    #[cfg(feature = "cli")]
+    #[error("数据库错误: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    /// 标准库 IO 错误的包装
+    #[error("IO错误: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+impl LineCounterError {
+    /// 该错误对应的进程退出码
+    ///
+    /// # 返回值
+    ///
+    /// 超过阈值时为 [`THRESHOLD_EXIT_CODE`]，其余错误为 1
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            LineCounterError::ThresholdExceeded { .. } => THRESHOLD_EXIT_CODE,
+            _ => 1,
+        }
+    }
+
+    /// 按输出语言渲染的错误信息
+    ///
+    /// # 参数
+    ///
+    /// * `lang` - 输出语言
+    ///
+    /// # 返回值
+    ///
+    /// 中文时与 `Display` 相同，其他语言为消息目录中的译文
+    pub fn localized(&self, lang: Lang) -> String {
+        if lang == Lang::Zh {
+            return self.to_string();
+        }
+        match self {
+            LineCounterError::InvalidPath { path } => lang.format("文件路径无效: {}", &[path]),
+            LineCounterError::FileNotFound { path } => lang.format("文件不存在: {}", &[path]),
+            LineCounterError::FileReadError { path } => lang.format("无法读取文件: {}", &[path]),
+            LineCounterError::IsDirectory { path } => {
+                lang.format("文件是一个目录，不是文件: {}", &[path])
+            }
+            LineCounterError::PermissionDenied { path } => {
+                lang.format("权限不足，无法访问文件: {}", &[path])
+            }
+            LineCounterError::FileTooLarge { path, size } => {
+                lang.format("文件过大，无法处理: {}, 大小: {} bytes", &[path, size])
+            }
+            LineCounterError::MissingArgument => lang.tr("缺少必需的文件路径参数").to_string(),
+            LineCounterError::BaselineExceeded { count } => {
+                lang.format("行数超过基线容差: {} 项指标超限", &[count])
+            }
+            LineCounterError::ThresholdExceeded { count } => {
+                lang.format("统计结果超过阈值: {} 项超限", &[count])
+            }
+            LineCounterError::TrailingWhitespace { lines } => {
+                lang.format("存在行尾空白: {} 行", &[lines])
+            }
+            LineCounterError::WarningsFound { count } => {
+                lang.format("存在文本规范警告: {} 条", &[count])
+            }
+            LineCounterError::FilesFailed { count } => lang.format("{} 个文件统计失败", &[count]),
+            LineCounterError::Git { path, message } => {
+                lang.format("Git 命令失败 ({}): {}", &[path, message])
+            }
+            LineCounterError::Config { path, message } => {
+                lang.format("配置文件无效 ({}): {}", &[path, &lang.translate(message)])
+            }
+            LineCounterError::NeverRead { path, pattern } => lang.format(
+                "拒绝读取 '{}'：匹配配置中的 never_read 规则 '{}'",
+                &[path, pattern],
+            ),
+            LineCounterError::LineReadError { line, .. } => {
+                lang.format("读取第 {} 行时发生错误", &[line])
+            }
+            LineCounterError::UnsupportedDatabase { version, supported } => lang.format(
+                "数据库表结构版本 {} 比本工具支持的版本 {} 更新",
+                &[version, supported],
+            ),
+            #[cfg(feature = "cli")]
+            LineCounterError::Database(error) => lang.format("数据库错误: {}", &[error]),
+            LineCounterError::IoError(error) => lang.format("IO错误: {}", &[error]),
+        }
+    }
+}
+
+/// 使用 [`LineCounterError`] 作为错误类型的 `Result` 别名
+pub type Result<T> = std::result::Result<T, LineCounterError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试错误类型的显示格式
+    #[test]
+    fn test_line_counter_error_display() {
+        let err = LineCounterError::FileNotFound {
+            path: "test.txt".to_string(),
+        };
+        assert_eq!(err.to_string(), "文件不存在: test.txt");
+    }
+
+    /// 测试缺少参数错误
+    #[test]
+    fn test_missing_argument_error() {
+        let err = LineCounterError::MissingArgument;
+        assert_eq!(err.to_string(), "缺少必需的文件路径参数");
+    }
+
+    /// 测试文件过大错误
+    #[test]
+    fn test_file_too_large_error() {
+        let err = LineCounterError::FileTooLarge {
+            path: "big_file.txt".to_string(),
+            size: 1024 * 1024 * 200, // 200MB
+        };
+        assert!(err.to_string().contains("文件过大"));
+        assert!(err.to_string().contains("209715200 bytes"));
+    }
+
+    /// 测试权限不足错误
+    #[test]
+    fn test_permission_denied_error() {
+        let err = LineCounterError::PermissionDenied {
+            path: "protected_file.txt".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "权限不足，无法访问文件: protected_file.txt"
+        );
+    }
+
+    /// 测试目录错误
+    #[test]
+    fn test_is_directory_error() {
+        let err = LineCounterError::IsDirectory {
+            path: "some_directory".to_string(),
+        };
+        assert_eq!(err.to_string(), "文件是一个目录，不是文件: some_directory");
+    }
+
+    /// 测试行读取错误
+    #[test]
+    fn test_line_read_error() {
+        let err = LineCounterError::LineReadError {
+            line: 3,
+            source: std::io::Error::from(std::io::ErrorKind::InvalidData),
+        };
+        assert_eq!(err.to_string(), "读取第 3 行时发生错误");
+    }
+
+    /// 测试行尾空白错误
+    #[test]
+    fn test_trailing_whitespace_error() {
+        let err = LineCounterError::TrailingWhitespace { lines: 2 };
+        assert_eq!(err.to_string(), "存在行尾空白: 2 行");
+    }
+
+    /// 测试存在警告错误
+    #[test]
+    fn test_warnings_found_error() {
+        let err = LineCounterError::WarningsFound { count: 3 };
+        assert_eq!(err.to_string(), "存在文本规范警告: 3 条");
+    }
+
+    /// 测试数据库版本错误
+    #[test]
+    fn test_unsupported_database_error() {
+        let err = LineCounterError::UnsupportedDatabase {
+            version: 3,
+            supported: 1,
+        };
+        assert_eq!(
+            err.to_string(),
+            "数据库表结构版本 3 比本工具支持的版本 1 更新"
+        );
+    }
+
+    /// 测试超过阈值错误及其专用的退出码
+    #[test]
+    fn test_threshold_exceeded_error() {
+        let err = LineCounterError::ThresholdExceeded { count: 2 };
+        assert_eq!(err.to_string(), "统计结果超过阈值: 2 项超限");
+        assert_eq!(err.exit_code(), THRESHOLD_EXIT_CODE);
+        assert_eq!(LineCounterError::MissingArgument.exit_code(), 1);
+    }
+
+    /// 测试 Git 错误
+    #[test]
+    fn test_git_error() {
+        let err = LineCounterError::Git {
+            path: "/tmp".to_string(),
+            message: "fatal: not a git repository".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Git 命令失败 (/tmp): fatal: not a git repository"
+        );
+    }
+
+    /// 测试拒绝读取的错误信息
+    #[test]
+    fn test_never_read_error() {
+        let err = LineCounterError::NeverRead {
+            path: "certs/server.pem".to_string(),
+            pattern: "**/*.pem".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "拒绝读取 'certs/server.pem'：匹配配置中的 never_read 规则 '**/*.pem'"
+        );
+    }
+
+    /// 测试错误信息的英文译文
+    #[test]
+    fn test_localized() {
+        let err = LineCounterError::FileNotFound {
+            path: "test.txt".to_string(),
+        };
+        assert_eq!(err.localized(Lang::Zh), err.to_string());
+        assert_eq!(err.localized(Lang::En), "File not found: test.txt");
+
+        let err = LineCounterError::Config {
+            path: "line-counter.toml".to_string(),
+            message: "无效的大小: '1X'".to_string(),
+        };
+        assert_eq!(
+            err.localized(Lang::En),
+            "Invalid config file (line-counter.toml): Invalid size: '1X'"
+        );
+    }
+}
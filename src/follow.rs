@@ -0,0 +1,244 @@
+//! 跟踪模式
+//!
+//! `--follow` 像 `tail -f` 一样在读到文件末尾后保持文件打开，等待新追加的内容并继续统计，
+//! 同时每隔 [`REFRESH_INTERVAL`] 向标准错误输出一次目前的总行数和增长速度，
+//! 便于在压力测试中观察日志的增长。
+//!
+//! 以下情况停止跟踪并输出完整的统计结果：
+//!
+//! - 到达 `--deadline` 指定的截止时间
+//! - 文件被删除或移动
+//! - 文件被截断（如 `logrotate` 的 `copytruncate`），之前的统计结果不再对应文件内容
+//!
+//! 没有指定 `--deadline` 时一直跟踪，按 Ctrl+C 结束时只保留已输出的中间结果。
+
+use crate::app::{
+    ReadOptions, count_file, count_reader, open_file_with_error_handling, validate_not_directory,
+};
+use crate::counter::CountOptions;
+use crate::input::{is_special_file, is_stdin};
+use crate::output::Style;
+use crate::report::FileReport;
+use anyhow::{Result, bail};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// 输出中间结果的间隔
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 读到文件末尾后检查新内容的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 跟踪并统计单个文件
+///
+/// 标准输入和命名管道等特殊文件本身就会读到写入端关闭为止，按普通方式统计。
+///
+/// # 参数
+///
+/// * `files` - 展开后的文件列表，只能有一个文件
+/// * `options` - 统计选项
+/// * `read_options` - 读取选项
+///
+/// # 返回值
+///
+/// * `Ok(FileReport)` - 停止跟踪时的统计报告
+/// * `Err(anyhow::Error)` - 文件不止一个、无法打开或读取失败
+pub fn count(
+    files: &[PathBuf],
+    options: &CountOptions,
+    read_options: &ReadOptions,
+) -> Result<FileReport> {
+    let [path] = files else {
+        bail!("--follow 只能用于单个文件");
+    };
+    if is_stdin(path) || is_special_file(path) {
+        return count_file(path, options, read_options);
+    }
+    let path_str = path.display().to_string();
+    validate_not_directory(path, &path_str)?;
+    let file = open_file_with_error_handling(path, &path_str)?;
+    let reader = FollowReader::new(file, path, read_options);
+    count_reader(reader, path.clone(), options, read_options)
+}
+
+/// 停止跟踪的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    Deadline,
+    Removed,
+    Truncated,
+}
+
+impl Stop {
+    fn message(self) -> &'static str {
+        match self {
+            Stop::Deadline => "到达截止时间，停止跟踪",
+            Stop::Removed => "文件已被删除或移动，停止跟踪",
+            Stop::Truncated => "文件被截断，停止跟踪",
+        }
+    }
+}
+
+/// 读到文件末尾后等待新内容的读取器
+///
+/// 只有停止跟踪时才返回文件结束，因此可以直接交给流式统计；
+/// 最后一行在换行符到达之前不会被统计。
+struct FollowReader<'a> {
+    file: File,
+    path: &'a Path,
+    style: &'a Style,
+    deadline: Option<Instant>,
+    /// 已读取的字节数
+    position: u64,
+    /// 已读取的换行符数
+    lines: usize,
+    /// 上一次输出中间结果的时间和当时的行数，第一次读到文件末尾之前为 `None`
+    last_refresh: Option<(Instant, usize)>,
+}
+
+impl<'a> FollowReader<'a> {
+    fn new(file: File, path: &'a Path, read_options: &'a ReadOptions) -> Self {
+        Self {
+            file,
+            path,
+            style: &read_options.style,
+            deadline: read_options.deadline,
+            position: 0,
+            lines: 0,
+            last_refresh: None,
+        }
+    }
+
+    /// 检查是否应当停止跟踪
+    fn stop(&self) -> Option<Stop> {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Some(Stop::Deadline);
+        }
+        match fs::metadata(self.path) {
+            Err(_) => Some(Stop::Removed),
+            Ok(metadata) if metadata.len() < self.position => Some(Stop::Truncated),
+            Ok(_) => None,
+        }
+    }
+
+    /// 距离上一次输出超过 [`REFRESH_INTERVAL`] 时输出目前的总行数和增长速度
+    fn refresh(&mut self) {
+        let Some((last, last_lines)) = self.last_refresh else {
+            return;
+        };
+        let elapsed = last.elapsed();
+        if elapsed < REFRESH_INTERVAL {
+            return;
+        }
+        let added = self.lines - last_lines;
+        let rate = added as f64 / elapsed.as_secs_f64();
+        info!(
+            "{}{}: {}",
+            self.style.icon("⏳ "),
+            self.path.display(),
+            self.style.format(
+                "总行数 {}, 新增 {} 行（{} 行/秒）",
+                &[&self.lines, &added, &format!("{:.1}", rate)]
+            )
+        );
+        self.last_refresh = Some((Instant::now(), self.lines));
+    }
+}
+
+impl Read for FollowReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let read = self.file.read(buf)?;
+            if read > 0 {
+                self.position += read as u64;
+                self.lines += memchr::memchr_iter(b'\n', &buf[..read]).count();
+                self.refresh();
+                return Ok(read);
+            }
+
+            if self.last_refresh.is_none() {
+                self.last_refresh = Some((Instant::now(), self.lines));
+                info!(
+                    "{}{}: {}",
+                    self.style.icon("👀 "),
+                    self.path.display(),
+                    self.style
+                        .format("已读到文件末尾（{} 行），等待新内容...", &[&self.lines])
+                );
+            }
+            if let Some(stop) = self.stop() {
+                info!(
+                    "{}{}: {}",
+                    self.style.icon("⏹️  "),
+                    self.path.display(),
+                    self.style.tr(stop.message())
+                );
+                return Ok(0);
+            }
+            self.refresh();
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn read_options(deadline: Duration) -> ReadOptions {
+        ReadOptions {
+            deadline: Some(Instant::now() + deadline),
+            ..ReadOptions::default()
+        }
+    }
+
+    /// 测试读到文件末尾后继续读取追加的内容，直到截止时间
+    #[test]
+    fn test_follow_appended() {
+        let mut temp = NamedTempFile::new().unwrap();
+        write!(temp, "a\nb\n").unwrap();
+        let path = temp.path().to_path_buf();
+        let writer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            write!(temp, "c\nd").unwrap();
+            temp.flush().unwrap();
+            thread::sleep(Duration::from_millis(300));
+            writeln!(temp).unwrap();
+            temp.flush().unwrap();
+            thread::sleep(Duration::from_millis(1500));
+        });
+
+        let options = read_options(Duration::from_millis(1200));
+        let mut reader = FollowReader::new(File::open(&path).unwrap(), &path, &options);
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "a\nb\nc\nd\n");
+        assert_eq!(reader.lines, 4);
+        writer.join().unwrap();
+    }
+
+    /// 测试文件被截断时停止跟踪
+    #[test]
+    fn test_follow_truncated() {
+        let mut temp = NamedTempFile::new().unwrap();
+        write!(temp, "line 1\nline 2\n").unwrap();
+        let path = temp.path().to_path_buf();
+        let options = read_options(Duration::from_secs(30));
+        let mut reader = FollowReader::new(File::open(&path).unwrap(), &path, &options);
+        let mut buf = [0u8; 64];
+        assert_eq!(reader.read(&mut buf).unwrap(), 14);
+
+        temp.as_file().set_len(0).unwrap();
+        assert_eq!(reader.stop(), Some(Stop::Truncated));
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+}
@@ -0,0 +1,713 @@
+//! Git 仓库集成
+//!
+//! `--git` 只统计 Git 仓库中已跟踪的文件：目录通过 `git ls-files` 展开（不需要 `-r`），
+//! 未跟踪的构建产物、依赖目录等自然被排除。直接给出的文件未被跟踪时同样被排除。
+//!
+//! 通过调用系统中的 `git` 命令实现，每个输入路径在其所在的目录中执行，因此输入可以来自不同的仓库。
+//! 已跟踪但在工作区中被删除的文件和子模块不参与统计。
+//!
+//! `line-counter git-diff <rev1>..<rev2>` 通过 [`diff_revisions`] 统计两个版本之间每个文件新增、删除和修改的行数。
+//!
+//! `--changed-only --since <rev>` 通过 [`changed_files`] 找出工作区中相对某个版本发生变化的文件（包括未跟踪的文件）。
+//!
+//! `--git --by-author` 通过 [`authors`] 对每个文件执行 `git blame`，把每一行归属于最后修改它的作者，
+//! 按作者汇总行数。工作区中尚未提交的行归属于 Git 的 `Not Committed Yet`。
+
+use crate::compare::FileStatus;
+use crate::error::{LineCounterError, Result};
+use crate::input::is_stdin;
+use crate::workspace::SUMMARY_FILE_NAME;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 在目录中执行 `git` 命令并返回标准输出
+///
+/// # 参数
+///
+/// * `dir` - 执行命令的目录
+/// * `args` - `git` 的参数
+///
+/// # 返回值
+///
+/// * `Ok(Vec<u8>)` - 命令的标准输出
+/// * `Err(LineCounterError)` - 无法执行 `git`，或命令以非零状态退出（如目录不在仓库中）
+pub fn run(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| LineCounterError::Git {
+            path: dir.display().to_string(),
+            message: format!("无法执行 git，请确认系统中已安装: {}", e),
+        })?;
+    if !output.status.success() {
+        return Err(LineCounterError::Git {
+            path: dir.display().to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(output.stdout)
+}
+
+/// 把输入路径展开为其中已跟踪的文件
+///
+/// # 参数
+///
+/// * `paths` - 命令行给出的路径
+///
+/// # 返回值
+///
+/// * `Ok(Vec<PathBuf>)` - 按输入顺序排列的已跟踪文件，每个输入内部按路径排序；
+///   标准输入和不存在的路径原样保留，交由后续步骤处理
+/// * `Err(LineCounterError)` - 路径不在 Git 仓库中或 `git` 无法执行
+pub fn tracked_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if is_stdin(path) || !path.exists() {
+            files.push(path.clone());
+            continue;
+        }
+
+        // 输出的路径相对于 `base`，与目录遍历得到的路径形式一致
+        let (base, pathspec) = if path.is_dir() {
+            (path.as_path(), Path::new("."))
+        } else {
+            let name = path.file_name().map_or(path.as_path(), Path::new);
+            (path.parent().unwrap_or(Path::new("")), name)
+        };
+        let dir = if base.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            base
+        };
+
+        let output = run(dir, &["ls-files", "-z", "--", &pathspec.to_string_lossy()])?;
+        let mut tracked: Vec<PathBuf> = output
+            .split(|&byte| byte == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| base.join(String::from_utf8_lossy(name).as_ref()))
+            .filter(|file| file.is_file() && file.file_name() != Some(SUMMARY_FILE_NAME.as_ref()))
+            .collect();
+        tracked.sort();
+        files.extend(tracked);
+    }
+    Ok(files)
+}
+
+/// 工作区中相对某个版本发生变化的文件
+///
+/// 包括相对该版本修改、新增的已跟踪文件（含暂存区中的变化）和未被忽略的未跟踪文件；
+/// 已经删除的文件同样列出，由调用方忽略。
+///
+/// # 参数
+///
+/// * `dir` - 仓库中的目录
+/// * `rev` - 版本（如 `HEAD~3`、`main`、提交哈希）
+///
+/// # 返回值
+///
+/// * `Ok(Vec<PathBuf>)` - 发生变化的文件的绝对路径（以仓库根目录为基准）
+/// * `Err(LineCounterError)` - 版本不存在、目录不在仓库中或 `git` 无法执行
+pub fn changed_files(dir: &Path, rev: &str) -> Result<Vec<PathBuf>> {
+    // 防止版本被当作 `git diff` 的选项
+    if rev.starts_with('-') {
+        return Err(LineCounterError::Git {
+            path: dir.display().to_string(),
+            message: format!("无效的版本: '{}'", rev),
+        });
+    }
+    let toplevel = run(dir, &["rev-parse", "--show-toplevel"])?;
+    let toplevel = PathBuf::from(String::from_utf8_lossy(&toplevel).trim_end());
+    let changed = run(
+        &toplevel,
+        &["diff", "--name-only", "-z", "--no-renames", rev, "--"],
+    )?;
+    let untracked = run(
+        &toplevel,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+    Ok(changed
+        .split(|&byte| byte == 0)
+        .chain(untracked.split(|&byte| byte == 0))
+        .filter(|name| !name.is_empty())
+        .map(|name| toplevel.join(String::from_utf8_lossy(name).as_ref()))
+        .collect())
+}
+
+/// 单个作者最后修改的行数
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuthorShare {
+    /// 作者名称
+    pub name: String,
+    /// 包含该作者所修改的行的文件数
+    pub files: usize,
+    /// 该作者最后修改的行数
+    pub lines: usize,
+    /// 占所有行的百分比
+    pub lines_percent: f64,
+}
+
+/// 按最后修改的作者统计文件中的行数
+///
+/// # 参数
+///
+/// * `path` - 仓库中的文件
+///
+/// # 返回值
+///
+/// * `Ok(BTreeMap<String, usize>)` - 作者名称到行数的映射
+/// * `Err(LineCounterError)` - 文件不在仓库中或 `git` 无法执行
+pub fn blame(path: &Path) -> Result<BTreeMap<String, usize>> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = path.file_name().map_or(path.as_os_str(), |name| name);
+    let output = run(
+        dir,
+        &["blame", "--line-porcelain", "--", &name.to_string_lossy()],
+    )?;
+
+    // 每一行的头部都有 `author <名称>`，行的内容以制表符开头，不会被误认
+    let mut lines = BTreeMap::new();
+    for line in String::from_utf8_lossy(&output).lines() {
+        if let Some(author) = line.strip_prefix("author ") {
+            *lines.entry(author.to_string()).or_insert(0) += 1;
+        }
+    }
+    Ok(lines)
+}
+
+/// 并行统计多个文件的作者归属并按作者汇总
+///
+/// # 参数
+///
+/// * `paths` - 仓库中的文件
+///
+/// # 返回值
+///
+/// * `Ok(Vec<AuthorShare>)` - 按行数从多到少排列（行数相同时按名称排列）
+/// * `Err(LineCounterError)` - 任意一个文件无法执行 `git blame`
+pub fn authors(paths: &[&Path]) -> Result<Vec<AuthorShare>> {
+    let per_file = paths
+        .par_iter()
+        .map(|path| blame(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut totals: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for file in per_file {
+        for (author, lines) in file {
+            let entry = totals.entry(author).or_default();
+            entry.0 += 1;
+            entry.1 += lines;
+        }
+    }
+    let total_lines: usize = totals.values().map(|(_, lines)| lines).sum();
+    let mut shares: Vec<AuthorShare> = totals
+        .into_iter()
+        .map(|(name, (files, lines))| AuthorShare {
+            name,
+            files,
+            lines,
+            lines_percent: if total_lines == 0 {
+                0.0
+            } else {
+                lines as f64 * 100.0 / total_lines as f64
+            },
+        })
+        .collect();
+    // 名称已按 BTreeMap 排列，稳定排序保持行数相同时的名称顺序
+    shares.sort_by_key(|share| std::cmp::Reverse(share.lines));
+    Ok(shares)
+}
+
+/// 行的变化数
+///
+/// 每个差异块中删除和新增的行先两两配对为修改的行，多出的部分才计为新增或删除，
+/// 因此把一行改写算作一行修改，而不是一行删除加一行新增。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LineChanges {
+    /// 新增的行数
+    pub added: usize,
+    /// 删除的行数
+    pub removed: usize,
+    /// 修改的行数
+    pub modified: usize,
+}
+
+impl LineChanges {
+    /// 由一个差异块的旧行数和新行数得到变化数
+    ///
+    /// # 参数
+    ///
+    /// * `old` - 差异块中删除的行数
+    /// * `new` - 差异块中新增的行数
+    pub fn from_hunk(old: usize, new: usize) -> Self {
+        let modified = old.min(new);
+        Self {
+            added: new - modified,
+            removed: old - modified,
+            modified,
+        }
+    }
+
+    /// 有变化的总行数
+    pub fn total(&self) -> usize {
+        self.added + self.removed + self.modified
+    }
+}
+
+impl AddAssign<&LineChanges> for LineChanges {
+    fn add_assign(&mut self, other: &LineChanges) {
+        self.added += other.added;
+        self.removed += other.removed;
+        self.modified += other.modified;
+    }
+}
+
+/// 单个文件在两个版本之间的变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    /// 相对于仓库根目录的路径（删除的文件为旧路径）
+    pub path: PathBuf,
+    /// 重命名前的路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<PathBuf>,
+    /// 变化类型
+    pub status: FileStatus,
+    /// 是否为二进制文件（不统计行的变化）
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub binary: bool,
+    /// 行的变化数
+    pub lines: LineChanges,
+}
+
+/// 两个版本之间的变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RevisionDiff {
+    /// 版本范围（如 `v1.0..v1.1`）
+    pub range: String,
+    /// 新增的文件数
+    pub added: usize,
+    /// 删除的文件数
+    pub removed: usize,
+    /// 修改的文件数
+    pub changed: usize,
+    /// 所有文件合计的行变化数
+    pub total: LineChanges,
+    /// 有变化的文件，按 `git diff` 的顺序排列
+    pub files: Vec<FileChange>,
+}
+
+impl RevisionDiff {
+    /// 由文件的变化汇总
+    ///
+    /// # 参数
+    ///
+    /// * `range` - 版本范围
+    /// * `files` - 有变化的文件
+    pub fn new(range: &str, files: Vec<FileChange>) -> Self {
+        let count = |status| files.iter().filter(|file| file.status == status).count();
+        let mut total = LineChanges::default();
+        for file in &files {
+            total += &file.lines;
+        }
+        Self {
+            range: range.to_string(),
+            added: count(FileStatus::Added),
+            removed: count(FileStatus::Removed),
+            changed: count(FileStatus::Changed),
+            total,
+            files,
+        }
+    }
+}
+
+/// 统计两个版本之间每个文件的行变化
+///
+/// # 参数
+///
+/// * `dir` - 仓库中的目录
+/// * `range` - `git diff` 接受的版本范围，如 `v1.0..v1.1` 或 `HEAD~3..HEAD`
+/// * `pathspecs` - 只统计这些路径下的文件，为空时统计整个仓库
+///
+/// # 返回值
+///
+/// * `Ok(RevisionDiff)` - 两个版本之间的变化
+/// * `Err(LineCounterError)` - 版本不存在、目录不在仓库中或 `git` 无法执行
+pub fn diff_revisions(dir: &Path, range: &str, pathspecs: &[PathBuf]) -> Result<RevisionDiff> {
+    // 防止版本范围被当作 `git diff` 的选项
+    if range.starts_with('-') {
+        return Err(LineCounterError::Git {
+            path: dir.display().to_string(),
+            message: format!("无效的版本范围: '{}'", range),
+        });
+    }
+    let pathspecs: Vec<String> = pathspecs
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    let mut args = vec![
+        "-c",
+        "core.quotePath=false",
+        "diff",
+        "--unified=0",
+        "--no-color",
+        "--no-ext-diff",
+        "--no-textconv",
+        "--find-renames",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+        range,
+        "--",
+    ];
+    args.extend(pathspecs.iter().map(String::as_str));
+    let output = run(dir, &args)?;
+    Ok(RevisionDiff::new(
+        range,
+        parse_patch(&String::from_utf8_lossy(&output)),
+    ))
+}
+
+/// 解析 `git diff --unified=0` 的输出
+///
+/// 只统计差异块头部（`@@ -a,b +c,d @@`）中的行数，差异块的内容按行数跳过，
+/// 因此内容以 `---` 或 `+++` 开头的行不会被误认为文件头。没有内容变化的文件（如只是重命名）不列出。
+fn parse_patch(patch: &str) -> Vec<FileChange> {
+    let mut files = Vec::new();
+    let mut current: Option<(Option<PathBuf>, Option<PathBuf>, bool, LineChanges)> = None;
+    let mut remaining = 0;
+    let mut finish = |current: Option<(Option<PathBuf>, Option<PathBuf>, bool, LineChanges)>| {
+        let Some((old, new, binary, lines)) = current else {
+            return;
+        };
+        if !binary && lines.total() == 0 {
+            return;
+        }
+        let (path, old_path, status) = match (old, new) {
+            (None, Some(new)) => (new, None, FileStatus::Added),
+            (Some(old), None) => (old, None, FileStatus::Removed),
+            (Some(old), Some(new)) => {
+                let old_path = (old != new).then_some(old);
+                (new, old_path, FileStatus::Changed)
+            }
+            (None, None) => return,
+        };
+        files.push(FileChange {
+            path,
+            old_path,
+            status,
+            binary,
+            lines,
+        });
+    };
+
+    for line in patch.lines() {
+        if remaining > 0 {
+            // 缺少结尾换行符的标记不是差异块中的行
+            if !line.starts_with('\\') {
+                remaining -= 1;
+            }
+            continue;
+        }
+        if line.starts_with("diff --git ") {
+            finish(current.take());
+            current = Some((None, None, false, LineChanges::default()));
+            continue;
+        }
+        let Some((old, new, binary, lines)) = current.as_mut() else {
+            continue;
+        };
+        if let Some(path) = line.strip_prefix("--- ") {
+            *old = patch_path(path, "a/");
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            *new = patch_path(path, "b/");
+        } else if let Some(paths) = line
+            .strip_prefix("Binary files ")
+            .and_then(|paths| paths.strip_suffix(" differ"))
+            && let Some((a, b)) = paths.split_once(" and ")
+        {
+            *old = patch_path(a, "a/");
+            *new = patch_path(b, "b/");
+            *binary = true;
+        } else if let Some((old_count, new_count)) = parse_hunk_header(line) {
+            *lines += &LineChanges::from_hunk(old_count, new_count);
+            remaining = old_count + new_count;
+        }
+    }
+    finish(current);
+    files
+}
+
+/// 解析差异块头部 `@@ -a[,b] +c[,d] @@` 中的旧行数和新行数，省略的行数为 1
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let ranges = line.strip_prefix("@@ -")?;
+    let (ranges, _) = ranges.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let count = |range: &str| match range.split_once(',') {
+        Some((_, count)) => count.parse().ok(),
+        None => Some(1),
+    };
+    Some((count(old)?, count(new)?))
+}
+
+/// 解析文件头中的路径，`/dev/null` 表示文件不存在
+///
+/// 路径包含空格时 Git 会在末尾加上制表符，包含引号、反斜杠或控制字符时会用双引号括起并转义。
+fn patch_path(path: &str, prefix: &str) -> Option<PathBuf> {
+    let path = path.trim_end_matches('\t');
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = match path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    {
+        Some(quoted) => unquote(quoted),
+        None => path.to_string(),
+    };
+    Some(PathBuf::from(path.strip_prefix(prefix).unwrap_or(&path)))
+}
+
+/// 还原 Git 用 C 风格转义的路径
+fn unquote(quoted: &str) -> String {
+    let mut path = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            path.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => path.push('\t'),
+            Some('n') => path.push('\n'),
+            Some(other) => path.push(other),
+            None => path.push('\\'),
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// 以指定的作者提交工作区中的全部变化
+    fn commit(root: &Path, author: &str, message: &str) {
+        run(root, &["add", "-A"]).unwrap();
+        run(
+            root,
+            &[
+                "-c",
+                &format!("user.name={}", author),
+                "-c",
+                "user.email=dev@example.com",
+                "commit",
+                "-q",
+                "-m",
+                message,
+            ],
+        )
+        .unwrap();
+    }
+
+    /// 测试只展开已跟踪的文件
+    #[test]
+    fn test_tracked_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        run(root, &["init", "-q"]).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("target/out.rs"), "").unwrap();
+        fs::write(root.join("deleted.rs"), "").unwrap();
+        run(root, &["add", "src", "deleted.rs"]).unwrap();
+        fs::remove_file(root.join("deleted.rs")).unwrap();
+
+        let files = tracked_files(&[root.to_path_buf()]).unwrap();
+        assert_eq!(
+            files,
+            vec![root.join("src/lib.rs"), root.join("src/main.rs")]
+        );
+
+        // 直接给出的文件未被跟踪时被排除
+        let files = tracked_files(&[root.join("src/main.rs"), root.join("target/out.rs")]).unwrap();
+        assert_eq!(files, vec![root.join("src/main.rs")]);
+
+        let outside = tempfile::TempDir::new().unwrap();
+        let err = tracked_files(&[outside.path().to_path_buf()]).unwrap_err();
+        assert!(matches!(err, LineCounterError::Git { .. }));
+    }
+
+    /// 测试差异输出的解析
+    #[test]
+    fn test_parse_patch() {
+        let patch = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,3 @@ fn main() {
+--- removed sql comment
+-old
++new
++++ added
++added
+@@ -10 +11,0 @@
+-gone
+\\ No newline at end of file
+diff --git a/new file.rs b/new file.rs
+new file mode 100644
+--- /dev/null
++++ b/new file.rs\t
+@@ -0,0 +1,2 @@
++a
++b
+diff --git a/old.rs b/renamed.rs
+similarity index 90%
+rename from old.rs
+rename to renamed.rs
+--- a/old.rs
++++ b/renamed.rs
+@@ -3 +3 @@
+-x
++y
+diff --git a/moved.rs b/moved2.rs
+similarity index 100%
+rename from moved.rs
+rename to moved2.rs
+diff --git a/logo.png b/logo.png
+deleted file mode 100644
+Binary files a/logo.png and /dev/null differ
+diff --git \"a/tab\\there.rs\" \"b/tab\\there.rs\"
+--- \"a/tab\\there.rs\"
++++ \"b/tab\\there.rs\"
+@@ -1 +0,0 @@
+-x
+";
+        let files = parse_patch(patch);
+        let summary: Vec<_> = files
+            .iter()
+            .map(|file| {
+                (
+                    file.path.to_str().unwrap(),
+                    file.status,
+                    file.binary,
+                    (file.lines.added, file.lines.removed, file.lines.modified),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("src/lib.rs", FileStatus::Changed, false, (1, 1, 2)),
+                ("new file.rs", FileStatus::Added, false, (2, 0, 0)),
+                ("renamed.rs", FileStatus::Changed, false, (0, 0, 1)),
+                ("logo.png", FileStatus::Removed, true, (0, 0, 0)),
+                ("tab\there.rs", FileStatus::Changed, false, (0, 1, 0)),
+            ]
+        );
+        assert_eq!(files[2].old_path, Some(PathBuf::from("old.rs")));
+
+        let diff = RevisionDiff::new("v1..v2", files);
+        assert_eq!((diff.added, diff.removed, diff.changed), (1, 1, 3));
+        assert_eq!(diff.total.total(), 8);
+    }
+
+    /// 测试统计两个提交之间的变化
+    #[test]
+    fn test_diff_revisions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        run(root, &["init", "-q"]).unwrap();
+        fs::write(root.join("a.rs"), "one\ntwo\nthree\n").unwrap();
+        commit(root, "test", "first");
+        fs::write(root.join("a.rs"), "one\n2\nthree\nfour\n").unwrap();
+        fs::write(root.join("b.rs"), "b\n").unwrap();
+        commit(root, "test", "second");
+
+        let diff = diff_revisions(root, "HEAD~1..HEAD", &[]).unwrap();
+        assert_eq!(diff.files.len(), 2);
+        assert_eq!(
+            diff.total,
+            LineChanges {
+                added: 2,
+                removed: 0,
+                modified: 1
+            }
+        );
+
+        let diff = diff_revisions(root, "HEAD~1..HEAD", &[PathBuf::from("b.rs")]).unwrap();
+        assert_eq!(diff.files.len(), 1);
+
+        assert!(diff_revisions(root, "--output=x", &[]).is_err());
+        assert!(diff_revisions(root, "missing..HEAD", &[]).is_err());
+    }
+
+    /// 测试找出相对某个版本发生变化的文件
+    #[test]
+    fn test_changed_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        run(root, &["init", "-q"]).unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/a.rs"), "a\n").unwrap();
+        fs::write(root.join("src/b.rs"), "b\n").unwrap();
+        commit(root, "test", "first");
+        fs::write(root.join("src/a.rs"), "a\n2\n").unwrap();
+        fs::write(root.join("src/c.rs"), "c\n").unwrap();
+        fs::write(root.join(".gitignore"), "ignored.rs\n").unwrap();
+        fs::write(root.join("ignored.rs"), "").unwrap();
+
+        let toplevel = root.canonicalize().unwrap();
+        let mut changed = changed_files(&root.join("src"), "HEAD").unwrap();
+        changed.sort();
+        assert_eq!(
+            changed,
+            [
+                toplevel.join(".gitignore"),
+                toplevel.join("src/a.rs"),
+                toplevel.join("src/c.rs")
+            ]
+        );
+
+        assert!(changed_files(root, "--output=x").is_err());
+        assert!(changed_files(root, "missing").is_err());
+    }
+
+    /// 测试按最后修改的作者汇总行数
+    #[test]
+    fn test_authors() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        run(root, &["init", "-q"]).unwrap();
+        fs::write(root.join("a.rs"), "one\ntwo\nthree\n").unwrap();
+        fs::write(root.join("b.rs"), "b\n").unwrap();
+        commit(root, "Alice", "first");
+        fs::write(root.join("a.rs"), "one\n2\nthree\nfour\n").unwrap();
+        commit(root, "Bob", "second");
+
+        let a = blame(&root.join("a.rs")).unwrap();
+        assert_eq!(a.get("Alice"), Some(&2));
+        assert_eq!(a.get("Bob"), Some(&2));
+
+        let shares = authors(&[&root.join("a.rs"), &root.join("b.rs")]).unwrap();
+        let summary: Vec<_> = shares
+            .iter()
+            .map(|share| (share.name.as_str(), share.files, share.lines))
+            .collect();
+        assert_eq!(summary, vec![("Alice", 2, 3), ("Bob", 1, 2)]);
+        assert!((shares[0].lines_percent - 60.0).abs() < 1e-9);
+
+        fs::write(root.join("untracked.rs"), "x\n").unwrap();
+        assert!(blame(&root.join("untracked.rs")).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! 分组汇总
+//!
+//! `--group-by` 在逐文件统计和输出之间增加一个汇总阶段，把多文件结果按分组键合并：
+//!
+//! - `ext`：按扩展名，例如 `.rs: 12340 行, 87 个文件`。扩展名按小写比较（`.RS` 与 `.rs` 归为一组），
+//!   没有扩展名的文件归入键为空字符串的组
+//! - `dir`：按目录，例如 `src/`、`tests/`、`benches/`。目录相对于文件所在的输入目录计算，
+//!   `--depth N` 控制保留的目录层数（默认 1，即顶层目录），直接位于输入目录中的文件归入键为空字符串的组
+//!
+//! 被跳过的二进制文件和不存在的路径不计入任何分组。
+
+use crate::report::FileReport;
+use crate::stats::LineStats;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// 分组方式
+#[derive(ValueEnum, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupBy {
+    /// 按文件扩展名
+    Ext,
+    /// 按目录（相对于输入目录，保留 `--depth` 层）
+    Dir,
+}
+
+/// 分组设置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grouping {
+    /// 分组方式
+    pub by: GroupBy,
+    /// 按目录分组时保留的目录层数（至少为 1）
+    pub depth: usize,
+    /// 输入路径，按目录分组时文件路径相对于包含它的输入目录计算
+    pub roots: Vec<PathBuf>,
+}
+
+impl Grouping {
+    /// 计算路径的分组键
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径
+    ///
+    /// # 返回值
+    ///
+    /// 分组键：
+    ///
+    /// * 按扩展名：带 `.` 的小写扩展名，没有扩展名时为空字符串
+    /// * 按目录：前 `depth` 层目录，以 `/` 分隔并以 `/` 结尾（如 `src/`），文件直接位于输入目录中时为空字符串
+    pub fn key(&self, path: &Path) -> String {
+        match self.by {
+            GroupBy::Ext => path
+                .extension()
+                .map(|extension| format!(".{}", extension.to_string_lossy().to_lowercase()))
+                .unwrap_or_default(),
+            GroupBy::Dir => {
+                let relative = self
+                    .roots
+                    .iter()
+                    .filter_map(|root| path.strip_prefix(root).ok())
+                    .filter(|relative| !relative.as_os_str().is_empty())
+                    .min_by_key(|relative| relative.components().count())
+                    .unwrap_or(path);
+                let Some(parent) = relative.parent() else {
+                    return String::new();
+                };
+                parent
+                    .components()
+                    .filter_map(|component| match component {
+                        Component::Normal(name) => Some(name.to_string_lossy()),
+                        _ => None,
+                    })
+                    .take(self.depth.max(1))
+                    .map(|name| format!("{}/", name))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// 单个分组的汇总结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Group {
+    /// 分组键（见 [`Grouping::key`]）
+    pub key: String,
+    /// 组内的文件数
+    pub files: usize,
+    /// 组内文件的总大小（字节）
+    pub bytes: u64,
+    /// 组内文件合并后的行数统计
+    pub stats: LineStats,
+}
+
+/// 按分组方式汇总文件报告
+///
+/// # 参数
+///
+/// * `reports` - 单个文件的统计报告；被跳过的报告不计入
+/// * `grouping` - 分组设置
+///
+/// # 返回值
+///
+/// 按总行数从多到少排列的分组（行数相同时按分组键排列）
+pub fn group(reports: &[FileReport], grouping: &Grouping) -> Vec<Group> {
+    let mut groups: HashMap<String, Group> = HashMap::new();
+    for report in reports.iter().filter(|report| !report.is_skipped()) {
+        let key = grouping.key(&report.path);
+        let group = groups.entry(key.clone()).or_insert_with(|| Group {
+            key,
+            ..Group::default()
+        });
+        group.files += 1;
+        group.bytes += report.size;
+        group.stats += &report.stats;
+    }
+
+    let mut groups: Vec<Group> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        b.stats
+            .total_lines
+            .cmp(&a.stats.total_lines)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn grouping(by: GroupBy, depth: usize, roots: &[&str]) -> Grouping {
+        Grouping {
+            by,
+            depth,
+            roots: roots.iter().map(PathBuf::from).collect(),
+        }
+    }
+
+    /// 测试扩展名分组键
+    #[test]
+    fn test_ext_key() {
+        let ext = grouping(GroupBy::Ext, 1, &[]);
+        let key = |path: &str| ext.key(Path::new(path));
+        assert_eq!(key("src/main.rs"), ".rs");
+        assert_eq!(key("LIB.RS"), ".rs");
+        assert_eq!(key("archive.tar.gz"), ".gz");
+        assert_eq!(key("Makefile"), "");
+        assert_eq!(key(".gitignore"), "");
+    }
+
+    /// 测试目录分组键
+    #[test]
+    fn test_dir_key() {
+        let top = grouping(GroupBy::Dir, 1, &["/repo", "."]);
+        let key = |path: &str| top.key(Path::new(path));
+        assert_eq!(key("/repo/src/main.rs"), "src/");
+        assert_eq!(key("/repo/src/deep/a.rs"), "src/");
+        assert_eq!(key("/repo/Cargo.toml"), "");
+        assert_eq!(key("./tests/a.rs"), "tests/");
+        // 直接给出的文件按其自身路径计算
+        assert_eq!(key("benches/b.rs"), "benches/");
+
+        let nested = grouping(GroupBy::Dir, 2, &["/repo"]);
+        assert_eq!(nested.key(Path::new("/repo/src/deep/x/a.rs")), "src/deep/");
+        assert_eq!(nested.key(Path::new("/repo/src/a.rs")), "src/");
+    }
+
+    /// 测试按扩展名汇总与排序
+    #[test]
+    fn test_group() {
+        let report = |path: &str, lines| FileReport {
+            path: PathBuf::from(path),
+            size: 10,
+            stats: LineStats::new(lines, lines, 0),
+            binary: false,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: None,
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+        let reports = vec![
+            report("a.rs", 5),
+            report("b.py", 20),
+            report("c.RS", 7),
+            report("README", 1),
+            FileReport::missing(PathBuf::from("d.rs")),
+        ];
+
+        let groups = group(&reports, &grouping(GroupBy::Ext, 1, &[]));
+        let keys: Vec<&str> = groups.iter().map(|group| group.key.as_str()).collect();
+        assert_eq!(keys, vec![".py", ".rs", ""]);
+        assert_eq!(groups[1].files, 2);
+        assert_eq!(groups[1].bytes, 20);
+        assert_eq!(groups[1].stats.total_lines, 12);
+        assert!(group(&[], &grouping(GroupBy::Ext, 1, &[])).is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+//! Shebang 与许可证头识别
+//!
+//! 文件开头的 shebang 行和许可证注释块属于样板内容，
+//! 单独统计它们的行数，并可通过 `--exclude-headers` 从代码行数中排除。
+
+use regex::Regex;
+
+/// 默认的许可证头识别模式
+pub const DEFAULT_LICENSE_PATTERNS: &[&str] = &[
+    r"(?i)copyright",
+    r"SPDX-License-Identifier",
+    r"(?i)licensed under",
+    r"(?i)\blicense\b",
+];
+
+/// 许可证注释块的最大行数
+///
+/// 超过该行数的开头注释块不再视为许可证头，避免缓存过多内容。
+pub const MAX_HEADER_LINES: usize = 100;
+
+/// 注释行的前缀
+const COMMENT_PREFIXES: &[&str] = &["//", "/*", "*", "#", "--", ";", "<!--", "%", "'"];
+
+/// 文件头识别选项
+#[derive(Debug, Clone)]
+pub struct HeaderOptions {
+    /// 开头注释块中任意一行匹配其中一个模式时，整个注释块视为许可证头
+    pub license_patterns: Vec<Regex>,
+    /// 从总行数、非空行数和空行数中排除 shebang 和许可证头
+    pub exclude: bool,
+}
+
+impl Default for HeaderOptions {
+    fn default() -> Self {
+        Self {
+            license_patterns: DEFAULT_LICENSE_PATTERNS
+                .iter()
+                .map(|pattern| Regex::new(pattern).expect("内置许可证模式无效"))
+                .collect(),
+            exclude: false,
+        }
+    }
+}
+
+impl HeaderOptions {
+    /// 判断一行是否匹配任意许可证模式
+    pub fn is_license_line(&self, line: &str) -> bool {
+        self.license_patterns
+            .iter()
+            .any(|pattern| pattern.is_match(line))
+    }
+}
+
+/// 判断一行是否为 shebang（`#!` 开头，但不是 Rust 的 `#![...]` 内部属性）
+pub fn is_shebang(line: &str) -> bool {
+    line.starts_with("#!") && !line.starts_with("#![")
+}
+
+/// 判断一行是否为注释行
+///
+/// 只根据常见的行首注释符号判断，不区分语言。
+/// Rust 属性（`#[...]`）不视为注释。
+pub fn is_comment_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("#[") || trimmed.starts_with("#![") {
+        return false;
+    }
+    COMMENT_PREFIXES
+        .iter()
+        .any(|prefix| trimmed.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试 shebang 识别
+    #[test]
+    fn test_is_shebang() {
+        assert!(is_shebang("#!/usr/bin/env python3"));
+        assert!(is_shebang("#!/bin/bash"));
+        assert!(!is_shebang("#![allow(dead_code)]"));
+        assert!(!is_shebang("# comment"));
+    }
+
+    /// 测试注释行识别
+    #[test]
+    fn test_is_comment_line() {
+        assert!(is_comment_line("// Copyright"));
+        assert!(is_comment_line(" * Licensed under MIT"));
+        assert!(is_comment_line("# SPDX-License-Identifier: MIT"));
+        assert!(!is_comment_line("#[derive(Debug)]"));
+        assert!(!is_comment_line("fn main() {}"));
+    }
+
+    /// 测试默认许可证模式
+    #[test]
+    fn test_default_license_patterns() {
+        let options = HeaderOptions::default();
+        assert!(options.is_license_line("// Copyright 2024 Example"));
+        assert!(options.is_license_line("// SPDX-License-Identifier: Apache-2.0"));
+        assert!(!options.is_license_line("// 计算两个数的和"));
+    }
+}
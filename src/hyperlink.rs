@@ -0,0 +1,275 @@
+//! 终端超链接
+//!
+//! 终端支持 OSC 8 超链接时，可读文本输出中的文件路径（报告、最长行和警告列表）渲染为可点击的链接。
+//! 链接地址由 `--hyperlink-format` 模板生成，变量和内置别名与 ripgrep 的同名选项一致：
+//!
+//! - `{path}`：文件的绝对路径（按 URL 规则转义），以 `/` 开头
+//! - `{line}`：行号，没有具体行时为 1
+//! - `{column}`：列号，总是 1
+//! - `{host}`：主机名
+//!
+//! `--hyperlinks` 控制何时输出超链接：`auto`（默认）只在输出流是支持超链接的终端时输出，
+//! `always` 总是输出，`never` 从不输出。无法解析为文件的路径（标准输入、不存在的路径）不加链接。
+
+use clap::ValueEnum;
+use std::fmt::Display;
+use std::path::Path;
+
+pub use supports_hyperlinks::Stream;
+
+/// 内置的模板别名
+const ALIASES: &[(&str, &str)] = &[
+    ("default", "file://{host}{path}"),
+    ("file", "file://{host}{path}"),
+    ("vscode", "vscode://file{path}:{line}:{column}"),
+    (
+        "vscode-insiders",
+        "vscode-insiders://file{path}:{line}:{column}",
+    ),
+    ("vscodium", "vscodium://file{path}:{line}:{column}"),
+    ("cursor", "cursor://file{path}:{line}:{column}"),
+    ("kitty", "file://{host}{path}#{line}"),
+    (
+        "textmate",
+        "txmt://open?url=file://{path}&line={line}&column={column}",
+    ),
+    ("none", ""),
+];
+
+/// 模板中可用的变量
+const VARIABLES: &[&str] = &["path", "line", "column", "host"];
+
+/// 何时输出超链接
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum When {
+    /// 输出流是支持超链接的终端时
+    #[default]
+    Auto,
+    /// 总是输出
+    Always,
+    /// 从不输出
+    Never,
+}
+
+/// 超链接地址模板
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperlinkFormat {
+    template: String,
+}
+
+impl HyperlinkFormat {
+    /// 是否关闭了超链接（`none`）
+    pub fn is_none(&self) -> bool {
+        self.template.is_empty()
+    }
+}
+
+/// 解析超链接地址模板
+///
+/// # 参数
+///
+/// * `value` - 内置别名（如 `vscode`）或包含 `{path}` 的模板（如 `myeditor://open{path}?line={line}`）
+///
+/// # 返回值
+///
+/// * `Ok(HyperlinkFormat)` - 地址模板
+/// * `Err(String)` - 模板缺少 `{path}`、包含未知变量或括号不匹配，供 `clap` 显示
+pub fn parse_format(value: &str) -> Result<HyperlinkFormat, String> {
+    if let Some(&(_, template)) = ALIASES.iter().find(|(alias, _)| *alias == value) {
+        return Ok(HyperlinkFormat {
+            template: template.to_string(),
+        });
+    }
+
+    let mut rest = value;
+    let mut has_path = false;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("超链接模板中的 '{{' 没有匹配的 '}}': '{}'", value))?;
+        let name = &rest[open + 1..open + close];
+        if !VARIABLES.contains(&name) {
+            return Err(format!(
+                "超链接模板中有未知变量 '{{{}}}'，可用变量: {{path}}、{{line}}、{{column}}、{{host}}",
+                name
+            ));
+        }
+        has_path |= name == "path";
+        rest = &rest[open + close + 1..];
+    }
+    if !has_path {
+        return Err(format!(
+            "超链接模板必须包含 {{path}}，或使用内置别名（{}）: '{}'",
+            ALIASES
+                .iter()
+                .map(|(alias, _)| *alias)
+                .collect::<Vec<_>>()
+                .join("、"),
+            value
+        ));
+    }
+    Ok(HyperlinkFormat {
+        template: value.to_string(),
+    })
+}
+
+/// 生成超链接的设置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hyperlinks {
+    format: HyperlinkFormat,
+    host: String,
+}
+
+impl Hyperlinks {
+    /// 使用给定的模板和本机主机名
+    ///
+    /// # 参数
+    ///
+    /// * `format` - 地址模板
+    pub fn new(format: HyperlinkFormat) -> Self {
+        Self {
+            format,
+            host: gethostname::gethostname().to_string_lossy().into_owned(),
+        }
+    }
+
+    /// 根据选项和输出流确定是否输出超链接
+    ///
+    /// # 参数
+    ///
+    /// * `when` - `--hyperlinks` 选项
+    /// * `format` - 地址模板；为 `none` 时不输出
+    /// * `stream` - 链接所在的输出流
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(Hyperlinks)` - 需要输出超链接
+    /// * `None` - 不输出超链接
+    pub fn enabled(when: When, format: &HyperlinkFormat, stream: Stream) -> Option<Self> {
+        let enabled = match when {
+            When::Always => true,
+            When::Never => false,
+            When::Auto => supports_hyperlinks::on(stream),
+        };
+        (enabled && !format.is_none()).then(|| Self::new(format.clone()))
+    }
+
+    /// 生成文件的链接地址
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径
+    /// * `line` - 行号，`None` 时为 1
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(String)` - 链接地址
+    /// * `None` - 路径无法解析为文件（标准输入、不存在的路径）
+    pub fn url(&self, path: &Path, line: Option<usize>) -> Option<String> {
+        let path = path.canonicalize().ok()?;
+        let path = encode_path(&path.to_string_lossy());
+        Some(
+            self.format
+                .template
+                .replace("{path}", &path)
+                .replace("{line}", &line.unwrap_or(1).to_string())
+                .replace("{column}", "1")
+                .replace("{host}", &self.host),
+        )
+    }
+
+    /// 把文本渲染为指向文件的超链接
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 显示的文本
+    /// * `path` - 文件路径
+    /// * `line` - 行号
+    ///
+    /// # 返回值
+    ///
+    /// 包含 OSC 8 转义序列的文本；路径无法解析为文件时为原文本
+    pub fn link(&self, text: impl Display, path: &Path, line: Option<usize>) -> String {
+        match self.url(path, line) {
+            Some(url) => format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// 按 URL 规则转义路径：保留字母、数字、`-._~` 和 `/`，其余字节转义为 `%XX`
+///
+/// Windows 路径的反斜杠转为 `/`，并在盘符前加 `/`，使 `{path}` 总以 `/` 开头。
+fn encode_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut encoded = String::with_capacity(path.len());
+    if !path.starts_with('/') {
+        encoded.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试模板的解析和别名
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            parse_format("vscode").unwrap().template,
+            "vscode://file{path}:{line}:{column}"
+        );
+        assert!(parse_format("none").unwrap().is_none());
+        assert!(parse_format("myeditor://open?file={path}&line={line}").is_ok());
+
+        assert!(parse_format("myeditor://open").is_err());
+        assert!(parse_format("x://{path}{file}").is_err());
+        assert!(parse_format("x://{path").is_err());
+    }
+
+    /// 测试链接地址的生成和路径转义
+    #[test]
+    fn test_url() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("a b#.rs");
+        std::fs::write(&path, "").unwrap();
+        let absolute = path.canonicalize().unwrap().to_string_lossy().into_owned();
+        let expected = absolute.replace(' ', "%20").replace('#', "%23");
+
+        let hyperlinks = Hyperlinks {
+            format: parse_format("vscode").unwrap(),
+            host: "build-01".to_string(),
+        };
+        assert_eq!(
+            hyperlinks.url(&path, Some(42)).unwrap(),
+            format!("vscode://file{}:42:1", expected)
+        );
+        let hyperlinks = Hyperlinks {
+            format: parse_format("default").unwrap(),
+            host: "build-01".to_string(),
+        };
+        assert_eq!(
+            hyperlinks.url(&path, None).unwrap(),
+            format!("file://build-01{}", expected)
+        );
+
+        // 不存在的路径不加链接
+        let missing = temp_dir.path().join("missing.rs");
+        assert_eq!(hyperlinks.url(&missing, None), None);
+        assert_eq!(hyperlinks.link("missing.rs", &missing, None), "missing.rs");
+        assert!(
+            hyperlinks
+                .link("a.rs", &path, None)
+                .starts_with("\x1b]8;;file://")
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! 输入源
+//!
+//! 除普通文件外，路径 `-` 表示从标准输入读取内容。命名管道、字符设备等特殊文件
+//! （如 `/dev/stdin`、`mkfifo` 创建的管道和 bash 的进程替换 `<(cmd)`）同样按流式读取（见 [`is_special_file`]）。
+//! 以 `http` 特性构建时，以 `http://` 或 `https://` 开头的路径表示下载并统计 URL 的内容（见 [`is_url`]）。
+//! 要统计的路径还可以通过 `--files-from` 从文件或标准输入中读取（见 [`read_file_list`]），
+//! 便于与 `find`、`git ls-files`、`fd` 等命令组合。
+
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::path::{Path, PathBuf};
+
+/// 表示标准输入的路径
+pub const STDIN_PATH: &str = "-";
+
+/// 标准输入在报告中的默认名称
+pub const STDIN_DISPLAY_NAME: &str = "<stdin>";
+
+/// 判断路径是否表示标准输入
+pub fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN_PATH
+}
+
+/// 判断路径是否表示 URL（以 `http://` 或 `https://` 开头）
+pub fn is_url(path: &Path) -> bool {
+    path.to_str().is_some_and(|path| {
+        let scheme = path.split_once("://").map(|(scheme, _)| scheme);
+        scheme.is_some_and(|scheme| {
+            scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")
+        })
+    })
+}
+
+/// 判断路径是否指向命名管道、字符设备、套接字等既不是普通文件也不是目录的特殊文件
+///
+/// 特殊文件的元数据中的大小没有意义，内容也不能回退或内存映射，只能像标准输入一样从头到尾读取一次。
+pub fn is_special_file(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| !metadata.is_file() && !metadata.is_dir())
+}
+
+/// 读取文件列表，每行一个路径，或以 NUL 分隔（`-0`，对应 `find -print0`）
+///
+/// 按行分隔时行尾的 `\r` 被去除；按 NUL 分隔时路径原样保留，可以包含换行符和首尾空白。空的项被忽略。
+///
+/// # 参数
+///
+/// * `reader` - 文件列表的内容
+/// * `null` - 路径以 NUL 而不是换行符分隔
+///
+/// # 返回值
+///
+/// * `Ok(Vec<PathBuf>)` - 按列表顺序排列的路径
+/// * `Err(io::Error)` - 读取失败
+pub fn read_file_list(reader: impl BufRead, null: bool) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in reader.split(if null { b'\0' } else { b'\n' }) {
+        let entry = entry?;
+        let entry = match entry.strip_suffix(b"\r") {
+            Some(line) if !null => line,
+            _ => &entry,
+        };
+        if !entry.is_empty() {
+            paths.push(path_from_bytes(entry));
+        }
+    }
+    Ok(paths)
+}
+
+/// 由字节构造路径；Unix 上保留非 UTF-8 的文件名，其他平台按 UTF-8 解码
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+    }
+    #[cfg(not(unix))]
+    {
+        PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+/// 统计已读取字节数的读取器
+///
+/// 标准输入等无法预先获取大小的输入，通过它得到实际读取的字节数。
+pub struct CountingReader<R> {
+    inner: R,
+    bytes_read: u64,
+}
+
+impl<R> CountingReader<R> {
+    /// 包装一个读取器
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            bytes_read: 0,
+        }
+    }
+
+    /// 已读取的字节数
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes_read += read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// 测试标准输入路径识别
+    #[test]
+    fn test_is_stdin() {
+        assert!(is_stdin(Path::new("-")));
+        assert!(!is_stdin(Path::new("./-")));
+        assert!(!is_stdin(Path::new("file.txt")));
+    }
+
+    /// 测试 URL 识别
+    #[test]
+    fn test_is_url() {
+        assert!(is_url(Path::new("https://example.com/big.log")));
+        assert!(is_url(Path::new("HTTP://example.com")));
+        assert!(!is_url(Path::new("ftp://example.com/a.txt")));
+        assert!(!is_url(Path::new("./https://example.com")));
+        assert!(!is_url(Path::new("src/main.rs")));
+    }
+
+    /// 测试特殊文件识别
+    #[cfg(unix)]
+    #[test]
+    fn test_is_special_file() {
+        assert!(is_special_file(Path::new("/dev/null")));
+        assert!(!is_special_file(Path::new("/")));
+        assert!(!is_special_file(Path::new("Cargo.toml")));
+        assert!(!is_special_file(Path::new("does-not-exist")));
+    }
+
+    /// 测试文件列表的读取
+    #[test]
+    fn test_read_file_list() {
+        let paths =
+            read_file_list(Cursor::new("src/a.rs\r\n\nsrc/b c.rs\n./中文.txt"), false).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("src/a.rs"),
+                PathBuf::from("src/b c.rs"),
+                PathBuf::from("./中文.txt")
+            ]
+        );
+        assert!(read_file_list(Cursor::new(""), false).unwrap().is_empty());
+
+        let paths = read_file_list(Cursor::new("a\nb.txt\0 c\r\0\0"), true).unwrap();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("a\nb.txt"), PathBuf::from(" c\r")]
+        );
+    }
+
+    /// 测试字节计数
+    #[test]
+    fn test_counting_reader() {
+        let mut reader = CountingReader::new(Cursor::new("你好\nworld"));
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(reader.bytes_read(), "你好\nworld".len() as u64);
+    }
+}
@@ -0,0 +1,383 @@
+//! JSON 语法检查
+//!
+//! `--mode jsonl` 和 `--mode json` 需要判断内容是否为合法的 JSON。核心统计逻辑不依赖 `serde_json`，
+//! 这里按 RFC 8259 实现一个可以分段输入的语法检查器：内容可以逐行输入，只检查语法、不构造值，
+//! 内存占用只与嵌套深度有关，因此也适用于很大的单个文档。
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// JSON 值的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum JsonKind {
+    /// 对象
+    Object,
+    /// 数组
+    Array,
+    /// 字符串
+    String,
+    /// 数字
+    Number,
+    /// `true` 或 `false`
+    Boolean,
+    /// `null`
+    Null,
+}
+
+/// 合法 JSON 文档的概况
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonDocument {
+    /// 顶层值的类型
+    pub kind: JsonKind,
+    /// 顶层数组的元素数，顶层不是数组时为 `None`
+    pub array_length: Option<usize>,
+}
+
+/// 容器类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Array,
+    Object,
+}
+
+/// 数字的解析状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberState {
+    /// `-` 之后
+    Minus,
+    /// 整数部分为 `0`
+    Zero,
+    /// 整数部分中
+    Integer,
+    /// `.` 之后
+    Dot,
+    /// 小数部分中
+    Fraction,
+    /// `e` 或 `E` 之后
+    Exponent,
+    /// 指数的符号之后
+    ExponentSign,
+    /// 指数部分中
+    ExponentDigits,
+}
+
+impl NumberState {
+    /// 数字能否在这里结束
+    fn is_complete(self) -> bool {
+        matches!(
+            self,
+            NumberState::Zero
+                | NumberState::Integer
+                | NumberState::Fraction
+                | NumberState::ExponentDigits
+        )
+    }
+
+    /// 读入一个字节后的状态，`None` 表示字节不属于数字
+    fn next(self, byte: u8) -> Option<Self> {
+        use NumberState::*;
+        Some(match (self, byte) {
+            (Minus, b'0') => Zero,
+            (Minus, b'1'..=b'9') => Integer,
+            (Integer, b'0'..=b'9') => Integer,
+            (Zero | Integer, b'.') => Dot,
+            (Dot | Fraction, b'0'..=b'9') => Fraction,
+            (Zero | Integer | Fraction, b'e' | b'E') => Exponent,
+            (Exponent, b'+' | b'-') => ExponentSign,
+            (Exponent | ExponentSign | ExponentDigits, b'0'..=b'9') => ExponentDigits,
+            _ => return None,
+        })
+    }
+}
+
+/// 语法检查器的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// 等待一个值（顶层值、数组元素或对象成员的值）
+    Value,
+    /// `[` 之后：等待第一个元素或 `]`
+    ArrayStart,
+    /// `{` 之后：等待第一个键或 `}`
+    ObjectStart,
+    /// `,` 之后：等待对象的键
+    Key,
+    /// 键之后：等待 `:`
+    Colon,
+    /// 值之后：等待 `,`、闭合括号或内容结束
+    AfterValue,
+    /// 字符串中，`key` 表示字符串是对象的键
+    String { key: bool },
+    /// 字符串中的 `\` 之后
+    Escape { key: bool },
+    /// `\u` 之后，还需要 `remaining` 个十六进制数字
+    Unicode { key: bool, remaining: u8 },
+    /// 数字中
+    Number(NumberState),
+    /// `true`、`false` 或 `null` 中，还需要的字节
+    Literal(&'static [u8]),
+    /// 语法错误
+    Error,
+}
+
+/// 分段输入的 JSON 语法检查器
+///
+/// 检查内容是否恰好是一个合法的 JSON 值（前后可以有空白）。
+#[derive(Debug)]
+pub struct JsonValidator {
+    stack: Vec<Container>,
+    state: State,
+    /// 顶层值的类型
+    kind: Option<JsonKind>,
+    /// 顶层数组的元素数
+    array_length: usize,
+}
+
+impl Default for JsonValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonValidator {
+    /// 创建语法检查器
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            state: State::Value,
+            kind: None,
+            array_length: 0,
+        }
+    }
+
+    /// 检查一段内容
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 紧接在之前输入的内容之后的内容
+    ///
+    /// # 返回值
+    ///
+    /// 到目前为止是否没有语法错误
+    pub fn push(&mut self, text: &str) -> bool {
+        for &byte in text.as_bytes() {
+            self.push_byte(byte);
+            if self.state == State::Error {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 到目前为止是否没有语法错误
+    pub fn is_ok(&self) -> bool {
+        self.state != State::Error
+    }
+
+    /// 结束检查
+    ///
+    /// # 返回值
+    ///
+    /// 内容恰好是一个合法的 JSON 值时为文档概况，否则为 `None`
+    pub fn finish(mut self) -> Option<JsonDocument> {
+        if let State::Number(number) = self.state
+            && number.is_complete()
+        {
+            self.state = State::AfterValue;
+        }
+        if self.state != State::AfterValue || !self.stack.is_empty() {
+            return None;
+        }
+        let kind = self.kind?;
+        Some(JsonDocument {
+            kind,
+            array_length: (kind == JsonKind::Array).then_some(self.array_length),
+        })
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        let whitespace = matches!(byte, b' ' | b'\t' | b'\n' | b'\r');
+        self.state = match self.state {
+            State::Error => State::Error,
+            State::Value | State::ArrayStart | State::ObjectStart | State::Key | State::Colon
+                if whitespace =>
+            {
+                self.state
+            }
+            State::AfterValue if whitespace => State::AfterValue,
+            State::ArrayStart if byte == b']' => self.close(Container::Array),
+            State::Value | State::ArrayStart => self.start_value(byte),
+            State::ObjectStart if byte == b'}' => self.close(Container::Object),
+            State::ObjectStart | State::Key if byte == b'"' => State::String { key: true },
+            State::Colon if byte == b':' => State::Value,
+            State::AfterValue => match (byte, self.stack.last()) {
+                (b',', Some(Container::Array)) => State::Value,
+                (b',', Some(Container::Object)) => State::Key,
+                (b']', Some(Container::Array)) => self.close(Container::Array),
+                (b'}', Some(Container::Object)) => self.close(Container::Object),
+                _ => State::Error,
+            },
+            State::String { key } => match byte {
+                b'"' if key => State::Colon,
+                b'"' => State::AfterValue,
+                b'\\' => State::Escape { key },
+                0x00..=0x1f => State::Error,
+                _ => State::String { key },
+            },
+            State::Escape { key } => match byte {
+                b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => State::String { key },
+                b'u' => State::Unicode { key, remaining: 4 },
+                _ => State::Error,
+            },
+            State::Unicode { key, remaining } if byte.is_ascii_hexdigit() => {
+                if remaining == 1 {
+                    State::String { key }
+                } else {
+                    State::Unicode {
+                        key,
+                        remaining: remaining - 1,
+                    }
+                }
+            }
+            State::Literal([expected, rest @ ..]) if byte == *expected => {
+                if rest.is_empty() {
+                    State::AfterValue
+                } else {
+                    State::Literal(rest)
+                }
+            }
+            State::Number(number) => match number.next(byte) {
+                Some(next) => State::Number(next),
+                // 数字在第一个不属于它的字节处结束，这个字节按值之后的内容处理
+                None if number.is_complete() => {
+                    self.state = State::AfterValue;
+                    self.push_byte(byte);
+                    return;
+                }
+                None => State::Error,
+            },
+            _ => State::Error,
+        };
+    }
+
+    /// 开始一个值
+    fn start_value(&mut self, byte: u8) -> State {
+        let kind = match byte {
+            b'{' => JsonKind::Object,
+            b'[' => JsonKind::Array,
+            b'"' => JsonKind::String,
+            b'-' | b'0'..=b'9' => JsonKind::Number,
+            b't' | b'f' => JsonKind::Boolean,
+            b'n' => JsonKind::Null,
+            _ => return State::Error,
+        };
+        match self.stack.as_slice() {
+            [] => self.kind = Some(kind),
+            [Container::Array] => self.array_length += 1,
+            _ => {}
+        }
+        match byte {
+            b'{' => {
+                self.stack.push(Container::Object);
+                State::ObjectStart
+            }
+            b'[' => {
+                self.stack.push(Container::Array);
+                State::ArrayStart
+            }
+            b'"' => State::String { key: false },
+            b'-' => State::Number(NumberState::Minus),
+            b'0' => State::Number(NumberState::Zero),
+            b't' => State::Literal(b"rue"),
+            b'f' => State::Literal(b"alse"),
+            b'n' => State::Literal(b"ull"),
+            _ => State::Number(NumberState::Integer),
+        }
+    }
+
+    /// 闭合一个容器
+    fn close(&mut self, container: Container) -> State {
+        debug_assert_eq!(self.stack.last(), Some(&container));
+        self.stack.pop();
+        State::AfterValue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str) -> Option<JsonDocument> {
+        let mut validator = JsonValidator::new();
+        validator.push(text);
+        validator.finish()
+    }
+
+    /// 测试合法的 JSON 值和顶层数组的元素数
+    #[test]
+    fn test_valid() {
+        let document = check(r#" [1, -2.5e+3, "a\"\u00e9", {"k": [true, null]}, [], {}] "#);
+        assert_eq!(
+            document,
+            Some(JsonDocument {
+                kind: JsonKind::Array,
+                array_length: Some(6),
+            })
+        );
+        assert_eq!(check("[]").unwrap().array_length, Some(0));
+        assert_eq!(check("0").unwrap().kind, JsonKind::Number);
+        assert_eq!(check("false").unwrap().kind, JsonKind::Boolean);
+        assert_eq!(check("\"多字节\"").unwrap().kind, JsonKind::String);
+        assert_eq!(check("{\"a\":{\"b\":1}}").unwrap().array_length, None);
+    }
+
+    /// 测试语法错误、不完整的内容和多个值
+    #[test]
+    fn test_invalid() {
+        for text in [
+            "",
+            "   ",
+            "[1,]",
+            "{\"a\" 1}",
+            "{\"a\":1,}",
+            "{1:2}",
+            "[1 2]",
+            "01",
+            "1.",
+            "-",
+            "1e",
+            "tru",
+            "nul1",
+            "\"a\\x\"",
+            "\"\\u12g4\"",
+            "\"a\tb\"",
+            "[1}",
+            "{\"a\":1]",
+            "[[1]",
+            "{} {}",
+            "1 2",
+            "'a'",
+        ] {
+            assert_eq!(check(text), None, "{:?}", text);
+        }
+    }
+
+    /// 测试分段输入与一次输入的结果相同
+    #[test]
+    fn test_push_in_parts() {
+        let mut validator = JsonValidator::new();
+        for part in ["[12", "34, \"ab", "c\", tr", "ue]"] {
+            assert!(validator.push(part));
+        }
+        assert_eq!(validator.finish().unwrap().array_length, Some(3));
+
+        let mut validator = JsonValidator::new();
+        assert!(validator.push("{\"a\": 1}\n"));
+        assert!(!validator.push("{\"b\": 2}\n"));
+        assert!(!validator.is_ok());
+    }
+}
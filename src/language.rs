@@ -0,0 +1,865 @@
+//! 语言识别与语言分布
+//!
+//! 根据文件名和扩展名识别文件的编程语言，并按字节数和行数汇总各语言的占比。
+//! 没有扩展名的脚本（如 `bin/deploy`）按第一行的 shebang（`#!/usr/bin/env python3`）识别。
+//! 语言名称和颜色与 GitHub linguist 的 `languages.yml` 一致，
+//! 自建的代码托管服务可以直接用 [`LanguageShare`] 渲染与 GitHub 相同的语言条。
+//!
+//! 内置语言同时记录注释语法，供 `--comments` 区分注释行和代码行（见 [`crate::comments`]）。
+//! 内置表之外的语言（如内部使用的 DSL）可以在 `--languages-file` 指定的 TOML 文件中声明，
+//! 见 [`Languages::load`]。
+//!
+//! 无法识别语言的文件和被跳过的二进制文件不计入语言分布。
+
+use crate::comments::{CommentSyntax, Continuation};
+use crate::error::{LineCounterError, Result};
+use crate::records::RecordFormat;
+use crate::report::FileReport;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 语言定义
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    /// 语言名称（与 linguist 一致）
+    pub name: &'static str,
+    /// linguist 中的语言颜色（如 `#dea584`），没有颜色的语言为 `None`
+    pub color: Option<&'static str>,
+    /// 扩展名（小写，不含 `.`）
+    pub extensions: &'static [&'static str],
+    /// 完整文件名（如 `Makefile`），优先于扩展名匹配
+    pub filenames: &'static [&'static str],
+    /// 注释语法，没有注释的语言（如 JSON）为 [`CommentSyntax::NONE`]
+    pub comments: CommentSyntax,
+    /// 是否为散文类语言（如 Markdown），`--reading-time` 只为这些文件估计阅读时间
+    pub prose: bool,
+    /// 结构化记录格式（如 CSV），`--mode auto` 为这些文件统计记录数
+    pub records: Option<RecordFormat>,
+}
+
+/// `//` 行注释和 `/* */` 块注释
+const C_STYLE: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    strings: &["\""],
+    continuation: Continuation::NONE,
+};
+
+/// `//`、`/* */`，字符串可以用单引号、双引号和反引号
+const JS_STYLE: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    strings: &["\"", "'", "`"],
+    continuation: Continuation::NONE,
+};
+
+/// `#` 行注释
+const HASH_STYLE: CommentSyntax = CommentSyntax {
+    line: &["#"],
+    block: &[],
+    strings: &["\"", "'"],
+    continuation: Continuation::NONE,
+};
+
+/// `<!-- -->` 块注释
+const XML_STYLE: CommentSyntax = CommentSyntax {
+    line: &[],
+    block: &[("<!--", "-->")],
+    strings: &[],
+    continuation: Continuation::NONE,
+};
+
+/// `--` 行注释和 `/* */` 块注释
+const SQL_STYLE: CommentSyntax = CommentSyntax {
+    line: &["--"],
+    block: &[("/*", "*/")],
+    strings: &["'"],
+    continuation: Continuation::NONE,
+};
+
+/// 只有行注释的语法
+const fn line_comments(line: &'static [&'static str]) -> CommentSyntax {
+    CommentSyntax {
+        line,
+        block: &[],
+        strings: &["\""],
+        continuation: Continuation::NONE,
+    }
+}
+
+/// 内置的语言表
+pub const LANGUAGES: &[Language] = &[
+    lang("Assembly", Some("#6E4C13"), &["asm", "s"], &[]).comments(line_comments(&[";"])),
+    lang("Batchfile", Some("#C1F12E"), &["bat", "cmd"], &[])
+        .comments(line_comments(&["::", "REM ", "rem "]))
+        .continuation(Continuation {
+            marker: Some("^"),
+            brackets: false,
+        }),
+    lang("C", Some("#555555"), &["c", "h"], &[])
+        .comments(C_STYLE)
+        .continuation(Continuation::BACKSLASH),
+    lang("C#", Some("#178600"), &["cs"], &[]).comments(C_STYLE),
+    lang(
+        "C++",
+        Some("#f34b7d"),
+        &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+        &[],
+    )
+    .comments(C_STYLE)
+    .continuation(Continuation::BACKSLASH),
+    lang("CMake", Some("#DA3434"), &["cmake"], &["CMakeLists.txt"]).comments(HASH_STYLE),
+    lang("CSS", Some("#663399"), &["css"], &[]).comments(CommentSyntax {
+        line: &[],
+        block: &[("/*", "*/")],
+        strings: &["\"", "'"],
+        continuation: Continuation::NONE,
+    }),
+    lang("CSV", Some("#237346"), &["csv"], &[]).records(RecordFormat::Csv),
+    lang("Dart", Some("#00B4AB"), &["dart"], &[]).comments(JS_STYLE),
+    lang(
+        "Dockerfile",
+        Some("#384d54"),
+        &["dockerfile"],
+        &["Dockerfile"],
+    )
+    .comments(HASH_STYLE)
+    .continuation(Continuation::BACKSLASH),
+    lang("Elixir", Some("#6e4a7e"), &["ex", "exs"], &[]).comments(CommentSyntax {
+        line: &["#"],
+        block: &[],
+        strings: &["\"\"\"", "\""],
+        continuation: Continuation::NONE,
+    }),
+    lang("Erlang", Some("#B83998"), &["erl", "hrl"], &[]).comments(line_comments(&["%"])),
+    lang("Go", Some("#00ADD8"), &["go"], &[]).comments(CommentSyntax {
+        line: &["//"],
+        block: &[("/*", "*/")],
+        strings: &["\"", "`"],
+        continuation: Continuation::NONE,
+    }),
+    lang("HTML", Some("#e34c26"), &["html", "htm"], &[]).comments(XML_STYLE),
+    lang("Haskell", Some("#5e5086"), &["hs"], &[]).comments(CommentSyntax {
+        line: &["--"],
+        block: &[("{-", "-}")],
+        strings: &["\""],
+        continuation: Continuation::NONE,
+    }),
+    lang("JSON", Some("#292929"), &["json"], &[]),
+    lang("Java", Some("#b07219"), &["java"], &[]).comments(C_STYLE),
+    lang(
+        "JavaScript",
+        Some("#f1e05a"),
+        &["js", "mjs", "cjs", "jsx"],
+        &[],
+    )
+    .comments(JS_STYLE),
+    lang("Julia", Some("#a270ba"), &["jl"], &[]).comments(CommentSyntax {
+        line: &["#"],
+        block: &[("#=", "=#")],
+        strings: &["\"\"\"", "\""],
+        continuation: Continuation::NONE,
+    }),
+    lang("Kotlin", Some("#A97BFF"), &["kt", "kts"], &[]).comments(C_STYLE),
+    lang("Lua", Some("#000080"), &["lua"], &[]).comments(CommentSyntax {
+        line: &["--"],
+        block: &[("--[[", "]]")],
+        strings: &["\"", "'"],
+        continuation: Continuation::NONE,
+    }),
+    lang(
+        "Makefile",
+        Some("#427819"),
+        &["mk", "mak"],
+        &["Makefile", "makefile", "GNUmakefile"],
+    )
+    .comments(line_comments(&["#"]))
+    .continuation(Continuation::BACKSLASH),
+    lang("Markdown", Some("#083fa1"), &["md", "markdown"], &[])
+        .comments(XML_STYLE)
+        .prose(),
+    lang("Nix", Some("#7e7eff"), &["nix"], &[]).comments(CommentSyntax {
+        line: &["#"],
+        block: &[("/*", "*/")],
+        strings: &["\"", "''"],
+        continuation: Continuation::NONE,
+    }),
+    lang("Objective-C", Some("#438eff"), &["m"], &[])
+        .comments(C_STYLE)
+        .continuation(Continuation::BACKSLASH),
+    lang("PHP", Some("#4F5D95"), &["php"], &[]).comments(CommentSyntax {
+        line: &["//", "#"],
+        block: &[("/*", "*/")],
+        strings: &["\"", "'"],
+        continuation: Continuation::NONE,
+    }),
+    lang("Perl", Some("#0298c3"), &["pl", "pm"], &[]).comments(HASH_STYLE),
+    lang("PowerShell", Some("#012456"), &["ps1", "psm1"], &[]).comments(CommentSyntax {
+        line: &["#"],
+        block: &[("<#", "#>")],
+        strings: &["\"", "'"],
+        continuation: Continuation {
+            marker: Some("`"),
+            brackets: false,
+        },
+    }),
+    lang("Python", Some("#3572A5"), &["py", "pyi", "pyw"], &[]).comments(CommentSyntax {
+        line: &["#"],
+        block: &[],
+        strings: &["\"\"\"", "'''", "\"", "'"],
+        continuation: Continuation {
+            marker: Some("\\"),
+            brackets: true,
+        },
+    }),
+    lang("R", Some("#198CE7"), &["r"], &[]).comments(HASH_STYLE),
+    lang("Ruby", Some("#701516"), &["rb"], &["Rakefile", "Gemfile"]).comments(HASH_STYLE),
+    lang("Rust", Some("#dea584"), &["rs"], &[]).comments(C_STYLE),
+    lang("SCSS", Some("#c6538c"), &["scss"], &[]).comments(JS_STYLE),
+    lang("SQL", Some("#e38c00"), &["sql"], &[]).comments(SQL_STYLE),
+    lang("Scala", Some("#c22d40"), &["scala"], &[]).comments(C_STYLE),
+    lang("Shell", Some("#89e051"), &["sh", "bash", "zsh"], &[])
+        .comments(HASH_STYLE)
+        .continuation(Continuation::BACKSLASH),
+    lang("Swift", Some("#F05138"), &["swift"], &[]).comments(C_STYLE),
+    lang("TOML", Some("#9c4221"), &["toml"], &["Cargo.lock"])
+        .comments(HASH_STYLE)
+        .continuation(Continuation {
+            marker: None,
+            brackets: true,
+        }),
+    lang("TSV", Some("#237346"), &["tsv"], &[]).records(RecordFormat::Tsv),
+    lang("TSX", Some("#3178c6"), &["tsx"], &[]).comments(JS_STYLE),
+    lang("Text", None, &["txt"], &[]).prose(),
+    lang("TypeScript", Some("#3178c6"), &["ts", "mts", "cts"], &[]).comments(JS_STYLE),
+    lang("Vim Script", Some("#199f4b"), &["vim"], &[".vimrc"]).comments(CommentSyntax {
+        line: &["\""],
+        block: &[],
+        strings: &["'"],
+        continuation: Continuation::NONE,
+    }),
+    lang("Vue", Some("#41b883"), &["vue"], &[]).comments(CommentSyntax {
+        line: &["//"],
+        block: &[("<!--", "-->"), ("/*", "*/")],
+        strings: &["\"", "'", "`"],
+        continuation: Continuation::NONE,
+    }),
+    lang("XML", Some("#0060ac"), &["xml"], &[]).comments(XML_STYLE),
+    lang("YAML", Some("#cb171e"), &["yml", "yaml"], &[]).comments(HASH_STYLE),
+    lang("Zig", Some("#ec915c"), &["zig"], &[]).comments(line_comments(&["//"])),
+];
+
+/// shebang 中的解释器与语言的对应关系（解释器名称不含版本号）
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("ash", "Shell"),
+    ("bash", "Shell"),
+    ("bun", "JavaScript"),
+    ("dash", "Shell"),
+    ("deno", "TypeScript"),
+    ("elixir", "Elixir"),
+    ("escript", "Erlang"),
+    ("julia", "Julia"),
+    ("ksh", "Shell"),
+    ("lua", "Lua"),
+    ("luajit", "Lua"),
+    ("make", "Makefile"),
+    ("node", "JavaScript"),
+    ("nodejs", "JavaScript"),
+    ("perl", "Perl"),
+    ("php", "PHP"),
+    ("pwsh", "PowerShell"),
+    ("pypy", "Python"),
+    ("python", "Python"),
+    ("Rscript", "R"),
+    ("ruby", "Ruby"),
+    ("sh", "Shell"),
+    ("ts-node", "TypeScript"),
+    ("zsh", "Shell"),
+];
+
+const fn lang(
+    name: &'static str,
+    color: Option<&'static str>,
+    extensions: &'static [&'static str],
+    filenames: &'static [&'static str],
+) -> Language {
+    Language {
+        name,
+        color,
+        extensions,
+        filenames,
+        comments: CommentSyntax::NONE,
+        prose: false,
+        records: None,
+    }
+}
+
+impl Language {
+    /// 设置注释语法
+    const fn comments(self, comments: CommentSyntax) -> Self {
+        Language { comments, ..self }
+    }
+
+    /// 设置续行规则（`--logical`）
+    const fn continuation(self, continuation: Continuation) -> Self {
+        let mut comments = self.comments;
+        comments.continuation = continuation;
+        Language { comments, ..self }
+    }
+
+    /// 标记为散文类语言
+    const fn prose(self) -> Self {
+        Language {
+            prose: true,
+            ..self
+        }
+    }
+
+    /// 设置结构化记录格式
+    const fn records(self, format: RecordFormat) -> Self {
+        Language {
+            records: Some(format),
+            ..self
+        }
+    }
+}
+
+/// 根据文件名识别语言（只使用内置的语言表）
+///
+/// # 参数
+///
+/// * `path` - 文件路径（标准输入使用 `--stdin-name` 指定的伪文件名）
+///
+/// # 返回值
+///
+/// * `Some(&Language)` - 识别出的语言
+/// * `None` - 无法识别
+pub fn detect(path: &Path) -> Option<&'static Language> {
+    Languages::default().detect(path)
+}
+
+/// 在语言表中按文件名查找语言，完整文件名优先于扩展名
+fn find(languages: &'static [Language], path: &Path) -> Option<&'static Language> {
+    let file_name = path.file_name()?.to_str()?;
+    if let Some(language) = languages
+        .iter()
+        .find(|language| language.filenames.contains(&file_name))
+    {
+        return Some(language);
+    }
+
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    languages
+        .iter()
+        .find(|language| language.extensions.contains(&extension.as_str()))
+}
+
+/// 内置语言表与自定义语言
+///
+/// 自定义语言优先于内置语言，因此可以用同名的扩展名覆盖内置语言的识别和注释语法。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Languages {
+    custom: &'static [Language],
+}
+
+/// 自定义语言文件的内容
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LanguagesFile {
+    #[serde(default)]
+    language: Vec<LanguageDefinition>,
+}
+
+/// 自定义语言文件中的一种语言
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LanguageDefinition {
+    name: String,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    filenames: Vec<String>,
+    #[serde(default)]
+    line_comments: Vec<String>,
+    #[serde(default)]
+    block_comments: Vec<(String, String)>,
+    #[serde(default)]
+    strings: Vec<String>,
+    #[serde(default)]
+    line_continuation: Option<String>,
+    #[serde(default)]
+    bracket_continuation: bool,
+    #[serde(default)]
+    prose: bool,
+}
+
+impl LanguageDefinition {
+    /// 校验并转换为语言定义
+    ///
+    /// 语言定义在整个运行期间有效，字符串被有意泄漏为 `'static`，与内置语言表使用相同的类型。
+    fn into_language(self) -> std::result::Result<Language, String> {
+        if self.name.trim().is_empty() {
+            return Err("语言名称不能为空".to_string());
+        }
+        if self.extensions.is_empty() && self.filenames.is_empty() {
+            return Err(format!("语言 '{}' 至少需要一个扩展名或文件名", self.name));
+        }
+        let markers = self
+            .line_comments
+            .iter()
+            .chain(
+                self.block_comments
+                    .iter()
+                    .flat_map(|(start, end)| [start, end]),
+            )
+            .chain(&self.strings)
+            .chain(&self.line_continuation);
+        if markers.into_iter().any(|marker| marker.is_empty()) {
+            return Err(format!(
+                "语言 '{}' 的注释标记、字符串定界符和续行符不能为空",
+                self.name
+            ));
+        }
+
+        let extensions = self
+            .extensions
+            .iter()
+            .map(|extension| extension.trim_start_matches('.').to_ascii_lowercase())
+            .map(leak)
+            .collect();
+        Ok(Language {
+            name: leak(self.name),
+            color: None,
+            extensions: leak_slice(extensions),
+            filenames: leak_slice(self.filenames.into_iter().map(leak).collect()),
+            comments: CommentSyntax {
+                line: leak_slice(self.line_comments.into_iter().map(leak).collect()),
+                block: leak_slice(
+                    self.block_comments
+                        .into_iter()
+                        .map(|(start, end)| (leak(start), leak(end)))
+                        .collect(),
+                ),
+                strings: leak_slice(self.strings.into_iter().map(leak).collect()),
+                continuation: Continuation {
+                    marker: self.line_continuation.map(leak),
+                    brackets: self.bracket_continuation,
+                },
+            },
+            prose: self.prose,
+            records: None,
+        })
+    }
+}
+
+fn leak(value: String) -> &'static str {
+    Box::leak(value.into_boxed_str())
+}
+
+fn leak_slice<T>(values: Vec<T>) -> &'static [T] {
+    Box::leak(values.into_boxed_slice())
+}
+
+impl Languages {
+    /// 读取自定义语言文件
+    ///
+    /// 文件中每个 `[[language]]` 表声明一种语言：
+    ///
+    /// ```toml
+    /// [[language]]
+    /// name = "Acme"
+    /// extensions = ["acme"]
+    /// filenames = ["Acmefile"]
+    /// line_comments = ["--"]
+    /// block_comments = [["{-", "-}"]]
+    /// strings = ["\""]
+    /// ```
+    ///
+    /// `prose = true` 把语言标记为散文类，`--reading-time` 会为这类文件估计阅读时间。
+    /// `line_continuation = "\\"` 和 `bracket_continuation = true` 是 `--logical` 使用的续行规则。
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 自定义语言文件路径
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(Languages)` - 内置语言表加上文件中的语言
+    /// * `Err(LineCounterError)` - 文件无法读取、不是有效的 TOML、包含未知字段或语言定义无效
+    pub fn load(path: &Path) -> Result<Self> {
+        let error = |message: String| LineCounterError::Config {
+            path: path.display().to_string(),
+            message,
+        };
+        let text = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let file: LanguagesFile =
+            toml::from_str(&text).map_err(|e| error(e.message().to_string()))?;
+        let custom = file
+            .language
+            .into_iter()
+            .map(LanguageDefinition::into_language)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(error)?;
+        Ok(Self {
+            custom: leak_slice(custom),
+        })
+    }
+
+    /// 自定义语言
+    pub fn custom(&self) -> &'static [Language] {
+        self.custom
+    }
+
+    /// 根据文件名识别语言，自定义语言优先
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径（标准输入使用 `--stdin-name` 指定的伪文件名）
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(&Language)` - 识别出的语言
+    /// * `None` - 无法识别
+    pub fn detect(&self, path: &Path) -> Option<&'static Language> {
+        find(self.custom, path).or_else(|| find(LANGUAGES, path))
+    }
+
+    /// 按名称查找语言，自定义语言优先
+    ///
+    /// # 参数
+    ///
+    /// * `name` - 语言名称（如 `Rust`）
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(&Language)` - 同名的语言
+    /// * `None` - 没有该名称的语言
+    pub fn by_name(&self, name: &str) -> Option<&'static Language> {
+        self.custom
+            .iter()
+            .chain(LANGUAGES)
+            .find(|language| language.name == name)
+    }
+
+    /// 根据文件名识别语言，没有扩展名的文件再按 shebang 识别
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径
+    /// * `shebang` - 按内容开头的 shebang 识别出的语言，见 [`from_shebang`]
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(&Language)` - 识别出的语言；文件名和扩展名优先于 shebang
+    /// * `None` - 无法识别
+    pub fn detect_with_shebang(
+        &self,
+        path: &Path,
+        shebang: Option<&'static Language>,
+    ) -> Option<&'static Language> {
+        self.detect(path)
+            .or_else(|| shebang.filter(|_| path.extension().is_none()))
+    }
+}
+
+/// 根据内容开头的 shebang 识别语言
+///
+/// 支持直接指定解释器（`#!/bin/bash`）和通过 `env` 查找解释器（`#!/usr/bin/env -S python3 -u`），
+/// 解释器名称末尾的版本号（`python3.12`）被忽略。
+///
+/// # 参数
+///
+/// * `content` - 内容的开头（可以带 UTF-8 BOM），只检查第一行
+///
+/// # 返回值
+///
+/// * `Some(&Language)` - 解释器对应的语言
+/// * `None` - 没有 shebang 或无法识别解释器
+pub fn from_shebang(content: &[u8]) -> Option<&'static Language> {
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+    let line = content.strip_prefix(b"#!")?;
+    let line = &line[..line.iter().position(|&b| b == b'\n').unwrap_or(line.len())];
+    let line = std::str::from_utf8(line).ok()?;
+
+    let mut words = line.split_whitespace();
+    let mut interpreter = interpreter_name(words.next()?);
+    if interpreter == "env" {
+        // 跳过 env 的选项和环境变量赋值
+        interpreter = words
+            .find(|word| !word.starts_with('-') && !word.contains('='))
+            .map(interpreter_name)?;
+    }
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    INTERPRETERS
+        .iter()
+        .find(|(name, _)| *name == interpreter)
+        .and_then(|(_, language)| by_name(language))
+}
+
+/// 解释器路径中的文件名（`/usr/bin/python3` → `python3`）
+fn interpreter_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// 根据名称查找语言
+fn by_name(name: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|language| language.name == name)
+}
+
+/// 单个语言在语言分布中的占比
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageShare {
+    /// 语言名称
+    pub name: &'static str,
+    /// linguist 中的语言颜色
+    pub color: Option<&'static str>,
+    /// 该语言的文件数
+    pub files: usize,
+    /// 该语言的总字节数
+    pub bytes: u64,
+    /// 该语言的总行数
+    pub lines: usize,
+    /// 按字节计算的占比（百分比）
+    pub bytes_percent: f64,
+    /// 按行计算的占比（百分比）
+    pub lines_percent: f64,
+}
+
+/// 按语言汇总文件报告
+///
+/// # 参数
+///
+/// * `reports` - 单个文件的统计报告
+///
+/// # 返回值
+///
+/// 按字节数从大到小排列的语言占比（字节数相同时按名称排列），与 GitHub 语言条的顺序一致
+pub fn breakdown(reports: &[FileReport]) -> Vec<LanguageShare> {
+    let mut shares: Vec<LanguageShare> = Vec::new();
+    for report in reports.iter().filter(|report| !report.is_skipped()) {
+        let Some(name) = report.language else {
+            continue;
+        };
+        let share = match shares.iter_mut().find(|share| share.name == name) {
+            Some(share) => share,
+            None => {
+                shares.push(LanguageShare {
+                    name,
+                    // 自定义语言没有颜色，除非与内置语言同名
+                    color: by_name(name).and_then(|language| language.color),
+                    files: 0,
+                    bytes: 0,
+                    lines: 0,
+                    bytes_percent: 0.0,
+                    lines_percent: 0.0,
+                });
+                shares.last_mut().expect("刚刚插入")
+            }
+        };
+        share.files += 1;
+        share.bytes += report.size;
+        share.lines += report.stats.total_lines;
+    }
+
+    let total_bytes: u64 = shares.iter().map(|share| share.bytes).sum();
+    let total_lines: usize = shares.iter().map(|share| share.lines).sum();
+    for share in &mut shares {
+        share.bytes_percent = percentage(share.bytes as f64, total_bytes as f64);
+        share.lines_percent = percentage(share.lines as f64, total_lines as f64);
+    }
+
+    shares.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(b.name)));
+    shares
+}
+
+fn percentage(part: f64, total: f64) -> f64 {
+    if total == 0.0 {
+        0.0
+    } else {
+        part / total * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::LineStats;
+    use std::path::PathBuf;
+
+    /// 测试按扩展名和文件名识别语言
+    #[test]
+    fn test_detect() {
+        let name = |path: &str| detect(Path::new(path)).map(|language| language.name);
+        assert_eq!(name("src/main.rs"), Some("Rust"));
+        assert_eq!(name("App.TSX"), Some("TSX"));
+        assert_eq!(name("build/Makefile"), Some("Makefile"));
+        assert_eq!(name("CMakeLists.txt"), Some("CMake"));
+        assert_eq!(name("notes.txt"), Some("Text"));
+        assert_eq!(name("data.bin"), None);
+        assert_eq!(name("LICENSE"), None);
+    }
+
+    /// 测试按 shebang 识别没有扩展名的脚本
+    #[test]
+    fn test_shebang() {
+        let name = |content: &str| from_shebang(content.as_bytes()).map(|language| language.name);
+        assert_eq!(name("#!/usr/bin/env python3\nprint(1)\n"), Some("Python"));
+        assert_eq!(name("#!/usr/bin/python3.12 -u\n"), Some("Python"));
+        assert_eq!(name("#!/bin/bash\n"), Some("Shell"));
+        assert_eq!(name("#! /bin/sh -e\n"), Some("Shell"));
+        assert_eq!(
+            name("#!/usr/bin/env -S LANG=C node --harmony\n"),
+            Some("JavaScript")
+        );
+        assert_eq!(name("\u{feff}#!/usr/bin/env ruby"), Some("Ruby"));
+        assert_eq!(name("#!/usr/bin/env\n"), None);
+        assert_eq!(name("#!/opt/custom/interp\n"), None);
+        assert_eq!(name("# not a shebang\n#!/bin/bash\n"), None);
+
+        let bash = from_shebang(b"#!/bin/bash\n");
+        let languages = Languages::default();
+        let name = |path: &str| {
+            languages
+                .detect_with_shebang(Path::new(path), bash)
+                .map(|l| l.name)
+        };
+        assert_eq!(name("bin/deploy"), Some("Shell"));
+        assert_eq!(name("Makefile"), Some("Makefile"));
+        assert_eq!(name("deploy.py"), Some("Python"));
+        assert_eq!(name("notes.unknown"), None);
+        assert_eq!(
+            languages.detect_with_shebang(Path::new("LICENSE"), None),
+            None
+        );
+    }
+
+    /// 测试语言表中的名称不重复，扩展名都是小写，且解释器都对应表中的语言
+    #[test]
+    fn test_language_table() {
+        for (i, language) in LANGUAGES.iter().enumerate() {
+            assert!(
+                LANGUAGES[i + 1..]
+                    .iter()
+                    .all(|other| other.name != language.name),
+                "重复的语言: {}",
+                language.name
+            );
+            for extension in language.extensions {
+                assert_eq!(*extension, extension.to_ascii_lowercase());
+            }
+        }
+        for (interpreter, language) in INTERPRETERS {
+            assert!(by_name(language).is_some(), "未知的语言: {}", language);
+            assert!(!interpreter.ends_with(|c: char| c.is_ascii_digit()));
+        }
+    }
+
+    /// 测试读取自定义语言文件
+    #[test]
+    fn test_load_languages() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("languages.toml");
+        fs::write(
+            &path,
+            r#"
+[[language]]
+name = "Acme"
+extensions = [".ACME", "acm"]
+filenames = ["Acmefile"]
+line_comments = ["--"]
+block_comments = [["{-", "-}"]]
+strings = ['"']
+line_continuation = "&"
+
+[[language]]
+name = "Rust"
+extensions = ["rs"]
+line_comments = ["//"]
+"#,
+        )
+        .unwrap();
+
+        let languages = Languages::load(&path).unwrap();
+        assert_eq!(languages.custom().len(), 2);
+        let acme = languages.detect(Path::new("src/main.acme")).unwrap();
+        assert_eq!(acme.name, "Acme");
+        assert_eq!(acme.extensions, ["acme", "acm"]);
+        assert_eq!(acme.comments.block, [("{-", "-}")]);
+        assert_eq!(acme.comments.continuation.marker, Some("&"));
+        assert!(!acme.comments.continuation.brackets);
+        assert_eq!(
+            languages.detect(Path::new("Acmefile")).map(|l| l.name),
+            Some("Acme")
+        );
+        // 自定义语言优先于内置语言
+        let rust = languages.detect(Path::new("lib.rs")).unwrap();
+        assert!(rust.comments.block.is_empty());
+        assert_eq!(
+            languages.detect(Path::new("app.py")).map(|l| l.name),
+            Some("Python")
+        );
+
+        for invalid in [
+            "[[language]]\nname = \"X\"\n",
+            "[[language]]\nname = \"X\"\nextensions = [\"x\"]\nline_comments = [\"\"]\n",
+            "[[language]]\nname = \"X\"\nextensions = [\"x\"]\ncolour = \"red\"\n",
+            "[[language]]\nname = \"X\"\nextensions = [\"x\"]\nblock_comments = [[\"(*\"]]\n",
+            "[[language]]\nname = \"X\"\nextensions = [\"x\"]\nline_continuation = \"\"\n",
+        ] {
+            fs::write(&path, invalid).unwrap();
+            assert!(
+                matches!(Languages::load(&path), Err(LineCounterError::Config { .. })),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    /// 测试语言分布的汇总与排序
+    #[test]
+    fn test_breakdown() {
+        let report = |path: &str, size: u64, lines: usize, binary: bool| FileReport {
+            path: PathBuf::from(path),
+            size,
+            stats: LineStats::new(lines, lines, 0),
+            binary,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: detect(Path::new(path)).map(|language| language.name),
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+        let reports = vec![
+            report("a.rs", 300, 10, false),
+            report("b.py", 100, 30, false),
+            report("c.rs", 100, 0, false),
+            report("d.unknown", 1000, 100, false),
+            report("e.py", 1000, 100, true),
+        ];
+
+        let shares = breakdown(&reports);
+        assert_eq!(shares.len(), 2);
+        assert_eq!(shares[0].name, "Rust");
+        assert_eq!(shares[0].color, Some("#dea584"));
+        assert_eq!(shares[0].files, 2);
+        assert_eq!(shares[0].bytes, 400);
+        assert_eq!(shares[0].lines, 10);
+        assert!((shares[0].bytes_percent - 80.0).abs() < 1e-9);
+        assert!((shares[0].lines_percent - 25.0).abs() < 1e-9);
+        assert_eq!(shares[1].name, "Python");
+        assert!((shares[1].bytes_percent - 20.0).abs() < 1e-9);
+        assert!((shares[1].lines_percent - 75.0).abs() < 1e-9);
+
+        assert!(breakdown(&[]).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+//! Line Counter - 行数统计工具
+//!
+//! 这是一个用 Rust 编写的命令行工具，用于统计文件的行数并提供详细的分析信息。
+//!
+//! ## 功能特性
+//!
+//! - 统计总行数、非空行数和空行数
+//! - 显示文件大小和空行占比
+//! - 支持 Unicode 和多种字符编码
+//! - 智能错误处理和用户友好的提示
+//! - 文件大小限制以避免处理过大文件
+//! - 多文件和目录的并行统计
+//!
+//! ## 使用示例
+//!
+//! ```bash
+//! line-counter -- example.txt
+//! line-counter -r src/ tests/
+//! ```
+//!
+//! ## 错误处理
+//!
+//! 本工具使用 `thiserror` 定义结构化错误类型，使用 `anyhow` 进行错误传播，
+//! 提供清晰的错误信息和上下文。
+//!
+//! ## Cargo 特性
+//!
+//! 不启用任何特性（`default-features = false`）时只包含核心统计逻辑（[`counter`]、[`stats`]、
+//! [`headers`]、[`comments`]、[`duplicates`]、[`todos`]、[`words`]、[`records`]、[`json`]、[`tokens`]、[`normalize`]、[`locale`] 等），不依赖 `anyhow`、`clap`、`serde`，
+//! 适合嵌入 WebAssembly 插件等对体积敏感的场景。
+//!
+//! - `serde` - 核心统计类型的序列化支持
+//! - `parallel` - 大文件的分块并行统计（`rayon`）
+//! - `cli`（默认）- 命令行工具及其余全部模块，包含 `serde` 和 `parallel`
+//! - `http` - 下载并统计 URL 的内容（`ureq`），包含 `cli`
+//! - `async` - 基于 tokio 的异步统计接口 `count_lines_async`，不依赖 `cli`
+//!
+//! ## 模块结构
+//!
+//! - [`error`] - 结构化错误类型
+//! - [`stats`] - 行数统计结果
+//! - [`counter`] - 核心统计逻辑
+//! - [`headers`] - shebang 与许可证头识别
+//! - [`comments`] - 注释行与代码行的分类、续行判断
+//! - [`hyperlink`] - 终端超链接（OSC 8）
+//! - [`color`] - 终端颜色
+//! - [`pattern`] - 正则表达式的大小写匹配方式
+//! - [`binary`] - 二进制文件识别
+//! - [`encoding`] - 文本编码识别与转码
+//! - [`decompress`] - 解压缩的安全限制（解压后大小与压缩比）
+//! - [`mmap`] - 内存映射统计
+//! - [`normalize`] - 行内容规范化（NFC、去除首尾空白、大小写折叠）
+//! - [`duplicates`] - 重复行检测
+//! - [`todos`] - 待办标记（TODO、FIXME 等）统计
+//! - [`words`] - 单词频率统计
+//! - [`records`] - CSV、TSV、JSON Lines 等结构化记录统计
+//! - [`json`] - JSON 语法检查
+//! - [`tokens`] - 大语言模型 token 数估计
+//! - [`tags`] - 文件标签与按标签汇总
+//! - [`resources`] - 运行环境的资源检测与低内存降级
+//! - [`engine`] - 统计引擎的自动选择
+//! - [`input`] - 输入源（文件与标准输入）
+//! - [`language`] - 语言识别与语言分布
+//! - [`locale`] - 输出语言与消息目录
+//! - [`walk`] - 输入路径展开
+//! - [`git`] - Git 仓库集成（只统计已跟踪的文件）
+//! - [`report`] - 统计报告与汇总
+//! - [`group`] - 按扩展名等分组汇总
+//! - [`warning`] - 文本规范警告（混用换行符、缺少末尾换行符）
+//! - [`output`] / [`meta`] - 结果输出与运行元数据
+//! - [`sink`] - 输出目标（可自行实现，把结果写入自己的存储）
+//! - [`progress`] - 进度显示
+//! - [`cli`] / [`app`] - 命令行参数与程序流程
+//! - [`config`] - 配置文件（永远不读取的路径等）
+//! - [`cache`] - 按路径、大小和修改时间持久化的统计结果缓存
+//! - [`watch`] - 监视模式
+//! - [`follow`] - 跟踪模式（持续统计追加到文件末尾的内容）
+//! - `download` - 下载并统计 URL 的内容（`http` 特性）
+//! - [`tui`] - 交互式浏览模式（终端目录树）
+//! - [`serve`] - HTTP 服务模式
+//! - [`workspace`] - 供编辑器读取的工作区摘要文件
+//! - [`database`] - 按运行追加写入的 SQLite 报告存储
+//! - [`diff`] - 差异模式
+//! - [`compare`] - 按相对路径比较两个目录
+//! - [`baseline`] - 与基线报告比较的回归检查
+//! - [`threshold`] - 行数和空行占比的阈值检查（CI 退出码）
+//! - [`bench`](mod@bench) - 与 `wc -l` 对比的吞吐量基准测试
+
+#[cfg(feature = "cli")]
+pub mod app;
+#[cfg(feature = "cli")]
+pub mod baseline;
+#[cfg(feature = "cli")]
+pub mod bench;
+pub mod binary;
+pub mod buffer;
+#[cfg(feature = "cli")]
+pub mod cache;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+pub mod color;
+pub mod comments;
+#[cfg(feature = "cli")]
+pub mod compare;
+#[cfg(feature = "cli")]
+pub mod config;
+pub mod counter;
+#[cfg(feature = "cli")]
+pub mod database;
+#[cfg(feature = "cli")]
+pub mod decompress;
+#[cfg(feature = "cli")]
+pub mod diff;
+#[cfg(feature = "http")]
+pub mod download;
+pub mod duplicates;
+#[cfg(feature = "cli")]
+pub mod encoding;
+pub mod engine;
+pub mod error;
+#[cfg(feature = "cli")]
+pub mod follow;
+#[cfg(feature = "cli")]
+pub mod git;
+#[cfg(feature = "cli")]
+pub mod group;
+pub mod headers;
+#[cfg(feature = "cli")]
+pub mod hyperlink;
+#[cfg(feature = "cli")]
+pub mod input;
+pub mod json;
+#[cfg(feature = "cli")]
+pub mod language;
+pub mod locale;
+#[cfg(feature = "cli")]
+pub mod logging;
+#[cfg(feature = "cli")]
+pub mod meta;
+#[cfg(feature = "cli")]
+pub mod mmap;
+pub mod normalize;
+#[cfg(feature = "cli")]
+pub mod output;
+#[cfg(feature = "cli")]
+pub mod pattern;
+#[cfg(feature = "cli")]
+pub mod progress;
+pub mod records;
+#[cfg(feature = "cli")]
+pub mod report;
+#[cfg(feature = "cli")]
+pub mod resources;
+#[cfg(feature = "cli")]
+pub mod serve;
+#[cfg(feature = "cli")]
+pub mod sink;
+pub mod stats;
+#[cfg(feature = "cli")]
+pub mod tags;
+#[cfg(feature = "cli")]
+pub mod threshold;
+pub mod todos;
+pub mod tokens;
+#[cfg(feature = "cli")]
+pub mod tui;
+pub mod units;
+#[cfg(feature = "cli")]
+pub mod walk;
+#[cfg(feature = "cli")]
+pub mod warning;
+#[cfg(feature = "cli")]
+pub mod watch;
+pub mod words;
+#[cfg(feature = "cli")]
+pub mod workspace;
+
+#[cfg(feature = "async")]
+pub use counter::count_lines_async;
+pub use counter::{
+    CountOptions, ReadAt, SectionFilter, count_lines, count_lines_in_ranges_parallel,
+    count_lines_in_slice, count_lines_streaming,
+};
+pub use error::LineCounterError;
+pub use stats::{
+    CommentStats, CsvStats, DuplicateStats, IndentStyle, Indentation, JsonDocumentStats,
+    JsonLinesStats, LineEnding, LineEndings, LineLength, LineStats, LogicalLines, LongLine,
+    RepeatedLine, TodoItem, TodoStats, TokenCount, WordCount, WordFrequency,
+};
@@ -0,0 +1,790 @@
+//! 输出语言
+//!
+//! 可读文本和错误信息以中文写成，中文原文同时作为消息目录的键（与 gettext 的 msgid 相同）：
+//! 选择其他语言时按原文在该语言的目录中查找译文，目录中没有的消息保持中文。
+//! 带参数的消息用 `{}` 标出参数的位置，译文中的 `{}` 按相同的顺序填入参数。
+//!
+//! 语言由 `--lang` 指定，未指定时按区域设置（`LC_ALL`、`LC_MESSAGES`、`LANG` 中第一个非空的值）选择：
+//! `zh` 开头的区域设置、`C` / `POSIX` 和未设置时为中文，其余为英文。
+
+use std::borrow::Cow;
+use std::fmt::Display;
+
+/// 输出语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Lang {
+    /// 中文
+    #[default]
+    Zh,
+    /// 英文
+    En,
+}
+
+impl Lang {
+    /// 按区域设置选择语言
+    ///
+    /// # 返回值
+    ///
+    /// `LC_ALL`、`LC_MESSAGES`、`LANG` 中第一个非空的值对应的语言；都未设置时为中文
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .map_or_else(Self::default, |locale| Self::from_locale(&locale))
+    }
+
+    /// 区域设置对应的语言
+    ///
+    /// # 参数
+    ///
+    /// * `locale` - 区域设置，如 `zh_CN.UTF-8`、`en_US.UTF-8`、`C`
+    pub fn from_locale(locale: &str) -> Self {
+        let language = locale.split(['_', '.', '@', '-']).next().unwrap_or("");
+        match language.to_ascii_lowercase().as_str() {
+            "" | "zh" | "c" | "posix" => Lang::Zh,
+            _ => Lang::En,
+        }
+    }
+
+    /// 该语言的消息目录，中文（原文）没有目录
+    fn catalog(self) -> Option<&'static [(&'static str, &'static str)]> {
+        match self {
+            Lang::Zh => None,
+            Lang::En => Some(EN),
+        }
+    }
+
+    /// 查找消息的译文
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 中文原文（可以包含 `{}` 参数位置）
+    ///
+    /// # 返回值
+    ///
+    /// 目录中的译文；中文或目录中没有该消息时为原文
+    pub fn tr(self, text: &'static str) -> &'static str {
+        self.catalog()
+            .and_then(|catalog| catalog.iter().find(|(source, _)| *source == text))
+            .map_or(text, |(_, translated)| translated)
+    }
+
+    /// 翻译带参数的消息并填入参数
+    ///
+    /// # 参数
+    ///
+    /// * `template` - 中文原文，用 `{}` 标出参数的位置
+    /// * `args` - 按顺序填入的参数
+    ///
+    /// # 返回值
+    ///
+    /// 填入参数后的消息；参数少于 `{}` 时多余的 `{}` 保持原样
+    pub fn format(self, template: &'static str, args: &[&dyn Display]) -> String {
+        let mut out = String::new();
+        let mut args = args.iter();
+        let mut pieces = self.tr(template).split("{}");
+        out.push_str(pieces.next().unwrap_or(""));
+        for piece in pieces {
+            match args.next() {
+                Some(arg) => out.push_str(&arg.to_string()),
+                None => out.push_str("{}"),
+            }
+            out.push_str(piece);
+        }
+        out
+    }
+
+    /// 翻译已经填入参数的消息（如错误信息和 `Display` 的结果）
+    ///
+    /// 先按整条消息查找，再逐个尝试带参数的原文：原文中 `{}` 之外的部分与消息一致时，
+    /// 把对应的参数（同样翻译）填入译文。
+    ///
+    /// # 参数
+    ///
+    /// * `message` - 中文消息
+    ///
+    /// # 返回值
+    ///
+    /// 译文；中文或没有匹配的原文时为原消息
+    pub fn translate(self, message: &str) -> Cow<'_, str> {
+        let Some(catalog) = self.catalog() else {
+            return Cow::Borrowed(message);
+        };
+        if let Some((_, translated)) = catalog.iter().find(|(source, _)| *source == message) {
+            return Cow::Borrowed(translated);
+        }
+        for (source, translated) in catalog {
+            if let Some(args) = match_template(source, message) {
+                let args: Vec<Cow<str>> = args.into_iter().map(|arg| self.translate(arg)).collect();
+                let args: Vec<&dyn Display> = args.iter().map(|arg| arg as &dyn Display).collect();
+                let mut out = String::new();
+                let mut pieces = translated.split("{}");
+                out.push_str(pieces.next().unwrap_or(""));
+                for (piece, arg) in pieces.zip(args) {
+                    out.push_str(&arg.to_string());
+                    out.push_str(piece);
+                }
+                return Cow::Owned(out);
+            }
+        }
+        Cow::Borrowed(message)
+    }
+}
+
+/// 用带参数的原文匹配消息
+///
+/// 只匹配 `{}` 之外的部分包含非 ASCII 字符（即中文）的原文，避免 `{}: {}` 之类的原文匹配任意消息；
+/// 相邻的 `{}` 无法确定边界，不参与匹配。
+///
+/// # 返回值
+///
+/// * `Some(args)` - 按顺序排列的参数
+/// * `None` - 不匹配
+fn match_template<'a>(template: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let mut pieces = template.split("{}");
+    let first = pieces.next()?;
+    let pieces: Vec<&str> = pieces.collect();
+    if pieces.is_empty() || template.replace("{}", "").is_ascii() {
+        return None;
+    }
+
+    let mut rest = message.strip_prefix(first)?;
+    let mut args = Vec::with_capacity(pieces.len());
+    for (index, piece) in pieces.iter().enumerate() {
+        if index + 1 == pieces.len() {
+            args.push(rest.strip_suffix(piece)?);
+        } else {
+            if piece.is_empty() {
+                return None;
+            }
+            let end = rest.find(piece)?;
+            args.push(&rest[..end]);
+            rest = &rest[end + piece.len()..];
+        }
+    }
+    Some(args)
+}
+
+/// 英文消息目录
+const EN: &[(&str, &str)] = &[
+    // 通用
+    ("（{}）", " ({})"),
+    ("，", ", "),
+    ("{}（符号链接，指向 {}）", "{} (symlink to {})"),
+    ("字节", "bytes"),
+    ("未通过: ", "FAILED: "),
+    ("通过: ", "PASSED: "),
+    ("错误", "Error"),
+    // 单个文件
+    ("文件分析完成!", "File analysis complete!"),
+    ("文件", "File"),
+    ("文件大小", "File size"),
+    ("编码: {}（带 BOM）", "Encoding: {} (with BOM)"),
+    ("编码", "Encoding"),
+    ("压缩格式", "Compression"),
+    ("总行数", "Total lines"),
+    ("非空行数", "Non-empty lines"),
+    ("空行数", "Empty lines"),
+    ("空行占比", "Empty line ratio"),
+    ("非空行占比", "Non-empty line ratio"),
+    ("Shebang 行数", "Shebang lines"),
+    ("许可证头行数", "License header lines"),
+    ("行尾空白行数", "Lines with trailing whitespace"),
+    ("匹配行数", "Matched lines"),
+    ("含无效 UTF-8 的行数", "Lines with invalid UTF-8"),
+    ("最长行: {} {}", "Longest line: {} {}"),
+    ("待办标记: {}（{}）", "TODO markers: {} ({})"),
+    (
+        "注释行数: {}, 代码行数: {}",
+        "Comment lines: {}, code lines: {}",
+    ),
+    (
+        "逻辑行数: {}（物理行数 {}, 合并续行 {} 行）",
+        "Logical lines: {} ({} physical, {} continued)",
+    ),
+    (
+        "记录数: {}, 列数: {}, 空字段: {}",
+        "Records: {}, columns: {}, empty fields: {}",
+    ),
+    (
+        "总行数 {}, 新增 {} 行（{} 行/秒）",
+        "total {}, {} new lines ({} lines/s)",
+    ),
+    (
+        "已读到文件末尾（{} 行），等待新内容...",
+        "Reached end of file ({} lines), waiting for new content...",
+    ),
+    (
+        "到达截止时间，停止跟踪",
+        "Deadline reached, stopped following",
+    ),
+    (
+        "文件已被删除或移动，停止跟踪",
+        "File was removed or moved, stopped following",
+    ),
+    (
+        "文件被截断，停止跟踪",
+        "File was truncated, stopped following",
+    ),
+    (
+        "--follow 只能用于单个文件",
+        "--follow only works with a single file",
+    ),
+    (
+        "watch 子命令不支持 --follow",
+        "The watch subcommand does not support --follow",
+    ),
+    ("Token 数: {}", "Tokens: {}"),
+    ("无法下载 '{}'", "Cannot download '{}'"),
+    (
+        "'{}' 的大小 {} bytes 超过下载上限 {} bytes",
+        "'{}' is {} bytes, which exceeds the download limit of {} bytes",
+    ),
+    (
+        "下载的内容超过上限 {} bytes",
+        "download exceeds the limit of {} bytes",
+    ),
+    (
+        "统计 URL 需要以 http 特性构建: {}",
+        "Counting URLs requires building with the http feature: {}",
+    ),
+    ("按字符类别估计", "estimated from character classes"),
+    ("引号没有闭合", "unterminated quote"),
+    (
+        "列数不一致的记录: {}",
+        "Records with inconsistent columns: {}",
+    ),
+    ("行 {}", "lines {}"),
+    (
+        "JSON 记录数: {}, 空行数: {}",
+        "JSON records: {}, empty lines: {}",
+    ),
+    ("无效的 JSON 行: {}", "Invalid JSON lines: {}"),
+    ("顶层数组共 {} 个元素", "{} top-level array elements"),
+    ("{} 个元素", "{} elements"),
+    (
+        "JSON 文档: {} 个, 无效 {} 个",
+        "JSON documents: {}, invalid: {}",
+    ),
+    (
+        "JSON 文档: 合法, 顶层为{}",
+        "JSON document: valid, top level is {}",
+    ),
+    (
+        "JSON 文档: 不是单个合法文档",
+        "JSON document: not a single valid document",
+    ),
+    ("对象", "an object"),
+    ("数组", "an array"),
+    ("字符串", "a string"),
+    ("数字", "a number"),
+    ("布尔值", "a boolean"),
+    (
+        "最长行: {} {}, {} 字符, {} 列",
+        "Longest line: {} {}, {} chars, {} columns",
+    ),
+    (
+        "行长度: 中位数 {} 列, P90 {} 列, P99 {} 列",
+        "Line length: median {} columns, p90 {} columns, p99 {} columns",
+    ),
+    (
+        "阅读时间: 约 {} 分钟（{} 个单词）",
+        "Reading time: about {} min ({} words)",
+    ),
+    ("{} 字符", "{} chars"),
+    ("{} 列", "{} columns"),
+    ("占 {}%", "{}%"),
+    ("不同内容 {} 行", "{} distinct"),
+    (
+        "超过跟踪上限，为近似值",
+        "approximate, tracking limit exceeded",
+    ),
+    ("重复行: {} 行", "Duplicate lines: {}"),
+    ("{} 次", "{} times"),
+    ("高频单词: 共 {} 个单词", "Top words: {} words in total"),
+    ("主要为{}", "mostly {}"),
+    ("主要为 {}", "mostly {}"),
+    ("宽度 {}", "width {}"),
+    ("混用", "mixed"),
+    (
+        "缩进: 空格 {} 行, 制表符 {} 行",
+        "Indentation: {} lines with spaces, {} lines with tabs",
+    ),
+    ("缩进", "Indentation"),
+    ("无", "none"),
+    ("换行", "LF"),
+    ("回车加换行", "CRLF"),
+    ("回车", "CR"),
+    ("换行符", "Line endings"),
+    ("制表符", "tabs"),
+    ("空格", "spaces"),
+    // 跳过的文件
+    ("跳过不存在的路径: ", "Skipped missing path: "),
+    ("统计失败: ", "Failed: "),
+    ("到达截止时间，未统计: ", "Deadline reached, not counted: "),
+    (
+        "跳过超过解压缩限制的文件: ",
+        "Skipped file over the decompression limit: ",
+    ),
+    ("跳过二进制文件: ", "Skipped binary file: "),
+    (
+        "使用 --count-binary 强制统计",
+        "use --count-binary to count it anyway",
+    ),
+    (
+        "解压后的大小超过上限 {} bytes",
+        "decompressed size exceeds the limit of {} bytes",
+    ),
+    (
+        "压缩比超过上限 {}",
+        "compression ratio exceeds the limit of {}",
+    ),
+    // 多个文件
+    ("文件 ", "File "),
+    (
+        "总行数 {}, 非空行数 {}, 空行数 {}",
+        "total {}, non-empty {}, empty {}",
+    ),
+    (", 匹配行数 {}", ", matched {}"),
+    (", 无效 UTF-8 行数 {}", ", invalid UTF-8 lines {}"),
+    (", 最长行 {} {}", ", longest line {} {}"),
+    (", 待办标记 {}", ", TODO markers {}"),
+    (", 注释行数 {}, 代码行数 {}", ", comments {}, code {}"),
+    (", 逻辑行数 {}", ", logical lines {}"),
+    (", Token 数 {}", ", tokens {}"),
+    (", 记录数 {}", ", records {}"),
+    (", 不是合法的 JSON", ", invalid JSON"),
+    (", 数组元素 {}", ", array elements {}"),
+    (", 阅读时间约 {} 分钟", ", reading time about {} min"),
+    ("（混合缩进）", " (mixed indentation)"),
+    ("（统计中被修改）", " (modified while counting)"),
+    (
+        "统计过程中文件被修改，结果可能不准确",
+        "The file was modified while counting; the result may be inaccurate",
+    ),
+    (
+        "文件在统计过程中被修改（{} → {} bytes），结果可能不准确",
+        "file was modified while counting ({} → {} bytes); the result may be inaccurate",
+    ),
+    ("汇总完成!", "Summary complete!"),
+    ("文件数", "Files"),
+    ("跳过的二进制文件数", "Skipped binary files"),
+    ("跳过的不存在路径数", "Skipped missing paths"),
+    ("统计失败的文件数", "Failed files"),
+    ("{} 个文件统计失败", "{} files failed"),
+    (
+        "跳过的超过解压缩限制的文件数",
+        "Skipped files over the decompression limit",
+    ),
+    (
+        "结果不完整: 到达截止时间时还有 {} 个文件未统计",
+        "Incomplete: {} files were not counted before the deadline",
+    ),
+    ("警告数", "Warnings"),
+    ("文件总大小", "Total size"),
+    ("耗时", "Elapsed"),
+    (
+        "空行占比: {}%（按行数加权）",
+        "Empty line ratio: {}% (weighted by lines)",
+    ),
+    (
+        "非空行占比: {}%（按行数加权）",
+        "Non-empty line ratio: {}% (weighted by lines)",
+    ),
+    (
+        "文件平均空行占比: {}%（每个文件权重相同）",
+        "Average empty line ratio per file: {}% (each file weighted equally)",
+    ),
+    ("标签:", "Tags:"),
+    (
+        "文件数 {}, 总行数 {}, 非空行数 {}, 空行数 {}",
+        "files {}, total {}, non-empty {}, empty {}",
+    ),
+    ("警告 ", "Warning "),
+    ("混用了多种换行符", "mixed line endings"),
+    ("最后一行缺少换行符", "missing newline at end of file"),
+    (
+        "混用了制表符缩进和空格缩进",
+        "mixed tab and space indentation",
+    ),
+    ("最长的 {} 行:", "Longest {} lines:"),
+    ("第 {} 行", "line {}"),
+    ("{} {}, {} 字符, {} 列", "{} {}, {} chars, {} columns"),
+    ("待办标记列表:", "TODO markers:"),
+    // 分组
+    (
+        "语言分布: 没有识别出语言的文件",
+        "Languages: no files with a recognized language",
+    ),
+    ("语言分布:", "Languages:"),
+    (
+        "按字节占 {}%, 按行占 {}%, {} 个文件, {}, {} 行",
+        "{}% of bytes, {}% of lines, {} files, {}, {} lines",
+    ),
+    (
+        "语言分布（按字节 / 按行）:",
+        "Languages (by bytes / by lines):",
+    ),
+    ("{} 个文件, {}, {} 行", "{} files, {}, {} lines"),
+    ("按扩展名汇总:", "By extension:"),
+    ("按目录汇总:", "By directory:"),
+    ("（无扩展名）", "(no extension)"),
+    ("（顶层文件）", "(top-level files)"),
+    ("{} 行", "{} lines"),
+    (
+        "{} 个文件（非空行数 {}, 空行数 {}, {}）",
+        "{} files (non-empty {}, empty {}, {})",
+    ),
+    (
+        "按作者统计: 没有可以归属作者的行",
+        "By author: no lines could be attributed",
+    ),
+    ("按作者统计:", "By author:"),
+    ("{} 行, 占 {}%, {} 个文件", "{} lines, {}%, {} files"),
+    (
+        "按作者统计（最后修改每一行的作者）:",
+        "By author (who last changed each line):",
+    ),
+    ("行", "lines"),
+    ("{} 个文件", "{} files"),
+    // 比较
+    ("新增", "added"),
+    ("删除", "removed"),
+    ("修改", "modified"),
+    ("大小 {} {}", "size {} {}"),
+    (
+        "文件: 新增 {}, 删除 {}, 修改 {}, 未变化 {}",
+        "Files: {} added, {} removed, {} modified, {} unchanged",
+    ),
+    ("比较", "Comparing"),
+    ("二进制文件", "binary file"),
+    (
+        "文件: 新增 {}, 删除 {}, 修改 {}",
+        "Files: {} added, {} removed, {} modified",
+    ),
+    ("共 {} 行变化", "{} lines changed"),
+    ("（由 {} 重命名）", " (renamed from {})"),
+    (
+        "新增 {} 行, 删除 {} 行, 修改 {} 行",
+        "{} lines added, {} removed, {} modified",
+    ),
+    (" 变为 ", " to "),
+    ("，变化 ", ", change "),
+    ("（忽略空行变化）", " (ignoring empty line changes)"),
+    // 基线与阈值
+    ("基线比较: {} (容差 {}%)", "Baseline: {} (tolerance {}%)"),
+    ("阈值检查通过", "Threshold check passed"),
+    ("阈值检查: {} 项超限", "Threshold check: {} exceeded"),
+    ("{} {} 超过上限 {}", "{} {} exceeds the limit of {}"),
+    ("文件行数", "File lines"),
+    (
+        "已读取 {} {}, 总行数 {}, 非空行数 {}, 空行数 {}",
+        "Read {} {}, total {}, non-empty {}, empty {}",
+    ),
+    // 进度和状态
+    (
+        "跳过 {}：匹配 never_read 规则 '{}'",
+        "Skipping {}: matches never_read rule '{}'",
+    ),
+    ("文件不存在", "file not found"),
+    (
+        "跳过匹配 exclude 规则的文件",
+        "Skipping file matching an exclude rule",
+    ),
+    (
+        "已到达截止时间，跳过文件",
+        "Deadline reached, skipping file",
+    ),
+    ("跳过不存在的文件", "Skipping missing file"),
+    ("跳过二进制文件", "Skipping binary file"),
+    ("开始统计", "Counting"),
+    ("统计完成", "Counted"),
+    ("使用缓存的统计结果", "Using cached result"),
+    (
+        "缓存命中 {} 个文件，重新统计 {} 个文件",
+        "{} files from cache, {} files to count",
+    ),
+    (
+        "大小 {} bytes 超过 --max-size，统计时会报错",
+        "size {} bytes exceeds --max-size and will fail when counting",
+    ),
+    (
+        "共 {} 个文件，总大小 {} bytes",
+        "{} files, {} bytes in total",
+    ),
+    (
+        "可用内存较少（{} MB），改为顺序统计并使用较小的读取缓冲区",
+        "Low available memory ({} MB), counting sequentially with a smaller read buffer",
+    ),
+    ("正在处理文件", "Processing file"),
+    ("正在处理 {} 个文件", "Processing {} files"),
+    ("已写入工作区摘要", "Wrote workspace summary"),
+    (
+        "已写入数据库: {}（运行编号 {}，{} 个文件）",
+        "Wrote database: {} (run {}, {} files)",
+    ),
+    (
+        "正在监视变化，按 Ctrl+C 退出...",
+        "Watching for changes, press Ctrl+C to exit...",
+    ),
+    (
+        "检测到变化，重新统计...",
+        "Change detected, counting again...",
+    ),
+    ("监视错误", "Watch error"),
+    (
+        "{spinner} [{elapsed_precise}] [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, 剩余 {eta})",
+        "{spinner} [{elapsed_precise}] [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta} left)",
+    ),
+    (
+        "{spinner} [{elapsed_precise}] [{bar:40}] {pos}/{len} 个文件",
+        "{spinner} [{elapsed_precise}] [{bar:40}] {pos}/{len} files",
+    ),
+    // 交互式浏览
+    ("名称", "Name"),
+    ("已统计 {} / {} 个文件", "Counted {} / {} files"),
+    ("，失败 {}", ", {} failed"),
+    ("超过解压缩限制", "over limit"),
+    ("过滤: ", "Filter: "),
+    ("Enter 确认  Esc 清除", "Enter apply  Esc clear"),
+    (
+        "↑↓ 移动  ←→ 折叠/展开  s 排序  r 反转  / 过滤  q 退出",
+        "↑↓ move  ←→ collapse/expand  s sort  r reverse  / filter  q quit",
+    ),
+    // HTTP 服务
+    ("正在监听 http://{}", "Listening on http://{}"),
+    ("只支持 GET 和 POST 请求", "Only GET and POST are supported"),
+    ("未知的路径: {}", "Unknown path: {}"),
+    (
+        "请求内容超过上限 {} bytes",
+        "Request body exceeds the limit of {} bytes",
+    ),
+    ("缺少参数 path", "Missing parameter path"),
+    (
+        "路径 '{}' 不在服务的根目录中",
+        "Path '{}' is outside the served root",
+    ),
+    ("读取请求内容时发生错误", "Error reading the request body"),
+    ("无法监听 {}:{}", "Cannot listen on {}:{}"),
+    // 用法
+    (
+        "错误: 缺少文件路径参数",
+        "Error: missing file path argument",
+    ),
+    ("用法: {} <文件路径>...", "Usage: {} <FILE>..."),
+    ("示例: {} example.txt", "Example: {} example.txt"),
+    ("示例: {} -r src/", "Example: {} -r src/"),
+    // 错误
+    ("文件路径无效: {}", "Invalid file path: {}"),
+    ("文件不存在: {}", "File not found: {}"),
+    ("无法读取文件: {}", "Cannot read file: {}"),
+    (
+        "文件是一个目录，不是文件: {}",
+        "Is a directory, not a file: {}",
+    ),
+    ("权限不足，无法访问文件: {}", "Permission denied: {}"),
+    (
+        "文件过大，无法处理: {}, 大小: {} bytes",
+        "File too large: {}, size: {} bytes",
+    ),
+    (
+        "缺少必需的文件路径参数",
+        "Missing required file path argument",
+    ),
+    (
+        "行数超过基线容差: {} 项指标超限",
+        "Line counts exceed the baseline tolerance: {} metrics",
+    ),
+    (
+        "统计结果超过阈值: {} 项超限",
+        "Counts exceed the thresholds: {} exceeded",
+    ),
+    ("存在行尾空白: {} 行", "Trailing whitespace found: {} lines"),
+    ("存在文本规范警告: {} 条", "Text hygiene warnings found: {}"),
+    ("Git 命令失败 ({}): {}", "Git command failed ({}): {}"),
+    ("配置文件无效 ({}): {}", "Invalid config file ({}): {}"),
+    (
+        "拒绝读取 '{}'：匹配配置中的 never_read 规则 '{}'",
+        "Refusing to read '{}': matches never_read rule '{}' in the config",
+    ),
+    ("读取第 {} 行时发生错误", "Error reading line {}"),
+    (
+        "数据库表结构版本 {} 比本工具支持的版本 {} 更新",
+        "Database schema version {} is newer than the supported version {}",
+    ),
+    ("数据库错误: {}", "Database error: {}"),
+    ("IO错误: {}", "IO error: {}"),
+    ("统计文件 '{}' 时发生错误", "Failed to count '{}'"),
+    ("无法解压文件 '{}'", "Cannot decompress '{}'"),
+    ("统计标准输入时发生错误", "Failed to count standard input"),
+    ("无法获取当前目录", "Cannot get the current directory"),
+    ("无法读取文件列表 '{}'", "Cannot read file list '{}'"),
+    (
+        "无法读取停用词文件 '{}'",
+        "Cannot read stop words file '{}'",
+    ),
+    (
+        "标准输入已用于读取文件列表，不能同时统计标准输入的内容",
+        "Standard input is already used for the file list and cannot also be counted",
+    ),
+    (
+        "无法写入工作区摘要到 '{}'",
+        "Cannot write workspace summary to '{}'",
+    ),
+    ("无法打开数据库 '{}'", "Cannot open database '{}'"),
+    ("无法写入数据库 '{}'", "Cannot write database '{}'"),
+    ("无法创建线程池", "Cannot create thread pool"),
+    ("无法写入缓存 '{}'", "Cannot write cache '{}'"),
+    ("无法写入输出文件 '{}'", "Cannot write output file '{}'"),
+    (
+        "读取文件开头时发生错误",
+        "Error reading the start of the file",
+    ),
+    (
+        "无法移回文件开头",
+        "Cannot seek back to the start of the file",
+    ),
+    ("无法获取文件 '{}' 的元数据", "Cannot get metadata for '{}'"),
+    ("尝试打开文件 '{}'", "While opening '{}'"),
+    ("'{}' 不是目录", "'{}' is not a directory"),
+    (
+        "--format badge 只适用于统计结果，不适用于 diff",
+        "--format badge only applies to counts, not to diff",
+    ),
+    (
+        "--format plain-kv 只适用于统计结果，不适用于 diff",
+        "--format plain-kv only applies to counts, not to diff",
+    ),
+    (
+        "--format badge 只适用于统计结果，不适用于 compare",
+        "--format badge only applies to counts, not to compare",
+    ),
+    (
+        "--format plain-kv 只适用于统计结果，不适用于 compare",
+        "--format plain-kv only applies to counts, not to compare",
+    ),
+    (
+        "--format badge 只适用于统计结果，不适用于 git-diff",
+        "--format badge only applies to counts, not to git-diff",
+    ),
+    (
+        "--format plain-kv 只适用于统计结果，不适用于 git-diff",
+        "--format plain-kv only applies to counts, not to git-diff",
+    ),
+    ("无法遍历目录 '{}'", "Cannot walk directory '{}'"),
+    ("无法读取报告 '{}'", "Cannot read report '{}'"),
+    ("无法解析报告 '{}'", "Cannot parse report '{}'"),
+    ("无法解析路径 '{}'", "Cannot resolve path '{}'"),
+    (
+        "无法创建文件系统监视器",
+        "Cannot create file system watcher",
+    ),
+    ("无法监视 '{}'", "Cannot watch '{}'"),
+    ("文件系统监视器已停止", "File system watcher stopped"),
+    (
+        "无法执行 git，请确认系统中已安装: {}",
+        "Cannot run git, is it installed? {}",
+    ),
+    ("无效的版本范围: '{}'", "Invalid revision range: '{}'"),
+    ("无效的版本: '{}'", "Invalid revision: '{}'"),
+    ("无效的路径模式 '{}': {}", "Invalid path pattern '{}': {}"),
+    (
+        "无效的行号 '{}'：行号从 1 开始",
+        "Invalid line number '{}': line numbers start at 1",
+    ),
+    (
+        "行号范围的开始大于结束: '{}'",
+        "Line range start is after its end: '{}'",
+    ),
+    ("无法识别的编码: '{}'", "Unknown encoding: '{}'"),
+    (
+        "无法识别的规范化方式: '{}'（支持 nfc、trim、casefold、all）",
+        "Unknown normalization: '{}' (supported: nfc, trim, casefold, all)",
+    ),
+    (
+        "标签规则应为 名称=模式: '{}'",
+        "Tag rule should be NAME=PATTERN: '{}'",
+    ),
+    ("标签名称不能为空: '{}'", "Tag name cannot be empty: '{}'"),
+    ("标签 '{}' 没有路径模式", "Tag '{}' has no path pattern"),
+    ("无效的百分比: '{}'", "Invalid percentage: '{}'"),
+    (
+        "百分比应在 0 到 100 之间: '{}'",
+        "Percentage must be between 0 and 100: '{}'",
+    ),
+    (
+        "无法识别的大小单位: '{}'（支持 B、K、M、G、T）",
+        "Unknown size unit: '{}' (supported: B, K, M, G, T)",
+    ),
+    ("大小超出范围: '{}'", "Size out of range: '{}'"),
+    ("缓冲区大小必须大于 0", "Buffer size must be greater than 0"),
+    ("无效的大小: '{}'", "Invalid size: '{}'"),
+    ("语言名称不能为空", "Language name cannot be empty"),
+    (
+        "语言 '{}' 至少需要一个扩展名或文件名",
+        "Language '{}' needs at least one extension or file name",
+    ),
+    (
+        "语言 '{}' 的注释标记、字符串定界符和续行符不能为空",
+        "Comment markers, string delimiters and line continuations of language '{}' cannot be empty",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试按区域设置选择语言
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Lang::from_locale("zh_CN.UTF-8"), Lang::Zh);
+        assert_eq!(Lang::from_locale("zh_TW"), Lang::Zh);
+        assert_eq!(Lang::from_locale("C"), Lang::Zh);
+        assert_eq!(Lang::from_locale("POSIX"), Lang::Zh);
+        assert_eq!(Lang::from_locale("C.UTF-8"), Lang::Zh);
+        assert_eq!(Lang::from_locale("en_US.UTF-8"), Lang::En);
+        assert_eq!(Lang::from_locale("de_DE@euro"), Lang::En);
+    }
+
+    /// 测试消息的查找、填入参数和匹配已填入参数的消息
+    #[test]
+    fn test_translate() {
+        assert_eq!(Lang::Zh.tr("总行数"), "总行数");
+        assert_eq!(Lang::En.tr("总行数"), "Total lines");
+        assert_eq!(Lang::En.tr("没有译文的消息"), "没有译文的消息");
+
+        assert_eq!(Lang::En.format("{} 个文件", &[&3]), "3 files");
+        assert_eq!(Lang::Zh.format("{} 个文件", &[&3]), "3 个文件");
+
+        assert_eq!(
+            Lang::En.translate("文件不存在: 数据/a.txt"),
+            "File not found: 数据/a.txt"
+        );
+        assert_eq!(
+            Lang::En.translate("统计文件 'a.txt' 时发生错误"),
+            "Failed to count 'a.txt'"
+        );
+        assert_eq!(Lang::Zh.translate("文件不存在: a"), "文件不存在: a");
+        assert_eq!(Lang::En.translate("other: message"), "other: message");
+    }
+
+    /// 测试目录中的译文与原文的参数个数一致，且原文不重复
+    #[test]
+    fn test_catalog() {
+        for (index, (source, translated)) in EN.iter().enumerate() {
+            assert_eq!(
+                source.matches("{}").count(),
+                translated.matches("{}").count(),
+                "{}",
+                source
+            );
+            assert!(
+                EN[..index].iter().all(|(other, _)| other != source),
+                "重复的原文: {}",
+                source
+            );
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! 日志输出
+//!
+//! 状态提示、警告和调试信息通过 `tracing` 事件输出到标准错误，标准输出只保留统计结果。
+//! 输出的详细程度由全局选项控制：
+//!
+//! - 默认输出状态提示和警告，只有消息本身，没有级别和时间前缀
+//! - `-q`、`--quiet` 只输出错误
+//! - `--verbose` 同时输出每个文件的统计耗时、跳过文件的原因和缓存命中等调试信息
+//! - `--verbose --verbose` 再输出读取缓冲区大小、识别出的编码等跟踪信息
+//!
+//! 指定 `--verbose` 时每条日志带有级别前缀，便于区分调试信息和状态提示。
+//! `-v` 与 grep 一样表示 `--invert-match`，因此详细输出只有长选项。
+
+use tracing::level_filters::LevelFilter;
+
+/// 按命令行选项确定输出的最低日志级别
+///
+/// # 参数
+///
+/// * `verbose` - `--verbose` 出现的次数
+/// * `quiet` - 是否指定了 `--quiet`
+pub fn level(verbose: u8, quiet: bool) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::ERROR,
+        (false, 0) => LevelFilter::INFO,
+        (false, 1) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    }
+}
+
+/// 安装输出到标准错误的日志订阅器
+///
+/// 已经安装过订阅器时（如在同一进程中多次执行）保留原来的订阅器。
+///
+/// # 参数
+///
+/// * `level` - 输出的最低日志级别
+pub fn init(level: LevelFilter) {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .without_time()
+        .with_target(false)
+        .with_level(level > LevelFilter::INFO)
+        .try_init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试 `--verbose` 和 `--quiet` 对应的日志级别
+    #[test]
+    fn test_level() {
+        assert_eq!(level(0, false), LevelFilter::INFO);
+        assert_eq!(level(1, false), LevelFilter::DEBUG);
+        assert_eq!(level(3, false), LevelFilter::TRACE);
+        assert_eq!(level(2, true), LevelFilter::ERROR);
+    }
+}
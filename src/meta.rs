@@ -0,0 +1,102 @@
+//! 运行元数据
+//!
+//! 结构化报告的头部信息：工具版本、命令行、起止时间、主机名和配置哈希，
+//! 使归档的报告可以自我描述并用于复现。
+
+use serde::Serialize;
+use std::time::SystemTime;
+
+/// 单次运行的元数据
+#[derive(Debug, Clone, Serialize)]
+pub struct RunMetadata {
+    /// 工具名称
+    pub tool: &'static str,
+    /// 工具版本
+    pub version: &'static str,
+    /// 完整的命令行参数
+    pub command_line: Vec<String>,
+    /// 开始时间（RFC 3339，UTC）
+    pub started_at: String,
+    /// 结束时间（RFC 3339，UTC）
+    pub finished_at: String,
+    /// 运行耗时（毫秒）
+    pub duration_ms: u128,
+    /// 主机名
+    pub host: String,
+    /// 影响统计结果的配置的哈希值
+    pub config_hash: String,
+}
+
+impl RunMetadata {
+    /// 收集本次运行的元数据
+    ///
+    /// # 参数
+    ///
+    /// * `command_line` - 完整的命令行参数
+    /// * `config` - 影响统计结果的配置，用于计算配置哈希
+    /// * `started` - 开始时间
+    /// * `finished` - 结束时间
+    pub fn new<C: Serialize>(
+        command_line: Vec<String>,
+        config: &C,
+        started: SystemTime,
+        finished: SystemTime,
+    ) -> Self {
+        Self {
+            tool: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            command_line,
+            started_at: humantime::format_rfc3339_millis(started).to_string(),
+            finished_at: humantime::format_rfc3339_millis(finished).to_string(),
+            duration_ms: finished
+                .duration_since(started)
+                .unwrap_or_default()
+                .as_millis(),
+            host: gethostname::gethostname().to_string_lossy().into_owned(),
+            config_hash: config_hash(config),
+        }
+    }
+}
+
+/// 计算配置的哈希值
+///
+/// 先将配置序列化为 JSON，再计算 64 位 FNV-1a 哈希。
+/// 与标准库的 `DefaultHasher` 不同，FNV-1a 的结果在不同版本和平台间保持稳定。
+pub fn config_hash<C: Serialize>(config: &C) -> String {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let bytes = serde_json::to_vec(config).unwrap_or_default();
+    let hash = bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+
+    format!("fnv1a64:{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 测试配置哈希稳定且区分不同配置
+    #[test]
+    fn test_config_hash() {
+        assert_eq!(config_hash(&("a", 1)), config_hash(&("a", 1)));
+        assert_ne!(config_hash(&("a", 1)), config_hash(&("a", 2)));
+        assert_eq!(config_hash(&()).len(), "fnv1a64:".len() + 16);
+    }
+
+    /// 测试时间戳与耗时
+    #[test]
+    fn test_run_metadata_times() {
+        let started = SystemTime::UNIX_EPOCH;
+        let finished = started + Duration::from_millis(1500);
+        let meta = RunMetadata::new(vec!["line-counter".to_string()], &(), started, finished);
+
+        assert_eq!(meta.started_at, "1970-01-01T00:00:00.000Z");
+        assert_eq!(meta.finished_at, "1970-01-01T00:00:01.500Z");
+        assert_eq!(meta.duration_ms, 1500);
+        assert_eq!(meta.version, env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -0,0 +1,100 @@
+//! 内存映射统计
+//!
+//! 将文件映射到内存后直接在字节上统计行数，避免逐行分配 `String`。
+//! 大文件还可以在映射后分块并行统计。
+
+use crate::counter::{CountOptions, count_lines_in_slice, count_lines_in_slice_parallel};
+use crate::error::Result;
+use crate::stats::LineStats;
+use memmap2::Mmap;
+use std::fs::File;
+
+/// 使用内存映射统计文件行数
+///
+/// # 参数
+///
+/// * `file` - 已打开的文件
+/// * `options` - 统计选项
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 映射失败或内容不是有效的 UTF-8
+///
+/// # 安全性
+///
+/// 如果文件在映射期间被其他进程截断，读取映射内存可能导致进程收到 `SIGBUS`。
+/// 这与 `wc`、`ripgrep` 等工具使用内存映射时的风险相同。
+pub fn count_file_mmap(file: &File, options: &CountOptions) -> Result<LineStats> {
+    match map_file(file)? {
+        Some(mmap) => count_lines_in_slice(&mmap, options),
+        None => Ok(LineStats::default()),
+    }
+}
+
+/// 使用内存映射并分块并行统计文件行数
+///
+/// # 参数
+///
+/// * `file` - 已打开的文件
+/// * `options` - 统计选项
+/// * `chunks` - 分块数，通常为可用的 CPU 核心数
+///
+/// # 返回值
+///
+/// * `Ok(LineStats)` - 行数统计结果
+/// * `Err(LineCounterError)` - 映射失败或内容不是有效的 UTF-8
+///
+/// # 安全性
+///
+/// 与 [`count_file_mmap`] 相同。
+pub fn count_file_parallel(
+    file: &File,
+    options: &CountOptions,
+    chunks: usize,
+) -> Result<LineStats> {
+    match map_file(file)? {
+        Some(mmap) => count_lines_in_slice_parallel(&mmap, options, chunks),
+        None => Ok(LineStats::default()),
+    }
+}
+
+/// 只读映射文件；空文件无法映射，返回 `None`
+fn map_file(file: &File) -> Result<Option<Mmap>> {
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+
+    // SAFETY: 映射只读，且映射由调用方在统计结束后立即释放
+    let mmap = unsafe { Mmap::map(file)? };
+    Ok(Some(mmap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// 测试内存映射统计
+    #[test]
+    fn test_count_file_mmap() {
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file
+            .write_all("第一行\n\n第三行\n".as_bytes())
+            .unwrap();
+
+        let stats = count_file_mmap(temp_file.as_file(), &CountOptions::default()).unwrap();
+        assert_eq!(stats, LineStats::new(3, 2, 1));
+    }
+
+    /// 测试空文件
+    #[test]
+    fn test_count_file_mmap_empty() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let stats = count_file_mmap(temp_file.as_file(), &CountOptions::default()).unwrap();
+        assert_eq!(stats, LineStats::default());
+
+        let stats = count_file_parallel(temp_file.as_file(), &CountOptions::default(), 4).unwrap();
+        assert_eq!(stats, LineStats::default());
+    }
+}
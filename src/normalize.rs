@@ -0,0 +1,151 @@
+//! 行内容规范化
+//!
+//! 在比较或哈希行内容之前对行做规范化，使视觉上相同的行被视为同一行：
+//!
+//! - `nfc`：Unicode NFC 规范化（组合字符与预组合字符视为相同，如 `e\u{301}` 与 `é`）
+//! - `trim`：去除行首尾的空白字符
+//! - `casefold`：Unicode 大小写折叠（如 `Straße` 与 `STRASSE`）
+//!
+//! 命令行中以逗号分隔的列表指定，例如 `--normalize nfc,trim`；`all` 表示全部启用。
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::borrow::Cow;
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
+
+/// 规范化选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Normalization {
+    /// Unicode NFC 规范化
+    pub nfc: bool,
+    /// 去除行首尾的空白字符
+    pub trim: bool,
+    /// Unicode 大小写折叠
+    pub case_fold: bool,
+}
+
+impl Normalization {
+    /// 启用全部规范化
+    pub const ALL: Normalization = Normalization {
+        nfc: true,
+        trim: true,
+        case_fold: true,
+    };
+
+    /// 是否不做任何规范化
+    pub fn is_identity(&self) -> bool {
+        *self == Normalization::default()
+    }
+
+    /// 规范化一行内容
+    ///
+    /// 依次去除首尾空白、大小写折叠、NFC 规范化。大小写折叠可能产生非 NFC 的序列，
+    /// 因此 NFC 放在最后。内容无需改变时不分配内存。
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 行内容（不含换行符）
+    ///
+    /// # 返回值
+    ///
+    /// 规范化后的行内容
+    pub fn apply<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(if self.trim { line.trim() } else { line });
+
+        if self.case_fold && !line.is_ascii() {
+            line = Cow::Owned(caseless::default_case_fold_str(&line));
+        } else if self.case_fold && line.bytes().any(|b| b.is_ascii_uppercase()) {
+            line = Cow::Owned(line.to_ascii_lowercase());
+        }
+
+        if self.nfc && is_nfc_quick(line.chars()) != IsNormalized::Yes {
+            line = Cow::Owned(line.nfc().collect());
+        }
+
+        line
+    }
+}
+
+/// 解析规范化列表
+///
+/// # 参数
+///
+/// * `value` - 逗号分隔的规范化名称：`nfc`、`trim`、`casefold`，或 `all`
+///
+/// # 返回值
+///
+/// * `Ok(Normalization)` - 规范化选项
+/// * `Err(String)` - 包含无法识别的名称，供 `clap` 显示
+pub fn parse_list(value: &str) -> Result<Normalization, String> {
+    let mut normalization = Normalization::default();
+    for name in value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        match name.to_ascii_lowercase().as_str() {
+            "nfc" => normalization.nfc = true,
+            "trim" => normalization.trim = true,
+            "casefold" | "case-fold" => normalization.case_fold = true,
+            "all" => normalization = Normalization::ALL,
+            _ => {
+                return Err(format!(
+                    "无法识别的规范化方式: '{}'（支持 nfc、trim、casefold、all）",
+                    name
+                ));
+            }
+        }
+    }
+    Ok(normalization)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试规范化列表的解析
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(parse_list(""), Ok(Normalization::default()));
+        assert_eq!(
+            parse_list("nfc, trim"),
+            Ok(Normalization {
+                nfc: true,
+                trim: true,
+                case_fold: false,
+            })
+        );
+        assert!(parse_list("CaseFold").unwrap().case_fold);
+        assert_eq!(parse_list("all"), Ok(Normalization::ALL));
+        assert!(parse_list("nfc,nfkc").is_err());
+    }
+
+    /// 测试各种规范化方式
+    #[test]
+    fn test_apply() {
+        let identity = Normalization::default();
+        assert!(identity.is_identity());
+        assert!(matches!(identity.apply("  Abc "), Cow::Borrowed("  Abc ")));
+
+        let nfc = parse_list("nfc").unwrap();
+        assert_eq!(nfc.apply("cafe\u{301}"), "café");
+        assert!(matches!(nfc.apply("café"), Cow::Borrowed(_)));
+
+        let trim = parse_list("trim").unwrap();
+        assert_eq!(trim.apply("\t中文 \u{3000}"), "中文");
+
+        let case_fold = parse_list("casefold").unwrap();
+        assert_eq!(case_fold.apply("Hello"), "hello");
+        assert_eq!(case_fold.apply("STRASSE"), case_fold.apply("Straße"));
+        assert!(matches!(case_fold.apply("hello"), Cow::Borrowed(_)));
+    }
+
+    /// 测试组合使用时视觉上相同的行规范化结果一致
+    #[test]
+    fn test_apply_all() {
+        let all = Normalization::ALL;
+        assert_eq!(all.apply("  E\u{301}COLE "), all.apply("école"));
+        assert_ne!(all.apply("ecole"), all.apply("école"));
+    }
+}
@@ -0,0 +1,2464 @@
+//! 结果输出
+//!
+//! 将统计报告渲染为不同的输出格式：
+//!
+//! - 可读文本：单个文件输出详细结果，多个文件先逐个输出简要结果再输出汇总
+//! - JSON：包含运行元数据（`meta`）、每个文件的结果（`files`）和汇总（`summary`），
+//!   指定 `--languages` 时还包含语言分布（`languages`），指定 `--group-by` 时还包含分组汇总（`groups`）
+//! - Markdown：GitHub 风格的表格，每个文件一行，多文件时最后一行为合计；
+//!   指定 `--group-by` 或 `--languages` 时在后面追加相应的表格
+//! - 徽章：shields.io 端点徽章的 JSON，显示简写的总行数
+//! - `key=value`：每行一个指标，键名稳定，不包含耗时，供 shell 脚本使用
+//!
+//! 差异模式的比较结果 [`StatsDiff`] 和目录比较的结果 [`DirDiff`] 同样支持这三种格式。
+//! 基线比较结果只有文本格式，JSON 和 Markdown 模式下输出到标准错误，保证标准输出可以直接使用。
+//!
+//! 可读文本的外观由 [`Style`] 控制：`--accessible` 面向屏幕阅读器，不输出表情符号、
+//! 对齐用的空白和缩进，指标使用完整的词语（如“字节”而不是 `bytes`），每行按顺序读出即可理解。
+//! `--no-emoji` 只去掉表情符号（检查结果的标记换成文字），区域设置不是 UTF-8 时自动生效。
+//! 终端支持时，文件路径渲染为可点击的超链接（见 [`hyperlink`](crate::hyperlink)），
+//! 标题、警告和超过阈值的数值使用颜色突出显示（见 [`color`](crate::color)）。
+
+use crate::baseline::BaselineCheck;
+use crate::color::{Role, Theme};
+use crate::compare::{DirDiff, FileStatus};
+use crate::diff::StatsDiff;
+use crate::git::{AuthorShare, FileChange, LineChanges, RevisionDiff};
+use crate::group::{self, Group, GroupBy, Grouping};
+use crate::hyperlink::Hyperlinks;
+use crate::json::JsonKind;
+use crate::language::{self, LanguageShare};
+use crate::locale::Lang;
+use crate::meta::RunMetadata;
+use crate::report::{FileReport, Summary};
+use crate::stats::{LineEnding, LineStats, LongLine, TodoItem};
+use crate::tags::TagRollup;
+use crate::threshold::{Metric, Violation};
+use crate::units;
+use serde::Serialize;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+use tracing::warn;
+
+/// 可读文本和 Markdown 中百分比默认保留的小数位数
+pub const DEFAULT_PRECISION: usize = 1;
+
+/// `key=value` 指标中百分比默认保留的小数位数
+pub const PLAIN_KV_PRECISION: usize = 2;
+
+/// 可读文本的输出风格
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Style {
+    /// 是否在每行开头输出表情符号
+    pub emoji: bool,
+    /// 无障碍模式：不对齐、不缩进，指标使用完整的词语
+    pub accessible: bool,
+    /// 把文件路径渲染为终端超链接，`None` 表示不输出超链接
+    pub hyperlinks: Option<Hyperlinks>,
+    /// 标题、警告等文本的颜色，`None` 表示不输出颜色
+    pub colors: Option<Theme>,
+    /// 大小按 1024 进制换算，较大的数量简写（`--human`）
+    pub human: bool,
+    /// 百分比保留的小数位数（`--precision`）
+    pub precision: usize,
+    /// 输出的语言
+    pub lang: Lang,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            emoji: true,
+            accessible: false,
+            hyperlinks: None,
+            colors: None,
+            human: false,
+            precision: DEFAULT_PRECISION,
+            lang: Lang::Zh,
+        }
+    }
+}
+
+impl Style {
+    /// 根据命令行选项构造风格
+    ///
+    /// # 参数
+    ///
+    /// * `accessible` - 是否面向屏幕阅读器（`--accessible`）
+    /// * `no_emoji` - 是否不输出表情符号（`--no-emoji`）
+    /// * `lang` - 输出语言
+    ///
+    /// # 返回值
+    ///
+    /// 不输出超链接和颜色的风格；区域设置（`LC_ALL`、`LC_CTYPE`、`LANG` 中第一个非空的值）
+    /// 不是 UTF-8 时同样不输出表情符号
+    pub fn from_options(accessible: bool, no_emoji: bool, lang: Lang) -> Self {
+        let style = if accessible {
+            Self::accessible()
+        } else {
+            Self {
+                emoji: !no_emoji && locale_is_utf8(),
+                ..Self::default()
+            }
+        };
+        Self { lang, ..style }
+    }
+
+    /// 面向屏幕阅读器的风格（`--accessible`）
+    pub fn accessible() -> Self {
+        Self {
+            emoji: false,
+            accessible: true,
+            hyperlinks: None,
+            colors: None,
+            human: false,
+            precision: DEFAULT_PRECISION,
+            lang: Lang::Zh,
+        }
+    }
+
+    /// 行首的表情符号
+    ///
+    /// # 参数
+    ///
+    /// * `icon` - 表情符号及其后的空格（如 `"📊 "`）
+    ///
+    /// # 返回值
+    ///
+    /// 启用表情符号时原样返回，否则为空字符串
+    pub fn icon(&self, icon: &'static str) -> &'static str {
+        if self.emoji { icon } else { "" }
+    }
+
+    /// 指向文件的文本，启用超链接时渲染为链接
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 显示的文本
+    /// * `path` - 文件路径
+    /// * `line` - 行号，`None` 表示整个文件
+    pub fn link(&self, text: impl Display, path: &Path, line: Option<usize>) -> String {
+        match &self.hyperlinks {
+            Some(hyperlinks) => hyperlinks.link(text, path, line),
+            None => text.to_string(),
+        }
+    }
+
+    /// 按用途着色的文本，不输出颜色时为原文本
+    ///
+    /// # 参数
+    ///
+    /// * `role` - 文本的用途
+    /// * `text` - 文本
+    pub fn paint(&self, role: Role, text: impl Display) -> String {
+        match &self.colors {
+            Some(theme) => theme.paint(role, text),
+            None => text.to_string(),
+        }
+    }
+
+    /// 按输出语言翻译的文本
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 中文原文
+    pub fn tr(&self, text: &'static str) -> &'static str {
+        self.lang.tr(text)
+    }
+
+    /// 按输出语言翻译并填入参数的文本
+    ///
+    /// # 参数
+    ///
+    /// * `template` - 中文原文，用 `{}` 标出参数的位置
+    /// * `args` - 按顺序填入的参数
+    pub fn format(&self, template: &'static str, args: &[&dyn Display]) -> String {
+        self.lang.format(template, args)
+    }
+
+    /// 括号中的补充说明，多项之间用逗号分隔，如 `（主要为空格，混用）`
+    fn notes(&self, notes: &[String]) -> String {
+        self.format("（{}）", &[&notes.join(self.tr("，"))])
+    }
+
+    /// 文件路径，启用超链接时渲染为指向该文件的链接
+    fn path(&self, path: &Path) -> String {
+        self.link(path.display(), path, None)
+    }
+
+    /// 报告中的文件，符号链接后面附上链接指向的文件
+    fn file(&self, report: &FileReport) -> String {
+        match &report.symlink_target {
+            Some(target) if self.accessible => self.format(
+                "{}（符号链接，指向 {}）",
+                &[&self.path(&report.path), &target.display()],
+            ),
+            Some(target) => format!("{} → {}", self.path(&report.path), target.display()),
+            None => self.path(&report.path),
+        }
+    }
+
+    /// 子项的缩进
+    fn indent(&self) -> &'static str {
+        if self.accessible { "" } else { "   " }
+    }
+
+    /// 数量，启用 `--human` 时带千位分隔符或简写为 `12.3k` 的形式
+    fn count(&self, count: usize) -> String {
+        if self.human {
+            units::format_count(count as u64)
+        } else {
+            count.to_string()
+        }
+    }
+
+    /// 按 `--precision` 保留小数位数的百分比数值（不带 `%`）
+    fn percent(&self, value: f64) -> String {
+        format!("{:.*}", self.precision, value)
+    }
+
+    /// 对齐百分比数值的列宽，能容纳 `100` 及其小数部分
+    fn percent_width(&self) -> usize {
+        match self.precision {
+            0 => 3,
+            precision => precision + 4,
+        }
+    }
+
+    /// 带单位的字节数，启用 `--human` 时换算为 `1.4 MiB` 的形式
+    fn size(&self, bytes: u64) -> String {
+        if self.human {
+            units::format_size(bytes)
+        } else {
+            format!("{} {}", bytes, self.bytes())
+        }
+    }
+
+    /// 字节数的单位
+    fn bytes(&self) -> &'static str {
+        if self.accessible {
+            self.tr("字节")
+        } else {
+            "bytes"
+        }
+    }
+
+    /// 检查结果的标记，按结果着色；不输出表情符号时为文字
+    fn verdict(&self, failed: bool) -> String {
+        let (mark, role) = match (!self.emoji, failed) {
+            (false, true) => ("❌ ", Role::Error),
+            (false, false) => ("✅ ", Role::Success),
+            (true, true) => (self.tr("未通过: "), Role::Error),
+            (true, false) => (self.tr("通过: "), Role::Success),
+        };
+        self.paint(role, mark)
+    }
+}
+
+/// 区域设置是否为 UTF-8
+///
+/// 依次查看 `LC_ALL`、`LC_CTYPE` 和 `LANG`，使用第一个非空的值；都未设置时视为 UTF-8。
+fn locale_is_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .is_none_or(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+/// JSON 报告结构
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    meta: &'a RunMetadata,
+    files: &'a [FileReport],
+    summary: Summary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    languages: Option<Vec<LanguageShare>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<Vec<Group>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authors: Option<&'a [AuthorShare]>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    incomplete: bool,
+}
+
+/// shields.io 端点徽章（<https://shields.io/badges/endpoint-badge>）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Badge {
+    schema_version: u8,
+    label: &'static str,
+    message: String,
+    color: &'static str,
+}
+
+/// 渲染 shields.io 端点徽章的 JSON
+///
+/// # 参数
+///
+/// * `summary` - 统计结果的汇总
+///
+/// # 返回值
+///
+/// * `Ok(String)` - 单行 JSON，`message` 为简写的总行数（如 `12.3k`），结果不完整时带有 `+` 后缀
+/// * `Err(serde_json::Error)` - 序列化失败
+pub fn render_badge(summary: &Summary) -> serde_json::Result<String> {
+    let mut message = units::format_compact(summary.stats.total_lines as u64);
+    if summary.is_incomplete() {
+        message.push('+');
+    }
+    serde_json::to_string(&Badge {
+        schema_version: 1,
+        label: "lines",
+        message,
+        color: "blue",
+    })
+}
+
+/// 渲染单个文件的 `key=value` 指标
+///
+/// 键为 `file.<序号>.<指标>`，序号从 1 开始，按输出顺序排列。`status` 为 `ok` 或跳过的原因
+/// （`binary`、`missing`、`limit-exceeded`、`timed-out`、`error`）；`matched_lines` 只在指定 `--match` 时输出，
+/// `invalid_utf8_lines` 只在指定 `--lossy` 时输出，`max_line_bytes` 只在指定 `--bytes-mode` 时输出，
+/// 待办标记总数 `todos` 只在指定 `--todos` 时输出，`comment_lines` 和 `code_lines` 只在指定 `--comments`
+/// 且文件的语言有注释语法时输出，`logical_lines` 和 `continued_lines` 只在指定 `--logical`
+/// 且文件的语言有续行规则时输出，`words` 和 `reading_minutes` 只在指定 `--reading-time` 且文件为散文类时输出，
+/// `tokens` 只在指定 `--tokens` 时输出，
+/// `csv_records`、`csv_columns`、`csv_inconsistent_records` 和 `csv_empty_fields` 只在文件按 CSV 或 TSV
+/// 统计记录时输出，`jsonl_records`、`jsonl_invalid_lines` 和 `jsonl_empty_lines` 只在按 `--mode jsonl` 统计时输出，
+/// `json_valid` 和 `json_array_length`（顶层为数组时）只在按 `--mode json` 检查时输出，
+/// `unstable` 只在文件在统计过程中被修改时输出。
+///
+/// # 参数
+///
+/// * `index` - 文件的序号（从 1 开始）
+/// * `report` - 单个文件的统计报告
+///
+/// # 返回值
+///
+/// 每行一个指标的文本，以换行符结尾
+pub fn render_plain_kv_file(index: usize, report: &FileReport) -> String {
+    let status = if report.binary {
+        "binary"
+    } else if report.missing {
+        "missing"
+    } else if report.limit_exceeded.is_some() {
+        "limit-exceeded"
+    } else if report.timed_out {
+        "timed-out"
+    } else if report.error.is_some() {
+        "error"
+    } else {
+        "ok"
+    };
+    let mut out = String::new();
+    let mut push = |key: &str, value: &dyn Display| {
+        out.push_str(&format!("file.{}.{}={}\n", index, key, value));
+    };
+    push("path", &plain_kv_escape(&report.path.display().to_string()));
+    push("status", &status);
+    push("bytes", &report.size);
+    push("total_lines", &report.stats.total_lines);
+    push("non_empty_lines", &report.stats.non_empty_lines);
+    push("empty_lines", &report.stats.empty_lines);
+    if let Some(matched) = report.stats.matched_lines {
+        push("matched_lines", &matched);
+    }
+    if let Some(invalid) = report.stats.invalid_utf8_lines {
+        push("invalid_utf8_lines", &invalid);
+    }
+    if let Some(bytes) = report.stats.max_line_bytes {
+        push("max_line_bytes", &bytes);
+    }
+    if let Some(todos) = &report.stats.todos {
+        push("todos", &todos.total());
+    }
+    if let Some(comments) = report.stats.comments {
+        push("comment_lines", &comments.comment_lines);
+        push("code_lines", &comments.code_lines);
+    }
+    if let Some(logical) = report.stats.logical {
+        push("logical_lines", &logical.logical_lines);
+        push("continued_lines", &logical.continued_lines());
+    }
+    if let Some(csv) = &report.stats.csv {
+        push("csv_records", &csv.records);
+        push("csv_columns", &csv.columns);
+        push("csv_inconsistent_records", &csv.inconsistent_records);
+        push("csv_empty_fields", &csv.empty_fields);
+    }
+    if let Some(jsonl) = &report.stats.jsonl {
+        push("jsonl_records", &jsonl.records);
+        push("jsonl_invalid_lines", &jsonl.invalid_lines);
+        push("jsonl_empty_lines", &jsonl.empty_lines);
+    }
+    if let Some(json) = &report.stats.json {
+        push("json_valid", &json.is_valid());
+        if let Some(length) = json.array_length {
+            push("json_array_length", &length);
+        }
+    }
+    if let Some(words) = report.stats.words {
+        push("words", &words);
+    }
+    if let Some(tokens) = report.stats.tokens {
+        push("tokens", &tokens.tokens);
+    }
+    if let Some(minutes) = report.reading_minutes {
+        push(
+            "reading_minutes",
+            &format!("{:.*}", PLAIN_KV_PRECISION, minutes),
+        );
+    }
+    if report.unstable {
+        push("unstable", &true);
+    }
+    out
+}
+
+/// 渲染汇总的 `key=value` 指标
+///
+/// 键为 `summary.<指标>`；不包含耗时等每次运行都不同的值，相同的输入总是得到相同的输出。
+///
+/// # 参数
+///
+/// * `summary` - 统计结果的汇总
+/// * `precision` - 百分比保留的小数位数
+///
+/// # 返回值
+///
+/// 每行一个指标的文本，以换行符结尾
+pub fn render_plain_kv_summary(summary: &Summary, precision: usize) -> String {
+    let mut out = String::new();
+    let mut push = |key: &str, value: &dyn Display| {
+        out.push_str(&format!("summary.{}={}\n", key, value));
+    };
+    push("files", &summary.files);
+    push("bytes", &summary.bytes);
+    push("skipped", &summary.skipped);
+    push("missing", &summary.missing);
+    push("limit_exceeded", &summary.limit_exceeded);
+    push("timed_out", &summary.timed_out);
+    push("failed", &summary.failed);
+    push("incomplete", &summary.is_incomplete());
+    push("total_lines", &summary.stats.total_lines);
+    push("non_empty_lines", &summary.stats.non_empty_lines);
+    push("empty_lines", &summary.stats.empty_lines);
+    push(
+        "empty_percentage",
+        &format!("{:.*}", precision, summary.empty_percentage),
+    );
+    push(
+        "non_empty_percentage",
+        &format!("{:.*}", precision, summary.non_empty_percentage),
+    );
+    if let Some(matched) = summary.stats.matched_lines {
+        push("matched_lines", &matched);
+    }
+    if let Some(invalid) = summary.stats.invalid_utf8_lines {
+        push("invalid_utf8_lines", &invalid);
+    }
+    if let Some(bytes) = summary.stats.max_line_bytes {
+        push("max_line_bytes", &bytes);
+    }
+    if let Some(todos) = &summary.stats.todos {
+        push("todos", &todos.total());
+    }
+    if let Some(comments) = summary.stats.comments {
+        push("comment_lines", &comments.comment_lines);
+        push("code_lines", &comments.code_lines);
+    }
+    if let Some(logical) = summary.stats.logical {
+        push("logical_lines", &logical.logical_lines);
+        push("continued_lines", &logical.continued_lines());
+    }
+    if let Some(csv) = &summary.stats.csv {
+        push("csv_records", &csv.records);
+        push("csv_columns", &csv.columns);
+        push("csv_inconsistent_records", &csv.inconsistent_records);
+        push("csv_empty_fields", &csv.empty_fields);
+    }
+    if let Some(jsonl) = &summary.stats.jsonl {
+        push("jsonl_records", &jsonl.records);
+        push("jsonl_invalid_lines", &jsonl.invalid_lines);
+        push("jsonl_empty_lines", &jsonl.empty_lines);
+    }
+    if let Some(json) = &summary.stats.json {
+        push("json_documents", &json.documents);
+        push("json_invalid_documents", &json.invalid_documents);
+        if let Some(length) = json.array_length {
+            push("json_array_length", &length);
+        }
+    }
+    if let Some(words) = summary.stats.words {
+        push("words", &words);
+    }
+    if let Some(tokens) = summary.stats.tokens {
+        push("tokens", &tokens.tokens);
+    }
+    if let Some(minutes) = summary.reading_minutes {
+        push("reading_minutes", &format!("{:.*}", precision, minutes));
+    }
+    out
+}
+
+/// 转义 `key=value` 中的值，保证每个指标只占一行（`\` 和换行符）
+fn plain_kv_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// 渲染 JSON 报告
+///
+/// # 参数
+///
+/// * `meta` - 运行元数据
+/// * `reports` - 按输入顺序排列的统计报告
+/// * `languages` - 是否包含语言分布
+/// * `grouping` - 分组汇总的设置，`None` 表示不包含分组汇总
+/// * `authors` - 按作者统计的行数，`None` 表示不包含
+///
+/// # 返回值
+///
+/// * `Ok(String)` - 格式化的 JSON 文本
+/// * `Err(serde_json::Error)` - 序列化失败（例如路径不是有效的 UTF-8）
+pub fn render_json(
+    meta: &RunMetadata,
+    reports: &[FileReport],
+    languages: bool,
+    grouping: Option<&Grouping>,
+    authors: Option<&[AuthorShare]>,
+) -> serde_json::Result<String> {
+    let summary = Summary::from_reports(reports);
+    serde_json::to_string_pretty(&JsonReport {
+        meta,
+        files: reports,
+        incomplete: summary.is_incomplete(),
+        summary,
+        languages: languages.then(|| language::breakdown(reports)),
+        groups: grouping.map(|grouping| group::group(reports, grouping)),
+        authors,
+    })
+}
+
+/// 以可读文本格式输出统计结果
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `reports` - 按输入顺序排列的统计报告
+/// * `elapsed` - 本次统计的耗时，在多文件的汇总中输出
+/// * `style` - 输出风格
+pub fn write_human(
+    out: &mut dyn Write,
+    reports: &[FileReport],
+    elapsed: Duration,
+    style: &Style,
+) -> io::Result<()> {
+    match reports {
+        [report] => write_single_file(out, report, style),
+        _ => write_multi_file(out, reports, elapsed, style),
+    }
+}
+
+/// 打印文件分析结果
+///
+/// 输出格式化的分析结果，包括文件信息和行数统计。
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `report` - 单个文件的统计报告
+/// * `style` - 输出风格
+fn write_single_file(out: &mut dyn Write, report: &FileReport, style: &Style) -> io::Result<()> {
+    if report.is_skipped() {
+        return write_skipped(out, report, style);
+    }
+
+    let line_stats = &report.stats;
+
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("✅ "),
+        style.paint(Role::Heading, style.tr("文件分析完成!"))
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("📄 "),
+        style.tr("文件"),
+        style.file(report)
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("📏 "),
+        style.tr("文件大小"),
+        style.size(report.size)
+    )?;
+    if report.unstable {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("⚠️  "),
+            style.paint(
+                Role::Warning,
+                style.tr("统计过程中文件被修改，结果可能不准确")
+            )
+        )?;
+    }
+    match report.encoding {
+        Some(encoding) if report.bom => writeln!(
+            out,
+            "{}{}",
+            style.icon("🔤 "),
+            style.format("编码: {}（带 BOM）", &[&encoding])
+        )?,
+        Some(encoding) if encoding != "UTF-8" => writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("🔤 "),
+            style.tr("编码"),
+            encoding
+        )?,
+        _ => {}
+    }
+    if let Some(compression) = report.compression {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("🗜️  "),
+            style.tr("压缩格式"),
+            compression
+        )?;
+    }
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("📊 "),
+        style.tr("总行数"),
+        style.count(line_stats.total_lines)
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("📝 "),
+        style.tr("非空行数"),
+        style.count(line_stats.non_empty_lines)
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("🔲 "),
+        style.tr("空行数"),
+        style.count(line_stats.empty_lines)
+    )?;
+
+    if line_stats.total_lines > 0 {
+        writeln!(
+            out,
+            "{}{}: {}%",
+            style.icon("📈 "),
+            style.tr("空行占比"),
+            style.percent(line_stats.empty_percentage())
+        )?;
+        writeln!(
+            out,
+            "{}{}: {}%",
+            style.icon("📈 "),
+            style.tr("非空行占比"),
+            style.percent(line_stats.non_empty_percentage())
+        )?;
+    }
+    if let (Some(minutes), Some(words)) = (report.reading_minutes, line_stats.words) {
+        write_reading_time(out, minutes, words, style)?;
+    }
+
+    write_header_stats(out, line_stats, style)
+}
+
+/// 打印估计的阅读时间（向上取整到分钟）
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `minutes` - 阅读时间（分钟）
+/// * `words` - 单词数
+/// * `style` - 输出风格
+fn write_reading_time(
+    out: &mut dyn Write,
+    minutes: f64,
+    words: usize,
+    style: &Style,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("📖 "),
+        style.format(
+            "阅读时间: 约 {} 分钟（{} 个单词）",
+            &[&(minutes.ceil() as u64), &style.count(words)]
+        )
+    )
+}
+
+/// 打印结构化记录统计：CSV/TSV 的记录数、JSON Lines 的记录数和 JSON 文档的检查结果（仅在存在时输出）
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `line_stats` - 行数统计结果
+/// * `style` - 输出风格
+fn write_records(out: &mut dyn Write, line_stats: &LineStats, style: &Style) -> io::Result<()> {
+    if let Some(csv) = &line_stats.csv {
+        writeln!(
+            out,
+            "{}{}{}",
+            style.icon("🧾 "),
+            style.format(
+                "记录数: {}, 列数: {}, 空字段: {}",
+                &[
+                    &style.count(csv.records),
+                    &csv.columns,
+                    &style.count(csv.empty_fields)
+                ]
+            ),
+            if csv.unterminated_quote {
+                style.notes(&[style.tr("引号没有闭合").to_string()])
+            } else {
+                String::new()
+            }
+        )?;
+        if csv.inconsistent_records > 0 {
+            writeln!(
+                out,
+                "{}{}{}",
+                style.indent(),
+                style.paint(
+                    Role::Warning,
+                    style.format(
+                        "列数不一致的记录: {}",
+                        &[&style.count(csv.inconsistent_records)]
+                    )
+                ),
+                line_numbers_note(&csv.inconsistent_lines, csv.inconsistent_records, style)
+            )?;
+        }
+    }
+    if let Some(jsonl) = &line_stats.jsonl {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("🧾 "),
+            style.format(
+                "JSON 记录数: {}, 空行数: {}",
+                &[&style.count(jsonl.records), &style.count(jsonl.empty_lines)]
+            )
+        )?;
+        if jsonl.invalid_lines > 0 {
+            writeln!(
+                out,
+                "{}{}{}",
+                style.indent(),
+                style.paint(
+                    Role::Warning,
+                    style.format("无效的 JSON 行: {}", &[&style.count(jsonl.invalid_lines)])
+                ),
+                line_numbers_note(&jsonl.invalid_line_numbers, jsonl.invalid_lines, style)
+            )?;
+        }
+    }
+    if let Some(json) = &line_stats.json {
+        if json.documents > 1 {
+            writeln!(
+                out,
+                "{}{}{}",
+                style.icon("🧾 "),
+                style.format(
+                    "JSON 文档: {} 个, 无效 {} 个",
+                    &[&json.documents, &json.invalid_documents]
+                ),
+                json.array_length
+                    .map(|length| style
+                        .notes(&[style.format("顶层数组共 {} 个元素", &[&style.count(length)])]))
+                    .unwrap_or_default()
+            )?;
+        } else if let Some(kind) = json.kind
+            && json.is_valid()
+        {
+            writeln!(
+                out,
+                "{}{}{}",
+                style.icon("🧾 "),
+                style.format(
+                    "JSON 文档: 合法, 顶层为{}",
+                    &[&style.tr(json_kind_name(kind))]
+                ),
+                json.array_length
+                    .map(|length| style
+                        .notes(&[style.format("{} 个元素", &[&style.count(length)])]))
+                    .unwrap_or_default()
+            )?;
+        } else {
+            writeln!(
+                out,
+                "{}{}{}",
+                style.icon("🧾 "),
+                style.paint(Role::Warning, style.tr("JSON 文档: 不是单个合法文档")),
+                json.error_line
+                    .map(|line| style.notes(&[style.format("第 {} 行", &[&line])]))
+                    .unwrap_or_default()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 问题行的行号说明，如“（行 4, 5, …）”
+///
+/// # 参数
+///
+/// * `lines` - 记录的前若干个行号
+/// * `total` - 问题行总数
+/// * `style` - 输出风格
+///
+/// # 返回值
+///
+/// 没有记录行号（如多文件汇总）时为空字符串
+fn line_numbers_note(lines: &[usize], total: usize, style: &Style) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut numbers: Vec<String> = lines.iter().map(ToString::to_string).collect();
+    if numbers.len() < total {
+        numbers.push("…".to_string());
+    }
+    style.notes(&[style.format("行 {}", &[&numbers.join(", ")])])
+}
+
+/// 多文件列表中每个文件的结构化记录摘要，如“, 记录数 120”
+///
+/// # 参数
+///
+/// * `line_stats` - 行数统计结果
+/// * `style` - 输出风格
+///
+/// # 返回值
+///
+/// 没有按记录格式统计时为空字符串
+fn record_summary(line_stats: &LineStats, style: &Style) -> String {
+    let records = line_stats
+        .csv
+        .as_ref()
+        .map(|csv| csv.records)
+        .or(line_stats.jsonl.as_ref().map(|jsonl| jsonl.records));
+    if let Some(records) = records {
+        return style.format(", 记录数 {}", &[&style.count(records)]);
+    }
+    match &line_stats.json {
+        Some(json) if !json.is_valid() => style.paint(Role::Warning, style.tr(", 不是合法的 JSON")),
+        Some(json) => json
+            .array_length
+            .map(|length| style.format(", 数组元素 {}", &[&style.count(length)]))
+            .unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
+/// JSON 值类型的中文名称
+fn json_kind_name(kind: JsonKind) -> &'static str {
+    match kind {
+        JsonKind::Object => "对象",
+        JsonKind::Array => "数组",
+        JsonKind::String => "字符串",
+        JsonKind::Number => "数字",
+        JsonKind::Boolean => "布尔值",
+        JsonKind::Null => "null",
+    }
+}
+
+/// 打印 shebang 和许可证头行数、行尾空白行数、匹配行数、待办标记数、注释行数、逻辑行数、token 数、结构化记录、最长行长度以及重复行、高频单词、缩进和换行符统计（仅在存在时输出）
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `line_stats` - 行数统计结果
+/// * `style` - 输出风格
+fn write_header_stats(
+    out: &mut dyn Write,
+    line_stats: &LineStats,
+    style: &Style,
+) -> io::Result<()> {
+    if line_stats.shebang_lines > 0 {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("🔖 "),
+            style.tr("Shebang 行数"),
+            line_stats.shebang_lines
+        )?;
+    }
+    if line_stats.license_lines > 0 {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("📜 "),
+            style.tr("许可证头行数"),
+            line_stats.license_lines
+        )?;
+    }
+    if line_stats.trailing_whitespace_lines > 0 {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("🧹 "),
+            style.tr("行尾空白行数"),
+            line_stats.trailing_whitespace_lines
+        )?;
+    }
+    if let Some(matched) = line_stats.matched_lines {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("🔍 "),
+            style.tr("匹配行数"),
+            matched
+        )?;
+    }
+    if let Some(invalid) = line_stats.invalid_utf8_lines {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("🧩 "),
+            style.tr("含无效 UTF-8 的行数"),
+            invalid
+        )?;
+    }
+    if let Some(todos) = &line_stats.todos {
+        let counts: Vec<String> = todos
+            .counts
+            .iter()
+            .map(|(marker, count)| format!("{} {}", marker, count))
+            .collect();
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("📌 "),
+            style.format("待办标记: {}（{}）", &[&todos.total(), &counts.join(", ")])
+        )?;
+    }
+    if let Some(comments) = line_stats.comments {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("💬 "),
+            style.format(
+                "注释行数: {}, 代码行数: {}",
+                &[&comments.comment_lines, &comments.code_lines]
+            )
+        )?;
+    }
+    if let Some(logical) = line_stats.logical {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("🔗 "),
+            style.format(
+                "逻辑行数: {}（物理行数 {}, 合并续行 {} 行）",
+                &[
+                    &style.count(logical.logical_lines),
+                    &style.count(logical.physical_lines),
+                    &style.count(logical.continued_lines())
+                ]
+            )
+        )?;
+    }
+    if let Some(tokens) = line_stats.tokens {
+        writeln!(
+            out,
+            "{}{}{}",
+            style.icon("🪙 "),
+            style.format("Token 数: {}", &[&style.count(tokens.tokens)]),
+            if tokens.estimated {
+                style.notes(&[style.tr("按字符类别估计").to_string()])
+            } else {
+                String::new()
+            }
+        )?;
+    }
+    write_records(out, line_stats, style)?;
+    if let Some(length) = line_stats.max_line_length {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("📐 "),
+            style.format(
+                "最长行: {} {}, {} 字符, {} 列",
+                &[&length.bytes, &style.bytes(), &length.chars, &length.cells]
+            )
+        )?;
+    }
+    if let Some(bytes) = line_stats.max_line_bytes {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("📐 "),
+            style.format("最长行: {} {}", &[&bytes, &style.bytes()])
+        )?;
+    }
+    if let Some(distribution) = &line_stats.line_length_distribution
+        && let Some(median) = distribution.median()
+    {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("📐 "),
+            style.format(
+                "行长度: 中位数 {} 列, P90 {} 列, P99 {} 列",
+                &[
+                    &median,
+                    &distribution.percentile(90.0).unwrap_or(median),
+                    &distribution.percentile(99.0).unwrap_or(median)
+                ]
+            )
+        )?;
+    }
+    if let Some(duplicates) = &line_stats.duplicates {
+        let mut notes = vec![
+            style.format("占 {}%", &[&style.percent(duplicates.ratio())]),
+            style.format("不同内容 {} 行", &[&duplicates.distinct]),
+        ];
+        if duplicates.approximate {
+            notes.push(style.tr("超过跟踪上限，为近似值").to_string());
+        }
+        writeln!(
+            out,
+            "{}{}{}",
+            style.icon("🔁 "),
+            style.format("重复行: {} 行", &[&duplicates.duplicates]),
+            style.notes(&notes)
+        )?;
+        for repeated in &duplicates.top {
+            writeln!(
+                out,
+                "{}{}: {}",
+                style.indent(),
+                style.format("{} 次", &[&repeated.count]),
+                repeated.text
+            )?;
+        }
+    }
+    if let Some(word_freq) = &line_stats.word_freq {
+        writeln!(
+            out,
+            "{}{}{}",
+            style.icon("🔤 "),
+            style.format("高频单词: 共 {} 个单词", &[&style.count(word_freq.words)]),
+            if word_freq.approximate {
+                style.notes(&[style.tr("超过跟踪上限，为近似值").to_string()])
+            } else {
+                String::new()
+            }
+        )?;
+        for counted in &word_freq.top {
+            writeln!(
+                out,
+                "{}{}: {}",
+                style.indent(),
+                style.format("{} 次", &[&style.count(counted.count)]),
+                counted.word
+            )?;
+        }
+    }
+    if let Some(indentation) = line_stats.indentation {
+        match indentation.style() {
+            Some(indent_style) => {
+                let mut notes = vec![style.format(
+                    "主要为{}",
+                    &[&style.lang.translate(&indent_style.to_string())],
+                )];
+                if let Some(width) = indentation.width() {
+                    notes.push(style.format("宽度 {}", &[&width]));
+                }
+                if indentation.is_mixed() {
+                    notes.push(style.tr("混用").to_string());
+                }
+                writeln!(
+                    out,
+                    "{}{}{}",
+                    style.icon("↔️  "),
+                    style.format(
+                        "缩进: 空格 {} 行, 制表符 {} 行",
+                        &[&indentation.spaces, &indentation.tabs]
+                    ),
+                    style.notes(&notes)
+                )?
+            }
+            None => writeln!(
+                out,
+                "{}{}: {}",
+                style.icon("↔️  "),
+                style.tr("缩进"),
+                style.tr("无")
+            )?,
+        }
+    }
+    if let Some(endings) = line_stats.line_endings {
+        // 无障碍模式下读出换行符的全称，而不是缩写
+        let name = |ending: LineEnding| match (style.accessible, ending) {
+            (false, ending) => ending.to_string(),
+            (true, LineEnding::Lf) => style.tr("换行").to_string(),
+            (true, LineEnding::Crlf) => style.tr("回车加换行").to_string(),
+            (true, LineEnding::Cr) => style.tr("回车").to_string(),
+        };
+        match endings.dominant() {
+            Some(dominant) => {
+                let mut notes = vec![style.format("主要为 {}", &[&name(dominant)])];
+                if endings.is_mixed() {
+                    notes.push(style.tr("混用").to_string());
+                }
+                writeln!(
+                    out,
+                    "{}{}: {} {}, {} {}, {} {}{}",
+                    style.icon("↩️  "),
+                    style.tr("换行符"),
+                    name(LineEnding::Lf),
+                    endings.lf,
+                    name(LineEnding::Crlf),
+                    endings.crlf,
+                    name(LineEnding::Cr),
+                    endings.cr,
+                    style.notes(&notes)
+                )?
+            }
+            None => writeln!(
+                out,
+                "{}{}: {}",
+                style.icon("↩️  "),
+                style.tr("换行符"),
+                style.tr("无")
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// 打印跳过文件的提示
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `report` - 被跳过的二进制文件或不存在的路径的报告
+/// * `style` - 输出风格
+fn write_skipped(out: &mut dyn Write, report: &FileReport, style: &Style) -> io::Result<()> {
+    if report.missing {
+        writeln!(
+            out,
+            "{}{}{}",
+            style.icon("⏭️  "),
+            style.paint(Role::Warning, style.tr("跳过不存在的路径: ")),
+            style.path(&report.path)
+        )?;
+    } else if report.timed_out {
+        writeln!(
+            out,
+            "{}{}{}",
+            style.icon("⏱️  "),
+            style.paint(Role::Warning, style.tr("到达截止时间，未统计: ")),
+            style.path(&report.path)
+        )?;
+    } else if let Some(error) = &report.error {
+        writeln!(
+            out,
+            "{}{}{}{}",
+            style.icon("❌ "),
+            style.paint(Role::Warning, style.tr("统计失败: ")),
+            style.path(&report.path),
+            style.notes(&[error.to_string()])
+        )?;
+    } else if let Some(exceeded) = report.limit_exceeded {
+        writeln!(
+            out,
+            "{}{}{}{}",
+            style.icon("⏭️  "),
+            style.paint(Role::Warning, style.tr("跳过超过解压缩限制的文件: ")),
+            style.path(&report.path),
+            style.notes(&[style.lang.translate(&exceeded.to_string()).into_owned()])
+        )?;
+    } else {
+        writeln!(
+            out,
+            "{}{}{}{}",
+            style.icon("⏭️  "),
+            style.paint(Role::Warning, style.tr("跳过二进制文件: ")),
+            style.path(&report.path),
+            style.notes(&[style.tr("使用 --count-binary 强制统计").to_string()])
+        )?;
+    }
+    Ok(())
+}
+
+/// 打印多文件统计结果
+///
+/// 先逐个输出每个文件的简要统计（混合缩进的文件会被标出），再输出汇总结果。
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `reports` - 按输入顺序排列的统计报告
+/// * `elapsed` - 本次统计的耗时
+/// * `style` - 输出风格
+fn write_multi_file(
+    out: &mut dyn Write,
+    reports: &[FileReport],
+    elapsed: Duration,
+    style: &Style,
+) -> io::Result<()> {
+    for report in reports {
+        if report.is_skipped() {
+            write_skipped(out, report, style)?;
+            continue;
+        }
+        let mixed_indentation = report
+            .stats
+            .indentation
+            .is_some_and(|indentation| indentation.is_mixed());
+        writeln!(
+            out,
+            "{}{}: {}{}{}{}{}{}{}{}{}{}{}{}",
+            if style.emoji {
+                "📄 "
+            } else {
+                style.tr("文件 ")
+            },
+            style.file(report),
+            style.format(
+                "总行数 {}, 非空行数 {}, 空行数 {}",
+                &[
+                    &style.count(report.stats.total_lines),
+                    &style.count(report.stats.non_empty_lines),
+                    &style.count(report.stats.empty_lines)
+                ]
+            ),
+            report
+                .stats
+                .matched_lines
+                .map(|matched| style.format(", 匹配行数 {}", &[&matched]))
+                .unwrap_or_default(),
+            report
+                .stats
+                .invalid_utf8_lines
+                .map(|invalid| style.format(", 无效 UTF-8 行数 {}", &[&invalid]))
+                .unwrap_or_default(),
+            report
+                .stats
+                .max_line_bytes
+                .map(|bytes| style.format(", 最长行 {} {}", &[&bytes, &style.bytes()]))
+                .unwrap_or_default(),
+            report
+                .stats
+                .todos
+                .as_ref()
+                .map(|todos| style.format(", 待办标记 {}", &[&todos.total()]))
+                .unwrap_or_default(),
+            report
+                .stats
+                .comments
+                .map(|comments| style.format(
+                    ", 注释行数 {}, 代码行数 {}",
+                    &[&comments.comment_lines, &comments.code_lines]
+                ))
+                .unwrap_or_default(),
+            report
+                .stats
+                .logical
+                .map(|logical| style.format(", 逻辑行数 {}", &[&style.count(logical.logical_lines)]))
+                .unwrap_or_default(),
+            report
+                .stats
+                .tokens
+                .map(|tokens| style.format(", Token 数 {}", &[&style.count(tokens.tokens)]))
+                .unwrap_or_default(),
+            record_summary(&report.stats, style),
+            report
+                .reading_minutes
+                .map(|minutes| style.format(", 阅读时间约 {} 分钟", &[&(minutes.ceil() as u64)]))
+                .unwrap_or_default(),
+            if mixed_indentation {
+                style.paint(Role::Warning, style.tr("（混合缩进）"))
+            } else {
+                String::new()
+            },
+            if report.unstable {
+                style.paint(Role::Warning, style.tr("（统计中被修改）"))
+            } else {
+                String::new()
+            }
+        )?;
+    }
+
+    let summary = Summary::from_reports(reports);
+
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("✅ "),
+        style.paint(Role::Heading, style.tr("汇总完成!"))
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("📁 "),
+        style.tr("文件数"),
+        style.count(summary.files)
+    )?;
+    if summary.skipped > 0 {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("⏭️  "),
+            style.tr("跳过的二进制文件数"),
+            summary.skipped
+        )?;
+    }
+    if summary.missing > 0 {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("⏭️  "),
+            style.tr("跳过的不存在路径数"),
+            summary.missing
+        )?;
+    }
+    if summary.limit_exceeded > 0 {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("⏭️  "),
+            style.tr("跳过的超过解压缩限制的文件数"),
+            summary.limit_exceeded
+        )?;
+    }
+    if summary.failed > 0 {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("❌ "),
+            style.tr("统计失败的文件数"),
+            style.paint(Role::Warning, summary.failed.to_string())
+        )?;
+    }
+    if summary.is_incomplete() {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("⏱️  "),
+            style.paint(
+                Role::Warning,
+                style.format(
+                    "结果不完整: 到达截止时间时还有 {} 个文件未统计",
+                    &[&summary.timed_out]
+                )
+            )
+        )?;
+    }
+    if summary.warnings > 0 {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.icon("⚠️  "),
+            style.tr("警告数"),
+            style.paint(Role::Warning, summary.warnings)
+        )?;
+    }
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("📏 "),
+        style.tr("文件总大小"),
+        style.size(summary.bytes)
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("⏱️  "),
+        style.tr("耗时"),
+        units::format_duration(elapsed)
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("📊 "),
+        style.tr("总行数"),
+        style.count(summary.stats.total_lines)
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("📝 "),
+        style.tr("非空行数"),
+        style.count(summary.stats.non_empty_lines)
+    )?;
+    writeln!(
+        out,
+        "{}{}: {}",
+        style.icon("🔲 "),
+        style.tr("空行数"),
+        style.count(summary.stats.empty_lines)
+    )?;
+
+    if summary.stats.total_lines > 0 {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("📈 "),
+            style.format(
+                "空行占比: {}%（按行数加权）",
+                &[&style.percent(summary.empty_percentage)]
+            )
+        )?;
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("📈 "),
+            style.format(
+                "非空行占比: {}%（按行数加权）",
+                &[&style.percent(summary.non_empty_percentage)]
+            )
+        )?;
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("📈 "),
+            style.format(
+                "文件平均空行占比: {}%（每个文件权重相同）",
+                &[&style.percent(summary.mean_empty_percentage)]
+            )
+        )?;
+    }
+    if let (Some(minutes), Some(words)) = (summary.reading_minutes, summary.stats.words) {
+        write_reading_time(out, minutes, words, style)?;
+    }
+
+    write_header_stats(out, &summary.stats, style)?;
+    write_tags(out, &summary.tags, style)
+}
+
+/// 打印按标签汇总的结果
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `rollups` - 按标签名称排列的汇总结果
+/// * `style` - 输出风格
+fn write_tags(out: &mut dyn Write, rollups: &[TagRollup], style: &Style) -> io::Result<()> {
+    if rollups.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("🏷️  "),
+        style.paint(Role::Heading, style.tr("标签:"))
+    )?;
+    for rollup in rollups {
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.indent(),
+            rollup.name,
+            style.format(
+                "文件数 {}, 总行数 {}, 非空行数 {}, 空行数 {}",
+                &[
+                    &rollup.files,
+                    &rollup.stats.total_lines,
+                    &rollup.stats.non_empty_lines,
+                    &rollup.stats.empty_lines
+                ]
+            )
+        )?;
+    }
+    Ok(())
+}
+
+/// 向标准错误输出每个文件的警告
+///
+/// 警告输出到标准错误，不影响标准输出中的结果（包括 JSON）。
+///
+/// # 参数
+///
+/// * `reports` - 按输入顺序排列的统计报告
+/// * `style` - 输出风格
+pub fn print_warnings(reports: &[FileReport], style: &Style) {
+    for report in reports {
+        for warning in &report.warnings {
+            warn!(
+                "{}{}: {}",
+                if style.emoji {
+                    "⚠️  "
+                } else {
+                    style.tr("警告 ")
+                },
+                style.path(&report.path),
+                style.paint(Role::Warning, style.lang.translate(&warning.to_string()))
+            );
+        }
+    }
+}
+
+/// 打印所有文件中按字节数最长的若干行
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `reports` - 按输入顺序排列的统计报告
+/// * `count` - 最多列出的行数
+/// * `style` - 输出风格
+pub fn write_top_lines(
+    out: &mut dyn Write,
+    reports: &[FileReport],
+    count: usize,
+    style: &Style,
+) -> io::Result<()> {
+    let mut lines: Vec<(&Path, &LongLine)> = reports
+        .iter()
+        .flat_map(|report| {
+            report
+                .stats
+                .longest_lines
+                .iter()
+                .map(|long_line| (report.path.as_path(), long_line))
+        })
+        .collect();
+    // 稳定排序：字节数相同时保持输入顺序和行号顺序
+    lines.sort_by_key(|&(_, long_line)| std::cmp::Reverse(long_line.length.bytes));
+    lines.truncate(count);
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("📏 "),
+        style.paint(
+            Role::Heading,
+            style.format("最长的 {} 行:", &[&lines.len()])
+        )
+    )?;
+    for (path, long_line) in lines {
+        let length = long_line.length;
+        let location = if reports.len() == 1 {
+            style.format("第 {} 行", &[&long_line.line])
+        } else {
+            format!("{}:{}", path.display(), long_line.line)
+        };
+        let location = style.link(location, path, Some(long_line.line));
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.indent(),
+            location,
+            style.format(
+                "{} {}, {} 字符, {} 列",
+                &[&length.bytes, &style.bytes(), &length.chars, &length.cells]
+            )
+        )?;
+    }
+    Ok(())
+}
+
+/// 按文件列出待办标记所在的行（仅在启用 `--list-todos` 时有内容）
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `reports` - 按输入顺序排列的统计报告
+/// * `style` - 输出风格
+pub fn write_todos(out: &mut dyn Write, reports: &[FileReport], style: &Style) -> io::Result<()> {
+    let items: Vec<(&Path, &TodoItem)> = reports
+        .iter()
+        .filter_map(|report| Some((report.path.as_path(), report.stats.todos.as_ref()?)))
+        .flat_map(|(path, todos)| todos.items.iter().map(move |item| (path, item)))
+        .collect();
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("📌 "),
+        style.paint(Role::Heading, style.tr("待办标记列表:"))
+    )?;
+    for (path, item) in items {
+        let location = if reports.len() == 1 {
+            style.format("第 {} 行", &[&item.line])
+        } else {
+            format!("{}:{}", path.display(), item.line)
+        };
+        let location = style.link(location, path, Some(item.line));
+        writeln!(out, "{}{}: {}", style.indent(), location, item.text)?;
+    }
+    Ok(())
+}
+
+/// 打印语言分布
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `shares` - 按字节数排列的语言占比
+/// * `style` - 输出风格；无障碍模式下每种语言输出为一句完整的描述，不做列对齐
+pub fn write_languages(
+    out: &mut dyn Write,
+    shares: &[LanguageShare],
+    style: &Style,
+) -> io::Result<()> {
+    if shares.is_empty() {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("🗂️  "),
+            style.tr("语言分布: 没有识别出语言的文件")
+        )?;
+        return Ok(());
+    }
+
+    if style.accessible {
+        writeln!(out, "{}", style.paint(Role::Heading, style.tr("语言分布:")))?;
+        for share in shares {
+            writeln!(
+                out,
+                "{}: {}",
+                share.name,
+                style.format(
+                    "按字节占 {}%, 按行占 {}%, {} 个文件, {}, {} 行",
+                    &[
+                        &style.percent(share.bytes_percent),
+                        &style.percent(share.lines_percent),
+                        &style.count(share.files),
+                        &style.size(share.bytes),
+                        &style.count(share.lines),
+                    ]
+                )
+            )?;
+        }
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("🗂️  "),
+        style.paint(Role::Heading, style.tr("语言分布（按字节 / 按行）:"))
+    )?;
+    let width = shares
+        .iter()
+        .map(|share| share.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let percent_width = style.percent_width();
+    for share in shares {
+        writeln!(
+            out,
+            "   {:<width$}  {:>percent_width$}% / {:>percent_width$}%  ({})",
+            share.name,
+            style.percent(share.bytes_percent),
+            style.percent(share.lines_percent),
+            style.format(
+                "{} 个文件, {}, {} 行",
+                &[
+                    &style.count(share.files),
+                    &style.size(share.bytes),
+                    &style.count(share.lines)
+                ]
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+/// 打印分组汇总结果
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `by` - 分组方式
+/// * `groups` - 按总行数从多到少排列的分组
+/// * `style` - 输出风格
+pub fn write_groups(
+    out: &mut dyn Write,
+    by: GroupBy,
+    groups: &[Group],
+    style: &Style,
+) -> io::Result<()> {
+    let title = match by {
+        GroupBy::Ext => style.tr("按扩展名汇总:"),
+        GroupBy::Dir => style.tr("按目录汇总:"),
+    };
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("📂 "),
+        style.paint(Role::Heading, title)
+    )?;
+    for group in groups {
+        let key = match (by, group.key.as_str()) {
+            (GroupBy::Ext, "") => style.tr("（无扩展名）"),
+            (GroupBy::Dir, "") => style.tr("（顶层文件）"),
+            (_, key) => key,
+        };
+        writeln!(
+            out,
+            "{}{}: {}",
+            style.indent(),
+            key,
+            style.format(
+                "{} 行, {} 个文件（非空行数 {}, 空行数 {}, {}）",
+                &[
+                    &style.count(group.stats.total_lines),
+                    &style.count(group.files),
+                    &style.count(group.stats.non_empty_lines),
+                    &style.count(group.stats.empty_lines),
+                    &style.size(group.bytes)
+                ]
+            )
+        )?;
+    }
+    Ok(())
+}
+
+/// 打印按作者统计的行数
+///
+/// # 参数
+///
+/// * `out` - 输出目标
+/// * `authors` - 按行数从多到少排列的作者
+/// * `style` - 输出风格
+pub fn write_authors(
+    out: &mut dyn Write,
+    authors: &[AuthorShare],
+    style: &Style,
+) -> io::Result<()> {
+    if authors.is_empty() {
+        writeln!(
+            out,
+            "{}{}",
+            style.icon("👥 "),
+            style.tr("按作者统计: 没有可以归属作者的行")
+        )?;
+        return Ok(());
+    }
+
+    if style.accessible {
+        writeln!(
+            out,
+            "{}",
+            style.paint(Role::Heading, style.tr("按作者统计:"))
+        )?;
+        for author in authors {
+            writeln!(
+                out,
+                "{}: {}",
+                author.name,
+                style.format(
+                    "{} 行, 占 {}%, {} 个文件",
+                    &[
+                        &author.lines,
+                        &style.percent(author.lines_percent),
+                        &author.files
+                    ]
+                )
+            )?;
+        }
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "{}{}",
+        style.icon("👥 "),
+        style.paint(
+            Role::Heading,
+            style.tr("按作者统计（最后修改每一行的作者）:")
+        )
+    )?;
+    let width = authors
+        .iter()
+        .map(|author| author.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let percent_width = style.percent_width();
+    for author in authors {
+        writeln!(
+            out,
+            "   {:<width$}  {:>8} {}  {:>percent_width$}%  ({})",
+            author.name,
+            author.lines,
+            style.tr("行"),
+            style.percent(author.lines_percent),
+            style.format("{} 个文件", &[&author.files])
+        )?;
+    }
+    Ok(())
+}
+
+/// 渲染 Markdown 表格报告
+///
+/// # 参数
+///
+/// * `reports` - 按输入顺序排列的统计报告
+/// * `elapsed` - 本次统计的耗时
+/// * `languages` - 是否追加语言分布表格
+/// * `grouping` - 分组汇总的设置，`None` 表示不追加分组汇总表格
+/// * `authors` - 按作者统计的行数，`None` 表示不追加作者表格
+/// * `precision` - 百分比保留的小数位数
+///
+/// # 返回值
+///
+/// GitHub 风格的 Markdown 文本，以换行符结尾
+pub fn render_markdown(
+    reports: &[FileReport],
+    elapsed: Duration,
+    languages: bool,
+    grouping: Option<&Grouping>,
+    authors: Option<&[AuthorShare]>,
+    precision: usize,
+) -> String {
+    let mut out = String::new();
+    out.push_str("| 文件 | 总行数 | 非空行数 | 空行数 | 空行占比 |\n");
+    out.push_str("| :--- | ---: | ---: | ---: | ---: |\n");
+    for report in reports {
+        let path = markdown_code(&report.path.display().to_string());
+        if report.is_skipped() {
+            let reason = if report.missing {
+                "不存在的路径"
+            } else if report.limit_exceeded.is_some() {
+                "超过解压缩限制"
+            } else if report.timed_out {
+                "到达截止时间"
+            } else if report.error.is_some() {
+                "统计失败"
+            } else {
+                "二进制文件"
+            };
+            out.push_str(&format!(
+                "| {}（跳过：{}） | — | — | — | — |\n",
+                path, reason
+            ));
+            continue;
+        }
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {:.*}% |\n",
+            path,
+            report.stats.total_lines,
+            report.stats.non_empty_lines,
+            report.stats.empty_lines,
+            precision,
+            report.stats.empty_percentage()
+        ));
+    }
+    let summary = Summary::from_reports(reports);
+    if reports.len() > 1 {
+        out.push_str(&format!(
+            "| **合计（{} 个文件）** | **{}** | **{}** | **{}** | **{:.*}%** |\n",
+            summary.files,
+            summary.stats.total_lines,
+            summary.stats.non_empty_lines,
+            summary.stats.empty_lines,
+            precision,
+            summary.empty_percentage
+        ));
+    }
+
+    if summary.is_incomplete() {
+        out.push_str(&format!(
+            "\n> **结果不完整**：到达截止时间时还有 {} 个文件未统计\n",
+            summary.timed_out
+        ));
+    }
+    out.push_str(&format!(
+        "\n共统计 {} 个文件，{} 字节，耗时 {}\n",
+        summary.files,
+        summary.bytes,
+        units::format_duration(elapsed)
+    ));
+
+    if let Some(grouping) = grouping {
+        let (title, column, empty_key) = match grouping.by {
+            GroupBy::Ext => ("按扩展名汇总", "扩展名", "（无扩展名）"),
+            GroupBy::Dir => ("按目录汇总", "目录", "（顶层文件）"),
+        };
+        out.push_str(&format!("\n### {}\n\n", title));
+        out.push_str(&format!(
+            "| {} | 文件数 | 总行数 | 非空行数 | 空行数 |\n",
+            column
+        ));
+        out.push_str("| :--- | ---: | ---: | ---: | ---: |\n");
+        for group in group::group(reports, grouping) {
+            let key = match group.key.as_str() {
+                "" => empty_key.to_string(),
+                key => markdown_code(key),
+            };
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                key,
+                group.files,
+                group.stats.total_lines,
+                group.stats.non_empty_lines,
+                group.stats.empty_lines
+            ));
+        }
+    }
+
+    if languages {
+        out.push_str("\n### 语言分布\n\n");
+        out.push_str("| 语言 | 文件数 | 字节占比 | 行数占比 |\n");
+        out.push_str("| :--- | ---: | ---: | ---: |\n");
+        for share in language::breakdown(reports) {
+            out.push_str(&format!(
+                "| {} | {} | {:.*}% | {:.*}% |\n",
+                markdown_escape(share.name),
+                share.files,
+                precision,
+                share.bytes_percent,
+                precision,
+                share.lines_percent
+            ));
+        }
+    }
+
+    if let Some(authors) = authors {
+        out.push_str("\n### 按作者统计\n\n");
+        out.push_str("| 作者 | 文件数 | 行数 | 行数占比 |\n");
+        out.push_str("| :--- | ---: | ---: | ---: |\n");
+        for author in authors {
+            out.push_str(&format!(
+                "| {} | {} | {} | {:.*}% |\n",
+                markdown_escape(&author.name),
+                author.files,
+                author.lines,
+                precision,
+                author.lines_percent
+            ));
+        }
+    }
+    out
+}
+
+/// 渲染差异比较结果的 Markdown 表格
+///
+/// # 参数
+///
+/// * `old` - 旧输入的路径
+/// * `new` - 新输入的路径
+/// * `diff` - 比较结果
+///
+/// # 返回值
+///
+/// GitHub 风格的 Markdown 文本，以换行符结尾
+pub fn render_diff_markdown(old: &Path, new: &Path, diff: &StatsDiff) -> String {
+    let mut out = format!(
+        "比较: `{}` → `{}`{}\n\n",
+        old.display(),
+        new.display(),
+        if diff.blank_ignored {
+            "（忽略空行变化）"
+        } else {
+            ""
+        }
+    );
+    out.push_str("| 指标 | 旧 | 新 | 变化 |\n");
+    out.push_str("| :--- | ---: | ---: | ---: |\n");
+    let rows = [
+        (
+            "总行数",
+            diff.old.total_lines,
+            diff.new.total_lines,
+            diff.delta.total_lines,
+        ),
+        (
+            "非空行数",
+            diff.old.non_empty_lines,
+            diff.new.non_empty_lines,
+            diff.delta.non_empty_lines,
+        ),
+        (
+            "空行数",
+            diff.old.empty_lines,
+            diff.new.empty_lines,
+            diff.delta.empty_lines,
+        ),
+    ];
+    for &(name, old, new, delta) in diff_rows(&rows, diff) {
+        out.push_str(&format!("| {} | {} | {} | {:+} |\n", name, old, new, delta));
+    }
+    out
+}
+
+/// 渲染目录比较结果的 Markdown
+///
+/// # 参数
+///
+/// * `old` - 旧目录
+/// * `new` - 新目录
+/// * `diff` - 比较结果
+///
+/// # 返回值
+///
+/// 整体变化的表格和各类文件数；有逐个文件的结果时再追加有变化的文件的表格
+pub fn render_dir_diff_markdown(old: &Path, new: &Path, diff: &DirDiff) -> String {
+    let mut out = render_diff_markdown(old, new, &diff.total);
+    out.push_str(&format!(
+        "\n文件: 新增 {}, 删除 {}, 修改 {}, 未变化 {}\n",
+        diff.added, diff.removed, diff.changed, diff.unchanged
+    ));
+    if diff.files.is_empty() {
+        return out;
+    }
+
+    out.push_str("\n### 有变化的文件\n\n");
+    let (metric, lines) = file_metric(diff);
+    out.push_str(&format!(
+        "| 文件 | 变化 | 旧{} | 新{} | 行数变化 | 大小变化 |\n",
+        metric, metric
+    ));
+    out.push_str("| :--- | :--- | ---: | ---: | ---: | ---: |\n");
+    for file in &diff.files {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {:+} | {:+} |\n",
+            markdown_code(&file.path.display().to_string()),
+            file.status,
+            lines(&file.stats.old),
+            lines(&file.stats.new),
+            file.stats.delta.total_lines,
+            file.bytes
+        ));
+    }
+    out
+}
+
+/// 渲染两个 Git 版本之间行变化的 Markdown 报告
+///
+/// # 参数
+///
+/// * `diff` - 两个版本之间的变化；`files` 非空时逐个列出文件
+///
+/// # 返回值
+///
+/// GitHub 风格的 Markdown 文本，以换行符结尾
+pub fn render_revision_diff_markdown(diff: &RevisionDiff) -> String {
+    let mut out = format!(
+        "比较: {}\n\n共 {} 行变化（新增 {}, 删除 {}, 修改 {}）\n\n文件: 新增 {}, 删除 {}, 修改 {}\n",
+        markdown_code(&diff.range),
+        diff.total.total(),
+        diff.total.added,
+        diff.total.removed,
+        diff.total.modified,
+        diff.added,
+        diff.removed,
+        diff.changed
+    );
+    if diff.files.is_empty() {
+        return out;
+    }
+
+    out.push_str("\n### 有变化的文件\n\n");
+    out.push_str("| 文件 | 变化 | 新增行 | 删除行 | 修改行 |\n");
+    out.push_str("| :--- | :--- | ---: | ---: | ---: |\n");
+    for file in &diff.files {
+        let mut path = markdown_code(&file.path.display().to_string());
+        if let Some(old_path) = &file.old_path {
+            path = format!(
+                "{}（由 {} 重命名）",
+                path,
+                markdown_code(&old_path.display().to_string())
+            );
+        }
+        if file.binary {
+            out.push_str(&format!(
+                "| {} | {}（二进制文件） | — | — | — |\n",
+                path, file.status
+            ));
+            continue;
+        }
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            path, file.status, file.lines.added, file.lines.removed, file.lines.modified
+        ));
+    }
+    out
+}
+
+/// 转义表格单元格中的 `|`，避免被当作列分隔符
+fn markdown_escape(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// 将文本放入行内代码中（路径中的 `_`、`*` 等不会被当作强调标记）
+fn markdown_code(text: &str) -> String {
+    format!("`{}`", markdown_escape(text).replace('`', "'"))
+}
+
+/// 以可读文本格式输出差异比较结果
+///
+/// # 参数
+///
+/// * `old` - 旧输入的路径
+/// * `new` - 新输入的路径
+/// * `diff` - 比较结果
+/// * `style` - 输出风格；无障碍模式下用“变为”“变化”代替箭头和括号
+pub fn print_diff(old: &Path, new: &Path, diff: &StatsDiff, style: &Style) {
+    print_diff_header(old, new, diff.blank_ignored, style);
+    print_diff_rows(diff, style);
+}
+
+/// 以可读文本格式输出目录比较结果
+///
+/// 依次输出有变化的文件（`--detail` 时）、各类文件数和整体的行数变化。
+///
+/// # 参数
+///
+/// * `old` - 旧目录
+/// * `new` - 新目录
+/// * `diff` - 比较结果
+/// * `style` - 输出风格；有变化的文件路径按风格渲染为指向新目录（删除的文件为旧目录）中文件的超链接
+pub fn print_dir_diff(old: &Path, new: &Path, diff: &DirDiff, style: &Style) {
+    let (arrow, open, close) = diff_arrows(style);
+    print_diff_header(old, new, diff.total.blank_ignored, style);
+    let (metric, lines) = file_metric(diff);
+    for file in &diff.files {
+        let (icon, root, value) = match file.status {
+            FileStatus::Added => ("➕ ", new, lines(&file.stats.new).to_string()),
+            FileStatus::Removed => ("➖ ", old, lines(&file.stats.old).to_string()),
+            FileStatus::Changed => (
+                "✏️  ",
+                new,
+                format!(
+                    "{}{}{}{}{:+}{}",
+                    lines(&file.stats.old),
+                    arrow,
+                    lines(&file.stats.new),
+                    open,
+                    file.stats.delta.total_lines,
+                    close
+                ),
+            ),
+        };
+        println!(
+            "{}{} {}: {} {}, {}",
+            style.icon(icon),
+            style.lang.translate(&file.status.to_string()),
+            style.link(file.path.display(), &root.join(&file.path), None),
+            style.tr(metric),
+            value,
+            style.format(
+                "大小 {} {}",
+                &[&format!("{:+}", file.bytes), &style.bytes()]
+            )
+        );
+    }
+    println!(
+        "{}{}",
+        style.icon("📁 "),
+        style.format(
+            "文件: 新增 {}, 删除 {}, 修改 {}, 未变化 {}",
+            &[&diff.added, &diff.removed, &diff.changed, &diff.unchanged]
+        )
+    );
+    print_diff_rows(&diff.total, style);
+}
+
+/// 以可读文本格式输出两个 Git 版本之间的行变化
+///
+/// # 参数
+///
+/// * `diff` - 两个版本之间的变化；`files` 非空时逐个列出文件
+/// * `style` - 输出风格
+pub fn print_revision_diff(diff: &RevisionDiff, style: &Style) {
+    println!("{}{}: {}", style.icon("🔍 "), style.tr("比较"), diff.range);
+    for file in &diff.files {
+        let icon = match file.status {
+            FileStatus::Added => "➕ ",
+            FileStatus::Removed => "➖ ",
+            FileStatus::Changed => "✏️  ",
+        };
+        println!(
+            "{}{} {}: {}",
+            style.icon(icon),
+            style.lang.translate(&file.status.to_string()),
+            revision_file(file, style),
+            if file.binary {
+                style.tr("二进制文件").to_string()
+            } else {
+                line_changes(&file.lines, style)
+            }
+        );
+    }
+    println!(
+        "{}{}",
+        style.icon("📁 "),
+        style.format(
+            "文件: 新增 {}, 删除 {}, 修改 {}",
+            &[&diff.added, &diff.removed, &diff.changed]
+        )
+    );
+    println!(
+        "{}{}: {}",
+        style.icon("📊 "),
+        style.format("共 {} 行变化", &[&diff.total.total()]),
+        line_changes(&diff.total, style)
+    );
+}
+
+/// 版本比较中的文件路径，重命名的文件附带原路径
+fn revision_file(file: &FileChange, style: &Style) -> String {
+    match &file.old_path {
+        Some(old_path) => format!(
+            "{}{}",
+            file.path.display(),
+            style.format("（由 {} 重命名）", &[&old_path.display()])
+        ),
+        None => file.path.display().to_string(),
+    }
+}
+
+/// 行变化的描述，如 `新增 3 行, 删除 1 行, 修改 2 行`
+fn line_changes(lines: &LineChanges, style: &Style) -> String {
+    style.format(
+        "新增 {} 行, 删除 {} 行, 修改 {} 行",
+        &[&lines.added, &lines.removed, &lines.modified],
+    )
+}
+
+/// 目录比较中逐个文件列出的指标：忽略空行变化时为非空行数，否则为总行数
+fn file_metric(diff: &DirDiff) -> (&'static str, fn(&LineStats) -> usize) {
+    if diff.total.blank_ignored {
+        ("非空行数", |stats| stats.non_empty_lines)
+    } else {
+        ("总行数", |stats| stats.total_lines)
+    }
+}
+
+/// 差异输出中列出的行：忽略空行变化时只列出非空行数（第二行），
+/// 总行数和空行数的新旧值仍包含空行，与记为非空行数变化或 0 的变化量对不上
+fn diff_rows<'a, T>(rows: &'a [T; 3], diff: &StatsDiff) -> &'a [T] {
+    if diff.blank_ignored {
+        &rows[1..2]
+    } else {
+        rows
+    }
+}
+
+/// 差异输出中的箭头和变化量的括号
+fn diff_arrows(style: &Style) -> (&'static str, &'static str, &'static str) {
+    if style.accessible {
+        (style.tr(" 变为 "), style.tr("，变化 "), "")
+    } else {
+        (" → ", " (", ")")
+    }
+}
+
+/// 输出差异比较的标题行
+fn print_diff_header(old: &Path, new: &Path, blank_ignored: bool, style: &Style) {
+    let (arrow, _, _) = diff_arrows(style);
+    println!(
+        "{}{}: {}{}{}{}",
+        style.icon("🔍 "),
+        style.tr("比较"),
+        old.display(),
+        arrow,
+        new.display(),
+        if blank_ignored {
+            style.tr("（忽略空行变化）")
+        } else {
+            ""
+        }
+    );
+}
+
+/// 输出总行数、非空行数和空行数的变化
+fn print_diff_rows(diff: &StatsDiff, style: &Style) {
+    let (arrow, open, close) = diff_arrows(style);
+    let rows = [
+        (
+            style.icon("📊 "),
+            "总行数",
+            diff.old.total_lines,
+            diff.new.total_lines,
+            diff.delta.total_lines,
+        ),
+        (
+            style.icon("📝 "),
+            "非空行数",
+            diff.old.non_empty_lines,
+            diff.new.non_empty_lines,
+            diff.delta.non_empty_lines,
+        ),
+        (
+            style.icon("🔲 "),
+            "空行数",
+            diff.old.empty_lines,
+            diff.new.empty_lines,
+            diff.delta.empty_lines,
+        ),
+    ];
+    for &(icon, name, old, new, delta) in diff_rows(&rows, diff) {
+        println!(
+            "{}{}: {}{}{}{}{:+}{}",
+            icon,
+            style.tr(name),
+            old,
+            arrow,
+            new,
+            open,
+            delta,
+            close
+        );
+    }
+}
+
+/// 格式化基线比较结果
+///
+/// # 参数
+///
+/// * `path` - 基线报告路径
+/// * `checks` - 各项指标的比较结果
+/// * `tolerance` - 允许的增长百分比
+/// * `style` - 输出风格；无障碍模式下用“通过”“未通过”代替标记
+///
+/// # 返回值
+///
+/// * `Vec<String>` - 待输出的文本行
+pub fn format_baseline(
+    path: &Path,
+    checks: &[BaselineCheck],
+    tolerance: f64,
+    style: &Style,
+) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{}{}",
+        style.icon("📐 "),
+        style.format(
+            "基线比较: {} (容差 {}%)",
+            &[&path.display(), &style.percent(tolerance)]
+        )
+    )];
+    let arrow = if style.accessible {
+        style.tr(" 变为 ")
+    } else {
+        " → "
+    };
+    for check in checks {
+        let growth = format!("({:+.*}%)", style.precision, check.growth_percent);
+        lines.push(format!(
+            "{}{}: {}{}{} {}",
+            style.verdict(check.exceeded),
+            match &check.tag {
+                Some(tag) => format!("[{}] {}", tag, style.lang.translate(check.metric)),
+                None => style.lang.translate(check.metric).into_owned(),
+            },
+            check.baseline,
+            arrow,
+            check.current,
+            if check.exceeded {
+                style.paint(Role::Error, growth)
+            } else {
+                growth
+            }
+        ));
+    }
+    lines
+}
+
+/// 格式化阈值检查结果
+///
+/// # 参数
+///
+/// * `violations` - 超过阈值的项目
+/// * `style` - 输出风格；超过阈值的文件路径按风格渲染为超链接
+///
+/// # 返回值
+///
+/// * `Vec<String>` - 待输出的文本行：没有超限时只有一行通过提示
+pub fn format_violations(violations: &[Violation], style: &Style) -> Vec<String> {
+    if violations.is_empty() {
+        return vec![format!(
+            "{}{}",
+            style.icon("✅ "),
+            style.paint(Role::Success, style.tr("阈值检查通过"))
+        )];
+    }
+
+    let mut lines = vec![format!(
+        "{}{}",
+        style.icon("🚦 "),
+        style.paint(
+            Role::Error,
+            style.format("阈值检查: {} 项超限", &[&violations.len()])
+        )
+    )];
+    for violation in violations {
+        let location = match &violation.path {
+            Some(path) => format!("{}: ", style.path(path)),
+            None => String::new(),
+        };
+        let (actual, limit) = match violation.metric {
+            Metric::EmptyPercent => (
+                format!("{}%", style.percent(violation.actual)),
+                format!("{}%", style.percent(violation.limit)),
+            ),
+            Metric::TotalLines | Metric::FileLines => {
+                (violation.actual.to_string(), violation.limit.to_string())
+            }
+        };
+        lines.push(format!(
+            "{}{}{}",
+            style.verdict(true),
+            location,
+            style.format(
+                "{} {} 超过上限 {}",
+                &[
+                    &style.lang.translate(&violation.metric.to_string()),
+                    &style.paint(Role::Error, actual),
+                    &limit
+                ]
+            )
+        ));
+    }
+    lines
+}
+
+/// 格式化流式模式的中间结果
+///
+/// # 参数
+///
+/// * `path` - 正在统计的文件
+/// * `stats` - 目前的统计结果
+/// * `bytes` - 已读取的字节数
+/// * `style` - 输出风格
+pub fn format_stream_progress(path: &Path, stats: &LineStats, bytes: u64, style: &Style) -> String {
+    format!(
+        "{}{}: {}",
+        style.icon("⏳ "),
+        path.display(),
+        style.format(
+            "已读取 {} {}, 总行数 {}, 非空行数 {}, 空行数 {}",
+            &[
+                &bytes,
+                &style.bytes(),
+                &stats.total_lines,
+                &stats.non_empty_lines,
+                &stats.empty_lines
+            ]
+        )
+    )
+}
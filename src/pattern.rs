@@ -0,0 +1,171 @@
+//! 模式的大小写匹配方式
+//!
+//! 与 ripgrep 的约定一致：
+//!
+//! - `-s` / `--case-sensitive`：区分大小写（默认）
+//! - `-i` / `--ignore-case`：不区分大小写
+//! - `-S` / `--smart-case`：模式中没有大写字母时不区分大小写，否则区分
+//!
+//! 三个选项互相覆盖，以最后出现的为准。大小写方式全局生效，
+//! 作用于命令行给出的所有正则表达式（区段标记、许可证模式等）和路径模式（`--exclude`、`--tag`
+//! 以及配置文件中的 `exclude`、`[tags]`）；内置模式和 `never_read` 不受影响。
+//! 命令行没有指定时使用配置文件中的 `case` 设置。
+
+use glob::MatchOptions;
+use regex::{Regex, RegexBuilder};
+use regex_syntax::ast::{self, Ast, ClassSetItem, Visitor};
+use serde::{Deserialize, Serialize};
+
+/// 大小写匹配方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaseMode {
+    /// 区分大小写
+    #[default]
+    Sensitive,
+    /// 不区分大小写
+    Insensitive,
+    /// 模式中没有大写字母时不区分大小写
+    Smart,
+}
+
+impl CaseMode {
+    /// 判断给定模式是否应当不区分大小写
+    ///
+    /// # 参数
+    ///
+    /// * `pattern` - 正则表达式模式
+    pub fn is_insensitive(self, pattern: &str) -> bool {
+        match self {
+            CaseMode::Sensitive => false,
+            CaseMode::Insensitive => true,
+            CaseMode::Smart => !has_uppercase_literal(pattern),
+        }
+    }
+
+    /// 路径模式（glob）的匹配选项：`*` 不跨越目录分隔符，`**` 匹配任意层目录
+    ///
+    /// 智能大小写时，路径模式中没有大写字母则不区分大小写。
+    ///
+    /// # 参数
+    ///
+    /// * `pattern` - glob 模式
+    pub fn glob_options(self, pattern: &str) -> MatchOptions {
+        let insensitive = match self {
+            CaseMode::Sensitive => false,
+            CaseMode::Insensitive => true,
+            CaseMode::Smart => !pattern.chars().any(char::is_uppercase),
+        };
+        MatchOptions {
+            case_sensitive: !insensitive,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        }
+    }
+
+    /// 按大小写方式重新编译正则表达式
+    ///
+    /// # 参数
+    ///
+    /// * `regex` - 已按区分大小写编译的正则表达式
+    ///
+    /// # 返回值
+    ///
+    /// 按大小写方式编译的正则表达式；不需要改变或重新编译失败时返回原表达式
+    pub fn apply(self, regex: &Regex) -> Regex {
+        if !self.is_insensitive(regex.as_str()) {
+            return regex.clone();
+        }
+        RegexBuilder::new(regex.as_str())
+            .case_insensitive(true)
+            .build()
+            .unwrap_or_else(|_| regex.clone())
+    }
+}
+
+/// 判断模式中是否有大写的字面字符
+///
+/// 与 ripgrep 一致，只检查字面字符（包括字符类中的字符和范围端点），
+/// `\S`、`\p{Lu}` 等转义和类名中的大写字母不算。模式无法解析时视为没有大写字母。
+fn has_uppercase_literal(pattern: &str) -> bool {
+    let Ok(ast) = ast::parse::Parser::new().parse(pattern) else {
+        return false;
+    };
+    ast::visit(&ast, UppercaseFinder(false)).unwrap_or(false)
+}
+
+/// 查找大写字面字符的语法树访问器
+struct UppercaseFinder(bool);
+
+impl Visitor for UppercaseFinder {
+    type Output = bool;
+    type Err = ();
+
+    fn finish(self) -> Result<bool, ()> {
+        Ok(self.0)
+    }
+
+    fn visit_pre(&mut self, ast: &Ast) -> Result<(), ()> {
+        if let Ast::Literal(literal) = ast {
+            self.0 |= literal.c.is_uppercase();
+        }
+        Ok(())
+    }
+
+    fn visit_class_set_item_pre(&mut self, item: &ClassSetItem) -> Result<(), ()> {
+        match item {
+            ClassSetItem::Literal(literal) => self.0 |= literal.c.is_uppercase(),
+            ClassSetItem::Range(range) => {
+                self.0 |= range.start.c.is_uppercase() || range.end.c.is_uppercase();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试智能大小写对模式的判断
+    #[test]
+    fn test_smart_case() {
+        assert!(CaseMode::Smart.is_insensitive("begin"));
+        assert!(CaseMode::Smart.is_insensitive(r"\S+\p{Lu}"));
+        assert!(!CaseMode::Smart.is_insensitive("BEGIN"));
+        assert!(!CaseMode::Smart.is_insensitive("[A-z]"));
+        assert!(!CaseMode::Smart.is_insensitive("Ärger"));
+        assert!(CaseMode::Smart.is_insensitive("(?i)中文"));
+
+        assert!(!CaseMode::Sensitive.is_insensitive("begin"));
+        assert!(CaseMode::Insensitive.is_insensitive("BEGIN"));
+    }
+
+    /// 测试路径模式的大小写匹配选项
+    #[test]
+    fn test_glob_options() {
+        assert!(CaseMode::Sensitive.glob_options("src/**").case_sensitive);
+        assert!(!CaseMode::Insensitive.glob_options("Src/**").case_sensitive);
+        assert!(!CaseMode::Smart.glob_options("src/**").case_sensitive);
+        assert!(CaseMode::Smart.glob_options("Src/**").case_sensitive);
+        assert!(
+            CaseMode::Smart
+                .glob_options("*.rs")
+                .require_literal_separator
+        );
+    }
+
+    /// 测试按大小写方式重新编译
+    #[test]
+    fn test_apply() {
+        let regex = Regex::new("begin").unwrap();
+        assert!(!CaseMode::Sensitive.apply(&regex).is_match("BEGIN"));
+        assert!(CaseMode::Insensitive.apply(&regex).is_match("BEGIN"));
+        assert!(CaseMode::Smart.apply(&regex).is_match("Begin"));
+
+        let regex = Regex::new("Begin").unwrap();
+        assert!(!CaseMode::Smart.apply(&regex).is_match("begin"));
+        assert!(CaseMode::Insensitive.apply(&regex).is_match("begin"));
+    }
+}
@@ -0,0 +1,73 @@
+//! 进度显示
+//!
+//! 使用 `indicatif` 在标准错误输出上绘制进度条：
+//!
+//! - 单个文件超过 [`PROGRESS_THRESHOLD`] 时，按已读取字节数显示进度
+//! - 多文件模式下，按已完成的文件数显示进度
+//!
+//! 标准错误输出不是终端时，`indicatif` 会自动隐藏进度条，不影响管道和日志。
+
+use crate::locale::Lang;
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// 显示字节进度条的文件大小阈值（字节）
+pub const PROGRESS_THRESHOLD: u64 = 16 * 1024 * 1024; // 16MB
+
+/// 创建按字节显示进度的进度条
+///
+/// # 参数
+///
+/// * `size` - 文件大小（字节）
+/// * `enabled` - 是否启用进度显示；文件小于阈值时同样返回隐藏的进度条
+/// * `lang` - 进度条文字的语言
+pub fn byte_progress(size: u64, enabled: bool, lang: Lang) -> ProgressBar {
+    if !enabled || size < PROGRESS_THRESHOLD {
+        return ProgressBar::hidden();
+    }
+
+    let bar = ProgressBar::new(size);
+    bar.set_style(
+        ProgressStyle::with_template(lang.tr(
+            "{spinner} [{elapsed_precise}] [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, 剩余 {eta})",
+        ))
+        .expect("进度条模板无效")
+        .progress_chars("=> "),
+    );
+    bar
+}
+
+/// 创建按文件数显示进度的进度条
+///
+/// # 参数
+///
+/// * `files` - 文件总数
+/// * `enabled` - 是否启用进度显示
+/// * `lang` - 进度条文字的语言
+pub fn file_progress(files: usize, enabled: bool, lang: Lang) -> ProgressBar {
+    if !enabled {
+        return ProgressBar::hidden();
+    }
+
+    let bar = ProgressBar::new(files as u64);
+    bar.set_style(
+        ProgressStyle::with_template(
+            lang.tr("{spinner} [{elapsed_precise}] [{bar:40}] {pos}/{len} 个文件"),
+        )
+        .expect("进度条模板无效")
+        .progress_chars("=> "),
+    );
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试小文件和禁用时不显示进度条
+    #[test]
+    fn test_byte_progress_hidden() {
+        assert!(byte_progress(1024, true, Lang::Zh).is_hidden());
+        assert!(byte_progress(PROGRESS_THRESHOLD * 2, false, Lang::En).is_hidden());
+        assert!(file_progress(10, false, Lang::Zh).is_hidden());
+    }
+}
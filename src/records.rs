@@ -0,0 +1,442 @@
+//! 结构化记录统计
+//!
+//! 行数无法反映结构化数据中的记录数：CSV 的引号字段可以包含换行符，一条记录可能跨越多行。
+//! 按 [`RecordFormat`] 逐行解析内容，统计记录数、列数、列数不一致的记录和空字段：
+//!
+//! - CSV 按 RFC 4180 解析：以 `"` 开始的字段为引号字段，其中的 `""` 表示一个引号，
+//!   字段内的逗号和换行符属于字段内容
+//! - TSV 按 IANA 的定义解析：字段以制表符分隔，不使用引号
+//!
+//! 第一条记录（通常为表头）的字段数作为列数，之后字段数不同的记录为列数不一致的记录。
+//! 记录之间的空行不是记录。
+//!
+//! JSON 数据按 [`crate::json`] 检查语法：
+//!
+//! - JSON Lines（NDJSON）每个非空行是一条记录，统计合法的记录、无效的行和空行
+//! - JSON 检查整个文件是否恰好是一个合法的文档，并统计顶层数组的元素数
+//!
+//! 默认按扩展名（`.csv`、`.tsv`）选择格式，`--mode` 可以对所有文件指定格式或只统计行数（见 [`RecordMode`]）。
+
+use crate::json::JsonValidator;
+use crate::stats::{CsvStats, JsonDocumentStats, JsonLinesStats, LineStats};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// 最多记录的问题行（列数不一致的记录、无效的 JSON 行）的行号数
+pub const MAX_INCONSISTENT_LINES: usize = 10;
+
+/// 结构化记录的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// 逗号分隔，支持引号字段
+    Csv,
+    /// 制表符分隔，不使用引号
+    Tsv,
+    /// 每行一个 JSON 值（JSON Lines、NDJSON）
+    JsonLines,
+    /// 单个 JSON 文档
+    Json,
+}
+
+/// 统计模式：如何为每个文件选择记录格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
+pub enum RecordMode {
+    /// 按文件的语言选择（`.csv` 为 CSV，`.tsv` 为 TSV），其余文件只统计行数
+    #[default]
+    Auto,
+    /// 只统计行数
+    Lines,
+    /// 所有文件都按 CSV 统计记录
+    Csv,
+    /// 所有文件都按 TSV 统计记录
+    Tsv,
+    /// 所有文件都按 JSON Lines 统计记录
+    Jsonl,
+    /// 检查所有文件是否为单个合法的 JSON 文档
+    Json,
+}
+
+impl RecordMode {
+    /// 确定文件使用的记录格式
+    ///
+    /// # 参数
+    ///
+    /// * `detected` - 按文件的语言识别出的记录格式
+    ///
+    /// # 返回值
+    ///
+    /// 记录格式，`None` 表示只统计行数
+    pub fn resolve(self, detected: Option<RecordFormat>) -> Option<RecordFormat> {
+        match self {
+            RecordMode::Auto => detected,
+            RecordMode::Lines => None,
+            RecordMode::Csv => Some(RecordFormat::Csv),
+            RecordMode::Tsv => Some(RecordFormat::Tsv),
+            RecordMode::Jsonl => Some(RecordFormat::JsonLines),
+            RecordMode::Json => Some(RecordFormat::Json),
+        }
+    }
+}
+
+/// 按记录格式逐行统计记录
+#[derive(Debug)]
+pub enum RecordTracker {
+    /// CSV 或 TSV
+    Csv(CsvTracker),
+    /// JSON Lines
+    JsonLines(JsonLinesTracker),
+    /// 单个 JSON 文档
+    Json(JsonDocumentTracker),
+}
+
+impl RecordTracker {
+    /// 创建统计器
+    ///
+    /// # 参数
+    ///
+    /// * `format` - 记录格式
+    pub fn new(format: RecordFormat) -> Self {
+        match format {
+            RecordFormat::Csv => RecordTracker::Csv(CsvTracker::csv()),
+            RecordFormat::Tsv => RecordTracker::Csv(CsvTracker::tsv()),
+            RecordFormat::JsonLines => RecordTracker::JsonLines(JsonLinesTracker::default()),
+            RecordFormat::Json => RecordTracker::Json(JsonDocumentTracker::default()),
+        }
+    }
+
+    /// 统计一行
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 行内容（不含换行符）
+    /// * `line_number` - 行号（从 1 开始）
+    pub fn push(&mut self, line: &str, line_number: usize) {
+        match self {
+            RecordTracker::Csv(tracker) => tracker.push(line, line_number),
+            RecordTracker::JsonLines(tracker) => tracker.push(line, line_number),
+            RecordTracker::Json(tracker) => tracker.push(line, line_number),
+        }
+    }
+
+    /// 结束统计，把结果写入统计结果中对应的字段
+    ///
+    /// # 参数
+    ///
+    /// * `stats` - 统计结果
+    pub fn finish(self, stats: &mut LineStats) {
+        match self {
+            RecordTracker::Csv(tracker) => stats.csv = Some(tracker.finish()),
+            RecordTracker::JsonLines(tracker) => stats.jsonl = Some(tracker.finish()),
+            RecordTracker::Json(tracker) => stats.json = Some(tracker.finish()),
+        }
+    }
+}
+
+/// 逐行解析 CSV 和 TSV 记录
+#[derive(Debug)]
+pub struct CsvTracker {
+    /// 字段分隔符
+    delimiter: char,
+    /// 是否支持引号字段
+    quoted: bool,
+    stats: CsvStats,
+    /// 是否在引号字段中（记录延续到下一行）
+    in_quotes: bool,
+    /// 当前记录中已经结束的字段数
+    fields: usize,
+    /// 当前字段的内容长度（字符数）
+    field_len: usize,
+    /// 当前字段是否为引号字段
+    field_quoted: bool,
+    /// 当前记录开始的行号
+    record_line: usize,
+}
+
+impl CsvTracker {
+    /// 创建 CSV 解析器
+    pub fn csv() -> Self {
+        Self::new(',', true)
+    }
+
+    /// 创建 TSV 解析器
+    pub fn tsv() -> Self {
+        Self::new('\t', false)
+    }
+
+    fn new(delimiter: char, quoted: bool) -> Self {
+        Self {
+            delimiter,
+            quoted,
+            stats: CsvStats::default(),
+            in_quotes: false,
+            fields: 0,
+            field_len: 0,
+            field_quoted: false,
+            record_line: 0,
+        }
+    }
+
+    /// 解析一行
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 行内容（不含换行符）
+    /// * `line_number` - 行号（从 1 开始）
+    pub fn push(&mut self, line: &str, line_number: usize) {
+        if self.in_quotes {
+            // 引号字段中的换行符属于字段内容
+            self.field_len += 1;
+        } else if line.is_empty() {
+            return;
+        } else {
+            self.record_line = line_number;
+        }
+
+        let delimiter = self.delimiter;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if self.in_quotes {
+                // 引号字段中的 `""` 表示一个引号
+                if c != '"' || chars.next_if_eq(&'"').is_some() {
+                    self.field_len += 1;
+                } else {
+                    self.in_quotes = false;
+                }
+            } else if c == delimiter {
+                self.end_field();
+            } else if c == '"' && self.quoted && self.field_len == 0 && !self.field_quoted {
+                self.in_quotes = true;
+                self.field_quoted = true;
+            } else {
+                self.field_len += 1;
+            }
+        }
+
+        if !self.in_quotes {
+            self.end_record();
+        }
+    }
+
+    /// 结束当前字段
+    fn end_field(&mut self) {
+        if self.field_len == 0 {
+            self.stats.empty_fields += 1;
+        }
+        self.fields += 1;
+        self.field_len = 0;
+        self.field_quoted = false;
+    }
+
+    /// 结束当前记录
+    fn end_record(&mut self) {
+        self.end_field();
+        let fields = std::mem::take(&mut self.fields);
+        self.stats.records += 1;
+        if self.stats.records == 1 {
+            self.stats.columns = fields;
+        } else if fields != self.stats.columns {
+            self.stats.inconsistent_records += 1;
+            if self.stats.inconsistent_lines.len() < MAX_INCONSISTENT_LINES {
+                self.stats.inconsistent_lines.push(self.record_line);
+            }
+        }
+    }
+
+    /// 结束解析，返回统计结果
+    ///
+    /// 内容在引号字段中结束时，最后一条记录同样计入，并标记为引号没有闭合。
+    pub fn finish(mut self) -> CsvStats {
+        if self.in_quotes {
+            self.stats.unterminated_quote = true;
+            self.end_record();
+        }
+        self.stats
+    }
+}
+
+/// 逐行统计 JSON Lines 记录
+#[derive(Debug, Default)]
+pub struct JsonLinesTracker {
+    stats: JsonLinesStats,
+}
+
+impl JsonLinesTracker {
+    /// 统计一行
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 行内容（不含换行符）
+    /// * `line_number` - 行号（从 1 开始）
+    pub fn push(&mut self, line: &str, line_number: usize) {
+        if line.trim().is_empty() {
+            self.stats.empty_lines += 1;
+            return;
+        }
+        let mut validator = JsonValidator::new();
+        validator.push(line);
+        if validator.finish().is_some() {
+            self.stats.records += 1;
+        } else {
+            self.stats.invalid_lines += 1;
+            if self.stats.invalid_line_numbers.len() < MAX_INCONSISTENT_LINES {
+                self.stats.invalid_line_numbers.push(line_number);
+            }
+        }
+    }
+
+    /// 结束统计，返回统计结果
+    pub fn finish(self) -> JsonLinesStats {
+        self.stats
+    }
+}
+
+/// 逐行检查整个文件是否为单个合法的 JSON 文档
+#[derive(Debug, Default)]
+pub struct JsonDocumentTracker {
+    validator: JsonValidator,
+    /// 第一个语法错误所在的行号
+    error_line: Option<usize>,
+    /// 最后一行的行号
+    last_line: usize,
+}
+
+impl JsonDocumentTracker {
+    /// 检查一行
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 行内容（不含换行符）
+    /// * `line_number` - 行号（从 1 开始）
+    pub fn push(&mut self, line: &str, line_number: usize) {
+        self.last_line = line_number;
+        if self.error_line.is_some() {
+            return;
+        }
+        if !self.validator.push(line) || !self.validator.push("\n") {
+            self.error_line = Some(line_number);
+        }
+    }
+
+    /// 结束检查，返回检查结果
+    ///
+    /// 内容不完整时（如括号没有闭合）错误位置为最后一行。
+    pub fn finish(self) -> JsonDocumentStats {
+        match self.validator.finish() {
+            Some(document) => JsonDocumentStats {
+                documents: 1,
+                invalid_documents: 0,
+                kind: Some(document.kind),
+                array_length: document.array_length,
+                error_line: None,
+            },
+            None => JsonDocumentStats {
+                documents: 1,
+                invalid_documents: 1,
+                kind: None,
+                array_length: None,
+                error_line: self
+                    .error_line
+                    .or((self.last_line > 0).then_some(self.last_line)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::JsonKind;
+
+    fn parse(mut tracker: CsvTracker, text: &str) -> CsvStats {
+        for (index, line) in text.lines().enumerate() {
+            tracker.push(line, index + 1);
+        }
+        tracker.finish()
+    }
+
+    /// 测试记录数、列数、列数不一致的记录和空字段
+    #[test]
+    fn test_csv() {
+        let stats = parse(
+            CsvTracker::csv(),
+            "id,name,note\n1,a,\n\n2,b\n3,,x,y\n4,c,d\n",
+        );
+        assert_eq!(stats.records, 5);
+        assert_eq!(stats.columns, 3);
+        assert_eq!(stats.inconsistent_records, 2);
+        assert_eq!(stats.inconsistent_lines, vec![4, 5]);
+        assert_eq!(stats.empty_fields, 2);
+        assert!(!stats.unterminated_quote);
+    }
+
+    /// 测试引号字段中的分隔符、转义的引号和换行符
+    #[test]
+    fn test_csv_quoted() {
+        let text = "id,text\n1,\"a, b\"\n2,\"多行\n\n\"\"引用\"\"\"\n3,\"\"\n";
+        let stats = parse(CsvTracker::csv(), text);
+        assert_eq!(stats.records, 4);
+        assert_eq!(stats.columns, 2);
+        assert_eq!(stats.inconsistent_records, 0);
+        // 引号中的空字段
+        assert_eq!(stats.empty_fields, 1);
+
+        let stats = parse(CsvTracker::csv(), "a,b\n1,\"没有闭合\n2,3\n");
+        assert_eq!(stats.records, 2);
+        assert!(stats.unterminated_quote);
+    }
+
+    /// 测试 TSV 不使用引号
+    #[test]
+    fn test_tsv() {
+        let stats = parse(CsvTracker::tsv(), "a\tb\n\"x\ty\n1,2\t\n");
+        assert_eq!(stats.records, 3);
+        assert_eq!(stats.columns, 2);
+        assert_eq!(stats.inconsistent_records, 0);
+        assert_eq!(stats.empty_fields, 1);
+    }
+
+    /// 测试 JSON Lines 的记录、无效行和空行
+    #[test]
+    fn test_json_lines() {
+        let mut tracker = JsonLinesTracker::default();
+        let text = "{\"id\": 1}\n\n[1, 2]\n{\"id\": \n  \n\"text\"\n";
+        for (index, line) in text.lines().enumerate() {
+            tracker.push(line, index + 1);
+        }
+        let stats = tracker.finish();
+        assert_eq!(stats.records, 3);
+        assert_eq!(stats.invalid_lines, 1);
+        assert_eq!(stats.invalid_line_numbers, vec![4]);
+        assert_eq!(stats.empty_lines, 2);
+    }
+
+    /// 测试单个 JSON 文档的检查结果和错误位置
+    #[test]
+    fn test_json_document() {
+        let check = |text: &str| {
+            let mut tracker = JsonDocumentTracker::default();
+            for (index, line) in text.lines().enumerate() {
+                tracker.push(line, index + 1);
+            }
+            tracker.finish()
+        };
+
+        let stats = check("[\n  {\"a\": 1},\n  {\"a\": 2}\n]\n");
+        assert_eq!(stats.invalid_documents, 0);
+        assert_eq!(stats.kind, Some(JsonKind::Array));
+        assert_eq!(stats.array_length, Some(2));
+
+        let stats = check("{\"a\": 1}\n{\"a\": 2}\n");
+        assert_eq!(stats.invalid_documents, 1);
+        assert_eq!(stats.error_line, Some(2));
+
+        let stats = check("[\n  1,\n");
+        assert_eq!(stats.invalid_documents, 1);
+        assert_eq!(stats.error_line, Some(2));
+        assert_eq!(check("").error_line, None);
+    }
+}
@@ -0,0 +1,588 @@
+//! 统计报告数据模型
+//!
+//! [`FileReport`] 描述单个文件的统计结果，[`Summary`] 描述多文件汇总结果。
+//!
+//! 多文件的空行占比有两种口径，汇总结果中同时给出：
+//!
+//! - **加权占比**（`empty_percentage`）：合并后的空行数 / 合并后的总行数，大文件的权重更大，
+//!   反映整个代码库中空行的比例
+//! - **文件平均占比**（`mean_empty_percentage`）：每个文件空行占比的算术平均，每个文件权重相同，
+//!   反映一个典型文件的空行比例；没有任何行的文件占比无定义，不参与平均
+
+use crate::app::error_chain;
+use crate::decompress::{Compression, LimitExceeded};
+use crate::error::LineCounterError;
+use crate::locale::Lang;
+use crate::stats::LineStats;
+use crate::tags::{self, TagRollup};
+use crate::warning::Warning;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// 单个文件的统计报告
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    /// 文件路径
+    pub path: PathBuf,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 行数统计结果
+    pub stats: LineStats,
+    /// 是否为被跳过的二进制文件（此时 `stats` 为空）
+    pub binary: bool,
+    /// 是否为使用 `--ignore-missing` 时跳过的不存在的路径（此时 `stats` 为空）
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+    /// 解压时超过的解压缩限制，此时该文件被跳过且 `stats` 为空（见 [`crate::decompress`]）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_exceeded: Option<LimitExceeded>,
+    /// 是否因到达 `--deadline` 截止时间而未统计（此时 `stats` 为空）
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// 内容的编码（如 `UTF-8`、`GBK`），被跳过的二进制文件为 `None`
+    pub encoding: Option<&'static str>,
+    /// 内容是否以字节顺序标记（BOM）开头；BOM 不计入第一行的内容
+    pub bom: bool,
+    /// 透明解压的压缩格式，此时 `size` 为压缩文件的大小（见 [`crate::decompress`]）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<Compression>,
+    /// `path` 是符号链接时，链接最终指向的文件（见 [`crate::walk::symlink_target`]）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<PathBuf>,
+    /// 根据文件名识别的语言（如 `Rust`），无法识别时为 `None`
+    pub language: Option<&'static str>,
+    /// 文本规范警告（仅在启用 `--warnings` 时检查）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+    /// 文件匹配的标签（见 [`crate::tags`]）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// 按单词数估计的阅读时间（分钟），只有统计了单词数的散文类文件才有（见 `--reading-time`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reading_minutes: Option<f64>,
+    /// 文件在统计过程中被修改（大小或修改时间与开始统计时不同），统计结果可能不对应任何时刻的文件内容
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub unstable: bool,
+    /// 统计失败的原因；多文件统计时单个文件失败不中断其他文件，此时该文件被跳过且 `stats` 为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<FileError>,
+}
+
+/// 单个文件统计失败的错误
+///
+/// 保留原始错误（可通过 [`FileError::kind`] 区分失败类型）和按 `--lang` 本地化后的错误链；
+/// 显示和序列化时为以 `: ` 连接的错误链
+#[derive(Debug, Clone)]
+pub struct FileError {
+    /// 原始错误
+    source: Arc<anyhow::Error>,
+    /// 本地化后的错误链，从外层上下文到根本原因
+    chain: Vec<String>,
+}
+
+impl FileError {
+    /// 从统计文件时的错误创建
+    ///
+    /// # 参数
+    ///
+    /// * `error` - 统计文件时的错误
+    /// * `lang` - 错误链的显示语言
+    pub fn new(error: anyhow::Error, lang: Lang) -> Self {
+        let chain = error_chain(&error, lang);
+        Self {
+            source: Arc::new(error),
+            chain,
+        }
+    }
+
+    /// 原始错误
+    pub fn source(&self) -> &anyhow::Error {
+        &self.source
+    }
+
+    /// 错误链中第一个 [`LineCounterError`]，即失败的类型；其他错误（如 I/O 错误）返回 `None`
+    pub fn kind(&self) -> Option<&LineCounterError> {
+        self.source.chain().find_map(|cause| cause.downcast_ref())
+    }
+
+    /// 本地化后的错误链，从外层上下文到根本原因
+    pub fn chain(&self) -> &[String] {
+        &self.chain
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.chain.join(": "))
+    }
+}
+
+impl Serialize for FileError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl FileReport {
+    /// 不存在的路径的报告
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 不存在的路径
+    pub fn missing(path: PathBuf) -> Self {
+        Self {
+            path,
+            size: 0,
+            stats: LineStats::default(),
+            binary: false,
+            missing: true,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: None,
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: None,
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        }
+    }
+
+    /// 到达截止时间时尚未统计的文件的报告
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径
+    pub fn timed_out(path: PathBuf) -> Self {
+        Self {
+            missing: false,
+            timed_out: true,
+            ..Self::missing(path)
+        }
+    }
+
+    /// 超过解压缩限制而被跳过的文件的报告
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径
+    /// * `size` - 压缩文件的大小（字节）
+    /// * `compression` - 压缩格式
+    /// * `exceeded` - 超过的限制
+    pub fn limit_exceeded(
+        path: PathBuf,
+        size: u64,
+        compression: Compression,
+        exceeded: LimitExceeded,
+    ) -> Self {
+        Self {
+            size,
+            missing: false,
+            limit_exceeded: Some(exceeded),
+            compression: Some(compression),
+            ..Self::missing(path)
+        }
+    }
+
+    /// 统计失败的文件的报告
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径
+    /// * `error` - 失败的原因
+    pub fn failed(path: PathBuf, error: FileError) -> Self {
+        Self {
+            missing: false,
+            error: Some(error),
+            ..Self::missing(path)
+        }
+    }
+
+    /// 是否被跳过（二进制文件、不存在的路径、超过解压缩限制、到达截止时间或统计失败），
+    /// 被跳过的报告不计入汇总和语言分布
+    pub fn is_skipped(&self) -> bool {
+        self.binary
+            || self.missing
+            || self.limit_exceeded.is_some()
+            || self.timed_out
+            || self.error.is_some()
+    }
+
+    /// 统计结果能否缓存并在之后直接使用
+    ///
+    /// 不存在的路径、超过解压缩限制、到达截止时间、统计过程中被修改和统计失败的报告
+    /// 不反映文件的完整内容，之后需要重新统计
+    pub fn is_cacheable(&self) -> bool {
+        !(self.missing
+            || self.limit_exceeded.is_some()
+            || self.timed_out
+            || self.unstable
+            || self.error.is_some())
+    }
+}
+
+/// 估计阅读时间默认的阅读速度（每分钟单词数）
+pub const DEFAULT_WPM: u64 = 200;
+
+/// 按单词数估计阅读时间
+///
+/// # 参数
+///
+/// * `words` - 单词数
+/// * `wpm` - 阅读速度（每分钟单词数）
+///
+/// # 返回值
+///
+/// 阅读时间（分钟）
+pub fn reading_minutes(words: usize, wpm: u64) -> f64 {
+    words as f64 / wpm as f64
+}
+
+/// 多文件结果的排序方式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// 按总行数从多到少
+    Lines,
+    /// 按文件大小从大到小
+    Size,
+    /// 按空行占比从高到低
+    EmptyPct,
+    /// 按路径的字典序
+    Path,
+}
+
+/// 对文件报告排序
+///
+/// 排序是稳定的：键相同的报告保持输入顺序。
+///
+/// # 参数
+///
+/// * `reports` - 单个文件的统计报告
+/// * `key` - 排序方式
+/// * `reverse` - 反转排序方向
+pub fn sort_reports(reports: &mut [FileReport], key: SortKey, reverse: bool) {
+    reports.sort_by(|a, b| {
+        let ordering = match key {
+            SortKey::Lines => b.stats.total_lines.cmp(&a.stats.total_lines),
+            SortKey::Size => b.size.cmp(&a.size),
+            SortKey::EmptyPct => b
+                .stats
+                .empty_percentage()
+                .total_cmp(&a.stats.empty_percentage()),
+            SortKey::Path => a.path.cmp(&b.path),
+        };
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+/// 多文件汇总结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    /// 文件数
+    pub files: usize,
+    /// 文件总大小（字节）
+    pub bytes: u64,
+    /// 跳过的二进制文件数（不计入 `files` 和 `bytes`）
+    #[serde(default)]
+    pub skipped: usize,
+    /// 跳过的不存在的路径数（不计入 `files` 和 `bytes`）
+    #[serde(default)]
+    pub missing: usize,
+    /// 因超过解压缩限制而跳过的文件数（不计入 `files` 和 `bytes`）
+    #[serde(default)]
+    pub limit_exceeded: usize,
+    /// 到达截止时间时尚未统计的文件数，大于 0 表示结果不完整
+    #[serde(default)]
+    pub timed_out: usize,
+    /// 统计失败的文件数（不计入 `files` 和 `bytes`）
+    #[serde(default)]
+    pub failed: usize,
+    /// 警告总数
+    #[serde(default)]
+    pub warnings: usize,
+    /// 合并后的行数统计
+    pub stats: LineStats,
+    /// 按行数加权的空行占比（0.0 - 100.0），即合并后的空行数除以合并后的总行数
+    #[serde(default)]
+    pub empty_percentage: f64,
+    /// 按行数加权的非空行占比（0.0 - 100.0），没有任何行时为 0.0
+    #[serde(default)]
+    pub non_empty_percentage: f64,
+    /// 每个文件空行占比的算术平均（0.0 - 100.0），没有任何行的文件不参与平均
+    #[serde(default)]
+    pub mean_empty_percentage: f64,
+    /// 按标签分别汇总的结果，按标签名称排列
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<TagRollup>,
+    /// 所有散文类文件的阅读时间之和（分钟），没有统计单词数时为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reading_minutes: Option<f64>,
+}
+
+impl Summary {
+    /// 结果是否不完整（到达截止时间时还有文件未统计）
+    pub fn is_incomplete(&self) -> bool {
+        self.timed_out > 0
+    }
+
+    /// 按顺序合并所有文件报告
+    ///
+    /// # 参数
+    ///
+    /// * `reports` - 单个文件的统计报告
+    pub fn from_reports(reports: &[FileReport]) -> Self {
+        let mut summary = Self::default();
+        let mut percentage_sum = 0.0;
+        let mut non_empty_files = 0;
+        for report in reports {
+            summary.warnings += report.warnings.len();
+            if report.binary {
+                summary.skipped += 1;
+                continue;
+            }
+            if report.missing {
+                summary.missing += 1;
+                continue;
+            }
+            if report.limit_exceeded.is_some() {
+                summary.limit_exceeded += 1;
+                continue;
+            }
+            if report.timed_out {
+                summary.timed_out += 1;
+                continue;
+            }
+            if report.error.is_some() {
+                summary.failed += 1;
+                continue;
+            }
+            summary.files += 1;
+            summary.bytes += report.size;
+            summary.stats += &report.stats;
+            if let Some(minutes) = report.reading_minutes {
+                *summary.reading_minutes.get_or_insert(0.0) += minutes;
+            }
+            if report.stats.total_lines > 0 {
+                percentage_sum += report.stats.empty_percentage();
+                non_empty_files += 1;
+            }
+        }
+        summary.empty_percentage = summary.stats.empty_percentage();
+        summary.non_empty_percentage = summary.stats.non_empty_percentage();
+        summary.tags = tags::rollup(reports);
+        if non_empty_files > 0 {
+            summary.mean_empty_percentage = percentage_sum / non_empty_files as f64;
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试汇总结果的合并
+    #[test]
+    fn test_summary_from_reports() {
+        let reports = vec![
+            FileReport {
+                path: PathBuf::from("a.txt"),
+                size: 10,
+                stats: LineStats::new(4, 3, 1),
+                binary: false,
+                missing: false,
+                limit_exceeded: None,
+                timed_out: false,
+                encoding: Some("UTF-8"),
+                bom: false,
+                compression: None,
+                symlink_target: None,
+                language: Some("Text"),
+                warnings: Vec::new(),
+                tags: Vec::new(),
+                reading_minutes: None,
+                unstable: false,
+                error: None,
+            },
+            FileReport {
+                path: PathBuf::from("b.txt"),
+                size: 5,
+                stats: LineStats::new(2, 1, 1),
+                binary: false,
+                missing: false,
+                limit_exceeded: None,
+                timed_out: false,
+                encoding: Some("UTF-8"),
+                bom: false,
+                compression: None,
+                symlink_target: None,
+                language: Some("Text"),
+                warnings: Vec::new(),
+                tags: Vec::new(),
+                reading_minutes: None,
+                unstable: false,
+                error: None,
+            },
+            FileReport {
+                path: PathBuf::from("c.bin"),
+                size: 100,
+                stats: LineStats::default(),
+                binary: true,
+                missing: false,
+                limit_exceeded: None,
+                timed_out: false,
+                encoding: None,
+                bom: false,
+                compression: None,
+                symlink_target: None,
+                language: None,
+                warnings: Vec::new(),
+                tags: Vec::new(),
+                reading_minutes: None,
+                unstable: false,
+                error: None,
+            },
+            FileReport::missing(PathBuf::from("d.txt")),
+            FileReport {
+                missing: false,
+                size: 50,
+                limit_exceeded: Some(LimitExceeded::Size { max_size: 10 }),
+                ..FileReport::missing(PathBuf::from("e.txt.gz"))
+            },
+        ];
+
+        let summary = Summary::from_reports(&reports);
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.bytes, 15);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.missing, 1);
+        assert_eq!(summary.limit_exceeded, 1);
+        assert_eq!(summary.stats, LineStats::new(6, 4, 2));
+        assert!(!summary.is_incomplete());
+
+        let summary = Summary::from_reports(&[FileReport::timed_out(PathBuf::from("f.txt"))]);
+        assert_eq!(summary.files, 0);
+        assert_eq!(summary.timed_out, 1);
+        assert!(summary.is_incomplete());
+    }
+
+    /// 测试文件报告的排序
+    #[test]
+    fn test_sort_reports() {
+        let report = |path: &str, size, lines, empty| FileReport {
+            path: PathBuf::from(path),
+            size,
+            stats: LineStats::new(lines, lines - empty, empty),
+            binary: false,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: Some("Text"),
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+        let mut reports = vec![
+            report("b.txt", 30, 10, 5),
+            report("c.txt", 10, 20, 4),
+            report("a.txt", 20, 10, 1),
+        ];
+        let order = |reports: &[FileReport]| {
+            reports
+                .iter()
+                .map(|report| report.path.to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        sort_reports(&mut reports, SortKey::Lines, false);
+        // 行数相同的保持原顺序
+        assert_eq!(order(&reports), ["c.txt", "b.txt", "a.txt"]);
+        sort_reports(&mut reports, SortKey::Size, false);
+        assert_eq!(order(&reports), ["b.txt", "a.txt", "c.txt"]);
+        sort_reports(&mut reports, SortKey::EmptyPct, false);
+        assert_eq!(order(&reports), ["b.txt", "c.txt", "a.txt"]);
+        sort_reports(&mut reports, SortKey::Path, false);
+        assert_eq!(order(&reports), ["a.txt", "b.txt", "c.txt"]);
+        sort_reports(&mut reports, SortKey::Path, true);
+        assert_eq!(order(&reports), ["c.txt", "b.txt", "a.txt"]);
+    }
+
+    /// 测试加权空行占比与文件平均空行占比
+    #[test]
+    fn test_summary_empty_percentages() {
+        let report = |lines, empty| FileReport {
+            path: PathBuf::from("a.txt"),
+            size: 0,
+            stats: LineStats::new(lines, lines - empty, empty),
+            binary: false,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: Some("Text"),
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+
+        // 一个 90 行的文件没有空行，一个 10 行的文件全是空行
+        let summary = Summary::from_reports(&[report(90, 0), report(10, 10), report(0, 0)]);
+        assert_eq!(summary.files, 3);
+        assert!((summary.empty_percentage - 10.0).abs() < 1e-9);
+        assert!((summary.non_empty_percentage - 90.0).abs() < 1e-9);
+        assert!((summary.mean_empty_percentage - 50.0).abs() < 1e-9);
+
+        let summary = Summary::from_reports(&[report(0, 0)]);
+        assert_eq!(summary.empty_percentage, 0.0);
+        assert_eq!(summary.mean_empty_percentage, 0.0);
+    }
+
+    /// 测试统计失败的文件被跳过，不计入汇总
+    #[test]
+    fn test_summary_failed() {
+        let error = anyhow::Error::from(LineCounterError::FileNotFound {
+            path: "b.txt".to_string(),
+        })
+        .context("无法统计");
+        let failed = FileReport::failed(PathBuf::from("b.txt"), FileError::new(error, Lang::Zh));
+        assert!(failed.is_skipped());
+        assert!(!failed.missing);
+        let error = failed.error.as_ref().unwrap();
+        assert!(matches!(
+            error.kind(),
+            Some(LineCounterError::FileNotFound { .. })
+        ));
+        assert_eq!(error.chain().len(), 2);
+        assert_eq!(
+            serde_json::to_value(error).unwrap(),
+            serde_json::Value::String(error.to_string())
+        );
+
+        let reports = [FileReport::timed_out(PathBuf::from("a.txt")), failed];
+        let summary = Summary::from_reports(&reports);
+        assert_eq!(summary.files, 0);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.timed_out, 1);
+    }
+}
@@ -0,0 +1,414 @@
+//! 运行环境的资源检测
+//!
+//! 启动时检测可用的 CPU 核心数和内存。可用内存低于 [`LOW_MEMORY_THRESHOLD`] 时
+//! （例如内存很小的 CI 容器），自动降级为顺序统计、不使用分块并行引擎，并把读取缓冲区限制为
+//! [`MIN_BUFFER_SIZE`]，避免因并行统计同时占用多份缓冲区而被 OOM 终止。
+//!
+//! 用户通过 `--jobs`、`--engine` 或 `--buffer-size` 显式指定的值不受影响。
+//!
+//! 在容器（如 Kubernetes pod）中，主机的核心数和 `/proc/meminfo` 反映的是整台机器，
+//! 因此还会读取当前进程所在 cgroup 的限制（[`CgroupLimits`]），同时支持 cgroup v1 和 v2：
+//!
+//! - CPU：v2 的 `cpu.max`，v1 的 `cpu.cfs_quota_us` / `cpu.cfs_period_us`，配额向上取整为核心数
+//! - 内存：v2 的 `memory.max` / `memory.current`，v1 的 `memory.limit_in_bytes` / `memory.usage_in_bytes`，
+//!   限制减去已用量即为 cgroup 内的可用内存
+//!
+//! 最终的核心数和可用内存取主机值与 cgroup 值中较小的一个。
+
+use crate::buffer::MIN_BUFFER_SIZE;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+
+/// 低内存阈值：可用内存低于该值时降级
+pub const LOW_MEMORY_THRESHOLD: u64 = 512 * 1024 * 1024; // 512MB
+
+/// cgroup 文件系统的挂载点
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// cgroup v1 中不小于该值的内存限制表示不限制（内核以接近 `i64::MAX` 的页对齐值表示）
+const UNLIMITED_MEMORY: u64 = 1 << 62;
+
+/// 当前进程所在 cgroup 的资源限制
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CgroupLimits {
+    /// CPU 配额（核心数，可以是小数，如 `1.5`），不限制时为 `None`
+    pub cpu_quota: Option<f64>,
+    /// 内存限制（字节），不限制时为 `None`
+    pub memory_limit: Option<u64>,
+    /// 已用内存（字节）
+    pub memory_usage: Option<u64>,
+}
+
+impl CgroupLimits {
+    /// 读取当前进程所在 cgroup 的限制
+    ///
+    /// 不在 Linux 上或没有 cgroup 时返回全部不限制。
+    pub fn detect() -> Self {
+        if !cfg!(target_os = "linux") {
+            return Self::default();
+        }
+        let self_cgroup = fs::read_to_string("/proc/self/cgroup").unwrap_or_default();
+        Self::read(Path::new(CGROUP_ROOT), &self_cgroup)
+    }
+
+    /// 从给定的 cgroup 挂载点读取限制
+    ///
+    /// # 参数
+    ///
+    /// * `root` - cgroup 文件系统的挂载点（通常为 `/sys/fs/cgroup`）
+    /// * `self_cgroup` - `/proc/self/cgroup` 的内容，用于定位进程所在的 cgroup 目录
+    pub fn read(root: &Path, self_cgroup: &str) -> Self {
+        // cgroup v2：统一层级，控制文件都在同一个目录
+        if root.join("cgroup.controllers").exists() {
+            let dir = cgroup_dir(root, self_cgroup_path(self_cgroup, None), "cpu.max");
+            return Self {
+                cpu_quota: read_file(&dir, "cpu.max").and_then(|value| parse_cpu_max(&value)),
+                memory_limit: read_file(&dir, "memory.max").and_then(|value| parse_memory(&value)),
+                memory_usage: read_file(&dir, "memory.current")
+                    .and_then(|value| parse_memory(&value)),
+            };
+        }
+
+        // cgroup v1：每个控制器单独挂载
+        let cpu_root = ["cpu,cpuacct", "cpu"]
+            .iter()
+            .map(|name| root.join(name))
+            .find(|dir| dir.exists());
+        let cpu_quota = cpu_root.and_then(|cpu_root| {
+            let dir = cgroup_dir(
+                &cpu_root,
+                self_cgroup_path(self_cgroup, Some("cpu")),
+                "cpu.cfs_quota_us",
+            );
+            let quota = read_file(&dir, "cpu.cfs_quota_us")?.trim().parse().ok()?;
+            let period = read_file(&dir, "cpu.cfs_period_us")?.trim().parse().ok()?;
+            cpu_quota_v1(quota, period)
+        });
+        let memory_root = root.join("memory");
+        let dir = cgroup_dir(
+            &memory_root,
+            self_cgroup_path(self_cgroup, Some("memory")),
+            "memory.limit_in_bytes",
+        );
+        Self {
+            cpu_quota,
+            memory_limit: read_file(&dir, "memory.limit_in_bytes")
+                .and_then(|value| parse_memory(&value)),
+            memory_usage: read_file(&dir, "memory.usage_in_bytes")
+                .and_then(|value| parse_memory(&value)),
+        }
+    }
+
+    /// CPU 配额向上取整后的核心数（至少为 1）
+    pub fn cores(&self) -> Option<usize> {
+        self.cpu_quota.map(|quota| (quota.ceil() as usize).max(1))
+    }
+
+    /// cgroup 内的可用内存：限制减去已用量
+    pub fn available_memory(&self) -> Option<u64> {
+        self.memory_limit
+            .map(|limit| limit.saturating_sub(self.memory_usage.unwrap_or(0)))
+    }
+}
+
+/// 检测到的资源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resources {
+    /// 可用的 CPU 核心数
+    pub cores: usize,
+    /// 可用内存（字节），无法检测时为 `None`
+    pub available_memory: Option<u64>,
+}
+
+impl Resources {
+    /// 检测当前环境的资源
+    ///
+    /// 核心数来自 [`std::thread::available_parallelism`]；
+    /// 可用内存在 Linux 上读取 `/proc/meminfo` 的 `MemAvailable`，其他平台无法检测。
+    /// 两者都再受 cgroup 限制（[`CgroupLimits::detect`]）约束。
+    pub fn detect() -> Self {
+        let cores = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let available_memory = if cfg!(target_os = "linux") {
+            fs::read_to_string("/proc/meminfo")
+                .ok()
+                .and_then(|meminfo| parse_meminfo(&meminfo))
+        } else {
+            None
+        };
+        Self::with_limits(cores, available_memory, &CgroupLimits::detect())
+    }
+
+    /// 用 cgroup 限制约束主机资源
+    ///
+    /// # 参数
+    ///
+    /// * `host_cores` - 主机（或调度器允许）的核心数
+    /// * `host_memory` - 主机的可用内存
+    /// * `limits` - cgroup 限制
+    ///
+    /// # 返回值
+    ///
+    /// 核心数和可用内存分别取主机值与 cgroup 值中较小的一个
+    pub fn with_limits(host_cores: usize, host_memory: Option<u64>, limits: &CgroupLimits) -> Self {
+        let cores = match limits.cores() {
+            Some(cores) => host_cores.min(cores),
+            None => host_cores,
+        };
+        let available_memory = match (host_memory, limits.available_memory()) {
+            (Some(host), Some(cgroup)) => Some(host.min(cgroup)),
+            (host, cgroup) => host.or(cgroup),
+        };
+        Self {
+            cores,
+            available_memory,
+        }
+    }
+
+    /// 是否处于低内存环境（无法检测可用内存时视为否）
+    pub fn is_low_memory(&self) -> bool {
+        self.available_memory
+            .is_some_and(|memory| memory < LOW_MEMORY_THRESHOLD)
+    }
+
+    /// 未指定 `--jobs` 时使用的线程数：低内存时为 1，否则为核心数
+    pub fn default_jobs(&self) -> usize {
+        if self.is_low_memory() {
+            1
+        } else {
+            self.cores.max(1)
+        }
+    }
+
+    /// 自动选择的读取缓冲区的上限：低内存时为 [`MIN_BUFFER_SIZE`]，否则不限制
+    pub fn buffer_cap(&self) -> Option<usize> {
+        self.is_low_memory().then_some(MIN_BUFFER_SIZE)
+    }
+}
+
+/// 从 `/proc/meminfo` 的内容中读取 `MemAvailable`
+///
+/// # 参数
+///
+/// * `meminfo` - `/proc/meminfo` 的内容
+///
+/// # 返回值
+///
+/// * `Option<u64>` - 可用内存（字节）；没有该字段（如 3.14 之前的内核）时为 `None`
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kilobytes: u64 = line.trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// 解析 cgroup v2 的 `cpu.max`
+///
+/// # 参数
+///
+/// * `value` - 文件内容，格式为 `配额 周期`，配额为 `max` 表示不限制
+///
+/// # 返回值
+///
+/// * `Option<f64>` - 配额对应的核心数；不限制或格式错误时为 `None`
+fn parse_cpu_max(value: &str) -> Option<f64> {
+    let mut fields = value.split_whitespace();
+    let quota = fields.next()?;
+    let period: u64 = fields
+        .next()
+        .map_or(Some(100_000), |period| period.parse().ok())?;
+    if quota == "max" {
+        return None;
+    }
+    cpu_quota_v1(quota.parse().ok()?, period)
+}
+
+/// 根据 CFS 配额和周期计算核心数
+///
+/// # 参数
+///
+/// * `quota` - 每个周期内可用的 CPU 时间（微秒），`-1` 表示不限制
+/// * `period` - 周期（微秒）
+fn cpu_quota_v1(quota: i64, period: u64) -> Option<f64> {
+    if quota <= 0 || period == 0 {
+        return None;
+    }
+    Some(quota as f64 / period as f64)
+}
+
+/// 解析 cgroup 的内存限制或用量
+///
+/// # 参数
+///
+/// * `value` - 文件内容：字节数，或 v2 中表示不限制的 `max`
+///
+/// # 返回值
+///
+/// * `Option<u64>` - 字节数；不限制（包括 v1 中接近 `i64::MAX` 的值）或格式错误时为 `None`
+fn parse_memory(value: &str) -> Option<u64> {
+    let bytes: u64 = value.trim().parse().ok()?;
+    (bytes < UNLIMITED_MEMORY).then_some(bytes)
+}
+
+/// 从 `/proc/self/cgroup` 中找出进程所在的 cgroup 路径
+///
+/// # 参数
+///
+/// * `self_cgroup` - `/proc/self/cgroup` 的内容，每行格式为 `层级ID:控制器列表:路径`
+/// * `controller` - cgroup v1 的控制器名称；`None` 表示 v2 的统一层级（控制器列表为空）
+fn self_cgroup_path<'a>(self_cgroup: &'a str, controller: Option<&str>) -> Option<&'a str> {
+    self_cgroup.lines().find_map(|line| {
+        let mut fields = line.splitn(3, ':');
+        let _id = fields.next()?;
+        let controllers = fields.next()?;
+        let path = fields.next()?;
+        let matches = match controller {
+            Some(controller) => controllers.split(',').any(|name| name == controller),
+            None => controllers.is_empty(),
+        };
+        matches.then_some(path)
+    })
+}
+
+/// 确定读取控制文件的 cgroup 目录
+///
+/// 容器内 `/proc/self/cgroup` 中的路径可能是主机视角的路径，在容器内不存在，
+/// 此时退回到挂载点本身（容器的 cgroup 命名空间通常就挂载在这里）。
+fn cgroup_dir(root: &Path, path: Option<&str>, probe: &str) -> PathBuf {
+    path.map(|path| root.join(path.trim_start_matches('/')))
+        .filter(|dir| dir.join(probe).exists())
+        .unwrap_or_else(|| root.to_path_buf())
+}
+
+/// 读取 cgroup 控制文件
+fn read_file(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试解析 `/proc/meminfo`
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       16318480 kB\nMemFree:         1234567 kB\nMemAvailable:    8159240 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(8159240 * 1024));
+        assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+        assert_eq!(parse_meminfo("MemAvailable: many kB\n"), None);
+    }
+
+    /// 测试低内存时的降级策略
+    #[test]
+    fn test_low_memory_policy() {
+        let plenty = Resources {
+            cores: 8,
+            available_memory: Some(8 * 1024 * 1024 * 1024),
+        };
+        assert!(!plenty.is_low_memory());
+        assert_eq!(plenty.default_jobs(), 8);
+        assert_eq!(plenty.buffer_cap(), None);
+
+        let constrained = Resources {
+            cores: 8,
+            available_memory: Some(256 * 1024 * 1024),
+        };
+        assert!(constrained.is_low_memory());
+        assert_eq!(constrained.default_jobs(), 1);
+        assert_eq!(constrained.buffer_cap(), Some(MIN_BUFFER_SIZE));
+
+        let unknown = Resources {
+            cores: 4,
+            available_memory: None,
+        };
+        assert!(!unknown.is_low_memory());
+        assert_eq!(unknown.default_jobs(), 4);
+    }
+
+    /// 测试 cgroup 控制文件的解析
+    #[test]
+    fn test_parse_cgroup_values() {
+        assert_eq!(parse_cpu_max("max 100000\n"), None);
+        assert_eq!(parse_cpu_max("200000 100000\n"), Some(2.0));
+        assert_eq!(parse_cpu_max("150000 100000"), Some(1.5));
+        assert_eq!(cpu_quota_v1(-1, 100_000), None);
+        assert_eq!(cpu_quota_v1(50_000, 100_000), Some(0.5));
+
+        assert_eq!(parse_memory("max\n"), None);
+        assert_eq!(parse_memory("536870912\n"), Some(536870912));
+        assert_eq!(parse_memory("9223372036854771712\n"), None);
+
+        let self_cgroup =
+            "12:memory:/kubepods/pod1\n4:cpu,cpuacct:/kubepods/pod1\n0::/kubepods/pod1\n";
+        assert_eq!(self_cgroup_path(self_cgroup, None), Some("/kubepods/pod1"));
+        assert_eq!(
+            self_cgroup_path(self_cgroup, Some("cpu")),
+            Some("/kubepods/pod1")
+        );
+        assert_eq!(self_cgroup_path(self_cgroup, Some("pids")), None);
+    }
+
+    /// 测试从 cgroup v2 层级读取限制
+    #[test]
+    fn test_read_cgroup_v2() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let dir = root.join("kubepods/pod1");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(root.join("cgroup.controllers"), "cpu memory\n").unwrap();
+        fs::write(dir.join("cpu.max"), "150000 100000\n").unwrap();
+        fs::write(dir.join("memory.max"), "1073741824\n").unwrap();
+        fs::write(dir.join("memory.current"), "805306368\n").unwrap();
+
+        let limits = CgroupLimits::read(root, "0::/kubepods/pod1\n");
+        assert_eq!(limits.cores(), Some(2));
+        assert_eq!(limits.available_memory(), Some(256 * 1024 * 1024));
+
+        // 路径在容器内不存在时读取挂载点本身
+        fs::write(root.join("cpu.max"), "max 100000\n").unwrap();
+        let limits = CgroupLimits::read(root, "0::/host/path\n");
+        assert_eq!(limits.cores(), None);
+        assert_eq!(limits.available_memory(), None);
+    }
+
+    /// 测试从 cgroup v1 层级读取限制
+    #[test]
+    fn test_read_cgroup_v1() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("cpu,cpuacct")).unwrap();
+        fs::create_dir_all(root.join("memory")).unwrap();
+        fs::write(root.join("cpu,cpuacct/cpu.cfs_quota_us"), "400000\n").unwrap();
+        fs::write(root.join("cpu,cpuacct/cpu.cfs_period_us"), "100000\n").unwrap();
+        fs::write(
+            root.join("memory/memory.limit_in_bytes"),
+            "9223372036854771712\n",
+        )
+        .unwrap();
+        fs::write(root.join("memory/memory.usage_in_bytes"), "1024\n").unwrap();
+
+        let limits = CgroupLimits::read(root, "4:cpu,cpuacct:/\n12:memory:/\n");
+        assert_eq!(limits.cores(), Some(4));
+        assert_eq!(limits.memory_limit, None);
+        assert_eq!(limits.available_memory(), None);
+    }
+
+    /// 测试 cgroup 限制对主机资源的约束
+    #[test]
+    fn test_with_limits() {
+        let limits = CgroupLimits {
+            cpu_quota: Some(2.0),
+            memory_limit: Some(300 * 1024 * 1024),
+            memory_usage: Some(100 * 1024 * 1024),
+        };
+        let resources = Resources::with_limits(64, Some(64 * 1024 * 1024 * 1024), &limits);
+        assert_eq!(resources.cores, 2);
+        assert_eq!(resources.available_memory, Some(200 * 1024 * 1024));
+        assert!(resources.is_low_memory());
+
+        let resources = Resources::with_limits(64, None, &CgroupLimits::default());
+        assert_eq!(resources.cores, 64);
+        assert_eq!(resources.available_memory, None);
+    }
+}
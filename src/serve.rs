@@ -0,0 +1,475 @@
+//! HTTP 服务模式
+//!
+//! `line-counter serve --port 8080` 以 HTTP 接口提供统计功能，其他语言编写的服务不必启动子进程：
+//!
+//! - `POST /count`：统计请求的内容，`?name=foo.rs` 指定报告中的文件名（同时用于识别语言），默认为 `<stdin>`
+//! - `GET /count?path=src`：统计根目录（`--root`，默认为当前目录）中的文件或目录（递归），
+//!   路径相对于根目录，不能指向根目录之外；遍历目录时不跟随符号链接
+//!
+//! 成功时返回与 `--format json` 结构相同的报告，报告中的路径相对于根目录。
+//! 失败时返回 `{"error": "..."}` 和对应的状态码：参数无效为 400，路径在根目录之外或匹配 `never_read`
+//! 规则为 403，路径不存在为 404，方法不支持为 405，内容超过 `--max-size` 为 413，其余为 500。
+//!
+//! 默认只监听本机地址；用 `--host 0.0.0.0` 对外提供服务时需要自行做好访问控制。
+
+use crate::app::{ReadOptions, count_files, count_reader, error_chain};
+use crate::cli::ServeArgs;
+use crate::config::NeverRead;
+use crate::counter::CountOptions;
+use crate::error::LineCounterError;
+use crate::headers::HeaderOptions;
+use crate::input::STDIN_DISPLAY_NAME;
+use crate::locale::Lang;
+use crate::meta::RunMetadata;
+use crate::output::{self, Style};
+use crate::report::FileReport;
+use crate::resources::Resources;
+use crate::walk::{SymlinkPolicy, collect_files};
+use anyhow::{Context, Result, anyhow};
+use percent_encoding::percent_decode_str;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tiny_http::{Header, Request, Server};
+
+/// 带状态码的请求错误
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+struct RequestError {
+    /// HTTP 状态码
+    status: u16,
+    /// 错误信息
+    message: String,
+}
+
+/// 请求的处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// HTTP 状态码
+    pub status: u16,
+    /// JSON 格式的响应内容
+    pub body: String,
+}
+
+/// 处理请求所需的状态，由所有工作线程共享
+#[derive(Debug)]
+pub struct Service {
+    /// 根目录（解析后的绝对路径）
+    root: PathBuf,
+    /// 服务的参数，用于报告中的配置哈希
+    args: ServeArgs,
+    options: CountOptions,
+    read_options: ReadOptions,
+    never_read: NeverRead,
+    lang: Lang,
+}
+
+/// 运行 HTTP 服务
+///
+/// 启动后在标准输出中输出实际监听的地址（`--port 0` 时由系统分配端口），之后不再返回。
+///
+/// # 参数
+///
+/// * `args` - `serve` 子命令的参数
+/// * `never_read` - 配置中永远不读取的路径，请求中匹配的路径返回 403
+/// * `lang` - 错误信息的语言
+///
+/// # 返回值
+///
+/// 只有在根目录无效或无法监听时返回错误
+pub fn run(args: &ServeArgs, never_read: NeverRead, lang: Lang) -> Result<()> {
+    let service = Arc::new(Service::new(args, never_read, lang)?);
+    let server = Server::http((args.host.as_str(), args.port))
+        .map_err(|e| anyhow!(e))
+        .with_context(|| format!("无法监听 {}:{}", args.host, args.port))?;
+    let address = server.server_addr().to_ip().map_or_else(
+        || format!("{}:{}", args.host, args.port),
+        |addr| addr.to_string(),
+    );
+    let style = Style::from_options(false, false, lang);
+    println!(
+        "{}{}",
+        style.icon("🌐 "),
+        style.format("正在监听 http://{}", &[&address])
+    );
+
+    let server = Arc::new(server);
+    let workers: Vec<_> = (0..Resources::detect().default_jobs())
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let service = Arc::clone(&service);
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    service.respond(request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+impl Service {
+    /// 创建服务
+    ///
+    /// # 参数
+    ///
+    /// * `args` - `serve` 子命令的参数
+    /// * `never_read` - 配置中永远不读取的路径
+    /// * `lang` - 错误信息的语言
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(Service)` - 服务
+    /// * `Err(anyhow::Error)` - 根目录不存在或匹配 `never_read` 规则
+    pub fn new(args: &ServeArgs, never_read: NeverRead, lang: Lang) -> Result<Self> {
+        never_read.check(&args.root)?;
+        let root = args
+            .root
+            .canonicalize()
+            .with_context(|| format!("无法解析路径 '{}'", args.root.display()))?;
+        Ok(Self {
+            root,
+            args: args.clone(),
+            options: CountOptions {
+                headers: HeaderOptions {
+                    exclude: args.exclude_headers,
+                    ..HeaderOptions::default()
+                },
+                ..CountOptions::default()
+            },
+            read_options: ReadOptions {
+                max_size: args.max_size,
+                comments: args.comments,
+                ..ReadOptions::default()
+            },
+            never_read,
+            lang,
+        })
+    }
+
+    /// 处理请求并发送响应
+    fn respond(&self, mut request: Request) {
+        let method = request.method().as_str().to_string();
+        let url = request.url().to_string();
+        let response = self.handle(&method, &url, request.as_reader());
+        let content_type = Header::from_bytes("Content-Type", "application/json; charset=utf-8")
+            .expect("内容类型是有效的 HTTP 头");
+        // 客户端提前断开时无法发送响应，不影响其他请求
+        let _ = request.respond(
+            tiny_http::Response::from_string(response.body)
+                .with_status_code(response.status)
+                .with_header(content_type),
+        );
+    }
+
+    /// 处理请求
+    ///
+    /// # 参数
+    ///
+    /// * `method` - 请求方法
+    /// * `url` - 请求的路径和查询字符串
+    /// * `body` - 请求的内容
+    ///
+    /// # 返回值
+    ///
+    /// 状态码和 JSON 格式的响应内容
+    pub fn handle(&self, method: &str, url: &str, body: impl Read) -> Response {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let result = match (method, path) {
+            ("POST", "/count") => self.count_body(query, body),
+            ("GET", "/count") => self.count_path(query),
+            (_, "/count") => Err(RequestError {
+                status: 405,
+                message: "只支持 GET 和 POST 请求".to_string(),
+            }
+            .into()),
+            _ => Err(RequestError {
+                status: 404,
+                message: format!("未知的路径: {}", path),
+            }
+            .into()),
+        };
+        match result {
+            Ok(body) => Response { status: 200, body },
+            Err(e) => Response {
+                status: status_code(&e),
+                body: serde_json::json!({ "error": error_chain(&e, self.lang).join(": ") })
+                    .to_string(),
+            },
+        }
+    }
+
+    /// 统计请求的内容
+    fn count_body(&self, query: &str, body: impl Read) -> Result<String> {
+        let started = SystemTime::now();
+        let name = query_param(query, "name").unwrap_or_else(|| STDIN_DISPLAY_NAME.to_string());
+        let limit = match self.read_options.max_size {
+            0 => u64::MAX,
+            max_size => max_size,
+        };
+        let mut data = Vec::new();
+        body.take(limit.saturating_add(1))
+            .read_to_end(&mut data)
+            .context("读取请求内容时发生错误")?;
+        if data.len() as u64 > limit {
+            return Err(RequestError {
+                status: 413,
+                message: format!("请求内容超过上限 {} bytes", limit),
+            }
+            .into());
+        }
+
+        let report = count_reader(
+            data.as_slice(),
+            PathBuf::from(name),
+            &self.options,
+            &self.read_options,
+        )?;
+        self.render(started, &[report])
+    }
+
+    /// 统计根目录中的文件或目录
+    fn count_path(&self, query: &str) -> Result<String> {
+        let started = SystemTime::now();
+        let Some(requested) = query_param(query, "path") else {
+            return Err(RequestError {
+                status: 400,
+                message: "缺少参数 path".to_string(),
+            }
+            .into());
+        };
+        let path = self.root.join(&requested);
+        let resolved = match path.canonicalize() {
+            Ok(resolved) => resolved,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(LineCounterError::FileNotFound { path: requested }.into());
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("无法解析路径 '{}'", requested));
+            }
+        };
+        if !resolved.starts_with(&self.root) {
+            return Err(RequestError {
+                status: 403,
+                message: format!("路径 '{}' 不在服务的根目录中", requested),
+            }
+            .into());
+        }
+        self.never_read.check_under(&path, &self.root)?;
+
+        let is_dir = resolved.is_dir();
+        let files = if is_dir {
+            let mut files = collect_files(&[resolved], true, SymlinkPolicy::NoFollow)?;
+            files.retain(|file| self.never_read.matching_under(file, &self.root).is_none());
+            files
+        } else {
+            vec![resolved]
+        };
+        // 目录中单个文件统计失败不影响其他文件，与命令行的 `--format json` 一样报告为统计失败
+        let read_options = ReadOptions {
+            keep_going: is_dir,
+            ..self.read_options.clone()
+        };
+        let mut reports = count_files(&files, self.args.jobs, &self.options, &read_options)?;
+        for report in &mut reports {
+            if let Ok(relative) = report.path.strip_prefix(&self.root) {
+                report.path = relative.to_path_buf();
+            }
+        }
+        self.render(started, &reports)
+    }
+
+    /// 生成与 `--format json` 结构相同的报告
+    fn render(&self, started: SystemTime, reports: &[FileReport]) -> Result<String> {
+        let meta = RunMetadata::new(
+            std::env::args().collect(),
+            &self.args,
+            started,
+            SystemTime::now(),
+        );
+        Ok(output::render_json(&meta, reports, false, None, None)?)
+    }
+}
+
+/// 错误对应的状态码
+fn status_code(error: &anyhow::Error) -> u16 {
+    for cause in error.chain() {
+        if let Some(error) = cause.downcast_ref::<RequestError>() {
+            return error.status;
+        }
+        if let Some(error) = cause.downcast_ref::<LineCounterError>() {
+            return match error {
+                LineCounterError::FileNotFound { .. } => 404,
+                LineCounterError::NeverRead { .. } | LineCounterError::PermissionDenied { .. } => {
+                    403
+                }
+                LineCounterError::FileTooLarge { .. } => 413,
+                _ => 500,
+            };
+        }
+    }
+    500
+}
+
+/// 查询字符串中参数的值（按 `application/x-www-form-urlencoded` 解码）
+///
+/// # 参数
+///
+/// * `query` - 查询字符串（不含 `?`）
+/// * `name` - 参数名
+///
+/// # 返回值
+///
+/// 第一个同名参数的值，没有该参数时为 `None`
+fn query_param(query: &str, name: &str) -> Option<String> {
+    let decode = |text: &str| {
+        percent_decode_str(&text.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    query
+        .split('&')
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .find(|(key, _)| decode(key) == name)
+        .map(|(_, value)| decode(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// 根目录为 `root` 的服务
+    fn service(root: &Path, never_read: NeverRead) -> Service {
+        let args = ServeArgs {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            root: root.to_path_buf(),
+            exclude_headers: false,
+            comments: false,
+            max_size: 16,
+            jobs: 1,
+        };
+        Service::new(&args, never_read, Lang::Zh).unwrap()
+    }
+
+    /// 响应中的 JSON
+    fn json(response: &Response) -> serde_json::Value {
+        serde_json::from_str(&response.body).unwrap()
+    }
+
+    /// 报告中的路径，用于测试
+    fn paths(body: &str) -> Vec<String> {
+        let report: serde_json::Value = serde_json::from_str(body).unwrap();
+        report["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| file["path"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    /// 测试查询字符串的解析
+    #[test]
+    fn test_query_param() {
+        assert_eq!(
+            query_param("path=src%2F%E4%B8%AD.rs&name=a+b", "path").as_deref(),
+            Some("src/中.rs")
+        );
+        assert_eq!(
+            query_param("path=a&name=a+b", "name").as_deref(),
+            Some("a b")
+        );
+        assert_eq!(query_param("flag&path=", "flag").as_deref(), Some(""));
+        assert_eq!(query_param("", "path"), None);
+    }
+
+    /// 测试统计请求的内容
+    #[test]
+    fn test_count_body() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let service = service(temp_dir.path(), NeverRead::default());
+
+        let response = service.handle("POST", "/count?name=main.rs", &b"fn main() {\n\n}\n"[..]);
+        assert_eq!(response.status, 200);
+        let report = json(&response);
+        assert_eq!(report["summary"]["stats"]["total_lines"], 3);
+        assert_eq!(report["files"][0]["path"], "main.rs");
+        assert_eq!(report["files"][0]["language"], "Rust");
+
+        let response = service.handle("POST", "/count", &b"a\n"[..]);
+        assert_eq!(json(&response)["files"][0]["path"], STDIN_DISPLAY_NAME);
+
+        let response = service.handle("POST", "/count", &[b'a'; 17][..]);
+        assert_eq!(response.status, 413);
+        assert_eq!(json(&response)["error"], "请求内容超过上限 16 bytes");
+    }
+
+    /// 测试统计根目录中的文件和目录，以及路径的限制
+    #[test]
+    fn test_count_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().join("root");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.txt"), "1\n2\n").unwrap();
+        fs::write(root.join("src/b.txt"), "1\n").unwrap();
+        fs::write(root.join("key.pem"), "secret\n").unwrap();
+        fs::create_dir_all(root.join("config/secrets")).unwrap();
+        fs::write(root.join("config/secrets/key"), "secret\n").unwrap();
+        fs::write(temp_dir.path().join("outside.txt"), "1\n").unwrap();
+        let never_read = NeverRead::try_from(vec![
+            "**/*.pem".to_string(),
+            "config/secrets/**".to_string(),
+        ])
+        .unwrap();
+        let service = service(&root, never_read);
+
+        let response = service.handle("GET", "/count?path=src", io::empty());
+        assert_eq!(response.status, 200);
+        assert_eq!(paths(&response.body), ["src/a.txt", "src/b.txt"]);
+        assert_eq!(json(&response)["summary"]["stats"]["total_lines"], 3);
+
+        let response = service.handle("GET", "/count?path=.", io::empty());
+        assert_eq!(paths(&response.body), ["src/a.txt", "src/b.txt"]);
+
+        // 目录中单个文件统计失败时其余文件照常统计
+        fs::write(root.join("src/large.txt"), "0123456789\n0123456789\n").unwrap();
+        let response = service.handle("GET", "/count?path=src", io::empty());
+        assert_eq!(response.status, 200);
+        let report = json(&response);
+        assert_eq!(report["summary"]["files"], 2);
+        assert_eq!(report["summary"]["failed"], 1);
+        assert_eq!(report["files"][2]["path"], "src/large.txt");
+        assert!(report["files"][2]["error"].is_string());
+        let response = service.handle("GET", "/count?path=src/large.txt", io::empty());
+        assert_ne!(response.status, 200);
+        fs::remove_file(root.join("src/large.txt")).unwrap();
+
+        let response = service.handle("GET", "/count?path=config", io::empty());
+        assert_eq!(response.status, 200);
+        assert!(paths(&response.body).is_empty());
+
+        let response = service.handle("GET", "/count?path=src/a.txt", io::empty());
+        assert_eq!(paths(&response.body), ["src/a.txt"]);
+
+        for (url, status) in [
+            ("/count?path=../outside.txt", 403),
+            ("/count?path=key.pem", 403),
+            ("/count?path=config/secrets/key", 403),
+            ("/count?path=./config/secrets/key", 403),
+            ("/count?path=missing.txt", 404),
+            ("/count", 400),
+            ("/other", 404),
+        ] {
+            let response = service.handle("GET", url, io::empty());
+            assert_eq!(response.status, status, "{}", url);
+            assert!(json(&response)["error"].is_string());
+        }
+        assert_eq!(service.handle("DELETE", "/count", io::empty()).status, 405);
+    }
+}
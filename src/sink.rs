@@ -0,0 +1,387 @@
+//! 输出目标
+//!
+//! [`ReportSink`] 依次接收每个文件的统计结果和最后的汇总。内置的输出格式（可读文本、JSON、
+//! Markdown 和徽章）都实现了这个 trait；作为库使用时可以自行实现，把结果直接写入自己的存储
+//! （数据库、消息队列等），不需要先渲染为 JSON 文本再解析。
+//!
+//! 内置格式需要看到全部文件才能决定输出（单个文件和多个文件的可读文本不同，JSON 是一个完整的文档），
+//! 因此它们先缓存收到的结果，在 [`ReportSink::write_summary`] 中一次输出。
+
+use crate::git::AuthorShare;
+use crate::group::{self, Grouping};
+use crate::language;
+use crate::meta::RunMetadata;
+use crate::output::{self, Style};
+use crate::report::{FileReport, Summary};
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// 统计结果的输出目标
+pub trait ReportSink {
+    /// 写入单个文件的统计结果，按输出顺序对每个文件调用一次
+    ///
+    /// # 参数
+    ///
+    /// * `report` - 单个文件的统计报告
+    fn write_file_result(&mut self, report: &FileReport) -> io::Result<()>;
+
+    /// 写入汇总结果，在所有文件之后调用一次
+    ///
+    /// # 参数
+    ///
+    /// * `summary` - 所有文件的汇总
+    /// * `elapsed` - 本次统计的耗时
+    fn write_summary(&mut self, summary: &Summary, elapsed: Duration) -> io::Result<()>;
+}
+
+/// 把统计报告依次写入输出目标
+///
+/// # 参数
+///
+/// * `sink` - 输出目标
+/// * `reports` - 按输出顺序排列的统计报告
+/// * `elapsed` - 本次统计的耗时
+///
+/// # 返回值
+///
+/// * `Ok(())` - 全部写入
+/// * `Err(io::Error)` - 输出目标写入失败
+pub fn write_reports(
+    sink: &mut dyn ReportSink,
+    reports: &[FileReport],
+    elapsed: Duration,
+) -> io::Result<()> {
+    for report in reports {
+        sink.write_file_result(report)?;
+    }
+    sink.write_summary(&Summary::from_reports(reports), elapsed)
+}
+
+/// 统计结果之外的附加部分
+#[derive(Debug, Clone, Default)]
+pub struct Sections {
+    /// 是否包含语言分布
+    pub languages: bool,
+    /// 分组汇总的设置，`None` 表示不包含分组汇总
+    pub grouping: Option<Grouping>,
+    /// 按作者统计的行数，`None` 表示不包含
+    pub authors: Option<Vec<AuthorShare>>,
+}
+
+/// 可读文本
+#[derive(Debug)]
+pub struct HumanSink<W> {
+    writer: W,
+    style: Style,
+    top_lines: usize,
+    sections: Sections,
+    reports: Vec<FileReport>,
+}
+
+impl<W: Write> HumanSink<W> {
+    /// 创建可读文本的输出目标
+    ///
+    /// # 参数
+    ///
+    /// * `writer` - 写入可读文本的位置
+    /// * `style` - 输出风格
+    /// * `top_lines` - 每个文件输出的最长行数，0 表示不输出
+    /// * `sections` - 附加部分
+    pub fn new(writer: W, style: Style, top_lines: usize, sections: Sections) -> Self {
+        Self {
+            writer,
+            style,
+            top_lines,
+            sections,
+            reports: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> ReportSink for HumanSink<W> {
+    fn write_file_result(&mut self, report: &FileReport) -> io::Result<()> {
+        self.reports.push(report.clone());
+        Ok(())
+    }
+
+    fn write_summary(&mut self, _summary: &Summary, elapsed: Duration) -> io::Result<()> {
+        let out = &mut self.writer;
+        let reports = &self.reports;
+        let style = &self.style;
+        output::write_human(out, reports, elapsed, style)?;
+        if self.top_lines > 0 {
+            output::write_top_lines(out, reports, self.top_lines, style)?;
+        }
+        output::write_todos(out, reports, style)?;
+        if let Some(grouping) = self
+            .sections
+            .grouping
+            .as_ref()
+            .filter(|_| reports.len() > 1)
+        {
+            output::write_groups(out, grouping.by, &group::group(reports, grouping), style)?;
+        }
+        if self.sections.languages {
+            output::write_languages(out, &language::breakdown(reports), style)?;
+        }
+        if let Some(authors) = &self.sections.authors {
+            output::write_authors(out, authors, style)?;
+        }
+        Ok(())
+    }
+}
+
+/// 带运行元数据的 JSON 报告
+#[derive(Debug)]
+pub struct JsonSink<W> {
+    writer: W,
+    meta: RunMetadata,
+    sections: Sections,
+    reports: Vec<FileReport>,
+}
+
+impl<W: Write> JsonSink<W> {
+    /// 创建 JSON 报告的输出目标
+    ///
+    /// # 参数
+    ///
+    /// * `writer` - 写入 JSON 文本的位置
+    /// * `meta` - 运行元数据
+    /// * `sections` - 附加部分
+    pub fn new(writer: W, meta: RunMetadata, sections: Sections) -> Self {
+        Self {
+            writer,
+            meta,
+            sections,
+            reports: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> ReportSink for JsonSink<W> {
+    fn write_file_result(&mut self, report: &FileReport) -> io::Result<()> {
+        self.reports.push(report.clone());
+        Ok(())
+    }
+
+    fn write_summary(&mut self, _summary: &Summary, _elapsed: Duration) -> io::Result<()> {
+        let json = output::render_json(
+            &self.meta,
+            &self.reports,
+            self.sections.languages,
+            self.sections.grouping.as_ref(),
+            self.sections.authors.as_deref(),
+        )?;
+        writeln!(self.writer, "{}", json)
+    }
+}
+
+/// GitHub 风格的 Markdown 表格
+#[derive(Debug)]
+pub struct MarkdownSink<W> {
+    writer: W,
+    sections: Sections,
+    precision: usize,
+    reports: Vec<FileReport>,
+}
+
+impl<W: Write> MarkdownSink<W> {
+    /// 创建 Markdown 表格的输出目标
+    ///
+    /// # 参数
+    ///
+    /// * `writer` - 写入 Markdown 文本的位置
+    /// * `sections` - 附加部分
+    /// * `precision` - 百分比保留的小数位数
+    pub fn new(writer: W, sections: Sections, precision: usize) -> Self {
+        Self {
+            writer,
+            sections,
+            precision,
+            reports: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> ReportSink for MarkdownSink<W> {
+    fn write_file_result(&mut self, report: &FileReport) -> io::Result<()> {
+        self.reports.push(report.clone());
+        Ok(())
+    }
+
+    fn write_summary(&mut self, _summary: &Summary, elapsed: Duration) -> io::Result<()> {
+        let markdown = output::render_markdown(
+            &self.reports,
+            elapsed,
+            self.sections.languages,
+            self.sections.grouping.as_ref(),
+            self.sections.authors.as_deref(),
+            self.precision,
+        );
+        self.writer.write_all(markdown.as_bytes())
+    }
+}
+
+/// shields.io 端点徽章的 JSON
+///
+/// 徽章只需要总行数，不缓存单个文件的结果。
+#[derive(Debug)]
+pub struct BadgeSink<W> {
+    writer: W,
+}
+
+impl<W: Write> BadgeSink<W> {
+    /// 创建徽章的输出目标
+    ///
+    /// # 参数
+    ///
+    /// * `writer` - 写入徽章 JSON 的位置
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> ReportSink for BadgeSink<W> {
+    fn write_file_result(&mut self, _report: &FileReport) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_summary(&mut self, summary: &Summary, _elapsed: Duration) -> io::Result<()> {
+        writeln!(self.writer, "{}", output::render_badge(summary)?)
+    }
+}
+
+/// 每行一个 `key=value` 指标
+///
+/// 每个文件的指标在收到时立即写入，不缓存。
+#[derive(Debug)]
+pub struct PlainKvSink<W> {
+    writer: W,
+    files: usize,
+    precision: usize,
+}
+
+impl<W: Write> PlainKvSink<W> {
+    /// 创建 `key=value` 指标的输出目标
+    ///
+    /// # 参数
+    ///
+    /// * `writer` - 写入指标的位置
+    /// * `precision` - 百分比保留的小数位数
+    pub fn new(writer: W, precision: usize) -> Self {
+        Self {
+            writer,
+            files: 0,
+            precision,
+        }
+    }
+}
+
+impl<W: Write> ReportSink for PlainKvSink<W> {
+    fn write_file_result(&mut self, report: &FileReport) -> io::Result<()> {
+        self.files += 1;
+        self.writer
+            .write_all(output::render_plain_kv_file(self.files, report).as_bytes())
+    }
+
+    fn write_summary(&mut self, summary: &Summary, _elapsed: Duration) -> io::Result<()> {
+        self.writer
+            .write_all(output::render_plain_kv_summary(summary, self.precision).as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{DEFAULT_PRECISION, PLAIN_KV_PRECISION};
+    use crate::stats::LineStats;
+    use std::path::PathBuf;
+    use std::time::SystemTime;
+
+    /// 测试用的报告
+    fn report(path: &str, total: usize) -> FileReport {
+        FileReport {
+            stats: LineStats::new(total, total, 0),
+            size: total as u64 * 2,
+            missing: false,
+            ..FileReport::missing(PathBuf::from(path))
+        }
+    }
+
+    /// 收集结果的自定义输出目标
+    #[derive(Default)]
+    struct Collect {
+        paths: Vec<PathBuf>,
+        summary: Option<Summary>,
+    }
+
+    impl ReportSink for Collect {
+        fn write_file_result(&mut self, report: &FileReport) -> io::Result<()> {
+            self.paths.push(report.path.clone());
+            Ok(())
+        }
+
+        fn write_summary(&mut self, summary: &Summary, _elapsed: Duration) -> io::Result<()> {
+            self.summary = Some(summary.clone());
+            Ok(())
+        }
+    }
+
+    /// 测试自定义输出目标按顺序收到每个文件和汇总
+    #[test]
+    fn test_custom_sink() {
+        let reports = vec![report("a.rs", 3), report("b.rs", 4)];
+        let mut sink = Collect::default();
+        write_reports(&mut sink, &reports, Duration::ZERO).unwrap();
+        assert_eq!(
+            sink.paths,
+            vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")]
+        );
+        assert_eq!(sink.summary.unwrap().stats.total_lines, 7);
+    }
+
+    /// 测试内置格式写入任意位置
+    #[test]
+    fn test_builtin_sinks() {
+        let reports = vec![report("a.rs", 3), report("b.rs", 4)];
+        let now = SystemTime::now();
+        let meta = RunMetadata::new(Vec::new(), &(), now, now);
+
+        let mut json = Vec::new();
+        let mut sink = JsonSink::new(&mut json, meta, Sections::default());
+        write_reports(&mut sink, &reports, Duration::ZERO).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["files"][1]["path"], "b.rs");
+        assert_eq!(value["summary"]["stats"]["total_lines"], 7);
+
+        let mut markdown = Vec::new();
+        write_reports(
+            &mut MarkdownSink::new(&mut markdown, Sections::default(), DEFAULT_PRECISION),
+            &reports,
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert!(String::from_utf8(markdown).unwrap().contains("**7**"));
+
+        let mut plain = Vec::new();
+        write_reports(
+            &mut PlainKvSink::new(&mut plain, PLAIN_KV_PRECISION),
+            &reports,
+            Duration::ZERO,
+        )
+        .unwrap();
+        let plain = String::from_utf8(plain).unwrap();
+        assert!(plain.starts_with("file.1.path=a.rs\nfile.1.status=ok\n"));
+        assert!(plain.contains("file.2.total_lines=4\n"));
+        assert!(plain.contains("summary.total_lines=7\n"));
+        assert!(plain.lines().all(|line| line.split_once('=').is_some()));
+
+        let mut badge = Vec::new();
+        write_reports(&mut BadgeSink::new(&mut badge), &reports, Duration::ZERO).unwrap();
+        assert!(
+            String::from_utf8(badge)
+                .unwrap()
+                .contains("\"message\":\"7\"")
+        );
+    }
+}
@@ -0,0 +1,1170 @@
+//! 行数统计结果
+//!
+//! 定义单个文件的统计结果 [`LineStats`]，以及多文件汇总时使用的合并逻辑。
+
+use crate::json::JsonKind;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::AddAssign;
+use unicode_width::UnicodeWidthStr;
+
+/// 行数统计结果
+///
+/// 包含文件的各种行数统计信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct LineStats {
+    /// 总行数
+    pub total_lines: usize,
+    /// 非空行数（去除空白字符后不为空的行）
+    pub non_empty_lines: usize,
+    /// 空行数（只包含空白字符的行）
+    pub empty_lines: usize,
+    /// shebang 行数（`#!` 开头的首行）
+    pub shebang_lines: usize,
+    /// 许可证头行数（文件开头包含许可证信息的注释块）
+    pub license_lines: usize,
+    /// 行尾有空格或制表符的非空行数
+    pub trailing_whitespace_lines: usize,
+    /// 匹配 `--match` 的行数（仅在指定 `--match` 时统计；指定 `--invert-match` 时为不匹配的行数）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub matched_lines: Option<usize>,
+    /// 含有无效 UTF-8 序列的行数（仅在启用 `--lossy` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub invalid_utf8_lines: Option<usize>,
+    /// 最长行的长度（仅在启用 `--line-lengths` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_line_length: Option<LineLength>,
+    /// 最长行的字节数，不含换行符（仅在启用 `--bytes-mode` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_line_bytes: Option<usize>,
+    /// 行长度（显示列宽）的分布，用于中位数和百分位数（仅在启用 `--line-lengths` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub line_length_distribution: Option<LengthDistribution>,
+    /// 各种换行符的数量（仅在启用 `--line-endings` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub line_endings: Option<LineEndings>,
+    /// 缩进统计（仅在启用 `--indentation` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub indentation: Option<Indentation>,
+    /// 最长的若干行，按字节数从大到小排列（仅在启用 `--top-lines` 时统计）
+    ///
+    /// 行号只在单个文件内有意义，多文件汇总时不合并。
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub longest_lines: Vec<LongLine>,
+    /// 重复行统计（仅在启用 `--duplicates` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub duplicates: Option<DuplicateStats>,
+    /// 待办标记统计（仅在启用 `--todos` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub todos: Option<TodoStats>,
+    /// 注释行与代码行统计（仅在启用 `--comments` 且文件的语言有注释语法时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub comments: Option<CommentStats>,
+    /// 物理行数与合并续行后的逻辑行数（仅在启用 `--logical` 且文件的语言有续行规则时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub logical: Option<LogicalLines>,
+    /// 单词数（仅在为散文类文件估计阅读时间时统计，见 `--reading-time`）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub words: Option<usize>,
+    /// 大语言模型的 token 数（仅在启用 `--tokens` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub tokens: Option<TokenCount>,
+    /// 出现次数最多的单词（仅在启用 `--word-freq` 时统计）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub word_freq: Option<WordFrequency>,
+    /// CSV 或 TSV 的记录统计（仅在按 CSV 或 TSV 统计时，见 `--mode`）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub csv: Option<CsvStats>,
+    /// JSON Lines 的记录统计（仅在按 JSON Lines 统计时，见 `--mode jsonl`）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub jsonl: Option<JsonLinesStats>,
+    /// JSON 文档的检查结果（仅在按单个 JSON 文档检查时，见 `--mode json`）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub json: Option<JsonDocumentStats>,
+}
+
+/// 行长度
+///
+/// 同一行在不同单位下的长度可能相差很大：协议限制通常按字节计算，
+/// 而代码风格的行宽限制按终端显示列宽计算（CJK 字符和 emoji 占两列）。
+/// 作为最大值使用时，三个单位分别取最大值，可能来自不同的行。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LineLength {
+    /// UTF-8 字节数（不含换行符）
+    pub bytes: usize,
+    /// Unicode 字符数
+    pub chars: usize,
+    /// 终端显示列宽
+    pub cells: usize,
+}
+
+impl LineLength {
+    /// 测量一行的长度
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 一行内容（不含换行符）
+    pub fn of(line: &str) -> Self {
+        if line.is_ascii() {
+            let cells = if line.bytes().all(|b| b.is_ascii_graphic() || b == b' ') {
+                line.len()
+            } else {
+                line.width()
+            };
+            return Self {
+                bytes: line.len(),
+                chars: line.len(),
+                cells,
+            };
+        }
+
+        Self {
+            bytes: line.len(),
+            chars: line.chars().count(),
+            cells: line.width(),
+        }
+    }
+
+    /// 分别取两个长度在各个单位下的最大值
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            bytes: self.bytes.max(other.bytes),
+            chars: self.chars.max(other.chars),
+            cells: self.cells.max(other.cells),
+        }
+    }
+}
+
+/// 行长度的分布
+///
+/// 按长度逐一计数，百分位数是精确值：每种长度至少需要一行那么多的字节，
+/// 不同长度的种数不超过 √(2 × 字节数)，即使 1 GB 的文件也只有约 4.6 万种，不需要近似的分位数草图。
+/// 多文件汇总时按长度合并计数，汇总的百分位数同样精确。
+///
+/// 序列化时额外输出行数 `lines`、中位数 `median` 以及 `p90`、`p99`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default))]
+pub struct LengthDistribution {
+    /// 每种长度的行数
+    pub counts: BTreeMap<usize, usize>,
+}
+
+impl LengthDistribution {
+    /// 记录一行的长度
+    pub fn add(&mut self, length: usize) {
+        *self.counts.entry(length).or_default() += 1;
+    }
+
+    /// 参与统计的行数
+    pub fn lines(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// 百分位数（最近秩法）
+    ///
+    /// # 参数
+    ///
+    /// * `percent` - 百分位（0.0 - 100.0），如 `90.0`
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(usize)` - 至少 `percent`% 的行不超过的最小长度
+    /// * `None` - 没有任何行
+    pub fn percentile(&self, percent: f64) -> Option<usize> {
+        let lines = self.lines();
+        if lines == 0 {
+            return None;
+        }
+        let rank = ((percent / 100.0 * lines as f64).ceil() as usize).clamp(1, lines);
+        let mut seen = 0;
+        self.counts.iter().find_map(|(&length, &count)| {
+            seen += count;
+            (seen >= rank).then_some(length)
+        })
+    }
+
+    /// 中位数
+    pub fn median(&self) -> Option<usize> {
+        self.percentile(50.0)
+    }
+}
+
+impl AddAssign<&LengthDistribution> for LengthDistribution {
+    fn add_assign(&mut self, other: &LengthDistribution) {
+        for (&length, &count) in &other.counts {
+            *self.counts.entry(length).or_default() += count;
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LengthDistribution {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("LengthDistribution", 5)?;
+        state.serialize_field("lines", &self.lines())?;
+        state.serialize_field("median", &self.median())?;
+        state.serialize_field("p90", &self.percentile(90.0))?;
+        state.serialize_field("p99", &self.percentile(99.0))?;
+        state.serialize_field("counts", &self.counts)?;
+        state.end()
+    }
+}
+
+/// 一个较长的行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LongLine {
+    /// 行号（从 1 开始，包含文件头和被区段过滤的行）
+    pub line: usize,
+    /// 行的长度
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub length: LineLength,
+}
+
+/// 重复行统计
+///
+/// 只检查非空行：与前面某一行内容完全相同（按 `--normalize` 规范化后）的行计为重复行。
+/// 多文件汇总时各项直接相加，不检测跨文件的重复；最常重复的行按内容合并次数。
+///
+/// 序列化时额外输出重复行占比 `ratio`（百分比）。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default))]
+pub struct DuplicateStats {
+    /// 参与检查的非空行数
+    pub lines: usize,
+    /// 不同内容的行数
+    pub distinct: usize,
+    /// 重复行数（`lines - distinct`）
+    pub duplicates: usize,
+    /// 不同内容的行数超过跟踪上限，之后新出现的内容不再跟踪，
+    /// 此时 `duplicates` 是下限
+    pub approximate: bool,
+    /// 最常重复的行，按出现次数从多到少排列（仅在指定 `--top-duplicates` 时记录）
+    pub top: Vec<RepeatedLine>,
+}
+
+/// 一个重复出现的行
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RepeatedLine {
+    /// 行内容（规范化后，过长时截断）
+    pub text: String,
+    /// 出现次数
+    pub count: usize,
+}
+
+impl DuplicateStats {
+    /// 重复行占参与检查的行数的比例
+    ///
+    /// # 返回值
+    ///
+    /// * `f64` - 重复行占比（0.0 - 100.0）
+    pub fn ratio(&self) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            self.duplicates as f64 / self.lines as f64 * 100.0
+        }
+    }
+}
+
+impl AddAssign<&DuplicateStats> for DuplicateStats {
+    fn add_assign(&mut self, other: &DuplicateStats) {
+        let keep = self.top.len().max(other.top.len());
+        self.lines += other.lines;
+        self.distinct += other.distinct;
+        self.duplicates += other.duplicates;
+        self.approximate |= other.approximate;
+
+        for repeated in &other.top {
+            match self.top.iter_mut().find(|line| line.text == repeated.text) {
+                Some(line) => line.count += repeated.count,
+                None => self.top.push(repeated.clone()),
+            }
+        }
+        self.top
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.text.cmp(&b.text)));
+        self.top.truncate(keep);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for DuplicateStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DuplicateStats", 6)?;
+        state.serialize_field("lines", &self.lines)?;
+        state.serialize_field("distinct", &self.distinct)?;
+        state.serialize_field("duplicates", &self.duplicates)?;
+        state.serialize_field("ratio", &self.ratio())?;
+        state.serialize_field("approximate", &self.approximate)?;
+        state.serialize_field("top", &self.top)?;
+        state.end()
+    }
+}
+
+/// CSV 或 TSV 的记录统计
+///
+/// 引号字段中的换行符不结束记录，因此记录数可能少于非空行数。
+/// 多文件汇总时各项直接相加，列数取各文件的最大值，列数不一致的记录的行号只在单个文件内有意义，不合并。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct CsvStats {
+    /// 记录数（包括表头）
+    pub records: usize,
+    /// 列数（第一条记录的字段数）
+    pub columns: usize,
+    /// 字段数与列数不同的记录数
+    pub inconsistent_records: usize,
+    /// 列数不一致的前若干条记录开始的行号
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub inconsistent_lines: Vec<usize>,
+    /// 内容为空的字段数
+    pub empty_fields: usize,
+    /// 内容在引号字段中结束（引号没有闭合）
+    pub unterminated_quote: bool,
+}
+
+impl AddAssign<&CsvStats> for CsvStats {
+    fn add_assign(&mut self, other: &CsvStats) {
+        self.records += other.records;
+        self.columns = self.columns.max(other.columns);
+        self.inconsistent_records += other.inconsistent_records;
+        self.inconsistent_lines.clear();
+        self.empty_fields += other.empty_fields;
+        self.unterminated_quote |= other.unterminated_quote;
+    }
+}
+
+/// 大语言模型的 token 数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct TokenCount {
+    /// token 数
+    pub tokens: usize,
+    /// 是否为按字符类别估计的值（而不是按 BPE 词表编码的结果）
+    pub estimated: bool,
+}
+
+impl AddAssign for TokenCount {
+    fn add_assign(&mut self, other: TokenCount) {
+        self.tokens += other.tokens;
+        self.estimated |= other.estimated;
+    }
+}
+
+/// JSON Lines（NDJSON）的记录统计
+///
+/// 每个非空行是一条记录。多文件汇总时各项直接相加，无效行的行号只在单个文件内有意义，不合并。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct JsonLinesStats {
+    /// 内容为合法 JSON 值的行数
+    pub records: usize,
+    /// 内容不是合法 JSON 值的行数
+    pub invalid_lines: usize,
+    /// 前若干个无效行的行号
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub invalid_line_numbers: Vec<usize>,
+    /// 空行和只有空白的行数
+    pub empty_lines: usize,
+}
+
+impl AddAssign<&JsonLinesStats> for JsonLinesStats {
+    fn add_assign(&mut self, other: &JsonLinesStats) {
+        self.records += other.records;
+        self.invalid_lines += other.invalid_lines;
+        self.invalid_line_numbers.clear();
+        self.empty_lines += other.empty_lines;
+    }
+}
+
+/// JSON 文档的检查结果
+///
+/// 单个文件的 `documents` 为 1，文件内容恰好是一个合法的 JSON 文档时 `invalid_documents` 为 0。
+/// 多文件汇总时文档数和顶层数组的元素数直接相加，顶层类型只在各文件相同时保留，错误位置不合并。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct JsonDocumentStats {
+    /// 检查的文件数
+    pub documents: usize,
+    /// 不是单个合法 JSON 文档的文件数
+    pub invalid_documents: usize,
+    /// 顶层值的类型
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub kind: Option<JsonKind>,
+    /// 顶层数组的元素数（顶层不是数组时为空）
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub array_length: Option<usize>,
+    /// 第一个语法错误所在的行号
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub error_line: Option<usize>,
+}
+
+impl JsonDocumentStats {
+    /// 是否都是合法的 JSON 文档
+    pub fn is_valid(&self) -> bool {
+        self.invalid_documents == 0
+    }
+}
+
+impl AddAssign<&JsonDocumentStats> for JsonDocumentStats {
+    fn add_assign(&mut self, other: &JsonDocumentStats) {
+        self.documents += other.documents;
+        self.invalid_documents += other.invalid_documents;
+        if self.kind != other.kind {
+            self.kind = None;
+        }
+        self.array_length = match (self.array_length, other.array_length) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.error_line = None;
+    }
+}
+
+/// 物理行数与逻辑行数
+///
+/// 以续行符结尾、括号没有闭合等延续到下一行的行与下一行合并为一个逻辑行（见 [`crate::comments`]）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct LogicalLines {
+    /// 参与合并的物理行数
+    pub physical_lines: usize,
+    /// 合并续行后的逻辑行数
+    pub logical_lines: usize,
+}
+
+impl LogicalLines {
+    /// 合并到前一行的续行数
+    pub fn continued_lines(&self) -> usize {
+        self.physical_lines - self.logical_lines
+    }
+}
+
+impl AddAssign<&LogicalLines> for LogicalLines {
+    fn add_assign(&mut self, other: &LogicalLines) {
+        self.physical_lines += other.physical_lines;
+        self.logical_lines += other.logical_lines;
+    }
+}
+
+/// 单词频率统计
+///
+/// 单词按小写比较，不含字母的片段和停用词不计入。
+/// 多文件汇总时单词数直接相加，出现次数最多的单词按各文件列出的单词合并次数，
+/// 只在某些文件中排名靠前的单词的汇总次数可能偏少。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct WordFrequency {
+    /// 计入统计的单词数
+    pub words: usize,
+    /// 不同单词数超过跟踪上限，丢弃过出现次数少的单词，此时出现次数是下限
+    pub approximate: bool,
+    /// 出现次数最多的单词，按出现次数从多到少排列
+    pub top: Vec<WordCount>,
+}
+
+/// 一个单词及其出现次数
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WordCount {
+    /// 单词（小写）
+    pub word: String,
+    /// 出现次数
+    pub count: usize,
+}
+
+impl AddAssign<&WordFrequency> for WordFrequency {
+    fn add_assign(&mut self, other: &WordFrequency) {
+        let keep = self.top.len().max(other.top.len());
+        self.words += other.words;
+        self.approximate |= other.approximate;
+
+        for counted in &other.top {
+            match self.top.iter_mut().find(|word| word.word == counted.word) {
+                Some(word) => word.count += counted.count,
+                None => self.top.push(counted.clone()),
+            }
+        }
+        self.top
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+        self.top.truncate(keep);
+    }
+}
+
+/// 注释行与代码行统计
+///
+/// 空行仍计入 [`LineStats::empty_lines`]，因此注释行数与代码行数之和等于非空行数。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct CommentStats {
+    /// 只包含注释的行数
+    pub comment_lines: usize,
+    /// 包含代码的行数
+    pub code_lines: usize,
+}
+
+impl AddAssign<&CommentStats> for CommentStats {
+    fn add_assign(&mut self, other: &CommentStats) {
+        self.comment_lines += other.comment_lines;
+        self.code_lines += other.code_lines;
+    }
+}
+
+/// 待办标记统计
+///
+/// 多文件汇总时各标记的次数直接相加；标记的位置只在单个文件内有意义，不合并。
+///
+/// 序列化时额外输出所有标记的总次数 `total`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default))]
+pub struct TodoStats {
+    /// 每种标记出现的次数，按标记排列；没有出现的标记为 0
+    pub counts: BTreeMap<String, usize>,
+    /// 标记出现的位置，按行号排列（仅在启用 `--list-todos` 时记录）
+    pub items: Vec<TodoItem>,
+}
+
+/// 一个待办标记出现的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TodoItem {
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 标记
+    pub marker: String,
+    /// 从标记开始到行尾的内容（过长时截断）
+    pub text: String,
+}
+
+impl TodoStats {
+    /// 所有标记出现的总次数
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+}
+
+impl AddAssign<&TodoStats> for TodoStats {
+    fn add_assign(&mut self, other: &TodoStats) {
+        for (marker, count) in &other.counts {
+            *self.counts.entry(marker.clone()).or_default() += count;
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for TodoStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TodoStats", 3)?;
+        state.serialize_field("total", &self.total())?;
+        state.serialize_field("counts", &self.counts)?;
+        if self.items.is_empty() {
+            state.skip_field("items")?;
+        } else {
+            state.serialize_field("items", &self.items)?;
+        }
+        state.end()
+    }
+}
+
+/// 换行符风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "UPPERCASE"))]
+pub enum LineEnding {
+    /// `\n`（Unix）
+    Lf,
+    /// `\r\n`（Windows）
+    Crlf,
+    /// 单独的 `\r`（经典 Mac OS）
+    Cr,
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+            LineEnding::Cr => "CR",
+        })
+    }
+}
+
+/// 各种换行符的数量
+///
+/// 行仍然只按 `\n` 划分（与 `wc -l` 一致），单独的 `\r` 不会把一行拆成两行，
+/// 但会计入 `cr`，因此只使用 `\r` 换行的文件同样能被识别出来。
+/// 换行符统计覆盖文件中的所有行，不受区段过滤和 `--exclude-headers` 影响。
+///
+/// 序列化时额外输出主要风格 `dominant` 和是否混用 `mixed`。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default))]
+pub struct LineEndings {
+    /// 以 `\n` 结尾的行数
+    pub lf: usize,
+    /// 以 `\r\n` 结尾的行数
+    pub crlf: usize,
+    /// 单独的 `\r` 的数量
+    pub cr: usize,
+    /// 最后一行没有换行符结尾的文件数
+    pub missing_final_newline: usize,
+}
+
+impl LineEndings {
+    /// 数量最多的换行符风格，数量相同时依次优先 LF、CRLF、CR；没有任何换行符时为 `None`
+    pub fn dominant(&self) -> Option<LineEnding> {
+        [
+            (LineEnding::Lf, self.lf),
+            (LineEnding::Crlf, self.crlf),
+            (LineEnding::Cr, self.cr),
+        ]
+        .into_iter()
+        .filter(|&(_, count)| count > 0)
+        .fold(
+            None,
+            |best: Option<(LineEnding, usize)>, (ending, count)| match best {
+                Some((_, best_count)) if best_count >= count => best,
+                _ => Some((ending, count)),
+            },
+        )
+        .map(|(ending, _)| ending)
+    }
+
+    /// 是否混用了多种换行符
+    pub fn is_mixed(&self) -> bool {
+        [self.lf, self.crlf, self.cr]
+            .iter()
+            .filter(|&&count| count > 0)
+            .count()
+            > 1
+    }
+}
+
+impl AddAssign<&LineEndings> for LineEndings {
+    fn add_assign(&mut self, other: &LineEndings) {
+        self.lf += other.lf;
+        self.crlf += other.crlf;
+        self.cr += other.cr;
+        self.missing_final_newline += other.missing_final_newline;
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LineEndings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("LineEndings", 6)?;
+        state.serialize_field("lf", &self.lf)?;
+        state.serialize_field("crlf", &self.crlf)?;
+        state.serialize_field("cr", &self.cr)?;
+        state.serialize_field("missing_final_newline", &self.missing_final_newline)?;
+        state.serialize_field("dominant", &self.dominant())?;
+        state.serialize_field("mixed", &self.is_mixed())?;
+        state.end()
+    }
+}
+
+/// 估计缩进宽度时考虑的最大宽度
+pub const MAX_INDENT_WIDTH: usize = 8;
+
+/// 缩进风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "lowercase"))]
+pub enum IndentStyle {
+    /// 制表符缩进
+    Tabs,
+    /// 空格缩进
+    Spaces,
+}
+
+impl fmt::Display for IndentStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IndentStyle::Tabs => "制表符",
+            IndentStyle::Spaces => "空格",
+        })
+    }
+}
+
+/// 缩进统计
+///
+/// 按缩进的第一个字符把有缩进的非空行分为制表符缩进和空格缩进。
+/// 缩进宽度根据相邻两个纯空格缩进的非空行之间缩进的增加量估计：
+/// 每次增加 1 到 [`MAX_INDENT_WIDTH`] 个空格记一票，得票最多的增加量即为缩进宽度。
+/// 票数在多文件汇总时直接相加。
+///
+/// 序列化时额外输出主要风格 `style`、估计的宽度 `width` 和是否混用 `mixed`。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default))]
+pub struct Indentation {
+    /// 以制表符开始缩进的行数
+    pub tabs: usize,
+    /// 以空格开始缩进的行数
+    pub spaces: usize,
+    /// 缩进增加 1 到 [`MAX_INDENT_WIDTH`] 个空格的次数
+    pub width_votes: [usize; MAX_INDENT_WIDTH],
+}
+
+impl Indentation {
+    /// 行数较多的缩进风格，行数相同时优先空格；没有缩进的行时为 `None`
+    pub fn style(&self) -> Option<IndentStyle> {
+        match (self.tabs, self.spaces) {
+            (0, 0) => None,
+            (tabs, spaces) if tabs > spaces => Some(IndentStyle::Tabs),
+            _ => Some(IndentStyle::Spaces),
+        }
+    }
+
+    /// 估计的空格缩进宽度，票数相同时取较小的宽度；没有投票时为 `None`
+    pub fn width(&self) -> Option<usize> {
+        let (index, &votes) = self
+            .width_votes
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|&(_, votes)| votes)?;
+        (votes > 0).then_some(index + 1)
+    }
+
+    /// 是否同时存在制表符缩进和空格缩进的行
+    pub fn is_mixed(&self) -> bool {
+        self.tabs > 0 && self.spaces > 0
+    }
+}
+
+impl AddAssign<&Indentation> for Indentation {
+    fn add_assign(&mut self, other: &Indentation) {
+        self.tabs += other.tabs;
+        self.spaces += other.spaces;
+        for (votes, other_votes) in self.width_votes.iter_mut().zip(other.width_votes) {
+            *votes += other_votes;
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Indentation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Indentation", 6)?;
+        state.serialize_field("tabs", &self.tabs)?;
+        state.serialize_field("spaces", &self.spaces)?;
+        state.serialize_field("width_votes", &self.width_votes)?;
+        state.serialize_field("style", &self.style())?;
+        state.serialize_field("width", &self.width())?;
+        state.serialize_field("mixed", &self.is_mixed())?;
+        state.end()
+    }
+}
+
+impl LineStats {
+    /// 创建新的行数统计结果
+    ///
+    /// # 参数
+    ///
+    /// * `total_lines` - 总行数
+    /// * `non_empty_lines` - 非空行数
+    /// * `empty_lines` - 空行数
+    pub fn new(total_lines: usize, non_empty_lines: usize, empty_lines: usize) -> Self {
+        Self {
+            total_lines,
+            non_empty_lines,
+            empty_lines,
+            ..Self::default()
+        }
+    }
+
+    /// 计算空行占比
+    ///
+    /// # 返回值
+    ///
+    /// * `f64` - 空行占比（0.0 - 100.0）
+    pub fn empty_percentage(&self) -> f64 {
+        if self.total_lines == 0 {
+            0.0
+        } else {
+            (self.empty_lines as f64 / self.total_lines as f64) * 100.0
+        }
+    }
+
+    /// 计算非空行占比
+    ///
+    /// # 返回值
+    ///
+    /// * `f64` - 非空行占比（0.0 - 100.0），没有任何行时为 0.0
+    pub fn non_empty_percentage(&self) -> f64 {
+        if self.total_lines == 0 {
+            0.0
+        } else {
+            (self.non_empty_lines as f64 / self.total_lines as f64) * 100.0
+        }
+    }
+}
+
+/// 合并另一个文件的统计结果，用于多文件汇总
+impl AddAssign<&LineStats> for LineStats {
+    fn add_assign(&mut self, other: &LineStats) {
+        self.total_lines += other.total_lines;
+        self.non_empty_lines += other.non_empty_lines;
+        self.empty_lines += other.empty_lines;
+        self.shebang_lines += other.shebang_lines;
+        self.license_lines += other.license_lines;
+        self.trailing_whitespace_lines += other.trailing_whitespace_lines;
+        self.matched_lines = match (self.matched_lines, other.matched_lines) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.invalid_utf8_lines = match (self.invalid_utf8_lines, other.invalid_utf8_lines) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.words = match (self.words, other.words) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.tokens = match (self.tokens, other.tokens) {
+            (Some(mut a), Some(b)) => {
+                a += b;
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        };
+        self.max_line_length = match (self.max_line_length, other.max_line_length) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_line_bytes = self.max_line_bytes.max(other.max_line_bytes);
+        if let Some(other_distribution) = &other.line_length_distribution {
+            match &mut self.line_length_distribution {
+                Some(distribution) => *distribution += other_distribution,
+                None => self.line_length_distribution = Some(other_distribution.clone()),
+            }
+        }
+        self.indentation = match (self.indentation, other.indentation) {
+            (Some(mut a), Some(b)) => {
+                a += &b;
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        };
+        self.line_endings = match (self.line_endings, other.line_endings) {
+            (Some(mut a), Some(b)) => {
+                a += &b;
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        };
+        self.comments = match (self.comments, other.comments) {
+            (Some(mut a), Some(b)) => {
+                a += &b;
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        };
+        self.logical = match (self.logical, other.logical) {
+            (Some(mut a), Some(b)) => {
+                a += &b;
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        };
+        if let Some(other_duplicates) = &other.duplicates {
+            match &mut self.duplicates {
+                Some(duplicates) => *duplicates += other_duplicates,
+                None => self.duplicates = Some(other_duplicates.clone()),
+            }
+        }
+        if let Some(other_csv) = &other.csv {
+            match &mut self.csv {
+                Some(csv) => *csv += other_csv,
+                None => {
+                    self.csv = Some(CsvStats {
+                        inconsistent_lines: Vec::new(),
+                        ..other_csv.clone()
+                    })
+                }
+            }
+        }
+        if let Some(other_jsonl) = &other.jsonl {
+            match &mut self.jsonl {
+                Some(jsonl) => *jsonl += other_jsonl,
+                None => {
+                    self.jsonl = Some(JsonLinesStats {
+                        invalid_line_numbers: Vec::new(),
+                        ..other_jsonl.clone()
+                    })
+                }
+            }
+        }
+        if let Some(other_json) = &other.json {
+            match &mut self.json {
+                Some(json) => *json += other_json,
+                None => {
+                    self.json = Some(JsonDocumentStats {
+                        error_line: None,
+                        ..other_json.clone()
+                    })
+                }
+            }
+        }
+        if let Some(other_word_freq) = &other.word_freq {
+            match &mut self.word_freq {
+                Some(word_freq) => *word_freq += other_word_freq,
+                None => self.word_freq = Some(other_word_freq.clone()),
+            }
+        }
+        if let Some(other_todos) = &other.todos {
+            match &mut self.todos {
+                Some(todos) => *todos += other_todos,
+                None => {
+                    self.todos = Some(TodoStats {
+                        items: Vec::new(),
+                        ..other_todos.clone()
+                    })
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试 LineStats 结构体
+    #[test]
+    fn test_line_stats() {
+        let stats = LineStats::new(100, 80, 20);
+        assert_eq!(stats.total_lines, 100);
+        assert_eq!(stats.non_empty_lines, 80);
+        assert_eq!(stats.empty_lines, 20);
+        assert_eq!(stats.empty_percentage(), 20.0);
+        assert_eq!(stats.non_empty_percentage(), 80.0);
+    }
+
+    /// 测试空文件的空行占比计算
+    #[test]
+    fn test_empty_file_percentage() {
+        let stats = LineStats::new(0, 0, 0);
+        assert_eq!(stats.empty_percentage(), 0.0);
+        assert_eq!(stats.non_empty_percentage(), 0.0);
+    }
+
+    /// 测试 100% 空行的情况
+    #[test]
+    fn test_all_empty_lines_percentage() {
+        let stats = LineStats::new(10, 0, 10);
+        assert_eq!(stats.empty_percentage(), 100.0);
+    }
+
+    /// 测试无空行的情况
+    #[test]
+    fn test_no_empty_lines_percentage() {
+        let stats = LineStats::new(10, 10, 0);
+        assert_eq!(stats.empty_percentage(), 0.0);
+    }
+
+    /// 测试三种单位的行长度
+    #[test]
+    fn test_line_length() {
+        assert_eq!(
+            LineLength::of("hello"),
+            LineLength {
+                bytes: 5,
+                chars: 5,
+                cells: 5
+            }
+        );
+        assert_eq!(
+            LineLength::of("中文ab"),
+            LineLength {
+                bytes: 8,
+                chars: 4,
+                cells: 6
+            }
+        );
+        assert_eq!(
+            LineLength::of("é"),
+            LineLength {
+                bytes: 2,
+                chars: 1,
+                cells: 1
+            }
+        );
+    }
+
+    /// 测试多文件合并时行长度分别取最大值
+    #[test]
+    fn test_merge_line_length() {
+        let mut total = LineStats::default();
+        total += &LineStats {
+            max_line_length: Some(LineLength::of("中文中文")),
+            ..LineStats::default()
+        };
+        total += &LineStats {
+            max_line_length: Some(LineLength::of("abcdefghij")),
+            ..LineStats::default()
+        };
+        assert_eq!(
+            total.max_line_length,
+            Some(LineLength {
+                bytes: 12,
+                chars: 10,
+                cells: 10
+            })
+        );
+    }
+
+    /// 测试行长度分布的百分位数与合并
+    #[test]
+    fn test_length_distribution() {
+        let distribution = |lengths: &[usize]| {
+            let mut distribution = LengthDistribution::default();
+            for &length in lengths {
+                distribution.add(length);
+            }
+            distribution
+        };
+        assert_eq!(LengthDistribution::default().median(), None);
+
+        let mut total = distribution(&(1..=100).collect::<Vec<_>>());
+        assert_eq!(total.median(), Some(50));
+        assert_eq!(total.percentile(90.0), Some(90));
+        assert_eq!(total.percentile(99.0), Some(99));
+        assert_eq!(total.percentile(0.0), Some(1));
+        assert_eq!(total.percentile(100.0), Some(100));
+
+        // 合并后一个很长的行只影响最高的百分位数
+        total += &distribution(&[1000; 10]);
+        assert_eq!(total.lines(), 110);
+        assert_eq!(total.median(), Some(55));
+        assert_eq!(total.percentile(90.0), Some(99));
+        assert_eq!(total.percentile(99.0), Some(1000));
+    }
+
+    /// 测试主要换行符风格和混用判断
+    #[test]
+    fn test_line_endings() {
+        let endings = |lf, crlf, cr| LineEndings {
+            lf,
+            crlf,
+            cr,
+            ..LineEndings::default()
+        };
+        assert_eq!(endings(0, 0, 0).dominant(), None);
+        assert!(!endings(0, 0, 0).is_mixed());
+        assert_eq!(endings(3, 0, 0).dominant(), Some(LineEnding::Lf));
+        assert!(!endings(3, 0, 0).is_mixed());
+        assert_eq!(endings(1, 5, 2).dominant(), Some(LineEnding::Crlf));
+        assert!(endings(1, 5, 2).is_mixed());
+        assert_eq!(endings(0, 2, 2).dominant(), Some(LineEnding::Crlf));
+        assert_eq!(endings(0, 0, 4).dominant(), Some(LineEnding::Cr));
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_value(endings(1, 2, 0)).unwrap();
+            assert_eq!(json["dominant"], "CRLF");
+            assert_eq!(json["mixed"], true);
+            let parsed: LineEndings = serde_json::from_value(json).unwrap();
+            assert_eq!(parsed, endings(1, 2, 0));
+        }
+
+        let mut total = LineStats::default();
+        total += &LineStats {
+            line_endings: Some(endings(1, 0, 0)),
+            ..LineStats::default()
+        };
+        total += &LineStats {
+            line_endings: Some(endings(0, 2, 1)),
+            ..LineStats::default()
+        };
+        assert_eq!(total.line_endings, Some(endings(1, 2, 1)));
+    }
+
+    /// 测试缩进风格、宽度估计和混用判断
+    #[test]
+    fn test_indentation() {
+        let mut indentation = Indentation::default();
+        assert_eq!(indentation.style(), None);
+        assert_eq!(indentation.width(), None);
+
+        indentation.spaces = 10;
+        indentation.width_votes[3] = 5;
+        indentation.width_votes[1] = 2;
+        assert_eq!(indentation.style(), Some(IndentStyle::Spaces));
+        assert_eq!(indentation.width(), Some(4));
+        assert!(!indentation.is_mixed());
+
+        indentation.width_votes[1] = 5;
+        assert_eq!(indentation.width(), Some(2));
+
+        let mut total = indentation;
+        total += &Indentation {
+            tabs: 20,
+            ..Indentation::default()
+        };
+        assert_eq!(total.style(), Some(IndentStyle::Tabs));
+        assert!(total.is_mixed());
+        assert_eq!(total.width_votes[3], 5);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_value(total).unwrap();
+            assert_eq!(json["style"], "tabs");
+            assert_eq!(json["width"], 2);
+            assert_eq!(json["mixed"], true);
+            let parsed: Indentation = serde_json::from_value(json).unwrap();
+            assert_eq!(parsed, total);
+        }
+    }
+
+    /// 测试重复行统计的合并
+    #[test]
+    fn test_duplicate_stats() {
+        let repeated = |text: &str, count| RepeatedLine {
+            text: text.to_string(),
+            count,
+        };
+        let mut a = DuplicateStats {
+            lines: 10,
+            distinct: 6,
+            duplicates: 4,
+            approximate: false,
+            top: vec![repeated("ok", 3), repeated("retry", 2)],
+        };
+        assert!((a.ratio() - 40.0).abs() < 1e-9);
+
+        a += &DuplicateStats {
+            lines: 10,
+            distinct: 8,
+            duplicates: 2,
+            approximate: true,
+            top: vec![repeated("retry", 3)],
+        };
+        assert_eq!(a.lines, 20);
+        assert_eq!(a.duplicates, 6);
+        assert!(a.approximate);
+        assert_eq!(a.top, vec![repeated("retry", 5), repeated("ok", 3)]);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_value(&a).unwrap();
+            assert_eq!(json["ratio"], 30.0);
+            let parsed: DuplicateStats = serde_json::from_value(json).unwrap();
+            assert_eq!(parsed, a);
+        }
+        assert_eq!(DuplicateStats::default().ratio(), 0.0);
+    }
+
+    /// 测试多文件统计结果的合并
+    #[test]
+    fn test_merge_stats() {
+        let mut total = LineStats::default();
+        total += &LineStats::new(10, 8, 2);
+        total += &LineStats::new(5, 5, 0);
+        assert_eq!(total, LineStats::new(15, 13, 2));
+    }
+}
@@ -0,0 +1,260 @@
+//! 文件标签
+//!
+//! `--tag legacy=src/old/**` 给路径匹配 glob 模式的文件打上标签，
+//! 汇总结果按标签分别合并（[`TagRollup`]），基线比较也会逐个标签检查行数增长，
+//! 便于持续跟踪遗留代码等特定部分的规模变化。
+//!
+//! 模式按输入路径（去掉开头的 `./`）匹配：`*` 不跨越目录分隔符，`**` 匹配任意层目录，
+//! 大小写按 `-i`、`-S` 等选项匹配（见 [`crate::pattern`]）。一个文件可以有多个标签。
+//! 配置文件的 `[tags]` 表中的规则与命令行的 `--tag` 合并生效。
+
+use crate::pattern::CaseMode;
+use crate::report::FileReport;
+use crate::stats::LineStats;
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// 标签规则
+#[derive(Debug, Clone)]
+pub struct TagRule {
+    /// 标签名称
+    pub name: String,
+    /// 路径模式，匹配任意一个即打上标签
+    pub patterns: Vec<Pattern>,
+}
+
+impl TagRule {
+    /// 创建标签规则
+    ///
+    /// # 参数
+    ///
+    /// * `name` - 标签名称
+    /// * `patterns` - 路径模式，开头的 `./` 被忽略，空模式被忽略
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(TagRule)` - 标签规则
+    /// * `Err(String)` - 名称为空、没有路径模式或模式无效
+    pub fn new<'a>(
+        name: &str,
+        patterns: impl IntoIterator<Item = &'a str>,
+    ) -> Result<TagRule, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("标签名称不能为空".to_string());
+        }
+
+        let patterns = patterns
+            .into_iter()
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| {
+                let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+                Pattern::new(pattern).map_err(|e| format!("无效的路径模式 '{}': {}", pattern, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if patterns.is_empty() {
+            return Err(format!("标签 '{}' 没有路径模式", name));
+        }
+
+        Ok(TagRule {
+            name: name.to_string(),
+            patterns,
+        })
+    }
+
+    /// 判断路径是否匹配该规则
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 输入路径
+    /// * `case` - 大小写匹配方式
+    pub fn matches(&self, path: &Path, case: CaseMode) -> bool {
+        let path = path.strip_prefix(".").unwrap_or(path);
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_path_with(path, case.glob_options(pattern.as_str())))
+    }
+}
+
+/// 序列化为 `名称=模式,模式` 形式，用于配置哈希
+impl Serialize for TagRule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let patterns: Vec<&str> = self.patterns.iter().map(Pattern::as_str).collect();
+        serializer.serialize_str(&format!("{}={}", self.name, patterns.join(",")))
+    }
+}
+
+/// 解析标签规则
+///
+/// # 参数
+///
+/// * `value` - `名称=模式[,模式...]`，如 `legacy=src/old/**,lib/compat/*.rs`
+///
+/// # 返回值
+///
+/// * `Ok(TagRule)` - 标签规则
+/// * `Err(String)` - 格式错误或模式无效，供 `clap` 显示
+pub fn parse_rule(value: &str) -> Result<TagRule, String> {
+    let (name, patterns) = value
+        .split_once('=')
+        .ok_or_else(|| format!("标签规则应为 名称=模式: '{}'", value))?;
+    if name.trim().is_empty() {
+        return Err(format!("标签名称不能为空: '{}'", value));
+    }
+    TagRule::new(name, patterns.split(','))
+}
+
+/// 查找路径匹配的所有标签
+///
+/// # 参数
+///
+/// * `rules` - 标签规则
+/// * `path` - 输入路径
+/// * `case` - 大小写匹配方式
+///
+/// # 返回值
+///
+/// 匹配的标签名称，按规则顺序排列且不重复
+pub fn tags_for(rules: &[TagRule], path: &Path, case: CaseMode) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for rule in rules.iter().filter(|rule| rule.matches(path, case)) {
+        if !tags.contains(&rule.name) {
+            tags.push(rule.name.clone());
+        }
+    }
+    tags
+}
+
+/// 单个标签的汇总结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagRollup {
+    /// 标签名称
+    pub name: String,
+    /// 带该标签的文件数
+    pub files: usize,
+    /// 带该标签的文件总大小（字节）
+    pub bytes: u64,
+    /// 带该标签的文件合并后的行数统计
+    pub stats: LineStats,
+}
+
+/// 按标签汇总文件报告
+///
+/// # 参数
+///
+/// * `reports` - 单个文件的统计报告；被跳过的报告不计入
+///
+/// # 返回值
+///
+/// 按标签名称排列的汇总结果
+pub fn rollup(reports: &[FileReport]) -> Vec<TagRollup> {
+    let mut rollups: BTreeMap<&str, TagRollup> = BTreeMap::new();
+    for report in reports.iter().filter(|report| !report.is_skipped()) {
+        for tag in &report.tags {
+            let rollup = rollups.entry(tag).or_insert_with(|| TagRollup {
+                name: tag.clone(),
+                ..TagRollup::default()
+            });
+            rollup.files += 1;
+            rollup.bytes += report.size;
+            rollup.stats += &report.stats;
+        }
+    }
+    rollups.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// 测试标签规则的解析
+    #[test]
+    fn test_parse_rule() {
+        let rule = parse_rule("legacy=src/old/**, ./lib/*.rs").unwrap();
+        assert_eq!(rule.name, "legacy");
+        assert_eq!(rule.patterns.len(), 2);
+        assert_eq!(
+            serde_json::to_value(&rule).unwrap(),
+            "legacy=src/old/**,lib/*.rs"
+        );
+
+        assert!(parse_rule("legacy").is_err());
+        assert!(parse_rule("=src/**").is_err());
+        assert!(parse_rule("legacy=").is_err());
+        assert!(parse_rule("legacy=src/[").is_err());
+    }
+
+    /// 测试路径匹配
+    #[test]
+    fn test_tags_for() {
+        let rules = vec![
+            parse_rule("legacy=src/old/**").unwrap(),
+            parse_rule("top=*.rs").unwrap(),
+            parse_rule("legacy=lib/compat.rs").unwrap(),
+        ];
+        let tags = |path: &str| tags_for(&rules, Path::new(path), CaseMode::Sensitive);
+
+        assert_eq!(tags("src/old/a.rs"), vec!["legacy"]);
+        assert_eq!(tags("./src/old/deep/b.rs"), vec!["legacy"]);
+        assert_eq!(tags("main.rs"), vec!["top"]);
+        assert_eq!(tags("lib/compat.rs"), vec!["legacy"]);
+        // `*` 不跨越目录分隔符
+        assert!(tags("src/new/a.rs").is_empty());
+
+        assert!(tags("Src/Old/a.rs").is_empty());
+        assert_eq!(
+            tags_for(&rules, Path::new("Src/Old/a.rs"), CaseMode::Insensitive),
+            vec!["legacy"]
+        );
+        assert_eq!(
+            tags_for(&rules, Path::new("MAIN.RS"), CaseMode::Smart),
+            vec!["top"]
+        );
+    }
+
+    /// 测试按标签汇总
+    #[test]
+    fn test_rollup() {
+        let report = |path: &str, lines, tags: &[&str]| FileReport {
+            path: PathBuf::from(path),
+            size: 10,
+            stats: LineStats::new(lines, lines, 0),
+            binary: false,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: Some("Rust"),
+            warnings: Vec::new(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+        let reports = vec![
+            report("a.rs", 5, &["legacy", "core"]),
+            report("b.rs", 3, &["legacy"]),
+            report("c.rs", 7, &[]),
+            FileReport {
+                tags: vec!["legacy".to_string()],
+                ..FileReport::missing(PathBuf::from("d.rs"))
+            },
+        ];
+
+        let rollups = rollup(&reports);
+        assert_eq!(rollups.len(), 2);
+        assert_eq!(rollups[0].name, "core");
+        assert_eq!(rollups[0].stats.total_lines, 5);
+        assert_eq!(rollups[1].name, "legacy");
+        assert_eq!(rollups[1].files, 2);
+        assert_eq!(rollups[1].bytes, 20);
+        assert_eq!(rollups[1].stats.total_lines, 8);
+    }
+}
@@ -0,0 +1,205 @@
+//! 阈值检查
+//!
+//! `--max-total-lines`、`--max-file-lines` 和 `--max-empty-pct` 在统计完成后检查结果是否超过给定的上限，
+//! 超过时以专用的退出码 [`THRESHOLD_EXIT_CODE`](crate::error::THRESHOLD_EXIT_CODE) 退出，
+//! 用作 CI 中防止生成文件膨胀的检查。与 [`crate::baseline`] 不同，阈值是绝对值，不需要保存之前的报告。
+//!
+//! 被跳过的文件（二进制、不存在、超时等）不参与检查。
+
+use crate::report::{FileReport, Summary};
+use std::fmt;
+use std::path::PathBuf;
+
+/// 阈值设置，`None` 表示不检查该项
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Thresholds {
+    /// 所有文件的总行数上限
+    pub max_total_lines: Option<usize>,
+    /// 单个文件的总行数上限
+    pub max_file_lines: Option<usize>,
+    /// 空行占比上限（百分比，按行数加权）
+    pub max_empty_percent: Option<f64>,
+}
+
+impl Thresholds {
+    /// 是否设置了任何阈值
+    pub fn is_empty(&self) -> bool {
+        self.max_total_lines.is_none()
+            && self.max_file_lines.is_none()
+            && self.max_empty_percent.is_none()
+    }
+}
+
+/// 超过阈值的指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// 所有文件的总行数
+    TotalLines,
+    /// 单个文件的总行数
+    FileLines,
+    /// 空行占比
+    EmptyPercent,
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Metric::TotalLines => "总行数",
+            Metric::FileLines => "文件行数",
+            Metric::EmptyPercent => "空行占比",
+        })
+    }
+}
+
+/// 单项超过阈值的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// 超过阈值的指标
+    pub metric: Metric,
+    /// 超过阈值的文件，`None` 表示整体汇总
+    pub path: Option<PathBuf>,
+    /// 实际的值
+    pub actual: f64,
+    /// 阈值
+    pub limit: f64,
+}
+
+/// 检查统计结果是否超过阈值
+///
+/// # 参数
+///
+/// * `thresholds` - 阈值设置
+/// * `reports` - 本次统计的报告
+///
+/// # 返回值
+///
+/// * `Vec<Violation>` - 超过阈值的项目：先是按输入顺序排列的单个文件，再是整体汇总
+pub fn evaluate(thresholds: &Thresholds, reports: &[FileReport]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    if let Some(limit) = thresholds.max_file_lines {
+        for report in reports.iter().filter(|report| !report.is_skipped()) {
+            if report.stats.total_lines > limit {
+                violations.push(Violation {
+                    metric: Metric::FileLines,
+                    path: Some(report.path.clone()),
+                    actual: report.stats.total_lines as f64,
+                    limit: limit as f64,
+                });
+            }
+        }
+    }
+
+    let summary = Summary::from_reports(reports);
+    if let Some(limit) = thresholds.max_total_lines
+        && summary.stats.total_lines > limit
+    {
+        violations.push(Violation {
+            metric: Metric::TotalLines,
+            path: None,
+            actual: summary.stats.total_lines as f64,
+            limit: limit as f64,
+        });
+    }
+    if let Some(limit) = thresholds.max_empty_percent
+        && summary.empty_percentage > limit
+    {
+        violations.push(Violation {
+            metric: Metric::EmptyPercent,
+            path: None,
+            actual: summary.empty_percentage,
+            limit,
+        });
+    }
+
+    violations
+}
+
+/// 解析百分比阈值
+///
+/// # 参数
+///
+/// * `value` - 0 到 100 之间的数（如 `25` 或 `12.5`）
+///
+/// # 返回值
+///
+/// * `Ok(f64)` - 百分比
+/// * `Err(String)` - 不是数字或超出范围，供 `clap` 显示
+pub fn parse_percent(value: &str) -> Result<f64, String> {
+    let percent: f64 = value
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("无效的百分比: '{}'", value))?;
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(format!("百分比应在 0 到 100 之间: '{}'", value));
+    }
+    Ok(percent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::LineStats;
+
+    /// 测试各项阈值的检查
+    #[test]
+    fn test_evaluate() {
+        let report = |path: &str, total, empty| FileReport {
+            path: PathBuf::from(path),
+            size: 10,
+            stats: LineStats::new(total, total - empty, empty),
+            binary: false,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: None,
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+        let reports = vec![
+            report("a.rs", 100, 10),
+            report("big.rs", 900, 400),
+            FileReport::missing(PathBuf::from("gone.rs")),
+        ];
+
+        assert!(evaluate(&Thresholds::default(), &reports).is_empty());
+        let relaxed = Thresholds {
+            max_total_lines: Some(1000),
+            max_file_lines: Some(900),
+            max_empty_percent: Some(41.0),
+        };
+        assert!(evaluate(&relaxed, &reports).is_empty());
+
+        let strict = Thresholds {
+            max_total_lines: Some(999),
+            max_file_lines: Some(500),
+            max_empty_percent: Some(40.0),
+        };
+        let violations = evaluate(&strict, &reports);
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0].metric, Metric::FileLines);
+        assert_eq!(violations[0].path, Some(PathBuf::from("big.rs")));
+        assert_eq!(violations[0].actual, 900.0);
+        assert_eq!(violations[1].metric, Metric::TotalLines);
+        assert_eq!(violations[1].actual, 1000.0);
+        assert_eq!(violations[2].metric, Metric::EmptyPercent);
+        assert!((violations[2].actual - 41.0).abs() < 1e-9);
+    }
+
+    /// 测试百分比的解析
+    #[test]
+    fn test_parse_percent() {
+        assert_eq!(parse_percent("25"), Ok(25.0));
+        assert_eq!(parse_percent("12.5%"), Ok(12.5));
+        assert!(parse_percent("101").is_err());
+        assert!(parse_percent("-1").is_err());
+        assert!(parse_percent("abc").is_err());
+    }
+}
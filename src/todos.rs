@@ -0,0 +1,167 @@
+//! 待办标记统计
+//!
+//! `--todos` 统计 `TODO`、`FIXME`、`HACK`、`XXX` 等标记在每个文件中出现的次数，
+//! `--todo-markers` 可以替换默认的标记，`--list-todos` 同时记录每个标记所在的行号和内容。
+//!
+//! 标记区分大小写，并且必须是完整的单词：`TODO:`、`// FIXME(alice)` 会被统计，
+//! `TODOS`、`MY_TODO` 不会。同一行中的多个标记分别计数。
+
+use crate::stats::{TodoItem, TodoStats};
+use regex::Regex;
+
+/// 默认统计的标记
+pub const DEFAULT_MARKERS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// 列出标记时保存的最大字符数
+pub const MAX_TODO_CHARS: usize = 200;
+
+/// 待办标记统计选项
+#[derive(Debug, Clone)]
+pub struct TodoOptions {
+    /// 统计的标记
+    markers: Vec<String>,
+    /// 匹配任意一个标记的正则表达式（较长的标记在前）
+    pattern: Regex,
+    /// 是否记录每个标记的行号和内容
+    pub list: bool,
+}
+
+impl Default for TodoOptions {
+    fn default() -> Self {
+        Self::new(DEFAULT_MARKERS, false)
+    }
+}
+
+impl TodoOptions {
+    /// 创建待办标记统计选项
+    ///
+    /// # 参数
+    ///
+    /// * `markers` - 统计的标记，空白的标记和重复的标记被忽略
+    /// * `list` - 是否记录每个标记的行号和内容
+    pub fn new<S: AsRef<str>>(markers: &[S], list: bool) -> Self {
+        let mut markers: Vec<String> = markers
+            .iter()
+            .map(|marker| marker.as_ref().trim().to_string())
+            .filter(|marker| !marker.is_empty())
+            .collect();
+        markers.sort();
+        markers.dedup();
+
+        // 正则表达式按最左优先匹配，较长的标记放在前面，避免 `TODO` 抢先匹配 `TODOLATER`
+        let mut alternatives: Vec<&String> = markers.iter().collect();
+        alternatives.sort_by_key(|marker| std::cmp::Reverse(marker.len()));
+        let alternatives: Vec<String> = alternatives
+            .into_iter()
+            .map(|marker| regex::escape(marker))
+            .collect();
+        let pattern = if alternatives.is_empty() {
+            // 不匹配任何内容
+            Regex::new(r"[^\s\S]").expect("空模式是有效的正则表达式")
+        } else {
+            Regex::new(&alternatives.join("|")).expect("转义后的标记是有效的正则表达式")
+        };
+
+        Self {
+            markers,
+            pattern,
+            list,
+        }
+    }
+
+    /// 统计的标记，按字母顺序排列
+    pub fn markers(&self) -> &[String] {
+        &self.markers
+    }
+
+    /// 创建每种标记出现 0 次的统计结果
+    pub fn empty_stats(&self) -> TodoStats {
+        TodoStats {
+            counts: self
+                .markers
+                .iter()
+                .map(|marker| (marker.clone(), 0))
+                .collect(),
+            items: Vec::new(),
+        }
+    }
+
+    /// 统计一行中的标记
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 一行内容（不含换行符）
+    /// * `line_number` - 行号（从 1 开始）
+    /// * `stats` - 累加的统计结果
+    pub fn scan(&self, line: &str, line_number: usize, stats: &mut TodoStats) {
+        for found in self.pattern.find_iter(line) {
+            let before = line[..found.start()].chars().next_back();
+            let after = line[found.end()..].chars().next();
+            if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+                continue;
+            }
+
+            *stats.counts.entry(found.as_str().to_string()).or_default() += 1;
+            if self.list {
+                stats.items.push(TodoItem {
+                    line: line_number,
+                    marker: found.as_str().to_string(),
+                    text: line[found.start()..]
+                        .trim_end()
+                        .chars()
+                        .take(MAX_TODO_CHARS)
+                        .collect(),
+                });
+            }
+        }
+    }
+}
+
+/// 是否为单词的一部分（字母、数字或下划线）
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试标记按完整的单词匹配并分别计数
+    #[test]
+    fn test_scan() {
+        let options = TodoOptions::new(DEFAULT_MARKERS, true);
+        let mut stats = options.empty_stats();
+        options.scan("// TODO: parse flags", 3, &mut stats);
+        options.scan("x = 1  # FIXME(alice) and TODO", 7, &mut stats);
+        options.scan("TODOS MY_TODO todo XXXL", 9, &mut stats);
+
+        assert_eq!(stats.total(), 3);
+        assert_eq!(stats.counts["TODO"], 2);
+        assert_eq!(stats.counts["FIXME"], 1);
+        assert_eq!(stats.counts["HACK"], 0);
+        assert_eq!(stats.counts["XXX"], 0);
+        assert_eq!(stats.items.len(), 3);
+        assert_eq!(stats.items[0].line, 3);
+        assert_eq!(stats.items[0].text, "TODO: parse flags");
+        assert_eq!(stats.items[1].marker, "FIXME");
+        assert_eq!(stats.items[2].text, "TODO");
+    }
+
+    /// 测试自定义标记与不记录位置
+    #[test]
+    fn test_custom_markers() {
+        let options = TodoOptions::new(&["NOTE", " ", "NOTE", "NOTEWORTHY"], false);
+        assert_eq!(options.markers(), ["NOTE", "NOTEWORTHY"]);
+
+        let mut stats = options.empty_stats();
+        options.scan("NOTEWORTHY: NOTE 注意", 1, &mut stats);
+        assert_eq!(stats.counts["NOTE"], 1);
+        assert_eq!(stats.counts["NOTEWORTHY"], 1);
+        assert!(stats.items.is_empty());
+
+        let options = TodoOptions::new::<&str>(&[], false);
+        let mut stats = options.empty_stats();
+        options.scan("TODO", 1, &mut stats);
+        assert_eq!(stats.total(), 0);
+    }
+}
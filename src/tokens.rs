@@ -0,0 +1,155 @@
+//! 大语言模型 token 数估计
+//!
+//! `--tokens` 统计每个文件大约对应多少个 token。默认的 [`Tokenizer::Approx`] 按字符类别估计，
+//! 不依赖词表：ASCII 单词约每 6 个字符一个 token，标点约每 3 个字符一个 token，
+//! 汉字等全角字符每个一个 token，行首缩进与换行符合并为一个 token。
+//! 对常见的源代码和中英文文档，估计值与 cl100k_base 的实际值通常相差在 10% 左右。
+//!
+//! 启用 `tiktoken` 特性后可以使用真实的 BPE 词表（[`Tokenizer::Cl100k`]、[`Tokenizer::O200k`]）。
+//! 为了能逐行、分块并行统计，每行单独编码，换行符计为一个 token，
+//! 因此连续空行会比整体编码多出几个 token。
+
+use crate::stats::TokenCount;
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use unicode_width::UnicodeWidthChar;
+
+/// 统计 token 数使用的分词方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
+pub enum Tokenizer {
+    /// 按字符类别估计，不依赖词表
+    #[default]
+    Approx,
+    /// GPT-4、GPT-3.5 使用的 cl100k_base 词表（需要 `tiktoken` 特性）
+    #[cfg(feature = "tiktoken")]
+    #[cfg_attr(feature = "cli", value(name = "cl100k"))]
+    #[cfg_attr(feature = "serde", serde(rename = "cl100k"))]
+    Cl100k,
+    /// GPT-4o 使用的 o200k_base 词表（需要 `tiktoken` 特性）
+    #[cfg(feature = "tiktoken")]
+    #[cfg_attr(feature = "cli", value(name = "o200k"))]
+    #[cfg_attr(feature = "serde", serde(rename = "o200k"))]
+    O200k,
+}
+
+impl Tokenizer {
+    /// 统计结果是否为估计值
+    pub fn is_estimate(self) -> bool {
+        matches!(self, Tokenizer::Approx)
+    }
+
+    /// 空的统计结果
+    pub fn empty_count(self) -> TokenCount {
+        TokenCount {
+            tokens: 0,
+            estimated: self.is_estimate(),
+        }
+    }
+
+    /// 统计一行（包括行尾的换行符）的 token 数
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 行内容（不含换行符）
+    pub fn count_line(self, line: &str) -> usize {
+        match self {
+            Tokenizer::Approx => estimate_line(line),
+            #[cfg(feature = "tiktoken")]
+            Tokenizer::Cl100k => {
+                tiktoken_rs::cl100k_base_singleton()
+                    .encode_ordinary(line)
+                    .len()
+                    + 1
+            }
+            #[cfg(feature = "tiktoken")]
+            Tokenizer::O200k => {
+                tiktoken_rs::o200k_base_singleton()
+                    .encode_ordinary(line)
+                    .len()
+                    + 1
+            }
+        }
+    }
+}
+
+/// 按字符类别估计一行（包括行尾的换行符）的 token 数
+fn estimate_line(line: &str) -> usize {
+    // 行首缩进与换行符合并为一个 token
+    let mut tokens: usize = 1;
+    let mut chars = line.trim_start().chars().peekable();
+    let is_word = |c: &char| c.is_ascii_alphanumeric() || *c == '_';
+    while let Some(c) = chars.next() {
+        let mut run: usize = 1;
+        if is_word(&c) {
+            while chars.next_if(is_word).is_some() {
+                run += 1;
+            }
+            tokens += run.div_ceil(6);
+        } else if c == ' ' {
+            while chars.next_if_eq(&' ').is_some() {
+                run += 1;
+            }
+            // 单个空格与后面的单词合并
+            if run > 1 {
+                tokens += 1;
+            }
+        } else if c.is_ascii() {
+            while chars.next_if(|c| c.is_ascii_punctuation()).is_some() {
+                run += 1;
+            }
+            tokens += run.div_ceil(3);
+        } else if c.width() == Some(2) {
+            tokens += 1;
+        } else {
+            while chars
+                .next_if(|c| !c.is_ascii() && c.width() != Some(2))
+                .is_some()
+            {
+                run += 1;
+            }
+            tokens += run.div_ceil(2);
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试按字符类别估计 token 数
+    #[test]
+    fn test_estimate_line() {
+        assert_eq!(estimate_line(""), 1);
+        // 缩进与换行符合并
+        assert_eq!(estimate_line("        "), 1);
+        // 短单词与前面的空格合并为一个 token
+        assert_eq!(estimate_line("the quick brown"), 1 + 3);
+        assert_eq!(estimate_line("internationalization"), 1 + 4);
+        assert_eq!(estimate_line("    let x = 1;"), 1 + 1 + 1 + 1 + 1 + 1);
+        assert_eq!(estimate_line("a    b"), 1 + 1 + 1 + 1);
+        assert_eq!(estimate_line("你好，世界"), 1 + 5);
+        assert_eq!(estimate_line("café"), 1 + 1 + 1);
+    }
+
+    /// 测试估计值的标记
+    #[test]
+    fn test_empty_count() {
+        let count = Tokenizer::Approx.empty_count();
+        assert_eq!(count.tokens, 0);
+        assert!(count.estimated);
+    }
+
+    /// 测试使用 BPE 词表统计 token 数
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn test_bpe() {
+        assert_eq!(Tokenizer::Cl100k.count_line("hello world"), 2 + 1);
+        assert_eq!(Tokenizer::O200k.count_line(""), 1);
+        assert!(!Tokenizer::Cl100k.is_estimate());
+    }
+}
@@ -0,0 +1,921 @@
+//! 交互式浏览模式
+//!
+//! `line-counter tui [目录]` 在终端中以目录树的形式浏览统计结果：每一行是一个文件或目录，
+//! 目录的数值是其中所有文件的合计，目录可以展开和折叠。
+//! 启动时先列出全部文件，统计在后台线程中并行进行，每个文件的结果到达后立即更新该文件及其所有上层目录，
+//! 大型代码库不必等统计完成就可以开始浏览。
+//!
+//! 按键：`↑` / `↓`（`k` / `j`）移动，`→`（`l`）展开，`←`（`h`）折叠或回到上层目录，`Enter` 切换展开，
+//! `s` 切换排序的列，`r` 反转排序，`/` 输入过滤条件（只显示相对路径包含该文本的文件及其上层目录），
+//! `Esc` 清除过滤条件，`q` 退出。
+
+use crate::app::{ReadOptions, count_file, error_chain};
+use crate::cli::TuiArgs;
+use crate::config::NeverRead;
+use crate::counter::CountOptions;
+use crate::headers::HeaderOptions;
+use crate::locale::Lang;
+use crate::report::FileReport;
+use crate::walk::{SymlinkPolicy, collect_files};
+use anyhow::{Context, Result, bail};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Cell, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// 等待按键的时间，同时也是刷新统计进度的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 单个文件的统计结果
+type Outcome = std::result::Result<FileReport, String>;
+
+/// 运行交互式浏览模式
+///
+/// # 参数
+///
+/// * `args` - `tui` 子命令的参数
+/// * `never_read` - 配置中永远不读取的路径，目录中匹配的文件不显示
+/// * `lang` - 界面语言
+///
+/// # 返回值
+///
+/// * `Ok(())` - 用户退出
+/// * `Err(anyhow::Error)` - 目录无效、无法遍历或终端操作失败
+pub fn run(args: &TuiArgs, never_read: &NeverRead, lang: Lang) -> Result<()> {
+    if !args.dir.is_dir() {
+        bail!("'{}' 不是目录", args.dir.display());
+    }
+    never_read.check(&args.dir)?;
+    let mut files = collect_files(std::slice::from_ref(&args.dir), true, SymlinkPolicy::Follow)
+        .with_context(|| format!("无法遍历目录 '{}'", args.dir.display()))?;
+    files.retain(|path| never_read.matching(path).is_none());
+
+    let options = CountOptions {
+        headers: HeaderOptions {
+            exclude: args.exclude_headers,
+            ..HeaderOptions::default()
+        },
+        ..CountOptions::default()
+    };
+    let mut app = App::new(Tree::new(&args.dir, &files));
+    let receiver = spawn_counting(files, options, args.jobs, lang)?;
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, &receiver, lang);
+    ratatui::restore();
+    result
+}
+
+/// 在后台线程中并行统计文件，每个文件统计完成后立即发送结果
+///
+/// 界面退出后接收端被丢弃，剩余的结果直接丢弃。
+fn spawn_counting(
+    files: Vec<PathBuf>,
+    options: CountOptions,
+    jobs: usize,
+    lang: Lang,
+) -> Result<Receiver<(PathBuf, Outcome)>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .context("无法创建线程池")?;
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let read_options = ReadOptions::default();
+        pool.install(|| {
+            files.par_iter().for_each_with(sender, |sender, path| {
+                let outcome = count_file(path, &options, &read_options)
+                    .map_err(|e| error_chain(&e, lang).join(": "));
+                let _ = sender.send((path.clone(), outcome));
+            })
+        });
+    });
+    Ok(receiver)
+}
+
+/// 绘制界面、接收统计结果并处理按键，直到用户退出
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    receiver: &Receiver<(PathBuf, Outcome)>,
+    lang: Lang,
+) -> Result<()> {
+    loop {
+        let mut received = false;
+        for (path, outcome) in receiver.try_iter() {
+            app.tree.record(&path, outcome);
+            received = true;
+        }
+        if received {
+            app.refresh();
+        }
+
+        terminal.draw(|frame| draw(frame, app, lang))?;
+
+        if event::poll(POLL_INTERVAL)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !app.handle_key(key)
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// 排序的列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// 名称（目录在前）
+    Name,
+    /// 总行数
+    Lines,
+    /// 非空行数
+    NonEmpty,
+    /// 空行数
+    Empty,
+    /// 文件数
+    Files,
+}
+
+impl Column {
+    /// 按界面中的顺序排列的所有列
+    const ALL: [Column; 5] = [
+        Column::Name,
+        Column::Lines,
+        Column::NonEmpty,
+        Column::Empty,
+        Column::Files,
+    ];
+
+    /// 下一列，最后一列之后回到第一列
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|column| *column == self);
+        Self::ALL[index.map_or(0, |index| (index + 1) % Self::ALL.len())]
+    }
+
+    /// 列标题
+    fn title(self) -> &'static str {
+        match self {
+            Column::Name => "名称",
+            Column::Lines => "总行数",
+            Column::NonEmpty => "非空行数",
+            Column::Empty => "空行数",
+            Column::Files => "文件数",
+        }
+    }
+
+    /// 按该列排序时比较的数值
+    fn value(self, totals: &Totals) -> usize {
+        match self {
+            Column::Name => 0,
+            Column::Lines => totals.total_lines,
+            Column::NonEmpty => totals.non_empty_lines,
+            Column::Empty => totals.empty_lines,
+            Column::Files => totals.files,
+        }
+    }
+}
+
+/// 文件或目录下所有文件的合计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Totals {
+    /// 文件数
+    pub files: usize,
+    /// 尚未统计的文件数
+    pub pending: usize,
+    /// 统计失败的文件数
+    pub failed: usize,
+    /// 总行数
+    pub total_lines: usize,
+    /// 非空行数
+    pub non_empty_lines: usize,
+    /// 空行数
+    pub empty_lines: usize,
+}
+
+/// 文件的统计状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FileState {
+    /// 尚未统计
+    Pending,
+    /// 已统计
+    Counted,
+    /// 被跳过，值为原因（二进制文件或超过解压缩限制）
+    Skipped(&'static str),
+    /// 统计失败，值为错误信息
+    Failed(String),
+}
+
+/// 目录树中的一个文件或目录
+#[derive(Debug, Clone)]
+struct Node {
+    /// 显示的名称：根目录为给出的路径，其余为最后一级文件名
+    name: String,
+    /// 相对于根目录的路径，用于过滤
+    relative: String,
+    /// 上层目录，根目录为 `None`
+    parent: Option<usize>,
+    /// 是否为目录
+    dir: bool,
+    /// 目录是否展开
+    expanded: bool,
+    /// 目录中的文件和子目录
+    children: Vec<usize>,
+    /// 文件的统计状态，目录总是 [`FileState::Pending`]
+    state: FileState,
+    /// 合计
+    totals: Totals,
+}
+
+impl Node {
+    fn new(name: String, relative: String, parent: Option<usize>, dir: bool) -> Self {
+        Self {
+            name,
+            relative,
+            parent,
+            dir,
+            expanded: false,
+            children: Vec::new(),
+            state: FileState::Pending,
+            totals: Totals::default(),
+        }
+    }
+}
+
+/// 界面中的一行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TreeRow {
+    /// 文件或目录
+    node: usize,
+    /// 缩进层级，根目录为 0
+    depth: usize,
+}
+
+/// 文件和目录组成的树，根目录的下标为 0
+#[derive(Debug, Clone)]
+pub struct Tree {
+    nodes: Vec<Node>,
+    /// 文件路径（与统计时使用的路径相同）到下标的映射
+    files: HashMap<PathBuf, usize>,
+}
+
+impl Tree {
+    /// 由目录中的文件建立目录树，所有文件都处于尚未统计的状态
+    ///
+    /// # 参数
+    ///
+    /// * `root` - 根目录
+    /// * `files` - 根目录中的文件
+    pub fn new(root: &Path, files: &[PathBuf]) -> Self {
+        let mut root_node = Node::new(root.display().to_string(), String::new(), None, true);
+        root_node.expanded = true;
+        let mut tree = Self {
+            nodes: vec![root_node],
+            files: HashMap::new(),
+        };
+        let mut dirs: HashMap<PathBuf, usize> = HashMap::new();
+
+        for path in files {
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            let mut parent = 0;
+            let mut prefix = PathBuf::new();
+            let components: Vec<_> = relative.components().collect();
+            for (index, component) in components.iter().enumerate() {
+                prefix.push(component);
+                let name = component.as_os_str().to_string_lossy().into_owned();
+                let relative = prefix.to_string_lossy().into_owned();
+                if index + 1 == components.len() {
+                    let node = tree.push(Node::new(name, relative, Some(parent), false));
+                    tree.files.insert(path.clone(), node);
+                    tree.add(node, |totals| {
+                        totals.files += 1;
+                        totals.pending += 1;
+                    });
+                } else {
+                    parent = match dirs.get(&prefix) {
+                        Some(&dir) => dir,
+                        None => {
+                            let dir = tree.push(Node::new(name, relative, Some(parent), true));
+                            dirs.insert(prefix.clone(), dir);
+                            dir
+                        }
+                    };
+                }
+            }
+        }
+        tree
+    }
+
+    /// 加入节点并登记到上层目录中
+    fn push(&mut self, node: Node) -> usize {
+        let index = self.nodes.len();
+        if let Some(parent) = node.parent {
+            self.nodes[parent].children.push(index);
+        }
+        self.nodes.push(node);
+        index
+    }
+
+    /// 修改节点及其所有上层目录的合计
+    fn add(&mut self, node: usize, update: impl Fn(&mut Totals)) {
+        let mut current = Some(node);
+        while let Some(index) = current {
+            update(&mut self.nodes[index].totals);
+            current = self.nodes[index].parent;
+        }
+    }
+
+    /// 记录文件的统计结果，更新该文件及其所有上层目录的合计
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径
+    /// * `outcome` - 统计报告，或统计失败时的错误信息
+    pub fn record(&mut self, path: &Path, outcome: Outcome) {
+        let Some(&node) = self.files.get(path) else {
+            return;
+        };
+        if self.nodes[node].state != FileState::Pending {
+            return;
+        }
+        let state = match &outcome {
+            Ok(report) if report.binary => FileState::Skipped("二进制文件"),
+            Ok(report) if report.is_skipped() => FileState::Skipped("超过解压缩限制"),
+            Ok(_) => FileState::Counted,
+            Err(message) => FileState::Failed(message.clone()),
+        };
+        self.add(node, |totals| {
+            totals.pending -= 1;
+            match &outcome {
+                Ok(report) => {
+                    totals.total_lines += report.stats.total_lines;
+                    totals.non_empty_lines += report.stats.non_empty_lines;
+                    totals.empty_lines += report.stats.empty_lines;
+                }
+                Err(_) => totals.failed += 1,
+            }
+        });
+        self.nodes[node].state = state;
+    }
+
+    /// 根目录的合计
+    pub fn totals(&self) -> Totals {
+        self.nodes[0].totals
+    }
+
+    /// 当前应当显示的行
+    ///
+    /// # 参数
+    ///
+    /// * `column` - 排序的列：名称升序（目录在前），数值降序
+    /// * `reverse` - 反转排序
+    /// * `filter` - 过滤条件，不为空时只显示相对路径包含该文本（不区分大小写）的文件及其上层目录，
+    ///   并展开所有目录
+    fn rows(&self, column: Column, reverse: bool, filter: &str) -> Vec<TreeRow> {
+        let visible = (!filter.is_empty()).then(|| self.matching(filter));
+        let mut rows = Vec::new();
+        self.push_rows(0, 0, column, reverse, visible.as_deref(), &mut rows);
+        rows
+    }
+
+    /// 按顺序加入节点及其展开的子节点
+    fn push_rows(
+        &self,
+        node: usize,
+        depth: usize,
+        column: Column,
+        reverse: bool,
+        visible: Option<&[bool]>,
+        rows: &mut Vec<TreeRow>,
+    ) {
+        rows.push(TreeRow { node, depth });
+        let current = &self.nodes[node];
+        if !current.dir || (visible.is_none() && !current.expanded) {
+            return;
+        }
+        let mut children: Vec<usize> = current
+            .children
+            .iter()
+            .copied()
+            .filter(|&child| visible.is_none_or(|visible| visible[child]))
+            .collect();
+        children.sort_by(|&a, &b| {
+            let ordering = self.compare(a, b, column);
+            if reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        for child in children {
+            self.push_rows(child, depth + 1, column, reverse, visible, rows);
+        }
+    }
+
+    /// 比较同一目录中的两个节点
+    fn compare(&self, a: usize, b: usize, column: Column) -> Ordering {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        match column {
+            Column::Name => b.dir.cmp(&a.dir),
+            _ => column.value(&b.totals).cmp(&column.value(&a.totals)),
+        }
+        .then_with(|| a.name.cmp(&b.name))
+    }
+
+    /// 标出匹配过滤条件的文件及其上层目录
+    fn matching(&self, filter: &str) -> Vec<bool> {
+        let filter = filter.to_lowercase();
+        let mut visible = vec![false; self.nodes.len()];
+        visible[0] = true;
+        for &node in self.files.values() {
+            if self.nodes[node].relative.to_lowercase().contains(&filter) {
+                let mut current = Some(node);
+                while let Some(index) = current
+                    && !visible[index]
+                {
+                    visible[index] = true;
+                    current = self.nodes[index].parent;
+                }
+            }
+        }
+        visible
+    }
+}
+
+/// 界面状态
+#[derive(Debug)]
+pub struct App {
+    /// 目录树
+    tree: Tree,
+    /// 排序的列
+    column: Column,
+    /// 是否反转排序
+    reverse: bool,
+    /// 过滤条件
+    filter: String,
+    /// 是否正在输入过滤条件
+    editing: bool,
+    /// 当前显示的行
+    rows: Vec<TreeRow>,
+    /// 选中的行和滚动位置
+    table: TableState,
+    /// 表格一页能显示的行数，用于翻页
+    page: usize,
+}
+
+impl App {
+    /// 创建界面状态，选中根目录
+    pub fn new(tree: Tree) -> Self {
+        let mut app = Self {
+            tree,
+            column: Column::Name,
+            reverse: false,
+            filter: String::new(),
+            editing: false,
+            rows: Vec::new(),
+            table: TableState::default().with_selected(Some(0)),
+            page: 1,
+        };
+        app.refresh();
+        app
+    }
+
+    /// 选中的行的下标
+    fn selected(&self) -> usize {
+        self.table.selected().unwrap_or(0)
+    }
+
+    /// 选中的文件或目录
+    fn selected_node(&self) -> Option<usize> {
+        self.rows.get(self.selected()).map(|row| row.node)
+    }
+
+    /// 选中一行，超出范围时选中最近的一行
+    fn select(&mut self, row: usize) {
+        self.table
+            .select(Some(row.min(self.rows.len().saturating_sub(1))));
+    }
+
+    /// 重新计算显示的行，保持选中同一个文件或目录
+    fn refresh(&mut self) {
+        let selected = self.selected_node();
+        self.rows = self.tree.rows(self.column, self.reverse, &self.filter);
+        let row = selected
+            .and_then(|node| self.rows.iter().position(|row| row.node == node))
+            .unwrap_or(self.selected());
+        self.select(row);
+    }
+
+    /// 展开或折叠选中的目录
+    fn set_expanded(&mut self, expanded: bool) {
+        if let Some(node) = self.selected_node()
+            && self.tree.nodes[node].dir
+        {
+            self.tree.nodes[node].expanded = expanded;
+        }
+    }
+
+    /// 折叠选中的目录；选中的是文件或已折叠的目录时选中上层目录
+    fn collapse(&mut self) {
+        let Some(node) = self.selected_node() else {
+            return;
+        };
+        let current = &self.tree.nodes[node];
+        if current.dir && current.expanded && self.filter.is_empty() {
+            self.set_expanded(false);
+        } else if let Some(parent) = current.parent
+            && let Some(row) = self.rows.iter().position(|row| row.node == parent)
+        {
+            self.select(row);
+        }
+    }
+
+    /// 处理按键
+    ///
+    /// # 返回值
+    ///
+    /// 用户退出时为 `false`
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.editing {
+            match key.code {
+                KeyCode::Enter => self.editing = false,
+                KeyCode::Esc => {
+                    self.editing = false;
+                    self.filter.clear();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => return true,
+            }
+            self.refresh();
+            return true;
+        }
+
+        let selected = self.selected();
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char('q') => return false,
+            KeyCode::Esc if self.filter.is_empty() => return false,
+            KeyCode::Esc => self.filter.clear(),
+            KeyCode::Up | KeyCode::Char('k') => self.select(selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(selected + 1),
+            KeyCode::PageUp => self.select(selected.saturating_sub(self.page)),
+            KeyCode::PageDown => self.select(selected + self.page),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(usize::MAX),
+            KeyCode::Right | KeyCode::Char('l') => self.set_expanded(true),
+            KeyCode::Left | KeyCode::Char('h') => self.collapse(),
+            KeyCode::Enter => {
+                if let Some(node) = self.selected_node() {
+                    self.set_expanded(!self.tree.nodes[node].expanded);
+                }
+            }
+            KeyCode::Char('s') => self.column = self.column.next(),
+            KeyCode::Char('r') => self.reverse = !self.reverse,
+            KeyCode::Char('/') => self.editing = true,
+            _ => return true,
+        }
+        self.refresh();
+        true
+    }
+}
+
+/// 绘制界面：标题行显示统计进度，中间是目录树，底部是按键说明或过滤条件
+fn draw(frame: &mut Frame, app: &mut App, lang: Lang) {
+    let [title_area, table_area, footer_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let totals = app.tree.totals();
+    let mut title = format!(
+        "{}  {}",
+        app.tree.nodes[0].name,
+        lang.format(
+            "已统计 {} / {} 个文件",
+            &[&(totals.files - totals.pending), &totals.files]
+        )
+    );
+    if totals.failed > 0 {
+        title.push_str(&lang.format("，失败 {}", &[&totals.failed]));
+    }
+    frame.render_widget(
+        Line::from(title).style(Style::new().add_modifier(Modifier::BOLD)),
+        title_area,
+    );
+
+    let header = Row::new(Column::ALL.map(|column| {
+        let title = lang.tr(column.title());
+        if column != app.column {
+            return Cell::from(title);
+        }
+        // 名称默认升序，数值默认降序
+        let ascending = (column == Column::Name) != app.reverse;
+        Cell::from(format!("{} {}", title, if ascending { "▲" } else { "▼" }))
+    }))
+    .style(Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED));
+    let rows = app.rows.iter().map(|row| {
+        let node = &app.tree.nodes[row.node];
+        let marker = match (node.dir, node.expanded || !app.filter.is_empty()) {
+            (true, true) => "▾ ",
+            (true, false) => "▸ ",
+            (false, _) => "  ",
+        };
+        let suffix = if node.dir && row.node != 0 { "/" } else { "" };
+        let name = format!(
+            "{}{}{}{}",
+            "  ".repeat(row.depth),
+            marker,
+            node.name,
+            suffix
+        );
+        let totals = &node.totals;
+        let numbers = match &node.state {
+            _ if node.dir => {
+                // 统计尚未完成的目录在总行数后标出省略号，表示数值还会增加
+                let pending = if totals.pending > 0 { "…" } else { "" };
+                [
+                    format!("{}{}", totals.total_lines, pending),
+                    totals.non_empty_lines.to_string(),
+                    totals.empty_lines.to_string(),
+                    totals.files.to_string(),
+                ]
+            }
+            FileState::Pending => ["…".to_string(), String::new(), String::new(), String::new()],
+            FileState::Counted => [
+                totals.total_lines.to_string(),
+                totals.non_empty_lines.to_string(),
+                totals.empty_lines.to_string(),
+                String::new(),
+            ],
+            FileState::Skipped(reason) => [
+                lang.tr(reason).to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ],
+            FileState::Failed(_) => [
+                lang.tr("错误").to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ],
+        };
+        let mut cells = vec![Cell::from(name)];
+        cells.extend(numbers.map(|number| Cell::from(Line::from(number).right_aligned())));
+        let row = Row::new(cells);
+        if matches!(node.state, FileState::Failed(_)) {
+            row.style(Style::new().add_modifier(Modifier::DIM))
+        } else {
+            row
+        }
+    });
+    let widths = [
+        Constraint::Fill(1),
+        Constraint::Length(14),
+        Constraint::Length(12),
+        Constraint::Length(10),
+        Constraint::Length(10),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    app.page = usize::from(table_area.height.saturating_sub(1)).max(1);
+    frame.render_stateful_widget(table, table_area, &mut app.table);
+
+    let footer = if app.editing {
+        format!(
+            "{}{}▏  {}",
+            lang.tr("过滤: "),
+            app.filter,
+            lang.tr("Enter 确认  Esc 清除")
+        )
+    } else if let Some(node) = app.selected_node()
+        && let FileState::Failed(message) = &app.tree.nodes[node].state
+    {
+        message.clone()
+    } else {
+        let help = lang.tr("↑↓ 移动  ←→ 折叠/展开  s 排序  r 反转  / 过滤  q 退出");
+        if app.filter.is_empty() {
+            help.to_string()
+        } else {
+            format!("{}{}  {}", lang.tr("过滤: "), app.filter, help)
+        }
+    };
+    frame.render_widget(
+        Line::from(footer).style(Style::new().add_modifier(Modifier::DIM)),
+        footer_area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::LineStats;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use unicode_width::UnicodeWidthStr;
+
+    /// 统计结果为指定行数的报告
+    fn report(path: &str, total: usize, empty: usize) -> Outcome {
+        let mut report = FileReport::missing(PathBuf::from(path));
+        report.missing = false;
+        report.stats = LineStats::new(total, total - empty, empty);
+        Ok(report)
+    }
+
+    /// 根目录 `root` 中的示例目录树
+    fn sample() -> Tree {
+        let files: Vec<PathBuf> = [
+            "root/b.txt",
+            "root/src/main.rs",
+            "root/src/lib.rs",
+            "root/a.md",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        Tree::new(Path::new("root"), &files)
+    }
+
+    /// 显示的每一行的名称（带缩进）
+    fn names(app: &App) -> Vec<String> {
+        app.rows
+            .iter()
+            .map(|row| {
+                format!(
+                    "{}{}",
+                    "  ".repeat(row.depth),
+                    app.tree.nodes[row.node].name
+                )
+            })
+            .collect()
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    /// 测试建立目录树与统计结果沿上层目录累加
+    #[test]
+    fn test_record() {
+        let mut tree = sample();
+        assert_eq!(tree.totals().files, 4);
+        assert_eq!(tree.totals().pending, 4);
+
+        tree.record(
+            Path::new("root/src/main.rs"),
+            report("root/src/main.rs", 10, 2),
+        );
+        tree.record(
+            Path::new("root/src/lib.rs"),
+            Err("无法读取文件".to_string()),
+        );
+        tree.record(Path::new("root/b.txt"), report("root/b.txt", 5, 0));
+        // 重复的结果和未知的路径被忽略
+        tree.record(Path::new("root/b.txt"), report("root/b.txt", 5, 0));
+        tree.record(Path::new("other.txt"), report("other.txt", 1, 0));
+
+        let totals = tree.totals();
+        assert_eq!(totals.pending, 1);
+        assert_eq!(totals.failed, 1);
+        assert_eq!(totals.total_lines, 15);
+        assert_eq!(totals.non_empty_lines, 13);
+        assert_eq!(totals.empty_lines, 2);
+
+        let src = tree.files[Path::new("root/src/main.rs")];
+        let src = tree.nodes[src].parent.unwrap();
+        assert_eq!(tree.nodes[src].name, "src");
+        assert_eq!(tree.nodes[src].totals.total_lines, 10);
+        assert_eq!(tree.nodes[src].totals.failed, 1);
+    }
+
+    /// 测试排序、展开折叠和过滤
+    #[test]
+    fn test_rows() {
+        let mut tree = sample();
+        tree.record(
+            Path::new("root/src/main.rs"),
+            report("root/src/main.rs", 10, 2),
+        );
+        tree.record(
+            Path::new("root/src/lib.rs"),
+            report("root/src/lib.rs", 3, 0),
+        );
+        tree.record(Path::new("root/b.txt"), report("root/b.txt", 20, 0));
+        tree.record(Path::new("root/a.md"), report("root/a.md", 1, 0));
+        let mut app = App::new(tree);
+
+        // 默认按名称排序，目录在前，根目录展开
+        assert_eq!(names(&app), ["root", "  src", "  a.md", "  b.txt"]);
+
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Right));
+        assert_eq!(
+            names(&app),
+            [
+                "root",
+                "  src",
+                "    lib.rs",
+                "    main.rs",
+                "  a.md",
+                "  b.txt"
+            ]
+        );
+
+        // 按总行数降序，选中的目录保持选中
+        app.handle_key(key(KeyCode::Char('s')));
+        assert_eq!(
+            names(&app),
+            [
+                "root",
+                "  b.txt",
+                "  src",
+                "    main.rs",
+                "    lib.rs",
+                "  a.md"
+            ]
+        );
+        assert_eq!(app.selected(), 2);
+        app.handle_key(key(KeyCode::Char('r')));
+        assert_eq!(
+            names(&app),
+            [
+                "root",
+                "  a.md",
+                "  src",
+                "    lib.rs",
+                "    main.rs",
+                "  b.txt"
+            ]
+        );
+
+        // 在文件上按 ← 回到上层目录，再按 ← 折叠
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Left));
+        assert_eq!(app.selected(), 2);
+        app.handle_key(key(KeyCode::Left));
+        assert_eq!(names(&app), ["root", "  a.md", "  src", "  b.txt"]);
+
+        // 过滤时展开所有目录，只显示匹配的文件及其上层目录
+        for code in [KeyCode::Char('/'), KeyCode::Char('M'), KeyCode::Char('a')] {
+            app.handle_key(key(code));
+        }
+        assert_eq!(names(&app), ["root", "  src", "    main.rs"]);
+        // 输入过滤条件时 q 是普通字符
+        assert!(app.handle_key(key(KeyCode::Char('q'))));
+        assert_eq!(names(&app), ["root"]);
+        app.handle_key(key(KeyCode::Backspace));
+        app.handle_key(key(KeyCode::Enter));
+        assert!(app.handle_key(key(KeyCode::Esc)));
+        assert_eq!(names(&app), ["root", "  a.md", "  src", "  b.txt"]);
+
+        assert!(!app.handle_key(key(KeyCode::Esc)));
+        assert!(!app.handle_key(key(KeyCode::Char('q'))));
+    }
+
+    /// 测试界面的绘制
+    #[test]
+    fn test_draw() {
+        let mut tree = sample();
+        tree.record(Path::new("root/b.txt"), report("root/b.txt", 20, 0));
+        tree.record(Path::new("root/a.md"), Err("权限不足".to_string()));
+        let mut app = App::new(tree);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 8)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &mut app, Lang::Zh))
+            .unwrap();
+        // 宽字符之后的单元格只用于占位，不属于显示的文本
+        let mut screen = String::new();
+        let mut skip = 0;
+        for (index, cell) in terminal.backend().buffer().content().iter().enumerate() {
+            if index > 0 && index % 80 == 0 {
+                screen.push('\n');
+            }
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            screen.push_str(cell.symbol());
+            skip = cell.symbol().width().saturating_sub(1);
+        }
+        assert!(screen.contains("已统计 2 / 4 个文件，失败 1"));
+        assert!(screen.contains("名称 ▲"));
+        assert!(screen.contains("20…"));
+        assert!(screen.contains("▸ src/"));
+        assert!(screen.contains("错误"));
+    }
+}
@@ -0,0 +1,246 @@
+//! 大小单位解析与数量的简写
+//!
+//! 解析命令行中带单位的大小，例如 `512`、`64K`、`100MB`、`2G`。
+//! 单位不区分大小写，均按 1024 进制计算（`K`、`KB`、`KiB` 含义相同）。
+//!
+//! [`format_compact`] 把数量按 1000 进制简写为 `12.3k` 的形式，用于徽章等空间有限的输出；
+//! [`format_count`] 和 [`format_size`] 是 `--human` 使用的数量和大小格式（`12,345`、`1.2M`、`1.4 MiB`）；
+//! [`format_duration`] 把耗时格式化为 `12 ms` 或 `1.50 s` 的形式。
+
+use std::time::Duration;
+
+/// 解析带单位的大小
+///
+/// # 参数
+///
+/// * `value` - 大小字符串，数字部分可以是小数（如 `1.5G`）
+///
+/// # 返回值
+///
+/// * `Ok(u64)` - 字节数
+/// * `Err(String)` - 格式无效或超出范围，供 `clap` 显示
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => {
+            return Err(format!(
+                "无法识别的大小单位: '{}'（支持 B、K、M、G、T）",
+                unit
+            ));
+        }
+    };
+
+    if let Ok(number) = number.parse::<u64>() {
+        return number
+            .checked_mul(multiplier)
+            .ok_or_else(|| format!("大小超出范围: '{}'", value));
+    }
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("无效的大小: '{}'", value))?;
+    let bytes = number * multiplier as f64;
+    if bytes > u64::MAX as f64 {
+        return Err(format!("大小超出范围: '{}'", value));
+    }
+    Ok(bytes.round() as u64)
+}
+
+/// 把数量简写为带 `k`、`M`、`G` 后缀的形式
+///
+/// # 参数
+///
+/// * `count` - 数量（如行数）
+///
+/// # 返回值
+///
+/// 小于 1000 时原样输出；否则保留一位小数并去掉末尾的 `.0`，如 `12.3k`、`45k`、`1.2M`
+pub fn format_compact(count: u64) -> String {
+    const SUFFIXES: [&str; 4] = ["k", "M", "G", "T"];
+    if count < 1000 {
+        return count.to_string();
+    }
+    let mut value = count as f64;
+    let mut suffix = "";
+    for next in SUFFIXES {
+        // 按四舍五入后的值判断，避免出现 `1000k`
+        if (value * 10.0).round() / 10.0 < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        suffix = next;
+    }
+    let text = format!("{:.1}", value);
+    format!("{}{}", text.strip_suffix(".0").unwrap_or(&text), suffix)
+}
+
+/// 给数量加上千位分隔符
+///
+/// # 参数
+///
+/// * `count` - 数量
+///
+/// # 返回值
+///
+/// 每三位用逗号分隔的数字，如 `1,234,567`
+pub fn format_thousands(count: u64) -> String {
+    let digits = count.to_string();
+    let mut text = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            text.push(',');
+        }
+        text.push(digit);
+    }
+    text
+}
+
+/// 把数量格式化为便于阅读的形式
+///
+/// # 参数
+///
+/// * `count` - 数量（如行数）
+///
+/// # 返回值
+///
+/// 小于 10000 时带千位分隔符（如 `9,999`），否则按 [`format_compact`] 简写（如 `12.3k`、`1.2M`）
+pub fn format_count(count: u64) -> String {
+    if count < 10_000 {
+        format_thousands(count)
+    } else {
+        format_compact(count)
+    }
+}
+
+/// 把字节数格式化为便于阅读的大小
+///
+/// # 参数
+///
+/// * `bytes` - 字节数
+///
+/// # 返回值
+///
+/// 按 1024 进制换算并保留一位小数（去掉末尾的 `.0`），如 `512 B`、`2 KiB`、`1.4 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        // 按四舍五入后的值判断，避免出现 `1024.0 KiB`
+        if (value * 10.0).round() / 10.0 < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    let text = format!("{:.1}", value);
+    format!("{} {}", text.strip_suffix(".0").unwrap_or(&text), unit)
+}
+
+/// 把耗时格式化为便于阅读的形式
+///
+/// # 参数
+///
+/// * `duration` - 耗时
+///
+/// # 返回值
+///
+/// 不到 1 秒时为整数毫秒（如 `12 ms`），否则为保留两位小数的秒数（如 `1.50 s`）
+pub fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{} ms", duration.as_millis())
+    } else {
+        format!("{:.2} s", duration.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试各种单位的解析
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("64K"), Ok(64 * 1024));
+        assert_eq!(parse_size("100MB"), Ok(100 * 1024 * 1024));
+        assert_eq!(parse_size("2g"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1.5KiB"), Ok(1536));
+        assert_eq!(parse_size("1 T"), Ok(1 << 40));
+    }
+
+    /// 测试无效输入
+    #[test]
+    fn test_parse_size_invalid() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("abc").is_err());
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("99999999999T").is_err());
+    }
+
+    /// 测试数量的简写
+    #[test]
+    fn test_format_compact() {
+        assert_eq!(format_compact(0), "0");
+        assert_eq!(format_compact(999), "999");
+        assert_eq!(format_compact(1000), "1k");
+        assert_eq!(format_compact(12_345), "12.3k");
+        assert_eq!(format_compact(45_000), "45k");
+        assert_eq!(format_compact(999_960), "1M");
+        assert_eq!(format_compact(1_260_000), "1.3M");
+        assert_eq!(format_compact(7_000_000_000), "7G");
+    }
+
+    /// 测试千位分隔符
+    #[test]
+    fn test_format_thousands() {
+        assert_eq!(format_thousands(0), "0");
+        assert_eq!(format_thousands(999), "999");
+        assert_eq!(format_thousands(1000), "1,000");
+        assert_eq!(format_thousands(123_456), "123,456");
+        assert_eq!(format_thousands(1_234_567), "1,234,567");
+    }
+
+    /// 测试便于阅读的数量
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(42), "42");
+        assert_eq!(format_count(9_999), "9,999");
+        assert_eq!(format_count(12_345), "12.3k");
+        assert_eq!(format_count(1_234_567), "1.2M");
+    }
+
+    /// 测试便于阅读的大小
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(2048), "2 KiB");
+        assert_eq!(format_size(1_468_006), "1.4 MiB");
+        assert_eq!(format_size(1024 * 1024 - 1), "1 MiB");
+        assert_eq!(format_size(3 << 30), "3 GiB");
+    }
+
+    /// 测试耗时的格式化
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::ZERO), "0 ms");
+        assert_eq!(format_duration(Duration::from_micros(12_900)), "12 ms");
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1.50 s");
+        assert_eq!(format_duration(Duration::from_secs(75)), "75.00 s");
+    }
+}
@@ -0,0 +1,200 @@
+//! 输入路径展开
+//!
+//! 将命令行给出的文件和目录展开为待统计的文件列表。
+//! 目录内容按文件名排序，保证多次运行得到相同的文件顺序。
+//! 本工具写入的工作区摘要文件（见 [`crate::workspace`]）不参与统计。
+//!
+//! 符号链接的处理由 [`SymlinkPolicy`] 决定。命令行直接给出的符号链接总是解析到其目标；
+//! 遍历目录时默认跟随符号链接（`--follow-symlinks`），`--no-follow` 则跳过目录中的符号链接。
+//! 跟随时会检测循环：指向正在遍历的上层目录的链接不再进入，避免无限递归。
+
+use crate::error::{LineCounterError, Result};
+use crate::workspace::SUMMARY_FILE_NAME;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 遍历目录时对符号链接的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// 跟随符号链接，统计链接指向的文件和目录（默认）
+    #[default]
+    Follow,
+    /// 跳过目录中的符号链接
+    NoFollow,
+}
+
+/// 将输入路径展开为文件列表
+///
+/// # 参数
+///
+/// * `paths` - 命令行给出的路径
+/// * `recursive` - 是否递归展开目录
+/// * `symlinks` - 遍历目录时对符号链接的处理方式
+///
+/// # 返回值
+///
+/// * `Ok(Vec<PathBuf>)` - 按输入顺序排列的文件列表，目录内部按文件名排序
+/// * `Err(LineCounterError)` - 路径是目录但未启用递归，或目录读取失败
+///
+/// 不存在的路径会原样保留，交由后续的文件验证步骤报告错误。
+pub fn collect_files(
+    paths: &[PathBuf],
+    recursive: bool,
+    symlinks: SymlinkPolicy,
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            if !recursive {
+                return Err(LineCounterError::IsDirectory {
+                    path: path.display().to_string(),
+                });
+            }
+            walk_dir(path, symlinks, &mut Vec::new(), &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    Ok(files)
+}
+
+/// 递归遍历目录，收集其中的所有文件
+///
+/// `ancestors` 是正在遍历的目录（含 `dir` 的上层）的真实路径，用于检测符号链接造成的循环。
+fn walk_dir(
+    dir: &Path,
+    symlinks: SymlinkPolicy,
+    ancestors: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let real_dir = dir.canonicalize()?;
+    if ancestors.contains(&real_dir) {
+        return Ok(());
+    }
+    ancestors.push(real_dir);
+
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.and_then(|e| Ok((e.path(), e.file_type()?.is_symlink()))))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for (entry, is_symlink) in entries {
+        if is_symlink && symlinks == SymlinkPolicy::NoFollow {
+            continue;
+        } else if entry.is_dir() {
+            walk_dir(&entry, symlinks, ancestors, files)?;
+        } else if entry.file_name() == Some(SUMMARY_FILE_NAME.as_ref()) {
+            continue;
+        } else {
+            files.push(entry);
+        }
+    }
+
+    ancestors.pop();
+    Ok(())
+}
+
+/// 符号链接最终指向的文件
+///
+/// # 参数
+///
+/// * `path` - 输入路径
+///
+/// # 返回值
+///
+/// * `Some(PathBuf)` - `path` 是符号链接时，解析所有链接后的真实路径
+/// * `None` - `path` 不是符号链接，或链接指向的文件不存在
+pub fn symlink_target(path: &Path) -> Option<PathBuf> {
+    if !fs::symlink_metadata(path).ok()?.is_symlink() {
+        return None;
+    }
+    path.canonicalize().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 测试递归展开目录时文件顺序稳定，并跳过工作区摘要文件
+    #[test]
+    fn test_collect_files_sorted() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("sub").join("c.txt"), "c").unwrap();
+        fs::write(root.join(SUMMARY_FILE_NAME), "{}").unwrap();
+
+        let files = collect_files(&[root.to_path_buf()], true, SymlinkPolicy::Follow).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                root.join("a.txt"),
+                root.join("b.txt"),
+                root.join("sub").join("c.txt"),
+            ]
+        );
+    }
+
+    /// 测试未启用递归时拒绝目录
+    #[test]
+    fn test_collect_files_rejects_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let err = collect_files(
+            &[temp_dir.path().to_path_buf()],
+            false,
+            SymlinkPolicy::Follow,
+        )
+        .unwrap_err();
+        assert!(matches!(err, LineCounterError::IsDirectory { .. }));
+    }
+
+    /// 测试符号链接的跟随、跳过和循环检测
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_files_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("dir/sub")).unwrap();
+        fs::write(root.join("dir/a.txt"), "a").unwrap();
+        fs::write(root.join("dir/sub/b.txt"), "b").unwrap();
+        symlink(root.join("dir/a.txt"), root.join("dir/link.txt")).unwrap();
+        // 指向上层目录的链接构成循环
+        symlink(root.join("dir"), root.join("dir/sub/loop")).unwrap();
+
+        let dir = root.join("dir");
+        let files = collect_files(std::slice::from_ref(&dir), true, SymlinkPolicy::Follow).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                dir.join("a.txt"),
+                dir.join("link.txt"),
+                dir.join("sub").join("b.txt"),
+            ]
+        );
+
+        let files =
+            collect_files(std::slice::from_ref(&dir), true, SymlinkPolicy::NoFollow).unwrap();
+        assert_eq!(
+            files,
+            vec![dir.join("a.txt"), dir.join("sub").join("b.txt")]
+        );
+
+        // 命令行直接给出的符号链接总是保留
+        let link = dir.join("link.txt");
+        let files =
+            collect_files(std::slice::from_ref(&link), false, SymlinkPolicy::NoFollow).unwrap();
+        assert_eq!(files, vec![link.clone()]);
+        assert_eq!(
+            symlink_target(&link),
+            Some(dir.join("a.txt").canonicalize().unwrap())
+        );
+        assert_eq!(symlink_target(&dir.join("a.txt")), None);
+    }
+}
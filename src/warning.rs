@@ -0,0 +1,100 @@
+//! 文本规范警告
+//!
+//! `--warnings` 检查每个文件是否混用换行符、最后一行是否缺少换行符、是否混用制表符和空格缩进，
+//! 可以把本工具当作 pre-commit 钩子中的轻量文本规范检查器使用。
+//! 警告基于换行符统计（[`LineEndings`](crate::stats::LineEndings)）和缩进统计
+//! （[`Indentation`](crate::stats::Indentation)），启用 `--warnings` 时会自动统计这两项。
+
+use crate::stats::LineStats;
+use serde::Serialize;
+use std::fmt;
+
+/// 单个文件的警告
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Warning {
+    /// 混用了多种换行符
+    MixedLineEndings,
+    /// 最后一行没有换行符结尾
+    MissingFinalNewline,
+    /// 同时存在制表符缩进和空格缩进的行
+    MixedIndentation,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Warning::MixedLineEndings => "混用了多种换行符",
+            Warning::MissingFinalNewline => "最后一行缺少换行符",
+            Warning::MixedIndentation => "混用了制表符缩进和空格缩进",
+        })
+    }
+}
+
+/// 根据统计结果检查警告
+///
+/// # 参数
+///
+/// * `stats` - 单个文件的统计结果；只检查已统计的项目（换行符、缩进）
+///
+/// # 返回值
+///
+/// * `Vec<Warning>` - 该文件的警告，按固定顺序排列
+pub fn check(stats: &LineStats) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    if let Some(endings) = stats.line_endings {
+        if endings.is_mixed() {
+            warnings.push(Warning::MixedLineEndings);
+        }
+        if endings.missing_final_newline > 0 {
+            warnings.push(Warning::MissingFinalNewline);
+        }
+    }
+    if stats
+        .indentation
+        .is_some_and(|indentation| indentation.is_mixed())
+    {
+        warnings.push(Warning::MixedIndentation);
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{Indentation, LineEndings};
+
+    /// 测试警告的检查
+    #[test]
+    fn test_check() {
+        let stats = |lf, crlf, missing_final_newline| LineStats {
+            line_endings: Some(LineEndings {
+                lf,
+                crlf,
+                missing_final_newline,
+                ..LineEndings::default()
+            }),
+            ..LineStats::default()
+        };
+
+        assert!(check(&LineStats::default()).is_empty());
+        assert!(check(&stats(3, 0, 0)).is_empty());
+        assert_eq!(check(&stats(3, 1, 0)), vec![Warning::MixedLineEndings]);
+        assert_eq!(check(&stats(3, 0, 1)), vec![Warning::MissingFinalNewline]);
+        assert_eq!(
+            check(&stats(1, 1, 1)),
+            vec![Warning::MixedLineEndings, Warning::MissingFinalNewline]
+        );
+
+        let indentation = |tabs, spaces| LineStats {
+            indentation: Some(Indentation {
+                tabs,
+                spaces,
+                ..Indentation::default()
+            }),
+            ..LineStats::default()
+        };
+        assert!(check(&indentation(5, 0)).is_empty());
+        assert_eq!(check(&indentation(5, 1)), vec![Warning::MixedIndentation]);
+    }
+}
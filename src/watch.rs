@@ -0,0 +1,510 @@
+//! 监视模式
+//!
+//! `line-counter watch <路径>...` 先统计一次，之后每当文件或目录发生变化就重新统计。
+//! 使用 `notify` 订阅文件系统事件，并在 `--debounce` 时间内合并连续的事件，
+//! 避免编辑器保存文件时触发多次统计。
+//!
+//! 加上 `--only-changes` 时，重新统计后只输出统计结果与上一次不同的文件（包括新出现的文件），
+//! 没有变化时不输出任何内容，使长时间运行的日志保持安静。
+//!
+//! 每个文件的统计结果缓存在 [`ReportCache`] 中，文件系统事件涉及的路径会立即使对应的缓存失效，
+//! 重新统计时只读取发生变化的文件，其余文件直接使用缓存，保证结果不会过期。
+//! 事件队列溢出等无法确定变化范围的情况下清空整个缓存。
+//! 到达截止时间、统计失败等不完整的结果不缓存（见 [`FileReport::is_cacheable`]），每次都重新统计。
+
+use crate::app::{ReadOptions, count_and_report_with, count_files, error_chain};
+use crate::cli::{self, WatchArgs};
+use crate::counter::CountOptions;
+use crate::error::LineCounterError;
+use crate::input::is_stdin;
+use crate::output::Style;
+use crate::report::FileReport;
+use crate::stats::LineStats;
+use anyhow::{Context, Result, bail};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// 运行监视模式
+///
+/// # 参数
+///
+/// * `args` - `watch` 子命令的参数
+///
+/// # 返回值
+///
+/// 正常情况下不会返回；只有在无法建立监视或事件通道关闭时返回错误。
+/// 单次统计失败（例如文件在保存过程中暂时不存在）只输出错误并继续监视。
+pub fn run(args: &WatchArgs) -> Result<()> {
+    if args.count.paths.is_empty() {
+        let program_name = std::env::args().next().unwrap_or_default();
+        cli::print_usage_help(&program_name, &args.count.style());
+        return Err(LineCounterError::MissingArgument.into());
+    }
+    if args.count.follow {
+        bail!("watch 子命令不支持 --follow");
+    }
+
+    let targets = args
+        .count
+        .paths
+        .iter()
+        .map(|path| {
+            std::path::absolute(path).with_context(|| format!("无法解析路径 '{}'", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).context("无法创建文件系统监视器")?;
+
+    for target in &targets {
+        // 监视单个文件时改为监视其所在目录：编辑器常通过“写入临时文件再重命名”保存，
+        // 直接监视文件会在第一次保存后失效。
+        let (watch_path, mode) = if target.is_dir() {
+            let mode = if args.count.recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            (target.as_path(), mode)
+        } else {
+            let parent = target.parent().unwrap_or(Path::new("/"));
+            (parent, RecursiveMode::NonRecursive)
+        };
+
+        watcher
+            .watch(watch_path, mode)
+            .with_context(|| format!("无法监视 '{}'", watch_path.display()))?;
+    }
+
+    let debounce = Duration::from_millis(args.debounce);
+    let mut state = WatchState {
+        cache: ReportCache::default(),
+        changes: args.only_changes.then(ChangeTracker::default),
+    };
+    let mut first = true;
+    let style = args.count.style();
+
+    loop {
+        if let Err(e) = count_and_report_with(&args.count, Some(&mut state)) {
+            error!(
+                "{}{}: {}",
+                style.icon("❌ "),
+                style.tr("错误"),
+                error_chain(&e, style.lang).join(": ")
+            );
+        }
+        if first || state.changes.is_none() {
+            info!(
+                "{}{}",
+                style.icon("👀 "),
+                style.tr("正在监视变化，按 Ctrl+C 退出...")
+            );
+        }
+        first = false;
+
+        match wait_for_change(&receiver, &targets, debounce, &style)? {
+            Some(paths) => state.cache.invalidate(&paths),
+            None => state.cache.clear(),
+        }
+        if state.changes.is_none() {
+            println!();
+            info!(
+                "{}{}",
+                style.icon("🔄 "),
+                style.tr("检测到变化，重新统计...")
+            );
+        }
+    }
+}
+
+/// 监视模式在多次统计之间保留的状态
+#[derive(Debug, Default)]
+pub struct WatchState {
+    /// 每个文件的统计结果缓存
+    pub cache: ReportCache,
+    /// 变化跟踪器（仅在 `--only-changes` 时）
+    pub changes: Option<ChangeTracker>,
+}
+
+/// 按文件缓存统计结果，由文件系统事件使其失效
+///
+/// 缓存以绝对路径为键；统计选项在监视期间不变，因此缓存的结果只会因文件内容变化而过期。
+/// 标准输入不缓存。
+#[derive(Debug, Default)]
+pub struct ReportCache {
+    entries: HashMap<PathBuf, FileReport>,
+}
+
+impl ReportCache {
+    /// 使事件涉及的路径（及其下的所有文件）的缓存失效
+    ///
+    /// # 参数
+    ///
+    /// * `paths` - 发生变化的绝对路径
+    pub fn invalidate(&mut self, paths: &[PathBuf]) {
+        self.entries
+            .retain(|cached, _| !paths.iter().any(|path| cached.starts_with(path)));
+    }
+
+    /// 清空缓存
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 缓存的文件数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 统计文件，有缓存的文件直接使用缓存的结果
+    ///
+    /// # 参数
+    ///
+    /// * `files` - 待统计的文件列表
+    /// * `jobs` - 线程数
+    /// * `options` - 统计选项
+    /// * `read_options` - 文件读取选项
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(Vec<FileReport>)` - 按输入顺序排列的统计报告
+    /// * `Err(anyhow::Error)` - 任意需要重新统计的文件统计失败
+    pub fn count(
+        &mut self,
+        files: &[PathBuf],
+        jobs: usize,
+        options: &CountOptions,
+        read_options: &ReadOptions,
+    ) -> Result<Vec<FileReport>> {
+        let keys: Vec<Option<PathBuf>> = files
+            .iter()
+            .map(|path| {
+                if is_stdin(path) {
+                    None
+                } else {
+                    std::path::absolute(path).ok()
+                }
+            })
+            .collect();
+
+        let stale: Vec<PathBuf> = files
+            .iter()
+            .zip(&keys)
+            .filter(|(_, key)| {
+                key.as_ref()
+                    .is_none_or(|key| !self.entries.contains_key(key))
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        let mut fresh = count_files(&stale, jobs, options, read_options)?.into_iter();
+
+        let mut reports = Vec::with_capacity(files.len());
+        for key in keys {
+            let cached = key.as_ref().and_then(|key| self.entries.get(key));
+            let report = match cached {
+                Some(report) => report.clone(),
+                None => {
+                    let report = fresh.next().expect("每个未缓存的文件都有一份报告");
+                    if let Some(key) = key
+                        && report.is_cacheable()
+                    {
+                        self.entries.insert(key, report.clone());
+                    }
+                    report
+                }
+            };
+            reports.push(report);
+        }
+        Ok(reports)
+    }
+}
+
+/// 跟踪每个文件上一次的统计结果，找出发生变化的文件
+#[derive(Debug, Default)]
+pub struct ChangeTracker {
+    /// 上一次统计中每个文件的大小和统计结果
+    previous: HashMap<PathBuf, (u64, LineStats)>,
+    /// 是否已经记录过一次统计
+    seeded: bool,
+}
+
+impl ChangeTracker {
+    /// 是否已经记录过一次统计结果
+    pub fn has_previous(&self) -> bool {
+        self.seeded
+    }
+
+    /// 记录本次统计结果，并返回与上一次相比发生变化的报告
+    ///
+    /// 被跳过的文件（二进制文件、不存在的路径）不参与比较；
+    /// 本次没有出现的文件会被遗忘，之后重新出现时视为新文件。
+    ///
+    /// # 参数
+    ///
+    /// * `reports` - 本次统计的全部报告
+    ///
+    /// # 返回值
+    ///
+    /// 大小或统计结果与上一次不同的报告（包括新出现的文件），保持输入顺序
+    pub fn update(&mut self, reports: &[FileReport]) -> Vec<FileReport> {
+        let mut current = HashMap::with_capacity(reports.len());
+        let mut changed = Vec::new();
+        for report in reports.iter().filter(|report| !report.is_skipped()) {
+            let state = (report.size, report.stats.clone());
+            if self.previous.get(&report.path) != Some(&state) {
+                changed.push(report.clone());
+            }
+            current.insert(report.path.clone(), state);
+        }
+
+        self.previous = current;
+        self.seeded = true;
+        changed
+    }
+}
+
+/// 等待与监视目标相关的变化
+///
+/// 收到第一个相关事件后，继续收集 `debounce` 时间内到达的事件，再返回。
+///
+/// # 返回值
+///
+/// * `Ok(Some(paths))` - 发生变化的路径
+/// * `Ok(None)` - 无法确定变化范围（监视错误或需要重新扫描），应使全部缓存失效
+/// * `Err(anyhow::Error)` - 文件系统监视器已停止
+fn wait_for_change(
+    receiver: &Receiver<notify::Result<Event>>,
+    targets: &[PathBuf],
+    debounce: Duration,
+    style: &Style,
+) -> Result<Option<Vec<PathBuf>>> {
+    let mut paths = Vec::new();
+    let mut rescan = false;
+    let mut collect = |event: notify::Result<Event>, paths: &mut Vec<PathBuf>| match event {
+        Ok(event) if event.need_rescan() => {
+            rescan = true;
+            true
+        }
+        Ok(event) if is_relevant(&event, targets) => {
+            paths.extend(event.paths);
+            true
+        }
+        Ok(_) => false,
+        Err(e) => {
+            warn!("{}{}: {}", style.icon("⚠️  "), style.tr("监视错误"), e);
+            rescan = true;
+            true
+        }
+    };
+
+    loop {
+        let event = receiver.recv().context("文件系统监视器已停止")?;
+        if collect(event, &mut paths) {
+            break;
+        }
+    }
+
+    loop {
+        match receiver.recv_timeout(debounce) {
+            Ok(event) => {
+                collect(event, &mut paths);
+            }
+            Err(RecvTimeoutError::Timeout) => break,
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(anyhow::anyhow!("文件系统监视器已停止"));
+            }
+        }
+    }
+
+    Ok((!rescan).then_some(paths))
+}
+
+/// 判断事件是否影响统计结果
+///
+/// 只关心内容变化、创建和删除；访问事件和元数据变化（如访问时间）会被忽略，
+/// 否则统计过程本身读取文件就会再次触发统计。
+fn is_relevant(event: &Event, targets: &[PathBuf]) -> bool {
+    let kind_matters = match event.kind {
+        EventKind::Create(_) | EventKind::Remove(_) => true,
+        EventKind::Modify(ModifyKind::Metadata(_)) => false,
+        EventKind::Modify(_) => true,
+        _ => false,
+    };
+
+    kind_matters
+        && event
+            .paths
+            .iter()
+            .any(|path| targets.iter().any(|target| path.starts_with(target)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, DataChange, MetadataKind};
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    /// 测试只返回发生变化的文件
+    #[test]
+    fn test_change_tracker() {
+        let report = |path: &str, lines| FileReport {
+            path: PathBuf::from(path),
+            size: lines as u64 * 2,
+            stats: LineStats::new(lines, lines, 0),
+            binary: false,
+            missing: false,
+            limit_exceeded: None,
+            timed_out: false,
+            encoding: Some("UTF-8"),
+            bom: false,
+            compression: None,
+            symlink_target: None,
+            language: None,
+            warnings: Vec::new(),
+            tags: Vec::new(),
+            reading_minutes: None,
+            unstable: false,
+            error: None,
+        };
+        let mut tracker = ChangeTracker::default();
+        assert!(!tracker.has_previous());
+
+        // 第一次统计时所有文件都是新文件
+        let changed = tracker.update(&[report("a.rs", 1), report("b.rs", 2)]);
+        assert_eq!(changed.len(), 2);
+        assert!(tracker.has_previous());
+
+        assert!(
+            tracker
+                .update(&[report("a.rs", 1), report("b.rs", 2)])
+                .is_empty()
+        );
+
+        let changed = tracker.update(&[report("a.rs", 1), report("b.rs", 3)]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].path, PathBuf::from("b.rs"));
+
+        // 删除后重新出现的文件视为新文件
+        assert!(tracker.update(&[report("b.rs", 3)]).is_empty());
+        let changed = tracker.update(&[report("a.rs", 1), report("b.rs", 3)]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].path, PathBuf::from("a.rs"));
+
+        // 被跳过的文件不输出
+        assert!(
+            tracker
+                .update(&[FileReport::missing(PathBuf::from("c.rs"))])
+                .is_empty()
+        );
+    }
+
+    /// 测试缓存的复用和失效
+    #[test]
+    fn test_report_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let first = temp_dir.path().join("a.txt");
+        let second = temp_dir.path().join("b.txt");
+        std::fs::write(&first, "1\n2\n").unwrap();
+        std::fs::write(&second, "1\n").unwrap();
+        let files = vec![first.clone(), second.clone()];
+        let count = |cache: &mut ReportCache| {
+            cache
+                .count(&files, 1, &CountOptions::default(), &ReadOptions::default())
+                .unwrap()
+                .iter()
+                .map(|report| report.stats.total_lines)
+                .collect::<Vec<_>>()
+        };
+
+        let mut cache = ReportCache::default();
+        assert_eq!(count(&mut cache), vec![2, 1]);
+        assert_eq!(cache.len(), 2);
+
+        // 没有失效时使用缓存的结果
+        std::fs::write(&first, "1\n2\n3\n").unwrap();
+        std::fs::write(&second, "1\n2\n").unwrap();
+        assert_eq!(count(&mut cache), vec![2, 1]);
+
+        // 只有失效的文件重新统计
+        cache.invalidate(std::slice::from_ref(&first));
+        assert_eq!(count(&mut cache), vec![3, 1]);
+
+        // 目录的事件使其下的所有文件失效
+        cache.invalidate(&[temp_dir.path().to_path_buf()]);
+        assert!(cache.is_empty());
+        assert_eq!(count(&mut cache), vec![3, 2]);
+
+        // 统计失败的文件不缓存，之后重新统计
+        let missing = temp_dir.path().join("c.txt");
+        let files = vec![first.clone(), missing.clone()];
+        let read_options = ReadOptions {
+            keep_going: true,
+            ..ReadOptions::default()
+        };
+        let reports = cache
+            .count(&files, 1, &CountOptions::default(), &read_options)
+            .unwrap();
+        assert!(reports[1].error.is_some());
+        assert!(!cache.entries.contains_key(&missing));
+        std::fs::write(&missing, "1\n2\n3\n4\n").unwrap();
+        let reports = cache
+            .count(&files, 1, &CountOptions::default(), &read_options)
+            .unwrap();
+        assert!(reports[1].error.is_none());
+        assert_eq!(reports[1].stats.total_lines, 4);
+    }
+
+    /// 测试事件过滤
+    #[test]
+    fn test_is_relevant() {
+        let targets = vec![
+            PathBuf::from("/project/src"),
+            PathBuf::from("/project/a.txt"),
+        ];
+
+        assert!(is_relevant(
+            &event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                "/project/a.txt"
+            ),
+            &targets
+        ));
+        assert!(is_relevant(
+            &event(EventKind::Create(CreateKind::File), "/project/src/new.rs"),
+            &targets
+        ));
+        // 同一目录中的其他文件
+        assert!(!is_relevant(
+            &event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                "/project/b.txt"
+            ),
+            &targets
+        ));
+        // 访问和元数据事件
+        assert!(!is_relevant(
+            &event(EventKind::Access(AccessKind::Any), "/project/a.txt"),
+            &targets
+        ));
+        assert!(!is_relevant(
+            &event(
+                EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)),
+                "/project/a.txt"
+            ),
+            &targets
+        ));
+    }
+}
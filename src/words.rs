@@ -0,0 +1,235 @@
+//! 单词频率统计
+//!
+//! `--word-freq <N>` 按 Unicode 单词边界（UAX #29）切分每一行，统计出现次数最多的 N 个单词。
+//! 单词按小写比较，不含字母的片段（如数字）不计入；`--stop-words <FILE>` 指定的停用词不计入。
+//!
+//! 为了让内存占用有上限，跟踪的不同单词数达到 [`MAX_TRACKED_WORDS`] 时，
+//! 依次丢弃出现次数最少的单词，直到只剩一半；之后重新出现的单词从零开始计数，
+//! 结果标记为近似值（出现次数为下限）。出现频率高的单词通常不会被丢弃。
+
+use crate::stats::{WordCount, WordFrequency};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 最多跟踪的不同单词数
+///
+/// 每个单词约占 50 字节（含单词本身），上限对应的内存约为 13MB。
+pub const MAX_TRACKED_WORDS: usize = 1 << 18;
+
+/// 停用词表
+///
+/// 停用词按小写保存，比较时不区分大小写。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StopWords(Arc<HashSet<String>>);
+
+impl StopWords {
+    /// 解析停用词表
+    ///
+    /// 每行可以有多个以空白分隔的单词，`#` 之后的内容为注释。
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 停用词文件的内容
+    pub fn parse(text: &str) -> Self {
+        let words = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .flat_map(str::split_whitespace)
+            .map(str::to_lowercase)
+            .collect();
+        Self(Arc::new(words))
+    }
+
+    /// 是否为停用词
+    ///
+    /// # 参数
+    ///
+    /// * `word` - 小写的单词
+    pub fn contains(&self, word: &str) -> bool {
+        self.0.contains(word)
+    }
+
+    /// 停用词数量
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// 停用词表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// 单词频率统计选项
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WordFreqOptions {
+    /// 列出的出现次数最多的单词数
+    pub top: usize,
+    /// 不计入统计的停用词
+    pub stop_words: StopWords,
+}
+
+/// 逐行统计单词的出现次数
+#[derive(Debug)]
+pub struct WordFreqTracker {
+    options: WordFreqOptions,
+    counts: HashMap<Box<str>, usize>,
+    limit: usize,
+    words: usize,
+    /// 已经丢弃的单词的最大出现次数
+    floor: usize,
+    approximate: bool,
+}
+
+impl WordFreqTracker {
+    /// 创建跟踪器
+    ///
+    /// # 参数
+    ///
+    /// * `options` - 单词频率统计选项
+    pub fn new(options: WordFreqOptions) -> Self {
+        Self::with_limit(options, MAX_TRACKED_WORDS)
+    }
+
+    /// 创建最多跟踪 `limit` 个不同单词的跟踪器
+    fn with_limit(options: WordFreqOptions, limit: usize) -> Self {
+        Self {
+            options,
+            counts: HashMap::new(),
+            limit,
+            words: 0,
+            floor: 0,
+            approximate: false,
+        }
+    }
+
+    /// 记录一行中的单词
+    ///
+    /// # 参数
+    ///
+    /// * `line` - 行内容（不含换行符）
+    pub fn push(&mut self, line: &str) {
+        for word in line.unicode_words() {
+            if !word.chars().any(char::is_alphabetic) {
+                continue;
+            }
+            let word = word.to_lowercase();
+            if self.options.stop_words.contains(&word) {
+                continue;
+            }
+            self.words += 1;
+            if let Some(count) = self.counts.get_mut(word.as_str()) {
+                *count += 1;
+                continue;
+            }
+            if self.counts.len() >= self.limit {
+                self.prune();
+            }
+            self.counts.insert(word.into_boxed_str(), 1);
+        }
+    }
+
+    /// 依次丢弃出现次数最少的单词，直到跟踪的单词数不超过上限的一半
+    fn prune(&mut self) {
+        self.approximate = true;
+        while self.counts.len() > self.limit / 2 {
+            self.floor += 1;
+            let floor = self.floor;
+            self.counts.retain(|_, count| *count > floor);
+        }
+    }
+
+    /// 结束统计，返回出现次数最多的单词
+    pub fn finish(self) -> WordFrequency {
+        let mut top: Vec<WordCount> = self
+            .counts
+            .into_iter()
+            .map(|(word, count)| WordCount {
+                word: word.into_string(),
+                count,
+            })
+            .collect();
+        top.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+        top.truncate(self.options.top);
+
+        WordFrequency {
+            words: self.words,
+            approximate: self.approximate,
+            top,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(lines: &[&str], options: WordFreqOptions) -> WordFrequency {
+        let mut tracker = WordFreqTracker::new(options);
+        for line in lines {
+            tracker.push(line);
+        }
+        tracker.finish()
+    }
+
+    fn counts(frequency: &WordFrequency) -> Vec<(&str, usize)> {
+        frequency
+            .top
+            .iter()
+            .map(|word| (word.word.as_str(), word.count))
+            .collect()
+    }
+
+    /// 测试按小写统计单词，跳过数字，按出现次数和字典序排列
+    #[test]
+    fn test_word_freq() {
+        let lines = ["The cat and the dog.", "THE END, 2026", "猫和狗"];
+        let frequency = track(
+            &lines,
+            WordFreqOptions {
+                top: 3,
+                ..WordFreqOptions::default()
+            },
+        );
+        assert_eq!(frequency.words, 10);
+        assert!(!frequency.approximate);
+        assert_eq!(counts(&frequency), vec![("the", 3), ("and", 1), ("cat", 1)]);
+    }
+
+    /// 测试停用词不计入统计
+    #[test]
+    fn test_word_freq_stop_words() {
+        let stop_words = StopWords::parse("# 常见虚词\nthe AND\n\na # 冠词\n");
+        assert_eq!(stop_words.len(), 3);
+        assert!(stop_words.contains("and"));
+
+        let frequency = track(
+            &["The cat and the dog", "a dog"],
+            WordFreqOptions { top: 5, stop_words },
+        );
+        assert_eq!(frequency.words, 3);
+        assert_eq!(counts(&frequency), vec![("dog", 2), ("cat", 1)]);
+    }
+
+    /// 测试超过跟踪上限时丢弃出现次数少的单词，保留高频单词
+    #[test]
+    fn test_word_freq_bounded() {
+        let mut tracker = WordFreqTracker::with_limit(
+            WordFreqOptions {
+                top: 1,
+                ..WordFreqOptions::default()
+            },
+            8,
+        );
+        for i in 0..100 {
+            tracker.push("common common");
+            tracker.push(&format!("rare{}", i));
+            assert!(tracker.counts.len() <= 8);
+        }
+        let frequency = tracker.finish();
+        assert_eq!(frequency.words, 300);
+        assert!(frequency.approximate);
+        assert_eq!(counts(&frequency), vec![("common", 200)]);
+    }
+}
@@ -0,0 +1,166 @@
+//! 工作区摘要文件
+//!
+//! `--emit-summary` 在工作区根目录写入紧凑的 [`SUMMARY_FILE_NAME`]，
+//! 编辑器插件启动时读取它即可在状态栏显示项目规模，而不必重新扫描。
+//!
+//! 工作区根目录是第一个输入路径向上最近的包含 `.git` 的目录；找不到时使用该路径所在的目录。
+//! 摘要先写入临时文件再重命名，读取方不会读到写了一半的内容。
+
+use crate::meta::RunMetadata;
+use crate::report::Summary;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 摘要文件名
+pub const SUMMARY_FILE_NAME: &str = ".lc-summary.json";
+
+/// 摘要格式版本，字段发生不兼容变化时递增
+pub const SUMMARY_VERSION: u32 = 1;
+
+/// 工作区摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceSummary {
+    /// 摘要格式版本
+    pub version: u32,
+    /// 生成摘要的工具版本
+    pub tool_version: String,
+    /// 生成时间（RFC 3339，UTC）
+    pub generated_at: String,
+    /// 影响统计结果的配置的哈希值，配置不同的摘要不能直接比较
+    pub config_hash: String,
+    /// 文件数
+    pub files: usize,
+    /// 文件总大小（字节）
+    pub bytes: u64,
+    /// 总行数
+    pub total_lines: usize,
+    /// 非空行数
+    pub non_empty_lines: usize,
+    /// 空行数
+    pub empty_lines: usize,
+}
+
+impl WorkspaceSummary {
+    /// 根据运行元数据和汇总结果构造摘要
+    ///
+    /// # 参数
+    ///
+    /// * `meta` - 运行元数据
+    /// * `summary` - 多文件汇总结果
+    pub fn new(meta: &RunMetadata, summary: &Summary) -> Self {
+        Self {
+            version: SUMMARY_VERSION,
+            tool_version: meta.version.to_string(),
+            generated_at: meta.finished_at.clone(),
+            config_hash: meta.config_hash.clone(),
+            files: summary.files,
+            bytes: summary.bytes,
+            total_lines: summary.stats.total_lines,
+            non_empty_lines: summary.stats.non_empty_lines,
+            empty_lines: summary.stats.empty_lines,
+        }
+    }
+}
+
+/// 查找工作区根目录
+///
+/// # 参数
+///
+/// * `path` - 输入路径（文件或目录）
+///
+/// # 返回值
+///
+/// 向上最近的包含 `.git` 的目录；找不到时为 `path` 本身（目录）或其所在目录（文件）
+pub fn find_root(path: &Path) -> PathBuf {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let start = if path.is_dir() {
+        path.as_path()
+    } else {
+        path.parent().unwrap_or(Path::new("."))
+    };
+
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(start)
+        .to_path_buf()
+}
+
+/// 将摘要写入工作区根目录
+///
+/// # 参数
+///
+/// * `root` - 工作区根目录
+/// * `summary` - 工作区摘要
+///
+/// # 返回值
+///
+/// * `Ok(PathBuf)` - 写入的摘要文件路径
+/// * `Err(io::Error)` - 写入或重命名失败
+pub fn write_summary(root: &Path, summary: &WorkspaceSummary) -> io::Result<PathBuf> {
+    let path = root.join(SUMMARY_FILE_NAME);
+    let temp_path = root.join(format!("{}.tmp", SUMMARY_FILE_NAME));
+
+    let mut json = serde_json::to_vec(summary)?;
+    json.push(b'\n');
+    fs::write(&temp_path, json)?;
+    fs::rename(&temp_path, &path)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::LineStats;
+    use std::time::SystemTime;
+
+    /// 测试向上查找包含 `.git` 的目录
+    #[test]
+    fn test_find_root() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let nested = root.join("src/deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("a.rs"), "").unwrap();
+
+        // 没有 .git 时使用输入所在的目录
+        assert_eq!(find_root(&nested.join("a.rs")), nested);
+        assert_eq!(find_root(&nested), nested);
+
+        fs::create_dir(root.join(".git")).unwrap();
+        assert_eq!(find_root(&nested.join("a.rs")), root);
+        assert_eq!(find_root(&root), root);
+    }
+
+    /// 测试摘要写入后可以读回
+    #[test]
+    fn test_write_summary() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let meta = RunMetadata::new(Vec::new(), &(), SystemTime::now(), SystemTime::now());
+        let summary = WorkspaceSummary::new(
+            &meta,
+            &Summary {
+                files: 2,
+                bytes: 30,
+                skipped: 0,
+                missing: 0,
+                warnings: 0,
+                stats: LineStats::new(5, 4, 1),
+                ..Summary::default()
+            },
+        );
+
+        let path = write_summary(temp_dir.path(), &summary).unwrap();
+        assert_eq!(path, temp_dir.path().join(SUMMARY_FILE_NAME));
+        assert!(!temp_dir.path().join(".lc-summary.json.tmp").exists());
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        let parsed: WorkspaceSummary = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed, summary);
+        assert_eq!(parsed.total_lines, 5);
+    }
+}
@@ -4,9 +4,9 @@
 //! 测试整个应用程序的功能而不是单个组件。
 
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{BufRead, Write};
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 use tempfile::TempDir;
 
 /// 创建临时测试文件的辅助函数
@@ -37,7 +37,7 @@ fn create_test_file(temp_dir: &TempDir, filename: &str, content: &str) -> PathBu
 /// * `std::process::Output` - 命令执行结果
 fn run_line_counter(args: &[&str]) -> std::process::Output {
     let mut cmd = Command::new("cargo");
-    cmd.args(&["run", "--"]);
+    cmd.args(["run", "--"]);
     cmd.args(args);
     let output = cmd.output().expect("Failed to execute command");
 
@@ -51,6 +51,36 @@ fn run_line_counter(args: &[&str]) -> std::process::Output {
     output
 }
 
+/// 通过标准输入传入内容运行 line-counter 的辅助函数
+///
+/// # 参数
+/// * `args` - 命令行参数
+/// * `input` - 写入标准输入的内容
+///
+/// # 返回值
+/// * `std::process::Output` - 命令执行结果
+fn run_line_counter_with_stdin(args: &[&str], input: &str) -> std::process::Output {
+    let mut child = Command::new("cargo")
+        .args(["run", "--"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    child
+        .stdin
+        .take()
+        .expect("Failed to open stdin")
+        .write_all(input.as_bytes())
+        .expect("Failed to write stdin");
+
+    child
+        .wait_with_output()
+        .expect("Failed to wait for command")
+}
+
 #[test]
 fn test_basic_file_counting() {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
@@ -409,7 +439,7 @@ fn test_utf8_with_bom() {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
 
     // 创建带有BOM的UTF-8文件
-    let content = "第一行\n第二行\n"; // 简化测试，不使用BOM
+    let content = "\u{FEFF}第一行\n第二行\n";
     let file_path = create_test_file(&temp_dir, "utf8_bom.txt", content);
 
     let output = run_line_counter(&[file_path.to_str().unwrap()]);
@@ -425,4 +455,3111 @@ fn test_utf8_with_bom() {
     let stdout = String::from_utf8(output.stdout).unwrap();
     assert!(stdout.contains("总行数: 2"));
     assert!(stdout.contains("非空行数: 2"));
+    assert!(stdout.contains("编码: UTF-8（带 BOM）"));
+
+    // 只有 BOM 的第一行是空行
+    let file_path = create_test_file(&temp_dir, "bom_blank.txt", "\u{FEFF}\n第二行\n");
+    for engine in ["buffered", "mmap", "parallel", "parallel-read"] {
+        let output = run_line_counter(&[
+            "--engine",
+            engine,
+            "--format",
+            "json",
+            file_path.to_str().unwrap(),
+        ]);
+        assert!(output.status.success(), "Command should succeed");
+        let report: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+        assert_eq!(report["files"][0]["bom"], true);
+        assert_eq!(report["files"][0]["stats"]["empty_lines"], 1, "{}", engine);
+        assert_eq!(
+            report["files"][0]["stats"]["non_empty_lines"], 1,
+            "{}",
+            engine
+        );
+    }
+
+    let output = run_line_counter_with_stdin(&["--format", "json", "-"], "\u{FEFF}\nx\n");
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(report["files"][0]["bom"], true);
+    assert_eq!(report["files"][0]["stats"]["empty_lines"], 1);
+}
+
+#[test]
+fn test_multiple_files() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let first = create_test_file(&temp_dir, "first.txt", "a\n\nb\n");
+    let second = create_test_file(&temp_dir, "second.txt", "c\nd\n");
+
+    let output = run_line_counter(&[first.to_str().unwrap(), second.to_str().unwrap()]);
+
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("文件数: 2"));
+    assert!(stdout.contains("总行数: 5"));
+    assert!(stdout.contains("非空行数: 4"));
+    assert!(stdout.contains("空行数: 1"));
+    assert!(stdout.contains("空行占比: 20.0%（按行数加权）"));
+    assert!(stdout.contains("文件平均空行占比: 16.7%"));
+
+    // 每个文件的结果按输入顺序输出
+    let first_pos = stdout.find("first.txt").unwrap();
+    let second_pos = stdout.find("second.txt").unwrap();
+    assert!(first_pos < second_pos);
+}
+
+#[test]
+fn test_sort_multiple_files() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let small = create_test_file(&temp_dir, "small.txt", "a\n");
+    let large = create_test_file(&temp_dir, "large.txt", "a\nb\nc\n\n");
+    let medium = create_test_file(&temp_dir, "medium.txt", "a\nb\n");
+    let paths = [
+        small.to_str().unwrap(),
+        large.to_str().unwrap(),
+        medium.to_str().unwrap(),
+    ];
+    let positions = |stdout: &str| {
+        let mut names = ["small.txt", "large.txt", "medium.txt"];
+        names.sort_by_key(|name| stdout.find(name).unwrap());
+        names
+    };
+
+    let mut args = vec!["--sort", "lines"];
+    args.extend(paths);
+    let output = run_line_counter(&args);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(positions(&stdout), ["large.txt", "medium.txt", "small.txt"]);
+
+    let mut args = vec!["--sort", "lines", "--reverse"];
+    args.extend(paths);
+    let stdout = String::from_utf8_lossy(&run_line_counter(&args).stdout).to_string();
+    assert_eq!(positions(&stdout), ["small.txt", "medium.txt", "large.txt"]);
+
+    let mut args = vec!["--sort", "path", "--format", "json"];
+    args.extend(paths);
+    let report: serde_json::Value = serde_json::from_slice(&run_line_counter(&args).stdout)
+        .expect("stdout should be valid JSON");
+    let files = report["files"].as_array().unwrap();
+    assert!(files[0]["path"].as_str().unwrap().ends_with("large.txt"));
+    assert!(files[2]["path"].as_str().unwrap().ends_with("small.txt"));
+}
+
+#[test]
+fn test_group_by_ext() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    create_test_file(&temp_dir, "a.rs", "a\nb\n\n");
+    create_test_file(&temp_dir, "b.RS", "a\n");
+    create_test_file(&temp_dir, "c.py", "a\nb\nc\nd\ne\n");
+    create_test_file(&temp_dir, "Makefile", "all:\n");
+    let root = temp_dir.path().to_str().unwrap();
+
+    let output = run_line_counter(&["-r", "--group-by", "ext", root]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("📂 按扩展名汇总:"));
+    assert!(stdout.contains(".py: 5 行, 1 个文件"));
+    assert!(stdout.contains(".rs: 4 行, 2 个文件（非空行数 3, 空行数 1"));
+    assert!(stdout.contains("（无扩展名）: 1 行, 1 个文件"));
+    assert!(stdout.find("   .py:").unwrap() < stdout.find("   .rs:").unwrap());
+
+    let output = run_line_counter(&["-r", "--group-by", "ext", "--format", "json", root]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(report["groups"][1]["key"], ".rs");
+    assert_eq!(report["groups"][1]["files"], 2);
+    assert_eq!(report["groups"][1]["stats"]["total_lines"], 4);
+}
+
+#[test]
+fn test_group_by_dir() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::create_dir_all(temp_dir.path().join("src/deep")).unwrap();
+    fs::create_dir(temp_dir.path().join("tests")).unwrap();
+    create_test_file(&temp_dir, "src/a.rs", "a\nb\n");
+    create_test_file(&temp_dir, "src/deep/b.rs", "a\nb\nc\n");
+    create_test_file(&temp_dir, "tests/t.rs", "a\n");
+    create_test_file(&temp_dir, "Cargo.toml", "[package]\n");
+    let root = temp_dir.path().to_str().unwrap();
+
+    let output = run_line_counter(&["-r", "--group-by", "dir", root]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("📂 按目录汇总:"));
+    assert!(stdout.contains("   src/: 5 行, 2 个文件"));
+    assert!(stdout.contains("   tests/: 1 行, 1 个文件"));
+    assert!(stdout.contains("   （顶层文件）: 1 行, 1 个文件"));
+
+    let output = run_line_counter(&["-r", "--group-by", "dir", "--depth", "2", root]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("   src/deep/: 3 行, 1 个文件"));
+    assert!(stdout.contains("   src/: 2 行, 1 个文件"));
+
+    let output = run_line_counter(&["--depth", "2", root]);
+    assert!(!output.status.success(), "--depth requires --group-by");
+}
+
+#[test]
+fn test_deadline() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let a = create_test_file(&temp_dir, "a.txt", "a\nb\n");
+    let b = create_test_file(&temp_dir, "b.txt", "c\n");
+    let paths = [a.to_str().unwrap(), b.to_str().unwrap()];
+
+    let mut args = vec!["--deadline", "1h"];
+    args.extend(paths);
+    let output = run_line_counter(&args);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("📊 总行数: 3"));
+    assert!(!stdout.contains("结果不完整"));
+
+    // 截止时间为 0 时不统计任何文件，但仍然正常输出并标记为不完整
+    let mut args = vec!["--deadline", "0s"];
+    args.extend(paths);
+    let output = run_line_counter(&args);
+    assert!(output.status.success(), "Deadline should stop gracefully");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("⏱️  到达截止时间，未统计"));
+    assert!(stdout.contains("⏱️  结果不完整: 到达截止时间时还有 2 个文件未统计"));
+
+    let mut args = vec!["--deadline", "0s", "--format", "json"];
+    args.extend(paths);
+    let report: serde_json::Value = serde_json::from_slice(&run_line_counter(&args).stdout)
+        .expect("stdout should be valid JSON");
+    assert_eq!(report["incomplete"], true);
+    assert_eq!(report["summary"]["timed_out"], 2);
+    assert_eq!(report["files"][0]["timed_out"], true);
+
+    let output = run_line_counter(&["--deadline", "soon", paths[0]]);
+    assert!(
+        !output.status.success(),
+        "Invalid duration should be rejected"
+    );
+}
+
+#[test]
+fn test_recursive_directory() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::create_dir(temp_dir.path().join("nested")).expect("Failed to create nested dir");
+    create_test_file(&temp_dir, "top.txt", "1\n2\n");
+    create_test_file(&temp_dir, "nested/inner.txt", "3\n\n4\n");
+
+    let output = run_line_counter(&["-r", "-j", "2", temp_dir.path().to_str().unwrap()]);
+
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("文件数: 2"));
+    assert!(stdout.contains("总行数: 5"));
+    assert!(stdout.contains("空行数: 1"));
+}
+
+#[test]
+fn test_mmap_mode() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let file_path = create_test_file(&temp_dir, "mmap.txt", "第一行\n第二行\n\n第四行");
+
+    let output = run_line_counter(&["--mmap", file_path.to_str().unwrap()]);
+
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("总行数: 4"));
+    assert!(stdout.contains("非空行数: 3"));
+    assert!(stdout.contains("空行数: 1"));
+}
+
+#[test]
+fn test_section_markers() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let content = "手写代码\n# BEGIN GENERATED\n生成1\n\n生成2\n# END GENERATED\n手写代码\n";
+    let file_path = create_test_file(&temp_dir, "sections.txt", content);
+
+    let output = run_line_counter(&[
+        "--section-start",
+        "^# BEGIN GENERATED",
+        "--section-end",
+        "^# END GENERATED",
+        file_path.to_str().unwrap(),
+    ]);
+
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("总行数: 3"));
+    assert!(stdout.contains("非空行数: 2"));
+    assert!(stdout.contains("空行数: 1"));
+}
+
+#[test]
+fn test_exclude_headers() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let content =
+        "#!/bin/bash\n# Copyright 2024 Example\n# SPDX-License-Identifier: MIT\n\necho hello\n";
+    let file_path = create_test_file(&temp_dir, "script.sh", content);
+
+    let output = run_line_counter(&[file_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("总行数: 5"));
+    assert!(stdout.contains("Shebang 行数: 1"));
+    assert!(stdout.contains("许可证头行数: 2"));
+
+    let output = run_line_counter(&["--exclude-headers", file_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("总行数: 2"));
+    assert!(stdout.contains("非空行数: 1"));
+}
+
+#[test]
+fn test_json_output_with_metadata() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let file_path = create_test_file(&temp_dir, "data.txt", "a\n\nb\n");
+
+    let output = run_line_counter(&["--format", "json", file_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+
+    let meta = &report["meta"];
+    assert_eq!(meta["tool"], "line-counter");
+    assert_eq!(meta["version"], env!("CARGO_PKG_VERSION"));
+    assert!(meta["command_line"].as_array().unwrap().len() >= 3);
+    assert!(meta["started_at"].as_str().unwrap().ends_with('Z'));
+    assert!(meta["finished_at"].as_str().unwrap().ends_with('Z'));
+    assert!(meta["host"].is_string());
+    assert!(
+        meta["config_hash"]
+            .as_str()
+            .unwrap()
+            .starts_with("fnv1a64:")
+    );
+
+    assert_eq!(report["files"][0]["stats"]["total_lines"], 3);
+    assert_eq!(report["summary"]["files"], 1);
+    assert_eq!(report["summary"]["stats"]["empty_lines"], 1);
+}
+
+#[test]
+fn test_stdin_with_assumed_name() {
+    let output = run_line_counter_with_stdin(
+        &["--stdin-name", "buffer.rs", "--format", "json", "-"],
+        "fn main() {\n\n}\n",
+    );
+    assert!(output.status.success(), "Command should succeed");
+
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(report["files"][0]["path"], "buffer.rs");
+    assert_eq!(report["files"][0]["size"], 15);
+    assert_eq!(report["files"][0]["stats"]["total_lines"], 3);
+    assert_eq!(report["files"][0]["stats"]["empty_lines"], 1);
+}
+
+#[test]
+fn test_diff_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let old = create_test_file(&temp_dir, "old.txt", "a\nb\n");
+    let new = create_test_file(&temp_dir, "new.txt", "a\n\nb\nc\n");
+
+    let output = run_line_counter(&["diff", old.to_str().unwrap(), new.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("总行数: 2 → 4 (+2)"));
+    assert!(stdout.contains("非空行数: 2 → 3 (+1)"));
+    assert!(stdout.contains("空行数: 0 → 1 (+1)"));
+}
+
+#[test]
+fn test_markdown_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "__init__.py", "a\n\nb\nc\n");
+    let b = create_test_file(&temp_dir, "b.rs", "x\n");
+
+    let output = run_line_counter(&[
+        "--format",
+        "markdown",
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "| 文件 | 总行数 | 非空行数 | 空行数 | 空行占比 |");
+    assert_eq!(lines[1], "| :--- | ---: | ---: | ---: | ---: |");
+    assert!(lines[2].ends_with("__init__.py` | 4 | 3 | 1 | 25.0% |"));
+    assert!(lines[3].ends_with("b.rs` | 1 | 1 | 0 | 0.0% |"));
+    assert_eq!(
+        lines[4],
+        "| **合计（2 个文件）** | **5** | **4** | **1** | **20.0%** |"
+    );
+    assert!(
+        !stdout.contains("📊"),
+        "Markdown output should not contain banners"
+    );
+
+    let output = run_line_counter(&[
+        "diff",
+        "--format",
+        "markdown",
+        b.to_str().unwrap(),
+        a.to_str().unwrap(),
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("| 总行数 | 1 | 4 | +3 |"));
+    assert!(stdout.contains("| 空行数 | 0 | 1 | +1 |"));
+}
+
+#[test]
+fn test_diff_reports() {
+    let temp_dir = TempDir::new().unwrap();
+    let old = create_test_file(&temp_dir, "old.txt", "a\nb\nc\n");
+    let new = create_test_file(&temp_dir, "new.txt", "a\n");
+
+    let mut reports = Vec::new();
+    for (name, file) in [("old.json", &old), ("new.json", &new)] {
+        let output = run_line_counter(&["--format", "json", file.to_str().unwrap()]);
+        assert!(output.status.success(), "Command should succeed");
+        reports.push(create_test_file(
+            &temp_dir,
+            name,
+            &String::from_utf8_lossy(&output.stdout),
+        ));
+    }
+
+    let output = run_line_counter(&[
+        "diff",
+        "--report",
+        "--format",
+        "json",
+        reports[0].to_str().unwrap(),
+        reports[1].to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+
+    let diff: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(diff["old"]["total_lines"], 3);
+    assert_eq!(diff["new"]["total_lines"], 1);
+    assert_eq!(diff["delta"]["total_lines"], -2);
+}
+
+#[test]
+fn test_bench_compare_wc() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "bench.txt", &"line\n".repeat(1000));
+
+    let output = run_line_counter(&[
+        "bench",
+        "--compare-wc",
+        "--iterations",
+        "1",
+        file_path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("buffered"));
+    assert!(stdout.contains("mmap"));
+    assert!(stdout.contains("wc -l"));
+}
+
+#[test]
+fn test_buffer_size_option() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = "行\n\n".repeat(500);
+    let file_path = create_test_file(&temp_dir, "buffer.txt", &content);
+
+    let output = run_line_counter(&["--buffer-size", "7", file_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("总行数: 1000"));
+    assert!(stdout.contains("空行数: 500"));
+
+    let output = run_line_counter(&["--buffer-size", "0", file_path.to_str().unwrap()]);
+    assert!(
+        !output.status.success(),
+        "Zero buffer size should be rejected"
+    );
+
+    for size in ["64K", "auto"] {
+        let output = run_line_counter(&["--buffer-size", size, file_path.to_str().unwrap()]);
+        assert!(output.status.success(), "{}", size);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 1000"));
+    }
+
+    // 标准输入同样使用指定的缓冲区大小
+    let output = run_line_counter_with_stdin(&["--buffer-size", "3", "-"], &content);
+    assert!(output.status.success(), "Command should succeed");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("空行数: 500"));
+}
+
+#[test]
+fn test_baseline_regression_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "grow.txt", &"line\n".repeat(100));
+
+    let output = run_line_counter(&["--format", "json", file_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let baseline = create_test_file(
+        &temp_dir,
+        "baseline.json",
+        &String::from_utf8_lossy(&output.stdout),
+    );
+
+    // 增长 4%，在 5% 容差内
+    create_test_file(&temp_dir, "grow.txt", &"line\n".repeat(104));
+    let output = run_line_counter(&[
+        "--baseline",
+        baseline.to_str().unwrap(),
+        "--tolerance",
+        "5",
+        file_path.to_str().unwrap(),
+    ]);
+    assert!(
+        output.status.success(),
+        "Growth within tolerance should pass"
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("✅ 总行数: 100 → 104"));
+
+    // 增长 10%，超过 5% 容差
+    create_test_file(&temp_dir, "grow.txt", &"line\n".repeat(110));
+    let output = run_line_counter(&[
+        "--baseline",
+        baseline.to_str().unwrap(),
+        "--tolerance",
+        "5",
+        file_path.to_str().unwrap(),
+    ]);
+    assert!(
+        !output.status.success(),
+        "Growth beyond tolerance should fail"
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("❌ 总行数: 100 → 110"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("行数超过基线容差"));
+}
+
+#[test]
+fn test_engines_agree() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = "#!/bin/sh\n// Copyright 2024\n\necho 1\n\n".repeat(200);
+    let file_path = create_test_file(&temp_dir, "engines.sh", &content);
+
+    let outputs: Vec<String> = ["auto", "buffered", "mmap", "parallel", "parallel-read"]
+        .iter()
+        .map(|engine| {
+            let output = run_line_counter(&["--engine", engine, file_path.to_str().unwrap()]);
+            assert!(output.status.success(), "Command should succeed");
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        })
+        .collect();
+
+    assert!(outputs[0].contains("总行数: 1000"));
+    assert!(outputs.iter().all(|output| output == &outputs[0]));
+}
+
+#[test]
+fn test_max_size_option() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "sized.txt", &"x\n".repeat(1024));
+
+    let output = run_line_counter(&["--max-size", "1K", file_path.to_str().unwrap()]);
+    assert!(!output.status.success(), "File over the limit should fail");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("文件过大"));
+
+    for limit in ["2K", "0"] {
+        let output = run_line_counter(&["--max-size", limit, file_path.to_str().unwrap()]);
+        assert!(output.status.success(), "Command should succeed");
+    }
+
+    let output = run_line_counter(&["--max-size", "10X", file_path.to_str().unwrap()]);
+    assert!(!output.status.success(), "Invalid size should be rejected");
+}
+
+#[test]
+fn test_stream_mode_ignores_size_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "stream.txt", &"x\n\n".repeat(1024));
+
+    let output = run_line_counter(&[
+        "--stream",
+        "--max-size",
+        "1K",
+        "--engine",
+        "mmap",
+        file_path.to_str().unwrap(),
+    ]);
+    assert!(
+        output.status.success(),
+        "Stream mode should not enforce the size limit"
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 2048"));
+
+    let output = run_line_counter_with_stdin(&["--stream", "-"], "a\n\nb\n");
+    assert!(output.status.success(), "Command should succeed");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 3"));
+}
+
+#[test]
+fn test_binary_file_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    let binary_path = temp_dir.path().join("image.bin");
+    fs::write(
+        &binary_path,
+        b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\n\xff\xfe",
+    )
+    .unwrap();
+    let text_path = create_test_file(&temp_dir, "notes.txt", "a\n\nb\n");
+
+    let output = run_line_counter(&[binary_path.to_str().unwrap()]);
+    assert!(
+        output.status.success(),
+        "Binary file should be skipped, not fail"
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("跳过二进制文件"));
+
+    let output = run_line_counter(&[
+        "--format",
+        "json",
+        binary_path.to_str().unwrap(),
+        text_path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(report["files"][0]["binary"], true);
+    assert_eq!(report["summary"]["files"], 1);
+    assert_eq!(report["summary"]["skipped"], 1);
+    assert_eq!(report["summary"]["stats"]["total_lines"], 3);
+
+    let output = run_line_counter(&["--count-binary", binary_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 4"));
+}
+
+#[test]
+fn test_line_lengths() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "widths.txt", "abc\n中文字符\n");
+
+    let output = run_line_counter(&["--line-lengths", file_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("最长行: 12 bytes, 4 字符, 8 列"));
+
+    let output = run_line_counter(&[
+        "--line-lengths",
+        "--format",
+        "json",
+        file_path.to_str().unwrap(),
+    ]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    let length = &report["summary"]["stats"]["max_line_length"];
+    assert_eq!(length["bytes"], 12);
+    assert_eq!(length["chars"], 4);
+    assert_eq!(length["cells"], 8);
+}
+
+#[test]
+fn test_non_utf8_encodings() {
+    let temp_dir = TempDir::new().unwrap();
+    let text = "// 计算两个数的和\nfn add() {}\n\n// 这是中文注释\n";
+
+    let gbk_path = temp_dir.path().join("gbk.rs");
+    fs::write(&gbk_path, encoding_rs::GBK.encode(text).0).unwrap();
+    let output = run_line_counter(&[gbk_path.to_str().unwrap()]);
+    assert!(output.status.success(), "GBK file should be transcoded");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("编码: GBK"));
+    assert!(stdout.contains("总行数: 4"));
+    assert!(stdout.contains("空行数: 1"));
+
+    let utf16_path = temp_dir.path().join("utf16.txt");
+    let mut utf16 = vec![0xFF, 0xFE];
+    utf16.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    fs::write(&utf16_path, utf16).unwrap();
+    let output = run_line_counter(&[utf16_path.to_str().unwrap()]);
+    assert!(
+        output.status.success(),
+        "UTF-16 file should not be treated as binary"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("编码: UTF-16LE"));
+    assert!(stdout.contains("总行数: 4"));
+
+    let output = run_line_counter(&[
+        "--encoding",
+        "latin1",
+        "--format",
+        "json",
+        gbk_path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(report["files"][0]["encoding"], "windows-1252");
+    assert_eq!(report["files"][0]["stats"]["total_lines"], 4);
+}
+
+#[test]
+fn test_language_breakdown() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(&temp_dir, "main.rs", "fn main() {\n}\n\n");
+    create_test_file(&temp_dir, "build.py", "print(1)\n");
+    create_test_file(&temp_dir, "LICENSE", "MIT\n");
+
+    let dir = temp_dir.path().to_str().unwrap();
+    let output = run_line_counter(&["-r", "--languages", dir]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("语言分布"));
+    assert!(stdout.contains("Rust"));
+    assert!(stdout.contains("Python"));
+
+    let output = run_line_counter(&["-r", "--languages", "--format", "json", dir]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    let languages = report["languages"].as_array().unwrap();
+    assert_eq!(languages.len(), 2);
+    assert_eq!(languages[0]["name"], "Rust");
+    assert_eq!(languages[0]["color"], "#dea584");
+    assert_eq!(languages[0]["bytes"], 15);
+    assert_eq!(languages[0]["lines"], 3);
+    assert_eq!(languages[1]["name"], "Python");
+    assert_eq!(languages[1]["lines_percent"], 25.0);
+
+    let output = run_line_counter(&["-r", "--format", "json", dir]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report.get("languages").is_none());
+}
+
+#[test]
+fn test_line_endings() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "endings.txt", "a\r\nb\r\nc\n");
+
+    let output = run_line_counter(&["--line-endings", file_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("总行数: 3"));
+    assert!(stdout.contains("换行符: LF 1, CRLF 2, CR 0（主要为 CRLF，混用）"));
+
+    let output = run_line_counter(&[
+        "--line-endings",
+        "--format",
+        "json",
+        file_path.to_str().unwrap(),
+    ]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    let endings = &report["summary"]["stats"]["line_endings"];
+    assert_eq!(endings["lf"], 1);
+    assert_eq!(endings["crlf"], 2);
+    assert_eq!(endings["dominant"], "CRLF");
+    assert_eq!(endings["mixed"], true);
+}
+
+#[test]
+fn test_duplicates() {
+    let temp_dir = TempDir::new().unwrap();
+    let log = create_test_file(
+        &temp_dir,
+        "app.log",
+        "GET /\nGET /\n\nPOST /login\nget /  \nGET /\n",
+    );
+
+    let output = run_line_counter(&[
+        "--duplicates",
+        "--top-duplicates",
+        "1",
+        log.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("重复行: 2 行（占 40.0%，不同内容 3 行）"));
+    assert!(stdout.contains("3 次: GET /"));
+
+    let output = run_line_counter(&[
+        "--duplicates",
+        "--normalize",
+        "trim,casefold",
+        "--format",
+        "json",
+        log.to_str().unwrap(),
+    ]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    let duplicates = &report["summary"]["stats"]["duplicates"];
+    assert_eq!(duplicates["lines"], 5);
+    assert_eq!(duplicates["duplicates"], 3);
+    assert_eq!(duplicates["ratio"], 60.0);
+    assert_eq!(duplicates["approximate"], false);
+
+    let output = run_line_counter(&["--normalize", "nfkc", "--duplicates", log.to_str().unwrap()]);
+    assert!(
+        !output.status.success(),
+        "Unknown normalization should fail"
+    );
+}
+
+#[test]
+fn test_tags() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join("old")).unwrap();
+    fs::create_dir(temp_dir.path().join("new")).unwrap();
+    create_test_file(&temp_dir, "old/legacy.rs", &"old\n".repeat(10));
+    create_test_file(&temp_dir, "new/modern.rs", &"new\n".repeat(100));
+
+    let root = temp_dir.path().to_str().unwrap();
+    let rule = format!("legacy={}/old/**", root);
+    let output = run_line_counter(&["-r", "--tag", &rule, root]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("legacy: 文件数 1, 总行数 10, 非空行数 10, 空行数 0"));
+
+    let output = run_line_counter(&["-r", "--tag", &rule, "--format", "json", root]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(report["summary"]["tags"][0]["name"], "legacy");
+    assert_eq!(report["summary"]["tags"][0]["stats"]["total_lines"], 10);
+    let baseline_dir = TempDir::new().unwrap();
+    let baseline = baseline_dir.path().join("baseline.json");
+    fs::write(&baseline, &output.stdout).unwrap();
+
+    // 整体增长不到 5%，但带标签的代码增长了 50%
+    create_test_file(&temp_dir, "old/legacy.rs", &"old\n".repeat(15));
+    let output = run_line_counter(&[
+        "-r",
+        "--tag",
+        &rule,
+        "--baseline",
+        baseline.to_str().unwrap(),
+        "--tolerance",
+        "5",
+        root,
+    ]);
+    assert!(!output.status.success(), "Tag growth should fail the check");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✅ 总行数: 110 → 115"));
+    assert!(stdout.contains("❌ [legacy] 总行数: 10 → 15"));
+
+    let output = run_line_counter(&["--tag", "legacy", root]);
+    assert!(!output.status.success(), "Malformed rule should fail");
+}
+
+#[test]
+fn test_top_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = create_test_file(&temp_dir, "first.txt", "short\nthis line is long\n");
+    let second = create_test_file(&temp_dir, "second.txt", "a\n\n\nthe longest line of all\n");
+
+    let output = run_line_counter(&["--top-lines", "2", first.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("最长的 2 行"));
+    assert!(stdout.contains("第 2 行: 17 bytes, 17 字符, 17 列"));
+    assert!(stdout.contains("第 1 行: 5 bytes"));
+
+    let output = run_line_counter(&[
+        "--top-lines",
+        "1",
+        first.to_str().unwrap(),
+        second.to_str().unwrap(),
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("最长的 1 行"));
+    assert!(stdout.contains("second.txt:4: 23 bytes"));
+    assert!(!stdout.contains("first.txt:2:"));
+
+    let output = run_line_counter(&[
+        "--top-lines",
+        "1",
+        "--format",
+        "json",
+        second.to_str().unwrap(),
+    ]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    let longest = &report["files"][0]["stats"]["longest_lines"][0];
+    assert_eq!(longest["line"], 4);
+    assert_eq!(longest["bytes"], 23);
+}
+
+#[test]
+fn test_indentation() {
+    let temp_dir = TempDir::new().unwrap();
+    let spaces = create_test_file(
+        &temp_dir,
+        "spaces.py",
+        "def f():\n  if x:\n    y()\n\n  return 1\n",
+    );
+    let mixed = create_test_file(&temp_dir, "mixed.py", "def f():\n    a()\n\tb()\n");
+
+    let output = run_line_counter(&["--indentation", spaces.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("缩进: 空格 3 行, 制表符 0 行（主要为空格，宽度 2）"));
+
+    let output = run_line_counter(&[
+        "--indentation",
+        spaces.to_str().unwrap(),
+        mixed.to_str().unwrap(),
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("mixed.py: 总行数 3, 非空行数 3, 空行数 0（混合缩进）"));
+    assert!(!stdout.contains("spaces.py: 总行数 5, 非空行数 4, 空行数 1（"));
+
+    let output = run_line_counter(&["--warnings", mixed.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("混用了制表符缩进和空格缩进"));
+
+    let output = run_line_counter(&["--indentation", "--format", "json", mixed.to_str().unwrap()]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    let indentation = &report["summary"]["stats"]["indentation"];
+    assert_eq!(indentation["spaces"], 1);
+    assert_eq!(indentation["tabs"], 1);
+    assert_eq!(indentation["width"], 4);
+    assert_eq!(indentation["mixed"], true);
+}
+
+#[test]
+fn test_emit_summary() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join(".git")).unwrap();
+    fs::create_dir(temp_dir.path().join("src")).unwrap();
+    fs::write(temp_dir.path().join("src/lib.rs"), "fn a() {}\n\n").unwrap();
+
+    let src = temp_dir.path().join("src");
+    let output = run_line_counter(&["-r", "--emit-summary", src.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("已写入工作区摘要"));
+
+    let summary_path = temp_dir.path().join(".lc-summary.json");
+    let summary: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&summary_path).unwrap()).unwrap();
+    assert_eq!(summary["version"], 1);
+    assert_eq!(summary["files"], 1);
+    assert_eq!(summary["total_lines"], 2);
+    assert_eq!(summary["empty_lines"], 1);
+
+    // 摘要文件本身不计入统计
+    let output = run_line_counter(&[
+        "-r",
+        "--emit-summary",
+        "--format",
+        "json",
+        temp_dir.path().join("src").to_str().unwrap(),
+        temp_dir.path().to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(report["summary"]["files"], 2);
+}
+
+#[test]
+fn test_hygiene_warnings() {
+    let temp_dir = TempDir::new().unwrap();
+    let clean = create_test_file(&temp_dir, "clean.txt", "a\nb\n");
+    let mixed = create_test_file(&temp_dir, "mixed.txt", "a\r\nb\nc");
+
+    let output = run_line_counter(&[
+        "--warnings",
+        clean.to_str().unwrap(),
+        mixed.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Warnings should not fail the run");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("mixed.txt: 混用了多种换行符"));
+    assert!(stderr.contains("mixed.txt: 最后一行缺少换行符"));
+    assert!(!stderr.contains("clean.txt:"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("警告数: 2"));
+
+    let output = run_line_counter(&[
+        "--warnings",
+        "--format",
+        "json",
+        clean.to_str().unwrap(),
+        mixed.to_str().unwrap(),
+    ]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert!(report["files"][0].get("warnings").is_none());
+    assert_eq!(
+        report["files"][1]["warnings"],
+        serde_json::json!(["mixed-line-endings", "missing-final-newline"])
+    );
+    assert_eq!(report["summary"]["warnings"], 2);
+
+    // 未启用 --warnings 时不检查
+    let output = run_line_counter(&[mixed.to_str().unwrap()]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("换行符"));
+}
+
+#[test]
+fn test_exit_code_overrides() {
+    let temp_dir = TempDir::new().unwrap();
+    let clean = create_test_file(&temp_dir, "clean.txt", "a\nb\n");
+    let mixed = create_test_file(&temp_dir, "mixed.txt", "a\r\nb\nc");
+
+    let output = run_line_counter(&["--warnings", "--fail-on-warning", clean.to_str().unwrap()]);
+    assert!(output.status.success(), "No warnings should succeed");
+
+    let output = run_line_counter(&["--warnings", "--fail-on-warning", mixed.to_str().unwrap()]);
+    assert!(!output.status.success(), "Warnings should fail the run");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("存在文本规范警告: 2 条"));
+
+    // --fail-on-warning 需要 --warnings
+    let output = run_line_counter(&["--fail-on-warning", mixed.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--warnings"));
+
+    // --no-fail 总是以零状态退出，但仍输出错误
+    let output = run_line_counter(&[
+        "--warnings",
+        "--fail-on-warning",
+        "--no-fail",
+        mixed.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "--no-fail should always succeed");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("存在文本规范警告"));
+
+    let missing = temp_dir.path().join("missing.txt");
+    let output = run_line_counter(&["--no-fail", missing.to_str().unwrap()]);
+    assert!(output.status.success(), "--no-fail should always succeed");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("文件不存在"));
+}
+
+#[test]
+fn test_ignore_missing() {
+    let temp_dir = TempDir::new().unwrap();
+    let existing = create_test_file(&temp_dir, "exists.txt", "a\nb\n");
+    let missing = temp_dir.path().join("deleted.txt");
+
+    let output = run_line_counter(&[existing.to_str().unwrap(), missing.to_str().unwrap()]);
+    assert!(
+        !output.status.success(),
+        "Missing file should fail by default"
+    );
+
+    let output = run_line_counter(&[
+        "--ignore-missing",
+        existing.to_str().unwrap(),
+        missing.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Missing file should be skipped");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("跳过不存在的路径"));
+    assert!(stdout.contains("文件数: 1"));
+    assert!(stdout.contains("总行数: 2"));
+
+    let output = run_line_counter(&[
+        "--ignore-missing",
+        "--format",
+        "json",
+        existing.to_str().unwrap(),
+        missing.to_str().unwrap(),
+    ]);
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(report["files"][1]["missing"], true);
+    assert!(report["files"][0].get("missing").is_none());
+    assert_eq!(report["summary"]["missing"], 1);
+    assert_eq!(report["summary"]["files"], 1);
+}
+
+#[test]
+fn test_case_modes() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "sections.txt", "a\nBEGIN\nb\nc\nEND\nd\n");
+    let path = file_path.to_str().unwrap();
+    let count = |extra: &[&str]| {
+        let mut args = vec!["--section-start", "begin", "--section-end", "end"];
+        args.extend_from_slice(extra);
+        args.push(path);
+        let output = run_line_counter(&args);
+        assert!(output.status.success(), "Command should succeed");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    // 默认区分大小写：没有匹配的区段
+    assert!(count(&[]).contains("总行数: 0"));
+    assert!(count(&["-i"]).contains("总行数: 2"));
+    assert!(count(&["--smart-case"]).contains("总行数: 2"));
+    // 以最后出现的选项为准
+    assert!(count(&["-i", "-s"]).contains("总行数: 0"));
+    assert!(count(&["-s", "-i"]).contains("总行数: 2"));
+}
+
+#[test]
+fn test_trailing_whitespace() {
+    let temp_dir = TempDir::new().unwrap();
+    let dirty = create_test_file(&temp_dir, "dirty.rs", "fn main() { \n\t\n}\t\n");
+    let clean = create_test_file(&temp_dir, "clean.rs", "fn main() {\n}\n");
+
+    let output = run_line_counter(&[dirty.to_str().unwrap()]);
+    assert!(output.status.success(), "Counting alone should not fail");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("行尾空白行数: 2"));
+
+    let output = run_line_counter(&["--fail-on-trailing-whitespace", dirty.to_str().unwrap()]);
+    assert!(!output.status.success(), "Trailing whitespace should fail");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("存在行尾空白: 2 行"));
+
+    let output = run_line_counter(&["--fail-on-trailing-whitespace", clean.to_str().unwrap()]);
+    assert!(output.status.success(), "Clean file should pass");
+}
+
+#[test]
+fn test_output_db() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = create_test_file(&temp_dir, "a.rs", "fn main() {}\n\n");
+    let db = temp_dir.path().join("stats.db");
+
+    for run_id in 1..=2 {
+        let output =
+            run_line_counter(&["--output-db", db.to_str().unwrap(), file.to_str().unwrap()]);
+        assert!(output.status.success(), "Command should succeed");
+        assert!(String::from_utf8_lossy(&output.stderr).contains(&format!("运行编号 {}", run_id)));
+    }
+
+    let connection = rusqlite::Connection::open(&db).unwrap();
+    let runs: i64 = connection
+        .query_row("SELECT COUNT(*) FROM runs", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(runs, 2);
+    let (total_lines, empty_lines): (i64, i64) = connection
+        .query_row(
+            "SELECT total_lines, empty_lines FROM file_history WHERE run_id = 2",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((total_lines, empty_lines), (2, 1));
+}
+
+#[test]
+fn test_accessible() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.rs", "fn a() {}\n\nfn b() {}\n");
+    let b = create_test_file(&temp_dir, "b.py", "x = 1\r\ny = 2");
+    // 表情符号、箭头和框线字符都不应出现在无障碍输出中
+    let is_symbol = |c: char| {
+        matches!(c, '\u{2190}'..='\u{21FF}' | '\u{2500}'..='\u{27BF}' | '\u{FE0F}')
+            || c > '\u{1F000}'
+    };
+
+    let output = run_line_counter(&[
+        "--accessible",
+        "--languages",
+        "--line-endings",
+        "--warnings",
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stdout.contains(is_symbol), "stdout: {}", stdout);
+    assert!(!stderr.contains(is_symbol), "stderr: {}", stderr);
+    assert!(stderr.contains("正在处理 2 个文件"));
+    assert!(stdout.contains("文件总大小: 33 字节"));
+    assert!(stdout.contains("换行符: 换行 3, 回车加换行 1, 回车 0"));
+    assert!(stdout.contains("Rust: 按字节占"));
+    // 每行左侧都没有用于对齐或缩进的空白
+    assert!(stdout.lines().all(|line| !line.starts_with(' ')));
+    assert!(stderr.contains("警告 "));
+
+    let output = run_line_counter(&[
+        "diff",
+        "--accessible",
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains(is_symbol), "stdout: {}", stdout);
+    assert!(stdout.contains("总行数: 3 变为 2，变化 -1"));
+}
+
+#[test]
+fn test_decompression() {
+    let temp_dir = TempDir::new().unwrap();
+    let gzip = |name: &str, content: &[u8]| {
+        let path = temp_dir.path().join(name);
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap();
+        path
+    };
+    let source = gzip("main.rs.gz", b"fn main() {}\n\nfn b() {}\n");
+    let bomb = gzip("bomb.log.gz", &b"a\n".repeat(1_500_000));
+
+    let output = run_line_counter(&["--format", "json", source.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    let file = &report["files"][0];
+    assert_eq!(file["compression"], "gzip");
+    assert_eq!(file["language"], "Rust");
+    assert_eq!(file["stats"]["total_lines"], 3);
+    assert_eq!(file["stats"]["empty_lines"], 1);
+
+    // 压缩比过高的文件被跳过，不影响其他文件
+    let output = run_line_counter(&[bomb.to_str().unwrap(), source.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("跳过超过解压缩限制的文件"));
+    assert!(stdout.contains("总行数: 3"));
+
+    let output = run_line_counter(&["--no-decompress", source.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("跳过二进制文件"));
+}
+
+#[test]
+fn test_line_range() {
+    let temp_dir = TempDir::new().unwrap();
+    let content: String = (1..=10)
+        .map(|i| {
+            if i % 2 == 0 {
+                "\n".to_string()
+            } else {
+                format!("line {}\n", i)
+            }
+        })
+        .collect();
+    let file = create_test_file(&temp_dir, "a.txt", &content);
+
+    let output = run_line_counter(&["--lines", "3..6", file.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("总行数: 4"));
+    assert!(stdout.contains("空行数: 2"));
+
+    let output = run_line_counter(&["--lines", "9..", file.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 2"));
+
+    let output = run_line_counter(&["--lines", "6..3", file.to_str().unwrap()]);
+    assert!(
+        !output.status.success(),
+        "Reversed range should be rejected"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("开始大于结束"));
+}
+
+/// 测试文件路径渲染为终端超链接
+#[test]
+fn test_hyperlinks() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.rs", "fn a() {}\n");
+    let b = create_test_file(&temp_dir, "b.rs", "fn b() {}\nfn c() { let x = 1; }\n");
+    let b_url = format!("vscode://file{}:2:1", b.canonicalize().unwrap().display());
+
+    let output = run_line_counter(&[
+        "--hyperlinks",
+        "always",
+        "--hyperlink-format",
+        "vscode",
+        "--top-lines",
+        "1",
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+        "missing.rs",
+        "--ignore-missing",
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!("\x1b]8;;{}\x1b\\", b_url)),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("a.rs\x1b]8;;\x1b\\: 总行数 1"));
+    // 不存在的路径不加链接
+    assert!(stdout.contains("跳过不存在的路径: missing.rs\n"));
+
+    // 默认只在终端中输出超链接
+    let output = run_line_counter(&[a.to_str().unwrap()]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("\x1b]8;;"));
+
+    let output = run_line_counter(&["--hyperlink-format", "editor://open", a.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("{path}"));
+}
+
+/// 测试符号链接的处理：显示链接目标、--no-follow 和循环链接
+#[cfg(unix)]
+#[test]
+fn test_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().join("src");
+    fs::create_dir(&dir).unwrap();
+    let target = dir.join("a.rs");
+    fs::write(&target, "fn a() {}\n\n").unwrap();
+    symlink(&target, dir.join("link.rs")).unwrap();
+    symlink(&dir, dir.join("loop")).unwrap();
+
+    let link = dir.join("link.rs");
+    let output = run_line_counter(&[link.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!(
+        "文件: {} → {}",
+        link.display(),
+        target.canonicalize().unwrap().display()
+    )));
+    assert!(stdout.contains("总行数: 2"));
+
+    // 默认跟随目录中的符号链接，循环链接不会导致无限递归
+    let output = run_line_counter(&["-r", dir.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("文件数: 2"), "stdout: {}", stdout);
+
+    let output = run_line_counter(&["-r", "--no-follow", dir.to_str().unwrap()]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("文件分析完成"), "stdout: {}", stdout);
+    assert!(!stdout.contains("link.rs"));
+
+    // 以最后出现的选项为准
+    let output = run_line_counter(&[
+        "-r",
+        "--no-follow",
+        "--follow-symlinks",
+        dir.to_str().unwrap(),
+    ]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("文件数: 2"));
+}
+
+/// 测试阈值检查及其专用的退出码
+#[test]
+fn test_thresholds() {
+    let temp_dir = TempDir::new().unwrap();
+    let small = create_test_file(&temp_dir, "small.rs", "fn a() {}\n\n");
+    let generated = create_test_file(&temp_dir, "generated.rs", &"x\n".repeat(50));
+    let paths = [small.to_str().unwrap(), generated.to_str().unwrap()];
+
+    let output = run_line_counter(&[
+        "--max-total-lines",
+        "100",
+        "--max-file-lines",
+        "50",
+        paths[0],
+        paths[1],
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("✅ 阈值检查通过"));
+
+    let output = run_line_counter(&[
+        "--max-total-lines",
+        "51",
+        "--max-file-lines",
+        "40",
+        "--max-empty-pct",
+        "1",
+        paths[0],
+        paths[1],
+    ]);
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("🚦 阈值检查: 3 项超限"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("generated.rs: 文件行数 50 超过上限 40"));
+    assert!(stdout.contains("❌ 总行数 52 超过上限 51"));
+    assert!(stdout.contains("❌ 空行占比 1.9% 超过上限 1.0%"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("统计结果超过阈值: 3 项超限"));
+
+    // JSON 模式下检查结果输出到标准错误
+    let output = run_line_counter(&["--format", "json", "--max-file-lines", "10", paths[1]]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("文件行数 50 超过上限 10"));
+
+    // 一般错误仍然以 1 退出
+    let output = run_line_counter(&["--max-file-lines", "10", "missing.rs"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = run_line_counter(&["--max-empty-pct", "150", paths[0]]);
+    assert!(!output.status.success());
+}
+
+/// 测试按相对路径比较两个目录
+#[test]
+fn test_compare_directories() {
+    let temp_dir = TempDir::new().unwrap();
+    let old = temp_dir.path().join("old");
+    let new = temp_dir.path().join("new");
+    for dir in [&old, &new] {
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("same.rs"), "a\nb\n").unwrap();
+    }
+    fs::write(old.join("sub/c.rs"), "1\n").unwrap();
+    fs::write(new.join("sub/c.rs"), "1\n2\n\n").unwrap();
+    fs::write(old.join("gone.rs"), "g\n").unwrap();
+    fs::write(new.join("added.rs"), "n\n").unwrap();
+
+    let output = run_line_counter(&[
+        "compare",
+        "--detail",
+        old.to_str().unwrap(),
+        new.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("➕ 新增 added.rs: 总行数 1"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("➖ 删除 gone.rs: 总行数 1"));
+    assert!(stdout.contains("修改 sub/c.rs: 总行数 1 → 3 (+2), 大小 +3 bytes"));
+    assert!(stdout.contains("📁 文件: 新增 1, 删除 1, 修改 1, 未变化 1"));
+    assert!(stdout.contains("📊 总行数: 4 → 6 (+2)"));
+    assert!(!stdout.contains("same.rs"));
+
+    // 不指定 --detail 时只输出汇总
+    let output = run_line_counter(&["compare", old.to_str().unwrap(), new.to_str().unwrap()]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("未变化 1"));
+    assert!(!stdout.contains("added.rs"));
+
+    let output = run_line_counter(&[
+        "compare",
+        "--detail",
+        "--format",
+        "json",
+        old.to_str().unwrap(),
+        new.to_str().unwrap(),
+    ]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["changed"], 1);
+    assert_eq!(json["files"][0]["path"], "added.rs");
+    assert_eq!(json["files"][0]["status"], "added");
+    assert_eq!(json["files"][2]["stats"]["delta"]["total_lines"], 2);
+
+    let output = run_line_counter(&[
+        "compare",
+        old.join("gone.rs").to_str().unwrap(),
+        new.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("不是目录"));
+}
+
+/// 测试差异和目录比较忽略只涉及空行的变化
+#[test]
+fn test_ignore_blank_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let old = create_test_file(&temp_dir, "old.rs", "fn a() {}\nfn b() {}\n");
+    let new = create_test_file(&temp_dir, "new.rs", "fn a() {}\n\n\nfn b() {}\n\n");
+
+    let output = run_line_counter(&[
+        "diff",
+        "--ignore-blank-changes",
+        old.to_str().unwrap(),
+        new.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("（忽略空行变化）"), "stdout: {}", stdout);
+    assert!(
+        stdout.contains("非空行数: 2 → 2 (+0)"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!stdout.contains("总行数"), "stdout: {}", stdout);
+    assert!(!stdout.contains("空行数: 0"), "stdout: {}", stdout);
+
+    let output = run_line_counter(&[
+        "diff",
+        "--ignore-blank-changes",
+        "--format",
+        "markdown",
+        old.to_str().unwrap(),
+        new.to_str().unwrap(),
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("| 非空行数 | 2 | 2 | +0 |"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!stdout.contains("| 总行数 |"), "stdout: {}", stdout);
+
+    let old_dir = temp_dir.path().join("a");
+    let new_dir = temp_dir.path().join("b");
+    fs::create_dir(&old_dir).unwrap();
+    fs::create_dir(&new_dir).unwrap();
+    fs::copy(&old, old_dir.join("x.rs")).unwrap();
+    fs::copy(&new, new_dir.join("x.rs")).unwrap();
+    fs::write(old_dir.join("y.rs"), "1\n").unwrap();
+    fs::write(new_dir.join("y.rs"), "1\n\n2\n").unwrap();
+
+    let output = run_line_counter(&[
+        "compare",
+        "--detail",
+        "--ignore-blank-changes",
+        old_dir.to_str().unwrap(),
+        new_dir.to_str().unwrap(),
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("修改 y.rs: 非空行数 1 → 2 (+1)"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!stdout.contains("x.rs"));
+    assert!(stdout.contains("修改 1, 未变化 1"));
+    assert!(
+        stdout.contains("非空行数: 3 → 4 (+1)"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!stdout.contains("总行数"), "stdout: {}", stdout);
+}
+
+/// 测试 shields.io 徽章输出
+#[test]
+fn test_badge_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "big.txt", &"line\n".repeat(12_345));
+
+    let output = run_line_counter(&["--format", "badge", file_path.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1);
+    let badge: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(badge["schemaVersion"], 1);
+    assert_eq!(badge["label"], "lines");
+    assert_eq!(badge["message"], "12.3k");
+
+    // 附加信息输出到标准错误，标准输出只有徽章
+    let output = run_line_counter(&[
+        "--format",
+        "badge",
+        "--max-total-lines",
+        "100000",
+        file_path.to_str().unwrap(),
+    ]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("阈值检查通过"));
+    assert!(serde_json::from_slice::<serde_json::Value>(&output.stdout).is_ok());
+
+    let output = run_line_counter(&[
+        "diff",
+        "--format",
+        "badge",
+        file_path.to_str().unwrap(),
+        file_path.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+}
+
+/// 测试 --git 只统计已跟踪的文件
+#[test]
+fn test_git_tracked_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(args)
+            .status()
+            .expect("Failed to execute git");
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    create_test_file(&temp_dir, "main.rs", "fn main() {}\n\n");
+    fs::create_dir(root.join("target")).unwrap();
+    fs::write(root.join("target/generated.rs"), "x\n".repeat(100)).unwrap();
+    git(&["add", "main.rs"]);
+
+    let output = run_line_counter(&["--git", root.to_str().unwrap()]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("main.rs"), "stdout: {}", stdout);
+    assert!(stdout.contains("总行数: 2"));
+    assert!(!stdout.contains("generated.rs"));
+
+    // 不使用 --git 时统计目录中的所有文件
+    let output = run_line_counter(&["-r", root.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("generated.rs"));
+
+    let outside = TempDir::new().unwrap();
+    let output = run_line_counter(&["--git", outside.path().to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Git 命令失败"));
+}
+
+/// 测试汇总输出总是包含文件数、总字节数和耗时
+#[test]
+fn test_summary_files_bytes_elapsed() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "one\ntwo\n");
+    let b = create_test_file(&temp_dir, "b.txt", "three\n");
+    let paths = [a.to_str().unwrap(), b.to_str().unwrap()];
+
+    let output = run_line_counter(&paths);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("文件数: 2"));
+    assert!(stdout.contains("文件总大小: 14 bytes"));
+    assert!(stdout.contains("耗时: "), "stdout: {}", stdout);
+
+    let output = run_line_counter(&["--format", "markdown", paths[0], paths[1]]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("共统计 2 个文件，14 字节，耗时 "),
+        "stdout: {}",
+        stdout
+    );
+
+    let output = run_line_counter(&["--format", "json", paths[0], paths[1]]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["summary"]["files"], 2);
+    assert_eq!(json["summary"]["bytes"], 14);
+    assert!(json["meta"]["duration_ms"].is_u64());
+}
+
+/// 测试 git-diff 子命令统计两个版本之间的行变化
+#[test]
+fn test_git_diff_subcommand() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .arg("-C")
+            .arg(root)
+            .args(args)
+            .status()
+            .expect("Failed to execute git");
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    create_test_file(&temp_dir, "lib.rs", "one\ntwo\nthree\n");
+    create_test_file(&temp_dir, "old.rs", "x\n");
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "first"]);
+    git(&["tag", "v1"]);
+    create_test_file(&temp_dir, "lib.rs", "one\n2\nthree\nfour\nfive\n");
+    fs::remove_file(root.join("old.rs")).unwrap();
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "second"]);
+
+    let repo = root.to_str().unwrap();
+    let output = run_line_counter(&["git-diff", "v1..HEAD", "--repo", repo]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("修改 lib.rs: 新增 2 行, 删除 0 行, 修改 1 行"));
+    assert!(stdout.contains("删除 old.rs: 新增 0 行, 删除 1 行, 修改 0 行"));
+    assert!(stdout.contains("共 4 行变化"), "stdout: {}", stdout);
+
+    let output = run_line_counter(&[
+        "git-diff", "v1..HEAD", "lib.rs", "--repo", repo, "--format", "json",
+    ]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["range"], "v1..HEAD");
+    assert_eq!(json["changed"], 1);
+    assert_eq!(json["total"]["modified"], 1);
+    assert_eq!(json["files"][0]["path"], "lib.rs");
+
+    let output = run_line_counter(&["git-diff", "v9..HEAD", "--repo", repo]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Git 命令失败"));
+}
+
+/// 测试 --by-author 按最后修改的作者统计行数
+#[test]
+fn test_git_by_author() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let git = |author: &str, args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-c")
+            .arg(format!("user.name={}", author))
+            .args(["-c", "user.email=dev@example.com", "-C"])
+            .arg(root)
+            .args(args)
+            .status()
+            .expect("Failed to execute git");
+        assert!(status.success());
+    };
+    git("Alice", &["init", "-q"]);
+    create_test_file(&temp_dir, "main.rs", "fn main() {\n}\n");
+    git("Alice", &["add", "-A"]);
+    git("Alice", &["commit", "-q", "-m", "init"]);
+    create_test_file(&temp_dir, "main.rs", "fn main() {\n    run();\n}\n");
+    create_test_file(&temp_dir, "lib.rs", "pub fn run() {}\n");
+    git("Bob", &["add", "-A"]);
+    git("Bob", &["commit", "-q", "-m", "run"]);
+
+    let dir = root.to_str().unwrap();
+    let output = run_line_counter(&["--git", "--by-author", dir]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("按作者统计"), "stdout: {}", stdout);
+
+    let output = run_line_counter(&["--git", "--by-author", "--format", "json", dir]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["authors"][0]["name"], "Alice");
+    assert_eq!(json["authors"][0]["lines"], 2);
+    assert_eq!(json["authors"][1]["name"], "Bob");
+    assert_eq!(json["authors"][1]["lines"], 2);
+    assert_eq!(json["authors"][1]["files"], 2);
+
+    // 需要 --git
+    let output = run_line_counter(&["--by-author", dir]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+/// 测试配置文件中的 never_read 规则：直接给出的文件被拒绝，遍历目录时被跳过
+#[test]
+fn test_config_never_read() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = create_test_file(
+        &temp_dir,
+        "config.toml",
+        "never_read = [\"**/*.pem\", \"**/secrets/**\"]\n",
+    );
+    let dir = temp_dir.path().join("project");
+    fs::create_dir_all(dir.join("secrets")).unwrap();
+    fs::write(dir.join("main.rs"), "fn main() {\n}\n").unwrap();
+    fs::write(
+        dir.join("server.pem"),
+        "-----BEGIN-----\nkey\n-----END-----\n",
+    )
+    .unwrap();
+    fs::write(dir.join("secrets").join("token"), "abc\n").unwrap();
+    let config = config.to_str().unwrap();
+
+    let pem = dir.join("server.pem");
+    let output = run_line_counter(&["--config", config, pem.to_str().unwrap()]);
+    assert!(!output.status.success(), "Command should refuse to read");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("拒绝读取"), "stderr: {}", stderr);
+    assert!(output.stdout.is_empty());
+
+    let output = run_line_counter(&[
+        "--config",
+        config,
+        "-r",
+        "--format",
+        "json",
+        dir.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["summary"]["files"], 1);
+    assert_eq!(json["summary"]["stats"]["total_lines"], 2);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("never_read"), "stderr: {}", stderr);
+
+    // 子命令同样生效
+    let main = dir.join("main.rs");
+    let output = run_line_counter(&[
+        "diff",
+        main.to_str().unwrap(),
+        pem.to_str().unwrap(),
+        "--config",
+        config,
+    ]);
+    assert!(!output.status.success(), "Command should refuse to read");
+
+    let invalid = create_test_file(&temp_dir, "invalid.toml", "never_read = \"*.pem\"\n");
+    let output = run_line_counter(&[
+        "--config",
+        invalid.to_str().unwrap(),
+        main.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success(), "Invalid config should fail");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("配置文件无效"), "stderr: {}", stderr);
+}
+
+/// 测试 --match 和 --invert-match 统计匹配的行数
+#[test]
+fn test_match_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.rs", "// TODO: x\nfn a() {}\n\n// todo: y\n");
+    let b = create_test_file(&temp_dir, "b.rs", "// TODO: z\n");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    let output = run_line_counter(&["--match", "TODO", a]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("匹配行数: 1"), "stdout: {}", stdout);
+
+    let output = run_line_counter(&["--match", "todo", "-i", "--format", "json", a, b]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["files"][0]["stats"]["matched_lines"], 2);
+    assert_eq!(json["summary"]["stats"]["matched_lines"], 3);
+
+    let output = run_line_counter(&["--match", "TODO", "-v", "--format", "json", a]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["files"][0]["stats"]["matched_lines"], 3);
+
+    // --invert-match 需要 --match
+    let output = run_line_counter(&["-v", a]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = run_line_counter(&["--format", "json", a]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["files"][0]["stats"].get("matched_lines").is_none());
+}
+
+/// 测试 --format plain-kv 输出稳定的 key=value 指标
+#[test]
+fn test_plain_kv_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "one\n\nthree\n");
+    let b = create_test_file(&temp_dir, "b.txt", "x\n");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    let output = run_line_counter(&["--format", "plain-kv", a, b]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("file.1.path={}\n", a)));
+    assert!(stdout.contains("file.1.total_lines=3\n"));
+    assert!(stdout.contains("file.2.empty_lines=0\n"));
+    assert!(stdout.contains("summary.files=2\n"));
+    assert!(stdout.contains("summary.total_lines=4\n"));
+    assert!(stdout.contains("summary.empty_percentage=25.00\n"));
+    assert!(stdout.lines().all(|line| line.contains('=')));
+    assert!(!stdout.contains('📊'));
+
+    // 相同的输入得到相同的输出
+    let again = run_line_counter(&["--format", "plain-kv", a, b]);
+    assert_eq!(output.stdout, again.stdout);
+
+    let output = run_line_counter(&["diff", a, b, "--format", "plain-kv"]);
+    assert!(!output.status.success());
+}
+
+/// 测试 --todos 按文件和总数统计待办标记，--list-todos 列出行号
+#[test]
+fn test_todos() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(
+        &temp_dir,
+        "a.rs",
+        "// TODO: split\nfn a() {} // FIXME\n// TODOS are not markers\n",
+    );
+    let b = create_test_file(&temp_dir, "b.rs", "fn b() {}\n// HACK: temporary\n");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    let output = run_line_counter(&["--todos", "--format", "json", a, b]);
+    assert!(output.status.success(), "Command should succeed");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["files"][0]["stats"]["todos"]["total"], 2);
+    assert_eq!(json["files"][0]["stats"]["todos"]["counts"]["XXX"], 0);
+    assert_eq!(json["files"][1]["stats"]["todos"]["counts"]["HACK"], 1);
+    assert_eq!(json["summary"]["stats"]["todos"]["total"], 3);
+    assert!(json["files"][0]["stats"]["todos"].get("items").is_none());
+
+    let output = run_line_counter(&["--todos", "--list-todos", a, b]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("待办标记: 3"), "stdout: {}", stdout);
+    assert!(
+        stdout.contains(&format!("{}:2: FIXME", a)),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains(&format!("{}:2: HACK: temporary", b)));
+
+    let output = run_line_counter(&[
+        "--todos",
+        "--todo-markers",
+        "HACK",
+        "--format",
+        "json",
+        a,
+        b,
+    ]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["summary"]["stats"]["todos"]["total"], 1);
+    assert!(
+        json["summary"]["stats"]["todos"]["counts"]
+            .get("TODO")
+            .is_none()
+    );
+
+    // --list-todos 需要 --todos
+    let output = run_line_counter(&["--list-todos", a]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_shebang_language() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(&temp_dir, "deploy", "#!/usr/bin/env python3\nprint(1)\n");
+    create_test_file(&temp_dir, "setup", "#!/bin/bash\nset -e\nmake\n");
+    create_test_file(&temp_dir, "LICENSE", "MIT\n");
+
+    let dir = temp_dir.path().to_str().unwrap();
+    let output = run_line_counter(&["-r", "--languages", "--format", "json", dir]);
+    assert!(output.status.success(), "Command should succeed");
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let languages = report["languages"].as_array().unwrap();
+    assert_eq!(languages.len(), 2);
+    assert_eq!(languages[0]["name"], "Python");
+    assert_eq!(languages[0]["lines"], 2);
+    assert_eq!(languages[1]["name"], "Shell");
+    assert_eq!(languages[1]["lines"], 3);
+}
+
+#[test]
+fn test_custom_languages_and_comments() {
+    let temp_dir = TempDir::new().unwrap();
+    let languages = create_test_file(
+        &temp_dir,
+        "languages.toml",
+        "[[language]]\nname = \"Acme\"\nextensions = [\"acme\"]\nline_comments = [\"--\"]\nblock_comments = [[\"{-\", \"-}\"]]\nstrings = ['\"']\n",
+    );
+    let acme = create_test_file(
+        &temp_dir,
+        "rules.acme",
+        "-- header\nrule a -- trailing\n\n{- block\n   more -}\nprint \"-- not a comment\"\n",
+    );
+    let rust = create_test_file(&temp_dir, "main.rs", "// doc\nfn main() {}\n");
+    let (languages, acme, rust) = (
+        languages.to_str().unwrap(),
+        acme.to_str().unwrap(),
+        rust.to_str().unwrap(),
+    );
+
+    let output = run_line_counter(&[
+        "--comments",
+        "--languages",
+        "--languages-file",
+        languages,
+        "--format",
+        "json",
+        acme,
+        rust,
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["language"], "Acme");
+    assert_eq!(report["files"][0]["stats"]["comments"]["comment_lines"], 3);
+    assert_eq!(report["files"][0]["stats"]["comments"]["code_lines"], 2);
+    assert_eq!(report["files"][1]["stats"]["comments"]["comment_lines"], 1);
+    assert_eq!(report["summary"]["stats"]["comments"]["code_lines"], 3);
+    assert!(
+        report["languages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|share| share["name"] == "Acme")
+    );
+
+    let output = run_line_counter(&["--comments", acme, rust]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("注释行数"), "stdout: {}", stdout);
+
+    // 没有 --comments 时不统计注释行
+    let output = run_line_counter(&["--format", "json", rust]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["files"][0]["stats"].get("comments").is_none());
+
+    let invalid = create_test_file(&temp_dir, "invalid.toml", "[[language]]\nname = \"X\"\n");
+    let output = run_line_counter(&["--languages-file", invalid.to_str().unwrap(), rust]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid.toml"));
+}
+
+/// 测试配置文件中的默认选项：排除规则、输出格式和阈值，命令行选项优先
+#[test]
+fn test_config_defaults() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = create_test_file(
+        &temp_dir,
+        "config.toml",
+        "exclude = [\"**/target/**\"]\nformat = \"json\"\nmax_size = \"1K\"\n\n[thresholds]\nmax_total_lines = 3\n",
+    );
+    let dir = temp_dir.path().join("project");
+    fs::create_dir_all(dir.join("target")).unwrap();
+    fs::write(dir.join("main.rs"), "fn main() {\n}\n").unwrap();
+    fs::write(dir.join("target").join("gen.rs"), "a\nb\nc\nd\n").unwrap();
+    let config = config.to_str().unwrap();
+    let dir = dir.to_str().unwrap();
+
+    let output = run_line_counter(&["--config", config, "-r", dir]);
+    assert!(output.status.success(), "Command should succeed");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["summary"]["files"], 1);
+    assert_eq!(json["summary"]["stats"]["total_lines"], 2);
+
+    // 命令行直接给出的文件不受排除规则影响，阈值来自配置文件
+    let generated = temp_dir.path().join("project/target/gen.rs");
+    let output = run_line_counter(&["--config", config, generated.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(3));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["summary"]["stats"]["total_lines"], 4);
+
+    // 命令行选项覆盖配置文件
+    let output = run_line_counter(&[
+        "--config",
+        config,
+        "--format",
+        "plain-kv",
+        "--max-total-lines",
+        "10",
+        "--exclude",
+        "**/main.rs",
+        "-r",
+        dir,
+    ]);
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("summary.files=0\n"), "stdout: {}", stdout);
+
+    let large = create_test_file(&temp_dir, "large.txt", &"x\n".repeat(1000));
+    let output = run_line_counter(&["--config", config, large.to_str().unwrap()]);
+    assert!(!output.status.success(), "File over max_size should fail");
+}
+
+#[test]
+fn test_color() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "1\n2\n3\n");
+    let b = create_test_file(&temp_dir, "b.txt", "1\n");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    // 标准输出不是终端时默认不输出颜色
+    let output = run_line_counter(&[a, b, "--no-progress"]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains('\x1b'));
+
+    let output = run_line_counter(&[
+        a,
+        b,
+        "--no-progress",
+        "--color",
+        "always",
+        "--max-file-lines",
+        "2",
+    ]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[1;36m汇总完成!\x1b[0m"));
+    assert!(stdout.contains("\x1b[1;31m3\x1b[0m 超过上限 2"));
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            a,
+            "--color",
+            "always",
+            "--max-total-lines",
+            "10",
+        ])
+        .env("LINE_COUNTER_COLORS", "heading=:success=1;32")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✅ 文件分析完成!\n"));
+    assert!(stdout.contains("\x1b[1;32m阈值检查通过\x1b[0m"));
+}
+
+#[test]
+fn test_no_emoji() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "1\n2\n3\n");
+    let b = create_test_file(&temp_dir, "b.txt", "1\n");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    let output = run_line_counter(&[a, b, "--no-progress", "--no-emoji", "--max-file-lines", "2"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\n汇总完成!\n"));
+    assert!(stdout.contains("\n总行数: 4\n"));
+    assert!(stdout.contains("未通过: "));
+    assert!(!stdout.contains(['✅', '❌', '📊', '📄']));
+
+    // 区域设置不是 UTF-8 时自动使用纯文本标签
+    let output = Command::new("cargo")
+        .args(["run", "--", a])
+        .env("LC_ALL", "")
+        .env("LC_CTYPE", "")
+        .env("LANG", "C")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("文件分析完成!\n"));
+    assert!(!stdout.contains('📊'));
+
+    let output = Command::new("cargo")
+        .args(["run", "--", a])
+        .env("LC_ALL", "zh_CN.UTF-8")
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("📊 总行数: 3"));
+}
+
+#[test]
+fn test_lang() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "1\n\n3\n");
+    let a = a.to_str().unwrap();
+
+    let output = run_line_counter(&[a, "--lang", "en"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Total lines: 3\n"));
+    assert!(stdout.contains("Empty lines: 1\n"));
+    assert!(!stdout.contains("总行数"));
+
+    let missing = temp_dir.path().join("missing.txt");
+    let output = run_line_counter(&[missing.to_str().unwrap(), "--lang", "en"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Error: File not found: "));
+
+    // 未指定 --lang 时按区域设置选择
+    let output = Command::new("cargo")
+        .args(["run", "--", a])
+        .env("LC_ALL", "")
+        .env("LC_MESSAGES", "")
+        .env("LANG", "en_US.UTF-8")
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("📊 Total lines: 3"));
+
+    let output = Command::new("cargo")
+        .args(["run", "--", a, "--lang", "zh"])
+        .env("LANG", "en_US.UTF-8")
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 3"));
+}
+
+#[test]
+fn test_tui_requires_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = create_test_file(&temp_dir, "a.txt", "1\n");
+
+    let output = run_line_counter(&["tui", file.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("不是目录"));
+}
+
+#[test]
+fn test_serve() {
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpStream;
+
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(&temp_dir, "a.txt", "1\n\n3\n");
+
+    let mut child = Command::new("cargo")
+        .args(["run", "--", "serve", "--port", "0", "--root"])
+        .arg(temp_dir.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute command");
+
+    // 第一行输出实际监听的地址
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let address = line.trim().rsplit("http://").next().unwrap().to_string();
+
+    let request = |request: &str| {
+        let mut stream = TcpStream::connect(&address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    let response =
+        request("GET /count?path=a.txt HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("\"path\": \"a.txt\""));
+    assert!(response.contains("\"total_lines\": 3"));
+
+    let response = request(
+        "POST /count HTTP/1.1\r\nHost: x\r\nContent-Length: 4\r\nConnection: close\r\n\r\na\nb\n",
+    );
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("\"total_lines\": 2"));
+
+    let response = request("GET /count?path=../ HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 403"));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_result_cache() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_dir = temp_dir.path().join("cache");
+    let a = create_test_file(&temp_dir, "a.txt", "1\n\n3\n");
+    // 刚刚修改过的文件不写入缓存
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+    File::options()
+        .write(true)
+        .open(&a)
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+    let run = |extra: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--", a.to_str().unwrap()])
+            .args(extra)
+            .env("XDG_CACHE_HOME", &cache_dir)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["--no-cache"]);
+    assert!(output.status.success());
+    assert!(!cache_dir.exists());
+
+    for _ in 0..2 {
+        let output = run(&[]);
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 3"));
+    }
+    let entries: Vec<_> = fs::read_dir(cache_dir.join("line-counter"))
+        .unwrap()
+        .collect();
+    assert_eq!(entries.len(), 1);
+}
+
+#[test]
+fn test_changed_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_dir = temp_dir.path().join("cache");
+    let a = create_test_file(&temp_dir, "a.txt", "1\n");
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+    let set_modified = |path: &PathBuf| {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+    };
+    set_modified(&a);
+    let run = |extra: &[&str]| {
+        let output = Command::new("cargo")
+            .args(["run", "--", a.to_str().unwrap()])
+            .args(extra)
+            .env("XDG_CACHE_HOME", &cache_dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    assert!(run(&[]).contains("总行数: 1"));
+
+    // 修改时间没有变化时增量统计直接使用缓存的结果
+    fs::write(&a, "1\n2\n").unwrap();
+    set_modified(&a);
+    assert!(run(&["--changed-only"]).contains("总行数: 1"));
+    assert!(run(&["--changed-only", "--since", "2000-01-01T00:00:00Z"]).contains("总行数: 2"));
+
+    let output = run_line_counter(&[a.to_str().unwrap(), "--since", "HEAD"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_files_from() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "1\n2\n");
+    let b = create_test_file(&temp_dir, "b c.txt", "1\n");
+    let list = format!("{}\n\n{}\n", a.display(), b.display());
+    let list_file = create_test_file(&temp_dir, "list.txt", &list);
+
+    let output = run_line_counter_with_stdin(&["--files-from", "-", "--format", "json"], &list);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["files"], 2);
+    assert_eq!(report["summary"]["stats"]["total_lines"], 3);
+
+    let output = run_line_counter(&[
+        "--files-from",
+        list_file.to_str().unwrap(),
+        "--format",
+        "json",
+    ]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["files"], 2);
+
+    // 空列表统计零个文件
+    let output = run_line_counter_with_stdin(&["--files-from", "-"], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 0"));
+
+    let output = run_line_counter_with_stdin(&["--files-from", "-", "-"], "");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_files_from_null() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a\nb.txt", "1\n2\n");
+    let b = create_test_file(&temp_dir, " c.txt", "1\n");
+    let list = format!("{}\0{}\0", a.display(), b.display());
+
+    let output =
+        run_line_counter_with_stdin(&["-0", "--files-from", "-", "--format", "json"], &list);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["files"], 2);
+    assert_eq!(report["summary"]["stats"]["total_lines"], 3);
+
+    // -0 只用于文件列表
+    let output = run_line_counter(&["-0", a.to_str().unwrap()]);
+    assert!(!output.status.success());
+}
+
+/// 测试 --output 把报告写入文件，提示信息输出到标准错误
+#[test]
+fn test_output_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "1\n2\n");
+    let b = create_test_file(&temp_dir, "b.txt", "1\n\n");
+    let report_path = temp_dir.path().join("report.json");
+    let a = a.to_str().unwrap();
+    let b = b.to_str().unwrap();
+    let report = report_path.to_str().unwrap();
+
+    let output = run_line_counter(&["--format", "json", "--output", report, a, b]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(json["summary"]["stats"]["total_lines"], 4);
+
+    // 可读文本写入文件，“正在处理”的提示仍然输出到标准错误
+    let output = run_line_counter(&["--output", report, a, b]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("正在处理 2 个文件"));
+    let text = fs::read_to_string(&report_path).unwrap();
+    assert!(text.contains("文件数"));
+    assert!(!text.contains("正在处理"));
+    assert!(!text.contains('\x1b'));
+
+    // 不留下临时文件
+    let names: Vec<_> = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(names.len(), 3);
+}
+
+/// 测试 --human 只影响可读文本中的大小和数量
+#[test]
+fn test_human_numbers() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = create_test_file(&temp_dir, "big.txt", &"line\n".repeat(12_345));
+    let file = file.to_str().unwrap();
+
+    let output = run_line_counter(&["--human", file]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("总行数: 12.3k"));
+    assert!(stdout.contains("文件大小: 60.3 KiB"));
+
+    let output = run_line_counter(&["--human", "--format", "json", file]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["size"], 61_725);
+    assert_eq!(report["summary"]["stats"]["total_lines"], 12_345);
+}
+
+/// 测试非空行占比与 --precision
+#[test]
+fn test_precision() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = create_test_file(&temp_dir, "a.txt", "1\n\n3\n");
+    let file = file.to_str().unwrap();
+
+    let output = run_line_counter(&[file]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("空行占比: 33.3%"));
+    assert!(stdout.contains("非空行占比: 66.7%"));
+
+    let output = run_line_counter(&["--precision", "3", file]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("空行占比: 33.333%"));
+    assert!(stdout.contains("非空行占比: 66.667%"));
+
+    let output = run_line_counter(&["--format", "plain-kv", "--precision", "0", file]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("summary.empty_percentage=33\n"));
+    assert!(stdout.contains("summary.non_empty_percentage=67\n"));
+
+    let output = run_line_counter(&["--precision", "11", file]);
+    assert!(!output.status.success());
+}
+
+/// 测试行长度的中位数和百分位数，汇总按全部文件的行计算，缓存的结果保持一致
+#[test]
+fn test_line_length_percentiles() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_dir = temp_dir.path().join("cache");
+    let short = create_test_file(&temp_dir, "short.txt", &"abc\n".repeat(95));
+    let long = create_test_file(
+        &temp_dir,
+        "long.txt",
+        &format!("{}\n", "x".repeat(200)).repeat(5),
+    );
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+    for path in [&short, &long] {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+    }
+    let run = || {
+        let output = Command::new("cargo")
+            .args(["run", "--", "--line-lengths", "--format", "json"])
+            .args([&short, &long])
+            .env("XDG_CACHE_HOME", &cache_dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        report["summary"]["stats"]["line_length_distribution"].clone()
+    };
+
+    let distribution = run();
+    assert_eq!(distribution["lines"], 100);
+    assert_eq!(distribution["median"], 3);
+    assert_eq!(distribution["p90"], 3);
+    assert_eq!(distribution["p99"], 200);
+    // 第二次运行从缓存读取
+    assert_eq!(run(), distribution);
+
+    let output = run_line_counter(&["--line-lengths", short.to_str().unwrap()]);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("行长度: 中位数 3 列, P90 3 列, P99 3 列")
+    );
+}
+
+/// 测试 --reading-time 只为散文类文件统计单词数，并按 --wpm 估计阅读时间
+#[test]
+fn test_reading_time() {
+    let temp_dir = TempDir::new().unwrap();
+    let doc = create_test_file(
+        &temp_dir,
+        "doc.md",
+        &"one two three four five\n".repeat(100),
+    );
+    let code = create_test_file(&temp_dir, "main.rs", "fn main() {}\n");
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "--no-cache",
+            "--reading-time",
+            "--wpm",
+            "100",
+            "--format",
+            "json",
+        ])
+        .args([&doc, &code])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["stats"]["words"], 500);
+    assert_eq!(report["files"][0]["reading_minutes"], 5.0);
+    assert!(report["files"][1]["stats"].get("words").is_none());
+    assert!(report["files"][1].get("reading_minutes").is_none());
+    assert_eq!(report["summary"]["reading_minutes"], 5.0);
+
+    let output = run_line_counter(&["--reading-time", doc.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("阅读时间: 约 3 分钟（500 个单词）"));
+
+    // --wpm 需要同时指定 --reading-time
+    let output = run_line_counter(&["--wpm", "100", doc.to_str().unwrap()]);
+    assert!(!output.status.success());
+}
+
+/// 测试 --word-freq 列出最常用的单词，--stop-words 中的单词不计入，汇总时合并各文件的结果
+#[test]
+fn test_word_freq() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "The quick fox\nthe lazy dog\n");
+    let b = create_test_file(&temp_dir, "b.txt", "A fox, a FOX!\n");
+    let stop_words = create_test_file(&temp_dir, "stop.txt", "# 冠词\nthe a\n");
+    let output = run_line_counter(&[
+        "--no-cache",
+        "--word-freq",
+        "2",
+        "--stop-words",
+        stop_words.to_str().unwrap(),
+        "--format",
+        "json",
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let word_freq = &report["summary"]["stats"]["word_freq"];
+    assert_eq!(word_freq["words"], 6);
+    assert_eq!(word_freq["approximate"], false);
+    assert_eq!(word_freq["top"][0]["word"], "fox");
+    assert_eq!(word_freq["top"][0]["count"], 3);
+    assert_eq!(word_freq["top"].as_array().unwrap().len(), 2);
+
+    let output = run_line_counter(&["--word-freq", "1", a.to_str().unwrap()]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("高频单词: 共 6 个单词"));
+    assert!(stdout.contains("2 次: the"));
+
+    let output = run_line_counter(&[
+        "--stop-words",
+        stop_words.to_str().unwrap(),
+        a.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+}
+
+/// 测试 --logical 按语言的续行规则合并续行，没有续行规则的语言不统计
+#[test]
+fn test_logical_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = create_test_file(&temp_dir, "build.sh", "./configure \\\n  --quiet\nmake\n");
+    let python = create_test_file(
+        &temp_dir,
+        "app.py",
+        "items = [\n    1,\n    2,\n]\nprint(items)\n",
+    );
+    let text = create_test_file(&temp_dir, "notes.txt", "a \\\nb\n");
+    let output = run_line_counter(&[
+        "--no-cache",
+        "--logical",
+        "--format",
+        "json",
+        script.to_str().unwrap(),
+        python.to_str().unwrap(),
+        text.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["stats"]["logical"]["logical_lines"], 2);
+    assert_eq!(report["files"][1]["stats"]["logical"]["logical_lines"], 2);
+    assert!(report["files"][2]["stats"].get("logical").is_none());
+    let logical = &report["summary"]["stats"]["logical"];
+    assert_eq!(logical["physical_lines"], 8);
+    assert_eq!(logical["logical_lines"], 4);
+
+    let output = run_line_counter(&["--logical", python.to_str().unwrap()]);
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("逻辑行数: 2（物理行数 5, 合并续行 3 行）")
+    );
+}
+
+/// 测试 CSV/TSV 记录统计和 --mode
+#[test]
+fn test_csv_mode() {
+    let temp_dir = TempDir::new().unwrap();
+    let csv = create_test_file(
+        &temp_dir,
+        "data.csv",
+        "id,note\n1,\"多行\n备注\"\n2,\n3,x,y\n",
+    );
+    let tsv = create_test_file(&temp_dir, "data.tsv", "a\tb\n1\t2\n");
+    let text = create_test_file(&temp_dir, "export.txt", "a,b\n1,2\n");
+    let output = run_line_counter(&[
+        "--no-cache",
+        "--format",
+        "json",
+        csv.to_str().unwrap(),
+        tsv.to_str().unwrap(),
+        text.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let records = &report["files"][0]["stats"]["csv"];
+    assert_eq!(report["files"][0]["stats"]["total_lines"], 5);
+    assert_eq!(records["records"], 4);
+    assert_eq!(records["columns"], 2);
+    assert_eq!(records["inconsistent_records"], 1);
+    assert_eq!(records["inconsistent_lines"], serde_json::json!([5]));
+    assert_eq!(records["empty_fields"], 1);
+    assert_eq!(report["files"][1]["stats"]["csv"]["records"], 2);
+    assert!(report["files"][2]["stats"].get("csv").is_none());
+    assert_eq!(report["summary"]["stats"]["csv"]["records"], 6);
+
+    // --mode csv 对所有文件按 CSV 统计，--mode lines 只统计行数
+    let output = run_line_counter(&["--no-cache", "--mode", "csv", text.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("记录数: 2, 列数: 2, 空字段: 0"));
+    let output = run_line_counter(&["--no-cache", "--mode", "lines", csv.to_str().unwrap()]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("记录数"));
+
+    let output = run_line_counter(&["--no-cache", csv.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("列数不一致的记录: 1（行 5）"));
+}
+
+/// 测试 --mode jsonl 和 --mode json
+#[test]
+fn test_json_modes() {
+    let temp_dir = TempDir::new().unwrap();
+    let events = create_test_file(
+        &temp_dir,
+        "events.ndjson",
+        "{\"id\": 1}\n{\"id\": 2}\n\n{\"id\": \n[3]\n",
+    );
+    let output = run_line_counter(&[
+        "--no-cache",
+        "--mode",
+        "jsonl",
+        "--format",
+        "json",
+        events.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let jsonl = &report["files"][0]["stats"]["jsonl"];
+    assert_eq!(jsonl["records"], 3);
+    assert_eq!(jsonl["invalid_lines"], 1);
+    assert_eq!(jsonl["invalid_line_numbers"], serde_json::json!([4]));
+    assert_eq!(jsonl["empty_lines"], 1);
+
+    let output = run_line_counter(&["--no-cache", "--mode", "jsonl", events.to_str().unwrap()]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("JSON 记录数: 3, 空行数: 1"));
+    assert!(stdout.contains("无效的 JSON 行: 1（行 4）"));
+
+    let array = create_test_file(
+        &temp_dir,
+        "array.json",
+        "[\n  {\"a\": 1},\n  {\"a\": 2}\n]\n",
+    );
+    let output = run_line_counter(&[
+        "--no-cache",
+        "--mode",
+        "json",
+        "--format",
+        "json",
+        array.to_str().unwrap(),
+        events.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let json = &report["files"][0]["stats"]["json"];
+    assert_eq!(json["invalid_documents"], 0);
+    assert_eq!(json["kind"], "array");
+    assert_eq!(json["array_length"], 2);
+    let json = &report["files"][1]["stats"]["json"];
+    assert_eq!(json["invalid_documents"], 1);
+    assert_eq!(json["error_line"], 2);
+    assert_eq!(report["summary"]["stats"]["json"]["documents"], 2);
+
+    let output = run_line_counter(&["--no-cache", "--mode", "json", array.to_str().unwrap()]);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("JSON 文档: 合法, 顶层为数组（2 个元素）")
+    );
+}
+
+/// 测试 --tokens 统计 token 数
+#[test]
+fn test_tokens() {
+    let temp_dir = TempDir::new().unwrap();
+    let code = create_test_file(
+        &temp_dir,
+        "main.rs",
+        "fn main() {\n    println!(\"hi\");\n}\n",
+    );
+    let text = create_test_file(&temp_dir, "notes.txt", "你好，世界\n");
+    let output = run_line_counter(&[
+        "--no-cache",
+        "--tokens",
+        "--format",
+        "json",
+        code.to_str().unwrap(),
+        text.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let tokens = &report["files"][1]["stats"]["tokens"];
+    assert_eq!(tokens["tokens"], 6);
+    assert_eq!(tokens["estimated"], true);
+    let code_tokens = report["files"][0]["stats"]["tokens"]["tokens"]
+        .as_u64()
+        .unwrap();
+    assert!(code_tokens > 3);
+    assert_eq!(
+        report["summary"]["stats"]["tokens"]["tokens"],
+        code_tokens + 6
+    );
+
+    let output = run_line_counter(&["--no-cache", "--tokens", text.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Token 数: 6（按字符类别估计）"));
+
+    // --tokenizer 需要同时指定 --tokens
+    let output = run_line_counter(&["--tokenizer", "approx", text.to_str().unwrap()]);
+    assert!(!output.status.success());
+}
+
+/// 测试 --follow 在读到文件末尾后继续统计追加的行，到达截止时间后输出结果
+#[test]
+fn test_follow() {
+    let temp_dir = TempDir::new().unwrap();
+    let log = create_test_file(&temp_dir, "app.log", "start\n");
+    let mut child = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "--follow",
+            "--deadline",
+            "3s",
+            "--format",
+            "json",
+        ])
+        .arg(&log)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    // 等到读到文件末尾后再追加内容
+    let mut stderr = std::io::BufReader::new(child.stderr.take().unwrap());
+    let mut line = String::new();
+    while !line.contains("等待新内容") {
+        line.clear();
+        assert_ne!(stderr.read_line(&mut line).unwrap(), 0);
+    }
+    let mut file = fs::OpenOptions::new().append(true).open(&log).unwrap();
+    file.write_all(b"request 1\n\nrequest 2\npartial").unwrap();
+    drop(file);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let stats = &report["files"][0]["stats"];
+    assert_eq!(stats["total_lines"], 5);
+    assert_eq!(stats["empty_lines"], 1);
+
+    let other = create_test_file(&temp_dir, "other.log", "x\n");
+    let output = run_line_counter(&["--follow", log.to_str().unwrap(), other.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--follow 只能用于单个文件"));
+}
+
+/// 测试命名管道按流式读取，不做大小检查
+#[cfg(unix)]
+#[test]
+fn test_fifo_input() {
+    let temp_dir = TempDir::new().unwrap();
+    let fifo = temp_dir.path().join("input.fifo");
+    assert!(
+        Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap()
+            .success()
+    );
+    let writer = {
+        let fifo = fifo.clone();
+        std::thread::spawn(move || fs::write(fifo, "a\n\nb\nc\n").unwrap())
+    };
+
+    let output = run_line_counter(&[
+        "--max-size",
+        "1",
+        "--format",
+        "json",
+        fifo.to_str().unwrap(),
+    ]);
+    writer.join().unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let file = &report["files"][0];
+    assert_eq!(file["size"], 7);
+    assert_eq!(file["stats"]["total_lines"], 4);
+    assert_eq!(file["stats"]["empty_lines"], 1);
+
+    let output = run_line_counter(&["/dev/null"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 0"));
+}
+
+#[test]
+fn test_list() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(&temp_dir, "a.rs", "fn main() {}\n");
+    create_test_file(&temp_dir, "big.log", &"x\n".repeat(1000));
+    fs::create_dir(temp_dir.path().join("target")).unwrap();
+    create_test_file(&temp_dir, "target/gen.rs", "1\n");
+    let dir = temp_dir.path().to_str().unwrap();
+
+    let output = run_line_counter(&[
+        "--list",
+        "-r",
+        "--exclude",
+        "**/target/**",
+        "--max-size",
+        "1K",
+        dir,
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let listed: Vec<_> = stdout.lines().collect();
+    assert_eq!(
+        listed,
+        vec![
+            temp_dir.path().join("a.rs").to_str().unwrap(),
+            temp_dir.path().join("big.log").to_str().unwrap(),
+        ]
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("big.log: 大小 2000 bytes 超过 --max-size"));
+    assert!(stderr.contains("共 2 个文件，总大小 2013 bytes"));
+}
+
+#[test]
+fn test_verbosity() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = create_test_file(&temp_dir, "a.rs", "fn main() {}\n");
+    let path = file_path.to_str().unwrap();
+
+    let output = run_line_counter(&["--no-cache", path]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("DEBUG"));
+
+    let output = run_line_counter(&["--no-cache", "--verbose", path]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("DEBUG 统计完成"), "stderr: {}", stderr);
+    assert!(stderr.contains("lines=1"), "stderr: {}", stderr);
+    assert!(!stderr.contains("TRACE"), "stderr: {}", stderr);
+
+    let output = run_line_counter(&["--no-cache", "--verbose", "--verbose", path]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("TRACE 开始统计"));
+
+    // 状态提示输出到标准错误，`-q` 时标准输出和标准错误中都只有结果
+    let output = run_line_counter(&["--no-cache", path]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("正在处理"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("正在处理文件"));
+    let output = run_line_counter(&["--no-cache", "-q", path]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("✅ 文件分析完成!"), "stdout: {}", stdout);
+    assert!(stdout.contains("总行数: 1"), "stdout: {}", stdout);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("正在处理"));
+
+    let output = run_line_counter(&["--list", path]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("共 1 个文件"));
+    let output = run_line_counter(&["-q", "--list", path]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("共 1 个文件"));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{}\n", path)
+    );
+}
+
+#[test]
+fn test_continue_on_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "1\n2\n");
+    let missing = temp_dir.path().join("missing.txt");
+    let b = create_test_file(&temp_dir, "b.txt", "1\n");
+    let paths = [
+        a.to_str().unwrap(),
+        missing.to_str().unwrap(),
+        b.to_str().unwrap(),
+    ];
+
+    let mut args = vec!["--no-cache", "--format", "json"];
+    args.extend(paths);
+    let output = run_line_counter(&args);
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["files"], 2);
+    assert_eq!(report["summary"]["failed"], 1);
+    assert_eq!(report["summary"]["stats"]["total_lines"], 3);
+    assert!(
+        report["files"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("文件不存在")
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("missing.txt: 文件不存在"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(stderr.contains("1 个文件统计失败"), "stderr: {}", stderr);
+
+    // 可读报告输出到终端时失败的文件只在报告中列出一次
+    let mut args = vec!["--no-cache"];
+    args.extend(paths);
+    let output = run_line_counter(&args);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined = format!("{}{}", stdout, stderr);
+    assert_eq!(combined.matches("文件不存在").count(), 1, "{}", combined);
+    assert!(stdout.contains("统计失败: "), "stdout: {}", stdout);
+    assert!(stdout.contains("文件不存在"), "stdout: {}", stdout);
+    assert!(stderr.contains("1 个文件统计失败"), "stderr: {}", stderr);
+
+    // --strict 时第一个错误即停止，不输出报告
+    let mut args = vec!["--no-cache", "--strict", "--format", "json"];
+    args.extend(paths);
+    let output = run_line_counter(&args);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("文件不存在"));
+}
+
+/// 测试 `--lossy` 替换无效的 UTF-8 序列并报告含有无效序列的行数
+#[test]
+fn test_lossy() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("app.log");
+    let mut content = "INFO 服务启动\n".repeat(20).into_bytes();
+    content.extend_from_slice(b"WARN \xff\xfe garbage\n\nINFO done\n");
+    fs::write(&path, &content).unwrap();
+    let path = path.to_str().unwrap();
+
+    let output = run_line_counter(&["--no-cache", path]);
+    assert!(!output.status.success());
+
+    let output = run_line_counter(&["--no-cache", "--lossy", "--format", "json", path]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let stats = &report["summary"]["stats"];
+    assert_eq!(stats["total_lines"], 23);
+    assert_eq!(stats["non_empty_lines"], 22);
+    assert_eq!(stats["invalid_utf8_lines"], 1);
+
+    let output = run_line_counter(&["--no-cache", "--lossy", path]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("含无效 UTF-8 的行数: 1"),
+        "stdout: {}",
+        stdout
+    );
+
+    // 纯 ASCII 中夹杂无效字节时默认识别为 windows-1252，指定 `--lossy` 时按 UTF-8 解码
+    let ascii = temp_dir.path().join("ascii.log");
+    fs::write(&ascii, b"INFO start\nWARN \xff\xfe\nINFO done\n").unwrap();
+    let ascii = ascii.to_str().unwrap();
+    let output = run_line_counter(&["--no-cache", "--format", "json", ascii]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["encoding"], "windows-1252");
+    let output = run_line_counter(&["--no-cache", "--lossy", "--format", "json", ascii]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["encoding"], "UTF-8");
+    assert_eq!(report["files"][0]["stats"]["total_lines"], 3);
+    assert_eq!(report["files"][0]["stats"]["invalid_utf8_lines"], 1);
+}
+
+/// 测试 `--bytes-mode` 按原始字节统计任意内容
+#[test]
+fn test_bytes_mode() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("dump.bin");
+    fs::write(&path, b"\x00\x01\x02\n\xff\xfe garbage\r\n\n  \nend").unwrap();
+    let path = path.to_str().unwrap();
+
+    // 默认作为二进制文件跳过
+    let output = run_line_counter(&["--no-cache", "--format", "json", path]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["binary"], true);
+
+    let output = run_line_counter(&["--no-cache", "--bytes-mode", "--format", "json", path]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let file = &report["files"][0];
+    assert_eq!(file["binary"], false);
+    assert!(file["encoding"].is_null());
+    assert_eq!(file["stats"]["total_lines"], 5);
+    assert_eq!(file["stats"]["non_empty_lines"], 3);
+    assert_eq!(file["stats"]["empty_lines"], 2);
+    assert_eq!(file["stats"]["max_line_bytes"], 10);
+
+    let output = run_line_counter(&["--no-cache", "--bytes-mode", "--match", "x", path]);
+    assert!(!output.status.success());
+}
+
+/// 测试全局选项写在子命令之前时仍然执行子命令，统计参数不能与子命令同时使用
+#[test]
+fn test_global_options_before_subcommand() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = create_test_file(&temp_dir, "a.txt", "1\n");
+    let b = create_test_file(&temp_dir, "b.txt", "1\n2\n");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    let output = run_line_counter(&["--lang", "en", "diff", a, b]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Total lines: 1 → 2 (+1)"),
+        "stdout: {}",
+        stdout
+    );
+
+    let output = run_line_counter(&["-q", "diff", a, b]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("总行数: 1 → 2 (+1)"));
+
+    let output = run_line_counter(&["--format", "json", "diff", a, b]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("'--format' 不能与子命令 'diff' 同时使用")
+    );
+
+    let mut child = Command::new("cargo")
+        .args(["run", "--", "--lang", "en", "watch", a])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    let mut stderr = std::io::BufReader::new(child.stderr.take().unwrap());
+    let mut line = String::new();
+    while !line.contains("Watching for changes") {
+        line.clear();
+        assert_ne!(stderr.read_line(&mut line).unwrap(), 0);
+        assert!(!line.contains("File not found"), "stderr: {}", line);
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+/// 测试路径模式按 `-i` 和配置文件中的 `case` 匹配大小写，配置文件中的标签规则与 `--tag` 合并
+#[test]
+fn test_glob_case_and_config_tags() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().join("project");
+    fs::create_dir_all(dir.join("Sub")).unwrap();
+    fs::create_dir_all(dir.join("Legacy")).unwrap();
+    fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    fs::write(dir.join("Sub").join("x.txt"), "x\n").unwrap();
+    fs::write(dir.join("Legacy").join("old.rs"), "a\nb\n").unwrap();
+    let dir = dir.to_str().unwrap();
+
+    let output = run_line_counter(&["--list", "-r", "--exclude", "**/sub/**", dir]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("x.txt"));
+    let output = run_line_counter(&["--list", "-r", "--exclude", "**/sub/**", "-i", dir]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("x.txt"), "stdout: {}", stdout);
+    assert!(stdout.contains("main.rs"), "stdout: {}", stdout);
+
+    let config = create_test_file(
+        &temp_dir,
+        "config.toml",
+        "case = \"smart\"\nexclude = [\"**/sub/**\"]\n\n[tags]\nlegacy = \"**/legacy/**\"\n",
+    );
+    let config = config.to_str().unwrap();
+    let tag_count = |extra: &[&str]| {
+        let mut args = vec!["--no-cache", "--config", config, "--format", "json", "-r"];
+        args.extend(extra);
+        args.push(dir);
+        let output = run_line_counter(&args);
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let report = tag_count(&["--tag", "top=**/main.rs"]);
+    assert_eq!(report["summary"]["files"], 2);
+    let tags = &report["summary"]["tags"];
+    assert_eq!(tags.as_array().unwrap().len(), 2, "tags: {}", tags);
+    assert_eq!(tags[0]["name"], "legacy");
+    assert_eq!(tags[0]["stats"]["total_lines"], 2);
+
+    // 命令行的 `-s` 覆盖配置文件中的 `case`
+    let report = tag_count(&["-s"]);
+    assert_eq!(report["summary"]["files"], 3);
+    assert!(
+        report["summary"]["tags"]
+            .as_array()
+            .is_none_or(|tags| tags.is_empty())
+    );
+}
+
+/// 测试修改自定义语言文件或停用词文件后不使用旧的缓存结果
+#[test]
+fn test_cache_key_includes_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_dir = temp_dir.path().join("cache");
+    let file = create_test_file(&temp_dir, "rules.acme", "-- note\nrule a\nthe rule\n");
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+    File::options()
+        .write(true)
+        .open(&file)
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+    let languages = temp_dir.path().join("languages.toml");
+    let stop_words = temp_dir.path().join("stop.txt");
+    let run = || {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "--comments",
+                "--word-freq",
+                "5",
+                "--format",
+                "json",
+            ])
+            .arg("--languages-file")
+            .arg(&languages)
+            .arg("--stop-words")
+            .arg(&stop_words)
+            .arg(&file)
+            .env("XDG_CACHE_HOME", &cache_dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        report["files"][0]["stats"].clone()
+    };
+
+    fs::write(
+        &languages,
+        "[[language]]\nname = \"Acme\"\nextensions = [\"acme\"]\nline_comments = [\"--\"]\n",
+    )
+    .unwrap();
+    fs::write(&stop_words, "the\n").unwrap();
+    let stats = run();
+    assert_eq!(stats["comments"]["comment_lines"], 1);
+    let words = stats["word_freq"].to_string();
+    assert!(!words.contains("\"the\""), "word_freq: {}", words);
+
+    fs::write(
+        &languages,
+        "[[language]]\nname = \"Acme\"\nextensions = [\"acme\"]\nline_comments = [\"#\"]\n",
+    )
+    .unwrap();
+    fs::write(&stop_words, "rule\n").unwrap();
+    let stats = run();
+    assert_eq!(stats["comments"]["comment_lines"], 0);
+    let words = stats["word_freq"].to_string();
+    assert!(words.contains("\"the\""), "word_freq: {}", words);
+    assert!(!words.contains("\"rule\""), "word_freq: {}", words);
 }